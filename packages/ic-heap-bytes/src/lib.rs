@@ -218,6 +218,7 @@ impl HeapBytes for prometheus::Histogram {
     }
 }
 impl HeapBytes for prometheus::IntCounter {}
+impl HeapBytes for prometheus::IntCounterVec {}
 impl HeapBytes for prometheus::IntGauge {}
 impl HeapBytes for tempfile::TempDir {
     fn heap_bytes(&self) -> usize {
@@ -0,0 +1,81 @@
+use candid::{CandidType, Deserialize, Nat};
+use serde::Serialize;
+
+use super::super::icrc1::account::{Account, Subaccount};
+use super::super::icrc1::transfer::Memo;
+use super::super::icrc7::transfer::TokenId;
+
+/// The details of an approval granted to a spender.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ApprovalInfo {
+    pub spender: Account,
+    #[serde(default)]
+    pub from_subaccount: Option<Subaccount>,
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    #[serde(default)]
+    pub memo: Option<Memo>,
+    pub created_at_time: u64,
+}
+
+/// The arguments for a single approval of the
+/// [ICRC-37 `approve_tokens`](https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-37/ICRC-37.md#icrc37_approve_tokens)
+/// endpoint.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ApproveTokenArg {
+    pub token_id: TokenId,
+    pub approval_info: ApprovalInfo,
+}
+
+/// The error type of a single approval of the ICRC-37 `approve_tokens` endpoint.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ApproveTokenError {
+    InvalidSpender,
+    Unauthorized,
+    NonExistingTokenId,
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    GenericError { error_code: Nat, message: String },
+    GenericBatchError { error_code: Nat, message: String },
+}
+
+pub type ApproveTokenResult = Result<Nat, ApproveTokenError>;
+
+/// The arguments for a single approval of the
+/// [ICRC-37 `approve_collection`](https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-37/ICRC-37.md#icrc37_approve_collection)
+/// endpoint.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ApproveCollectionArg {
+    pub approval_info: ApprovalInfo,
+}
+
+/// The error type of a single approval of the ICRC-37 `approve_collection` endpoint.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ApproveCollectionError {
+    InvalidSpender,
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    GenericError { error_code: Nat, message: String },
+    GenericBatchError { error_code: Nat, message: String },
+}
+
+pub type ApproveCollectionResult = Result<Nat, ApproveCollectionError>;
+
+/// The arguments for a single check of the ICRC-37 `is_approved` endpoint.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct IsApprovedArg {
+    pub spender: Account,
+    #[serde(default)]
+    pub from_subaccount: Option<Subaccount>,
+    pub token_id: TokenId,
+}
+
+/// A token-level approval returned by the ICRC-37 `get_token_approvals` endpoint.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TokenApproval {
+    pub token_id: TokenId,
+    pub approval_info: ApprovalInfo,
+}
+
+/// A collection-level approval returned by the ICRC-37 `get_collection_approvals` endpoint.
+pub type CollectionApproval = ApprovalInfo;
@@ -0,0 +1,6 @@
+//! The [ICRC-37](https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-37/ICRC-37.md)
+//! Approval Support for the Minimal Non-Fungible Token standard.
+
+pub mod approve;
+pub mod revoke;
+pub mod transfer_from;
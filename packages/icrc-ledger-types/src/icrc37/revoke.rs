@@ -0,0 +1,63 @@
+use candid::{CandidType, Deserialize, Nat};
+use serde::Serialize;
+
+use super::super::icrc1::account::{Account, Subaccount};
+use super::super::icrc1::transfer::Memo;
+use super::super::icrc7::transfer::TokenId;
+
+/// The arguments for a single revocation of the
+/// [ICRC-37 `revoke_token_approvals`](https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-37/ICRC-37.md#icrc37_revoke_token_approvals)
+/// endpoint. If `spender` is `None`, the approvals of all spenders are revoked.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RevokeTokenApprovalArg {
+    #[serde(default)]
+    pub spender: Option<Account>,
+    #[serde(default)]
+    pub from_subaccount: Option<Subaccount>,
+    pub token_id: TokenId,
+    #[serde(default)]
+    pub memo: Option<Memo>,
+    #[serde(default)]
+    pub created_at_time: Option<u64>,
+}
+
+/// The error type of a single revocation of the ICRC-37 `revoke_token_approvals` endpoint.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum RevokeTokenApprovalError {
+    ApprovalDoesNotExist,
+    Unauthorized,
+    NonExistingTokenId,
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    GenericError { error_code: Nat, message: String },
+    GenericBatchError { error_code: Nat, message: String },
+}
+
+pub type RevokeTokenApprovalResponse = Result<Nat, RevokeTokenApprovalError>;
+
+/// The arguments for a single revocation of the
+/// [ICRC-37 `revoke_collection_approvals`](https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-37/ICRC-37.md#icrc37_revoke_collection_approvals)
+/// endpoint. If `spender` is `None`, the approvals of all spenders are revoked.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RevokeCollectionApprovalArg {
+    #[serde(default)]
+    pub spender: Option<Account>,
+    #[serde(default)]
+    pub from_subaccount: Option<Subaccount>,
+    #[serde(default)]
+    pub memo: Option<Memo>,
+    #[serde(default)]
+    pub created_at_time: Option<u64>,
+}
+
+/// The error type of a single revocation of the ICRC-37 `revoke_collection_approvals` endpoint.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum RevokeCollectionApprovalError {
+    ApprovalDoesNotExist,
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    GenericError { error_code: Nat, message: String },
+    GenericBatchError { error_code: Nat, message: String },
+}
+
+pub type RevokeCollectionApprovalResult = Result<Nat, RevokeCollectionApprovalError>;
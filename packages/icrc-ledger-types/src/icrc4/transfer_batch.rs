@@ -0,0 +1,105 @@
+use candid::{CandidType, Deserialize, Nat};
+use serde::Serialize;
+use std::fmt;
+
+use super::super::icrc1::account::Account;
+use super::super::icrc1::transfer::{TransferArg, TransferError};
+
+/// The arguments for the
+/// [ICRC-4 `transfer_batch`](https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-4/ICRC-4.md#icrc4_transfer_batch)
+/// endpoint: a list of ICRC-1 transfers from accounts of the caller.
+pub type TransferBatchArgs = Vec<TransferArg>;
+
+/// The result of a single transfer of an ICRC-4 batch.
+pub type TransferBatchResult = Result<Nat, TransferBatchError>;
+
+/// The return type of the ICRC-4 `transfer_batch` endpoint. The result at
+/// position `i` corresponds to the transfer at position `i` of the arguments
+/// and is `None` if the transfer was not processed.
+pub type TransferBatchResults = Vec<Option<TransferBatchResult>>;
+
+/// The arguments for the ICRC-4 `balance_of_batch` endpoint.
+pub type BalanceQueryArgs = Vec<Account>;
+
+/// The return type of the ICRC-4 `balance_of_batch` endpoint.
+pub type BalanceQueryResult = Vec<Nat>;
+
+/// The error type of a single transfer of the
+/// [ICRC-4 `transfer_batch`](https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-4/ICRC-4.md#icrc4_transfer_batch)
+/// endpoint.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum TransferBatchError {
+    BadFee { expected_fee: Nat },
+    BadBurn { min_burn_amount: Nat },
+    InsufficientFunds { balance: Nat },
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    // The batch contains more transfers than the ledger accepts.
+    TooManyRequests { limit: Nat },
+    Duplicate { duplicate_of: Nat },
+    TemporarilyUnavailable,
+    GenericError { error_code: Nat, message: String },
+    // An error affecting the whole batch rather than this transfer.
+    GenericBatchError { error_code: Nat, message: String },
+}
+
+impl From<TransferError> for TransferBatchError {
+    fn from(err: TransferError) -> Self {
+        match err {
+            TransferError::BadFee { expected_fee } => Self::BadFee { expected_fee },
+            TransferError::BadBurn { min_burn_amount } => Self::BadBurn { min_burn_amount },
+            TransferError::InsufficientFunds { balance } => Self::InsufficientFunds { balance },
+            TransferError::TooOld => Self::TooOld,
+            TransferError::CreatedInFuture { ledger_time } => Self::CreatedInFuture { ledger_time },
+            TransferError::TemporarilyUnavailable => Self::TemporarilyUnavailable,
+            TransferError::Duplicate { duplicate_of } => Self::Duplicate { duplicate_of },
+            TransferError::GenericError {
+                error_code,
+                message,
+            } => Self::GenericError {
+                error_code,
+                message,
+            },
+        }
+    }
+}
+
+impl fmt::Display for TransferBatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadFee { expected_fee } => {
+                write!(f, "transfer fee should be {expected_fee}")
+            }
+            Self::BadBurn { min_burn_amount } => write!(
+                f,
+                "the minimum number of tokens to be burned is {min_burn_amount}"
+            ),
+            Self::InsufficientFunds { balance } => write!(
+                f,
+                "the debit account doesn't have enough funds to complete the transaction, current balance: {balance}"
+            ),
+            Self::TooOld => write!(f, "transaction's created_at_time is too far in the past"),
+            Self::CreatedInFuture { ledger_time } => write!(
+                f,
+                "transaction's created_at_time is in future, current ledger time is {ledger_time}"
+            ),
+            Self::TooManyRequests { limit } => write!(
+                f,
+                "the batch contains more than the maximum of {limit} transfers"
+            ),
+            Self::Duplicate { duplicate_of } => write!(
+                f,
+                "transaction is a duplicate of another transaction in block {duplicate_of}"
+            ),
+            Self::TemporarilyUnavailable => write!(f, "the ledger is temporarily unavailable"),
+            Self::GenericError {
+                error_code,
+                message,
+            }
+            | Self::GenericBatchError {
+                error_code,
+                message,
+            } => write!(f, "{error_code} {message}"),
+        }
+    }
+}
@@ -0,0 +1,4 @@
+//! The [ICRC-7](https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-7/ICRC-7.md)
+//! Minimal Non-Fungible Token standard.
+
+pub mod transfer;
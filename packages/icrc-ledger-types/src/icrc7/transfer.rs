@@ -0,0 +1,41 @@
+use candid::{CandidType, Deserialize, Nat};
+use serde::Serialize;
+
+use super::super::icrc1::account::{Account, Subaccount};
+use super::super::icrc1::transfer::Memo;
+
+/// The identifier of a non-fungible token.
+pub type TokenId = Nat;
+
+/// The arguments for a single transfer of the
+/// [ICRC-7 `transfer`](https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-7/ICRC-7.md#icrc7_transfer)
+/// endpoint.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TransferArg {
+    #[serde(default)]
+    pub from_subaccount: Option<Subaccount>,
+    pub to: Account,
+    pub token_id: TokenId,
+    #[serde(default)]
+    pub memo: Option<Memo>,
+    #[serde(default)]
+    pub created_at_time: Option<u64>,
+}
+
+/// The error type of a single transfer of the ICRC-7 `transfer` endpoint.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum TransferError {
+    NonExistingTokenId,
+    InvalidRecipient,
+    Unauthorized,
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    Duplicate { duplicate_of: Nat },
+    GenericError { error_code: Nat, message: String },
+    // An error affecting the whole batch rather than this transfer.
+    GenericBatchError { error_code: Nat, message: String },
+}
+
+/// The result of a single transfer of the ICRC-7 `transfer` endpoint: the
+/// index of the block recording the transfer, or an error.
+pub type TransferResult = Result<Nat, TransferError>;
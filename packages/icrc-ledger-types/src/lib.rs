@@ -7,3 +7,6 @@ pub mod icrc106;
 pub mod icrc2;
 pub mod icrc21;
 pub mod icrc3;
+pub mod icrc37;
+pub mod icrc4;
+pub mod icrc7;
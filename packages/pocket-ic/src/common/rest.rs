@@ -2,7 +2,7 @@
 //! The types in this module are used to serialize and deserialize data
 //! from and to JSON, and are used by both crates.
 
-use crate::RejectResponse;
+use crate::{RejectCode, RejectResponse};
 use candid::Principal;
 use hex;
 use reqwest::Response;
@@ -564,6 +564,11 @@ pub struct IcpConfig {
     /// Rate-limiting of canister execution (enabled on the ICP mainnet).
     /// Canister execution refers to instructions and memory writes here.
     pub canister_execution_rate_limiting: Option<IcpConfigFlag>,
+    /// Fault injection for inter-canister calls (disabled on the ICP mainnet).
+    /// If enabled, calls between canisters on the same subnet take at least one round to be delivered.
+    pub fault_injection: Option<IcpConfigFlag>,
+    /// Profiling of canister executions (disabled on the ICP mainnet).
+    pub execution_profiling: Option<IcpConfigFlag>,
 }
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize, Default, JsonSchema)]
@@ -650,11 +655,25 @@ pub struct InstanceConfig {
     pub log_level: Option<String>,
     pub bitcoind_addr: Option<Vec<SocketAddr>>,
     pub dogecoind_addr: Option<Vec<SocketAddr>>,
+    pub bitcoin_local_chain: Option<LocalChainConfig>,
+    pub dogecoin_local_chain: Option<LocalChainConfig>,
     pub icp_features: Option<IcpFeatures>,
     pub incomplete_state: Option<IncompleteStateFlag>,
     pub initial_time: Option<InitialTime>,
 }
 
+/// Configures a regtest chain produced by the PocketIC server itself,
+/// which replaces a `bitcoind` or `dogecoind` process.
+/// The chain only produces blocks on request, e.g., via `PocketIc::mine_blocks`.
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize, Default, JsonSchema)]
+pub struct LocalChainConfig {
+    /// The number of blocks produced when the chain is started.
+    pub initial_blocks: u32,
+    /// The regtest address receiving the block rewards.
+    /// The block rewards are unspendable if no address is provided.
+    pub coinbase_address: Option<String>,
+}
+
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize, Default, JsonSchema)]
 pub struct ExtendedSubnetConfigSet {
     pub nns: Option<SubnetSpec>,
@@ -1123,3 +1142,277 @@ pub struct RawCanisterSnapshotId {
     #[serde(serialize_with = "base64::serialize")]
     pub snapshot_id: Vec<u8>,
 }
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawSaveState {
+    pub state_dir: PathBuf,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawDiffStates {
+    pub from_state_dir: PathBuf,
+    pub to_state_dir: PathBuf,
+}
+
+/// How a canister differs between two saved PocketIC states.
+#[derive(Clone, Copy, Eq, PartialEq, Serialize, Deserialize, Debug, JsonSchema)]
+pub enum CanisterStateDiffKind {
+    /// The canister only exists in the newer state.
+    Created,
+    /// The canister only exists in the older state.
+    Deleted,
+    /// The canister exists in both states, but some of its fields differ.
+    Modified,
+}
+
+/// A field of the canister state whose value differs between two saved PocketIC states.
+/// Changes that are not reported as a pair of values (the cycles balance delta,
+/// the changed memory pages, and the added or removed snapshots) only have
+/// an `after` value describing the change.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct CanisterStateChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawCanisterStateDiff {
+    pub canister_id: RawCanisterId,
+    pub kind: CanisterStateDiffKind,
+    pub changes: Vec<CanisterStateChange>,
+}
+
+/// The difference in the state of a single canister between two saved PocketIC states.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CanisterStateDiff {
+    pub canister_id: Principal,
+    pub kind: CanisterStateDiffKind,
+    pub changes: Vec<CanisterStateChange>,
+}
+
+impl From<RawCanisterStateDiff> for CanisterStateDiff {
+    fn from(raw_canister_state_diff: RawCanisterStateDiff) -> Self {
+        Self {
+            canister_id: raw_canister_state_diff.canister_id.into(),
+            kind: raw_canister_state_diff.kind,
+            changes: raw_canister_state_diff.changes,
+        }
+    }
+}
+
+/// The fault injected into the inter-canister calls matching a `FaultInjectionRule`.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug, JsonSchema)]
+pub enum FaultInjectionAction {
+    /// The callee never receives the request and the caller receives
+    /// a reject response with the given reject code and message instead.
+    Reject {
+        reject_code: RejectCode,
+        message: String,
+    },
+    /// The response to a best-effort call is dropped so that the call times out
+    /// once its deadline expires. Guaranteed response calls are not affected.
+    Timeout,
+    /// The request is delivered to the callee the given number of rounds later.
+    Delay { rounds: u64 },
+}
+
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawFaultInjectionRule {
+    pub caller: Option<RawCanisterId>,
+    pub callee: Option<RawCanisterId>,
+    pub method: Option<String>,
+    pub action: FaultInjectionAction,
+}
+
+/// A rule matching inter-canister calls by their caller, callee, and method name
+/// (`None` matches any value).
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct FaultInjectionRule {
+    pub caller: Option<Principal>,
+    pub callee: Option<Principal>,
+    pub method: Option<String>,
+    pub action: FaultInjectionAction,
+}
+
+impl From<FaultInjectionRule> for RawFaultInjectionRule {
+    fn from(rule: FaultInjectionRule) -> Self {
+        Self {
+            caller: rule.caller.map(|caller| caller.into()),
+            callee: rule.callee.map(|callee| callee.into()),
+            method: rule.method,
+            action: rule.action,
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawExecutionProfile {
+    pub canister_id: RawCanisterId,
+    pub api_type: String,
+    pub function: String,
+    pub instructions: u64,
+    pub system_api_call_counts: BTreeMap<String, u64>,
+    pub dirty_heap_bytes: u64,
+    pub dirty_stable_memory_bytes: u64,
+}
+
+/// The profile of a single execution of a canister method, callback, or cleanup.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ExecutionProfile {
+    pub canister_id: Principal,
+    /// The kind of the execution, e.g., "update", "reply callback", "reject callback", or "cleanup".
+    pub api_type: String,
+    /// The executed function: the exported method (e.g., "canister_update whois")
+    /// or, for callbacks and cleanups, the kind of the execution.
+    pub function: String,
+    /// The number of instructions executed (over all slices of the execution).
+    pub instructions: u64,
+    /// How many times each System API function (e.g., "ic0.msg_reply") was called.
+    pub system_api_call_counts: BTreeMap<String, u64>,
+    /// The number of bytes of the canister's heap memory written by the execution.
+    pub dirty_heap_bytes: u64,
+    /// The number of bytes of the canister's stable memory written by the execution.
+    pub dirty_stable_memory_bytes: u64,
+}
+
+impl From<RawExecutionProfile> for ExecutionProfile {
+    fn from(raw_execution_profile: RawExecutionProfile) -> Self {
+        Self {
+            canister_id: raw_execution_profile.canister_id.into(),
+            api_type: raw_execution_profile.api_type,
+            function: raw_execution_profile.function,
+            instructions: raw_execution_profile.instructions,
+            system_api_call_counts: raw_execution_profile.system_api_call_counts,
+            dirty_heap_bytes: raw_execution_profile.dirty_heap_bytes,
+            dirty_stable_memory_bytes: raw_execution_profile.dirty_stable_memory_bytes,
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawCanisterCyclesProfile {
+    pub canister_id: RawCanisterId,
+    pub cycles_by_use_case: BTreeMap<String, u128>,
+}
+
+/// The cycles consumed by a single canister, broken down by the use cases
+/// for which the cycles account manager charged the canister.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CanisterCyclesProfile {
+    pub canister_id: Principal,
+    pub cycles_by_use_case: BTreeMap<String, u128>,
+}
+
+impl From<RawCanisterCyclesProfile> for CanisterCyclesProfile {
+    fn from(raw_canister_cycles_profile: RawCanisterCyclesProfile) -> Self {
+        Self {
+            canister_id: raw_canister_cycles_profile.canister_id.into(),
+            cycles_by_use_case: raw_canister_cycles_profile.cycles_by_use_case,
+        }
+    }
+}
+
+/// A regtest chain produced by the PocketIC server (see [`LocalChainConfig`]).
+#[derive(Clone, Copy, Eq, PartialEq, Serialize, Deserialize, Debug, JsonSchema)]
+pub enum LocalChain {
+    Bitcoin,
+    Dogecoin,
+}
+
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawLocalChain {
+    pub chain: LocalChain,
+}
+
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawMineBlocks {
+    pub chain: LocalChain,
+    pub count: u64,
+    /// The regtest address receiving the block rewards instead of
+    /// the coinbase address configured for the local chain.
+    pub coinbase_address: Option<String>,
+}
+
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawReorgBlocks {
+    pub chain: LocalChain,
+    pub depth: u64,
+    pub count: u64,
+}
+
+/// Block hashes or transaction IDs of a local chain
+/// as hex strings in the byte order displayed by block explorers.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawLocalChainIds {
+    pub ids: Vec<String>,
+}
+
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawCallProfile {
+    pub executions: Vec<RawExecutionProfile>,
+    pub cycles: Vec<RawCanisterCyclesProfile>,
+}
+
+/// The profile of all canister executions (on all subnets) while profiling was active.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CallProfile {
+    /// The profiles of all executions in the order in which they finished.
+    pub executions: Vec<ExecutionProfile>,
+    /// The cycles consumed by every canister that consumed any cycles.
+    pub cycles: Vec<CanisterCyclesProfile>,
+}
+
+impl From<RawCallProfile> for CallProfile {
+    fn from(raw_call_profile: RawCallProfile) -> Self {
+        Self {
+            executions: raw_call_profile
+                .executions
+                .into_iter()
+                .map(|execution| execution.into())
+                .collect(),
+            cycles: raw_call_profile
+                .cycles
+                .into_iter()
+                .map(|cycles| cycles.into())
+                .collect(),
+        }
+    }
+}
+
+impl CallProfile {
+    /// Returns the total number of instructions executed by the given kind of execution
+    /// (e.g., "update", "reply callback", or "cleanup") over all canisters.
+    pub fn instructions(&self, api_type: &str) -> u64 {
+        self.executions
+            .iter()
+            .filter(|execution| execution.api_type == api_type)
+            .map(|execution| execution.instructions)
+            .sum()
+    }
+
+    /// Returns the instructions executed by every canister and function in the
+    /// folded-stack format (one `<canister id>;<function> <instructions>` line per
+    /// canister and function) that flame graph tools (e.g., `inferno-flamegraph`) take as input.
+    pub fn folded_stacks(&self) -> String {
+        let mut stacks: BTreeMap<(String, String), u64> = BTreeMap::new();
+        for execution in &self.executions {
+            // `;` separates the frames of a stack.
+            let function = execution.function.replace(';', "_");
+            *stacks
+                .entry((execution.canister_id.to_string(), function))
+                .or_default() += execution.instructions;
+        }
+        stacks
+            .into_iter()
+            .map(|((canister_id, function), instructions)| {
+                format!("{canister_id};{function} {instructions}\n")
+            })
+            .collect()
+    }
+
+    /// Writes the output of [`Self::folded_stacks`] to the given file.
+    pub fn write_folded_stacks(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.folded_stacks())
+    }
+}
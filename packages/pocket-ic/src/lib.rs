@@ -55,10 +55,11 @@
 ///
 use crate::{
     common::rest::{
-        AutoProgressConfig, BlobCompression, BlobId, CanisterHttpRequest, ExtendedSubnetConfigSet,
-        HttpsConfig, IcpConfig, IcpFeatures, InitialTime, InstanceHttpGatewayConfig, InstanceId,
-        MockCanisterHttpResponse, RawEffectivePrincipal, RawMessageId, RawTime, SubnetId,
-        SubnetKind, SubnetSpec, Topology,
+        AutoProgressConfig, BlobCompression, BlobId, CallProfile, CanisterHttpRequest,
+        CanisterStateDiff, ExtendedSubnetConfigSet, FaultInjectionRule, HttpsConfig, IcpConfig,
+        IcpFeatures, InitialTime, InstanceHttpGatewayConfig, InstanceId, LocalChain,
+        LocalChainConfig, MockCanisterHttpResponse, RawEffectivePrincipal, RawMessageId, RawTime,
+        SubnetId, SubnetKind, SubnetSpec, Topology,
     },
     nonblocking::PocketIc as PocketIcAsync,
 };
@@ -170,6 +171,8 @@ pub struct PocketIcBuilder {
     log_level: Option<Level>,
     bitcoind_addr: Option<Vec<SocketAddr>>,
     dogecoind_addr: Option<Vec<SocketAddr>>,
+    bitcoin_local_chain: Option<LocalChainConfig>,
+    dogecoin_local_chain: Option<LocalChainConfig>,
     icp_features: IcpFeatures,
     initial_time: Option<InitialTime>,
 }
@@ -189,6 +192,8 @@ impl PocketIcBuilder {
             log_level: None,
             bitcoind_addr: None,
             dogecoind_addr: None,
+            bitcoin_local_chain: None,
+            dogecoin_local_chain: None,
             icp_features: IcpFeatures::default(),
             initial_time: None,
         }
@@ -212,6 +217,8 @@ impl PocketIcBuilder {
             self.log_level,
             self.bitcoind_addr,
             self.dogecoind_addr,
+            self.bitcoin_local_chain,
+            self.dogecoin_local_chain,
             self.icp_features,
             self.initial_time,
             self.http_gateway_config,
@@ -230,6 +237,8 @@ impl PocketIcBuilder {
             self.log_level,
             self.bitcoind_addr,
             self.dogecoind_addr,
+            self.bitcoin_local_chain,
+            self.dogecoin_local_chain,
             self.icp_features,
             self.initial_time,
             self.http_gateway_config,
@@ -297,6 +306,24 @@ impl PocketIcBuilder {
         }
     }
 
+    /// Let the PocketIC server produce a Bitcoin regtest chain instead of connecting to a `bitcoind` process.
+    /// The chain is scripted using `PocketIc::mine_blocks`, `PocketIc::reorg_blocks`, and `PocketIc::get_mempool`.
+    pub fn with_bitcoin_local_chain(self, config: LocalChainConfig) -> Self {
+        Self {
+            bitcoin_local_chain: Some(config),
+            ..self
+        }
+    }
+
+    /// Let the PocketIC server produce a Dogecoin regtest chain instead of connecting to a `dogecoind` process.
+    /// The chain is scripted using `PocketIc::mine_blocks`, `PocketIc::reorg_blocks`, and `PocketIc::get_mempool`.
+    pub fn with_dogecoin_local_chain(self, config: LocalChainConfig) -> Self {
+        Self {
+            dogecoin_local_chain: Some(config),
+            ..self
+        }
+    }
+
     /// Add an empty NNS subnet unless an NNS subnet has already been added.
     pub fn with_nns_subnet(mut self) -> Self {
         let mut config = self.config.unwrap_or_default();
@@ -595,6 +622,8 @@ impl PocketIc {
         log_level: Option<Level>,
         bitcoind_addr: Option<Vec<SocketAddr>>,
         dogecoind_addr: Option<Vec<SocketAddr>>,
+        bitcoin_local_chain: Option<LocalChainConfig>,
+        dogecoin_local_chain: Option<LocalChainConfig>,
         icp_features: IcpFeatures,
         initial_time: Option<InitialTime>,
         http_gateway_config: Option<InstanceHttpGatewayConfig>,
@@ -621,6 +650,8 @@ impl PocketIc {
                 log_level,
                 bitcoind_addr,
                 dogecoind_addr,
+                bitcoin_local_chain,
+                dogecoin_local_chain,
                 icp_features,
                 initial_time,
                 http_gateway_config,
@@ -1488,6 +1519,153 @@ impl PocketIc {
                 .await
         })
     }
+
+    /// Saves the current state of this instance as a checkpoint with the given name,
+    /// replacing any previous checkpoint with the same name.
+    /// Every subnet executes a round to create the checkpoint.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn checkpoint(&self, name: &str) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.checkpoint(name).await })
+    }
+
+    /// Creates a new PocketIC instance on the same server from the checkpoint with the given name.
+    /// The new instance is created with the same builder configuration as this instance
+    /// (except for the port of the HTTP gateway) and evolves independently of this instance.
+    /// Panics if there is no checkpoint with the given name.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn fork(&self, name: &str) -> PocketIc {
+        let read_only_state_dir = self.pocket_ic.checkpoint_dir(name);
+        let (server_url, max_request_time_ms, builder_config) = self.pocket_ic.fork_params();
+        PocketIc::from_components(
+            builder_config.subnet_config_set,
+            Some(server_url),
+            None,
+            max_request_time_ms,
+            Some(read_only_state_dir),
+            None,
+            builder_config.icp_config,
+            builder_config.log_level,
+            builder_config.bitcoind_addr,
+            builder_config.dogecoind_addr,
+            builder_config.bitcoin_local_chain,
+            builder_config.dogecoin_local_chain,
+            builder_config.icp_features,
+            builder_config.initial_time,
+            builder_config.http_gateway_config,
+        )
+    }
+
+    /// Returns the canisters whose state differs between the checkpoints with the given names.
+    /// Panics if there is no checkpoint with one of the given names.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn diff_checkpoints(&self, from: &str, to: &str) -> Vec<CanisterStateDiff> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.diff_checkpoints(from, to).await })
+    }
+
+    /// Registers a rule injecting a fault into all subsequent inter-canister calls matching the rule.
+    /// If multiple rules match a call, the rule registered first applies.
+    /// Requires fault injection to be enabled in the ICP config of this instance.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn add_fault_injection_rule(&self, rule: FaultInjectionRule) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.add_fault_injection_rule(rule).await })
+    }
+
+    /// Removes all fault injection rules.
+    /// Messages that have already been delayed by a rule are still delivered.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn clear_fault_injection_rules(&self) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.clear_fault_injection_rules().await })
+    }
+
+    /// Starts profiling all canister executions on this instance.
+    /// Requires execution profiling to be enabled in the ICP config of this instance.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn start_profiling(&self) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.start_profiling().await })
+    }
+
+    /// Stops profiling and returns the profile of all canister executions
+    /// and the cycles consumed by all canisters since profiling was started.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn stop_profiling(&self) -> CallProfile {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.stop_profiling().await })
+    }
+
+    /// Execute an update call on a canister and return its result together with
+    /// the profile of all canister executions (including downstream calls and callbacks)
+    /// and the cycles consumed by all canisters while the call was processed.
+    /// Requires execution profiling to be enabled in the ICP config of this instance.
+    #[instrument(skip(self, payload), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), sender = %sender.to_string(), method = %method, payload_len = %payload.len()))]
+    pub fn update_call_with_profile(
+        &self,
+        canister_id: CanisterId,
+        sender: Principal,
+        method: &str,
+        payload: Vec<u8>,
+    ) -> (Result<Vec<u8>, RejectResponse>, CallProfile) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .update_call_with_profile(canister_id, sender, method, payload)
+                .await
+        })
+    }
+
+    /// Produces `count` blocks on top of the tip of a local chain
+    /// and returns their hashes (as hex strings).
+    /// The first block includes all transactions in the mempool of the local chain.
+    /// Panics if the local chain is not configured for this instance.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn mine_blocks(&self, chain: LocalChain, count: u64) -> Vec<String> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.mine_blocks(chain, count).await })
+    }
+
+    /// Produces `count` blocks on top of the tip of a local chain paying the block rewards
+    /// to the given regtest address and returns their hashes (as hex strings).
+    /// The first block includes all transactions in the mempool of the local chain.
+    /// Panics if the local chain is not configured for this instance.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn mine_blocks_to_address(
+        &self,
+        chain: LocalChain,
+        count: u64,
+        address: &str,
+    ) -> Vec<String> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .mine_blocks_to_address(chain, count, address)
+                .await
+        })
+    }
+
+    /// Replaces the last `depth` blocks of a local chain with `count` new blocks
+    /// and returns the hashes (as hex strings) of the new blocks.
+    /// The transactions of the replaced blocks are returned to the mempool
+    /// and included in the first new block.
+    /// Panics if the local chain is not configured for this instance
+    /// or if `count` does not exceed `depth`.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn reorg_blocks(&self, chain: LocalChain, depth: u64, count: u64) -> Vec<String> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.reorg_blocks(chain, depth, count).await })
+    }
+
+    /// Returns the IDs (as hex strings) of the transactions
+    /// waiting to be included in the next block of a local chain.
+    /// Panics if the local chain is not configured for this instance.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn get_mempool(&self, chain: LocalChain) -> Vec<String> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.get_mempool(chain).await })
+    }
 }
 
 impl Default for PocketIc {
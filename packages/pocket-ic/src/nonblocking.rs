@@ -1,14 +1,17 @@
 pub use crate::DefaultEffectiveCanisterIdError;
 use crate::common::rest::{
-    ApiResponse, AutoProgressConfig, BlobCompression, BlobId, CanisterHttpRequest,
-    CreateHttpGatewayResponse, CreateInstanceResponse, ExtendedSubnetConfigSet, HttpGatewayBackend,
-    HttpGatewayConfig, HttpGatewayInfo, HttpsConfig, IcpConfig, IcpFeatures, InitialTime,
-    InstanceConfig, InstanceHttpGatewayConfig, InstanceId, MockCanisterHttpResponse, RawAddCycles,
+    ApiResponse, AutoProgressConfig, BlobCompression, BlobId, CallProfile, CanisterHttpRequest,
+    CanisterStateDiff, CreateHttpGatewayResponse, CreateInstanceResponse, ExtendedSubnetConfigSet,
+    FaultInjectionRule, HttpGatewayBackend, HttpGatewayConfig, HttpGatewayInfo, HttpsConfig,
+    IcpConfig, IcpFeatures, InitialTime, InstanceConfig, InstanceHttpGatewayConfig, InstanceId,
+    LocalChain, LocalChainConfig, MockCanisterHttpResponse, RawAddCycles, RawCallProfile,
     RawCanisterCall, RawCanisterHttpRequest, RawCanisterId, RawCanisterResult,
-    RawCanisterSnapshotDownload, RawCanisterSnapshotId, RawCanisterSnapshotUpload, RawCycles,
-    RawEffectivePrincipal, RawIngressStatusArgs, RawMessageId, RawMockCanisterHttpResponse,
-    RawPrincipalId, RawSetStableMemory, RawStableMemory, RawSubnetId, RawTime,
-    RawVerifyCanisterSigArg, SubnetId, TickConfigs, Topology,
+    RawCanisterSnapshotDownload, RawCanisterSnapshotId, RawCanisterSnapshotUpload,
+    RawCanisterStateDiff, RawCycles, RawDiffStates, RawEffectivePrincipal, RawFaultInjectionRule,
+    RawIngressStatusArgs, RawLocalChain, RawLocalChainIds, RawMessageId, RawMineBlocks,
+    RawMockCanisterHttpResponse, RawPrincipalId, RawReorgBlocks, RawSaveState, RawSetStableMemory,
+    RawStableMemory, RawSubnetId, RawTime, RawVerifyCanisterSigArg, SubnetId, TickConfigs,
+    Topology,
 };
 #[cfg(windows)]
 use crate::wsl_path;
@@ -38,10 +41,12 @@ use reqwest::{StatusCode, Url};
 use serde::{Serialize, de::DeserializeOwned};
 use sha2::{Digest, Sha256};
 use slog::Level;
+use std::collections::BTreeMap;
 use std::fs::read_dir;
 use std::future::Future;
 use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
+use std::sync::Mutex;
 use std::time::Duration;
 use tracing::{debug, instrument, warn};
 use tracing_appender::non_blocking::WorkerGuard;
@@ -89,9 +94,29 @@ pub struct PocketIc {
     // the instance should only be deleted when dropping this handle if this handle owns the instance
     owns_instance: bool,
     state_dir: Option<PocketIcState>,
+    // builder config used to create instances forked from a checkpoint
+    builder_config: BuilderConfig,
+    // named checkpoints of this instance
+    checkpoints: Mutex<BTreeMap<String, PocketIcState>>,
     _log_guard: Option<WorkerGuard>,
 }
 
+/// The configuration of the `PocketIcBuilder` that created an instance.
+/// Instances forked from a checkpoint of the instance are created with the same configuration.
+#[derive(Clone, Default)]
+pub(crate) struct BuilderConfig {
+    pub(crate) subnet_config_set: ExtendedSubnetConfigSet,
+    pub(crate) icp_config: IcpConfig,
+    pub(crate) log_level: Option<Level>,
+    pub(crate) bitcoind_addr: Option<Vec<SocketAddr>>,
+    pub(crate) dogecoind_addr: Option<Vec<SocketAddr>>,
+    pub(crate) bitcoin_local_chain: Option<LocalChainConfig>,
+    pub(crate) dogecoin_local_chain: Option<LocalChainConfig>,
+    pub(crate) icp_features: IcpFeatures,
+    pub(crate) initial_time: Option<InitialTime>,
+    pub(crate) http_gateway_config: Option<InstanceHttpGatewayConfig>,
+}
+
 impl PocketIc {
     /// Creates a new PocketIC instance with a single application subnet on the server.
     /// The server is started if it's not already running.
@@ -125,6 +150,8 @@ impl PocketIc {
             reqwest_client,
             owns_instance: false,
             state_dir: None,
+            builder_config: BuilderConfig::default(),
+            checkpoints: Mutex::new(BTreeMap::new()),
             _log_guard: log_guard,
         }
     }
@@ -141,6 +168,8 @@ impl PocketIc {
         log_level: Option<Level>,
         bitcoind_addr: Option<Vec<SocketAddr>>,
         dogecoind_addr: Option<Vec<SocketAddr>>,
+        bitcoin_local_chain: Option<LocalChainConfig>,
+        dogecoin_local_chain: Option<LocalChainConfig>,
         icp_features: IcpFeatures,
         initial_time: Option<InitialTime>,
         http_gateway_config: Option<InstanceHttpGatewayConfig>,
@@ -182,6 +211,25 @@ impl PocketIc {
             .expect("Failed to copy state directory");
         };
 
+        let builder_config = BuilderConfig {
+            subnet_config_set: subnet_config_set.clone(),
+            icp_config: icp_config.clone(),
+            log_level,
+            bitcoind_addr: bitcoind_addr.clone(),
+            dogecoind_addr: dogecoind_addr.clone(),
+            bitcoin_local_chain: bitcoin_local_chain.clone(),
+            dogecoin_local_chain: dogecoin_local_chain.clone(),
+            icp_features: icp_features.clone(),
+            initial_time: initial_time.clone(),
+            // the HTTP gateway of a forked instance cannot listen on the same port
+            http_gateway_config: http_gateway_config.clone().map(|http_gateway_config| {
+                InstanceHttpGatewayConfig {
+                    port: None,
+                    ..http_gateway_config
+                }
+            }),
+        };
+
         let instance_config = InstanceConfig {
             subnet_config_set,
             http_gateway_config,
@@ -195,6 +243,8 @@ impl PocketIc {
             log_level: log_level.map(|l| l.to_string()),
             bitcoind_addr,
             dogecoind_addr,
+            bitcoin_local_chain,
+            dogecoin_local_chain,
             icp_features: Some(icp_features),
             incomplete_state: None,
             initial_time,
@@ -231,6 +281,8 @@ impl PocketIc {
             reqwest_client,
             owns_instance: true,
             state_dir,
+            builder_config,
+            checkpoints: Mutex::new(BTreeMap::new()),
             _log_guard: log_guard,
         }
     }
@@ -1747,6 +1799,221 @@ impl PocketIc {
             .await
             .snapshot_id
     }
+
+    /// Saves the current state of this instance as a checkpoint with the given name,
+    /// replacing any previous checkpoint with the same name.
+    /// Every subnet executes a round to create the checkpoint.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id))]
+    pub async fn checkpoint(&self, name: &str) {
+        let endpoint = "update/save_state";
+        let state = PocketIcState::new();
+        #[cfg(not(windows))]
+        let state_dir = state.state_dir();
+        #[cfg(windows)]
+        let state_dir = wsl_path(&state.state_dir(), "state directory").into();
+        self.post::<(), _>(endpoint, RawSaveState { state_dir })
+            .await;
+        self.checkpoints
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), state);
+    }
+
+    /// Creates a new PocketIC instance on the same server from the checkpoint with the given name.
+    /// The new instance is created with the same builder configuration as this instance
+    /// (except for the port of the HTTP gateway) and evolves independently of this instance.
+    /// Panics if there is no checkpoint with the given name.
+    #[instrument(skip(self), fields(instance_id=self.instance_id))]
+    pub async fn fork(&self, name: &str) -> PocketIc {
+        let read_only_state_dir = self.checkpoint_dir(name);
+        let (server_url, max_request_time_ms, builder_config) = self.fork_params();
+        PocketIc::from_components(
+            builder_config.subnet_config_set,
+            Some(server_url),
+            None,
+            max_request_time_ms,
+            Some(read_only_state_dir),
+            None,
+            builder_config.icp_config,
+            builder_config.log_level,
+            builder_config.bitcoind_addr,
+            builder_config.dogecoind_addr,
+            builder_config.bitcoin_local_chain,
+            builder_config.dogecoin_local_chain,
+            builder_config.icp_features,
+            builder_config.initial_time,
+            builder_config.http_gateway_config,
+        )
+        .await
+    }
+
+    /// Returns the canisters whose state differs between the checkpoints with the given names.
+    /// Panics if there is no checkpoint with one of the given names.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id))]
+    pub async fn diff_checkpoints(&self, from: &str, to: &str) -> Vec<CanisterStateDiff> {
+        let endpoint = "read/diff_states";
+        #[cfg(not(windows))]
+        let (from_state_dir, to_state_dir) = (self.checkpoint_dir(from), self.checkpoint_dir(to));
+        #[cfg(windows)]
+        let (from_state_dir, to_state_dir) = (
+            wsl_path(&self.checkpoint_dir(from), "state directory").into(),
+            wsl_path(&self.checkpoint_dir(to), "state directory").into(),
+        );
+        let raw_diff_states = RawDiffStates {
+            from_state_dir,
+            to_state_dir,
+        };
+        self.post::<Vec<RawCanisterStateDiff>, _>(endpoint, raw_diff_states)
+            .await
+            .into_iter()
+            .map(|diff| diff.into())
+            .collect()
+    }
+
+    /// Registers a rule injecting a fault into all subsequent inter-canister calls matching the rule.
+    /// If multiple rules match a call, the rule registered first applies.
+    /// Requires fault injection to be enabled in the ICP config of this instance.
+    #[instrument(skip(self), fields(instance_id=self.instance_id))]
+    pub async fn add_fault_injection_rule(&self, rule: FaultInjectionRule) {
+        let endpoint = "update/add_fault_injection_rule";
+        self.post::<(), _>(endpoint, RawFaultInjectionRule::from(rule))
+            .await;
+    }
+
+    /// Removes all fault injection rules.
+    /// Messages that have already been delayed by a rule are still delivered.
+    #[instrument(skip(self), fields(instance_id=self.instance_id))]
+    pub async fn clear_fault_injection_rules(&self) {
+        let endpoint = "update/clear_fault_injection_rules";
+        self.post::<(), _>(endpoint, "").await;
+    }
+
+    /// Starts profiling all canister executions on this instance.
+    /// Requires execution profiling to be enabled in the ICP config of this instance.
+    #[instrument(skip(self), fields(instance_id=self.instance_id))]
+    pub async fn start_profiling(&self) {
+        let endpoint = "update/start_profiling";
+        self.post::<(), _>(endpoint, "").await;
+    }
+
+    /// Stops profiling and returns the profile of all canister executions
+    /// and the cycles consumed by all canisters since profiling was started.
+    #[instrument(skip(self), fields(instance_id=self.instance_id))]
+    pub async fn stop_profiling(&self) -> CallProfile {
+        let endpoint = "update/stop_profiling";
+        self.post::<RawCallProfile, _>(endpoint, "").await.into()
+    }
+
+    /// Execute an update call on a canister and return its result together with
+    /// the profile of all canister executions (including downstream calls and callbacks)
+    /// and the cycles consumed by all canisters while the call was processed.
+    /// Requires execution profiling to be enabled in the ICP config of this instance.
+    #[instrument(skip(self, payload), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), sender = %sender.to_string(), method = %method, payload_len = %payload.len()))]
+    pub async fn update_call_with_profile(
+        &self,
+        canister_id: CanisterId,
+        sender: Principal,
+        method: &str,
+        payload: Vec<u8>,
+    ) -> (Result<Vec<u8>, RejectResponse>, CallProfile) {
+        self.start_profiling().await;
+        let result = self.update_call(canister_id, sender, method, payload).await;
+        let profile = self.stop_profiling().await;
+        (result, profile)
+    }
+
+    /// Produces `count` blocks on top of the tip of a local chain
+    /// and returns their hashes (as hex strings).
+    /// The first block includes all transactions in the mempool of the local chain.
+    /// Panics if the local chain is not configured for this instance.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id))]
+    pub async fn mine_blocks(&self, chain: LocalChain, count: u64) -> Vec<String> {
+        self.mine_blocks_internal(chain, count, None).await
+    }
+
+    /// Produces `count` blocks on top of the tip of a local chain paying the block rewards
+    /// to the given regtest address and returns their hashes (as hex strings).
+    /// The first block includes all transactions in the mempool of the local chain.
+    /// Panics if the local chain is not configured for this instance.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id))]
+    pub async fn mine_blocks_to_address(
+        &self,
+        chain: LocalChain,
+        count: u64,
+        address: &str,
+    ) -> Vec<String> {
+        self.mine_blocks_internal(chain, count, Some(address.to_string()))
+            .await
+    }
+
+    async fn mine_blocks_internal(
+        &self,
+        chain: LocalChain,
+        count: u64,
+        coinbase_address: Option<String>,
+    ) -> Vec<String> {
+        let endpoint = "update/mine_blocks";
+        let res: RawLocalChainIds = self
+            .post(
+                endpoint,
+                RawMineBlocks {
+                    chain,
+                    count,
+                    coinbase_address,
+                },
+            )
+            .await;
+        res.ids
+    }
+
+    /// Replaces the last `depth` blocks of a local chain with `count` new blocks
+    /// and returns the hashes (as hex strings) of the new blocks.
+    /// The transactions of the replaced blocks are returned to the mempool
+    /// and included in the first new block.
+    /// Panics if the local chain is not configured for this instance
+    /// or if `count` does not exceed `depth`.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id))]
+    pub async fn reorg_blocks(&self, chain: LocalChain, depth: u64, count: u64) -> Vec<String> {
+        let endpoint = "update/reorg_blocks";
+        let res: RawLocalChainIds = self
+            .post(
+                endpoint,
+                RawReorgBlocks {
+                    chain,
+                    depth,
+                    count,
+                },
+            )
+            .await;
+        res.ids
+    }
+
+    /// Returns the IDs (as hex strings) of the transactions
+    /// waiting to be included in the next block of a local chain.
+    /// Panics if the local chain is not configured for this instance.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id))]
+    pub async fn get_mempool(&self, chain: LocalChain) -> Vec<String> {
+        let endpoint = "read/get_mempool";
+        let res: RawLocalChainIds = self.post(endpoint, RawLocalChain { chain }).await;
+        res.ids
+    }
+
+    pub(crate) fn checkpoint_dir(&self, name: &str) -> PathBuf {
+        self.checkpoints
+            .lock()
+            .unwrap()
+            .get(name)
+            .unwrap_or_else(|| panic!("No checkpoint named {name}"))
+            .state_dir()
+    }
+
+    pub(crate) fn fork_params(&self) -> (Url, Option<u64>, BuilderConfig) {
+        (
+            self.server_url.clone(),
+            self.max_request_time_ms,
+            self.builder_config.clone(),
+        )
+    }
 }
 
 /// Call a canister candid method, authenticated. The sender can be impersonated (i.e., the
@@ -1056,6 +1056,8 @@ async fn with_all_icp_features_and_nns_subnet_state() {
         log_level: None,
         bitcoind_addr: None,
         dogecoind_addr: None,
+        bitcoin_local_chain: None,
+        dogecoin_local_chain: None,
         icp_features: Some(all_icp_features()),
         incomplete_state: None,
         initial_time: None,
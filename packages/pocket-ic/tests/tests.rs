@@ -13,7 +13,8 @@ use pocket_ic::{
     PocketIcState, RejectCode, StartServerParams, Time,
     common::rest::{
         AutoProgressConfig, BlobCompression, CanisterHttpReply, CanisterHttpResponse,
-        CanisterIdRange, CreateInstanceResponse, HttpGatewayDetails, HttpsConfig, IcpFeatures,
+        CanisterIdRange, CanisterStateDiffKind, CreateInstanceResponse, FaultInjectionAction,
+        FaultInjectionRule, HttpGatewayDetails, HttpsConfig, IcpConfig, IcpConfigFlag, IcpFeatures,
         IcpFeaturesConfig, InitialTime, InstanceConfig, InstanceHttpGatewayConfig,
         MockCanisterHttpResponse, RawEffectivePrincipal, RawMessageId, SubnetConfigSet, SubnetKind,
     },
@@ -2624,6 +2625,326 @@ async fn state_handle_async() {
     pic2.drop().await;
 }
 
+#[test]
+fn checkpoint_fork_and_diff() {
+    let pic = PocketIc::new();
+    let canister_id = deploy_counter_canister(&pic);
+    pic.checkpoint("before_writes");
+
+    call_counter_canister(&pic, canister_id, "write");
+    call_counter_canister(&pic, canister_id, "write");
+    let new_canister_id = pic.create_canister();
+    pic.checkpoint("after_writes");
+
+    // A fork starts from the checkpointed state and evolves independently.
+    let fork = pic.fork("before_writes");
+    assert!(!fork.canister_exists(new_canister_id));
+    let reply = call_counter_canister(&fork, canister_id, "write");
+    assert_eq!(reply, vec![1, 0, 0, 0]);
+    let reply = call_counter_canister(&pic, canister_id, "read");
+    assert_eq!(reply, vec![2, 0, 0, 0]);
+
+    let diff = pic.diff_checkpoints("before_writes", "after_writes");
+    assert_eq!(diff.len(), 2);
+    let counter_diff = diff.iter().find(|d| d.canister_id == canister_id).unwrap();
+    assert_eq!(counter_diff.kind, CanisterStateDiffKind::Modified);
+    assert!(
+        counter_diff
+            .changes
+            .iter()
+            .any(|change| change.field == "wasm_memory")
+    );
+    assert!(
+        !counter_diff
+            .changes
+            .iter()
+            .any(|change| change.field == "module_hash")
+    );
+    let new_canister_diff = diff
+        .iter()
+        .find(|d| d.canister_id == new_canister_id)
+        .unwrap();
+    assert_eq!(new_canister_diff.kind, CanisterStateDiffKind::Created);
+
+    assert!(
+        pic.diff_checkpoints("after_writes", "after_writes")
+            .is_empty()
+    );
+}
+
+#[test]
+fn fork_keeps_builder_config() {
+    let http_gateway_config = InstanceHttpGatewayConfig {
+        ip_addr: None,
+        port: None,
+        domains: None,
+        https_config: None,
+    };
+    let pic = PocketIcBuilder::new()
+        .with_application_subnet()
+        .with_auto_progress()
+        .with_http_gateway(http_gateway_config)
+        .build();
+    let canister_id = deploy_counter_canister(&pic);
+    pic.checkpoint("deployed");
+
+    let fork = pic.fork("deployed");
+    assert!(fork.canister_exists(canister_id));
+    assert!(fork.auto_progress_enabled());
+    let fork_url = fork.url().unwrap();
+    assert_ne!(fork_url, pic.url().unwrap());
+}
+
+#[test]
+fn fault_injection_for_inter_canister_calls() {
+    let icp_config = IcpConfig {
+        fault_injection: Some(IcpConfigFlag::Enabled),
+        ..Default::default()
+    };
+    let pic = PocketIcBuilder::new()
+        .with_application_subnet()
+        .with_icp_config(icp_config)
+        .build();
+
+    let caller = pic.create_canister();
+    let callee = pic.create_canister();
+    for canister in [caller, callee] {
+        pic.add_cycles(canister, INIT_CYCLES);
+        pic.install_canister(canister, test_canister_wasm(), vec![], None);
+    }
+    let whois = |pic: &PocketIc| {
+        pic.update_call(
+            caller,
+            Principal::anonymous(),
+            "whois",
+            encode_one(callee).unwrap(),
+        )
+    };
+
+    // Without any rules, the inter-canister call succeeds.
+    let reply = whois(&pic).unwrap();
+    assert_eq!(decode_one::<String>(&reply).unwrap(), callee.to_string());
+
+    // The caller receives the injected reject (and traps on it).
+    pic.add_fault_injection_rule(FaultInjectionRule {
+        caller: Some(caller),
+        callee: Some(callee),
+        method: Some("whoami".to_string()),
+        action: FaultInjectionAction::Reject {
+            reject_code: RejectCode::SysTransient,
+            message: "injected fault".to_string(),
+        },
+    });
+    let err = whois(&pic).unwrap_err();
+    assert!(err.reject_message.contains("injected fault"));
+
+    // The call is only delivered to the callee after the delay.
+    pic.clear_fault_injection_rules();
+    pic.add_fault_injection_rule(FaultInjectionRule {
+        caller: None,
+        callee: Some(callee),
+        method: None,
+        action: FaultInjectionAction::Delay { rounds: 10 },
+    });
+    let message_id = pic
+        .submit_call(
+            caller,
+            Principal::anonymous(),
+            "whois",
+            encode_one(callee).unwrap(),
+        )
+        .unwrap();
+    for _ in 0..5 {
+        pic.tick();
+    }
+    assert!(pic.ingress_status(message_id.clone()).is_none());
+    let reply = pic.await_call(message_id).unwrap();
+    assert_eq!(decode_one::<String>(&reply).unwrap(), callee.to_string());
+
+    // Once the rules are cleared, calls are no longer affected.
+    pic.clear_fault_injection_rules();
+    let reply = whois(&pic).unwrap();
+    assert_eq!(decode_one::<String>(&reply).unwrap(), callee.to_string());
+}
+
+#[test]
+fn fault_injection_on_resumed_instance() {
+    let icp_config = IcpConfig {
+        fault_injection: Some(IcpConfigFlag::Enabled),
+        ..Default::default()
+    };
+    let pic = PocketIcBuilder::new()
+        .with_application_subnet()
+        .with_icp_config(icp_config.clone())
+        .with_state(PocketIcState::new())
+        .build();
+
+    let caller = pic.create_canister();
+    let callee = pic.create_canister();
+    for canister in [caller, callee] {
+        pic.add_cycles(canister, INIT_CYCLES);
+        pic.install_canister(canister, test_canister_wasm(), vec![], None);
+    }
+
+    pic.add_fault_injection_rule(FaultInjectionRule {
+        caller: None,
+        callee: Some(callee),
+        method: None,
+        action: FaultInjectionAction::Delay { rounds: 10 },
+    });
+    let message_id = pic
+        .submit_call(
+            caller,
+            Principal::anonymous(),
+            "whois",
+            encode_one(callee).unwrap(),
+        )
+        .unwrap();
+    for _ in 0..3 {
+        pic.tick();
+    }
+    let state = pic.drop_and_take_state().unwrap();
+
+    let pic = PocketIcBuilder::new()
+        .with_icp_config(icp_config)
+        .with_state(state)
+        .build();
+
+    // The held request is still delivered to the callee after the delay.
+    for _ in 0..3 {
+        pic.tick();
+    }
+    assert!(pic.ingress_status(message_id.clone()).is_none());
+    let reply = pic.await_call(message_id).unwrap();
+    assert_eq!(decode_one::<String>(&reply).unwrap(), callee.to_string());
+
+    // The rule still applies to new calls.
+    let message_id = pic
+        .submit_call(
+            caller,
+            Principal::anonymous(),
+            "whois",
+            encode_one(callee).unwrap(),
+        )
+        .unwrap();
+    for _ in 0..5 {
+        pic.tick();
+    }
+    assert!(pic.ingress_status(message_id).is_none());
+}
+
+#[test]
+#[should_panic(expected = "Fault injection must be enabled")]
+fn fault_injection_requires_icp_config() {
+    let pic = PocketIc::new();
+    pic.clear_fault_injection_rules();
+}
+
+#[test]
+fn update_call_with_profile() {
+    let icp_config = IcpConfig {
+        execution_profiling: Some(IcpConfigFlag::Enabled),
+        ..Default::default()
+    };
+    let pic = PocketIcBuilder::new()
+        .with_application_subnet()
+        .with_icp_config(icp_config)
+        .build();
+
+    let caller = pic.create_canister();
+    let callee = pic.create_canister();
+    for canister in [caller, callee] {
+        pic.add_cycles(canister, INIT_CYCLES);
+        pic.install_canister(canister, test_canister_wasm(), vec![], None);
+    }
+
+    let (result, profile) = pic.update_call_with_profile(
+        caller,
+        Principal::anonymous(),
+        "whois",
+        encode_one(callee).unwrap(),
+    );
+    let reply = result.unwrap();
+    assert_eq!(decode_one::<String>(&reply).unwrap(), callee.to_string());
+
+    // The call is executed in three phases: the update method of the caller,
+    // the update method of the callee, and the reply callback of the caller.
+    let phases: Vec<_> = profile
+        .executions
+        .iter()
+        .map(|execution| (execution.canister_id, execution.api_type.as_str()))
+        .collect();
+    assert_eq!(
+        phases,
+        vec![
+            (caller, "update"),
+            (callee, "update"),
+            (caller, "reply callback")
+        ]
+    );
+    for execution in &profile.executions {
+        assert!(execution.instructions > 0);
+    }
+    assert!(profile.instructions("update") > 0);
+    assert_eq!(profile.instructions("cleanup"), 0);
+
+    // Every System API call is counted, not only a fixed set of calls.
+    let caller_update = &profile.executions[0];
+    assert_eq!(caller_update.function, "canister_update whois");
+    for function in ["ic0.msg_arg_data_copy", "ic0.call_new", "ic0.call_perform"] {
+        assert_eq!(caller_update.system_api_call_counts[function], 1);
+    }
+    let callee_update = &profile.executions[1];
+    assert_eq!(callee_update.function, "canister_update whoami");
+    assert_eq!(callee_update.system_api_call_counts["ic0.msg_reply"], 1);
+    assert_eq!(profile.executions[2].function, "reply callback");
+
+    // The profile can be rendered as a flame graph.
+    let folded_stacks = profile.folded_stacks();
+    let callee_stack = format!(
+        "{callee};canister_update whoami {}",
+        callee_update.instructions
+    );
+    assert!(folded_stacks.lines().any(|line| line == callee_stack));
+    assert_eq!(folded_stacks.lines().count(), 3);
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("profile.folded");
+    profile.write_folded_stacks(&path).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), folded_stacks);
+
+    // The caller pays for the ingress message, the instructions, and the inter-canister call.
+    let caller_cycles = profile
+        .cycles
+        .iter()
+        .find(|cycles| cycles.canister_id == caller)
+        .unwrap();
+    for use_case in [
+        "IngressInduction",
+        "Instructions",
+        "RequestAndResponseTransmission",
+    ] {
+        assert!(caller_cycles.cycles_by_use_case[use_case] > 0);
+    }
+
+    // No executions are profiled after profiling has been stopped.
+    pic.update_call(
+        caller,
+        Principal::anonymous(),
+        "whois",
+        encode_one(callee).unwrap(),
+    )
+    .unwrap();
+    pic.start_profiling();
+    assert!(pic.stop_profiling().executions.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Execution profiling must be enabled")]
+fn profiling_requires_icp_config() {
+    let pic = PocketIc::new();
+    pic.start_profiling();
+}
+
 #[test]
 #[should_panic(expected = "PocketIC instance state must be empty if a read-only state is mounted.")]
 fn non_empty_state_and_read_only_state() {
@@ -2995,6 +3316,8 @@ async fn with_http_gateway_config_invalid_instance_config() {
         log_level: Some("invalid".to_string()),
         bitcoind_addr: None,
         dogecoind_addr: None,
+        bitcoin_local_chain: None,
+        dogecoin_local_chain: None,
         icp_features: None,
         incomplete_state: None,
         initial_time: Some(InitialTime::AutoProgress(auto_progress_config)),
@@ -3059,6 +3382,8 @@ async fn with_http_gateway_config_invalid_gateway_port() {
         log_level: None,
         bitcoind_addr: None,
         dogecoind_addr: None,
+        bitcoin_local_chain: None,
+        dogecoin_local_chain: None,
         icp_features: None,
         incomplete_state: None,
         initial_time: Some(InitialTime::AutoProgress(auto_progress_config)),
@@ -3110,6 +3435,8 @@ async fn with_http_gateway_config_invalid_gateway_https_config() {
         log_level: None,
         bitcoind_addr: None,
         dogecoind_addr: None,
+        bitcoin_local_chain: None,
+        dogecoin_local_chain: None,
         icp_features: None,
         incomplete_state: None,
         initial_time: Some(InitialTime::AutoProgress(auto_progress_config)),
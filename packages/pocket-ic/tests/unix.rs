@@ -129,6 +129,8 @@ async fn resume_killed_instance_impl(
         log_level: None,
         bitcoind_addr: None,
         dogecoind_addr: None,
+        bitcoin_local_chain: None,
+        dogecoin_local_chain: None,
         icp_features: None,
         incomplete_state,
         initial_time: None,
@@ -11,11 +11,14 @@ use crate::{
 };
 use bitcoin::{
     BlockHash,
+    bip158::{FilterHash, FilterHeader},
     block::Header as PureHeader,
     hashes::Hash as _,
     p2p::{
+        ServiceFlags,
         message::{MAX_INV_SIZE, NetworkMessage},
         message_blockdata::{GetHeadersMessage, Inventory},
+        message_filter::{CFHeaders, CFilter, GetCFHeaders, GetCFilters},
     },
 };
 use hashlink::{LinkedHashMap, LinkedHashSet};
@@ -40,6 +43,21 @@ const MAX_UNSOLICITED_HEADERS: usize = 20;
 /// to a peer at a time.
 const INV_PER_GET_DATA_REQUEST: u32 = 8;
 
+/// Max number of `getcfheaders` and `getcfilters` requests that can be in flight to a peer at a time.
+const CFILTERS_REQUESTS_PER_PEER: u32 = 8;
+
+/// The number of peers that must report the same filter hash for a block before the
+/// filter of the block is downloaded. If peers disagree, the agreeing peers must also
+/// outnumber the dissenting ones. Filters are only accepted if their hash matches.
+const FILTER_HASH_QUORUM: usize = 2;
+
+/// The maximum number of verified filter headers kept by the BlockchainManager. When
+/// the limit is reached, the oldest filter header is evicted.
+const MAX_VERIFIED_FILTER_HEADERS: usize = 10_000;
+
+/// The filter type of BIP-158 basic filters.
+const BASIC_FILTER_TYPE: u8 = 0;
+
 /// Block locators. Consists of starting hashes and a stop hash.
 type Locators = (Vec<BlockHash>, BlockHash);
 
@@ -82,6 +100,43 @@ pub enum ReceivedBlockMessageError {
     BlockNotAdded,
 }
 
+/// The possible errors the `BlockchainManager::received_cfilter_message(...)` may produce.
+#[derive(Debug, Error)]
+enum ReceivedCFilterMessageError {
+    /// This variant represents when a message from an unknown peer.
+    #[error("Unknown peer")]
+    UnknownPeer,
+    /// The filter was not requested from the peer.
+    #[error("Unsolicited filter")]
+    UnsolicitedFilter,
+    /// Only basic filters are requested by the adapter.
+    #[error("Unexpected filter type {0}")]
+    UnexpectedFilterType(u8),
+    /// The hash of the filter differs from the filter hash the peers agreed on.
+    #[error("Filter hash mismatch")]
+    FilterHashMismatch,
+}
+
+/// The possible errors the `BlockchainManager::received_cfheaders_message(...)` may produce.
+#[derive(Debug, Error)]
+enum ReceivedCFHeadersMessageError {
+    /// This variant represents when a message from an unknown peer.
+    #[error("Unknown peer")]
+    UnknownPeer,
+    /// The filter headers were not requested from the peer.
+    #[error("Unsolicited filter headers")]
+    UnsolicitedFilterHeaders,
+    /// Only basic filters are requested by the adapter.
+    #[error("Unexpected filter type {0}")]
+    UnexpectedFilterType(u8),
+    /// The adapter requests the filter hash of a single block at a time.
+    #[error("Unexpected number of filter hashes {0}")]
+    UnexpectedFilterHashCount(usize),
+    /// The previous filter header differs from the verified filter header of the parent block.
+    #[error("Filter header does not extend the verified filter header chain")]
+    InconsistentFilterHeaderChain,
+}
+
 /// This struct stores the information regarding a peer with respect to synchronizing the blockchain.
 /// This information is useful to keep track of the commands that have been sent to the peer,
 /// and how much blockchain state has already been synced with the peer.
@@ -129,6 +184,47 @@ struct GetDataRequestInfo {
     sent_at: Option<Instant>,
 }
 
+/// This struct stores the `getcfheaders` requests sent for a block and the filter hashes
+/// reported by peers in response.
+#[derive(Debug, Default)]
+struct FilterHashReports {
+    /// The peers to which a `getcfheaders` request is in flight and when it was sent.
+    requests: HashMap<SocketAddr, Instant>,
+    /// The previous filter header and the filter hash reported by each peer.
+    reports: HashMap<SocketAddr, (FilterHeader, FilterHash)>,
+}
+
+impl FilterHashReports {
+    /// Returns the report made by the most peers, how many peers made it and how many
+    /// peers reported something else.
+    fn leading_report(&self) -> Option<((FilterHeader, FilterHash), usize, usize)> {
+        let mut votes: HashMap<(FilterHeader, FilterHash), usize> = HashMap::new();
+        for report in self.reports.values() {
+            *votes.entry(*report).or_default() += 1;
+        }
+        votes
+            .into_iter()
+            .max_by_key(|(_, count)| *count)
+            .map(|(report, count)| (report, count, self.reports.len() - count))
+    }
+
+    /// Returns the number of reports needed for the leading report to be verified,
+    /// assuming that all further reports agree with it.
+    fn required_reports(&self) -> usize {
+        let dissenting = self
+            .leading_report()
+            .map_or(0, |(_, _, dissenting)| dissenting);
+        FILTER_HASH_QUORUM.max(dissenting + 1) + dissenting
+    }
+}
+
+/// The filter hash and filter header of a block that a quorum of peers agreed on.
+#[derive(Clone, Copy, Debug)]
+struct VerifiedFilterHeader {
+    filter_hash: FilterHash,
+    filter_header: FilterHeader,
+}
+
 /// The BlockChainManager struct handles interactions that involve the headers.
 pub struct BlockchainManager<Network: BlockchainNetwork> {
     /// This field contains the BlockchainState, which stores and manages
@@ -165,6 +261,33 @@ pub struct BlockchainManager<Network: BlockchainNetwork> {
     /// A block hash is removed when it is determined a peer can receive another `getdata` message.
     block_sync_queue: LinkedHashSet<BlockHash>,
 
+    /// This HashMap stores the information related to each `getcfilters` request
+    /// sent by the BlockChainManager. An entry is removed from this hashmap when
+    /// the corresponding `cfilter` response is received.
+    getcfilters_request_info: LinkedHashMap<BlockHash, GetDataRequestInfo>,
+
+    /// This queue stores the hashes of the blocks whose filters have yet to be synced by the
+    /// BlockChainManager and stored into the filter cache. A block hash is added when the
+    /// `GetBlockFilters` request is processed.
+    ///
+    /// A block hash is removed when the `getcfilters` request for the block is sent, which
+    /// only happens once the filter hash of the block has been verified.
+    filter_sync_queue: LinkedHashSet<BlockHash>,
+
+    /// This HashMap stores the filter hashes reported by peers in `cfheaders` messages for
+    /// the blocks whose filter hash has yet to be verified. An entry is removed when
+    /// `FILTER_HASH_QUORUM` peers agree on the filter hash and outnumber the peers that
+    /// reported a different one.
+    filter_hash_reports: HashMap<BlockHash, FilterHashReports>,
+
+    /// This field stores the filter headers of blocks, oldest first, that a quorum of peers
+    /// agreed on. A filter is only added to the filter cache if its hash matches.
+    verified_filter_headers: LinkedHashMap<BlockHash, VerifiedFilterHeader>,
+
+    /// This set stores the peers that reported a filter hash contradicting the one a quorum
+    /// of peers agreed on. These peers are disconnected when filters are synced next.
+    outlier_filter_peers: HashSet<SocketAddr>,
+
     /// This field contains a logger for the blockchain manager's use.
     logger: ReplicaLogger,
     metrics: RouterMetrics,
@@ -195,6 +318,11 @@ where
             getheaders_requests: HashMap::new(),
             catchup_headers: HashSet::new(),
             block_sync_queue: LinkedHashSet::new(),
+            getcfilters_request_info: LinkedHashMap::new(),
+            filter_sync_queue: LinkedHashSet::new(),
+            filter_hash_reports: HashMap::new(),
+            verified_filter_headers: LinkedHashMap::new(),
+            outlier_filter_peers: HashSet::new(),
             logger,
             metrics,
             request_timeout,
@@ -209,6 +337,11 @@ where
 
         self.block_sync_queue.clear();
         self.getdata_request_info.clear();
+        self.filter_sync_queue.clear();
+        self.getcfilters_request_info.clear();
+        self.filter_hash_reports.clear();
+        self.verified_filter_headers.clear();
+        self.outlier_filter_peers.clear();
         self.peer_info.clear();
         self.blockchain.clear_blocks();
     }
@@ -453,6 +586,142 @@ where
         }
     }
 
+    /// This function processes "cfilter" messages received from Bitcoin nodes.
+    fn received_cfilter_message(
+        &mut self,
+        addr: &SocketAddr,
+        cfilter: &CFilter,
+    ) -> Result<(), ReceivedCFilterMessageError> {
+        if !self.peer_info.contains_key(addr) {
+            return Err(ReceivedCFilterMessageError::UnknownPeer);
+        }
+        if cfilter.filter_type != BASIC_FILTER_TYPE {
+            return Err(ReceivedCFilterMessageError::UnexpectedFilterType(
+                cfilter.filter_type,
+            ));
+        }
+        match self.getcfilters_request_info.get(&cfilter.block_hash) {
+            Some(request) if request.socket == *addr => {}
+            _ => return Err(ReceivedCFilterMessageError::UnsolicitedFilter),
+        }
+        self.getcfilters_request_info.remove(&cfilter.block_hash);
+
+        let verified = self
+            .verified_filter_headers
+            .get(&cfilter.block_hash)
+            .map(|verified| verified.filter_hash);
+        if verified != Some(FilterHash::hash(&cfilter.filter)) {
+            // Request the filter again, possibly from another peer.
+            if verified.is_some() {
+                self.filter_sync_queue.insert(cfilter.block_hash);
+            }
+            return Err(ReceivedCFilterMessageError::FilterHashMismatch);
+        }
+
+        trace!(
+            self.logger,
+            "Received cfilter message from {} for block {}", addr, cfilter.block_hash
+        );
+        self.blockchain
+            .add_block_filter(cfilter.block_hash, cfilter.filter.clone());
+        Ok(())
+    }
+
+    /// This function processes "cfheaders" messages received from Bitcoin nodes. The filter
+    /// hash reported by the peer is recorded and verified once enough peers reported it.
+    fn received_cfheaders_message(
+        &mut self,
+        addr: &SocketAddr,
+        cfheaders: &CFHeaders,
+    ) -> Result<(), ReceivedCFHeadersMessageError> {
+        if !self.peer_info.contains_key(addr) {
+            return Err(ReceivedCFHeadersMessageError::UnknownPeer);
+        }
+        if cfheaders.filter_type != BASIC_FILTER_TYPE {
+            return Err(ReceivedCFHeadersMessageError::UnexpectedFilterType(
+                cfheaders.filter_type,
+            ));
+        }
+        let block_hash = cfheaders.stop_hash;
+        let parent_filter_header = self.parent_filter_header(&block_hash);
+        let Some(reports) = self.filter_hash_reports.get_mut(&block_hash) else {
+            return Err(ReceivedCFHeadersMessageError::UnsolicitedFilterHeaders);
+        };
+        if reports.requests.remove(addr).is_none() {
+            return Err(ReceivedCFHeadersMessageError::UnsolicitedFilterHeaders);
+        }
+        let [filter_hash] = cfheaders.filter_hashes[..] else {
+            return Err(ReceivedCFHeadersMessageError::UnexpectedFilterHashCount(
+                cfheaders.filter_hashes.len(),
+            ));
+        };
+        if parent_filter_header
+            .is_some_and(|filter_header| filter_header != cfheaders.previous_filter_header)
+        {
+            return Err(ReceivedCFHeadersMessageError::InconsistentFilterHeaderChain);
+        }
+
+        trace!(
+            self.logger,
+            "Received cfheaders message from {} for block {}", addr, block_hash
+        );
+        reports
+            .reports
+            .insert(*addr, (cfheaders.previous_filter_header, filter_hash));
+        self.evaluate_filter_hash_reports(&block_hash);
+        Ok(())
+    }
+
+    /// Returns the verified filter header of the parent of the given block, if any.
+    fn parent_filter_header(&self, block_hash: &BlockHash) -> Option<FilterHeader> {
+        let node = self.blockchain.get_cached_header(block_hash)?;
+        self.verified_filter_headers
+            .get(&node.data.header.prev_block_hash())
+            .map(|verified| verified.filter_header)
+    }
+
+    /// Verifies the filter hash of the given block once `FILTER_HASH_QUORUM` peers reported
+    /// the same filter hash and they outnumber the peers that reported a different one. The
+    /// dissenting peers are then marked as outliers. As long as peers disagree without a
+    /// clear majority, the filter hash is requested from further peers.
+    fn evaluate_filter_hash_reports(&mut self, block_hash: &BlockHash) {
+        let Some(reports) = self.filter_hash_reports.get(block_hash) else {
+            return;
+        };
+        let Some((leading, agreeing, dissenting)) = reports.leading_report() else {
+            return;
+        };
+        if dissenting > 0 {
+            warn!(
+                self.logger,
+                "Peers reported different filter hashes for block {}", block_hash
+            );
+        }
+        if agreeing < FILTER_HASH_QUORUM || agreeing <= dissenting {
+            return;
+        }
+        let (previous_filter_header, filter_hash) = leading;
+        self.outlier_filter_peers.extend(
+            reports
+                .reports
+                .iter()
+                .filter(|(_, report)| **report != leading)
+                .map(|(peer, _)| *peer),
+        );
+
+        self.filter_hash_reports.remove(block_hash);
+        if self.verified_filter_headers.len() >= MAX_VERIFIED_FILTER_HEADERS {
+            self.verified_filter_headers.pop_front();
+        }
+        self.verified_filter_headers.replace(
+            *block_hash,
+            VerifiedFilterHeader {
+                filter_hash,
+                filter_header: filter_hash.filter_header(&previous_filter_header),
+            },
+        );
+    }
+
     /// This function adds a new peer to `peer_info`
     /// and initiates sync with the peer by sending `getheaders` message.
     fn add_peer(
@@ -497,6 +766,17 @@ where
             }
         }
 
+        // Retry the `getcfilters` requests that have been sent to the peer before.
+        for request in self.getcfilters_request_info.values_mut() {
+            if request.socket == *addr {
+                request.sent_at = None;
+            }
+        }
+        // Ask other peers for the filter hashes requested from the peer.
+        for reports in self.filter_hash_reports.values_mut() {
+            reports.requests.remove(addr);
+        }
+
         // Remove getheaders request sent to peer.
         self.getheaders_requests.remove(addr);
         // Unset catch-up flag
@@ -634,6 +914,138 @@ where
         }
     }
 
+    /// Syncs the filters of the queued block hashes from peers that serve compact block
+    /// filters. The filter hash of a block is first requested with `getcfheaders` from
+    /// `FILTER_HASH_QUORUM` peers. Once the filter hash is verified, the filter itself is
+    /// requested with `getcfilters`. Each request covers a single block.
+    fn sync_block_filters(&mut self, channel: &mut impl Channel<Network::Header, Network::Block>) {
+        let request_timeout = self.request_timeout;
+        // Timed out requests are retried, possibly with another peer.
+        let timed_out: Vec<BlockHash> = self
+            .getcfilters_request_info
+            .iter()
+            .filter(|(_, request)| {
+                request
+                    .sent_at
+                    .is_none_or(|sent_at| sent_at.elapsed() > request_timeout)
+            })
+            .map(|(block_hash, _)| *block_hash)
+            .collect();
+        for block_hash in timed_out {
+            self.getcfilters_request_info.remove(&block_hash);
+            self.filter_sync_queue.insert(block_hash);
+        }
+        for reports in self.filter_hash_reports.values_mut() {
+            reports
+                .requests
+                .retain(|_, sent_at| sent_at.elapsed() <= request_timeout);
+        }
+
+        for peer in self.outlier_filter_peers.drain() {
+            warn!(
+                self.logger,
+                "Disconnecting from {} as it reported an outlier filter hash", peer
+            );
+            channel.discard(&peer);
+        }
+
+        if self.filter_sync_queue.is_empty() {
+            return;
+        }
+
+        let peers: Vec<SocketAddr> = channel
+            .available_connections_with_services(ServiceFlags::COMPACT_FILTERS)
+            .into_iter()
+            .filter(|addr| self.peer_info.contains_key(addr))
+            .collect();
+
+        let mut requests_sent_to_peer: HashMap<SocketAddr, u32> = HashMap::new();
+        for request in self.getcfilters_request_info.values() {
+            *requests_sent_to_peer.entry(request.socket).or_default() += 1;
+        }
+        for reports in self.filter_hash_reports.values() {
+            for peer in reports.requests.keys() {
+                *requests_sent_to_peer.entry(*peer).or_default() += 1;
+            }
+        }
+        let has_capacity = |requests_sent_to_peer: &HashMap<SocketAddr, u32>, peer| {
+            requests_sent_to_peer.get(peer).copied().unwrap_or_default()
+                < CFILTERS_REQUESTS_PER_PEER
+        };
+
+        let queued: Vec<BlockHash> = self.filter_sync_queue.iter().copied().collect();
+        for block_hash in queued {
+            let Some(node) = self.blockchain.get_cached_header(&block_hash) else {
+                self.filter_sync_queue.remove(&block_hash);
+                continue;
+            };
+
+            if self.verified_filter_headers.contains_key(&block_hash) {
+                let Some(peer) = peers
+                    .iter()
+                    .find(|peer| has_capacity(&requests_sent_to_peer, *peer))
+                    .copied()
+                else {
+                    continue;
+                };
+                trace!(
+                    self.logger,
+                    "Sending getcfilters to {} : Block {}", peer, block_hash
+                );
+                channel
+                    .send(Command {
+                        address: Some(peer),
+                        message: NetworkMessage::GetCFilters(GetCFilters {
+                            filter_type: BASIC_FILTER_TYPE,
+                            start_height: node.data.height,
+                            stop_hash: block_hash,
+                        }),
+                    })
+                    .ok();
+                *requests_sent_to_peer.entry(peer).or_default() += 1;
+                self.filter_sync_queue.remove(&block_hash);
+                self.getcfilters_request_info.replace(
+                    block_hash,
+                    GetDataRequestInfo {
+                        socket: peer,
+                        sent_at: Some(Instant::now()),
+                    },
+                );
+                continue;
+            }
+
+            let reports = self.filter_hash_reports.entry(block_hash).or_default();
+            let required_reports = reports.required_reports();
+            for peer in &peers {
+                if reports.requests.len() + reports.reports.len() >= required_reports {
+                    break;
+                }
+                if !has_capacity(&requests_sent_to_peer, peer)
+                    || reports.requests.contains_key(peer)
+                    || reports.reports.contains_key(peer)
+                {
+                    continue;
+                }
+                trace!(
+                    self.logger,
+                    "Sending getcfheaders to {} : Block {}", peer, block_hash
+                );
+                channel
+                    .send(Command {
+                        address: Some(*peer),
+                        message: NetworkMessage::GetCFHeaders(GetCFHeaders {
+                            filter_type: BASIC_FILTER_TYPE,
+                            start_height: node.data.height,
+                            stop_hash: block_hash,
+                        }),
+                    })
+                    .ok();
+                *requests_sent_to_peer.entry(*peer).or_default() += 1;
+                reports.requests.insert(*peer, Instant::now());
+            }
+        }
+    }
+
     /// This function is called by the adapter when a new event takes place.
     /// The event could be receiving "getheaders", "getdata", "inv" messages from bitcoin peers.
     /// The event could be change in connection status with a bitcoin peer.
@@ -668,6 +1080,21 @@ where
                     return Err(ProcessNetworkMessageError::InvalidMessage);
                 }
             }
+            NetworkMessage::CFHeaders(cfheaders) => {
+                if let Err(err) = self.received_cfheaders_message(&addr, cfheaders) {
+                    warn!(
+                        self.logger,
+                        "Received an invalid cfheaders {}: {}", addr, err
+                    );
+                    return Err(ProcessNetworkMessageError::InvalidMessage);
+                }
+            }
+            NetworkMessage::CFilter(cfilter) => {
+                if let Err(err) = self.received_cfilter_message(&addr, cfilter) {
+                    warn!(self.logger, "Received an invalid cfilter {}: {}", addr, err);
+                    return Err(ProcessNetworkMessageError::InvalidMessage);
+                }
+            }
             _ => {}
         };
         Ok(())
@@ -704,6 +1131,7 @@ where
         }
 
         self.sync_blocks(channel);
+        self.sync_block_filters(channel);
         self.handle_getheaders_timeouts(channel);
     }
 
@@ -721,6 +1149,20 @@ where
         }
     }
 
+    /// Add block hashes to the filter sync queue whose filters are not already being synced,
+    /// planned to be synced, or in the filter cache. Unknown block hashes are ignored.
+    pub fn enqueue_block_filters_to_download(&mut self, block_hashes: Vec<BlockHash>) {
+        for hash in block_hashes {
+            if self.blockchain.get_cached_header(&hash).is_some()
+                && self.blockchain.get_block_filter(&hash).is_none()
+                && !self.filter_sync_queue.contains(&hash)
+                && !self.getcfilters_request_info.contains_key(&hash)
+            {
+                self.filter_sync_queue.insert(hash);
+            }
+        }
+    }
+
     /// Wrapper function to access the blockchain state to prune blocks that are no longer
     /// needed.
     pub fn prune_blocks(&mut self, anchor: BlockHash, processed_block_hashes: Vec<BlockHash>) {
@@ -1661,4 +2103,266 @@ pub mod test {
         assert!(channel.has_discarded_address(&addr));
         assert!(!channel.has_discarded_address(&addr2));
     }
+
+    /// Creates a blockchain manager with two headers on top of genesis and three peers,
+    /// where only the last two serve compact block filters.
+    async fn create_filter_sync_setup() -> (
+        BlockchainManager<Network>,
+        TestChannel,
+        Vec<BlockHeader>,
+        [SocketAddr; 3],
+    ) {
+        let addrs = [
+            SocketAddr::from_str("127.0.0.1:8333").expect("bad address format"),
+            SocketAddr::from_str("127.0.0.1:8444").expect("bad address format"),
+            SocketAddr::from_str("127.0.0.1:8555").expect("bad address format"),
+        ];
+        let mut channel = TestChannel::new(addrs.to_vec());
+        channel.set_services(addrs[1], ServiceFlags::COMPACT_FILTERS);
+        channel.set_services(addrs[2], ServiceFlags::COMPACT_FILTERS);
+        let (genesis, mut blockchain_manager) = create_blockchain_manager(Network::Regtest);
+        let headers = generate_headers(genesis.block_hash(), genesis.time, 2, &[]);
+        blockchain_manager.blockchain.add_headers(&headers).await;
+        for addr in &addrs {
+            blockchain_manager.add_peer(&mut channel, addr);
+        }
+        while channel.pop_front().is_some() {}
+        (blockchain_manager, channel, headers, addrs)
+    }
+
+    fn cfheaders(
+        block_hash: BlockHash,
+        previous_filter_header: FilterHeader,
+        filter: &[u8],
+    ) -> CFHeaders {
+        CFHeaders {
+            filter_type: BASIC_FILTER_TYPE,
+            stop_hash: block_hash,
+            previous_filter_header,
+            filter_hashes: vec![FilterHash::hash(filter)],
+        }
+    }
+
+    /// Tests that filter hashes and block filters are only requested from peers serving
+    /// compact filters and that only filters whose hash a quorum of peers agreed on are
+    /// added to the filter cache.
+    #[tokio::test]
+    async fn test_sync_block_filters() {
+        let (mut blockchain_manager, mut channel, headers, [addr, filter_addr1, filter_addr2]) =
+            create_filter_sync_setup().await;
+
+        let unknown_hash = BlockHash::all_zeros();
+        blockchain_manager.enqueue_block_filters_to_download(vec![
+            headers[0].block_hash(),
+            headers[1].block_hash(),
+            unknown_hash,
+        ]);
+        assert_eq!(blockchain_manager.filter_sync_queue.len(), 2);
+
+        // The filter hash of each block is requested from both filter peers.
+        blockchain_manager.sync_block_filters(&mut channel);
+        assert_eq!(channel.command_count(), 4);
+        for (height, header) in headers.iter().enumerate() {
+            for filter_addr in [filter_addr1, filter_addr2] {
+                let command = channel.pop_front().expect("command not found");
+                assert_eq!(command.address, Some(filter_addr));
+                assert_eq!(
+                    command.message,
+                    NetworkMessage::GetCFHeaders(GetCFHeaders {
+                        filter_type: BASIC_FILTER_TYPE,
+                        start_height: height as u32 + 1,
+                        stop_hash: header.block_hash(),
+                    })
+                );
+            }
+        }
+
+        let filter = vec![1, 2, 3];
+        let block_hash = headers[0].block_hash();
+        let cfheaders = cfheaders(block_hash, FilterHeader::all_zeros(), &filter);
+        assert!(
+            blockchain_manager
+                .received_cfheaders_message(&addr, &cfheaders)
+                .is_err()
+        );
+        blockchain_manager
+            .received_cfheaders_message(&filter_addr1, &cfheaders)
+            .expect("the filter hash was requested from the peer");
+        // A single report is not enough to download the filter.
+        blockchain_manager.sync_block_filters(&mut channel);
+        assert_eq!(channel.command_count(), 0);
+
+        blockchain_manager
+            .received_cfheaders_message(&filter_addr2, &cfheaders)
+            .expect("the filter hash was requested from the peer");
+        blockchain_manager.sync_block_filters(&mut channel);
+        assert_eq!(channel.command_count(), 1);
+        let command = channel.pop_front().expect("command not found");
+        assert_eq!(command.address, Some(filter_addr1));
+        assert_eq!(
+            command.message,
+            NetworkMessage::GetCFilters(GetCFilters {
+                filter_type: BASIC_FILTER_TYPE,
+                start_height: 1,
+                stop_hash: block_hash,
+            })
+        );
+
+        // A filter that does not match the verified filter hash is rejected and requested again.
+        let invalid_cfilter = CFilter {
+            filter_type: BASIC_FILTER_TYPE,
+            block_hash,
+            filter: vec![4, 5, 6],
+        };
+        assert!(matches!(
+            blockchain_manager.received_cfilter_message(&filter_addr1, &invalid_cfilter),
+            Err(ReceivedCFilterMessageError::FilterHashMismatch)
+        ));
+        assert_eq!(
+            blockchain_manager.blockchain.get_block_filter(&block_hash),
+            None
+        );
+        assert!(blockchain_manager.filter_sync_queue.contains(&block_hash));
+
+        blockchain_manager.sync_block_filters(&mut channel);
+        assert_eq!(channel.command_count(), 1);
+        let command = channel.pop_front().expect("command not found");
+        let cfilter = CFilter {
+            filter_type: BASIC_FILTER_TYPE,
+            block_hash,
+            filter: filter.clone(),
+        };
+        assert!(
+            blockchain_manager
+                .received_cfilter_message(&addr, &cfilter)
+                .is_err()
+        );
+        blockchain_manager
+            .received_cfilter_message(&command.address.unwrap(), &cfilter)
+            .expect("the filter was requested from the peer");
+        assert_eq!(
+            blockchain_manager
+                .blockchain
+                .get_block_filter(&block_hash)
+                .as_deref(),
+            Some(&filter)
+        );
+        assert!(blockchain_manager.getcfilters_request_info.is_empty());
+    }
+
+    /// Tests that if peers report different filter hashes, the filter hash is requested from
+    /// another peer and the outlier peer is disconnected once a majority agrees.
+    #[tokio::test]
+    async fn test_outlier_filter_hash_is_requeried() {
+        let (mut blockchain_manager, mut channel, headers, [_, filter_addr1, filter_addr2]) =
+            create_filter_sync_setup().await;
+        let filter_addr3 = SocketAddr::from_str("127.0.0.1:8666").expect("bad address format");
+        channel.add_address(filter_addr3);
+        channel.set_services(filter_addr3, ServiceFlags::COMPACT_FILTERS);
+        blockchain_manager.add_peer(&mut channel, &filter_addr3);
+        while channel.pop_front().is_some() {}
+
+        let block_hash = headers[0].block_hash();
+        blockchain_manager.enqueue_block_filters_to_download(vec![block_hash]);
+        blockchain_manager.sync_block_filters(&mut channel);
+        assert_eq!(channel.command_count(), 2);
+        while channel.pop_front().is_some() {}
+
+        let filter = vec![1, 2, 3];
+        blockchain_manager
+            .received_cfheaders_message(
+                &filter_addr1,
+                &cfheaders(block_hash, FilterHeader::all_zeros(), &filter),
+            )
+            .expect("the filter hash was requested from the peer");
+        blockchain_manager
+            .received_cfheaders_message(
+                &filter_addr2,
+                &cfheaders(block_hash, FilterHeader::all_zeros(), &[4, 5, 6]),
+            )
+            .expect("the filter hash was requested from the peer");
+        assert!(
+            !blockchain_manager
+                .verified_filter_headers
+                .contains_key(&block_hash)
+        );
+        assert!(blockchain_manager.filter_sync_queue.contains(&block_hash));
+
+        // The filter hash is requested from the peer that has not reported yet.
+        blockchain_manager.sync_block_filters(&mut channel);
+        assert_eq!(channel.command_count(), 1);
+        let command = channel.pop_front().expect("command not found");
+        assert_eq!(command.address, Some(filter_addr3));
+        blockchain_manager
+            .received_cfheaders_message(
+                &filter_addr3,
+                &cfheaders(block_hash, FilterHeader::all_zeros(), &filter),
+            )
+            .expect("the filter hash was requested from the peer");
+        assert_eq!(
+            blockchain_manager
+                .verified_filter_headers
+                .get(&block_hash)
+                .expect("the filter hash was verified")
+                .filter_hash,
+            FilterHash::hash(&filter)
+        );
+
+        // The outlier is disconnected and the filter is downloaded from another peer.
+        blockchain_manager.sync_block_filters(&mut channel);
+        assert!(channel.has_discarded_address(&filter_addr2));
+        assert!(!channel.has_discarded_address(&filter_addr1));
+        assert_eq!(channel.command_count(), 1);
+        let command = channel.pop_front().expect("command not found");
+        assert_eq!(command.address, Some(filter_addr1));
+        assert!(matches!(command.message, NetworkMessage::GetCFilters(_)));
+    }
+
+    /// Tests that a filter hash is rejected if its previous filter header does not match the
+    /// verified filter header of the parent block.
+    #[tokio::test]
+    async fn test_filter_headers_must_extend_the_verified_chain() {
+        let (mut blockchain_manager, mut channel, headers, [_, filter_addr1, filter_addr2]) =
+            create_filter_sync_setup().await;
+        let parent_hash = headers[0].block_hash();
+        let child_hash = headers[1].block_hash();
+        blockchain_manager.enqueue_block_filters_to_download(vec![parent_hash, child_hash]);
+        blockchain_manager.sync_block_filters(&mut channel);
+        while channel.pop_front().is_some() {}
+
+        let parent_cfheaders = cfheaders(parent_hash, FilterHeader::all_zeros(), &[1, 2, 3]);
+        for filter_addr in [filter_addr1, filter_addr2] {
+            blockchain_manager
+                .received_cfheaders_message(&filter_addr, &parent_cfheaders)
+                .expect("the filter hash was requested from the peer");
+        }
+        let parent_filter_header = blockchain_manager
+            .verified_filter_headers
+            .get(&parent_hash)
+            .expect("the filter hash was verified")
+            .filter_header;
+        assert_eq!(
+            parent_filter_header,
+            FilterHash::hash(&[1, 2, 3]).filter_header(&FilterHeader::all_zeros())
+        );
+
+        assert!(matches!(
+            blockchain_manager.received_cfheaders_message(
+                &filter_addr1,
+                &cfheaders(child_hash, FilterHeader::all_zeros(), &[4, 5, 6]),
+            ),
+            Err(ReceivedCFHeadersMessageError::InconsistentFilterHeaderChain)
+        ));
+        blockchain_manager
+            .received_cfheaders_message(
+                &filter_addr2,
+                &cfheaders(child_hash, parent_filter_header, &[4, 5, 6]),
+            )
+            .expect("the filter hash was requested from the peer");
+        assert!(
+            !blockchain_manager
+                .verified_filter_headers
+                .contains_key(&child_hash)
+        );
+    }
 }
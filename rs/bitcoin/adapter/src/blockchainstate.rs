@@ -7,6 +7,7 @@ use crate::{
     metrics::BlockchainStateMetrics,
 };
 use bitcoin::{BlockHash, block::Header, consensus::Encodable, dogecoin::Header as DogecoinHeader};
+use hashlink::LinkedHashMap;
 use ic_btc_validation::doge::DogecoinHeaderValidator;
 use ic_btc_validation::{
     AuxPowHeaderValidator, HeaderStore, ValidateAuxPowHeaderError, ValidateHeaderError,
@@ -27,6 +28,10 @@ use thiserror::Error;
 const BLOCK_CACHE_THRESHOLD_BYTES: usize = 10 * ONE_MB;
 const ONE_MB: usize = 1_024 * 1_024;
 
+/// The maximum number of block filters kept in the filter cache. When the cache is full,
+/// the oldest filter is evicted.
+const FILTER_CACHE_CAPACITY: usize = 10_000;
+
 #[derive(Debug, Error)]
 pub enum AddHeaderError<Error> {
     /// When the received header is invalid (e.g., not in the right format).
@@ -52,6 +57,8 @@ pub enum AddBlockError<Error> {
 
 pub type SerializedBlock = Vec<u8>;
 
+pub type SerializedFilter = Vec<u8>;
+
 /// This struct is a cache of Bitcoin blockchain.
 /// The BlockChainState caches all the Bitcoin headers, some of the Bitcoin blocks.
 /// The BlockChainState also maintains the child relationhips between the headers.
@@ -62,6 +69,9 @@ pub struct BlockchainState<Network: BlockchainNetwork> {
     /// This field stores a hashmap containing BlockHash and the corresponding SerializedBlock.
     block_cache: RwLock<HashMap<BlockHash, Arc<SerializedBlock>>>,
 
+    /// This field stores the BIP-158 basic filters of blocks, oldest first.
+    filter_cache: RwLock<LinkedHashMap<BlockHash, Arc<SerializedFilter>>>,
+
     /// Used to determine how validation should be handled with `validate_header`.
     network: Network,
     metrics: BlockchainStateMetrics,
@@ -115,6 +125,7 @@ where
         BlockchainState {
             header_cache,
             block_cache,
+            filter_cache: RwLock::new(LinkedHashMap::new()),
             network,
             metrics: BlockchainStateMetrics::new(metrics_registry),
             logger,
@@ -338,6 +349,22 @@ where
     /// Used when the adapter is shutdown and no longer requires holding on to blocks.
     pub fn clear_blocks(&self) {
         *self.block_cache.write().unwrap() = HashMap::new();
+        *self.filter_cache.write().unwrap() = LinkedHashMap::new();
+    }
+
+    /// Adds the BIP-158 basic filter of a block to the `filter_cache`, evicting the oldest
+    /// filter if the cache is full.
+    pub fn add_block_filter(&self, block_hash: BlockHash, filter: SerializedFilter) {
+        let mut filter_cache = self.filter_cache.write().unwrap();
+        if filter_cache.len() >= FILTER_CACHE_CAPACITY && !filter_cache.contains_key(&block_hash) {
+            filter_cache.pop_front();
+        }
+        filter_cache.replace(block_hash, Arc::new(filter));
+    }
+
+    /// Returns the cached BIP-158 basic filter of the given block.
+    pub fn get_block_filter(&self, block_hash: &BlockHash) -> Option<Arc<SerializedFilter>> {
+        self.filter_cache.read().unwrap().get(block_hash).cloned()
     }
 
     pub(crate) fn is_block_cache_full(&self) -> bool {
@@ -744,6 +771,31 @@ mod test {
         run_with_cache_dir(Network::Bitcoin, test_pruning_blocks_from_the_cache).await
     }
 
+    /// Tests that the filter cache evicts the oldest filter when it is full.
+    #[test]
+    fn test_filter_cache_evicts_oldest_filter() {
+        run_in_memory(Network::Bitcoin, |state| {
+            let block_hash =
+                |i: usize| <BlockHash as bitcoin::hashes::Hash>::hash(&i.to_le_bytes());
+            for i in 0..=FILTER_CACHE_CAPACITY {
+                state.add_block_filter(block_hash(i), vec![i as u8]);
+            }
+
+            assert_eq!(state.get_block_filter(&block_hash(0)), None);
+            assert_eq!(
+                state.get_block_filter(&block_hash(1)).as_deref(),
+                Some(&vec![1])
+            );
+            assert_eq!(
+                state.filter_cache.read().unwrap().len(),
+                FILTER_CACHE_CAPACITY
+            );
+
+            state.clear_blocks();
+            assert_eq!(state.get_block_filter(&block_hash(1)), None);
+        })
+    }
+
     /// Tests the functionality of `BlockchainState::prune_blocks_below_height(...)` to ensure
     /// blocks are removed from the cache that are below a given height.
     async fn test_pruning_blocks_below_a_given_height_from_the_cache(
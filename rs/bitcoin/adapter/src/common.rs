@@ -340,11 +340,11 @@ pub trait HeaderValidator<Network: BlockchainNetwork> {
 #[cfg(test)]
 pub mod test_common {
     use std::{
-        collections::{HashSet, VecDeque},
+        collections::{HashMap, HashSet, VecDeque},
         net::SocketAddr,
     };
 
-    use bitcoin::{Block, consensus::deserialize};
+    use bitcoin::{Block, consensus::deserialize, p2p::ServiceFlags};
     use hex::FromHex;
 
     use crate::{Channel, ChannelError, Command};
@@ -363,6 +363,8 @@ pub mod test_common {
         available_connections: Vec<SocketAddr>,
         /// The addresses that disconnect was called on.
         disconnected_addresses: HashSet<SocketAddr>,
+        /// The services advertised by the available connections.
+        services: HashMap<SocketAddr, ServiceFlags>,
     }
 
     impl<Header, Block> TestChannel<Header, Block> {
@@ -371,6 +373,7 @@ pub mod test_common {
                 received_commands: VecDeque::new(),
                 available_connections,
                 disconnected_addresses: HashSet::new(),
+                services: HashMap::new(),
             }
         }
     }
@@ -394,6 +397,10 @@ pub mod test_common {
         pub fn add_address(&mut self, addr: SocketAddr) {
             self.available_connections.push(addr);
         }
+
+        pub fn set_services(&mut self, addr: SocketAddr, services: ServiceFlags) {
+            self.services.insert(addr, services);
+        }
     }
 
     impl<Header, Block> Channel<Header, Block> for TestChannel<Header, Block> {
@@ -410,6 +417,17 @@ pub mod test_common {
                 .collect()
         }
 
+        fn available_connections_with_services(&self, services: ServiceFlags) -> Vec<SocketAddr> {
+            self.available_connections()
+                .into_iter()
+                .filter(|addr| {
+                    self.services
+                        .get(addr)
+                        .is_some_and(|flags| flags.has(services))
+                })
+                .collect()
+        }
+
         fn discard(&mut self, addr: &SocketAddr) {
             self.disconnected_addresses.insert(*addr);
         }
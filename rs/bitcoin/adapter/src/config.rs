@@ -53,6 +53,27 @@ pub struct Config<Network> {
     pub cache_dir: Option<PathBuf>,
     /// Request timeout duration.
     pub request_timeout: Option<Duration>,
+    /// When set, the adapter does not connect to any node and instead produces
+    /// blocks of a local chain itself. Only supported on Bitcoin and Dogecoin regtest.
+    #[serde(default)]
+    pub local_chain: Option<LocalChainConfig>,
+}
+
+/// Configuration of the local chain produced by the adapter itself instead of
+/// being downloaded from the Bitcoin or Dogecoin network.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize)]
+pub struct LocalChainConfig {
+    /// If set, a new block is produced at every interval. Otherwise, blocks are
+    /// only produced on demand.
+    #[serde(default)]
+    pub block_interval: Option<Duration>,
+    /// The number of blocks produced on startup.
+    #[serde(default)]
+    pub initial_blocks: u32,
+    /// The address receiving the block rewards. If not set, the block rewards
+    /// are burned to an `OP_RETURN` output.
+    #[serde(default)]
+    pub coinbase_address: Option<String>,
 }
 
 /// Set the default idle seconds to one hour.
@@ -119,6 +140,7 @@ impl<Network> Config<Network> {
             address_limits: self.address_limits,
             cache_dir: self.cache_dir,
             request_timeout: self.request_timeout,
+            local_chain: self.local_chain,
         }
     }
 
@@ -143,6 +165,7 @@ impl<Network: Copy + Into<AdapterNetwork>> Config<Network> {
             address_limits: address_limits(network.into()),
             cache_dir: None,
             request_timeout: Some(request_timeout(network.into())),
+            local_chain: None,
         }
     }
 }
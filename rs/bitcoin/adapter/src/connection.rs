@@ -1,4 +1,5 @@
 use crate::addressbook::AddressEntry;
+use bitcoin::p2p::ServiceFlags;
 use std::time::{Duration, SystemTime};
 use thiserror::Error;
 use tokio::{sync::mpsc::UnboundedSender, task::JoinHandle};
@@ -93,6 +94,8 @@ pub struct Connection<NetworkMessage> {
     ping_state: PingState,
     /// Ping timeout
     ping_timeout: Duration,
+    /// The services advertised by the BTC node in its `version` message.
+    services: ServiceFlags,
 }
 
 impl<NetworkMessage> Connection<NetworkMessage> {
@@ -117,6 +120,7 @@ impl<NetworkMessage> Connection<NetworkMessage> {
                 last_pong_at: timestamp,
             },
             ping_timeout,
+            services: ServiceFlags::NONE,
         }
     }
 
@@ -127,6 +131,16 @@ impl<NetworkMessage> Connection<NetworkMessage> {
             .map_err(|_| ConnectionError::SendToStreamError)
     }
 
+    /// Records the services advertised by the BTC node.
+    pub fn set_services(&mut self, services: ServiceFlags) {
+        self.services = services;
+    }
+
+    /// Returns whether the BTC node advertised all the given services.
+    pub fn has_services(&self, services: ServiceFlags) -> bool {
+        self.services.has(services)
+    }
+
     /// This function retrieves the address field for public use.
     pub fn address_entry(&self) -> &AddressEntry {
         &self.address_entry
@@ -287,6 +301,7 @@ mod test {
                 writer,
                 ping_state: PingState::Idle { last_pong_at },
                 ping_timeout,
+                services: ServiceFlags::NONE,
             }
         }
     }
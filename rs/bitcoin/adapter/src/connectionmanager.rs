@@ -463,6 +463,9 @@ impl<Network: BlockchainNetwork> ConnectionManager<Network> {
             );
             return Err(ProcessNetworkMessageError::InvalidMessage);
         }
+        if let Ok(conn) = self.get_connection(address) {
+            conn.set_services(message.services);
+        }
         self.send_verack(address).ok();
 
         if !self.address_book.has_max_address() {
@@ -640,6 +643,17 @@ impl<Network: BlockchainNetwork> Channel<Network::Header, Network::Block>
             .collect()
     }
 
+    fn available_connections_with_services(&self, services: ServiceFlags) -> Vec<SocketAddr> {
+        self.available_connections()
+            .into_iter()
+            .filter(|addr| {
+                self.connections
+                    .get(addr)
+                    .is_some_and(|conn| conn.has_services(services))
+            })
+            .collect()
+    }
+
     fn discard(&mut self, addr: &SocketAddr) {
         self.internal_discard(addr);
     }
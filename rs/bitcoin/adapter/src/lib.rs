@@ -4,7 +4,7 @@
 //! and publish transactions. Moreover, it interacts with the Bitcoin system
 //! component to provide blocks and collect outgoing transactions.
 
-use bitcoin::p2p::message::NetworkMessage;
+use bitcoin::p2p::{ServiceFlags, message::NetworkMessage};
 use bitcoin::{BlockHash, block::Header as PureHeader};
 use ic_logger::ReplicaLogger;
 use ic_metrics::MetricsRegistry;
@@ -33,6 +33,9 @@ mod connection;
 /// BTC nodes.
 mod connectionmanager;
 mod header_cache;
+/// This module contains the block producer of the local regtest chain that replaces
+/// the Bitcoin network when the adapter is configured with a local chain.
+mod local_chain;
 mod metrics;
 /// The module is responsible for awaiting messages from bitcoin peers and dispaching them
 /// to the correct component.
@@ -53,11 +56,15 @@ pub use blockchainstate::BlockchainState;
 pub use common::{
     AdapterNetwork, BlockchainBlock, BlockchainHeader, BlockchainNetwork, HeaderValidator,
 };
-pub use config::{Config, IncomingSource, address_limits};
+pub use config::{Config, IncomingSource, LocalChainConfig, address_limits};
+pub use local_chain::{LocalChainError, LocalChainHandle};
 
 use crate::{
-    get_successors_handler::GetSuccessorsHandler, router::start_main_event_loop,
-    rpc_server::start_grpc_server, stream::StreamEvent,
+    get_successors_handler::GetSuccessorsHandler,
+    local_chain::{LocalChainNetwork, local_chain_channel, start_local_chain_event_loop},
+    router::start_main_event_loop,
+    rpc_server::start_grpc_server,
+    stream::StreamEvent,
 };
 
 /// This struct is used to represent commands given to the adapter in order to interact
@@ -94,6 +101,10 @@ trait Channel<Header, Block> {
     /// that have completed the version handshake.
     fn available_connections(&self) -> Vec<SocketAddr>;
 
+    /// This method is used to retrieve the available connections to nodes
+    /// that advertised all the given services.
+    fn available_connections_with_services(&self, services: ServiceFlags) -> Vec<SocketAddr>;
+
     /// Used to disconnect from nodes that are misbehaving.
     fn discard(&mut self, addr: &SocketAddr);
 }
@@ -126,6 +137,8 @@ pub(crate) enum BlockchainManagerRequest {
     EnqueueNewBlocksToDownload(Vec<PureHeader>),
     /// Inform the adapter to prune the following block hashes from the cache.
     PruneBlocks(BlockHash, Vec<BlockHash>),
+    /// Inform the adapter to enqueue the filters of the following blocks into the syncing queue.
+    EnqueueBlockFiltersToDownload(Vec<BlockHash>),
 }
 
 /// The transaction manager is owned by a single thread which listens on a channel
@@ -245,12 +258,110 @@ async fn start_server_helper<Network>(
     }
 }
 
+fn start_local_chain_server_helper(
+    log: ReplicaLogger,
+    metrics_registry: &MetricsRegistry,
+    config: config::Config<AdapterNetwork>,
+) -> Result<(LocalChainHandle, [tokio::task::JoinHandle<()>; 2]), LocalChainError> {
+    let local_chain_config = config.local_chain.clone().unwrap_or_default();
+    let coinbase_script = local_chain::coinbase_script(config.network, &local_chain_config)?;
+    Ok(match config.network {
+        AdapterNetwork::Bitcoin(network) => start_local_chain(
+            log,
+            metrics_registry,
+            config.with_network(network),
+            &local_chain_config,
+            coinbase_script,
+        ),
+        AdapterNetwork::Dogecoin(network) => start_local_chain(
+            log,
+            metrics_registry,
+            config.with_network(network),
+            &local_chain_config,
+            coinbase_script,
+        ),
+    })
+}
+
+fn start_local_chain<Network>(
+    log: ReplicaLogger,
+    metrics_registry: &MetricsRegistry,
+    config: config::Config<Network>,
+    local_chain_config: &LocalChainConfig,
+    coinbase_script: bitcoin::ScriptBuf,
+) -> (LocalChainHandle, [tokio::task::JoinHandle<()>; 2])
+where
+    Network: LocalChainNetwork + Sync + Send + 'static,
+    Network::Header: Send,
+    Network::Block: Send + Sync,
+    BlockchainState<Network>: HeaderValidator<Network>,
+    <BlockchainState<Network> as HeaderValidator<Network>>::HeaderError: Send + Sync,
+{
+    // The local chain never becomes idle, so nobody listens to the received requests.
+    let (_adapter_state, tx) = AdapterState::new(config.idle_seconds);
+    let (blockchain_manager_tx, blockchain_manager_rx) = channel(100);
+    let blockchain_state = Arc::new(BlockchainState::new(
+        config.network,
+        config.cache_dir.clone(),
+        metrics_registry,
+        log.clone(),
+    ));
+
+    let (transaction_manager_tx, transaction_manager_rx) = channel(100);
+    let (handle, local_chain_rx) = local_chain_channel();
+    let handles = [
+        start_grpc_server(
+            config.network,
+            config.incoming_source.clone(),
+            log.clone(),
+            tx,
+            blockchain_state.clone(),
+            blockchain_manager_tx,
+            transaction_manager_tx,
+            metrics_registry,
+        ),
+        start_local_chain_event_loop(
+            local_chain_config,
+            coinbase_script,
+            log,
+            blockchain_state,
+            transaction_manager_rx,
+            blockchain_manager_rx,
+            local_chain_rx,
+            metrics_registry,
+        ),
+    ];
+    (handle, handles)
+}
+
+/// Starts the gRPC server backed by a local Bitcoin or Dogecoin regtest chain produced
+/// by the adapter itself, and returns a handle to script the chain together with the
+/// tasks running the server. The server runs in the background, so the function must be
+/// called from within a Tokio runtime.
+pub fn start_local_chain_server(
+    log: ReplicaLogger,
+    metrics_registry: MetricsRegistry,
+    config: config::Config<AdapterNetwork>,
+) -> Result<(LocalChainHandle, [tokio::task::JoinHandle<()>; 2]), LocalChainError> {
+    start_local_chain_server_helper(log, &metrics_registry, config)
+}
+
 /// Starts the gRPC server and the router for handling incoming requests.
+/// If the config contains a local chain, the router is replaced by the local chain.
 pub async fn start_server(
     log: ReplicaLogger,
     metrics_registry: MetricsRegistry,
     config: config::Config<AdapterNetwork>,
 ) {
+    if config.local_chain.is_some() {
+        let (_handle, handles) = start_local_chain_server_helper(log, &metrics_registry, config)
+            .unwrap_or_else(|err| panic!("Failed to start the local chain: {err}"));
+        for handle in handles {
+            let _ = handle.await; // Waits for each task to complete
+        }
+        return;
+    }
+
     match config.network {
         AdapterNetwork::Bitcoin(network) => {
             let btc_config = config.with_network(network);
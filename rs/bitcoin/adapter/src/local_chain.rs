@@ -0,0 +1,877 @@
+//! The module produces a local Bitcoin or Dogecoin regtest chain inside the adapter, so
+//! that the Bitcoin and Dogecoin canisters can be exercised end to end without running a
+//! `bitcoind` or `dogecoind` node.
+//!
+//! Transactions sent by the system component are kept in the [TransactionStore] and
+//! included in the next produced block. They are not validated, so tests are expected
+//! to only submit transactions spending existing outputs.
+use crate::{
+    AdapterNetwork, BlockchainManagerRequest, BlockchainState, TransactionManagerRequest,
+    common::{BlockHeight, BlockchainHeader, BlockchainNetwork, HeaderValidator},
+    config::LocalChainConfig,
+    transaction_store::TransactionStore,
+};
+use bitcoin::{
+    Address, Amount, Block, BlockHash, OutPoint, ScriptBuf, Sequence, Transaction, TxIn,
+    TxMerkleNode, TxOut, Txid, Witness, absolute::LockTime, block::Header, block::Version,
+    consensus::serialize, hashes::Hash, opcodes::all::OP_RETURN, script::Builder, transaction,
+};
+use ic_logger::{ReplicaLogger, info};
+use ic_metrics::MetricsRegistry;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::sync::{
+    mpsc::{Receiver, Sender, channel},
+    oneshot,
+};
+use tokio::time::interval;
+
+/// The block subsidy is halved every 150 blocks on regtest.
+const SUBSIDY_HALVING_INTERVAL: BlockHeight = 150;
+
+/// The block subsidy before the first halving.
+const INITIAL_SUBSIDY: Amount = Amount::from_sat(50 * 100_000_000);
+
+/// The block subsidy before the first halving on Dogecoin regtest.
+const DOGE_INITIAL_SUBSIDY: Amount = Amount::from_sat(500_000 * 100_000_000);
+
+/// The constant block subsidy on Dogecoin regtest after the sixth halving interval.
+const DOGE_CONSTANT_SUBSIDY: Amount = Amount::from_sat(10_000 * 100_000_000);
+
+/// The AuxPoW chain ID of Dogecoin, which non-legacy blocks must have in their version.
+const DOGE_AUXPOW_CHAIN_ID: i32 = 98;
+
+/// The maximum number of pending requests to the local chain.
+const LOCAL_CHAIN_CHANNEL_SIZE: usize = 100;
+
+/// Errors returned when starting or scripting the local chain.
+#[derive(Clone, Eq, PartialEq, Debug, Error)]
+pub enum LocalChainError {
+    /// The local chain is only supported on Bitcoin and Dogecoin regtest.
+    #[error("the local chain is not supported on {0}")]
+    UnsupportedNetwork(AdapterNetwork),
+    /// The configured coinbase address could not be parsed as a regtest address.
+    #[error("invalid coinbase address {address}: {reason}")]
+    InvalidCoinbaseAddress {
+        /// The configured address.
+        address: String,
+        /// Why the address was rejected.
+        reason: String,
+    },
+    /// A reorg must fork off a block of the active chain.
+    #[error("cannot fork {depth} blocks below the tip at height {height}")]
+    ReorgTooDeep {
+        /// The requested reorg depth.
+        depth: BlockHeight,
+        /// The height of the active chain tip.
+        height: BlockHeight,
+    },
+    /// A reorg must produce more blocks than it replaces to become the active chain.
+    #[error("a fork of {count} blocks does not replace {depth} blocks")]
+    ReorgTooShort {
+        /// The requested reorg depth.
+        depth: BlockHeight,
+        /// The requested number of blocks on the fork.
+        count: BlockHeight,
+    },
+    /// A produced block was rejected by the blockchain state.
+    #[error("failed to add block {0}: {1}")]
+    AddBlock(BlockHash, String),
+    /// The local chain task is not running anymore.
+    #[error("the local chain is stopped")]
+    Stopped,
+}
+
+/// Requests sent to the local chain task by a [LocalChainHandle].
+#[derive(Debug)]
+pub(crate) enum LocalChainRequest {
+    /// Produce blocks on top of the active chain tip.
+    MineBlocks {
+        count: BlockHeight,
+        reply: oneshot::Sender<Result<Vec<BlockHash>, LocalChainError>>,
+    },
+    /// Produce blocks on top of the active chain tip paying the block rewards to `address`.
+    MineBlocksToAddress {
+        count: BlockHeight,
+        address: String,
+        reply: oneshot::Sender<Result<Vec<BlockHash>, LocalChainError>>,
+    },
+    /// Produce a fork starting `depth` blocks below the active chain tip.
+    Reorg {
+        depth: BlockHeight,
+        count: BlockHeight,
+        reply: oneshot::Sender<Result<Vec<BlockHash>, LocalChainError>>,
+    },
+    /// Return the transactions waiting to be included in a block.
+    GetMempool { reply: oneshot::Sender<Vec<Txid>> },
+    /// Return the active chain tip.
+    GetTip {
+        reply: oneshot::Sender<(BlockHeight, BlockHash)>,
+    },
+}
+
+/// A handle used to script the local chain, e.g., from PocketIC or tests.
+#[derive(Clone, Debug)]
+pub struct LocalChainHandle {
+    sender: Sender<LocalChainRequest>,
+}
+
+impl LocalChainHandle {
+    /// Produces `count` blocks on top of the active chain tip and returns their hashes.
+    /// The first block includes all pending transactions.
+    pub async fn mine_blocks(&self, count: BlockHeight) -> Result<Vec<BlockHash>, LocalChainError> {
+        self.request(|reply| LocalChainRequest::MineBlocks { count, reply })
+            .await?
+    }
+
+    /// Produces `count` blocks on top of the active chain tip paying the block rewards to
+    /// the given regtest address instead of the configured coinbase address, and returns
+    /// their hashes. The first block includes all pending transactions.
+    pub async fn mine_blocks_to_address(
+        &self,
+        count: BlockHeight,
+        address: String,
+    ) -> Result<Vec<BlockHash>, LocalChainError> {
+        self.request(|reply| LocalChainRequest::MineBlocksToAddress {
+            count,
+            address,
+            reply,
+        })
+        .await?
+    }
+
+    /// Replaces the last `depth` blocks of the active chain with a fork of `count` blocks
+    /// and returns the hashes of the fork. The transactions of the replaced blocks are
+    /// returned to the mempool, so the first block of the fork includes them together
+    /// with all pending transactions.
+    pub async fn reorg(
+        &self,
+        depth: BlockHeight,
+        count: BlockHeight,
+    ) -> Result<Vec<BlockHash>, LocalChainError> {
+        self.request(|reply| LocalChainRequest::Reorg {
+            depth,
+            count,
+            reply,
+        })
+        .await?
+    }
+
+    /// Returns the IDs of the transactions waiting to be included in a block.
+    pub async fn mempool(&self) -> Result<Vec<Txid>, LocalChainError> {
+        self.request(|reply| LocalChainRequest::GetMempool { reply })
+            .await
+    }
+
+    /// Returns the height and hash of the active chain tip.
+    pub async fn tip(&self) -> Result<(BlockHeight, BlockHash), LocalChainError> {
+        self.request(|reply| LocalChainRequest::GetTip { reply })
+            .await
+    }
+
+    async fn request<T>(
+        &self,
+        request: impl FnOnce(oneshot::Sender<T>) -> LocalChainRequest,
+    ) -> Result<T, LocalChainError> {
+        let (reply, response) = oneshot::channel();
+        self.sender
+            .send(request(reply))
+            .await
+            .map_err(|_| LocalChainError::Stopped)?;
+        response.await.map_err(|_| LocalChainError::Stopped)
+    }
+}
+
+/// Differences between the Bitcoin and Dogecoin regtest chains produced by the adapter.
+pub(crate) trait LocalChainNetwork: BlockchainNetwork {
+    /// Returns the script paying to the given regtest address.
+    fn address_script(address: &str) -> Result<ScriptBuf, String>;
+
+    /// Returns the block subsidy at the given height.
+    fn block_subsidy(height: BlockHeight) -> Amount;
+
+    /// Returns the version of the produced blocks.
+    fn block_version() -> Version;
+
+    /// Returns whether the proof of work of the header meets its target.
+    fn meets_target(header: &Header) -> bool;
+
+    /// Converts a produced block into a block of this network.
+    fn into_block(block: Block) -> Self::Block;
+}
+
+impl LocalChainNetwork for bitcoin::Network {
+    fn address_script(address: &str) -> Result<ScriptBuf, String> {
+        let address = Address::from_str(address)
+            .map_err(|err| err.to_string())?
+            .require_network(bitcoin::Network::Regtest)
+            .map_err(|err| err.to_string())?;
+        Ok(address.script_pubkey())
+    }
+
+    fn block_subsidy(height: BlockHeight) -> Amount {
+        let halvings = height / SUBSIDY_HALVING_INTERVAL;
+        if halvings >= 64 {
+            return Amount::ZERO;
+        }
+        Amount::from_sat(INITIAL_SUBSIDY.to_sat() >> halvings)
+    }
+
+    fn block_version() -> Version {
+        Version::TWO
+    }
+
+    fn meets_target(header: &Header) -> bool {
+        header.validate_pow(header.target()).is_ok()
+    }
+
+    fn into_block(block: Block) -> Block {
+        block
+    }
+}
+
+impl LocalChainNetwork for bitcoin::dogecoin::Network {
+    fn address_script(address: &str) -> Result<ScriptBuf, String> {
+        let address = bitcoin::dogecoin::Address::from_str(address)
+            .map_err(|err| err.to_string())?
+            .require_network(bitcoin::dogecoin::Network::Regtest)
+            .map_err(|err| err.to_string())?;
+        Ok(address.script_pubkey())
+    }
+
+    // See `GetDogecoinBlockSubsidy` in Dogecoin Core, regtest uses the simplified rewards.
+    fn block_subsidy(height: BlockHeight) -> Amount {
+        let halvings = height / SUBSIDY_HALVING_INTERVAL;
+        if halvings >= 6 {
+            return DOGE_CONSTANT_SUBSIDY;
+        }
+        Amount::from_sat(DOGE_INITIAL_SUBSIDY.to_sat() >> halvings)
+    }
+
+    fn block_version() -> Version {
+        Version::from_consensus(4 | (DOGE_AUXPOW_CHAIN_ID << 16))
+    }
+
+    fn meets_target(header: &Header) -> bool {
+        header.validate_pow_with_scrypt(header.target()).is_ok()
+    }
+
+    // The produced blocks are mined directly, so they do not carry an AuxPoW.
+    fn into_block(block: Block) -> bitcoin::dogecoin::Block {
+        bitcoin::dogecoin::Block {
+            header: bitcoin::dogecoin::Header {
+                pure_header: block.header,
+                aux_pow: None,
+            },
+            txdata: block.txdata,
+        }
+    }
+}
+
+/// Returns the script receiving the block rewards of the local chain.
+pub(crate) fn coinbase_script(
+    network: AdapterNetwork,
+    config: &LocalChainConfig,
+) -> Result<ScriptBuf, LocalChainError> {
+    let address_script: fn(&str) -> Result<ScriptBuf, String> = match network {
+        AdapterNetwork::Bitcoin(bitcoin::Network::Regtest) => {
+            <bitcoin::Network as LocalChainNetwork>::address_script
+        }
+        AdapterNetwork::Dogecoin(bitcoin::dogecoin::Network::Regtest) => {
+            <bitcoin::dogecoin::Network as LocalChainNetwork>::address_script
+        }
+        _ => return Err(LocalChainError::UnsupportedNetwork(network)),
+    };
+    let Some(address) = &config.coinbase_address else {
+        return Ok(Builder::new().push_opcode(OP_RETURN).into_script());
+    };
+    address_script(address).map_err(|reason| LocalChainError::InvalidCoinbaseAddress {
+        address: address.clone(),
+        reason,
+    })
+}
+
+/// Creates a handle and the receiving end of its requests.
+pub(crate) fn local_chain_channel() -> (LocalChainHandle, Receiver<LocalChainRequest>) {
+    let (sender, receiver) = channel(LOCAL_CHAIN_CHANNEL_SIZE);
+    (LocalChainHandle { sender }, receiver)
+}
+
+/// Produces the blocks of the local chain.
+struct LocalChain<Network: BlockchainNetwork> {
+    blockchain_state: Arc<BlockchainState<Network>>,
+    transaction_store: TransactionStore,
+    /// The transactions of the produced blocks, except for the coinbase transactions,
+    /// keyed by block hash. They are kept separately from the block cache, which the
+    /// Bitcoin canister prunes once it processed the blocks, so that a reorg can return
+    /// them to the mempool.
+    block_transactions: HashMap<BlockHash, Vec<Transaction>>,
+    coinbase_script: ScriptBuf,
+    logger: ReplicaLogger,
+}
+
+impl<Network> LocalChain<Network>
+where
+    Network: LocalChainNetwork,
+    BlockchainState<Network>: HeaderValidator<Network>,
+{
+    /// Produces `count` blocks on top of the active chain tip.
+    async fn mine_blocks(&mut self, count: BlockHeight) -> Result<Vec<BlockHash>, LocalChainError> {
+        let tip = self.blockchain_state.get_active_chain_tip();
+        let coinbase_script = self.coinbase_script.clone();
+        self.mine_on(
+            tip.header.into_pure_header(),
+            tip.height,
+            count,
+            &coinbase_script,
+        )
+        .await
+    }
+
+    /// Produces `count` blocks on top of the active chain tip paying the block rewards to
+    /// the given address.
+    async fn mine_blocks_to_address(
+        &mut self,
+        count: BlockHeight,
+        address: &str,
+    ) -> Result<Vec<BlockHash>, LocalChainError> {
+        let coinbase_script = Network::address_script(address).map_err(|reason| {
+            LocalChainError::InvalidCoinbaseAddress {
+                address: address.to_string(),
+                reason,
+            }
+        })?;
+        let tip = self.blockchain_state.get_active_chain_tip();
+        self.mine_on(
+            tip.header.into_pure_header(),
+            tip.height,
+            count,
+            &coinbase_script,
+        )
+        .await
+    }
+
+    /// Produces a fork of `count` blocks starting `depth` blocks below the active chain tip.
+    async fn reorg(
+        &mut self,
+        depth: BlockHeight,
+        count: BlockHeight,
+    ) -> Result<Vec<BlockHash>, LocalChainError> {
+        if count <= depth {
+            return Err(LocalChainError::ReorgTooShort { depth, count });
+        }
+        let tip = self.blockchain_state.get_active_chain_tip();
+        if depth > tip.height {
+            return Err(LocalChainError::ReorgTooDeep {
+                depth,
+                height: tip.height,
+            });
+        }
+        let mut fork_point = tip.header.into_pure_header();
+        let mut replaced_blocks = vec![];
+        for _ in 0..depth {
+            replaced_blocks.push(fork_point.block_hash());
+            fork_point = self
+                .blockchain_state
+                .get_cached_header(&fork_point.prev_blockhash)
+                .map(|node| node.data.header.into_pure_header())
+                .ok_or(LocalChainError::ReorgTooDeep {
+                    depth,
+                    height: tip.height,
+                })?;
+        }
+        info!(
+            self.logger,
+            "Replacing {} blocks of the local chain with {} blocks", depth, count
+        );
+        self.return_to_mempool(replaced_blocks.iter().rev());
+        let coinbase_script = self.coinbase_script.clone();
+        self.mine_on(fork_point, tip.height - depth, count, &coinbase_script)
+            .await
+    }
+
+    /// Puts the transactions of the given blocks back into the mempool, ahead of the
+    /// pending transactions, since the pending transactions may spend their outputs.
+    fn return_to_mempool<'a>(&mut self, block_hashes: impl Iterator<Item = &'a BlockHash>) {
+        let pending = self.transaction_store.drain_transactions();
+        for block_hash in block_hashes {
+            // The coinbase transaction is only valid in the replaced block.
+            for transaction in self
+                .block_transactions
+                .remove(block_hash)
+                .unwrap_or_default()
+            {
+                self.transaction_store
+                    .enqueue_transaction(&serialize(&transaction));
+            }
+        }
+        for transaction in pending {
+            self.transaction_store
+                .enqueue_transaction(&serialize(&transaction));
+        }
+    }
+
+    async fn mine_on(
+        &mut self,
+        mut prev_header: Header,
+        mut prev_height: BlockHeight,
+        count: BlockHeight,
+        coinbase_script: &ScriptBuf,
+    ) -> Result<Vec<BlockHash>, LocalChainError> {
+        let mut block_hashes = vec![];
+        for _ in 0..count {
+            let transactions = select_transactions(self.transaction_store.drain_transactions());
+            let block = make_block::<Network>(
+                &prev_header,
+                prev_height + 1,
+                coinbase_script,
+                transactions.clone(),
+            );
+            let block_hash = block.block_hash();
+            prev_header = block.header;
+            prev_height += 1;
+            self.blockchain_state
+                .add_block(Network::into_block(block))
+                .await
+                .map_err(|err| LocalChainError::AddBlock(block_hash, err.to_string()))?;
+            if !transactions.is_empty() {
+                self.block_transactions.insert(block_hash, transactions);
+            }
+            block_hashes.push(block_hash);
+        }
+        Ok(block_hashes)
+    }
+}
+
+/// Drops transactions that are replaced by a later transaction spending one of their
+/// inputs, mimicking replace-by-fee. The order of the remaining transactions is kept.
+fn select_transactions(transactions: Vec<Transaction>) -> Vec<Transaction> {
+    let mut spent: HashSet<OutPoint> = HashSet::new();
+    let mut selected: Vec<_> = transactions
+        .into_iter()
+        .rev()
+        .filter(|tx| {
+            let outpoints: Vec<_> = tx.input.iter().map(|input| input.previous_output).collect();
+            if outpoints.iter().any(|outpoint| spent.contains(outpoint)) {
+                return false;
+            }
+            spent.extend(outpoints);
+            true
+        })
+        .collect();
+    selected.reverse();
+    selected
+}
+
+/// Builds a block at the given height paying the block subsidy to `coinbase_script`
+/// and solves its proof of work.
+fn make_block<Network: LocalChainNetwork>(
+    prev_header: &Header,
+    height: BlockHeight,
+    coinbase_script: &ScriptBuf,
+    transactions: Vec<Transaction>,
+) -> Block {
+    let coinbase = Transaction {
+        version: transaction::Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::null(),
+            // BIP-34 requires the height as the first item of the coinbase script.
+            script_sig: Builder::new()
+                .push_int(height as i64)
+                .push_int(0)
+                .into_script(),
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Network::block_subsidy(height),
+            script_pubkey: coinbase_script.clone(),
+        }],
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("the system time is before the unix epoch")
+        .as_secs() as u32;
+    let mut block = Block {
+        header: Header {
+            version: Network::block_version(),
+            prev_blockhash: prev_header.block_hash(),
+            merkle_root: TxMerkleNode::all_zeros(),
+            // The timestamp must exceed the median time of the previous blocks.
+            time: now.max(prev_header.time + 1),
+            bits: prev_header.bits,
+            nonce: 0,
+        },
+        txdata: std::iter::once(coinbase).chain(transactions).collect(),
+    };
+    block.header.merkle_root = block
+        .compute_merkle_root()
+        .expect("a block with a coinbase has a merkle root");
+    while !Network::meets_target(&block.header) {
+        block.header.nonce += 1;
+    }
+    block
+}
+
+/// The function starts a Tokio task producing the blocks of the local chain. The task
+/// replaces the main event loop of the adapter: it collects the transactions sent by
+/// the system component and serves the requests of the [LocalChainHandle].
+pub(crate) fn start_local_chain_event_loop<Network>(
+    config: &LocalChainConfig,
+    coinbase_script: ScriptBuf,
+    logger: ReplicaLogger,
+    blockchain_state: Arc<BlockchainState<Network>>,
+    mut transaction_manager_rx: Receiver<TransactionManagerRequest>,
+    mut blockchain_manager_rx: Receiver<BlockchainManagerRequest>,
+    mut local_chain_rx: Receiver<LocalChainRequest>,
+    metrics_registry: &MetricsRegistry,
+) -> tokio::task::JoinHandle<()>
+where
+    Network: LocalChainNetwork + Sync + Send + 'static,
+    Network::Header: Send,
+    Network::Block: Send + Sync,
+    BlockchainState<Network>: HeaderValidator<Network>,
+    <BlockchainState<Network> as HeaderValidator<Network>>::HeaderError: Send + Sync,
+{
+    let mut local_chain = LocalChain {
+        blockchain_state: blockchain_state.clone(),
+        transaction_store: TransactionStore::new(logger.clone(), metrics_registry),
+        block_transactions: HashMap::new(),
+        coinbase_script,
+        logger,
+    };
+    let initial_blocks = config.initial_blocks;
+    let mut block_interval = config.block_interval.map(interval);
+
+    tokio::task::spawn(async move {
+        if let Err(err) = local_chain.mine_blocks(initial_blocks).await {
+            panic!("Failed to produce the initial blocks of the local chain: {err}");
+        }
+
+        loop {
+            let next_block = async {
+                match block_interval.as_mut() {
+                    Some(block_interval) => {
+                        block_interval.tick().await;
+                    }
+                    None => std::future::pending().await,
+                }
+            };
+
+            tokio::select! {
+                Some(request) = local_chain_rx.recv() => {
+                    match request {
+                        LocalChainRequest::MineBlocks { count, reply } => {
+                            let _ = reply.send(local_chain.mine_blocks(count).await);
+                        }
+                        LocalChainRequest::MineBlocksToAddress { count, address, reply } => {
+                            let _ = reply.send(local_chain.mine_blocks_to_address(count, &address).await);
+                        }
+                        LocalChainRequest::Reorg { depth, count, reply } => {
+                            let _ = reply.send(local_chain.reorg(depth, count).await);
+                        }
+                        LocalChainRequest::GetMempool { reply } => {
+                            let _ = reply.send(local_chain.transaction_store.txids());
+                        }
+                        LocalChainRequest::GetTip { reply } => {
+                            let tip = blockchain_state.get_active_chain_tip();
+                            let _ = reply.send((tip.height, tip.header.block_hash()));
+                        }
+                    }
+                },
+                transaction_manager_request = transaction_manager_rx.recv() => {
+                    match transaction_manager_request.unwrap() {
+                        TransactionManagerRequest::SendTransaction(transaction) => {
+                            local_chain.transaction_store.enqueue_transaction(&transaction)
+                        }
+                    }
+                },
+                result = blockchain_manager_rx.recv() => {
+                    let command = result.expect("Receiving should not fail because the sender part of the channel is never closed.");
+                    match command {
+                        // All blocks of the local chain are already in the block cache.
+                        BlockchainManagerRequest::EnqueueNewBlocksToDownload(_) => {}
+                        BlockchainManagerRequest::PruneBlocks(_anchor, processed_block_hashes) => {
+                            blockchain_state.prune_blocks(&processed_block_hashes);
+                        }
+                        // The local chain does not serve block filters.
+                        BlockchainManagerRequest::EnqueueBlockFiltersToDownload(_) => {}
+                    };
+                },
+                _ = next_block => {
+                    if let Err(err) = local_chain.mine_blocks(1).await {
+                        panic!("Failed to produce a block of the local chain: {err}");
+                    }
+                }
+            };
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoin::consensus::deserialize;
+    use ic_logger::no_op_logger;
+
+    fn local_chain_on<Network: LocalChainNetwork>(network: Network) -> LocalChain<Network>
+    where
+        AdapterNetwork: From<Network>,
+    {
+        let metrics_registry = MetricsRegistry::new();
+        LocalChain {
+            blockchain_state: Arc::new(BlockchainState::new(
+                network,
+                None,
+                &metrics_registry,
+                no_op_logger(),
+            )),
+            transaction_store: TransactionStore::new(no_op_logger(), &metrics_registry),
+            block_transactions: HashMap::new(),
+            coinbase_script: coinbase_script(network.into(), &LocalChainConfig::default()).unwrap(),
+            logger: no_op_logger(),
+        }
+    }
+
+    fn local_chain() -> LocalChain<bitcoin::Network> {
+        local_chain_on(bitcoin::Network::Regtest)
+    }
+
+    fn transaction(previous_output: OutPoint, value: u64) -> Transaction {
+        Transaction {
+            version: transaction::Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(value),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        }
+    }
+
+    fn block(chain: &LocalChain<bitcoin::Network>, block_hash: &BlockHash) -> Block {
+        let serialized = chain.blockchain_state.get_block(block_hash).unwrap();
+        deserialize(&serialized).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_mine_blocks_extends_the_active_chain() {
+        let mut chain = local_chain();
+        let genesis_hash = chain.blockchain_state.genesis().block_hash();
+
+        let block_hashes = chain.mine_blocks(3).await.unwrap();
+
+        let tip = chain.blockchain_state.get_active_chain_tip();
+        assert_eq!(tip.height, 3);
+        assert_eq!(tip.header.block_hash(), block_hashes[2]);
+        let first = block(&chain, &block_hashes[0]);
+        assert_eq!(first.header.prev_blockhash, genesis_hash);
+        assert_eq!(first.txdata[0].output[0].value, INITIAL_SUBSIDY);
+    }
+
+    #[tokio::test]
+    async fn test_mined_block_includes_pending_transactions() {
+        let mut chain = local_chain();
+        let outpoint = OutPoint::new(Txid::all_zeros(), 0);
+        let replaced = transaction(outpoint, 1_000);
+        let replacement = transaction(outpoint, 900);
+        let other = transaction(OutPoint::new(Txid::all_zeros(), 1), 500);
+        for tx in [&replaced, &other, &replacement] {
+            chain.transaction_store.enqueue_transaction(&serialize(tx));
+        }
+
+        let block_hashes = chain.mine_blocks(2).await.unwrap();
+
+        let txids: Vec<_> = block(&chain, &block_hashes[0])
+            .txdata
+            .iter()
+            .skip(1)
+            .map(|tx| tx.compute_txid())
+            .collect();
+        assert_eq!(
+            txids,
+            vec![other.compute_txid(), replacement.compute_txid()]
+        );
+        assert_eq!(block(&chain, &block_hashes[1]).txdata.len(), 1);
+        assert!(chain.transaction_store.txids().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reorg_replaces_the_tip() {
+        let mut chain = local_chain();
+        let block_hashes = chain.mine_blocks(5).await.unwrap();
+
+        let fork = chain.reorg(2, 3).await.unwrap();
+
+        let tip = chain.blockchain_state.get_active_chain_tip();
+        assert_eq!(tip.height, 6);
+        assert_eq!(tip.header.block_hash(), fork[2]);
+        assert_eq!(
+            block(&chain, &fork[0]).header.prev_blockhash,
+            block_hashes[2]
+        );
+
+        assert_eq!(
+            chain.reorg(2, 2).await,
+            Err(LocalChainError::ReorgTooShort { depth: 2, count: 2 })
+        );
+        assert_eq!(
+            chain.reorg(7, 8).await,
+            Err(LocalChainError::ReorgTooDeep {
+                depth: 7,
+                height: 6
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reorg_returns_replaced_transactions_to_the_mempool() {
+        let mut chain = local_chain();
+        let replaced = transaction(OutPoint::new(Txid::all_zeros(), 0), 1_000);
+        chain
+            .transaction_store
+            .enqueue_transaction(&serialize(&replaced));
+        chain.mine_blocks(2).await.unwrap();
+        let pending = transaction(OutPoint::new(replaced.compute_txid(), 0), 900);
+        chain
+            .transaction_store
+            .enqueue_transaction(&serialize(&pending));
+
+        let fork = chain.reorg(2, 3).await.unwrap();
+
+        let txids: Vec<_> = block(&chain, &fork[0])
+            .txdata
+            .iter()
+            .skip(1)
+            .map(|tx| tx.compute_txid())
+            .collect();
+        assert_eq!(txids, vec![replaced.compute_txid(), pending.compute_txid()]);
+        assert!(chain.transaction_store.txids().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reorg_returns_transactions_of_pruned_blocks_to_the_mempool() {
+        let mut chain = local_chain();
+        let replaced = transaction(OutPoint::new(Txid::all_zeros(), 0), 1_000);
+        chain
+            .transaction_store
+            .enqueue_transaction(&serialize(&replaced));
+        let block_hashes = chain.mine_blocks(2).await.unwrap();
+        // The Bitcoin canister prunes the blocks it processed from the block cache.
+        chain.blockchain_state.prune_blocks(&block_hashes);
+        assert!(chain.blockchain_state.get_block(&block_hashes[0]).is_none());
+
+        let fork = chain.reorg(2, 3).await.unwrap();
+
+        let txids: Vec<_> = block(&chain, &fork[0])
+            .txdata
+            .iter()
+            .skip(1)
+            .map(|tx| tx.compute_txid())
+            .collect();
+        assert_eq!(txids, vec![replaced.compute_txid()]);
+        assert!(chain.transaction_store.txids().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mine_blocks_to_address() {
+        let mut chain = local_chain();
+        let address = "bcrt1qar0srrr7xfkvy5l643lydnw9re59gtzzxxk9p6";
+
+        let block_hashes = chain.mine_blocks_to_address(1, address).await.unwrap();
+
+        assert_eq!(
+            block(&chain, &block_hashes[0]).txdata[0].output[0].script_pubkey,
+            <bitcoin::Network as LocalChainNetwork>::address_script(address).unwrap()
+        );
+        assert!(matches!(
+            chain
+                .mine_blocks_to_address(1, "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq")
+                .await,
+            Err(LocalChainError::InvalidCoinbaseAddress { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mine_dogecoin_blocks() {
+        let mut chain = local_chain_on(bitcoin::dogecoin::Network::Regtest);
+        let genesis_hash = chain.blockchain_state.genesis().block_hash();
+
+        let block_hashes = chain.mine_blocks(3).await.unwrap();
+
+        let tip = chain.blockchain_state.get_active_chain_tip();
+        assert_eq!(tip.height, 3);
+        assert_eq!(tip.header.block_hash(), block_hashes[2]);
+        let serialized = chain.blockchain_state.get_block(&block_hashes[0]).unwrap();
+        let first: bitcoin::dogecoin::Block = deserialize(&serialized).unwrap();
+        assert_eq!(first.header.pure_header.prev_blockhash, genesis_hash);
+        assert!(first.header.aux_pow.is_none());
+        assert_eq!(first.txdata[0].output[0].value, DOGE_INITIAL_SUBSIDY);
+    }
+
+    #[test]
+    fn test_local_chain_requires_regtest() {
+        let config = LocalChainConfig::default();
+        for network in [
+            AdapterNetwork::Bitcoin(bitcoin::Network::Testnet),
+            AdapterNetwork::Dogecoin(bitcoin::dogecoin::Network::Testnet),
+        ] {
+            assert_eq!(
+                coinbase_script(network, &config),
+                Err(LocalChainError::UnsupportedNetwork(network))
+            );
+        }
+        assert!(
+            coinbase_script(
+                AdapterNetwork::Dogecoin(bitcoin::dogecoin::Network::Regtest),
+                &config
+            )
+            .is_ok()
+        );
+
+        let config = LocalChainConfig {
+            coinbase_address: Some("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            coinbase_script(AdapterNetwork::Bitcoin(bitcoin::Network::Regtest), &config),
+            Err(LocalChainError::InvalidCoinbaseAddress { .. })
+        ));
+    }
+
+    #[test]
+    fn test_block_subsidy_halves() {
+        let block_subsidy = <bitcoin::Network as LocalChainNetwork>::block_subsidy;
+        assert_eq!(block_subsidy(0), INITIAL_SUBSIDY);
+        assert_eq!(block_subsidy(149), INITIAL_SUBSIDY);
+        assert_eq!(
+            block_subsidy(150),
+            Amount::from_sat(INITIAL_SUBSIDY.to_sat() / 2)
+        );
+        assert_eq!(block_subsidy(64 * 150), Amount::ZERO);
+    }
+
+    #[test]
+    fn test_dogecoin_block_subsidy_becomes_constant() {
+        let block_subsidy = <bitcoin::dogecoin::Network as LocalChainNetwork>::block_subsidy;
+        assert_eq!(block_subsidy(0), DOGE_INITIAL_SUBSIDY);
+        assert_eq!(
+            block_subsidy(150),
+            Amount::from_sat(DOGE_INITIAL_SUBSIDY.to_sat() / 2)
+        );
+        assert_eq!(
+            block_subsidy(5 * 150),
+            Amount::from_sat(DOGE_INITIAL_SUBSIDY.to_sat() / 32)
+        );
+        assert_eq!(block_subsidy(6 * 150), DOGE_CONSTANT_SUBSIDY);
+    }
+}
@@ -111,6 +111,9 @@ where
                         BlockchainManagerRequest::PruneBlocks(anchor, processed_block_hashes) => {
                             blockchain_manager.prune_blocks(anchor, processed_block_hashes);
                         }
+                        BlockchainManagerRequest::EnqueueBlockFiltersToDownload(block_hashes) => {
+                            blockchain_manager.enqueue_block_filters_to_download(block_hashes);
+                        }
                     };
                 }
                 transaction_manager_request = transaction_manager_rx.recv() => {
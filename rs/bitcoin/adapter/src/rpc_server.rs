@@ -3,10 +3,13 @@ use crate::{
     blockchainstate::BlockchainState,
     common::{BlockchainHeader, BlockchainNetwork},
     get_successors_handler::{GetSuccessorsRequest, GetSuccessorsResponse},
-    metrics::{LABEL_GET_SUCCESSOR, LABEL_SEND_TRANSACTION, ServiceMetrics},
+    metrics::{
+        LABEL_GET_BLOCK_FILTERS, LABEL_GET_SUCCESSOR, LABEL_SEND_TRANSACTION, ServiceMetrics,
+    },
 };
-use bitcoin::{BlockHash, hashes::Hash};
+use bitcoin::{BlockHash, bip158::BlockFilter, hashes::Hash};
 use ic_btc_service::{
+    BtcServiceBlockFilter, BtcServiceGetBlockFiltersRequest, BtcServiceGetBlockFiltersResponse,
     BtcServiceGetSuccessorsRequest, BtcServiceGetSuccessorsResponse,
     BtcServiceSendTransactionRequest, BtcServiceSendTransactionResponse,
     btc_service_server::{BtcService, BtcServiceServer},
@@ -20,9 +23,14 @@ use std::time::Instant;
 use tokio::sync::{mpsc, watch};
 use tonic::{Request, Response, Status, transport::Server};
 
+/// The maximum number of block filters that can be requested at once.
+const MAX_BLOCK_FILTERS_PER_REQUEST: usize = 100;
+
 struct BtcServiceImpl<Network: BlockchainNetwork> {
     last_received_tx: watch::Sender<Option<Instant>>,
     get_successors_handler: GetSuccessorsHandler<Network>,
+    blockchain_state: Arc<BlockchainState<Network>>,
+    blockchain_manager_tx: mpsc::Sender<BlockchainManagerRequest>,
     transaction_manager_tx: mpsc::Sender<TransactionManagerRequest>,
     logger: ReplicaLogger,
     metrics: ServiceMetrics,
@@ -124,6 +132,61 @@ where
             );
         Ok(Response::new(BtcServiceSendTransactionResponse {}))
     }
+
+    async fn get_block_filters(
+        &self,
+        request: Request<BtcServiceGetBlockFiltersRequest>,
+    ) -> Result<Response<BtcServiceGetBlockFiltersResponse>, Status> {
+        let _timer = self
+            .metrics
+            .request_duration
+            .with_label_values(&[LABEL_GET_BLOCK_FILTERS])
+            .start_timer();
+        let _ = self.last_received_tx.send(Some(Instant::now()));
+        let inner = request.into_inner();
+        if inner.block_hashes.len() > MAX_BLOCK_FILTERS_PER_REQUEST {
+            return Err(Status::invalid_argument(format!(
+                "Cannot request more than {MAX_BLOCK_FILTERS_PER_REQUEST} block filters!"
+            )));
+        }
+        let block_hashes = inner
+            .block_hashes
+            .iter()
+            .map(|hash| {
+                BlockHash::from_slice(hash.as_slice())
+                    .map_err(|_| Status::unknown("Failed to read block_hashes!"))
+            })
+            .collect::<Result<Vec<_>, Status>>()?;
+
+        let mut filters = vec![];
+        let mut missing = vec![];
+        for block_hash in block_hashes {
+            match self.blockchain_state.get_block_filter(&block_hash) {
+                Some(filter) => {
+                    // A filter that cannot be decoded is reported as a match, so that callers
+                    // fall back to the full block instead of missing a transaction.
+                    let matches = !inner.scripts.is_empty()
+                        && BlockFilter::new(&filter)
+                            .match_any(&block_hash, inner.scripts.iter().map(|s| s.as_slice()))
+                            .unwrap_or(true);
+                    filters.push(BtcServiceBlockFilter {
+                        block_hash: block_hash.to_byte_array().to_vec(),
+                        filter: filter.to_vec(),
+                        matches,
+                    });
+                }
+                None => missing.push(block_hash),
+            }
+        }
+        if !missing.is_empty() {
+            // The filters are requested again later, so the request can be dropped if the
+            // channel is full.
+            let _ = self.blockchain_manager_tx.try_send(
+                BlockchainManagerRequest::EnqueueBlockFiltersToDownload(missing),
+            );
+        }
+        Ok(Response::new(BtcServiceGetBlockFiltersResponse { filters }))
+    }
 }
 
 /// Blocks until the server binds to the socket
@@ -145,14 +208,16 @@ where
         network,
         // The get successor handler should be low latency, and instead of not sharing state and
         // offloading the computation to an event loop here we directly access the shared state.
-        blockchain_state,
-        blockchain_manager_tx,
+        blockchain_state.clone(),
+        blockchain_manager_tx.clone(),
         metrics_registry,
     );
 
     let btc_adapter_impl = BtcServiceImpl {
         last_received_tx,
         get_successors_handler,
+        blockchain_state,
+        blockchain_manager_tx,
         transaction_manager_tx,
         logger,
         metrics: ServiceMetrics::new(metrics_registry),
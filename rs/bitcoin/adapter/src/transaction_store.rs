@@ -102,6 +102,25 @@ impl TransactionStore {
         }
     }
 
+    /// Removes all transactions from the store, oldest first.
+    /// Used by the local chain to include the transactions in the next produced block.
+    pub fn drain_transactions(&mut self) -> Vec<Transaction> {
+        let transactions: Vec<_> = std::mem::take(&mut self.transactions)
+            .into_iter()
+            .map(|(_, info)| info.transaction)
+            .collect();
+        self.metrics
+            .txn_ops
+            .with_label_values(&["remove", "mined"])
+            .inc_by(transactions.len() as u64);
+        transactions
+    }
+
+    /// Returns the IDs of the transactions held by the store, oldest first.
+    pub fn txids(&self) -> Vec<Txid> {
+        self.transactions.keys().copied().collect()
+    }
+
     /// Clear out transactions that have been held on to for more than the transaction's ttl period.
     fn remove_old_txns(&mut self) {
         let now = SystemTime::now();
@@ -325,6 +344,31 @@ mod test {
         assert!(manager.transactions.get(&first_tx.compute_txid()).is_none());
     }
 
+    /// This function tests that draining the store returns the transactions oldest first
+    /// and leaves the store empty.
+    #[test]
+    fn test_drain_transactions() {
+        let mut manager = make_transaction_manager();
+        let transactions: Vec<_> = (0..3)
+            .map(|i| {
+                let mut transaction = get_transaction();
+                transaction.lock_time = LockTime::from_height(i).unwrap();
+                manager.enqueue_transaction(&serialize(&transaction));
+                transaction
+            })
+            .collect();
+        assert_eq!(
+            manager.txids(),
+            transactions
+                .iter()
+                .map(|tx| tx.compute_txid())
+                .collect::<Vec<_>>()
+        );
+
+        assert_eq!(manager.drain_transactions(), transactions);
+        assert!(manager.txids().is_empty());
+    }
+
     /// This function tests that we don't readvertise transactions that were already advertised.
     /// Test Steps:
     /// 1. Add transaction to manager.
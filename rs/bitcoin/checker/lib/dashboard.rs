@@ -0,0 +1,139 @@
+//! The dashboard of a checker canister, rendered for the network it is configured with.
+use crate::fetch::FetchTxStatus;
+use crate::state::{CheckerNetwork, Config, FetchTxCacheStore, OUTCALL_CAPACITY, Timestamp};
+use askama::Template;
+use ic_btc_interface::Txid;
+use std::fmt;
+
+pub mod filters {
+    pub fn timestamp_to_datetime<T: std::fmt::Display>(timestamp: T) -> askama::Result<String> {
+        let input = timestamp.to_string();
+        let ts: i128 = input
+            .parse()
+            .map_err(|e| askama::Error::Custom(Box::new(e)))?;
+        let dt_offset = time::OffsetDateTime::from_unix_timestamp_nanos(ts).unwrap();
+        // 2020-12-09T17:25:40+00:00
+        let format =
+            time::format_description::parse("[year]-[month]-[day]T[hour]:[minute]:[second]+00:00")
+                .unwrap();
+        Ok(dt_offset.format(&format).unwrap())
+    }
+}
+
+#[derive(Template)]
+#[template(path = "dashboard.html", whitespace = "suppress")]
+pub struct DashboardTemplate<Network: CheckerNetwork> {
+    pub config: Config<Network>,
+    pub outcall_capacity: u32,
+    pub cached_entries: usize,
+    pub tx_table_page_size: usize,
+    pub tx_table_page_index: usize,
+    pub oldest_entry_time: Option<Timestamp>,
+    pub latest_entry_time: Option<Timestamp>,
+    pub fetch_tx_status: Vec<(Txid, Timestamp, Status<Network::Address>)>,
+}
+
+impl<Network: CheckerNetwork> DashboardTemplate<Network> {
+    fn currency(&self) -> &'static str {
+        Network::CURRENCY
+    }
+
+    fn explorer_tx_url(&self, txid: &Txid) -> Option<String> {
+        self.config.network().explorer_tx_url(txid)
+    }
+
+    fn is_blocked(&self, address: &Network::Address) -> bool {
+        Network::is_blocked(address)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Status<Address> {
+    PendingOutcall,
+    PendingRetry,
+    Error(String),
+    Fetched(Fetched<Address>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Fetched<Address> {
+    pub input_addresses: Vec<Option<Address>>,
+}
+
+impl<Address> fmt::Display for Status<Address> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Status::PendingOutcall => write!(f, "Pending outcall"),
+            Status::PendingRetry => write!(f, "Pending retry"),
+            Status::Error(_) => write!(f, "Error"),
+            Status::Fetched(_) => write!(f, "Fetched"),
+        }
+    }
+}
+
+impl<Address> Status<Address> {
+    pub fn fetched(&self) -> Option<&Fetched<Address>> {
+        match self {
+            Status::Fetched(fetched) => Some(fetched),
+            _ => None,
+        }
+    }
+    pub fn error(&self) -> Option<&String> {
+        match self {
+            Status::Error(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// Default number of transactions to display per page is 500.
+pub const DEFAULT_TX_TABLE_PAGE_SIZE: usize = 500;
+
+/// Renders the given page of the cached transactions of the checker canister
+/// whose cache is kept in `S`.
+pub fn dashboard<Network, S>(
+    config: Config<Network>,
+    page_index: usize,
+) -> DashboardTemplate<Network>
+where
+    Network: CheckerNetwork,
+    S: FetchTxCacheStore<Address = Network::Address>,
+{
+    let tx_table_page_size = DEFAULT_TX_TABLE_PAGE_SIZE;
+    S::with_fetch_tx_cache(|cache| DashboardTemplate {
+        config,
+        outcall_capacity: OUTCALL_CAPACITY.with(|capacity| *capacity.borrow()),
+        cached_entries: cache.iter().count(),
+        tx_table_page_size,
+        tx_table_page_index: page_index,
+        oldest_entry_time: cache.iter().next().map(|(_, timestamp, _)| timestamp),
+        latest_entry_time: cache.iter().next_back().map(|(_, timestamp, _)| timestamp),
+        fetch_tx_status: cache
+            .iter()
+            .rev()
+            .skip(page_index * tx_table_page_size)
+            .take(tx_table_page_size)
+            .map(|(txid, timestamp, status)| {
+                (
+                    txid,
+                    timestamp,
+                    match status {
+                        FetchTxStatus::PendingOutcall => Status::PendingOutcall,
+                        FetchTxStatus::PendingRetry { .. } => Status::PendingRetry,
+                        FetchTxStatus::Error(err) => Status::Error(format!("{:?}", err.error)),
+                        FetchTxStatus::Fetched(fetched) => {
+                            // Return an empty list if no input address is available yet.
+                            let input_addresses =
+                                if fetched.input_addresses.iter().all(|x| x.is_none()) {
+                                    Vec::new()
+                                } else {
+                                    fetched.input_addresses.clone()
+                                };
+                            Status::Fetched(Fetched { input_addresses })
+                        }
+                    },
+                )
+            })
+            .collect::<Vec<_>>(),
+    })
+}
@@ -0,0 +1,522 @@
+//! Fetching transactions with HTTPS outcalls and checking their input addresses.
+//!
+//! The logic is independent of the network: the checker canisters implement
+//! [`FetchEnv`] to provide the network specific parts, such as the providers to
+//! fetch transactions from, the address type, and the blocklist.
+use crate::outcall::STATS;
+use crate::{
+    CHECK_TRANSACTION_CYCLES_REQUIRED, CHECK_TRANSACTION_CYCLES_SERVICE_FEE, CheckMode,
+    CheckTransactionIrrecoverableError, CheckTransactionQueryResponse, CheckTransactionResponse,
+    CheckTransactionRetriable, CheckTransactionStatus, INITIAL_MAX_RESPONSE_BYTES,
+    RETRY_MAX_RESPONSE_BYTES, get_tx_cycle_cost,
+};
+use bitcoin::Transaction;
+use futures::future::try_join_all;
+use ic_btc_interface::Txid;
+use ic_canister_log::{GlobalBuffer, log};
+use std::convert::Infallible;
+use std::fmt;
+
+/// Error returned by calling `http_get_tx`.
+#[derive(Debug, Clone)]
+pub enum HttpGetTxError {
+    TxEncoding(String),
+    TxidMismatch { expected: Txid, decoded: Txid },
+    ResponseTooLarge,
+    Rejected { code: u32, message: String },
+    CallPerformFailed,
+}
+
+impl fmt::Display for HttpGetTxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use HttpGetTxError::*;
+        match self {
+            TxEncoding(s) => write!(f, "TxEncoding: {s}"),
+            TxidMismatch { expected, decoded } => {
+                write!(f, "TxidMismatch: expected {expected} but decoded {decoded}")
+            }
+            ResponseTooLarge => write!(f, "ResponseTooLarge"),
+            Rejected { code, message } => write!(f, "Rejected: code {code:?}, {message}"),
+            CallPerformFailed => write!(f, "CallPerformedFailed"),
+        }
+    }
+}
+
+impl HttpGetTxError {
+    pub fn into_response(self, txid: Txid) -> CheckTransactionResponse {
+        let txid = txid.as_ref().to_vec();
+        match self {
+            HttpGetTxError::Rejected { message, .. } => {
+                CheckTransactionRetriable::TransientInternalError(message).into()
+            }
+            HttpGetTxError::ResponseTooLarge => {
+                (CheckTransactionIrrecoverableError::ResponseTooLarge { txid }).into()
+            }
+            _ => CheckTransactionRetriable::TransientInternalError(self.to_string()).into(),
+        }
+    }
+}
+
+/// We store in state the `FetchStatus` for every `Txid` we fetch.
+/// It transitions from `PendingOutcall` to any one of the three
+/// possible outcomes: `PendingRetry`, `Error`, or `Fetched`.
+#[derive(Debug, Clone)]
+pub enum FetchTxStatus<Provider, Address> {
+    PendingOutcall,
+    PendingRetry { max_response_bytes: u32 },
+    Error(FetchTxStatusError<Provider>),
+    Fetched(FetchedTx<Address>),
+}
+
+#[derive(Debug, Clone)]
+pub struct FetchTxStatusError<Provider> {
+    pub provider: Provider,
+    pub max_response_bytes: u32,
+    pub error: HttpGetTxError,
+}
+
+/// Once the transaction data is successfully fetched, we create
+/// a list of `input_addresses` (matching the number of inputs)
+/// that is initialized as `None`. Once a corresponding input
+/// transaction is fetched (see function `check_fetched`), its
+/// input address will be computed and filled in.
+#[derive(Clone, Debug)]
+pub struct FetchedTx<Address> {
+    pub tx: TransactionCheckData<Address>,
+    pub input_addresses: Vec<Option<Address>>,
+}
+
+/// Instead of storing the full transaction data, we only
+/// store relevant bits, including inputs (which are previous
+/// outputs) and output addresses.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TransactionCheckData<Address> {
+    pub inputs: Vec<PreviousOutput>,
+    pub outputs: Vec<Option<Address>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PreviousOutput {
+    pub txid: Txid,
+    pub vout: u32,
+}
+
+impl<Address> TransactionCheckData<Address> {
+    /// Extracts the previous outputs and the output addresses of the given transaction,
+    /// computing the addresses with `address_from_script`.
+    pub fn from_transaction(
+        tx: &Transaction,
+        address_from_script: impl Fn(&bitcoin::Script) -> Option<Address>,
+    ) -> Self {
+        let inputs = tx
+            .input
+            .iter()
+            .map(|input| PreviousOutput {
+                txid: Txid::from(*(input.previous_output.txid.as_ref() as &[u8; 32])),
+                vout: input.previous_output.vout,
+            })
+            .collect();
+        // Some outputs do not have addresses. These outputs will never be
+        // inputs of other transactions, so it is okay to treat them as `None`.
+        let outputs = tx
+            .output
+            .iter()
+            .map(|output| address_from_script(&output.script_pubkey))
+            .collect();
+        Self { inputs, outputs }
+    }
+}
+
+#[derive(Debug)]
+pub enum FetchGuardError {
+    NoCapacity,
+}
+
+pub enum FetchResult<Address> {
+    RetryWithBiggerBuffer,
+    Error(HttpGetTxError),
+    Fetched(FetchedTx<Address>),
+}
+
+pub enum TryFetchResult<Address, F> {
+    Pending,
+    HighLoad,
+    NotEnoughCycles,
+    Fetched(FetchedTx<Address>),
+    ToFetch(F),
+}
+
+/// Trait that abstracts over system functions like fetching transaction, calcuating cycles, etc.,
+/// and over the network specific parts of a checker canister.
+#[allow(async_fn_in_trait)]
+pub trait FetchEnv {
+    type FetchGuard;
+    /// The provider that transactions are fetched from.
+    type Provider: Clone;
+    /// The address type of the network.
+    type Address: Clone + fmt::Display;
+
+    fn new_fetch_guard(&self, txid: Txid) -> Result<Self::FetchGuard, FetchGuardError>;
+    fn check_mode(&self) -> CheckMode;
+    fn num_subnet_nodes(&self) -> u16;
+
+    /// Returns the provider to fetch a transaction from that has not been fetched before.
+    fn next_provider(&self) -> Self::Provider;
+    /// Returns the provider to retry fetching a transaction after `provider` failed.
+    fn retry_provider(&self, provider: &Self::Provider) -> Self::Provider;
+
+    fn get_fetch_status(&self, txid: Txid)
+    -> Option<FetchTxStatus<Self::Provider, Self::Address>>;
+    fn set_fetch_status(&self, txid: Txid, status: FetchTxStatus<Self::Provider, Self::Address>);
+    /// Set the address at the given `index` in the `Fetched` status of the given `txid`.
+    fn set_fetched_address(&self, txid: Txid, index: usize, address: Self::Address);
+
+    /// Extracts the data needed to check a transaction fetched from `provider`.
+    fn transaction_check_data(
+        &self,
+        provider: &Self::Provider,
+        tx: &Transaction,
+    ) -> TransactionCheckData<Self::Address>;
+    fn is_blocked(&self, address: &Self::Address) -> bool;
+    /// The log buffer for high-priority messages.
+    fn warn_log(&self) -> &'static GlobalBuffer;
+
+    async fn http_get_tx(
+        &self,
+        provider: &Self::Provider,
+        txid: Txid,
+        max_response_bytes: u32,
+    ) -> Result<Transaction, HttpGetTxError>;
+    fn cycles_accept(&self, cycles: u128) -> u128;
+
+    /// Try to fetch a transaction given its txid:
+    /// - If it is already available, return `Fetched`.
+    /// - If it is already pending, return `Pending`.
+    /// - If it is pending retry or not found, return a future that calls `fetch_tx`.
+    /// - Or return other conditions like `HighLoad` or `Error`.
+    fn try_fetch_tx(
+        &self,
+        txid: Txid,
+    ) -> TryFetchResult<
+        Self::Address,
+        impl futures::Future<Output = Result<FetchResult<Self::Address>, Infallible>>,
+    > {
+        let (provider, max_response_bytes) = match self.get_fetch_status(txid) {
+            None => (self.next_provider(), INITIAL_MAX_RESPONSE_BYTES),
+            Some(FetchTxStatus::PendingRetry {
+                max_response_bytes, ..
+            }) => (self.next_provider(), max_response_bytes),
+            Some(FetchTxStatus::PendingOutcall) => return TryFetchResult::Pending,
+            Some(FetchTxStatus::Error(err)) => (
+                // An FetchTxStatus error can be retried with another provider
+                self.retry_provider(&err.provider),
+                // The next provider can use the same max_response_bytes
+                err.max_response_bytes,
+            ),
+            Some(FetchTxStatus::Fetched(fetched)) => return TryFetchResult::Fetched(fetched),
+        };
+        let guard = match self.new_fetch_guard(txid) {
+            Ok(guard) => guard,
+            Err(_) => return TryFetchResult::HighLoad,
+        };
+        let cycle_cost = get_tx_cycle_cost(max_response_bytes, self.num_subnet_nodes());
+        if self.cycles_accept(cycle_cost) < cycle_cost {
+            TryFetchResult::NotEnoughCycles
+        } else {
+            TryFetchResult::ToFetch(self.fetch_tx(guard, provider, txid, max_response_bytes))
+        }
+    }
+
+    /// Fetch a transaction using http outcall by its txid and set its status to:
+    /// - `Fetched`, if it is available.
+    /// - `PendingRetry`, if the allocated buffer for outcall wasn't enough.
+    /// - `Error`, if an irrecoverable error happened during the outcall of `http_get_tx`.
+    ///
+    /// Return the correponding `FetchResult`.
+    ///
+    /// Note that this function does not return any error, but due to requirements
+    /// of `try_join_all` it must return a `Result` type.
+    async fn fetch_tx(
+        &self,
+        _guard: Self::FetchGuard,
+        provider: Self::Provider,
+        txid: Txid,
+        max_response_bytes: u32,
+    ) -> Result<FetchResult<Self::Address>, Infallible> {
+        match self.http_get_tx(&provider, txid, max_response_bytes).await {
+            Ok(tx) => {
+                let fetched = FetchedTx {
+                    tx: self.transaction_check_data(&provider, &tx),
+                    input_addresses: tx.input.iter().map(|_| None).collect(),
+                };
+                self.set_fetch_status(txid, FetchTxStatus::Fetched(fetched.clone()));
+                Ok(FetchResult::Fetched(fetched))
+            }
+            Err(HttpGetTxError::ResponseTooLarge)
+                if max_response_bytes < RETRY_MAX_RESPONSE_BYTES =>
+            {
+                self.set_fetch_status(
+                    txid,
+                    FetchTxStatus::PendingRetry {
+                        max_response_bytes: RETRY_MAX_RESPONSE_BYTES,
+                    },
+                );
+                Ok(FetchResult::RetryWithBiggerBuffer)
+            }
+            Err(err) => {
+                self.set_fetch_status(
+                    txid,
+                    FetchTxStatus::Error(FetchTxStatusError {
+                        provider,
+                        max_response_bytes,
+                        error: err.clone(),
+                    }),
+                );
+                Ok(FetchResult::Error(err))
+            }
+        }
+    }
+
+    /// After a transaction is successfully fetched, we still need to fetch
+    /// all of its inputs in order to calculate input addresses. The steps
+    /// are described as follows:
+    /// - Fetch more if there are transaction inputs to be fetched and checked.
+    /// - When they are done, calculate input addresses and record them.
+    /// - For those failed due to insufficient outcall response buffer, mark their status
+    ///   as `PendingRetry`.
+    /// - If we are short of cycles and couldn't fetch all inputs, return `NotEnoughCycles`.
+    /// - When all inputs are fetched, compute their addresses and return `Passed`
+    ///   if all of them pass the check. Otherwise return `Failed`.
+    ///
+    /// Pre-condition: `txid` already exists in state with a `Fetched` status.
+    async fn check_fetched(
+        &self,
+        txid: Txid,
+        fetched: &FetchedTx<Self::Address>,
+    ) -> CheckTransactionResponse {
+        match self.check_for_blocked_input_addresses(fetched) {
+            // If some input addresses are missing, try to fetch them and try again.
+            Err(CheckTxInputsError::MissingInputAddresses) => (),
+            result => return result.into(),
+        }
+
+        let mut futures = vec![];
+        let mut jobs = vec![];
+        let mut high_load = false;
+        let mut not_enough_cycles = false;
+        for (index, input) in fetched.tx.inputs.iter().enumerate() {
+            if fetched.input_addresses[index].is_none() {
+                use TryFetchResult::*;
+                match self.try_fetch_tx(input.txid) {
+                    ToFetch(do_fetch) => {
+                        jobs.push((index, input.txid, input.vout));
+                        futures.push(do_fetch)
+                    }
+                    Fetched(fetched) => {
+                        if let Some(address) = &fetched.tx.outputs[input.vout as usize] {
+                            self.set_fetched_address(txid, index, address.clone());
+                        } else {
+                            // This error shouldn't happen unless blockdata is corrupted.
+                            let msg = format!(
+                                "Tx {} vout {} has no address, but is vin {} of tx {}",
+                                input.txid, input.vout, index, txid
+                            );
+                            log!(self.warn_log(), "{msg}");
+                            return CheckTransactionIrrecoverableError::InvalidTransaction(msg)
+                                .into();
+                        }
+                    }
+                    Pending => {}
+                    HighLoad => {
+                        high_load = true;
+                    }
+                    NotEnoughCycles => {
+                        not_enough_cycles = true;
+                    }
+                }
+            }
+        }
+
+        if futures.is_empty() {
+            if not_enough_cycles {
+                return CheckTransactionStatus::NotEnoughCycles.into();
+            }
+            if high_load {
+                return CheckTransactionRetriable::HighLoad.into();
+            }
+        }
+
+        let fetch_results = try_join_all(futures)
+            .await
+            .unwrap_or_else(|err| unreachable!("error in try_join_all {:?}", err));
+
+        let mut error = None;
+        for (i, result) in fetch_results.into_iter().enumerate() {
+            let (index, input_txid, vout) = jobs[i];
+            match result {
+                FetchResult::Fetched(fetched) => {
+                    if let Some(address) = &fetched.tx.outputs[vout as usize] {
+                        self.set_fetched_address(txid, index, address.clone());
+                    } else {
+                        // This error shouldn't happen unless blockdata is corrupted.
+                        let msg = format!(
+                            "Tx {input_txid} vout {vout} has no address, but is vin {index} of tx {txid}"
+                        );
+                        log!(self.warn_log(), "{msg}");
+                        error = Some(
+                            CheckTransactionIrrecoverableError::InvalidTransaction(msg).into(),
+                        );
+                    }
+                }
+                FetchResult::Error(err) => error = Some(err.into_response(input_txid)),
+                FetchResult::RetryWithBiggerBuffer => (),
+            }
+        }
+        if let Some(err) = error {
+            return err;
+        }
+        // Check again to see if we have completed
+        if let Some(FetchTxStatus::Fetched(fetched)) = self.get_fetch_status(txid) {
+            self.check_for_blocked_input_addresses(&fetched).into()
+        } else {
+            CheckTransactionRetriable::Pending.into()
+        }
+    }
+
+    /// Return `Ok` if no input address is blocked, and an `Err` if either one of the input
+    /// addresses is blocked, or one of the input addresses is not available.
+    fn check_for_blocked_input_addresses(
+        &self,
+        fetched: &FetchedTx<Self::Address>,
+    ) -> Result<(), CheckTxInputsError> {
+        if fetched.input_addresses.iter().any(|x| x.is_none()) {
+            return Err(CheckTxInputsError::MissingInputAddresses);
+        }
+        let blocked: Vec<String> = fetched
+            .input_addresses
+            .iter()
+            .flatten()
+            .filter(|address| self.is_blocked(address))
+            .map(|address| address.to_string())
+            .collect();
+        if blocked.is_empty() {
+            Ok(())
+        } else {
+            Err(CheckTxInputsError::BlockedInputAddresses(blocked))
+        }
+    }
+
+    /// Check the input addresses of a transaction given its txid.
+    /// It consists of the following steps:
+    ///
+    /// 1. Call `try_fetch_tx` to find if the transaction has already
+    ///    been fetched, or if another fetch is already pending, or
+    ///    if we are experiencing high load, or we need to retry the
+    ///    fetch (when the previous max_response_bytes setting wasn't
+    ///    enough).
+    ///
+    /// 2. If we need to fetch this tranction, call the function `do_fetch`.
+    ///
+    /// 3. For fetched transaction, call `check_fetched`, which further
+    ///    checks if the input addresses of this transaction are available.
+    ///    If not, we need to additionally fetch those input transactions
+    ///    in order to compute their corresponding addresses.
+    async fn check_transaction_inputs(&self, txid: Txid) -> CheckTransactionResponse {
+        match self.check_mode() {
+            CheckMode::AcceptAll => CheckTransactionResponse::Passed,
+            CheckMode::RejectAll => CheckTransactionResponse::Failed(Vec::new()),
+            CheckMode::Normal => match self.try_fetch_tx(txid) {
+                TryFetchResult::Pending => CheckTransactionRetriable::Pending.into(),
+                TryFetchResult::HighLoad => CheckTransactionRetriable::HighLoad.into(),
+                TryFetchResult::NotEnoughCycles => CheckTransactionStatus::NotEnoughCycles.into(),
+                TryFetchResult::Fetched(fetched) => self.check_fetched(txid, &fetched).await,
+                TryFetchResult::ToFetch(do_fetch) => match do_fetch.await {
+                    Ok(FetchResult::Fetched(fetched)) => self.check_fetched(txid, &fetched).await,
+                    Ok(FetchResult::Error(err)) => err.into_response(txid),
+                    Ok(FetchResult::RetryWithBiggerBuffer) => {
+                        CheckTransactionRetriable::Pending.into()
+                    }
+                    Err(_) => unreachable!(), // should never happen
+                },
+            },
+        }
+    }
+
+    /// Check the input addresses of a transaction given its txid without performing any HTTP outcalls.
+    /// A `Pass` or `Fail` status will only be returned if the txid and all of its inputs were already
+    /// fetched and available in heap memory. Otherwise, `Unknown` is returned.
+    fn check_fetched_transaction_inputs(&self, txid: Txid) -> CheckTransactionQueryResponse {
+        match self.check_mode() {
+            CheckMode::AcceptAll => CheckTransactionQueryResponse::Passed,
+            CheckMode::RejectAll => CheckTransactionQueryResponse::Failed(Vec::new()),
+            CheckMode::Normal => match self.get_fetch_status(txid) {
+                Some(FetchTxStatus::Fetched(fetched)) => {
+                    self.check_for_blocked_input_addresses(&fetched).into()
+                }
+                Some(
+                    FetchTxStatus::PendingOutcall
+                    | FetchTxStatus::PendingRetry { .. }
+                    | FetchTxStatus::Error(_),
+                )
+                | None => CheckTransactionQueryResponse::Unknown,
+            },
+        }
+    }
+
+    /// Charges the `CHECK_TRANSACTION_CYCLES_SERVICE_FEE` and checks the input addresses of
+    /// the transaction returned by `get_txid` if the caller attached enough cycles.
+    async fn check_transaction_with<F: FnOnce() -> Result<Txid, String>>(
+        &self,
+        get_txid: F,
+    ) -> CheckTransactionResponse {
+        if self.cycles_accept(CHECK_TRANSACTION_CYCLES_SERVICE_FEE)
+            < CHECK_TRANSACTION_CYCLES_SERVICE_FEE
+        {
+            return CheckTransactionStatus::NotEnoughCycles.into();
+        }
+
+        match get_txid() {
+            Ok(txid) => {
+                STATS.with(|s| s.borrow_mut().check_transaction_count += 1);
+                if ic_cdk::api::msg_cycles_available()
+                    .checked_add(CHECK_TRANSACTION_CYCLES_SERVICE_FEE)
+                    .unwrap()
+                    < CHECK_TRANSACTION_CYCLES_REQUIRED
+                {
+                    CheckTransactionStatus::NotEnoughCycles.into()
+                } else {
+                    self.check_transaction_inputs(txid).await
+                }
+            }
+            Err(err) => CheckTransactionIrrecoverableError::InvalidTransactionId(err).into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum CheckTxInputsError {
+    MissingInputAddresses,
+    BlockedInputAddresses(Vec<String>),
+}
+
+impl From<CheckTxInputsError> for CheckTransactionResponse {
+    fn from(error: CheckTxInputsError) -> Self {
+        match error {
+            CheckTxInputsError::MissingInputAddresses => CheckTransactionRetriable::Pending.into(),
+            CheckTxInputsError::BlockedInputAddresses(blocked) => {
+                CheckTransactionResponse::Failed(blocked)
+            }
+        }
+    }
+}
+
+impl From<CheckTxInputsError> for CheckTransactionQueryResponse {
+    fn from(error: CheckTxInputsError) -> Self {
+        match error {
+            CheckTxInputsError::MissingInputAddresses => CheckTransactionQueryResponse::Unknown,
+            CheckTxInputsError::BlockedInputAddresses(blocked) => {
+                CheckTransactionQueryResponse::Failed(blocked)
+            }
+        }
+    }
+}
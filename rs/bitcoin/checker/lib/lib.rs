@@ -1,4 +1,8 @@
 pub mod blocklist;
+pub mod dashboard;
+pub mod fetch;
+pub mod outcall;
+pub mod state;
 mod types;
 
 pub use types::*;
@@ -0,0 +1,247 @@
+//! HTTPS outcalls to transaction providers and the statistics collected about them.
+use crate::RETRY_MAX_RESPONSE_BYTES;
+use crate::fetch::HttpGetTxError;
+use bitcoin::Transaction;
+use candid::Nat;
+use ic_btc_interface::Txid;
+use ic_canister_log::{GlobalBuffer, log};
+use ic_cdk::call::Error;
+use ic_cdk::management_canister::{HttpHeader, HttpRequestArgs};
+use ic_metrics_encoder::MetricsEncoder;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(PartialOrd, Ord, PartialEq, Eq)]
+pub enum IcError {
+    CallRejected {
+        raw_reject_code: u32,
+        reject_message: String,
+    },
+    CallPerformFailed,
+}
+
+#[derive(PartialOrd, Ord, PartialEq, Eq)]
+pub enum HttpsOutcallStatus {
+    ResponseTooLarge,
+    IcError(IcError),
+    HttpStatusCode(Nat),
+}
+
+impl fmt::Display for HttpsOutcallStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ResponseTooLarge => write!(f, "ResponseTooLarge"),
+            Self::IcError(ic_error) => match ic_error {
+                IcError::CallRejected {
+                    raw_reject_code, ..
+                } => write!(f, "IcErrorCallRejected({raw_reject_code})"),
+                IcError::CallPerformFailed => write!(f, "IcErrorCallPerformFailed"),
+            },
+            Self::HttpStatusCode(status_code) => write!(f, "HttpStatusCode({status_code})"),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Stats {
+    pub https_outcall_status: BTreeMap<(String, HttpsOutcallStatus), u64>,
+    pub http_response_size: BTreeMap<u32, u64>,
+    pub check_transaction_count: u64,
+}
+
+thread_local! {
+    pub static STATS : RefCell<Stats> = RefCell::default();
+}
+
+pub fn is_response_too_large(reject: &ic_cdk::call::CallRejected) -> bool {
+    match reject.reject_code() {
+        Ok(ic_cdk::call::RejectCode::SysFatal) => {
+            let message = reject.reject_message();
+            message.contains("size limit") || message.contains("length limit")
+        }
+        _ => false,
+    }
+}
+
+/// Fetches a transaction from the provider named `provider_name` with the given HTTPS
+/// outcall request and records the outcome of the outcall in `STATS`.
+///
+/// The body of a successful response is decoded with `decode_tx`, and the transaction ID
+/// of the decoded transaction must match `txid`. Failed outcalls are logged to `debug_log`.
+pub async fn http_get_tx(
+    provider_name: String,
+    request: HttpRequestArgs,
+    txid: Txid,
+    max_response_bytes: u32,
+    debug_log: &'static GlobalBuffer,
+    decode_tx: impl FnOnce(&[u8]) -> Result<Transaction, HttpGetTxError>,
+) -> Result<Transaction, HttpGetTxError> {
+    use ic_cdk::management_canister::http_request;
+    let url = request.url.clone();
+    match http_request(&request).await {
+        Ok(response) => {
+            STATS.with(|s| {
+                let mut stat = s.borrow_mut();
+                *stat
+                    .https_outcall_status
+                    .entry((
+                        provider_name.clone(),
+                        HttpsOutcallStatus::HttpStatusCode(response.status.clone()),
+                    ))
+                    .or_default() += 1;
+                // Calculate size bucket as a series of power of 2s.
+                // Note that the max is bounded by `max_response_bytes`, which fits `u32`.
+                let size = 2u32.pow((response.body.len() as f64).log2().floor() as u32);
+                *stat.http_response_size.entry(size).or_default() += 1;
+            });
+
+            // Ensure response is 200 before decoding
+            if response.status != 200u32 {
+                // All non-200 status are treated as transient errors
+                return Err(HttpGetTxError::Rejected {
+                    code: 2, //SYS_TRANSIENT
+                    message: format!("HTTP call {} received code {}", url, response.status),
+                });
+            }
+            let tx = decode_tx(response.body.as_slice())?;
+            // Verify the correctness of the transaction by recomputing the transaction ID.
+            let decoded_txid = tx.compute_txid();
+            if decoded_txid.as_ref() as &[u8; 32] != txid.as_ref() {
+                return Err(HttpGetTxError::TxidMismatch {
+                    expected: txid,
+                    decoded: Txid::from(*(decoded_txid.as_ref() as &[u8; 32])),
+                });
+            }
+            Ok(tx)
+        }
+        Err(Error::CallRejected(reject)) if is_response_too_large(&reject) => {
+            if max_response_bytes >= RETRY_MAX_RESPONSE_BYTES {
+                STATS.with(|s| {
+                    let mut stat = s.borrow_mut();
+                    *stat
+                        .https_outcall_status
+                        .entry((provider_name, HttpsOutcallStatus::ResponseTooLarge))
+                        .or_default() += 1;
+                });
+            }
+            Err(HttpGetTxError::ResponseTooLarge)
+        }
+        Err(error) => {
+            let (status, http_error) = match error {
+                Error::InsufficientLiquidCycleBalance(e) => {
+                    panic!("BUG: Insufficient liquidity balance: {}", e)
+                }
+                Error::CandidDecodeFailed(e) => {
+                    panic!("BUG: Candid decoding http_request failed: {}", e)
+                }
+                Error::CallPerformFailed(_e) => (
+                    HttpsOutcallStatus::IcError(IcError::CallPerformFailed),
+                    HttpGetTxError::CallPerformFailed,
+                ),
+                Error::CallRejected(e) => (
+                    HttpsOutcallStatus::IcError(IcError::CallRejected {
+                        raw_reject_code: e.raw_reject_code(),
+                        reject_message: e.reject_message().to_string(),
+                    }),
+                    HttpGetTxError::Rejected {
+                        code: e.raw_reject_code(),
+                        message: e.reject_message().to_string(),
+                    },
+                ),
+            };
+            STATS.with(|s| {
+                let mut stat = s.borrow_mut();
+                *stat
+                    .https_outcall_status
+                    .entry((provider_name, status))
+                    .or_default() += 1;
+            });
+            log!(
+                debug_log,
+                "The http_request resulted into error. Error: {http_error}, Request: {request:?}"
+            );
+            Err(http_error)
+        }
+    }
+}
+
+/// Encodes the statistics in `STATS` as metrics whose names start with the given prefix,
+/// e.g., `btc` for `btc_checker_http_calls_total`.
+pub fn encode_stats_metrics(writer: &mut MetricsEncoder<Vec<u8>>, prefix: &str) {
+    STATS.with(|s| {
+        let stats = s.borrow();
+        let mut counter = writer
+            .counter_vec(
+                &format!("{prefix}_checker_http_calls_total"),
+                "The number of http outcalls made since the last canister upgrade.",
+            )
+            .unwrap();
+        for ((provider, status), count) in stats.https_outcall_status.iter() {
+            counter = counter
+                .value(
+                    &[
+                        ("provider", provider.as_str()),
+                        ("status", status.to_string().as_ref()),
+                    ],
+                    *count as f64,
+                )
+                .unwrap();
+        }
+        let mut counter = writer
+            .counter_vec(
+                &format!("{prefix}_checker_http_response_size"),
+                "The byte sizes of http outcall responses.",
+            )
+            .unwrap();
+        for (size, count) in stats.http_response_size.iter() {
+            counter = counter
+                .value(&[("size", size.to_string().as_str())], *count as f64)
+                .unwrap();
+        }
+        writer
+            .counter_vec(
+                &format!("{prefix}_check_requests_total"),
+                "The number of check requests received since the last canister upgrade.",
+            )
+            .unwrap()
+            .value(
+                &[("type", "check_transaction")],
+                stats.check_transaction_count as f64,
+            )
+            .unwrap();
+    });
+}
+
+/// Removes the credentials from the given JSON RPC URL of a regtest node and returns
+/// them as a basic authorization header.
+pub fn parse_authorization_header_from_url(
+    json_rpc_url: &str,
+) -> Result<(url::Url, HttpHeader), String> {
+    let mut url = url::Url::parse(json_rpc_url).map_err(|err| err.to_string())?;
+    let username = url.username();
+    let password = url.password().unwrap_or_default();
+    let authorization = base64::encode(format!(
+        "{}:{}",
+        url::form_urlencoded::parse(username.as_bytes())
+            .next()
+            .ok_or("Missing username or error in url_decode".to_string())?
+            .0,
+        url::form_urlencoded::parse(password.as_bytes())
+            .next()
+            .ok_or("Missing password or error in url_decode".to_string())?
+            .0,
+    ));
+    url.set_username("")
+        .map_err(|()| format!("Invalid JSON RPC URL {json_rpc_url}"))?;
+    url.set_password(None)
+        .map_err(|()| format!("Invalid JSON RPC URL {json_rpc_url}"))?;
+    let header = HttpHeader {
+        name: "Authorization".to_string(),
+        value: format!("Basic {authorization}"),
+    };
+    Ok((url, header))
+}
@@ -0,0 +1,296 @@
+//! The state of a checker canister: its configuration and the cache of fetched
+//! transactions.
+//!
+//! The logic is independent of the network: the checker canisters keep the cache
+//! in a thread-local variable and expose it through [`FetchTxCacheStore`].
+use crate::blocklist::is_blocked;
+use crate::fetch::{FetchGuardError, FetchTxStatus};
+use crate::outcall::parse_authorization_header_from_url;
+use crate::{BtcNetwork, CheckMode};
+use ic_btc_interface::Txid;
+use ic_stable_structures::{Storable, storable::Bound};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::marker::PhantomData;
+
+#[cfg(test)]
+mod tests;
+
+// Max number of concurrent http outcalls.
+pub const MAX_CONCURRENT: u32 = 50;
+
+// Max number of entries in the cache is set to 10_000. Since the average transaction size
+// is below 1kB, the estimated memory usage of the cache is in the order of 10s of MBs.
+pub const MAX_FETCH_TX_ENTRIES: usize = 10_000;
+
+// The outcall capacity is a semaphore limiting max concurrent outcalls.
+//
+// TODO(XC-191): persist canister state
+thread_local! {
+    pub static OUTCALL_CAPACITY: RefCell<u32> = const { RefCell::new(MAX_CONCURRENT) };
+}
+
+// Time in nanoseconds since the epoch (1970-01-01).
+pub type Timestamp = u64;
+
+/// The fetch transaction status, indexed by transaction id.
+pub struct FetchTxCache<T> {
+    max_entries: usize,
+    status: BTreeMap<Txid, T>,
+    created: VecDeque<(Txid, Timestamp)>,
+}
+
+impl<T> FetchTxCache<T> {
+    pub fn new(max_entries: usize) -> Self {
+        assert!(max_entries > 0);
+        Self {
+            max_entries,
+            status: BTreeMap::new(),
+            created: VecDeque::new(),
+        }
+    }
+
+    pub fn get_status(&self, txid: Txid) -> Option<&T> {
+        self.status.get(&txid)
+    }
+
+    // Set the status of a txid with a timestamp. If the txid does not
+    // already have a status, a new status entry is created and associated
+    // with the given the timestamp.
+    //
+    // This function may also remove and return the oldest entry if we
+    // exceeds the `max_entries` settings.
+    pub fn set_status_with(
+        &mut self,
+        txid: Txid,
+        status: T,
+        now: Timestamp,
+    ) -> Option<(Txid, Timestamp, T)> {
+        if self.status.insert(txid, status).is_none() {
+            // This is a new entry, record its created time.
+            self.created.push_back((txid, now));
+            assert_eq!(self.status.len(), self.created.len());
+            // Purge the oldest entry when we exceed max_entries.
+            if self.created.len() > self.max_entries {
+                let removed = self.created.pop_front().and_then(|(txid, timestamp)| {
+                    self.status
+                        .remove(&txid)
+                        .map(|status| (txid, timestamp, status))
+                });
+                assert_eq!(self.status.len(), self.created.len());
+                assert!(self.created.len() <= self.max_entries);
+                return removed;
+            }
+        }
+        None
+    }
+
+    pub fn clear_status(&mut self, txid: Txid) {
+        let _ = self.status.remove(&txid);
+        self.created.retain(|(id, _)| *id != txid);
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (Txid, Timestamp, &T)> + '_ {
+        self.created
+            .iter()
+            .map(|(txid, timestamp)| (*txid, *timestamp, self.status.get(txid).unwrap()))
+    }
+}
+
+impl<Provider, Address> FetchTxCache<FetchTxStatus<Provider, Address>> {
+    /// Set the address at the given `index` in the `Fetched` status of the given `txid`.
+    /// Pre-condition: the status of `txid` is `Fetched`, and `index` is within bounds.
+    pub fn set_fetched_address(&mut self, txid: Txid, index: usize, address: Address) {
+        self.status.entry(txid).and_modify(|status| {
+            if let FetchTxStatus::Fetched(fetched) = status {
+                fetched.input_addresses[index] = Some(address);
+            };
+        });
+    }
+}
+
+/// Gives access to the cache of fetched transactions of a checker canister.
+pub trait FetchTxCacheStore {
+    type Provider: Clone;
+    type Address: Clone;
+
+    fn with_fetch_tx_cache<R>(
+        f: impl FnOnce(&mut FetchTxCache<FetchTxStatus<Self::Provider, Self::Address>>) -> R,
+    ) -> R;
+
+    /// The current time in nanoseconds since the epoch.
+    fn now() -> Timestamp;
+}
+
+pub fn get_fetch_status<S: FetchTxCacheStore>(
+    txid: Txid,
+) -> Option<FetchTxStatus<S::Provider, S::Address>> {
+    S::with_fetch_tx_cache(|cache| cache.get_status(txid).cloned())
+}
+
+pub fn set_fetch_status<S: FetchTxCacheStore>(
+    txid: Txid,
+    status: FetchTxStatus<S::Provider, S::Address>,
+) {
+    let now = S::now();
+    let _ = S::with_fetch_tx_cache(|cache| cache.set_status_with(txid, status, now));
+}
+
+pub fn clear_fetch_status<S: FetchTxCacheStore>(txid: Txid) {
+    S::with_fetch_tx_cache(|cache| cache.clear_status(txid))
+}
+
+/// Set the address at the given `index` in the `Fetched` status of the given `txid`.
+/// Pre-condition: the status of `txid` is `Fetched`, and `index` is within bounds.
+pub fn set_fetched_address<S: FetchTxCacheStore>(txid: Txid, index: usize, address: S::Address) {
+    S::with_fetch_tx_cache(|cache| cache.set_fetched_address(txid, index, address))
+}
+
+/// Reserves the outcall capacity to fetch a transaction and marks it as
+/// `PendingOutcall` until dropped.
+pub struct FetchGuard<S: FetchTxCacheStore> {
+    txid: Txid,
+    _marker: PhantomData<S>,
+}
+
+impl<S: FetchTxCacheStore> FetchGuard<S> {
+    pub fn new(txid: Txid) -> Result<Self, FetchGuardError> {
+        let guard = OUTCALL_CAPACITY.with(|capacity| {
+            let mut capacity = capacity.borrow_mut();
+            if *capacity > 0 {
+                *capacity -= 1;
+                Ok(FetchGuard {
+                    txid,
+                    _marker: PhantomData,
+                })
+            } else {
+                Err(FetchGuardError::NoCapacity)
+            }
+        })?;
+        set_fetch_status::<S>(txid, FetchTxStatus::PendingOutcall);
+        Ok(guard)
+    }
+}
+
+impl<S: FetchTxCacheStore> Drop for FetchGuard<S> {
+    fn drop(&mut self) {
+        OUTCALL_CAPACITY.with(|capacity| {
+            let mut capacity = capacity.borrow_mut();
+            *capacity += 1;
+        });
+        let txid = self.txid;
+        if let Some(FetchTxStatus::PendingOutcall) = get_fetch_status::<S>(txid) {
+            // Only clear the status when it is still `PendingOutcall`
+            clear_fetch_status::<S>(txid);
+        }
+    }
+}
+
+/// A network that a checker canister can be configured with.
+pub trait CheckerNetwork: Clone + fmt::Display {
+    /// The address type of the network.
+    type Address: Clone + fmt::Display;
+
+    /// The name of the currency of the network, e.g. `Bitcoin`.
+    const CURRENCY: &'static str;
+
+    /// The URL of the node that transactions are fetched from on regtest.
+    fn json_rpc_url(&self) -> Option<&str>;
+
+    /// The link to the given transaction on a block explorer, if any.
+    fn explorer_tx_url(&self, txid: &Txid) -> Option<String>;
+
+    fn is_blocked(address: &Self::Address) -> bool;
+}
+
+impl CheckerNetwork for BtcNetwork {
+    type Address = bitcoin::Address;
+
+    const CURRENCY: &'static str = "Bitcoin";
+
+    fn json_rpc_url(&self) -> Option<&str> {
+        match self {
+            Self::Regtest { json_rpc_url } => Some(json_rpc_url),
+            Self::Mainnet | Self::Testnet => None,
+        }
+    }
+
+    fn explorer_tx_url(&self, txid: &Txid) -> Option<String> {
+        match self {
+            Self::Mainnet => Some(format!("https://live.blockcypher.com/btc/tx/{txid}")),
+            Self::Testnet => Some(format!(
+                "https://live.blockcypher.com/btc-testnet/tx/{txid}"
+            )),
+            Self::Regtest { .. } => None,
+        }
+    }
+
+    fn is_blocked(address: &bitcoin::Address) -> bool {
+        is_blocked(address)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Config<Network> {
+    // The field keeps the names it had before the state was shared so that the
+    // checkers can decode the configuration they stored in stable memory.
+    #[serde(rename = "btc_network", alias = "doge_network")]
+    network: Network,
+    pub check_mode: CheckMode,
+    #[serde(default = "default_num_subnet_nodes")]
+    pub num_subnet_nodes: u16,
+}
+
+fn default_num_subnet_nodes() -> u16 {
+    34
+}
+
+impl<Network: CheckerNetwork> Config<Network> {
+    pub fn new_and_validate(
+        network: Network,
+        check_mode: CheckMode,
+        num_subnet_nodes: u16,
+    ) -> Result<Self, String> {
+        if let Some(json_rpc_url) = network.json_rpc_url() {
+            let _ = parse_authorization_header_from_url(json_rpc_url)?;
+        }
+        Ok(Self {
+            network,
+            check_mode,
+            num_subnet_nodes,
+        })
+    }
+
+    pub fn network(&self) -> Network {
+        self.network.clone()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum ConfigState<Network> {
+    Uninitialized,
+    Initialized(Config<Network>),
+}
+
+impl<Network: Clone + Serialize + DeserializeOwned> Storable for ConfigState<Network> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let mut buf = vec![];
+        ciborium::ser::into_writer(self, &mut buf).expect("failed to encode ConfigState");
+        Cow::Owned(buf)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        ciborium::de::from_reader(bytes.as_ref()).unwrap_or_else(|e| {
+            panic!(
+                "failed to decode ConfigState bytes {:?}: {}",
+                bytes.as_ref(),
+                e
+            )
+        })
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
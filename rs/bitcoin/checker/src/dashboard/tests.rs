@@ -1,9 +1,10 @@
 use crate::dashboard::tests::assertions::DashboardAssert;
-use crate::dashboard::{DEFAULT_TX_TABLE_PAGE_SIZE, DashboardTemplate, Fetched, Status, filters};
-use crate::state::{Config, Timestamp, TransactionCheckData};
+use crate::dashboard::{DashboardTemplate, Status};
+use crate::state::{Config, Timestamp};
 use crate::{dashboard, state};
 use bitcoin::Address;
 use bitcoin::{Transaction, absolute::LockTime, transaction::Version};
+use ic_btc_checker::dashboard::{DEFAULT_TX_TABLE_PAGE_SIZE, Fetched, filters};
 use ic_btc_checker::{BtcNetwork, CheckMode, blocklist::BTC_ADDRESS_BLOCKLIST};
 use ic_btc_interface::Txid;
 use std::str::FromStr;
@@ -38,7 +39,7 @@ fn should_display_metadata() {
     };
 
     DashboardAssert::assert_that(dashboard)
-        .has_btc_network_in_title(config.btc_network())
+        .has_btc_network_in_title(config.network())
         .has_check_mode(config.check_mode)
         .has_outcall_capacity(outcall_capacity)
         .has_cached_entries(cached_entries)
@@ -124,16 +125,15 @@ fn test_pagination() {
         state::Config::new_and_validate(BtcNetwork::Mainnet, CheckMode::Normal, TEST_SUBNET_NODES)
             .unwrap(),
     );
-    let mock_transaction = TransactionCheckData::from_transaction(
+    let mock_transaction = state::transaction_check_data(
         &BtcNetwork::Mainnet,
-        Transaction {
+        &Transaction {
             version: Version::ONE,
             lock_time: LockTime::ZERO,
             input: Vec::new(),
             output: Vec::new(),
         },
-    )
-    .unwrap();
+    );
     let mut expected_txids = vec![];
     // Generate entries to fill one and half pages
     for i in 0..DEFAULT_TX_TABLE_PAGE_SIZE * 3 / 2 {
@@ -1,12 +1,15 @@
 use super::*;
-use crate::{CheckTransactionIrrecoverableError, providers::Provider};
+use crate::state::{Config, FetchTxStatusError, FetchedTx};
 use bitcoin::{
     Amount, OutPoint, PubkeyHash, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
     absolute::LockTime, address::Address, hashes::Hash, transaction::Version,
 };
 use ic_btc_checker::{
-    BtcNetwork, CHECK_TRANSACTION_CYCLES_REQUIRED, CHECK_TRANSACTION_CYCLES_SERVICE_FEE, CheckMode,
-    blocklist,
+    CHECK_TRANSACTION_CYCLES_REQUIRED, CHECK_TRANSACTION_CYCLES_SERVICE_FEE,
+    CheckTransactionIrrecoverableError, CheckTransactionResponse, CheckTransactionRetriable,
+    CheckTransactionStatus, INITIAL_MAX_RESPONSE_BYTES, RETRY_MAX_RESPONSE_BYTES, blocklist,
+    fetch::{FetchResult, TryFetchResult},
+    get_tx_cycle_cost,
 };
 use std::cell::RefCell;
 use std::collections::VecDeque;
@@ -27,6 +30,8 @@ const TEST_SUBNET_NODES: u16 = 13;
 
 impl FetchEnv for MockEnv {
     type FetchGuard = ();
+    type Provider = Provider;
+    type Address = Address;
 
     fn new_fetch_guard(&self, _txid: Txid) -> Result<Self::FetchGuard, FetchGuardError> {
         if self.high_load {
@@ -36,8 +41,48 @@ impl FetchEnv for MockEnv {
         }
     }
 
-    fn config(&self) -> Config {
-        Config::new_and_validate(BtcNetwork::Mainnet, CheckMode::Normal, TEST_SUBNET_NODES).unwrap()
+    fn check_mode(&self) -> CheckMode {
+        self.config().check_mode
+    }
+
+    fn num_subnet_nodes(&self) -> u16 {
+        self.config().num_subnet_nodes
+    }
+
+    fn next_provider(&self) -> Provider {
+        providers::next_provider(self.config().network())
+    }
+
+    fn retry_provider(&self, provider: &Provider) -> Provider {
+        provider.next()
+    }
+
+    fn get_fetch_status(&self, txid: Txid) -> Option<FetchTxStatus> {
+        state::get_fetch_status(txid)
+    }
+
+    fn set_fetch_status(&self, txid: Txid, status: FetchTxStatus) {
+        state::set_fetch_status(txid, status)
+    }
+
+    fn set_fetched_address(&self, txid: Txid, index: usize, address: Address) {
+        state::set_fetched_address(txid, index, address)
+    }
+
+    fn transaction_check_data(
+        &self,
+        provider: &Provider,
+        tx: &Transaction,
+    ) -> TransactionCheckData {
+        state::transaction_check_data(provider.btc_network(), tx)
+    }
+
+    fn is_blocked(&self, address: &Address) -> bool {
+        is_blocked(address)
+    }
+
+    fn warn_log(&self) -> &'static GlobalBuffer {
+        &WARN
     }
 
     async fn http_get_tx(
@@ -69,6 +114,9 @@ impl FetchEnv for MockEnv {
 }
 
 impl MockEnv {
+    fn config(&self) -> Config {
+        Config::new_and_validate(BtcNetwork::Mainnet, CheckMode::Normal, TEST_SUBNET_NODES).unwrap()
+    }
     fn new(available_cycles: u128) -> Self {
         Self {
             high_load: false,
@@ -169,7 +217,7 @@ fn mock_transaction_with_inputs(input_txids: Vec<(Txid, u32)>) -> Transaction {
 async fn test_mock_env() {
     // Test cycle mock functions
     let env = MockEnv::new(CHECK_TRANSACTION_CYCLES_REQUIRED);
-    let provider = providers::next_provider(env.config().btc_network());
+    let provider = providers::next_provider(env.config().network());
     assert_eq!(
         env.cycles_accept(CHECK_TRANSACTION_CYCLES_SERVICE_FEE),
         CHECK_TRANSACTION_CYCLES_SERVICE_FEE
@@ -202,9 +250,8 @@ fn test_try_fetch_tx() {
     let txid_0 = mock_txid(0);
     let txid_1 = mock_txid(1);
     let txid_2 = mock_txid(2);
-    let from_tx = |tx: &bitcoin::Transaction| {
-        TransactionCheckData::from_transaction(&env.config().btc_network(), tx.clone()).unwrap()
-    };
+    let from_tx =
+        |tx: &bitcoin::Transaction| state::transaction_check_data(&env.config().network(), tx);
 
     // case Fetched
     let fetched_0 = FetchTxStatus::Fetched(FetchedTx {
@@ -248,13 +295,12 @@ fn test_try_fetch_tx() {
 #[tokio::test]
 async fn test_fetch_tx() {
     let env = MockEnv::new(CHECK_TRANSACTION_CYCLES_REQUIRED);
-    let provider = providers::next_provider(env.config().btc_network());
+    let provider = providers::next_provider(env.config().network());
     let txid_0 = mock_txid(0);
     let txid_1 = mock_txid(1);
     let txid_2 = mock_txid(2);
-    let from_tx = |tx: &bitcoin::Transaction| {
-        TransactionCheckData::from_transaction(&env.config().btc_network(), tx.clone()).unwrap()
-    };
+    let from_tx =
+        |tx: &bitcoin::Transaction| state::transaction_check_data(&env.config().network(), tx);
 
     // case Fetched
     let tx_0 = mock_transaction_with_inputs(vec![(txid_1, 0), (txid_2, 1)]);
@@ -321,10 +367,8 @@ async fn test_check_fetched() {
     let tx_0 = mock_transaction_with_inputs(vec![(txid_1, 0), (txid_2, 1)]);
     let tx_1 = mock_transaction_with_outputs(1);
     let tx_2 = mock_transaction_with_outputs(2);
-    let network = env.config().btc_network();
-    let from_tx = |tx: &bitcoin::Transaction| {
-        TransactionCheckData::from_transaction(&network, tx.clone()).unwrap()
-    };
+    let network = env.config().network();
+    let from_tx = |tx: &bitcoin::Transaction| state::transaction_check_data(&network, tx);
 
     // case Passed
     let fetched = FetchedTx {
@@ -522,7 +566,7 @@ async fn test_check_fetched() {
 
     // case HttpGetTxError can be retried.
     let remaining_cycles = env.cycles_available();
-    let provider = providers::next_provider(env.config().btc_network());
+    let provider = providers::next_provider(env.config().network());
     state::set_fetch_status(
         txid_2,
         FetchTxStatus::Error(FetchTxStatusError {
@@ -1,4 +1,5 @@
 use crate::BtcNetwork;
+use ic_btc_checker::outcall::parse_authorization_header_from_url;
 use ic_btc_interface::Txid;
 use ic_cdk::management_canister::{
     HttpHeader, HttpMethod, HttpRequestArgs, TransformContext, TransformFunc,
@@ -6,9 +7,6 @@ use ic_cdk::management_canister::{
 use std::cell::RefCell;
 use std::fmt;
 
-#[cfg(test)]
-mod tests;
-
 /// Return the next Bitcoin API provider for the given `btc_network`.
 ///
 /// Internally it remembers the previously used provider in a thread local
@@ -181,31 +179,3 @@ fn param_transform() -> Option<TransformContext> {
         context: vec![],
     })
 }
-
-pub(crate) fn parse_authorization_header_from_url(
-    json_rpc_url: &str,
-) -> Result<(url::Url, HttpHeader), String> {
-    let mut url = url::Url::parse(json_rpc_url).map_err(|err| err.to_string())?;
-    let username = url.username();
-    let password = url.password().unwrap_or_default();
-    let authorization = base64::encode(format!(
-        "{}:{}",
-        url::form_urlencoded::parse(username.as_bytes())
-            .next()
-            .ok_or("Missing username or error in url_decode".to_string())?
-            .0,
-        url::form_urlencoded::parse(password.as_bytes())
-            .next()
-            .ok_or("Missing password or error in url_decode".to_string())?
-            .0,
-    ));
-    url.set_username("")
-        .map_err(|()| format!("Invalid JSON RPC URL {json_rpc_url}"))?;
-    url.set_password(None)
-        .map_err(|()| format!("Invalid JSON RPC URL {json_rpc_url}"))?;
-    let header = HttpHeader {
-        name: "Authorization".to_string(),
-        value: format!("Basic {authorization}"),
-    };
-    Ok((url, header))
-}
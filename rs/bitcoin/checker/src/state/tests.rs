@@ -1,34 +1,5 @@
 use super::*;
 
-#[test]
-fn test_fetch_guard() {
-    use super::*;
-    let txid = Txid::from([0u8; 32]);
-    let max = MAX_CONCURRENT;
-    {
-        let _guard = FetchGuard::new(txid).unwrap();
-        assert_eq!(OUTCALL_CAPACITY.with(|c| *c.borrow()), max - 1);
-        assert!(matches!(
-            get_fetch_status(txid),
-            Some(FetchTxStatus::PendingOutcall)
-        ));
-    }
-    assert!(get_fetch_status(txid).is_none());
-    assert_eq!(OUTCALL_CAPACITY.with(|c| *c.borrow()), max);
-
-    {
-        let mut guards = Vec::new();
-        for i in 0..max {
-            assert_eq!(OUTCALL_CAPACITY.with(|c| *c.borrow()), max - i);
-            let txid = Txid::from([(i + 1) as u8; 32]);
-            guards.push(FetchGuard::new(txid).unwrap());
-        }
-        assert!(FetchGuard::new(txid).is_err());
-        assert_eq!(OUTCALL_CAPACITY.with(|c| *c.borrow()), 0);
-    }
-    assert_eq!(OUTCALL_CAPACITY.with(|c| *c.borrow()), max);
-}
-
 #[test]
 fn test_fetch_status() {
     let txid_0 = Txid::from([0u8; 32]);
@@ -54,9 +25,7 @@ fn test_fetch_status() {
     use bitcoin::consensus::Decodable;
     let tx = Transaction::consensus_decode(&mut bytes.to_vec().as_slice()).unwrap();
     let txid_1 = Txid::from(*(tx.compute_txid().as_ref() as &[u8; 32]));
-    let from_tx = |tx: &bitcoin::Transaction| {
-        TransactionCheckData::from_transaction(&BtcNetwork::Mainnet, tx.clone()).unwrap()
-    };
+    let from_tx = |tx: &bitcoin::Transaction| transaction_check_data(&BtcNetwork::Mainnet, tx);
     set_fetch_status(
         txid_1,
         FetchTxStatus::Fetched(FetchedTx {
@@ -81,155 +50,3 @@ fn test_fetch_status() {
     clear_fetch_status(txid_1);
     assert!(get_fetch_status(txid_1).is_none());
 }
-
-#[test]
-fn test_fetch_tx_cache_bounds() {
-    let txid_0 = Txid::from([0u8; 32]);
-    let txid_1 = Txid::from([1u8; 32]);
-    let txid_2 = Txid::from([2u8; 32]);
-    let txid_3 = Txid::from([3u8; 32]);
-    let max_entries = 3;
-    let mut cache = FetchTxCache::new(max_entries);
-    assert_eq!(cache.set_status_with(txid_0, (), 0), None);
-    assert_eq!(cache.set_status_with(txid_1, (), 100), None);
-    assert_eq!(cache.set_status_with(txid_2, (), 200), None);
-    assert_eq!(
-        cache.iter().collect::<Vec<_>>(),
-        vec![(txid_0, 0, &()), (txid_1, 100, &()), (txid_2, 200, &())]
-    );
-
-    assert_eq!(
-        cache.set_status_with(txid_3, (), 300),
-        Some((txid_0, 0, ()))
-    );
-    assert_eq!(
-        cache.iter().collect::<Vec<_>>(),
-        vec![(txid_1, 100, &()), (txid_2, 200, &()), (txid_3, 300, &())]
-    );
-
-    // setting status of an existing entry will not change its creation
-    // time, neither will it purge any existing entry.
-    assert_eq!(cache.set_status_with(txid_2, (), 400), None);
-    assert_eq!(
-        cache.iter().collect::<Vec<_>>(),
-        vec![(txid_1, 100, &()), (txid_2, 200, &()), (txid_3, 300, &())]
-    );
-
-    // clear_status should make room for one more entry
-    cache.clear_status(txid_2);
-    assert_eq!(
-        cache.iter().collect::<Vec<_>>(),
-        vec![(txid_1, 100, &()), (txid_3, 300, &())]
-    );
-    assert_eq!(cache.set_status_with(txid_0, (), 500), None);
-    assert_eq!(
-        cache.iter().collect::<Vec<_>>(),
-        vec![(txid_1, 100, &()), (txid_3, 300, &()), (txid_0, 500, &())]
-    );
-}
-
-#[test]
-fn cache_should_have_same_size() {
-    enum Status {
-        Status0,
-        Status1,
-        Status2,
-    }
-
-    let txid_0 = Txid::from([0u8; 32]);
-    let max_entries = 3;
-    let mut cache = FetchTxCache::new(max_entries);
-    assert_eq!(cache.status.len(), 0);
-    assert_eq!(cache.created.len(), 0);
-
-    cache.set_status_with(txid_0, Status::Status0, 0);
-    assert_eq!(cache.status.len(), 1);
-    assert_eq!(cache.created.len(), 1);
-
-    cache.set_status_with(txid_0, Status::Status1, 1);
-    assert_eq!(cache.status.len(), 1);
-    assert_eq!(cache.created.len(), 1);
-
-    cache.set_status_with(txid_0, Status::Status2, 2);
-    assert_eq!(cache.status.len(), 1);
-    assert_eq!(cache.created.len(), 1);
-}
-
-mod schema_upgrades {
-    use crate::state::Config;
-    use candid::Deserialize;
-    use ic_btc_checker::{BtcNetwork, CheckMode};
-    use proptest::arbitrary::any;
-    use proptest::prelude::{Just, Strategy};
-    use proptest::{prop_oneof, proptest};
-    use serde::Serialize;
-
-    #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
-    pub struct ConfigPreviousVersion {
-        btc_network: BtcNetwork,
-        check_mode: CheckMode,
-    }
-
-    impl From<Config> for ConfigPreviousVersion {
-        fn from(
-            Config {
-                btc_network,
-                check_mode,
-                num_subnet_nodes: _,
-            }: Config,
-        ) -> Self {
-            Self {
-                btc_network,
-                check_mode,
-            }
-        }
-    }
-
-    proptest! {
-        #[test]
-        fn should_be_able_to_upgrade_state(config in arb_config()) {
-            let config_before_upgrade: ConfigPreviousVersion = config.into();
-
-            let serialized_config_before_upgrade = encode(&config_before_upgrade);
-            let config_after_upgrade: Config = decode(serialized_config_before_upgrade.as_slice());
-            assert_eq!(config_before_upgrade, config_after_upgrade.clone().into());
-        }
-    }
-
-    fn encode<S: ?Sized + serde::Serialize>(state: &S) -> Vec<u8> {
-        let mut buf = vec![];
-        ciborium::ser::into_writer(state, &mut buf).expect("failed to encode state");
-        buf
-    }
-
-    fn decode<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> T {
-        ciborium::de::from_reader(bytes)
-            .unwrap_or_else(|e| panic!("failed to decode state bytes {}: {e}", hex::encode(bytes)))
-    }
-
-    pub fn arb_config() -> impl Strategy<Value = Config> {
-        (arb_btc_network(), arb_check_mode(), any::<u16>()).prop_map(
-            |(btc_network, check_mode, num_subnet_nodes)| Config {
-                btc_network,
-                check_mode,
-                num_subnet_nodes,
-            },
-        )
-    }
-
-    pub fn arb_btc_network() -> impl Strategy<Value = BtcNetwork> {
-        prop_oneof![
-            Just(BtcNetwork::Mainnet),
-            Just(BtcNetwork::Testnet),
-            ".*".prop_map(|json_rpc_url| BtcNetwork::Regtest { json_rpc_url }),
-        ]
-    }
-
-    pub fn arb_check_mode() -> impl Strategy<Value = CheckMode> {
-        prop_oneof![
-            Just(CheckMode::AcceptAll),
-            Just(CheckMode::RejectAll),
-            Just(CheckMode::Normal),
-        ]
-    }
-}
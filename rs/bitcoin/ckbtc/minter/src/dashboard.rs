@@ -7,20 +7,75 @@ use icrc_ledger_types::icrc1::account::Account;
 use state::CkBtcMinterState;
 use std::io::Write;
 
+/// The parts of the minter dashboard that depend on the network the minter runs on.
+pub trait DashboardNetwork {
+    /// The name of the minted token, e.g. `ckBTC`.
+    const TOKEN: &'static str;
+    /// The ticker of the native currency, e.g. `BTC`.
+    const CURRENCY: &'static str;
+    /// The name of the network, e.g. `Bitcoin`.
+    const NETWORK_NAME: &'static str;
+    /// The page describing the minted token, if any.
+    const TOKEN_URL: Option<&'static str>;
+
+    fn network(s: &CkBtcMinterState) -> String;
+
+    fn main_address(s: &CkBtcMinterState) -> String;
+
+    fn display_address(address: &address::BitcoinAddress, s: &CkBtcMinterState) -> String;
+
+    fn txid_link(txid: &Txid, s: &CkBtcMinterState) -> String;
+}
+
+/// The dashboard of the ckBTC minter.
+pub struct Bitcoin;
+
+impl DashboardNetwork for Bitcoin {
+    const TOKEN: &'static str = "ckBTC";
+    const CURRENCY: &'static str = "BTC";
+    const NETWORK_NAME: &'static str = "Bitcoin";
+    const TOKEN_URL: Option<&'static str> = Some("https://internetcomputer.org/ckbtc/");
+
+    fn network(s: &CkBtcMinterState) -> String {
+        s.btc_network.to_string()
+    }
+
+    fn main_address(s: &CkBtcMinterState) -> String {
+        let main_account = Account {
+            owner: ic_cdk::api::canister_self(),
+            subaccount: None,
+        };
+        s.ecdsa_public_key
+            .clone()
+            .map(|key| {
+                address::account_to_bitcoin_address(&key, &main_account).display(s.btc_network)
+            })
+            .unwrap_or_default()
+    }
+
+    fn display_address(address: &address::BitcoinAddress, s: &CkBtcMinterState) -> String {
+        address.display(s.btc_network)
+    }
+
+    fn txid_link(txid: &Txid, s: &CkBtcMinterState) -> String {
+        txid_link_on(txid, s.btc_network)
+    }
+}
+
 fn with_utf8_buffer(f: impl FnOnce(&mut Vec<u8>)) -> String {
     let mut buf = Vec::new();
     f(&mut buf);
     String::from_utf8(buf).unwrap()
 }
 
-pub fn build_dashboard(account_to_utxos_start: u64) -> Vec<u8> {
+pub fn build_dashboard<N: DashboardNetwork>(account_to_utxos_start: u64) -> Vec<u8> {
     state::read_state(|s| {
         let html = format!(
         "
         <!DOCTYPE html>
         <html lang=\"en\">
             <head>
-                <title>ckBTC Minter Dashboard</title>
+                <title>{token} Minter Dashboard</title>
                 <style>
                     body {{
                         font-family: monospace;
@@ -64,14 +119,14 @@ pub fn build_dashboard(account_to_utxos_start: u64) -> Vec<u8> {
             </head>
             <body>
               <div class='background'><div class='content'>
-                <h2>ckBTC Minter Dashboard</h2>
+                <h2>{token} Minter Dashboard</h2>
                 <p>
-                    On the <a href=\"https://internetcomputer.org/ckbtc/\" target=\"_blank\">ckBTC</a> minter dashboard,
+                    On the {token_link} minter dashboard,
                     you can find all the information about the minter's current state, the available UTXOs, outgoing transactions, current parameters, and the logs.
                 </p>
                 <h3>Metadata</h3>
                 {}
-                <h3>Pending retrieve BTC requests</h3>
+                <h3>Pending retrieve {currency} requests</h3>
                      <table>
                         <thead>
                             <tr>
@@ -82,7 +137,7 @@ pub fn build_dashboard(account_to_utxos_start: u64) -> Vec<u8> {
                         </thead>
                         <tbody>{}</tbody>
                     </table>
-                <h3>In flight retrieve BTC requests</h3>
+                <h3>In flight retrieve {currency} requests</h3>
                 <table>
                     <thead>
                         <tr>
@@ -101,12 +156,12 @@ pub fn build_dashboard(account_to_utxos_start: u64) -> Vec<u8> {
                             <th>Input UTXO Txid</th>
                             <th>Input UTXO Vout</th>
                             <th>Input UTXO Height</th>
-                            <th>Input UTXO Value (BTC)</th>
+                            <th>Input UTXO Value ({currency})</th>
                         </tr>
                     </thead>
                     <tbody>{}</tbody>
                 </table>
-                <h3>Finalized retrieve BTC requests</h3>
+                <h3>Finalized retrieve {currency} requests</h3>
                 <table>
                     <thead>
                         <tr>
@@ -124,7 +179,7 @@ pub fn build_dashboard(account_to_utxos_start: u64) -> Vec<u8> {
                         <tr>
                             <th>Txid</th>
                             <th>Vout</th>
-                            <th>Value (BTC)</th>
+                            <th>Value ({currency})</th>
                         </tr>
                     </thead>
                     <tbody>{}</tbody>
@@ -136,7 +191,7 @@ pub fn build_dashboard(account_to_utxos_start: u64) -> Vec<u8> {
                             <th>Txid</th>
                             <th>Vout</th>
                             <th>Height</th>
-                            <th>Value (BTC)</th>
+                            <th>Value ({currency})</th>
                         </tr>
                     </thead>
                     <tbody>
@@ -150,7 +205,7 @@ pub fn build_dashboard(account_to_utxos_start: u64) -> Vec<u8> {
                             <th>Txid</th>
                             <th>Vout</th>
                             <th>Height</th>
-                            <th>Value (BTC)</th>
+                            <th>Value ({currency})</th>
                         </tr>
                     </thead>
                     <tbody>
@@ -164,7 +219,7 @@ pub fn build_dashboard(account_to_utxos_start: u64) -> Vec<u8> {
                             <th>Txid</th>
                             <th>Vout</th>
                             <th>Height</th>
-                            <th>Value (BTC)</th>
+                            <th>Value ({currency})</th>
                         </tr>
                     </thead>
                     <tbody>
@@ -179,30 +234,36 @@ pub fn build_dashboard(account_to_utxos_start: u64) -> Vec<u8> {
                             <th>Txid</th>
                             <th>Vout</th>
                             <th>Height</th>
-                            <th>Value (BTC)</th>
+                            <th>Value ({currency})</th>
                         </tr>
                     </thead>
                     <tbody>{}</tbody>
                 </table>
                 <h3>Update balance principals pending</h3>
                 <ul>{}</ul>
-                <h3>Retrieve BTC principals pending</h3>
+                <h3>Retrieve {currency} principals pending</h3>
                 <ul>{}</ul>
               </div></div>
             </body>
         </html>",
-        build_metadata(s),
-        build_pending_request_tx(s),
-        build_requests_in_flight_tx(s),
-        build_submitted_transactions(s),
-        build_finalized_requests(s),
-        build_unconfirmed_change(s),
-        build_mint_unknown_utxos(s),
-        build_quarantined_utxos(s),
-        build_ignored_utxos(s),
-        build_account_to_utxos_table(s, account_to_utxos_start, DEFAULT_PAGE_SIZE),
+        build_metadata::<N>(s),
+        build_pending_request_tx::<N>(s),
+        build_requests_in_flight_tx::<N>(s),
+        build_submitted_transactions::<N>(s),
+        build_finalized_requests::<N>(s),
+        build_unconfirmed_change::<N>(s),
+        build_mint_unknown_utxos::<N>(s),
+        build_quarantined_utxos::<N>(s),
+        build_ignored_utxos::<N>(s),
+        build_account_to_utxos_table::<N>(s, account_to_utxos_start, DEFAULT_PAGE_SIZE),
         build_update_balance_principals(s),
         build_retrieve_btc_principals(s),
+        token = N::TOKEN,
+        token_link = match N::TOKEN_URL {
+            Some(url) => format!("<a href=\"{url}\" target=\"_blank\">{}</a>", N::TOKEN),
+            None => N::TOKEN.to_string(),
+        },
+        currency = N::CURRENCY,
     );
         html.into_bytes()
     })
@@ -213,7 +274,11 @@ const DEFAULT_PAGE_SIZE: u64 = 100;
 
 /// Build the account-to-utxos table with pagination support.
 /// It will show at most [page_size] number of items, starting from the [start] index (inclusive).
-pub fn build_account_to_utxos_table(s: &CkBtcMinterState, start: u64, page_size: u64) -> String {
+pub fn build_account_to_utxos_table<N: DashboardNetwork>(
+    s: &CkBtcMinterState,
+    start: u64,
+    page_size: u64,
+) -> String {
     with_utf8_buffer(|buf| {
         let mut pagination = vec![];
         let mut total = 0;
@@ -250,7 +315,7 @@ pub fn build_account_to_utxos_table(s: &CkBtcMinterState, start: u64, page_size:
                     writeln!(
                         buf,
                         "<td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
-                        txid_link(s, &utxo.outpoint.txid),
+                        N::txid_link(&utxo.outpoint.txid, s),
                         utxo.outpoint.vout,
                         utxo.height,
                         DisplayAmount(utxo.value),
@@ -274,11 +339,7 @@ pub fn build_account_to_utxos_table(s: &CkBtcMinterState, start: u64, page_size:
     })
 }
 
-pub fn build_metadata(s: &CkBtcMinterState) -> String {
-    let main_account = Account {
-        owner: ic_cdk::api::canister_self(),
-        subaccount: None,
-    };
+pub fn build_metadata<N: DashboardNetwork>(s: &CkBtcMinterState) -> String {
     format!(
         "<table>
                 <tbody>
@@ -287,7 +348,7 @@ pub fn build_metadata(s: &CkBtcMinterState) -> String {
                         <td>{}</td>
                     </tr>
                     <tr>
-                        <th>Main address (do not send BTC here)</th>
+                        <th>Main address (do not send {currency} here)</th>
                         <td><code>{}</code></td>
                     </tr>
                     <tr>
@@ -299,7 +360,7 @@ pub fn build_metadata(s: &CkBtcMinterState) -> String {
                         <td><code>{}</code></td>
                     </tr>
                     <tr>
-                        <th>Bitcoin Checker Principal</th>
+                        <th>{network_name} Checker Principal</th>
                         <td><code>{}</code></td>
                     </tr>
                     <tr>
@@ -307,26 +368,25 @@ pub fn build_metadata(s: &CkBtcMinterState) -> String {
                         <td>{}</td>
                     </tr>
                     <tr>
-                        <th>Min retrieve BTC amount</th>
+                        <th>Min retrieve {currency} amount</th>
                         <td>{}</td>
                     </tr>
                     <tr>
-                        <th>Min retrieve BTC amount (fee based)</th>
+                        <th>Min retrieve {currency} amount (fee based)</th>
                         <td>{}</td>
                     </tr>
                     <tr>
-                        <th>Total BTC managed</th>
+                        <th>Total {currency} managed</th>
+                        <td>{}</td>
+                    </tr>
+                    <tr>
+                        <th>UTXO consolidation max fee (millisatoshi/vbyte)</th>
                         <td>{}</td>
                     </tr>
                 </tbody>
             </table>",
-        s.btc_network,
-        s.ecdsa_public_key
-            .clone()
-            .map(|key| {
-                address::account_to_bitcoin_address(&key, &main_account).display(s.btc_network)
-            })
-            .unwrap_or_default(),
+        N::network(s),
+        N::main_address(s),
         s.min_confirmations,
         s.ledger_id,
         s.btc_checker_principal
@@ -335,18 +395,23 @@ pub fn build_metadata(s: &CkBtcMinterState) -> String {
         DisplayAmount(s.check_fee),
         DisplayAmount(s.retrieve_btc_min_amount),
         DisplayAmount(s.fee_based_retrieve_btc_min_amount),
-        DisplayAmount(s.get_total_btc_managed())
+        DisplayAmount(s.get_total_btc_managed()),
+        s.utxo_consolidation_max_fee_per_vbyte
+            .map(|fee| fee.to_string())
+            .unwrap_or_else(|| "Disabled".to_string()),
+        currency = N::CURRENCY,
+        network_name = N::NETWORK_NAME,
     )
 }
 
-pub fn build_pending_request_tx(s: &CkBtcMinterState) -> String {
+pub fn build_pending_request_tx<N: DashboardNetwork>(s: &CkBtcMinterState) -> String {
     with_utf8_buffer(|buf| {
         for req in s.pending_retrieve_btc_requests.iter() {
             writeln!(
                 buf,
                 "<tr><td>{}</td><td><code>{}</code></td><td>{}</td></tr>",
                 req.block_index,
-                req.address.display(s.btc_network),
+                N::display_address(&req.address, s),
                 req.amount
             )
             .unwrap();
@@ -354,7 +419,7 @@ pub fn build_pending_request_tx(s: &CkBtcMinterState) -> String {
     })
 }
 
-pub fn build_requests_in_flight_tx(s: &CkBtcMinterState) -> String {
+pub fn build_requests_in_flight_tx<N: DashboardNetwork>(s: &CkBtcMinterState) -> String {
     with_utf8_buffer(|buf| {
         for (id, status) in &s.requests_in_flight {
             write!(buf, "<tr><td>{id}</td>").unwrap();
@@ -363,12 +428,7 @@ pub fn build_requests_in_flight_tx(s: &CkBtcMinterState) -> String {
                     write!(buf, "<td>Signing...</td>").unwrap();
                 }
                 state::InFlightStatus::Sending { txid } => {
-                    write!(
-                        buf,
-                        "<td>Sending TX {}</td>",
-                        txid_link_on(txid, s.btc_network)
-                    )
-                    .unwrap();
+                    write!(buf, "<td>Sending TX {}</td>", N::txid_link(txid, s)).unwrap();
                 }
             }
             writeln!(buf, "</tr>").unwrap();
@@ -376,7 +436,7 @@ pub fn build_requests_in_flight_tx(s: &CkBtcMinterState) -> String {
     })
 }
 
-pub fn build_submitted_transactions(s: &CkBtcMinterState) -> String {
+pub fn build_submitted_transactions<N: DashboardNetwork>(s: &CkBtcMinterState) -> String {
     with_utf8_buffer(|buf| {
         for tx in s.submitted_transactions.iter() {
             for (i, utxo) in tx.used_utxos.iter().enumerate() {
@@ -387,11 +447,14 @@ pub fn build_submitted_transactions(s: &CkBtcMinterState) -> String {
                         buf,
                         "<td rowspan='{}'>{}</td>",
                         rowspan,
-                        txid_link(s, &tx.txid)
+                        N::txid_link(&tx.txid, s)
                     )
                     .unwrap();
 
                     write!(buf, "<td rowspan='{rowspan}'>").unwrap();
+                    if tx.requests == state::SubmittedWithdrawalRequests::ToConsolidate {
+                        write!(buf, "<i>UTXO consolidation</i>").unwrap();
+                    }
                     for req in tx.requests.iter() {
                         write!(
                             buf,
@@ -403,7 +466,7 @@ pub fn build_submitted_transactions(s: &CkBtcMinterState) -> String {
                             </table>",
                             req.block_index,
                             DisplayAmount(req.amount),
-                            req.address.display(s.btc_network),
+                            N::display_address(&req.address, s),
                             req.received_at,
                         )
                         .unwrap();
@@ -413,7 +476,7 @@ pub fn build_submitted_transactions(s: &CkBtcMinterState) -> String {
                 writeln!(
                     buf,
                     "<td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
-                    txid_link(s, &utxo.outpoint.txid),
+                    N::txid_link(&utxo.outpoint.txid, s),
                     utxo.outpoint.vout,
                     utxo.height,
                     DisplayAmount(utxo.value),
@@ -424,7 +487,7 @@ pub fn build_submitted_transactions(s: &CkBtcMinterState) -> String {
     })
 }
 
-pub fn build_finalized_requests(s: &CkBtcMinterState) -> String {
+pub fn build_finalized_requests<N: DashboardNetwork>(s: &CkBtcMinterState) -> String {
     with_utf8_buffer(|buf| {
         for req in &s.finalized_requests {
             write!(
@@ -434,7 +497,7 @@ pub fn build_finalized_requests(s: &CkBtcMinterState) -> String {
                         <td><code>{}</code></td>
                         <td>{}</td>",
                 req.request.block_index,
-                req.request.address.display(s.btc_network),
+                N::display_address(&req.request.address, s),
                 DisplayAmount(req.request.amount),
             )
             .unwrap();
@@ -442,19 +505,16 @@ pub fn build_finalized_requests(s: &CkBtcMinterState) -> String {
                 state::FinalizedStatus::AmountTooLow => {
                     write!(buf, "<td>Amount is too low to cover fees</td>").unwrap()
                 }
-                state::FinalizedStatus::Confirmed { txid } => write!(
-                    buf,
-                    "<td>Confirmed {}</td>",
-                    txid_link_on(txid, s.btc_network)
-                )
-                .unwrap(),
+                state::FinalizedStatus::Confirmed { txid } => {
+                    write!(buf, "<td>Confirmed {}</td>", N::txid_link(txid, s)).unwrap()
+                }
             }
             writeln!(buf, "</tr>").unwrap();
         }
     })
 }
 
-pub fn build_mint_unknown_utxos(s: &CkBtcMinterState) -> String {
+pub fn build_mint_unknown_utxos<N: DashboardNetwork>(s: &CkBtcMinterState) -> String {
     with_utf8_buffer(|buf| {
         for utxo in s.mint_status_unknown_utxos() {
             writeln!(
@@ -465,7 +525,7 @@ pub fn build_mint_unknown_utxos(s: &CkBtcMinterState) -> String {
                     <td>{}</td>
                     <td>{}</td>
                 </tr>",
-                txid_link(s, &utxo.outpoint.txid),
+                N::txid_link(&utxo.outpoint.txid, s),
                 utxo.outpoint.vout,
                 utxo.height,
                 DisplayAmount(utxo.value)
@@ -475,7 +535,7 @@ pub fn build_mint_unknown_utxos(s: &CkBtcMinterState) -> String {
     })
 }
 
-pub fn build_quarantined_utxos(s: &CkBtcMinterState) -> String {
+pub fn build_quarantined_utxos<N: DashboardNetwork>(s: &CkBtcMinterState) -> String {
     with_utf8_buffer(|buf| {
         for utxo in s.quarantined_utxos() {
             writeln!(
@@ -486,7 +546,7 @@ pub fn build_quarantined_utxos(s: &CkBtcMinterState) -> String {
                     <td>{}</td>
                     <td>{}</td>
                 </tr>",
-                txid_link(s, &utxo.outpoint.txid),
+                N::txid_link(&utxo.outpoint.txid, s),
                 utxo.outpoint.vout,
                 utxo.height,
                 DisplayAmount(utxo.value)
@@ -496,7 +556,7 @@ pub fn build_quarantined_utxos(s: &CkBtcMinterState) -> String {
     })
 }
 
-pub fn build_ignored_utxos(s: &CkBtcMinterState) -> String {
+pub fn build_ignored_utxos<N: DashboardNetwork>(s: &CkBtcMinterState) -> String {
     with_utf8_buffer(|buf| {
         for utxo in s.ignored_utxos() {
             writeln!(
@@ -507,7 +567,7 @@ pub fn build_ignored_utxos(s: &CkBtcMinterState) -> String {
                     <td>{}</td>
                     <td>{}</td>
                 </tr>",
-                txid_link(s, &utxo.outpoint.txid),
+                N::txid_link(&utxo.outpoint.txid, s),
                 utxo.outpoint.vout,
                 utxo.height,
                 DisplayAmount(utxo.value)
@@ -517,7 +577,7 @@ pub fn build_ignored_utxos(s: &CkBtcMinterState) -> String {
     })
 }
 
-pub fn build_unconfirmed_change(s: &CkBtcMinterState) -> String {
+pub fn build_unconfirmed_change<N: DashboardNetwork>(s: &CkBtcMinterState) -> String {
     with_utf8_buffer(|buf| {
         let mut total = 0;
         for tx in &s.submitted_transactions {
@@ -525,7 +585,7 @@ pub fn build_unconfirmed_change(s: &CkBtcMinterState) -> String {
                 writeln!(
                     buf,
                     "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
-                    txid_link_on(&tx.txid, s.btc_network),
+                    N::txid_link(&tx.txid, s),
                     change.vout,
                     DisplayAmount(change.value)
                 )
@@ -558,10 +618,6 @@ pub fn build_retrieve_btc_principals(s: &CkBtcMinterState) -> String {
     })
 }
 
-fn txid_link(s: &CkBtcMinterState, txid: &Txid) -> String {
-    txid_link_on(txid, s.btc_network)
-}
-
 fn txid_link_on(txid: &Txid, btc_network: Network) -> String {
     let net_prefix = if btc_network == Network::Mainnet {
         ""
@@ -89,6 +89,31 @@ impl Drop for TimerLogicGuard {
     }
 }
 
+/// Prevents concurrent UTXO consolidations without blocking the other timer
+/// tasks while a consolidation transaction is being signed.
+#[must_use]
+pub struct ConsolidateUtxosGuard(());
+
+impl ConsolidateUtxosGuard {
+    pub fn new() -> Option<Self> {
+        mutate_state(|s| {
+            if s.is_consolidating_utxos {
+                return None;
+            }
+            s.is_consolidating_utxos = true;
+            Some(ConsolidateUtxosGuard(()))
+        })
+    }
+}
+
+impl Drop for ConsolidateUtxosGuard {
+    fn drop(&mut self) {
+        mutate_state(|s| {
+            s.is_consolidating_utxos = false;
+        });
+    }
+}
+
 pub fn balance_update_guard(account: Account) -> Result<Guard<PendingBalanceUpdates>, GuardError> {
     Guard::new(account)
 }
@@ -108,7 +133,7 @@ mod tests {
     use candid::Principal;
     use ic_base_types::CanisterId;
 
-    use super::{Account, TimerLogicGuard, balance_update_guard};
+    use super::{Account, ConsolidateUtxosGuard, TimerLogicGuard, balance_update_guard};
 
     fn test_principal(id: u64) -> Principal {
         Principal::try_from_slice(&id.to_le_bytes()).unwrap()
@@ -136,6 +161,7 @@ mod tests {
             kyt_principal: None,
             kyt_fee: None,
             get_utxos_cache_expiration_seconds: None,
+            utxo_consolidation_max_fee_per_vbyte: None,
         }
     }
 
@@ -192,4 +218,19 @@ mod tests {
         drop(guard);
         assert!(!read_state(|s| s.is_timer_running));
     }
+
+    #[test]
+    fn guard_consolidate_utxos_guard() {
+        init(test_state_args(), &IC_CANISTER_RUNTIME);
+
+        let guard = ConsolidateUtxosGuard::new().expect("could not grab consolidation guard");
+        assert!(ConsolidateUtxosGuard::new().is_none());
+        assert!(read_state(|s| s.is_consolidating_utxos));
+
+        // Consolidating UTXOs must not block the other timer tasks.
+        let _timer_guard = TimerLogicGuard::new().expect("could not grab timer logic guard");
+
+        drop(guard);
+        assert!(!read_state(|s| s.is_consolidating_utxos));
+    }
 }
@@ -3,6 +3,7 @@ use crate::logs::Priority;
 use crate::management::CallError;
 use crate::queries::WithdrawalFee;
 use crate::reimbursement::{InvalidTransactionError, WithdrawalReimbursementReason};
+use crate::updates::bump_withdrawal_fee::BumpWithdrawalFeeError;
 use crate::updates::update_balance::UpdateBalanceError;
 use async_trait::async_trait;
 use candid::{CandidType, Deserialize, Principal};
@@ -87,6 +88,19 @@ pub const UTXOS_COUNT_THRESHOLD: usize = 1_000;
 /// to ensure that the resulting signed transaction is standard.
 pub const MAX_NUM_INPUTS_IN_TRANSACTION: usize = 1_000;
 
+/// The minimum number of small UTXOs (i.e., with a value below `retrieve_btc_min_amount`)
+/// under management before the minter merges them in a consolidation transaction.
+pub const MIN_UTXOS_TO_CONSOLIDATE: usize = 100;
+
+/// Maximum number of UTXOs merged by a single consolidation transaction.
+pub const MAX_UTXOS_TO_CONSOLIDATE: usize = 500;
+
+/// Having a sequence number lower than (0xffffffff - 1) signals the use of replacement by fee.
+/// It allows us to increase the fee of a transaction already sent to the mempool.
+/// The rbf option is used in `resubmit_retrieve_btc`.
+/// https://github.com/bitcoin/bips/blob/master/bip-0125.mediawiki
+const SEQUENCE_RBF_ENABLED: u32 = 0xfffffffd;
+
 pub const IC_CANISTER_RUNTIME: IcCanisterRuntime = IcCanisterRuntime {};
 
 #[derive(Debug, CandidType, Deserialize, Serialize)]
@@ -280,6 +294,7 @@ fn reimburse_canceled_requests<R: CanisterRuntime>(
     requests: BTreeSet<state::RetrieveBtcRequest>,
     reason: WithdrawalReimbursementReason,
     total_fee: u64,
+    fee_bumps: BTreeMap<u64, u64>,
     runtime: &R,
 ) {
     assert!(!requests.is_empty());
@@ -293,7 +308,12 @@ fn reimburse_canceled_requests<R: CanisterRuntime>(
     );
     for (request, fee) in requests.into_iter().zip(fees.into_iter()) {
         if let Some(account) = request.reimbursement_account {
-            let amount = request.amount.saturating_sub(fee);
+            // The canceled transaction never spent the fee bumps of its requests.
+            let fee_bump = fee_bumps
+                .get(&request.block_index)
+                .copied()
+                .unwrap_or_default();
+            let amount = (request.amount + fee_bump).saturating_sub(fee);
             if amount > 0 {
                 state::audit::reimburse_withdrawal(
                     state,
@@ -323,9 +343,10 @@ pub fn confirm_transaction<R: CanisterRuntime>(
         reason,
         requests,
         fee,
+        fee_bumps,
     }) = state::audit::confirm_transaction(state, txid, runtime)
     {
-        reimburse_canceled_requests(state, requests, reason, fee, runtime)
+        reimburse_canceled_requests(state, requests, reason, fee, fee_bumps, runtime)
     }
 }
 
@@ -396,6 +417,7 @@ async fn submit_pending_requests<R: CanisterRuntime>(runtime: &R) {
                     batch,
                     reason,
                     REIMBURSEMENT_FEE_FOR_PENDING_WITHDRAWAL_REQUESTS,
+                    BTreeMap::new(),
                     runtime,
                 );
                 None
@@ -559,6 +581,158 @@ async fn submit_pending_requests<R: CanisterRuntime>(runtime: &R) {
     }
 }
 
+/// Merges small UTXOs of the minter into a single output to its main address
+/// if the median Bitcoin fee is below the configured threshold.
+///
+/// The minter pays the Bitcoin fee of the consolidation transaction out of the
+/// minter fees it collected on finalized withdrawals and skips the
+/// consolidation if these fees do not cover the Bitcoin fee.
+pub async fn consolidate_utxos<R: CanisterRuntime>(runtime: &R) {
+    let max_fee_per_vbyte = match state::read_state(|s| {
+        s.utxo_consolidation_max_fee_per_vbyte
+            .filter(|_| !s.has_pending_consolidation())
+    }) {
+        Some(fee) => fee,
+        None => return,
+    };
+    if state::read_state(|s| s.utxos_to_consolidate(MIN_UTXOS_TO_CONSOLIDATE).len())
+        < MIN_UTXOS_TO_CONSOLIDATE
+    {
+        return;
+    }
+
+    let fee_millisatoshi_per_vbyte = match estimate_fee_per_vbyte(runtime).await {
+        Some(fee) => fee,
+        None => return,
+    };
+    if fee_millisatoshi_per_vbyte > max_fee_per_vbyte {
+        log!(
+            Priority::Debug,
+            "[consolidate_utxos]: median fee {fee_millisatoshi_per_vbyte} is above the consolidation threshold {max_fee_per_vbyte}"
+        );
+        return;
+    }
+
+    let ecdsa_public_key = updates::get_btc_address::init_ecdsa_public_key().await;
+    let main_address = state::read_state(|s| runtime.derive_minter_address(s));
+    let fee_estimator = read_state(|s| runtime.fee_estimator(s));
+
+    let maybe_sign_request = state::mutate_state(|s| {
+        let utxos = s.utxos_to_consolidate(MAX_UTXOS_TO_CONSOLIDATE);
+        if utxos.len() < MIN_UTXOS_TO_CONSOLIDATE || s.has_pending_consolidation() {
+            return None;
+        }
+        match build_consolidation_transaction(
+            &utxos,
+            main_address,
+            fee_millisatoshi_per_vbyte,
+            &fee_estimator,
+        ) {
+            Ok((unsigned_tx, change_output, total_fee)) => {
+                let fee_budget = s.consolidation_fee_budget();
+                if total_fee.bitcoin_fee > fee_budget {
+                    log!(
+                        Priority::Debug,
+                        "[consolidate_utxos]: Bitcoin fee {} is above the collected minter fees {fee_budget}",
+                        total_fee.bitcoin_fee
+                    );
+                    return None;
+                }
+                for utxo in utxos.iter() {
+                    assert!(s.available_utxos.remove(utxo));
+                }
+                Some((
+                    SignTxRequest {
+                        key_name: s.ecdsa_key_name.clone(),
+                        ecdsa_public_key,
+                        change_output,
+                        network: s.btc_network,
+                        unsigned_tx,
+                        requests: BTreeSet::new(),
+                        utxos,
+                    },
+                    total_fee,
+                ))
+            }
+            Err(err) => {
+                log!(
+                    Priority::Info,
+                    "[consolidate_utxos]: failed to build a consolidation transaction for {} UTXOs: {:?}",
+                    utxos.len(),
+                    err
+                );
+                None
+            }
+        }
+    });
+
+    if let Some((req, total_fee)) = maybe_sign_request {
+        // This guard ensures that we return the UTXOs back to the state if
+        // signing or sending the transaction fails or panics.
+        let utxos_guard = guard(req.utxos, |utxos| {
+            undo_sign_request(BTreeSet::new(), utxos);
+        });
+
+        let txid = req.unsigned_tx.txid();
+        let signed_tx = match sign_transaction(
+            req.key_name,
+            &req.ecdsa_public_key,
+            |outpoint| state::read_state(|s| s.outpoint_account.get(outpoint).cloned()),
+            req.unsigned_tx,
+            runtime,
+        )
+        .await
+        {
+            Ok(signed_tx) => signed_tx,
+            Err(err) => {
+                log!(
+                    Priority::Info,
+                    "[consolidate_utxos]: failed to sign a Bitcoin transaction: {}",
+                    err
+                );
+                return;
+            }
+        };
+
+        log!(
+            Priority::Info,
+            "[consolidate_utxos]: sending a consolidation transaction {} merging {} UTXOs",
+            &txid,
+            utxos_guard.len()
+        );
+        match runtime.send_transaction(&signed_tx, req.network).await {
+            Ok(()) => {
+                // Defuse the guard because we sent the transaction
+                // successfully.
+                let used_utxos = ScopeGuard::into_inner(utxos_guard);
+
+                state::mutate_state(|s| {
+                    state::audit::sent_consolidation_transaction(
+                        s,
+                        state::SubmittedBtcTransaction {
+                            requests: state::SubmittedWithdrawalRequests::ToConsolidate,
+                            txid,
+                            used_utxos,
+                            change_output: Some(req.change_output),
+                            submitted_at: runtime.time(),
+                            fee_per_vbyte: Some(fee_millisatoshi_per_vbyte),
+                            withdrawal_fee: Some(total_fee),
+                        },
+                        runtime,
+                    );
+                });
+            }
+            Err(err) => {
+                log!(
+                    Priority::Info,
+                    "[consolidate_utxos]: failed to send a Bitcoin transaction: {}",
+                    err
+                );
+            }
+        }
+    }
+}
+
 fn finalization_time_estimate<R: CanisterRuntime>(
     min_confirmations: u32,
     network: Network,
@@ -646,7 +820,10 @@ async fn finalize_requests<R: CanisterRuntime>(runtime: &R, force_resubmit: bool
 
             s.submitted_transactions
                 .iter()
-                .filter(|&req| req.submitted_at + (wait_time.as_nanos() as u64) < now)
+                .filter(|&req| {
+                    req.submitted_at + (wait_time.as_nanos() as u64) < now
+                        || s.pending_fee_bumps.contains(&req.txid)
+                })
                 .map(|req| (req.txid, req.clone()))
                 .collect()
         });
@@ -680,8 +857,12 @@ async fn finalize_requests<R: CanisterRuntime>(runtime: &R, force_resubmit: bool
 
     // Do not replace transactions if less than MIN_RESUBMISSION_DELAY passed since their
     // submission. This strategy works around short-term fee spikes.
-    maybe_finalized_transactions.retain(|_txid, tx| {
-        force_resubmit || tx.submitted_at + MIN_RESUBMISSION_DELAY.as_nanos() as u64 <= now
+    // Transactions with a pending fee bump are replaced right away, the requester paid for it.
+    let pending_fee_bumps = state::read_state(|s| s.pending_fee_bumps.clone());
+    maybe_finalized_transactions.retain(|txid, tx| {
+        force_resubmit
+            || pending_fee_bumps.contains(txid)
+            || tx.submitted_at + MIN_RESUBMISSION_DELAY.as_nanos() as u64 <= now
     });
     if maybe_finalized_transactions.is_empty() {
         // There are no transactions eligible for replacement.
@@ -758,6 +939,7 @@ async fn finalize_requests<R: CanisterRuntime>(runtime: &R, force_resubmit: bool
         state::read_state(|s| s.retrieve_btc_min_amount),
         maybe_finalized_transactions,
         |outpoint| state::read_state(|s| s.outpoint_account.get(outpoint).cloned()),
+        |requests| state::read_state(|s| s.withdrawal_fee_bump(requests)),
         |old_txid, new_tx, reason| {
             state::mutate_state(|s| {
                 state::audit::replace_transaction(s, old_txid, new_tx, reason, runtime);
@@ -772,6 +954,7 @@ async fn finalize_requests<R: CanisterRuntime>(runtime: &R, force_resubmit: bool
 pub async fn resubmit_transactions<
     R: CanisterRuntime,
     F: Fn(&OutPoint) -> Option<Account>,
+    B: Fn(&state::SubmittedWithdrawalRequests) -> u64,
     G: Fn(Txid, state::SubmittedBtcTransaction, state::eventlog::ReplacedReason),
     Fee: FeeEstimator,
 >(
@@ -783,6 +966,7 @@ pub async fn resubmit_transactions<
     retrieve_btc_min_amount: u64,
     transactions: BTreeMap<Txid, state::SubmittedBtcTransaction>,
     lookup_outpoint_account: F,
+    lookup_fee_bump: B,
     replace_transaction: G,
     runtime: &R,
     fee_estimator: &Fee,
@@ -805,18 +989,29 @@ pub async fn resubmit_transactions<
             state::SubmittedWithdrawalRequests::ToCancel { .. } => {
                 vec![(main_address.clone(), retrieve_btc_min_amount)]
             }
+            // The only output of a consolidation transaction goes to the main address.
+            state::SubmittedWithdrawalRequests::ToConsolidate => vec![],
         };
 
         let mut input_utxos = submitted_tx.used_utxos;
         let mut replaced_reason = state::eventlog::ReplacedReason::ToRetry;
         let mut new_tx_requests = submitted_tx.requests;
-        let build_result = match build_unsigned_transaction_from_inputs(
-            &input_utxos,
-            outputs,
-            main_address.clone(),
-            tx_fee_per_vbyte,
-            fee_estimator,
-        ) {
+        let build_result = match &new_tx_requests {
+            state::SubmittedWithdrawalRequests::ToConsolidate => build_consolidation_transaction(
+                &input_utxos,
+                main_address.clone(),
+                tx_fee_per_vbyte,
+                fee_estimator,
+            ),
+            _ => build_unsigned_transaction_from_inputs(
+                &input_utxos,
+                outputs,
+                main_address.clone(),
+                tx_fee_per_vbyte,
+                fee_estimator,
+            ),
+        };
+        let build_result = match build_result {
             Err(BuildTxError::InvalidTransaction(err)) => {
                 log!(
                     Priority::Info,
@@ -836,6 +1031,9 @@ pub async fn resubmit_transactions<
                     state::SubmittedWithdrawalRequests::ToCancel { .. } => {
                         unreachable!("cancellation tx never has too many inputs!")
                     }
+                    state::SubmittedWithdrawalRequests::ToConsolidate => {
+                        unreachable!("consolidation tx never has too many inputs!")
+                    }
                 };
                 let reason = reimbursement::WithdrawalReimbursementReason::InvalidTransaction(err);
                 replaced_reason = state::eventlog::ReplacedReason::ToCancel {
@@ -853,6 +1051,16 @@ pub async fn resubmit_transactions<
             }
             result => result,
         };
+        let build_result =
+            build_result.and_then(|(mut unsigned_tx, mut change_output, mut total_fee)| {
+                apply_fee_bump::<Fee>(
+                    &mut unsigned_tx,
+                    &mut change_output,
+                    &mut total_fee,
+                    lookup_fee_bump(&new_tx_requests),
+                )?;
+                Ok((unsigned_tx, change_output, total_fee))
+            });
         let (unsigned_tx, change_output, total_fee) = match build_result {
             Ok(tx) => tx,
             // If it's impossible to build a new transaction, the fees probably became too high.
@@ -1190,11 +1398,6 @@ pub fn build_unsigned_transaction_from_inputs<F: FeeEstimator>(
     fee_estimator: &F,
 ) -> Result<(tx::UnsignedTransaction, state::ChangeOutput, WithdrawalFee), BuildTxError> {
     assert!(!outputs.is_empty());
-    /// Having a sequence number lower than (0xffffffff - 1) signals the use of replacement by fee.
-    /// It allows us to increase the fee of a transaction already sent to the mempool.
-    /// The rbf option is used in `resubmit_retrieve_btc`.
-    /// https://github.com/bitcoin/bips/blob/master/bip-0125.mediawiki
-    const SEQUENCE_RBF_ENABLED: u32 = 0xfffffffd;
 
     let amount = outputs.iter().map(|(_, amount)| amount).sum::<u64>();
 
@@ -1297,6 +1500,146 @@ pub fn build_unsigned_transaction_from_inputs<F: FeeEstimator>(
     ))
 }
 
+/// Builds a transaction that merges the specified UTXOs into a single output
+/// to the minter's main address. The minter pays the transaction fee and does
+/// not charge a minter fee.
+///
+/// The only output of the transaction is returned as the change output so that
+/// the minter can finalize the transaction as it does for withdrawals.
+pub fn build_consolidation_transaction<F: FeeEstimator>(
+    input_utxos: &[Utxo],
+    main_address: BitcoinAddress,
+    fee_per_vbyte: u64,
+    fee_estimator: &F,
+) -> Result<(tx::UnsignedTransaction, state::ChangeOutput, WithdrawalFee), BuildTxError> {
+    let num_inputs = input_utxos.len();
+    if num_inputs == 0 {
+        return Err(BuildTxError::NotEnoughFunds);
+    }
+    if num_inputs > MAX_NUM_INPUTS_IN_TRANSACTION {
+        return Err(BuildTxError::InvalidTransaction(
+            InvalidTransactionError::TooManyInputs {
+                max_num_inputs: MAX_NUM_INPUTS_IN_TRANSACTION,
+                num_inputs,
+            },
+        ));
+    }
+
+    let inputs_value = input_utxos.iter().map(|u| u.value).sum::<u64>();
+
+    let mut unsigned_tx = tx::UnsignedTransaction {
+        inputs: input_utxos
+            .iter()
+            .map(|utxo| tx::UnsignedInput {
+                previous_output: utxo.outpoint.clone(),
+                value: utxo.value,
+                sequence: SEQUENCE_RBF_ENABLED,
+            })
+            .collect(),
+        outputs: vec![tx::TxOut {
+            address: main_address,
+            value: inputs_value,
+        }],
+        lock_time: 0,
+    };
+
+    let fee = fee_estimator.evaluate_transaction_fee(&unsigned_tx, fee_per_vbyte);
+
+    if inputs_value <= fee + F::DUST_LIMIT {
+        return Err(BuildTxError::AmountTooLow);
+    }
+
+    let change_output = state::ChangeOutput {
+        vout: 0,
+        value: inputs_value - fee,
+    };
+    unsigned_tx.outputs[0].value = change_output.value;
+
+    Ok((
+        unsigned_tx,
+        change_output,
+        WithdrawalFee {
+            bitcoin_fee: fee,
+            minter_fee: 0,
+        },
+    ))
+}
+
+/// Pays the extra fee bought by the requesters of a withdrawal out of the
+/// change output of the transaction.
+pub fn apply_fee_bump<F: FeeEstimator>(
+    unsigned_tx: &mut tx::UnsignedTransaction,
+    change_output: &mut state::ChangeOutput,
+    withdrawal_fee: &mut WithdrawalFee,
+    fee_bump: u64,
+) -> Result<(), BuildTxError> {
+    if fee_bump == 0 {
+        return Ok(());
+    }
+    let output = &mut unsigned_tx.outputs[change_output.vout as usize];
+    debug_assert_eq!(output.value, change_output.value);
+    if output.value <= fee_bump + F::DUST_LIMIT {
+        return Err(BuildTxError::DustOutput {
+            address: output.address.clone(),
+            amount: output.value,
+        });
+    }
+    output.value -= fee_bump;
+    change_output.value -= fee_bump;
+    withdrawal_fee.bitcoin_fee += fee_bump;
+    Ok(())
+}
+
+/// Reconstructs the unsigned transaction of a submitted withdrawal.
+///
+/// Returns `None` if the minter does not have enough data to rebuild the
+/// exact transaction it sent, e.g., for transactions submitted before the
+/// minter recorded fees or for cancellation transactions.
+pub fn rebuild_unsigned_transaction<F: FeeEstimator>(
+    submitted_tx: &state::SubmittedBtcTransaction,
+    main_address: BitcoinAddress,
+    fee_estimator: &F,
+) -> Option<tx::UnsignedTransaction> {
+    let fee_per_vbyte = submitted_tx.fee_per_vbyte?;
+    let withdrawal_fee = submitted_tx.withdrawal_fee?;
+    let (mut unsigned_tx, mut change_output, mut total_fee) = match &submitted_tx.requests {
+        state::SubmittedWithdrawalRequests::ToConfirm { requests } => {
+            build_unsigned_transaction_from_inputs(
+                &submitted_tx.used_utxos,
+                requests
+                    .iter()
+                    .map(|req| (req.address.clone(), req.amount))
+                    .collect(),
+                main_address,
+                fee_per_vbyte,
+                fee_estimator,
+            )
+            .ok()?
+        }
+        state::SubmittedWithdrawalRequests::ToConsolidate => build_consolidation_transaction(
+            &submitted_tx.used_utxos,
+            main_address,
+            fee_per_vbyte,
+            fee_estimator,
+        )
+        .ok()?,
+        state::SubmittedWithdrawalRequests::ToCancel { .. } => return None,
+    };
+    // The difference with the recorded fee is the fee bump applied when the minter sent the
+    // transaction.
+    let fee_bump = withdrawal_fee
+        .bitcoin_fee
+        .checked_sub(total_fee.bitcoin_fee)?;
+    apply_fee_bump::<F>(
+        &mut unsigned_tx,
+        &mut change_output,
+        &mut total_fee,
+        fee_bump,
+    )
+    .ok()?;
+    (unsigned_tx.txid() == submitted_tx.txid).then_some(unsigned_tx)
+}
+
 /// Distributes an amount across the specified number of shares as fairly as
 /// possible.
 ///
@@ -1442,6 +1785,17 @@ pub trait CanisterRuntime {
         memo: Memo,
     ) -> Result<u64, UpdateBalanceError>;
 
+    /// Burns the extra fee of a bump_withdrawal_fee request from the requester
+    /// and returns the index of the burn block.
+    async fn burn_withdrawal_fee_bump(
+        &self,
+        from: Account,
+        amount: u64,
+        memo: Memo,
+    ) -> Result<u64, BumpWithdrawalFeeError> {
+        updates::bump_withdrawal_fee::burn_ckbtcs_icrc2(from, amount, memo).await
+    }
+
     async fn sign_with_ecdsa(
         &self,
         key_name: String,
@@ -49,6 +49,11 @@ pub struct UpgradeArgs {
     /// the get_utxos cache.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub get_utxos_cache_expiration_seconds: Option<u64>,
+
+    /// The maximum median fee per vbyte (in millisatoshi) at which the minter
+    /// consolidates its small UTXOs into fewer outputs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utxo_consolidation_max_fee_per_vbyte: Option<u64>,
 }
 
 pub fn post_upgrade<R: CanisterRuntime>(upgrade_args: Option<UpgradeArgs>, runtime: &R) {
@@ -3,13 +3,18 @@ use ic_btc_interface::Utxo;
 use ic_cdk::{init, post_upgrade, query, update};
 use ic_ckbtc_minter::lifecycle::upgrade::UpgradeArgs;
 use ic_ckbtc_minter::lifecycle::{self, init::MinterArg};
-use ic_ckbtc_minter::queries::{EstimateFeeArg, RetrieveBtcStatusRequest, WithdrawalFee};
+use ic_ckbtc_minter::queries::{
+    EstimateFeeArg, GetWithdrawalPsbtError, RetrieveBtcStatusRequest, WithdrawalFee, WithdrawalPsbt,
+};
 use ic_ckbtc_minter::reimbursement::InvalidTransactionError;
 use ic_ckbtc_minter::state::eventlog::Event;
 use ic_ckbtc_minter::state::{
     BtcRetrievalStatusV2, RetrieveBtcStatus, RetrieveBtcStatusV2, read_state,
 };
 use ic_ckbtc_minter::tasks::{TaskType, schedule_now};
+use ic_ckbtc_minter::updates::bump_withdrawal_fee::{
+    BumpWithdrawalFeeArgs, BumpWithdrawalFeeError, BumpWithdrawalFeeOk,
+};
 use ic_ckbtc_minter::updates::retrieve_btc::{
     RetrieveBtcArgs, RetrieveBtcError, RetrieveBtcOk, RetrieveBtcWithApprovalArgs,
     RetrieveBtcWithApprovalError,
@@ -47,6 +52,7 @@ fn init(args: MinterArg) {
 fn setup_tasks() {
     schedule_now(TaskType::ProcessLogic(true), &IC_CANISTER_RUNTIME);
     schedule_now(TaskType::RefreshFeePercentiles, &IC_CANISTER_RUNTIME);
+    schedule_now(TaskType::ConsolidateUtxos, &IC_CANISTER_RUNTIME);
 }
 
 #[cfg(feature = "self_check")]
@@ -159,6 +165,25 @@ async fn retrieve_btc_with_approval(
     )
 }
 
+#[update]
+async fn bump_withdrawal_fee(
+    args: BumpWithdrawalFeeArgs,
+) -> Result<BumpWithdrawalFeeOk, BumpWithdrawalFeeError> {
+    check_anonymous_caller();
+    check_postcondition(
+        updates::bump_withdrawal_fee::bump_withdrawal_fee(args, &IC_CANISTER_RUNTIME).await,
+    )
+}
+
+#[query]
+fn get_withdrawal_psbt(
+    req: RetrieveBtcStatusRequest,
+) -> Result<WithdrawalPsbt, GetWithdrawalPsbtError> {
+    read_state(|s| {
+        ic_ckbtc_minter::queries::get_withdrawal_psbt(s, req.block_index, &IC_CANISTER_RUNTIME)
+    })
+}
+
 #[query]
 fn retrieve_btc_status(req: RetrieveBtcStatusRequest) -> RetrieveBtcStatus {
     read_state(|s| s.retrieve_btc_status(req.block_index))
@@ -80,4 +80,12 @@ pub enum BurnMemo<'a> {
         /// The status of the Bitcoin check.
         status: Option<Status>,
     },
+    #[n(1)]
+    /// The requester of a retrieve_btc request paid an extra Bitcoin fee.
+    BumpWithdrawalFee {
+        #[n(0)]
+        /// The id corresponding to the withdrawal request,
+        /// which corresponds to the ledger burn index.
+        withdrawal_id: LedgerBurnIndex,
+    },
 }
@@ -1,12 +1,14 @@
-use crate::dashboard::build_dashboard;
+use crate::dashboard::{Bitcoin, build_dashboard};
 use crate::metrics::encode_metrics;
-use crate::state::read_state;
+use crate::state::{CkBtcMinterState, read_state};
 use crate::updates::update_balance::UpdateBalanceArgs;
+use crate::{CanisterRuntime, address, tx};
 use candid::CandidType;
-use ic_btc_interface::Utxo;
+use ic_btc_interface::{Txid, Utxo};
 use ic_http_types::{HttpRequest, HttpResponse, HttpResponseBuilder};
 use icrc_ledger_types::icrc1::account::Account;
 use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
 
 #[derive(CandidType, Deserialize)]
 pub struct RetrieveBtcStatusRequest {
@@ -24,6 +26,68 @@ pub struct WithdrawalFee {
     pub bitcoin_fee: u64,
 }
 
+/// The unsigned transaction serving a submitted retrieve_btc request.
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct WithdrawalPsbt {
+    /// The identifier of the transaction.
+    pub txid: Txid,
+    /// The transaction encoded as a Partially Signed Bitcoin Transaction (BIP-174).
+    pub psbt: ByteBuf,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub enum GetWithdrawalPsbtError {
+    /// The minter has no submitted transaction serving the request.
+    NotSubmitted,
+    /// The minter cannot reconstruct the transaction serving the request.
+    Unavailable,
+}
+
+pub fn get_withdrawal_psbt<R: CanisterRuntime>(
+    state: &CkBtcMinterState,
+    block_index: u64,
+    runtime: &R,
+) -> Result<WithdrawalPsbt, GetWithdrawalPsbtError> {
+    let submitted_tx = state
+        .find_submitted_transaction(block_index)
+        .ok_or(GetWithdrawalPsbtError::NotSubmitted)?;
+    let ecdsa_public_key = state
+        .ecdsa_public_key
+        .as_ref()
+        .ok_or(GetWithdrawalPsbtError::Unavailable)?;
+    let unsigned_tx = crate::rebuild_unsigned_transaction(
+        submitted_tx,
+        runtime.derive_minter_address(state),
+        &runtime.fee_estimator(state),
+    )
+    .ok_or(GetWithdrawalPsbtError::Unavailable)?;
+    let spent_outputs = submitted_tx
+        .used_utxos
+        .iter()
+        .map(|utxo| {
+            let account = state
+                .outpoint_account
+                .get(&utxo.outpoint)
+                .ok_or(GetWithdrawalPsbtError::Unavailable)?;
+            Ok(tx::TxOut {
+                value: utxo.value,
+                address: address::account_to_bitcoin_address(ecdsa_public_key, account),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(WithdrawalPsbt {
+        txid: submitted_tx.txid,
+        psbt: ByteBuf::from(
+            tx::PartiallySignedTransaction {
+                unsigned_tx: &unsigned_tx,
+                spent_outputs,
+            }
+            .serialize(),
+        ),
+    })
+}
+
 pub fn get_known_utxos(args: UpdateBalanceArgs) -> Vec<Utxo> {
     read_state(|s| {
         s.known_utxos_for_account(&Account {
@@ -67,7 +131,7 @@ pub fn http_request(req: HttpRequest) -> HttpResponse {
                 },
                 None => 0,
             };
-            let dashboard: Vec<u8> = build_dashboard(account_to_utxos_start);
+            let dashboard: Vec<u8> = build_dashboard::<Bitcoin>(account_to_utxos_start);
             HttpResponseBuilder::ok()
                 .header("Content-Type", "text/html; charset=utf-8")
                 .with_body_and_content_length(dashboard)
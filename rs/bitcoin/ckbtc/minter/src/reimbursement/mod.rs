@@ -40,6 +40,10 @@ pub enum ReimbursedError {
 pub enum WithdrawalReimbursementReason {
     #[serde(rename = "invalid_transaction")]
     InvalidTransaction(InvalidTransactionError),
+    /// The withdrawal left the set of submitted transactions before the
+    /// minter could apply the fee bump paid by the requester.
+    #[serde(rename = "fee_bump_not_applied")]
+    FeeBumpNotApplied,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize, candid::CandidType)]
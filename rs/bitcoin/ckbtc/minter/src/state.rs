@@ -92,6 +92,9 @@ pub enum SubmittedWithdrawalRequests {
         requests: BTreeSet<RetrieveBtcRequest>,
         reason: WithdrawalReimbursementReason,
     },
+    /// The transaction merges small UTXOs of the minter and does not serve any
+    /// retrieve_btc request.
+    ToConsolidate,
 }
 
 impl From<Vec<RetrieveBtcRequest>> for SubmittedWithdrawalRequests {
@@ -107,6 +110,7 @@ impl SubmittedWithdrawalRequests {
         match self {
             Self::ToConfirm { requests } => requests.iter(),
             Self::ToCancel { requests, .. } => requests.iter(),
+            Self::ToConsolidate => Default::default(),
         }
     }
 
@@ -114,6 +118,7 @@ impl SubmittedWithdrawalRequests {
         match self {
             Self::ToConfirm { requests } => requests.is_empty(),
             Self::ToCancel { requests, .. } => requests.is_empty(),
+            Self::ToConsolidate => true,
         }
     }
 
@@ -121,6 +126,7 @@ impl SubmittedWithdrawalRequests {
         match self {
             Self::ToConfirm { requests } => requests.len(),
             Self::ToCancel { requests, .. } => requests.len(),
+            Self::ToConsolidate => 0,
         }
     }
 }
@@ -207,7 +213,10 @@ impl From<RetrieveBtcStatus> for RetrieveBtcStatusV2 {
             RetrieveBtcStatus::Pending => RetrieveBtcStatusV2::Pending,
             RetrieveBtcStatus::Signing => RetrieveBtcStatusV2::Signing,
             RetrieveBtcStatus::Sending { txid } => RetrieveBtcStatusV2::Sending { txid },
-            RetrieveBtcStatus::Submitted { txid } => RetrieveBtcStatusV2::Submitted { txid },
+            RetrieveBtcStatus::Submitted { txid } => RetrieveBtcStatusV2::Submitted {
+                txid,
+                replaced_txids: None,
+            },
             RetrieveBtcStatus::AmountTooLow => RetrieveBtcStatusV2::AmountTooLow,
             RetrieveBtcStatus::Confirmed { txid } => RetrieveBtcStatusV2::Confirmed { txid },
         }
@@ -226,7 +235,13 @@ pub enum RetrieveBtcStatusV2 {
     /// Sending the transaction satisfying this request.
     Sending { txid: Txid },
     /// Awaiting for confirmations on the transaction satisfying this request.
-    Submitted { txid: Txid },
+    Submitted {
+        txid: Txid,
+        /// The identifiers of the transactions that `txid` replaced, from
+        /// the oldest to the most recent one. Not set if the minter has not
+        /// replaced the original transaction.
+        replaced_txids: Option<Vec<Txid>>,
+    },
     /// The retrieval amount was too low. Satisfying the request is impossible.
     AmountTooLow,
     /// Confirmed a transaction satisfying this request.
@@ -382,6 +397,13 @@ pub struct CkBtcMinterState {
     /// Maps ID of a replacement transaction to the ID of the corresponding stuck transaction.
     pub rev_replacement_txid: BTreeMap<Txid, Txid>,
 
+    /// Maps the block index of a submitted retrieve_btc request to the total
+    /// amount (in satoshi) the requester paid to increase the transaction fee.
+    pub withdrawal_fee_bumps: BTreeMap<u64, u64>,
+
+    /// Submitted transactions that the minter must replace to apply a fee bump.
+    pub pending_fee_bumps: BTreeSet<Txid>,
+
     /// Finalized retrieve_btc requests for which we received enough confirmations.
     pub finalized_requests: VecDeque<FinalizedBtcRetrieval>,
 
@@ -394,6 +416,13 @@ pub struct CkBtcMinterState {
     /// The total amount of ckBTC burned.
     pub tokens_burned: u64,
 
+    /// The total minter fee (in satoshi) of finalized withdrawal transactions.
+    pub minter_fees_collected: u64,
+
+    /// The total Bitcoin fee (in satoshi) of finalized consolidation
+    /// transactions, paid out of [`Self::minter_fees_collected`].
+    pub consolidation_fees_paid: u64,
+
     /// The CanisterId of the ckBTC Ledger.
     pub ledger_id: CanisterId,
 
@@ -424,6 +453,9 @@ pub struct CkBtcMinterState {
 
     pub is_distributing_fee: bool,
 
+    /// Consolidate UTXOs in one timer event at a time.
+    pub is_consolidating_utxos: bool,
+
     /// The mode in which the minter runs.
     pub mode: Mode,
 
@@ -432,6 +464,10 @@ pub struct CkBtcMinterState {
     /// The last median fee per vbyte computed from `last_fee_per_vbyte`.
     pub last_median_fee_per_vbyte: Option<u64>,
 
+    /// The maximum median fee per vbyte (in millisatoshi) at which the minter
+    /// consolidates its small UTXOs. Consolidation is disabled if not set.
+    pub utxo_consolidation_max_fee_per_vbyte: Option<u64>,
+
     /// The fee for a single Bitcoin check request.
     pub check_fee: u64,
 
@@ -497,6 +533,8 @@ pub struct WithdrawalCancellation {
     pub fee: u64,
     pub reason: WithdrawalReimbursementReason,
     pub requests: BTreeSet<RetrieveBtcRequest>,
+    /// The fee bumps paid for the canceled requests, indexed by block index.
+    pub fee_bumps: BTreeMap<u64, u64>,
 }
 
 impl CkBtcMinterState {
@@ -516,6 +554,7 @@ impl CkBtcMinterState {
             kyt_principal: _,
             kyt_fee,
             get_utxos_cache_expiration_seconds,
+            utxo_consolidation_max_fee_per_vbyte,
         }: InitArgs,
     ) {
         self.btc_network = btc_network;
@@ -538,6 +577,7 @@ impl CkBtcMinterState {
             self.get_utxos_cache
                 .set_expiration(Duration::from_secs(expiration));
         }
+        self.utxo_consolidation_max_fee_per_vbyte = utxo_consolidation_max_fee_per_vbyte;
     }
 
     #[allow(deprecated)]
@@ -553,6 +593,7 @@ impl CkBtcMinterState {
             kyt_principal: _,
             kyt_fee,
             get_utxos_cache_expiration_seconds,
+            utxo_consolidation_max_fee_per_vbyte,
         }: UpgradeArgs,
     ) {
         if let Some(retrieve_btc_min_amount) = retrieve_btc_min_amount {
@@ -589,6 +630,9 @@ impl CkBtcMinterState {
             self.get_utxos_cache
                 .set_expiration(Duration::from_secs(expiration));
         }
+        if let Some(max_fee_per_vbyte) = utxo_consolidation_max_fee_per_vbyte {
+            self.utxo_consolidation_max_fee_per_vbyte = Some(max_fee_per_vbyte);
+        }
     }
 
     pub fn validate_config(&self) {
@@ -662,7 +706,10 @@ impl CkBtcMinterState {
             match reason {
                 WithdrawalReimbursementReason::InvalidTransaction(
                     InvalidTransactionError::TooManyInputs { .. },
-                ) => ReimbursementReason::CallFailed,
+                )
+                | WithdrawalReimbursementReason::FeeBumpNotApplied => {
+                    ReimbursementReason::CallFailed
+                }
             }
         }
 
@@ -696,7 +743,16 @@ impl CkBtcMinterState {
             };
         }
 
-        let status_v2: RetrieveBtcStatusV2 = self.retrieve_btc_status(block_index).into();
+        let status_v2: RetrieveBtcStatusV2 = match self.retrieve_btc_status(block_index) {
+            RetrieveBtcStatus::Submitted { txid } => {
+                let replaced_txids = self.replaced_txids(&txid);
+                RetrieveBtcStatusV2::Submitted {
+                    txid,
+                    replaced_txids: (!replaced_txids.is_empty()).then_some(replaced_txids),
+                }
+            }
+            status => status.into(),
+        };
 
         status_v2
     }
@@ -719,17 +775,8 @@ impl CkBtcMinterState {
             };
         }
 
-        if let Some(txid) = self
-            .submitted_transactions
-            .iter()
-            .find_map(|tx| match &tx.requests {
-                SubmittedWithdrawalRequests::ToConfirm { requests } => {
-                    (requests.iter().any(|r| r.block_index == block_index)).then_some(tx.txid)
-                }
-                SubmittedWithdrawalRequests::ToCancel { .. } => None,
-            })
-        {
-            return RetrieveBtcStatus::Submitted { txid };
+        if let Some(tx) = self.find_submitted_transaction(block_index) {
+            return RetrieveBtcStatus::Submitted { txid: tx.txid };
         }
 
         match self
@@ -785,6 +832,33 @@ impl CkBtcMinterState {
         batch
     }
 
+    /// Returns up to `max_count` available UTXOs whose value is below
+    /// `retrieve_btc_min_amount`, smallest first.
+    pub fn utxos_to_consolidate(&self, max_count: usize) -> Vec<Utxo> {
+        let mut small_utxos: Vec<_> = self
+            .available_utxos
+            .iter()
+            .filter(|utxo| utxo.value < self.retrieve_btc_min_amount)
+            .collect();
+        small_utxos.sort_by_key(|utxo| utxo.value);
+        small_utxos.into_iter().take(max_count).cloned().collect()
+    }
+
+    /// Returns the amount (in satoshi) of collected minter fees that the
+    /// minter can still spend on the Bitcoin fees of consolidation transactions.
+    pub fn consolidation_fee_budget(&self) -> u64 {
+        self.minter_fees_collected
+            .saturating_sub(self.consolidation_fees_paid)
+    }
+
+    /// Returns true if a UTXO consolidation transaction is waiting for
+    /// finalization.
+    pub fn has_pending_consolidation(&self) -> bool {
+        self.submitted_transactions
+            .iter()
+            .any(|tx| tx.requests == SubmittedWithdrawalRequests::ToConsolidate)
+    }
+
     /// Returns the total number of all retrieve_btc requests that we haven't
     /// finalized yet.
     pub fn count_incomplete_retrieve_btc_requests(&self) -> usize {
@@ -851,10 +925,15 @@ impl CkBtcMinterState {
         for utxo in finalized_tx.used_utxos.iter() {
             self.forget_utxo(utxo);
         }
-        match finalized_tx.requests {
+        let cancellation = match finalized_tx.requests {
             SubmittedWithdrawalRequests::ToConfirm { requests } => {
                 self.finalized_requests_count += requests.len() as u64;
+                self.minter_fees_collected += finalized_tx
+                    .withdrawal_fee
+                    .map(|fee| fee.minter_fee)
+                    .unwrap_or_default();
                 for request in requests {
+                    self.withdrawal_fee_bumps.remove(&request.block_index);
                     self.push_finalized_request(FinalizedBtcRetrieval {
                         request,
                         state: FinalizedStatus::Confirmed { txid: *txid },
@@ -877,13 +956,40 @@ impl CkBtcMinterState {
                     canceled_requests, requests,
                     "Cancelled requests set does not match what was in cancellation tx {txid}"
                 );
+                let fee_bumps = requests
+                    .iter()
+                    .filter_map(|req| {
+                        self.withdrawal_fee_bumps
+                            .remove(&req.block_index)
+                            .map(|amount| (req.block_index, amount))
+                    })
+                    .collect();
                 Some(WithdrawalCancellation {
                     reason,
                     fee,
                     requests,
+                    fee_bumps,
                 })
             }
-        }
+            SubmittedWithdrawalRequests::ToConsolidate => {
+                self.consolidation_fees_paid += finalized_tx
+                    .withdrawal_fee
+                    .map(|fee| fee.bitcoin_fee)
+                    .unwrap_or_default();
+                self.cleanup_tx_replacement_chain(txid);
+                None
+            }
+        };
+
+        let submitted_txids: BTreeSet<_> = self
+            .submitted_transactions
+            .iter()
+            .map(|tx| tx.txid)
+            .collect();
+        self.pending_fee_bumps
+            .retain(|txid| submitted_txids.contains(txid));
+
+        cancellation
     }
 
     // Return removed txids
@@ -947,7 +1053,8 @@ impl CkBtcMinterState {
                 }
                 let mut requests = match &tx.requests {
                     SubmittedWithdrawalRequests::ToConfirm { requests } => requests.clone(),
-                    SubmittedWithdrawalRequests::ToCancel { .. } => BTreeSet::new(),
+                    SubmittedWithdrawalRequests::ToCancel { .. }
+                    | SubmittedWithdrawalRequests::ToConsolidate => BTreeSet::new(),
                 };
                 canceled_requests.append(&mut requests);
             }
@@ -1000,6 +1107,61 @@ impl CkBtcMinterState {
         self.stuck_transactions.push(tx);
         self.replacement_txid.insert(*old_txid, new_txid);
         self.rev_replacement_txid.insert(new_txid, *old_txid);
+        self.pending_fee_bumps.remove(old_txid);
+    }
+
+    /// Returns the identifiers of the transactions that the given transaction
+    /// replaced, from the oldest to the most recent one.
+    pub fn replaced_txids(&self, txid: &Txid) -> Vec<Txid> {
+        let mut replaced = vec![];
+        let mut txid = txid;
+        while let Some(older_txid) = self.rev_replacement_txid.get(txid) {
+            replaced.push(*older_txid);
+            txid = older_txid;
+        }
+        replaced.reverse();
+        replaced
+    }
+
+    /// Returns the submitted transaction that serves the retrieve_btc request
+    /// with the specified block index.
+    pub fn find_submitted_transaction(&self, block_index: u64) -> Option<&SubmittedBtcTransaction> {
+        self.submitted_transactions
+            .iter()
+            .find(|tx| match &tx.requests {
+                SubmittedWithdrawalRequests::ToConfirm { requests } => {
+                    requests.iter().any(|r| r.block_index == block_index)
+                }
+                SubmittedWithdrawalRequests::ToCancel { .. }
+                | SubmittedWithdrawalRequests::ToConsolidate => false,
+            })
+    }
+
+    /// Returns the total amount (in satoshi) that the requesters paid to bump
+    /// the fee of a transaction serving the specified requests.
+    pub fn withdrawal_fee_bump(&self, requests: &SubmittedWithdrawalRequests) -> u64 {
+        match requests {
+            SubmittedWithdrawalRequests::ToConfirm { requests } => requests
+                .iter()
+                .filter_map(|req| self.withdrawal_fee_bumps.get(&req.block_index))
+                .sum(),
+            SubmittedWithdrawalRequests::ToCancel { .. }
+            | SubmittedWithdrawalRequests::ToConsolidate => 0,
+        }
+    }
+
+    /// Records a fee bump for a submitted retrieve_btc request and marks the
+    /// transaction serving the request for replacement.
+    pub(crate) fn bump_withdrawal_fee(&mut self, block_index: u64, amount: u64) {
+        let txid = self
+            .find_submitted_transaction(block_index)
+            .unwrap_or_else(|| {
+                panic!("BUG: cannot bump the fee of request {block_index} without a submitted transaction")
+            })
+            .txid;
+        *self.withdrawal_fee_bumps.entry(block_index).or_default() += amount;
+        self.pending_fee_bumps.insert(txid);
+        self.tokens_burned += amount;
     }
 
     /// Returns the identifier of the most recent replacement transaction for the given stuck
@@ -1422,6 +1584,22 @@ impl CkBtcMinterState {
 
         ensure_eq!(self.check_fee, other.check_fee, "check_fee does not match");
 
+        ensure_eq!(
+            self.utxo_consolidation_max_fee_per_vbyte,
+            other.utxo_consolidation_max_fee_per_vbyte,
+            "utxo_consolidation_max_fee_per_vbyte does not match"
+        );
+        ensure_eq!(
+            self.minter_fees_collected,
+            other.minter_fees_collected,
+            "minter_fees_collected does not match"
+        );
+        ensure_eq!(
+            self.consolidation_fees_paid,
+            other.consolidation_fees_paid,
+            "consolidation_fees_paid does not match"
+        );
+
         ensure_eq!(
             self.owed_kyt_amount,
             other.owed_kyt_amount,
@@ -1475,6 +1653,18 @@ impl CkBtcMinterState {
             "rev_replacement_txid maps do not match"
         );
 
+        ensure_eq!(
+            self.withdrawal_fee_bumps,
+            other.withdrawal_fee_bumps,
+            "withdrawal_fee_bumps do not match"
+        );
+
+        ensure_eq!(
+            self.pending_fee_bumps,
+            other.pending_fee_bumps,
+            "pending_fee_bumps do not match"
+        );
+
         Ok(())
     }
 
@@ -1737,11 +1927,15 @@ impl From<InitArgs> for CkBtcMinterState {
             replacement_txid: Default::default(),
             retrieve_btc_account_to_block_indices: Default::default(),
             rev_replacement_txid: Default::default(),
+            withdrawal_fee_bumps: Default::default(),
+            pending_fee_bumps: Default::default(),
             stuck_transactions: Default::default(),
             finalized_requests: VecDeque::with_capacity(MAX_FINALIZED_REQUESTS),
             finalized_requests_count: 0,
             tokens_minted: 0,
             tokens_burned: 0,
+            minter_fees_collected: 0,
+            consolidation_fees_paid: 0,
             ledger_id: args.ledger_id,
             btc_checker_principal: args.btc_checker_principal,
             available_utxos: Default::default(),
@@ -1750,9 +1944,11 @@ impl From<InitArgs> for CkBtcMinterState {
             finalized_utxos: Default::default(),
             is_timer_running: false,
             is_distributing_fee: false,
+            is_consolidating_utxos: false,
             mode: args.mode,
             last_fee_per_vbyte: vec![1; 100],
             last_median_fee_per_vbyte: Some(1),
+            utxo_consolidation_max_fee_per_vbyte: args.utxo_consolidation_max_fee_per_vbyte,
             check_fee: args
                 .check_fee
                 .unwrap_or(crate::lifecycle::init::DEFAULT_CHECK_FEE),
@@ -2,7 +2,7 @@
 
 use super::{
     CkBtcMinterState, FinalizedBtcRetrieval, FinalizedStatus, LedgerBurnIndex, RetrieveBtcRequest,
-    SubmittedBtcTransaction, SuspendedReason, WithdrawalCancellation,
+    SubmittedBtcTransaction, SubmittedWithdrawalRequests, SuspendedReason, WithdrawalCancellation,
     eventlog::{EventType, ReplacedReason},
 };
 use crate::reimbursement::{ReimburseWithdrawalTask, WithdrawalReimbursementReason};
@@ -94,6 +94,34 @@ pub fn sent_transaction<R: CanisterRuntime>(
     state.push_submitted_transaction(tx);
 }
 
+pub fn sent_consolidation_transaction<R: CanisterRuntime>(
+    state: &mut CkBtcMinterState,
+    tx: SubmittedBtcTransaction,
+    runtime: &R,
+) {
+    assert_eq!(tx.requests, SubmittedWithdrawalRequests::ToConsolidate);
+    record_event(
+        EventType::SentConsolidationTransaction {
+            txid: tx.txid,
+            utxos: tx.used_utxos.clone(),
+            change_output: tx
+                .change_output
+                .clone()
+                .expect("BUG: consolidation transaction must have an output"),
+            submitted_at: tx.submitted_at,
+            fee_per_vbyte: tx
+                .fee_per_vbyte
+                .expect("BUG: consolidation transaction must have a fee per vbyte"),
+            withdrawal_fee: tx
+                .withdrawal_fee
+                .expect("BUG: consolidation transaction must have a fee"),
+        },
+        runtime,
+    );
+
+    state.push_submitted_transaction(tx);
+}
+
 pub fn confirm_transaction<R: CanisterRuntime>(
     state: &mut CkBtcMinterState,
     txid: &Txid,
@@ -225,6 +253,24 @@ pub fn replace_transaction<R: CanisterRuntime>(
     state.replace_transaction(&old_txid, new_tx);
 }
 
+pub fn bump_withdrawal_fee<R: CanisterRuntime>(
+    state: &mut CkBtcMinterState,
+    block_index: u64,
+    burn_block_index: u64,
+    amount: u64,
+    runtime: &R,
+) {
+    record_event(
+        EventType::BumpedWithdrawalFee {
+            block_index,
+            burn_block_index,
+            amount,
+        },
+        runtime,
+    );
+    state.bump_withdrawal_fee(block_index, amount);
+}
+
 pub fn distributed_kyt_fee<R: CanisterRuntime>(
     state: &mut CkBtcMinterState,
     kyt_provider: Principal,
@@ -148,6 +148,30 @@ mod event {
             new_utxos: Option<Vec<Utxo>>,
         },
 
+        /// Indicates that the minter sent out a transaction merging some of its
+        /// small UTXOs into a single output to its main address.
+        #[serde(rename = "sent_consolidation_transaction")]
+        SentConsolidationTransaction {
+            /// The Txid of the Bitcoin transaction.
+            #[serde(rename = "txid")]
+            txid: Txid,
+            /// UTXOs merged by the transaction.
+            #[serde(rename = "utxos")]
+            utxos: Vec<Utxo>,
+            /// The single output of the transaction, owned by the minter.
+            #[serde(rename = "change_output")]
+            change_output: ChangeOutput,
+            /// The IC time at which the minter submitted the transaction.
+            #[serde(rename = "submitted_at")]
+            submitted_at: u64,
+            /// The fee per vbyte (in millisatoshi) that we used for the transaction.
+            #[serde(rename = "fee")]
+            fee_per_vbyte: u64,
+            /// The fee paid by the minter for this transaction.
+            #[serde(rename = "withdrawal_fee")]
+            withdrawal_fee: WithdrawalFee,
+        },
+
         /// Indicates that the minter received enough confirmations for a bitcoin
         /// transaction.
         #[serde(rename = "confirmed_transaction")]
@@ -268,6 +292,21 @@ mod event {
             /// The mint block on the ledger.
             mint_block_index: u64,
         },
+
+        /// Indicates that the requester of a submitted retrieve_btc request
+        /// burned ckBTC to increase the fee of the transaction serving it.
+        #[serde(rename = "bumped_withdrawal_fee")]
+        BumpedWithdrawalFee {
+            /// The burn block of the retrieve_btc request.
+            #[serde(rename = "block_index")]
+            block_index: u64,
+            /// The burn block of the extra fee on the ledger.
+            #[serde(rename = "burn_block_index")]
+            burn_block_index: u64,
+            /// The extra fee amount in satoshi.
+            #[serde(rename = "amount")]
+            amount: u64,
+        },
     }
 }
 
@@ -381,6 +420,27 @@ pub fn replay<I: CheckInvariants>(
                     withdrawal_fee,
                 });
             }
+            EventType::SentConsolidationTransaction {
+                txid,
+                utxos,
+                change_output,
+                submitted_at,
+                fee_per_vbyte,
+                withdrawal_fee,
+            } => {
+                for utxo in utxos.iter() {
+                    state.available_utxos.remove(utxo);
+                }
+                state.push_submitted_transaction(SubmittedBtcTransaction {
+                    requests: SubmittedWithdrawalRequests::ToConsolidate,
+                    txid,
+                    used_utxos: utxos,
+                    change_output: Some(change_output),
+                    submitted_at,
+                    fee_per_vbyte: Some(fee_per_vbyte),
+                    withdrawal_fee: Some(withdrawal_fee),
+                });
+            }
             EventType::ReplacedBtcTransaction {
                 old_txid,
                 new_txid,
@@ -409,6 +469,9 @@ pub fn replay<I: CheckInvariants>(
                         SubmittedWithdrawalRequests::ToCancel { .. } => {
                             panic!("Cannot cancel a cancelation request")
                         }
+                        SubmittedWithdrawalRequests::ToConsolidate => {
+                            panic!("Cannot cancel a consolidation transaction")
+                        }
                         SubmittedWithdrawalRequests::ToConfirm { requests } => {
                             assert!(
                                 new_utxos.is_some(),
@@ -552,6 +615,18 @@ pub fn replay<I: CheckInvariants>(
             } => {
                 state.reimburse_withdrawal_completed(burn_block_index, mint_block_index);
             }
+            EventType::BumpedWithdrawalFee {
+                block_index,
+                amount,
+                ..
+            } => {
+                if state.find_submitted_transaction(block_index).is_none() {
+                    return Err(ReplayLogError::InconsistentLog(format!(
+                        "Cannot bump the fee of request {block_index} without a submitted transaction"
+                    )));
+                }
+                state.bump_withdrawal_fee(block_index, amount);
+            }
         }
     }
 
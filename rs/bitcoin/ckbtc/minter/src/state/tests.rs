@@ -458,3 +458,234 @@ mod withdrawal_reimbursement {
         }
     }
 }
+
+mod utxo_consolidation {
+    use crate::WithdrawalFee;
+    use crate::address::BitcoinAddress;
+    use crate::state::invariants::CheckInvariantsImpl;
+    use crate::state::{
+        ChangeOutput, CkBtcMinterState, RetrieveBtcRequest, RetrieveBtcStatus,
+        SubmittedBtcTransaction, SubmittedWithdrawalRequests,
+    };
+    use crate::test_fixtures::{init_args, ledger_account};
+    use ic_btc_interface::{OutPoint, Utxo};
+
+    #[test]
+    fn should_select_smallest_utxos_below_retrieve_btc_min_amount() {
+        let mut state = CkBtcMinterState::from(init_args());
+        let min_amount = state.retrieve_btc_min_amount;
+        let utxos: Vec<_> = [min_amount + 1, 5_000, min_amount, 1_000, 3_000]
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| utxo(i as u8, value))
+            .collect();
+        state.add_utxos::<CheckInvariantsImpl>(ledger_account(), utxos.clone());
+
+        assert_eq!(
+            state.utxos_to_consolidate(10),
+            vec![utxos[3].clone(), utxos[4].clone(), utxos[1].clone()]
+        );
+        assert_eq!(
+            state.utxos_to_consolidate(2),
+            vec![utxos[3].clone(), utxos[4].clone()]
+        );
+    }
+
+    #[test]
+    fn should_finalize_consolidation_transaction() {
+        let mut state = CkBtcMinterState::from(init_args());
+        let utxos: Vec<_> = (0..3).map(|i| utxo(i, 1_000)).collect();
+        state.add_utxos::<CheckInvariantsImpl>(ledger_account(), utxos.clone());
+        for utxo in utxos.iter() {
+            state.available_utxos.remove(utxo);
+        }
+        assert!(!state.has_pending_consolidation());
+
+        let txid = "688f1309fe62ae66ea71959ef6d747bb63ec7c5ab3d8b1e25d8233616c3ec71a"
+            .parse()
+            .unwrap();
+        state.push_submitted_transaction(SubmittedBtcTransaction {
+            requests: SubmittedWithdrawalRequests::ToConsolidate,
+            txid,
+            used_utxos: utxos,
+            submitted_at: 0,
+            change_output: Some(ChangeOutput {
+                vout: 0,
+                value: 2_800,
+            }),
+            fee_per_vbyte: Some(1_000),
+            withdrawal_fee: Some(WithdrawalFee {
+                bitcoin_fee: 200,
+                minter_fee: 0,
+            }),
+        });
+        assert!(state.has_pending_consolidation());
+        assert_eq!(state.count_incomplete_retrieve_btc_requests(), 0);
+        assert_eq!(state.retrieve_btc_status(0), RetrieveBtcStatus::Unknown);
+
+        assert!(state.finalize_transaction(&txid).is_none());
+
+        assert!(!state.has_pending_consolidation());
+        assert_eq!(state.consolidation_fees_paid, 200);
+        assert!(state.submitted_transactions.is_empty());
+        assert!(state.finalized_requests.is_empty());
+        assert!(state.utxos_state_addresses.is_empty());
+        state.check_invariants().expect("violated invariants");
+    }
+
+    #[test]
+    fn should_pay_consolidation_fees_out_of_collected_minter_fees() {
+        let mut state = CkBtcMinterState::from(init_args());
+        let utxo = utxo(0, 100_000);
+        state.add_utxos::<CheckInvariantsImpl>(ledger_account(), vec![utxo.clone()]);
+        state.available_utxos.remove(&utxo);
+        assert_eq!(state.consolidation_fee_budget(), 0);
+
+        let txid = "688f1309fe62ae66ea71959ef6d747bb63ec7c5ab3d8b1e25d8233616c3ec71a"
+            .parse()
+            .unwrap();
+        state.push_submitted_transaction(SubmittedBtcTransaction {
+            requests: vec![RetrieveBtcRequest {
+                amount: 50_000,
+                address: BitcoinAddress::P2wpkhV0([1; 20]),
+                block_index: 0,
+                received_at: 0,
+                kyt_provider: None,
+                reimbursement_account: None,
+            }]
+            .into(),
+            txid,
+            used_utxos: vec![utxo],
+            submitted_at: 0,
+            change_output: Some(ChangeOutput {
+                vout: 1,
+                value: 49_000,
+            }),
+            fee_per_vbyte: Some(1_000),
+            withdrawal_fee: Some(WithdrawalFee {
+                bitcoin_fee: 700,
+                minter_fee: 300,
+            }),
+        });
+        assert!(state.finalize_transaction(&txid).is_none());
+        assert_eq!(state.minter_fees_collected, 300);
+        assert_eq!(state.consolidation_fee_budget(), 300);
+
+        state.consolidation_fees_paid = 200;
+        assert_eq!(state.consolidation_fee_budget(), 100);
+        state.consolidation_fees_paid = 400;
+        assert_eq!(state.consolidation_fee_budget(), 0);
+    }
+
+    fn utxo(index: u8, value: u64) -> Utxo {
+        Utxo {
+            outpoint: OutPoint {
+                txid: [index; 32].into(),
+                vout: 0,
+            },
+            value,
+            height: 0,
+        }
+    }
+}
+
+mod withdrawal_fee_bump {
+    use crate::address::BitcoinAddress;
+    use crate::state::{
+        CkBtcMinterState, RetrieveBtcRequest, RetrieveBtcStatus, RetrieveBtcStatusV2,
+        SubmittedBtcTransaction,
+    };
+    use crate::test_fixtures::{expect_panic_with_message, init_args, ledger_account};
+    use ic_btc_interface::Txid;
+
+    #[test]
+    fn should_record_fee_bump_and_replacement_chain() {
+        let mut state = CkBtcMinterState::from(init_args());
+        let block_index = 1;
+        let tx = submitted_tx(block_index, txid(1));
+        state.push_submitted_transaction(tx.clone());
+        assert_eq!(
+            state.retrieve_btc_status_v2(block_index),
+            RetrieveBtcStatusV2::Submitted {
+                txid: txid(1),
+                replaced_txids: None,
+            }
+        );
+
+        state.bump_withdrawal_fee(block_index, 500);
+        state.bump_withdrawal_fee(block_index, 300);
+
+        assert_eq!(state.withdrawal_fee_bump(&tx.requests), 800);
+        assert!(state.pending_fee_bumps.contains(&txid(1)));
+        assert_eq!(state.tokens_burned, 800);
+
+        state.replace_transaction(
+            &txid(1),
+            SubmittedBtcTransaction {
+                txid: txid(2),
+                ..tx.clone()
+            },
+        );
+        assert!(state.pending_fee_bumps.is_empty());
+        state.replace_transaction(
+            &txid(2),
+            SubmittedBtcTransaction {
+                txid: txid(3),
+                ..tx.clone()
+            },
+        );
+        assert_eq!(
+            state.retrieve_btc_status_v2(block_index),
+            RetrieveBtcStatusV2::Submitted {
+                txid: txid(3),
+                replaced_txids: Some(vec![txid(1), txid(2)]),
+            }
+        );
+        assert_eq!(
+            state.retrieve_btc_status(block_index),
+            RetrieveBtcStatus::Submitted { txid: txid(3) }
+        );
+
+        assert!(state.finalize_transaction(&txid(3)).is_none());
+
+        assert!(state.withdrawal_fee_bumps.is_empty());
+        assert_eq!(
+            state.retrieve_btc_status_v2(block_index),
+            RetrieveBtcStatusV2::Confirmed { txid: txid(3) }
+        );
+    }
+
+    #[test]
+    fn should_fail_to_bump_fee_without_submitted_transaction() {
+        let mut state = CkBtcMinterState::from(init_args());
+
+        expect_panic_with_message(
+            || state.bump_withdrawal_fee(1, 500),
+            "BUG: cannot bump the fee of request 1",
+        );
+    }
+
+    fn submitted_tx(block_index: u64, txid: Txid) -> SubmittedBtcTransaction {
+        SubmittedBtcTransaction {
+            requests: vec![RetrieveBtcRequest {
+                amount: 100_000,
+                address: BitcoinAddress::P2wpkhV0([1; 20]),
+                block_index,
+                received_at: 0,
+                kyt_provider: None,
+                reimbursement_account: Some(ledger_account()),
+            }]
+            .into(),
+            txid,
+            used_utxos: vec![],
+            submitted_at: 0,
+            change_output: None,
+            fee_per_vbyte: None,
+            withdrawal_fee: None,
+        }
+    }
+
+    fn txid(index: u8) -> Txid {
+        [index; 32].into()
+    }
+}
@@ -1,7 +1,10 @@
 #[cfg(test)]
 mod tests;
 use crate::reimbursement::reimburse_withdrawals;
-use crate::{CanisterRuntime, estimate_fee_per_vbyte, finalize_requests, submit_pending_requests};
+use crate::{
+    CanisterRuntime, consolidate_utxos, estimate_fee_per_vbyte, finalize_requests,
+    submit_pending_requests,
+};
 use scopeguard::guard;
 use std::cell::{Cell, RefCell};
 use std::collections::{BTreeMap, BTreeSet};
@@ -16,6 +19,7 @@ thread_local! {
 pub enum TaskType {
     ProcessLogic(bool),
     RefreshFeePercentiles,
+    ConsolidateUtxos,
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
@@ -160,5 +164,18 @@ pub(crate) async fn run_task<R: CanisterRuntime>(task: Task, runtime: R) {
             };
             let _ = estimate_fee_per_vbyte(&runtime).await;
         }
+        TaskType::ConsolidateUtxos => {
+            const INTERVAL_CONSOLIDATION: Duration = Duration::from_secs(60 * 60);
+
+            let _enqueue_followup_guard = guard((), |_| {
+                schedule_after(INTERVAL_CONSOLIDATION, TaskType::ConsolidateUtxos, &runtime)
+            });
+
+            let _guard = match crate::guard::ConsolidateUtxosGuard::new() {
+                Some(guard) => guard,
+                None => return,
+            };
+            consolidate_utxos(&runtime).await;
+        }
     }
 }
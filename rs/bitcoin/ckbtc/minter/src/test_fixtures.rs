@@ -38,6 +38,7 @@ pub fn init_args() -> InitArgs {
         kyt_principal: None,
         kyt_fee: None,
         get_utxos_cache_expiration_seconds: None,
+        utxo_consolidation_max_fee_per_vbyte: None,
     }
 }
 
@@ -174,6 +175,7 @@ pub mod mock {
     use crate::CkBtcMinterState;
     use crate::fees::BitcoinFeeEstimator;
     use crate::management::CallError;
+    use crate::updates::bump_withdrawal_fee::BumpWithdrawalFeeError;
     use crate::updates::update_balance::UpdateBalanceError;
     use crate::{
         BitcoinAddress, BtcAddressCheckStatus, CanisterRuntime, GetCurrentFeePercentilesRequest,
@@ -210,6 +212,7 @@ pub mod mock {
             async fn get_utxos(&self, request: &GetUtxosRequest) -> Result<GetUtxosResponse, CallError>;
             async fn check_transaction(&self, btc_checker_principal: Option<Principal>, utxo: &Utxo, cycle_payment: u128, ) -> Result<CheckTransactionResponse, CallError>;
             async fn mint_ckbtc(&self, amount: u64, to: Account, memo: Memo) -> Result<u64, UpdateBalanceError>;
+            async fn burn_withdrawal_fee_bump(&self, from: Account, amount: u64, memo: Memo) -> Result<u64, BumpWithdrawalFeeError>;
             async fn sign_with_ecdsa(&self, key_name: String, derivation_path: Vec<Vec<u8>>, message_hash: [u8; 32]) -> Result<Vec<u8>, CallError>;
             async fn send_transaction(&self, transaction: &tx::SignedTransaction, network: Network) -> Result<(), CallError>;
             async fn check_address( &self, btc_checker_principal: Option<Principal>, address: String) -> Result<BtcAddressCheckStatus, CallError>;
@@ -456,6 +459,7 @@ pub mod arbitrary {
                 btc_checker_principal: option::of(canister_id()),
                 kyt_principal: option::of(canister_id()),
                 get_utxos_cache_expiration_seconds: option::of(any::<u64>()),
+                utxo_consolidation_max_fee_per_vbyte: option::of(any::<u64>()),
             })
         }
 
@@ -470,6 +474,7 @@ pub mod arbitrary {
                 btc_checker_principal: option::of(canister_id()),
                 kyt_principal: option::of(canister_id()),
                 get_utxos_cache_expiration_seconds: option::of(any::<u64>()),
+                utxo_consolidation_max_fee_per_vbyte: option::of(any::<u64>()),
             })
         }
 
@@ -505,6 +510,14 @@ pub mod arbitrary {
                     reason: option::of(replaced_reason()),
                     new_utxos: option::of(pvec(utxo(amount()), 0..10_000)),
                 }),
+                prop_struct!(EventType::SentConsolidationTransaction {
+                    txid: txid(),
+                    utxos: pvec(utxo(amount()), 0..10_000),
+                    change_output: change_output(),
+                    submitted_at: any::<u64>(),
+                    fee_per_vbyte: any::<u64>(),
+                    withdrawal_fee: withdrawal_fee(),
+                }),
                 prop_struct!(EventType::ConfirmedBtcTransaction { txid: txid() }),
                 prop_struct!(EventType::CheckedUtxo {
                     utxo: utxo(amount()),
@@ -547,6 +560,11 @@ pub mod arbitrary {
                     burn_block_index: any::<u64>(),
                     mint_block_index: any::<u64>(),
                 }),
+                prop_struct!(EventType::BumpedWithdrawalFee {
+                    block_index: any::<u64>(),
+                    burn_block_index: any::<u64>(),
+                    amount: any::<u64>(),
+                }),
             ]
         }
     }
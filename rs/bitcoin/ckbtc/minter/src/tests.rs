@@ -3,8 +3,10 @@ use crate::test_fixtures::{bitcoin_fee_estimator, build_bitcoin_unsigned_transac
 use crate::{
     BuildTxError, CacheWithExpiration, Network,
     address::BitcoinAddress,
-    build_unsigned_transaction, estimate_retrieve_btc_fee, fake_sign, greedy,
+    apply_fee_bump, build_consolidation_transaction, build_unsigned_transaction,
+    build_unsigned_transaction_from_inputs, estimate_retrieve_btc_fee, fake_sign, greedy,
     lifecycle::init::InitArgs,
+    rebuild_unsigned_transaction,
     state::invariants::CheckInvariantsImpl,
     state::{
         ChangeOutput, CkBtcMinterState, Mode, RetrieveBtcRequest, RetrieveBtcStatus,
@@ -46,6 +48,7 @@ fn default_init_args() -> InitArgs {
         kyt_principal: None,
         kyt_fee: None,
         get_utxos_cache_expiration_seconds: None,
+        utxo_consolidation_max_fee_per_vbyte: None,
     }
 }
 
@@ -456,6 +459,142 @@ fn test_no_dust_in_change_output() {
     }
 }
 
+#[test]
+fn should_merge_utxos_into_single_output_to_main_address() {
+    let utxos: Vec<_> = (1..=10).map(|i| dummy_utxo_from_value(i * 1_000)).collect();
+    let inputs_value = utxos.iter().map(|u| u.value).sum::<u64>();
+    let minter_addr = BitcoinAddress::P2wpkhV0([0; 20]);
+    let fee_per_vbyte = 2_000;
+    let fee_estimator = bitcoin_fee_estimator();
+
+    let (tx, change_output, withdrawal_fee) =
+        build_consolidation_transaction(&utxos, minter_addr.clone(), fee_per_vbyte, &fee_estimator)
+            .expect("failed to build a consolidation transaction");
+
+    assert_eq!(tx.inputs.len(), utxos.len());
+    assert_eq!(
+        withdrawal_fee.bitcoin_fee,
+        fee_estimator.evaluate_transaction_fee(&tx, fee_per_vbyte)
+    );
+    assert_eq!(withdrawal_fee.minter_fee, 0);
+    assert_eq!(
+        change_output,
+        ChangeOutput {
+            vout: 0,
+            value: inputs_value - withdrawal_fee.bitcoin_fee,
+        }
+    );
+    assert_eq!(
+        tx.outputs,
+        vec![tx::TxOut {
+            address: minter_addr,
+            value: change_output.value,
+        }]
+    );
+}
+
+#[test]
+fn should_not_consolidate_utxos_that_do_not_cover_the_fee() {
+    let utxos = vec![dummy_utxo_from_value(600), dummy_utxo_from_value(700)];
+    let minter_addr = BitcoinAddress::P2wpkhV0([0; 20]);
+
+    assert_eq!(
+        build_consolidation_transaction(
+            &utxos,
+            minter_addr.clone(),
+            10_000,
+            &bitcoin_fee_estimator()
+        ),
+        Err(BuildTxError::AmountTooLow)
+    );
+    assert_eq!(
+        build_consolidation_transaction(&[], minter_addr, 10_000, &bitcoin_fee_estimator()),
+        Err(BuildTxError::NotEnoughFunds)
+    );
+}
+
+#[test]
+fn should_pay_fee_bump_from_change_and_rebuild_transaction() {
+    let utxos = vec![dummy_utxo_from_value(200_000)];
+    let minter_addr = BitcoinAddress::P2wpkhV0([0; 20]);
+    let request = RetrieveBtcRequest {
+        amount: 100_000,
+        address: BitcoinAddress::P2wpkhV0([1; 20]),
+        block_index: 1,
+        received_at: 0,
+        kyt_provider: None,
+        reimbursement_account: None,
+    };
+    let fee_per_vbyte = 2_000;
+    let fee_bump = 10_000;
+    let fee_estimator = bitcoin_fee_estimator();
+
+    let build_tx = || {
+        build_unsigned_transaction_from_inputs(
+            &utxos,
+            vec![(request.address.clone(), request.amount)],
+            minter_addr.clone(),
+            fee_per_vbyte,
+            &fee_estimator,
+        )
+        .expect("failed to build a transaction")
+    };
+
+    let (mut tx, mut change_output, mut withdrawal_fee) = build_tx();
+    let change = change_output.value;
+    assert_eq!(
+        apply_fee_bump::<BitcoinFeeEstimator>(
+            &mut tx,
+            &mut change_output,
+            &mut withdrawal_fee,
+            change,
+        ),
+        Err(BuildTxError::DustOutput {
+            address: minter_addr.clone(),
+            amount: change,
+        })
+    );
+
+    let (mut tx, mut change_output, mut withdrawal_fee) = build_tx();
+    let original_bitcoin_fee = withdrawal_fee.bitcoin_fee;
+    apply_fee_bump::<BitcoinFeeEstimator>(
+        &mut tx,
+        &mut change_output,
+        &mut withdrawal_fee,
+        fee_bump,
+    )
+    .expect("failed to apply the fee bump");
+
+    assert_eq!(change_output.value, change - fee_bump);
+    assert_eq!(tx.outputs[1].value, change_output.value);
+    assert_eq!(withdrawal_fee.bitcoin_fee, original_bitcoin_fee + fee_bump);
+
+    let submitted_tx = SubmittedBtcTransaction {
+        requests: vec![request].into(),
+        txid: tx.txid(),
+        used_utxos: utxos,
+        submitted_at: 0,
+        change_output: Some(change_output),
+        fee_per_vbyte: Some(fee_per_vbyte),
+        withdrawal_fee: Some(withdrawal_fee),
+    };
+    assert_eq!(
+        rebuild_unsigned_transaction(&submitted_tx, minter_addr.clone(), &fee_estimator),
+        Some(tx)
+    );
+    assert_eq!(
+        rebuild_unsigned_transaction(
+            &SubmittedBtcTransaction {
+                fee_per_vbyte: Some(fee_per_vbyte + 1_000),
+                ..submitted_tx
+            },
+            minter_addr,
+            &fee_estimator
+        ),
+        None
+    );
+}
+
 proptest! {
     #[test]
     fn greedy_solution_properties(
@@ -536,6 +675,43 @@ proptest! {
         prop_assert_eq!(&arb_tx.txid().as_ref().to_vec(), &*btc_tx.txid());
     }
 
+    #[test]
+    fn psbt_encoding_model(
+        inputs in pvec(arbitrary::unsigned_input(5_000u64..1_000_000_000), 1..20),
+        outputs in pvec(arbitrary::tx_out(), 1..20),
+        lock_time in any::<u32>(),
+    ) {
+        let arb_tx = tx::UnsignedTransaction { inputs, outputs, lock_time };
+        let spent_outputs: Vec<tx::TxOut> = arb_tx
+            .inputs
+            .iter()
+            .zip(arb_tx.outputs.iter().cycle())
+            .map(|(input, output)| tx::TxOut {
+                value: input.value,
+                address: output.address.clone(),
+            })
+            .collect();
+
+        let mut btc_psbt = bitcoin::util::psbt::PartiallySignedTransaction::from_unsigned_tx(
+            unsigned_tx_to_bitcoin_tx(&arb_tx),
+        )
+        .expect("failed to create a PSBT from an unsigned transaction");
+        for (input, spent_output) in btc_psbt.inputs.iter_mut().zip(spent_outputs.iter()) {
+            input.witness_utxo = Some(bitcoin::TxOut {
+                value: spent_output.value,
+                script_pubkey: address_to_script_pubkey(&spent_output.address),
+            });
+        }
+
+        let psbt_bytes = tx::PartiallySignedTransaction {
+            unsigned_tx: &arb_tx,
+            spent_outputs,
+        }
+        .serialize();
+
+        prop_assert_eq!(bitcoin::consensus::encode::serialize(&btc_psbt), psbt_bytes);
+    }
+
     #[test]
     fn unsigned_tx_sighash_model(
         inputs_data in pvec(
@@ -1074,7 +1250,7 @@ fn can_form_a_batch_conditions() {
 
 #[test]
 fn test_build_account_to_utxos_table_pagination() {
-    use crate::dashboard;
+    use crate::dashboard::{Bitcoin, build_account_to_utxos_table};
 
     let mut state = CkBtcMinterState::from(InitArgs {
         retrieve_btc_min_amount: 5_000u64,
@@ -1096,24 +1272,24 @@ fn test_build_account_to_utxos_table_pagination() {
 
     // Check if all pages combined together would give the full utxos set.
     let pages = [
-        dashboard::build_account_to_utxos_table(&state, 0, 7),
-        dashboard::build_account_to_utxos_table(&state, 7, 7),
-        dashboard::build_account_to_utxos_table(&state, 14, 7),
-        dashboard::build_account_to_utxos_table(&state, 21, 7),
-        dashboard::build_account_to_utxos_table(&state, 28, 7),
+        build_account_to_utxos_table::<Bitcoin>(&state, 0, 7),
+        build_account_to_utxos_table::<Bitcoin>(&state, 7, 7),
+        build_account_to_utxos_table::<Bitcoin>(&state, 14, 7),
+        build_account_to_utxos_table::<Bitcoin>(&state, 21, 7),
+        build_account_to_utxos_table::<Bitcoin>(&state, 28, 7),
     ];
     for (i, utxo) in utxos.iter().enumerate() {
         assert!(pages[i / 7].contains(&format!("{}", utxo.outpoint.txid)));
     }
     // Check if everything is on the same page when page_size = number of utxos.
-    let single_page = dashboard::build_account_to_utxos_table(&state, 0, utxos.len() as u64);
+    let single_page = build_account_to_utxos_table::<Bitcoin>(&state, 0, utxos.len() as u64);
     for utxo in utxos.iter() {
         assert!(single_page.contains(&format!("{}", utxo.outpoint.txid)));
     }
     // Content should be equal when page size is greater than total number of utxos.
     assert_eq!(
         single_page,
-        dashboard::build_account_to_utxos_table(&state, 0, 1 + utxos.len() as u64)
+        build_account_to_utxos_table::<Bitcoin>(&state, 0, 1 + utxos.len() as u64)
     );
     // After removing the last line (which are links to other pages), the size of
     // the paginated content should be less than 1/4 of size of a full page.
@@ -1124,7 +1300,7 @@ fn test_build_account_to_utxos_table_pagination() {
     };
     assert!(remove_last_line(&pages[0]).len() * 4 < remove_last_line(&single_page).len());
     // No utxos should be displayed when start is out of range.
-    let no_utxo_page = dashboard::build_account_to_utxos_table(&state, utxos.len() as u64, 7);
+    let no_utxo_page = build_account_to_utxos_table::<Bitcoin>(&state, utxos.len() as u64, 7);
     for utxo in utxos.iter() {
         assert!(!no_utxo_page.contains(&format!("{}", utxo.outpoint.txid)));
     }
@@ -1238,3 +1414,222 @@ proptest! {
         }
     }
 }
+
+mod consolidate_utxos {
+    use crate::address::BitcoinAddress;
+    use crate::lifecycle::init::InitArgs;
+    use crate::management::CallError;
+    use crate::state::eventlog::{EventType, ReplacedReason, replay};
+    use crate::state::invariants::CheckInvariantsImpl;
+    use crate::state::{
+        SubmittedBtcTransaction, SubmittedWithdrawalRequests, audit, mutate_state, read_state,
+    };
+    use crate::storage;
+    use crate::test_fixtures::{
+        NOW, bitcoin_fee_estimator, ecdsa_public_key, init_args, init_state, ledger_account,
+        mock::MockCanisterRuntime,
+    };
+    use crate::{MIN_UTXOS_TO_CONSOLIDATE, consolidate_utxos, resubmit_transactions};
+    use ic_btc_interface::{OutPoint, Utxo};
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+
+    const MAX_FEE_PER_VBYTE: u64 = 5_000;
+    const MEDIAN_FEE_PER_VBYTE: u64 = 2_000;
+
+    #[tokio::test]
+    async fn should_not_consolidate_when_fee_is_above_threshold() {
+        let utxos = init_state_with_small_utxos();
+        let mut runtime = mock_runtime(MAX_FEE_PER_VBYTE + 1);
+        runtime.expect_sign_with_ecdsa().never();
+        runtime.expect_send_transaction().never();
+
+        consolidate_utxos(&runtime).await;
+
+        assert!(!read_state(|s| s.has_pending_consolidation()));
+        assert_eq!(read_state(|s| s.available_utxos.len()), utxos.len());
+    }
+
+    #[tokio::test]
+    async fn should_not_consolidate_when_collected_fees_do_not_cover_the_fee() {
+        let utxos = init_state_with_small_utxos();
+        mutate_state(|s| s.minter_fees_collected = 1_000);
+        let mut runtime = mock_runtime(MEDIAN_FEE_PER_VBYTE);
+        runtime.expect_sign_with_ecdsa().never();
+        runtime.expect_send_transaction().never();
+
+        consolidate_utxos(&runtime).await;
+
+        assert!(!read_state(|s| s.has_pending_consolidation()));
+        assert_eq!(read_state(|s| s.available_utxos.len()), utxos.len());
+    }
+
+    #[tokio::test]
+    async fn should_record_sent_consolidation_transaction() {
+        let utxos = init_state_with_small_utxos();
+        let mut runtime = mock_runtime(MEDIAN_FEE_PER_VBYTE);
+        runtime
+            .expect_sign_with_ecdsa()
+            .times(utxos.len())
+            .returning(|_, _, _| Ok(vec![1; 64]));
+        runtime
+            .expect_send_transaction()
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        consolidate_utxos(&runtime).await;
+
+        assert!(read_state(|s| s.has_pending_consolidation()));
+        assert!(read_state(|s| s.available_utxos.is_empty()));
+        let submitted_tx = read_state(|s| s.submitted_transactions.clone())
+            .pop()
+            .expect("missing consolidation transaction");
+        assert_eq!(
+            submitted_tx.requests,
+            SubmittedWithdrawalRequests::ToConsolidate
+        );
+        assert_eq!(submitted_tx.used_utxos, utxos);
+        assert_eq!(submitted_tx.fee_per_vbyte, Some(MEDIAN_FEE_PER_VBYTE));
+        assert_matches::assert_matches!(
+            storage::events().last().map(|event| event.payload),
+            Some(EventType::SentConsolidationTransaction { txid, utxos: used_utxos, .. })
+                if txid == submitted_tx.txid && used_utxos == utxos
+        );
+
+        let replayed_state =
+            replay::<CheckInvariantsImpl>(storage::events()).expect("failed to replay the events");
+        assert!(replayed_state.has_pending_consolidation());
+        assert_eq!(
+            replayed_state.submitted_transactions,
+            vec![submitted_tx.clone()]
+        );
+        assert!(replayed_state.available_utxos.is_empty());
+
+        mutate_state(|s| audit::confirm_transaction(s, &submitted_tx.txid, &runtime));
+        assert!(!read_state(|s| s.has_pending_consolidation()));
+        assert_eq!(
+            read_state(|s| s.consolidation_fees_paid),
+            submitted_tx.withdrawal_fee.unwrap().bitcoin_fee
+        );
+    }
+
+    #[tokio::test]
+    async fn should_return_utxos_when_sending_consolidation_transaction_fails() {
+        let utxos = init_state_with_small_utxos();
+        let mut runtime = mock_runtime(MEDIAN_FEE_PER_VBYTE);
+        runtime
+            .expect_sign_with_ecdsa()
+            .returning(|_, _, _| Ok(vec![1; 64]));
+        runtime
+            .expect_send_transaction()
+            .times(1)
+            .returning(|_, _| {
+                Err(CallError::from_cdk_call_error(
+                    "bitcoin_send_transaction",
+                    ic_cdk::call::CallFailed::CallPerformFailed(ic_cdk::call::CallPerformFailed {}),
+                ))
+            });
+        let num_events = storage::count_events();
+
+        consolidate_utxos(&runtime).await;
+
+        assert!(!read_state(|s| s.has_pending_consolidation()));
+        assert_eq!(
+            read_state(|s| s.available_utxos.iter().cloned().collect::<Vec<_>>()),
+            utxos
+        );
+        assert_eq!(storage::count_events(), num_events);
+    }
+
+    #[tokio::test]
+    async fn should_resubmit_consolidation_transaction_with_higher_fee() {
+        init_state_with_small_utxos();
+        let mut runtime = mock_runtime(MEDIAN_FEE_PER_VBYTE);
+        runtime
+            .expect_sign_with_ecdsa()
+            .returning(|_, _, _| Ok(vec![1; 64]));
+        runtime.expect_send_transaction().returning(|_, _| Ok(()));
+        consolidate_utxos(&runtime).await;
+        let stuck_tx = read_state(|s| s.submitted_transactions[0].clone());
+
+        let replaced = RefCell::new(vec![]);
+        resubmit_transactions(
+            "key_1",
+            MEDIAN_FEE_PER_VBYTE,
+            main_address(),
+            ecdsa_public_key(),
+            crate::Network::Mainnet,
+            init_args().retrieve_btc_min_amount,
+            BTreeMap::from([(stuck_tx.txid, stuck_tx.clone())]),
+            |outpoint| read_state(|s| s.outpoint_account.get(outpoint).cloned()),
+            |_| 0,
+            |old_txid, new_tx, reason| replaced.borrow_mut().push((old_txid, new_tx, reason)),
+            &runtime,
+            &bitcoin_fee_estimator(),
+        )
+        .await;
+
+        let replaced = replaced.into_inner();
+        assert_eq!(replaced.len(), 1);
+        let (old_txid, new_tx, reason): &(_, SubmittedBtcTransaction, _) = &replaced[0];
+        assert_eq!(old_txid, &stuck_tx.txid);
+        assert_eq!(reason, &ReplacedReason::ToRetry);
+        assert_eq!(new_tx.requests, SubmittedWithdrawalRequests::ToConsolidate);
+        assert_eq!(new_tx.used_utxos, stuck_tx.used_utxos);
+        assert_eq!(
+            new_tx.fee_per_vbyte,
+            Some(MEDIAN_FEE_PER_VBYTE + crate::MIN_RELAY_FEE_PER_VBYTE)
+        );
+        assert!(
+            new_tx.withdrawal_fee.unwrap().bitcoin_fee
+                > stuck_tx.withdrawal_fee.unwrap().bitcoin_fee
+        );
+    }
+
+    fn init_state_with_small_utxos() -> Vec<Utxo> {
+        let args = InitArgs {
+            utxo_consolidation_max_fee_per_vbyte: Some(MAX_FEE_PER_VBYTE),
+            ..init_args()
+        };
+        let runtime = mock_runtime(MEDIAN_FEE_PER_VBYTE);
+        storage::record_event(EventType::Init(args.clone()), &runtime);
+        init_state(args);
+        let utxos: Vec<_> = (0..MIN_UTXOS_TO_CONSOLIDATE as u64)
+            .map(|i| Utxo {
+                outpoint: OutPoint {
+                    txid: [(i + 1) as u8; 32].into(),
+                    vout: 0,
+                },
+                value: 1_000 + i,
+                height: 0,
+            })
+            .collect();
+        mutate_state(|s| {
+            s.ecdsa_public_key = Some(ecdsa_public_key());
+            s.minter_fees_collected = 1_000_000;
+            audit::add_utxos(s, None, ledger_account(), utxos.clone(), &runtime);
+        });
+        utxos
+    }
+
+    fn mock_runtime(median_fee_per_vbyte: u64) -> MockCanisterRuntime {
+        let mut runtime = MockCanisterRuntime::new();
+        runtime
+            .expect_time()
+            .return_const(NOW.as_nanos_since_unix_epoch());
+        runtime
+            .expect_get_current_fee_percentiles()
+            .returning(move |_| Ok(vec![median_fee_per_vbyte; 100]));
+        runtime
+            .expect_fee_estimator()
+            .returning(|_| bitcoin_fee_estimator());
+        runtime
+            .expect_derive_minter_address()
+            .returning(|_| main_address());
+        runtime
+    }
+
+    fn main_address() -> BitcoinAddress {
+        BitcoinAddress::P2wpkhV0([7; 20])
+    }
+}
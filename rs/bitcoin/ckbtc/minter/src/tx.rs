@@ -547,3 +547,60 @@ impl Encode for SignedTransaction {
         self.lock_time.encode(buf)
     }
 }
+
+/// The magic bytes that start every PSBT.
+/// See https://github.com/bitcoin/bips/blob/master/bip-0174.mediawiki#specification.
+const PSBT_MAGIC: [u8; 5] = [0x70, 0x73, 0x62, 0x74, 0xff];
+// The key type of the unsigned transaction in the global map.
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+// The key type of the spent output in the per-input map of segwit inputs.
+const PSBT_IN_WITNESS_UTXO: u8 = 0x01;
+// Terminates a key-value map.
+const PSBT_SEPARATOR: u8 = 0x00;
+
+/// An unsigned transaction together with the outputs it spends, encoded as a
+/// Partially Signed Bitcoin Transaction (BIP-174, version 0).
+pub struct PartiallySignedTransaction<'a> {
+    pub unsigned_tx: &'a UnsignedTransaction,
+    /// The outputs spent by the transaction inputs, in the order of inputs.
+    pub spent_outputs: Vec<TxOut>,
+}
+
+impl PartiallySignedTransaction<'_> {
+    pub fn serialize(&self) -> Vec<u8> {
+        encode_into(self, Vec::<u8>::new())
+    }
+}
+
+fn write_psbt_entry(key_type: u8, value: &[u8], buf: &mut impl Buffer) {
+    // All the keys we write consist of the key type only.
+    write_compact_size(1, buf);
+    buf.write(&[key_type]);
+    write_compact_size(value.len(), buf);
+    buf.write(value);
+}
+
+impl Encode for PartiallySignedTransaction<'_> {
+    fn encode(&self, buf: &mut impl Buffer) {
+        assert_eq!(self.unsigned_tx.inputs.len(), self.spent_outputs.len());
+
+        buf.write(&PSBT_MAGIC);
+        write_psbt_entry(
+            PSBT_GLOBAL_UNSIGNED_TX,
+            &encode_into(self.unsigned_tx, Vec::<u8>::new()),
+            buf,
+        );
+        buf.write(&[PSBT_SEPARATOR]);
+        for spent_output in self.spent_outputs.iter() {
+            write_psbt_entry(
+                PSBT_IN_WITNESS_UTXO,
+                &encode_into(spent_output, Vec::<u8>::new()),
+                buf,
+            );
+            buf.write(&[PSBT_SEPARATOR]);
+        }
+        for _ in self.unsigned_tx.outputs.iter() {
+            buf.write(&[PSBT_SEPARATOR]);
+        }
+    }
+}
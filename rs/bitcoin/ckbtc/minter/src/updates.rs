@@ -1,11 +1,13 @@
 #[cfg(test)]
 mod tests;
 
+pub mod bump_withdrawal_fee;
 pub mod get_btc_address;
 pub mod get_withdrawal_account;
 pub mod retrieve_btc;
 pub mod update_balance;
 
+pub use bump_withdrawal_fee::bump_withdrawal_fee;
 pub use get_btc_address::get_btc_address;
 pub use get_withdrawal_account::get_withdrawal_account;
 pub use retrieve_btc::retrieve_btc;
@@ -0,0 +1,272 @@
+use crate::fees::FeeEstimator;
+use crate::guard::{GuardError, retrieve_btc_guard};
+use crate::memo::BurnMemo;
+use crate::reimbursement::WithdrawalReimbursementReason;
+use crate::tasks::{TaskType, schedule_now};
+use crate::{
+    CanisterRuntime,
+    state::{self, SubmittedBtcTransaction, mutate_state, read_state},
+};
+use candid::{CandidType, Deserialize, Nat};
+use canlog::log;
+use icrc_ledger_client_cdk::{CdkRuntime, ICRC1Client};
+use icrc_ledger_types::icrc1::account::{Account, Subaccount};
+use icrc_ledger_types::icrc1::transfer::Memo;
+use icrc_ledger_types::icrc2::transfer_from::{TransferFromArgs, TransferFromError};
+use num_traits::cast::ToPrimitive;
+
+/// The arguments of the [bump_withdrawal_fee] endpoint.
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct BumpWithdrawalFeeArgs {
+    // the burn block index of the retrieve_btc request
+    pub block_index: u64,
+
+    // extra Bitcoin fee to pay in satoshi
+    pub amount: u64,
+
+    // The subaccount to burn ckBTC from.
+    pub from_subaccount: Option<Subaccount>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct BumpWithdrawalFeeOk {
+    // the index of the burn block of the extra fee on the ckbtc ledger
+    pub burn_block_index: u64,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub enum BumpWithdrawalFeeError {
+    /// There is another request for this principal.
+    AlreadyProcessing,
+
+    /// The minter has no submitted transaction serving the request.
+    NotSubmitted,
+
+    /// The caller did not issue the retrieve_btc request.
+    NotRequester,
+
+    /// The extra fee is too low.
+    AmountTooLow(u64),
+
+    /// The change output of the transaction cannot cover the extra fee.
+    AmountTooHigh { max_amount: u64 },
+
+    /// The account does not hold the requested ckBTC amount.
+    InsufficientFunds { balance: u64 },
+
+    /// The caller didn't approve enough funds for spending.
+    InsufficientAllowance { allowance: u64 },
+
+    /// The transaction left the set of submitted transactions while the
+    /// minter burned the extra fee. The minter will reimburse the burned
+    /// amount.
+    WillReimburse { burn_block_index: u64 },
+
+    /// There are too many concurrent requests, retry later.
+    TemporarilyUnavailable(String),
+
+    /// A generic error reserved for future extensions.
+    GenericError {
+        error_message: String,
+        error_code: u64,
+    },
+}
+
+impl From<GuardError> for BumpWithdrawalFeeError {
+    fn from(e: GuardError) -> Self {
+        match e {
+            GuardError::AlreadyProcessing => Self::AlreadyProcessing,
+            GuardError::TooManyConcurrentRequests => {
+                Self::TemporarilyUnavailable("too many concurrent requests".to_string())
+            }
+        }
+    }
+}
+
+/// Burns the specified amount of ckBTC from the requester of a submitted
+/// retrieve_btc request and replaces the transaction serving the request with
+/// a transaction paying the burned amount as an extra Bitcoin fee.
+pub async fn bump_withdrawal_fee<R: CanisterRuntime>(
+    args: BumpWithdrawalFeeArgs,
+    runtime: &R,
+) -> Result<BumpWithdrawalFeeOk, BumpWithdrawalFeeError> {
+    let caller = runtime.caller();
+
+    state::read_state(|s| s.mode.is_withdrawal_available_for(&caller))
+        .map_err(BumpWithdrawalFeeError::TemporarilyUnavailable)?;
+
+    let caller_account = Account {
+        owner: caller,
+        subaccount: args.from_subaccount,
+    };
+    let _guard = retrieve_btc_guard(caller_account)?;
+
+    read_state(|s| {
+        let tx = s
+            .find_submitted_transaction(args.block_index)
+            .ok_or(BumpWithdrawalFeeError::NotSubmitted)?;
+        let request = tx
+            .requests
+            .iter()
+            .find(|req| req.block_index == args.block_index)
+            .expect("BUG: the submitted transaction must serve the request");
+        if request.reimbursement_account.map(|account| account.owner) != Some(caller) {
+            return Err(BumpWithdrawalFeeError::NotRequester);
+        }
+        if args.amount == 0 {
+            return Err(BumpWithdrawalFeeError::AmountTooLow(1));
+        }
+        let max_amount = max_fee_bump(s, tx, &runtime.fee_estimator(s));
+        if args.amount > max_amount {
+            return Err(BumpWithdrawalFeeError::AmountTooHigh { max_amount });
+        }
+        Ok(())
+    })?;
+
+    let memo = BurnMemo::BumpWithdrawalFee {
+        withdrawal_id: args.block_index,
+    };
+    let burn_block_index = runtime
+        .burn_withdrawal_fee_bump(
+            caller_account,
+            args.amount,
+            crate::memo::encode(&memo).into(),
+        )
+        .await?;
+
+    // The minter could have finalized or replaced the transaction during the burn.
+    if read_state(|s| s.find_submitted_transaction(args.block_index).is_none()) {
+        log!(
+            crate::Priority::Info,
+            "[bump_withdrawal_fee]: request {} left the submitted transactions, reimbursing fee bump {}",
+            args.block_index,
+            burn_block_index
+        );
+        mutate_state(|s| {
+            state::audit::reimburse_withdrawal(
+                s,
+                burn_block_index,
+                args.amount,
+                caller_account,
+                WithdrawalReimbursementReason::FeeBumpNotApplied,
+                runtime,
+            )
+        });
+        return Err(BumpWithdrawalFeeError::WillReimburse { burn_block_index });
+    }
+
+    mutate_state(|s| {
+        state::audit::bump_withdrawal_fee(
+            s,
+            args.block_index,
+            burn_block_index,
+            args.amount,
+            runtime,
+        )
+    });
+
+    schedule_now(TaskType::ProcessLogic(false), runtime);
+
+    Ok(BumpWithdrawalFeeOk { burn_block_index })
+}
+
+/// Returns the largest extra fee that the change output of the transaction
+/// can cover on top of the fee bumps already paid for its requests.
+fn max_fee_bump<F: FeeEstimator>(
+    state: &state::CkBtcMinterState,
+    tx: &SubmittedBtcTransaction,
+    fee_estimator: &F,
+) -> u64 {
+    // Replacement transactions spend the same inputs and pay the same outputs,
+    // so their change output does not depend on the fee rate.
+    let inputs_value = tx.used_utxos.iter().map(|utxo| utxo.value).sum::<u64>();
+    let outputs_value = tx.requests.iter().map(|req| req.amount).sum::<u64>();
+    let minter_fee = fee_estimator
+        .evaluate_minter_fee(tx.used_utxos.len() as u64, (tx.requests.len() + 1) as u64);
+    let change = (inputs_value + minter_fee).saturating_sub(outputs_value);
+    change.saturating_sub(state.withdrawal_fee_bump(&tx.requests) + F::DUST_LIMIT + 1)
+}
+
+pub(crate) async fn burn_ckbtcs_icrc2(
+    user: Account,
+    amount: u64,
+    memo: Memo,
+) -> Result<u64, BumpWithdrawalFeeError> {
+    debug_assert!(memo.0.len() <= crate::CKBTC_LEDGER_MEMO_SIZE as usize);
+
+    let client = ICRC1Client {
+        runtime: CdkRuntime,
+        ledger_canister_id: read_state(|s| s.ledger_id.get().into()),
+    };
+    let minter = ic_cdk::api::canister_self();
+    let result = client
+        .transfer_from(TransferFromArgs {
+            spender_subaccount: None,
+            from: user,
+            to: Account {
+                owner: minter,
+                subaccount: None,
+            },
+            amount: Nat::from(amount),
+            fee: None,
+            memo: Some(memo),
+            created_at_time: None,
+        })
+        .await
+        .map_err(|(code, msg)| {
+            BumpWithdrawalFeeError::TemporarilyUnavailable(format!(
+                "cannot enqueue a burn transaction: {msg} (reject_code = {code})"
+            ))
+        })?;
+
+    match result {
+        Ok(block_index) => Ok(block_index.0.to_u64().expect("nat does not fit into u64")),
+        Err(TransferFromError::InsufficientFunds { balance }) => {
+            Err(BumpWithdrawalFeeError::InsufficientFunds {
+                balance: balance
+                    .0
+                    .to_u64()
+                    .expect("unreachable: ledger balance does not fit into u64"),
+            })
+        }
+        Err(TransferFromError::InsufficientAllowance { allowance }) => {
+            Err(BumpWithdrawalFeeError::InsufficientAllowance {
+                allowance: allowance
+                    .0
+                    .to_u64()
+                    .expect("unreachable: ledger balance does not fit into u64"),
+            })
+        }
+        Err(TransferFromError::BadBurn { min_burn_amount }) => {
+            Err(BumpWithdrawalFeeError::AmountTooLow(
+                min_burn_amount
+                    .0
+                    .to_u64()
+                    .expect("unreachable: ledger min burn amount does not fit into u64"),
+            ))
+        }
+        Err(TransferFromError::TemporarilyUnavailable) => {
+            Err(BumpWithdrawalFeeError::TemporarilyUnavailable(
+                "cannot burn ckBTC: the ledger is busy".to_string(),
+            ))
+        }
+        Err(TransferFromError::GenericError {
+            error_code,
+            message,
+        }) => Err(BumpWithdrawalFeeError::TemporarilyUnavailable(format!(
+            "cannot burn ckBTC: the ledger fails with: {message} (error code {error_code})"
+        ))),
+        Err(TransferFromError::BadFee { expected_fee }) => ic_cdk::trap(format!(
+            "unreachable: the ledger demands the fee of {expected_fee} even though the fee field is unset"
+        )),
+        Err(TransferFromError::Duplicate { duplicate_of }) => ic_cdk::trap(format!(
+            "unreachable: the ledger reports duplicate ({duplicate_of}) even though the create_at_time field is unset"
+        )),
+        Err(TransferFromError::CreatedInFuture { .. }) => ic_cdk::trap(
+            "unreachable: the ledger reports CreatedInFuture even though the create_at_time field is unset",
+        ),
+        Err(TransferFromError::TooOld) => ic_cdk::trap(
+            "unreachable: the ledger reports TooOld even though the create_at_time field is unset",
+        ),
+    }
+}
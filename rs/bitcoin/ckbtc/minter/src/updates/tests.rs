@@ -958,3 +958,94 @@ mod update_balance {
         })
     }
 }
+
+mod bump_withdrawal_fee {
+    use crate::address::BitcoinAddress;
+    use crate::reimbursement::WithdrawalReimbursementReason;
+    use crate::state::{
+        RetrieveBtcRequest, SubmittedBtcTransaction, eventlog::EventType, mutate_state,
+    };
+    use crate::storage;
+    use crate::test_fixtures::{
+        NOW, bitcoin_fee_estimator, init_args, init_state, ledger_account,
+        mock::MockCanisterRuntime, utxo,
+    };
+    use crate::updates::bump_withdrawal_fee::{
+        BumpWithdrawalFeeArgs, BumpWithdrawalFeeError, bump_withdrawal_fee,
+    };
+    use ic_btc_interface::{Txid, Utxo};
+
+    #[tokio::test]
+    async fn should_reimburse_fee_bump_when_transaction_is_finalized_during_burn() {
+        init_state(init_args());
+        let requester = ledger_account();
+        let block_index = 7;
+        let txid: Txid = [1; 32].into();
+        mutate_state(|s| {
+            s.push_submitted_transaction(SubmittedBtcTransaction {
+                requests: vec![RetrieveBtcRequest {
+                    amount: 100_000,
+                    address: BitcoinAddress::P2wpkhV0([1; 20]),
+                    block_index,
+                    received_at: 0,
+                    kyt_provider: None,
+                    reimbursement_account: Some(requester),
+                }]
+                .into(),
+                txid,
+                used_utxos: vec![Utxo {
+                    value: 200_000,
+                    ..utxo()
+                }],
+                submitted_at: 0,
+                change_output: None,
+                fee_per_vbyte: Some(2_000),
+                withdrawal_fee: None,
+            })
+        });
+
+        let fee_bump = 1_000;
+        let burn_block_index = 42;
+        let mut runtime = MockCanisterRuntime::new();
+        runtime.expect_caller().return_const(requester.owner);
+        runtime
+            .expect_time()
+            .return_const(NOW.as_nanos_since_unix_epoch());
+        runtime
+            .expect_fee_estimator()
+            .returning(|_| bitcoin_fee_estimator());
+        runtime
+            .expect_burn_withdrawal_fee_bump()
+            .times(1)
+            .withf(move |from, amount, _memo| from == &requester && *amount == fee_bump)
+            .returning(move |_, _, _| {
+                // The minter finalizes the transaction while the ledger burns the fee bump.
+                mutate_state(|s| s.finalize_transaction(&txid));
+                Ok(burn_block_index)
+            });
+
+        let result = bump_withdrawal_fee(
+            BumpWithdrawalFeeArgs {
+                block_index,
+                amount: fee_bump,
+                from_subaccount: requester.subaccount,
+            },
+            &runtime,
+        )
+        .await;
+
+        assert_eq!(
+            result,
+            Err(BumpWithdrawalFeeError::WillReimburse { burn_block_index })
+        );
+        assert_eq!(
+            storage::events().last().map(|event| event.payload),
+            Some(EventType::ScheduleWithdrawalReimbursement {
+                account: requester,
+                amount: fee_bump,
+                reason: WithdrawalReimbursementReason::FeeBumpNotApplied,
+                burn_block_index,
+            })
+        );
+    }
+}
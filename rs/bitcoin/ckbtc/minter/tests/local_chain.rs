@@ -0,0 +1,357 @@
+//! End-to-end tests of ckBTC deposits and withdrawals against the Bitcoin canister
+//! syncing a regtest chain that is produced by the PocketIC server.
+use candid::{Decode, Encode, Nat, Principal};
+use ic_base_types::{CanisterId, PrincipalId};
+use ic_btc_checker::{
+    BtcNetwork as CheckerBtcNetwork, CheckArg, CheckMode, InitArg as CheckerInitArg,
+};
+use ic_ckbtc_minter::lifecycle::init::{InitArgs as CkbtcMinterInitArgs, MinterArg};
+use ic_ckbtc_minter::queries::RetrieveBtcStatusRequest;
+use ic_ckbtc_minter::state::{Mode, RetrieveBtcStatusV2};
+use ic_ckbtc_minter::updates::get_btc_address::GetBtcAddressArgs;
+use ic_ckbtc_minter::updates::retrieve_btc::{
+    RetrieveBtcOk, RetrieveBtcWithApprovalArgs, RetrieveBtcWithApprovalError,
+};
+use ic_ckbtc_minter::updates::update_balance::{UpdateBalanceArgs, UpdateBalanceError, UtxoStatus};
+use ic_ckbtc_minter::{CKBTC_LEDGER_MEMO_SIZE, Network};
+use ic_icrc1_ledger::{InitArgsBuilder as LedgerInitArgsBuilder, LedgerArgument};
+use ic_test_utilities_load_wasm::load_wasm;
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc2::approve::{ApproveArgs, ApproveError};
+use pocket_ic::common::rest::{IcpFeatures, IcpFeaturesConfig, LocalChain, LocalChainConfig};
+use pocket_ic::{PocketIc, PocketIcBuilder};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+const CHECK_FEE: u64 = 2_000;
+const TRANSFER_FEE: u64 = 10;
+const MIN_CONFIRMATIONS: u32 = 6;
+const MAX_TIME_IN_QUEUE: Duration = Duration::from_secs(10);
+const RETRIEVE_BTC_MIN_AMOUNT: u64 = 10_000;
+/// The block subsidy on regtest before the first halving.
+const COINBASE_REWARD: u64 = 50 * 100_000_000;
+/// The number of blocks after which a coinbase output can be spent.
+const COINBASE_MATURITY: u64 = 100;
+const WITHDRAWAL_AMOUNT: u64 = 100_000_000;
+/// The maximum number of rounds to wait for the canisters to make progress.
+const MAX_ROUNDS: usize = 500;
+const T: u128 = 1_000_000_000_000;
+
+const USER: Principal = Principal::from_slice(&[0_u8, 42]);
+const RECIPIENT: Principal = Principal::from_slice(&[0_u8, 43]);
+
+fn ledger_wasm() -> Vec<u8> {
+    let path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap())
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("ledger_suite")
+        .join("icrc1")
+        .join("ledger");
+    load_wasm(path, "ic-icrc1-ledger", &[])
+}
+
+fn minter_wasm() -> Vec<u8> {
+    load_wasm(
+        std::env::var("CARGO_MANIFEST_DIR").unwrap(),
+        "ic-ckbtc-minter",
+        &[],
+    )
+}
+
+fn btc_checker_wasm() -> Vec<u8> {
+    load_wasm(
+        PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap())
+            .parent()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .join("checker"),
+        "ic-btc-checker",
+        &[],
+    )
+}
+
+struct LocalChainSetup {
+    pic: PocketIc,
+    minter_id: Principal,
+    ledger_id: Principal,
+}
+
+impl LocalChainSetup {
+    fn new() -> Self {
+        let pic = PocketIcBuilder::new()
+            .with_nns_subnet()
+            .with_bitcoin_subnet()
+            .with_fiduciary_subnet() // to have tECDSA keys available
+            .with_bitcoin_local_chain(LocalChainConfig {
+                initial_blocks: 1,
+                coinbase_address: None,
+            })
+            .with_icp_features(IcpFeatures {
+                bitcoin: Some(IcpFeaturesConfig::DefaultConfig),
+                ..Default::default()
+            })
+            .build();
+        pic.set_time(SystemTime::now().into());
+
+        let fiduciary_subnet = pic.topology().get_fiduciary().unwrap();
+        let ledger_id = pic.create_canister_on_subnet(None, None, fiduciary_subnet);
+        let minter_id = pic.create_canister_on_subnet(None, None, fiduciary_subnet);
+        let btc_checker_id = pic.create_canister_on_subnet(None, None, fiduciary_subnet);
+        for canister_id in [ledger_id, minter_id, btc_checker_id] {
+            pic.add_cycles(canister_id, 100 * T);
+        }
+
+        pic.install_canister(
+            ledger_id,
+            ledger_wasm(),
+            Encode!(&LedgerArgument::Init(
+                LedgerInitArgsBuilder::with_symbol_and_name("ckBTC", "ckBTC")
+                    .with_minting_account(minter_id)
+                    .with_transfer_fee(TRANSFER_FEE)
+                    .with_max_memo_length(CKBTC_LEDGER_MEMO_SIZE)
+                    .with_feature_flags(ic_icrc1_ledger::FeatureFlags { icrc2: true })
+                    .build()
+            ))
+            .unwrap(),
+            None,
+        );
+        pic.install_canister(
+            minter_id,
+            minter_wasm(),
+            Encode!(&MinterArg::Init(CkbtcMinterInitArgs {
+                btc_network: Network::Regtest,
+                ecdsa_key_name: "dfx_test_key".into(),
+                retrieve_btc_min_amount: RETRIEVE_BTC_MIN_AMOUNT,
+                ledger_id: canister_id(ledger_id),
+                max_time_in_queue_nanos: MAX_TIME_IN_QUEUE.as_nanos() as u64,
+                min_confirmations: Some(MIN_CONFIRMATIONS),
+                mode: Mode::GeneralAvailability,
+                check_fee: Some(CHECK_FEE),
+                btc_checker_principal: Some(canister_id(btc_checker_id)),
+                kyt_principal: None,
+                kyt_fee: None,
+                get_utxos_cache_expiration_seconds: None,
+                utxo_consolidation_max_fee_per_vbyte: None,
+            }))
+            .unwrap(),
+            None,
+        );
+        pic.install_canister(
+            btc_checker_id,
+            btc_checker_wasm(),
+            Encode!(&CheckArg::InitArg(CheckerInitArg {
+                btc_network: CheckerBtcNetwork::Mainnet,
+                check_mode: CheckMode::AcceptAll,
+                num_subnet_nodes: 1,
+            }))
+            .unwrap(),
+            None,
+        );
+
+        Self {
+            pic,
+            minter_id,
+            ledger_id,
+        }
+    }
+
+    /// Executes rounds until `f` returns a value.
+    fn wait_for<T>(&self, what: &str, mut f: impl FnMut() -> Option<T>) -> T {
+        for _ in 0..MAX_ROUNDS {
+            if let Some(result) = f() {
+                return result;
+            }
+            self.pic.advance_time(Duration::from_secs(1));
+            self.pic.tick();
+        }
+        panic!("Timed out waiting for {what}");
+    }
+
+    fn get_btc_address(&self, owner: Principal) -> String {
+        let reply = self
+            .pic
+            .update_call(
+                self.minter_id,
+                owner,
+                "get_btc_address",
+                Encode!(&GetBtcAddressArgs {
+                    owner: None,
+                    subaccount: None,
+                })
+                .unwrap(),
+            )
+            .expect("failed to get the deposit address");
+        Decode!(&reply, String).unwrap()
+    }
+
+    fn update_balance(&self, owner: Principal) -> Result<Vec<UtxoStatus>, UpdateBalanceError> {
+        let reply = self
+            .pic
+            .update_call(
+                self.minter_id,
+                owner,
+                "update_balance",
+                Encode!(&UpdateBalanceArgs {
+                    owner: None,
+                    subaccount: None,
+                })
+                .unwrap(),
+            )
+            .expect("failed to update the balance");
+        Decode!(&reply, Result<Vec<UtxoStatus>, UpdateBalanceError>).unwrap()
+    }
+
+    /// Calls `update_balance` until the minter mints ckBTC for a new UTXO and returns
+    /// the minted amount.
+    fn wait_for_mint(&self, owner: Principal) -> u64 {
+        self.wait_for("ckBTC to be minted", || match self.update_balance(owner) {
+            Ok(statuses) => statuses.into_iter().find_map(|status| match status {
+                UtxoStatus::Minted { minted_amount, .. } => Some(minted_amount),
+                _ => None,
+            }),
+            // The Bitcoin canister has not synced the deposit yet.
+            Err(UpdateBalanceError::NoNewUtxos { .. })
+            | Err(UpdateBalanceError::TemporarilyUnavailable(_))
+            | Err(UpdateBalanceError::AlreadyProcessing) => None,
+            Err(err) => panic!("unexpected update_balance error: {err:?}"),
+        })
+    }
+
+    fn balance_of(&self, owner: Principal) -> u64 {
+        let reply = self
+            .pic
+            .query_call(
+                self.ledger_id,
+                Principal::anonymous(),
+                "icrc1_balance_of",
+                Encode!(&Account::from(owner)).unwrap(),
+            )
+            .expect("failed to query the balance");
+        u64::try_from(Decode!(&reply, Nat).unwrap().0).unwrap()
+    }
+
+    fn approve_minter(&self, owner: Principal, amount: u64) {
+        let reply = self
+            .pic
+            .update_call(
+                self.ledger_id,
+                owner,
+                "icrc2_approve",
+                Encode!(&ApproveArgs {
+                    from_subaccount: None,
+                    spender: Account::from(self.minter_id),
+                    amount: Nat::from(amount),
+                    expected_allowance: None,
+                    expires_at: None,
+                    fee: None,
+                    memo: None,
+                    created_at_time: None,
+                })
+                .unwrap(),
+            )
+            .expect("failed to approve the minter");
+        Decode!(&reply, Result<Nat, ApproveError>)
+            .unwrap()
+            .expect("failed to approve the minter");
+    }
+
+    fn retrieve_btc_with_approval(&self, owner: Principal, address: String, amount: u64) -> u64 {
+        let reply = self
+            .pic
+            .update_call(
+                self.minter_id,
+                owner,
+                "retrieve_btc_with_approval",
+                Encode!(&RetrieveBtcWithApprovalArgs {
+                    address,
+                    amount,
+                    from_subaccount: None,
+                })
+                .unwrap(),
+            )
+            .expect("failed to retrieve BTC");
+        let RetrieveBtcOk { block_index } =
+            Decode!(&reply, Result<RetrieveBtcOk, RetrieveBtcWithApprovalError>)
+                .unwrap()
+                .expect("failed to retrieve BTC");
+        block_index
+    }
+
+    fn retrieve_btc_status_v2(&self, block_index: u64) -> RetrieveBtcStatusV2 {
+        let reply = self
+            .pic
+            .query_call(
+                self.minter_id,
+                Principal::anonymous(),
+                "retrieve_btc_status_v2",
+                Encode!(&RetrieveBtcStatusRequest { block_index }).unwrap(),
+            )
+            .expect("failed to query the retrieval status");
+        Decode!(&reply, RetrieveBtcStatusV2).unwrap()
+    }
+}
+
+fn canister_id(principal: Principal) -> CanisterId {
+    CanisterId::unchecked_from_principal(PrincipalId(principal))
+}
+
+#[test]
+fn should_deposit_and_withdraw_btc_on_local_chain() {
+    let setup = LocalChainSetup::new();
+    let pic = &setup.pic;
+
+    // Deposit the reward of a block mined to the deposit address of the user, and
+    // mine past the coinbase maturity so that the minter can spend it.
+    let deposit_address = setup.get_btc_address(USER);
+    pic.mine_blocks_to_address(LocalChain::Bitcoin, 1, &deposit_address);
+    pic.mine_blocks(
+        LocalChain::Bitcoin,
+        COINBASE_MATURITY.max(MIN_CONFIRMATIONS as u64),
+    );
+
+    let minted_amount = setup.wait_for_mint(USER);
+    assert_eq!(minted_amount, COINBASE_REWARD - CHECK_FEE);
+    assert_eq!(setup.balance_of(USER), minted_amount);
+
+    // Withdraw to the deposit address of another user, so that the withdrawal can be
+    // observed on the chain by depositing it.
+    let withdrawal_address = setup.get_btc_address(RECIPIENT);
+    setup.approve_minter(USER, WITHDRAWAL_AMOUNT + TRANSFER_FEE);
+    let block_index = setup.retrieve_btc_with_approval(USER, withdrawal_address, WITHDRAWAL_AMOUNT);
+    assert_eq!(
+        setup.balance_of(USER),
+        minted_amount - WITHDRAWAL_AMOUNT - 2 * TRANSFER_FEE
+    );
+
+    let txid = setup.wait_for("the withdrawal to be submitted", || {
+        match setup.retrieve_btc_status_v2(block_index) {
+            RetrieveBtcStatusV2::Submitted { txid, .. } => Some(txid),
+            _ => None,
+        }
+    });
+    setup.wait_for("the withdrawal to reach the mempool", || {
+        (!pic.get_mempool(LocalChain::Bitcoin).is_empty()).then_some(())
+    });
+    assert_eq!(pic.get_mempool(LocalChain::Bitcoin), vec![txid.to_string()]);
+
+    pic.mine_blocks(LocalChain::Bitcoin, MIN_CONFIRMATIONS as u64);
+    assert!(pic.get_mempool(LocalChain::Bitcoin).is_empty());
+    setup.wait_for("the withdrawal to be confirmed", || {
+        match setup.retrieve_btc_status_v2(block_index) {
+            RetrieveBtcStatusV2::Confirmed { txid: confirmed } => {
+                assert_eq!(confirmed, txid);
+                Some(())
+            }
+            _ => None,
+        }
+    });
+
+    // The withdrawn amount minus the Bitcoin transaction fee reached the recipient.
+    let received = setup.wait_for_mint(RECIPIENT) + CHECK_FEE;
+    assert!(received < WITHDRAWAL_AMOUNT && received > WITHDRAWAL_AMOUNT - 100_000);
+}
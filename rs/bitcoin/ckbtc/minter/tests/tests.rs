@@ -1,7 +1,7 @@
 use assert_matches::assert_matches;
 use bitcoin::util::psbt::serialize::Deserialize;
 use bitcoin::{Address as BtcAddress, Network as BtcNetwork};
-use candid::{Decode, Encode, Nat, Principal};
+use candid::{CandidType, Decode, Deserialize, Encode, Nat, Principal};
 use canlog::LogEntry;
 use ic_base_types::{CanisterId, PrincipalId};
 use ic_bitcoin_canister_mock::{OutPoint, PushUtxosToAddress, Utxo};
@@ -18,7 +18,13 @@ use ic_ckbtc_minter::logs::Priority;
 use ic_ckbtc_minter::queries::{EstimateFeeArg, RetrieveBtcStatusRequest, WithdrawalFee};
 use ic_ckbtc_minter::reimbursement::{InvalidTransactionError, WithdrawalReimbursementReason};
 use ic_ckbtc_minter::state::eventlog::{Event, EventType};
-use ic_ckbtc_minter::state::{BtcRetrievalStatusV2, Mode, RetrieveBtcStatus, RetrieveBtcStatusV2};
+use ic_ckbtc_minter::state::{
+    BtcRetrievalStatusV2, Mode, ReimburseDepositTask, ReimbursedDeposit, RetrieveBtcStatus,
+    RetrieveBtcStatusV2,
+};
+use ic_ckbtc_minter::updates::bump_withdrawal_fee::{
+    BumpWithdrawalFeeArgs, BumpWithdrawalFeeError, BumpWithdrawalFeeOk,
+};
 use ic_ckbtc_minter::updates::get_btc_address::GetBtcAddressArgs;
 use ic_ckbtc_minter::updates::retrieve_btc::{
     ErrorCode, RetrieveBtcArgs, RetrieveBtcError, RetrieveBtcOk, RetrieveBtcWithApprovalArgs,
@@ -69,6 +75,7 @@ fn default_init_args() -> CkbtcMinterInitArgs {
         kyt_principal: None,
         kyt_fee: None,
         get_utxos_cache_expiration_seconds: None,
+        utxo_consolidation_max_fee_per_vbyte: None,
     }
 }
 
@@ -147,6 +154,17 @@ fn assert_reply(result: WasmResult) -> Vec<u8> {
     }
 }
 
+/// Drops the replacement chain that only `retrieve_btc_status_v2` reports.
+fn without_replaced_txids(status: RetrieveBtcStatusV2) -> RetrieveBtcStatusV2 {
+    match status {
+        RetrieveBtcStatusV2::Submitted { txid, .. } => RetrieveBtcStatusV2::Submitted {
+            txid,
+            replaced_txids: None,
+        },
+        status => status,
+    }
+}
+
 fn assert_replacement_transaction(old: &bitcoin::Transaction, new: &bitcoin::Transaction) {
     fn input_utxos(tx: &bitcoin::Transaction) -> Vec<bitcoin::OutPoint> {
         tx.input.iter().map(|txin| txin.previous_output).collect()
@@ -1191,6 +1209,33 @@ impl CkBtcSetup {
         ).unwrap()
     }
 
+    pub fn bump_withdrawal_fee(
+        &self,
+        block_index: u64,
+        amount: u64,
+        from_subaccount: Option<[u8; 32]>,
+    ) -> Result<BumpWithdrawalFeeOk, BumpWithdrawalFeeError> {
+        Decode!(
+            &assert_reply(
+                self.env
+                    .execute_ingress_as(
+                        self.caller,
+                        self.minter_id,
+                        "bump_withdrawal_fee",
+                        Encode!(&BumpWithdrawalFeeArgs {
+                            block_index,
+                            amount,
+                            from_subaccount,
+                        })
+                        .unwrap()
+                    )
+                    .expect("failed to execute bump_withdrawal_fee request")
+            ),
+            Result<BumpWithdrawalFeeOk, BumpWithdrawalFeeError>
+        )
+        .unwrap()
+    }
+
     pub fn retrieve_btc_status(&self, block_index: u64) -> RetrieveBtcStatus {
         Decode!(
             &assert_reply(
@@ -1282,7 +1327,10 @@ impl CkBtcSetup {
         for _ in 0..max_ticks {
             let status_v2 = self.retrieve_btc_status_v2(block_index);
             let status = self.retrieve_btc_status(block_index);
-            assert_eq!(RetrieveBtcStatusV2::from(status.clone()), status_v2);
+            assert_eq!(
+                RetrieveBtcStatusV2::from(status.clone()),
+                without_replaced_txids(status_v2)
+            );
             match status {
                 RetrieveBtcStatus::Submitted { txid } => {
                     return txid;
@@ -1318,7 +1366,10 @@ impl CkBtcSetup {
         for _ in 0..max_ticks {
             let status_v2 = self.retrieve_btc_status_v2(block_index);
             let status = self.retrieve_btc_status(block_index);
-            assert_eq!(RetrieveBtcStatusV2::from(status.clone()), status_v2);
+            assert_eq!(
+                RetrieveBtcStatusV2::from(status.clone()),
+                without_replaced_txids(status_v2)
+            );
             match status {
                 RetrieveBtcStatus::Confirmed { txid } => {
                     return txid;
@@ -1826,6 +1877,13 @@ fn test_transaction_resubmission_finalize_middle() {
         .expect("the pool does not contain the third transaction");
 
     assert_replacement_transaction(second_tx, third_tx);
+    assert_eq!(
+        ckbtc.retrieve_btc_status_v2(block_index),
+        RetrieveBtcStatusV2::Submitted {
+            txid: third_txid,
+            replaced_txids: Some(vec![old_txid, second_txid]),
+        }
+    );
 
     // finalize the middle transaction
 
@@ -2444,3 +2502,135 @@ fn should_cancel_and_reimburse_large_withdrawal() {
         balance_before_withdrawal.clone() - REIMBURSEMENT_FEE_FOR_PENDING_WITHDRAWAL_REQUESTS
     );
 }
+
+#[test]
+fn should_bump_withdrawal_fee_and_resubmit_transaction() {
+    let ckbtc = CkBtcSetup::new();
+    let user = Principal::from(ckbtc.caller);
+
+    let deposit_value = 100_000_000;
+    ckbtc.deposit_utxo(
+        user,
+        Utxo {
+            height: 0,
+            outpoint: OutPoint {
+                txid: range_to_txid(1..=32),
+                vout: 1,
+            },
+            value: deposit_value,
+        },
+    );
+
+    let withdrawal_amount = 50_000_000;
+    let fee_bump = 10_000;
+    ckbtc.approve_minter(user, withdrawal_amount + fee_bump, None);
+    let RetrieveBtcOk { block_index } = ckbtc
+        .retrieve_btc_with_approval(WITHDRAWAL_ADDRESS.to_string(), withdrawal_amount, None)
+        .expect("retrieve_btc failed");
+
+    ckbtc.env.advance_time(MAX_TIME_IN_QUEUE);
+    let txid = ckbtc.await_btc_transaction(block_index, 10);
+    let tx = ckbtc
+        .mempool()
+        .get(&txid)
+        .expect("the mempool does not contain the original transaction")
+        .clone();
+
+    // The change output of the transaction cannot cover the whole deposit.
+    assert_matches!(
+        ckbtc.bump_withdrawal_fee(block_index, deposit_value, None),
+        Err(BumpWithdrawalFeeError::AmountTooHigh { max_amount })
+            if max_amount < deposit_value - withdrawal_amount
+    );
+
+    let balance_before_bump = ckbtc.balance_of(user);
+    let BumpWithdrawalFeeOk { burn_block_index } = ckbtc
+        .bump_withdrawal_fee(block_index, fee_bump, None)
+        .expect("failed to bump the withdrawal fee");
+    assert_eq!(ckbtc.balance_of(user), balance_before_bump - fee_bump);
+
+    // The minter replaces the transaction right away, without waiting for MIN_RESUBMISSION_DELAY.
+    let mempool = ckbtc.tick_until("mempool has a replacement transaction", 10, |ckbtc| {
+        let mempool = ckbtc.mempool();
+        (mempool.len() > 1).then_some(mempool)
+    });
+    let new_txid = ckbtc.await_btc_transaction(block_index, 10);
+    let new_tx = mempool
+        .get(&new_txid)
+        .expect("the mempool does not contain the replacement transaction");
+    assert_replacement_transaction(&tx, new_tx);
+
+    let out_value = |tx: &bitcoin::Transaction| tx.output.iter().map(|out| out.value).sum::<u64>();
+    assert!(
+        out_value(new_tx) + fee_bump <= out_value(&tx),
+        "the replacement transaction should pay the fee bump of {fee_bump}"
+    );
+    assert_eq!(
+        ckbtc.retrieve_btc_status_v2(block_index),
+        RetrieveBtcStatusV2::Submitted {
+            txid: new_txid,
+            replaced_txids: Some(vec![txid]),
+        }
+    );
+    assert!(ckbtc.get_events().iter().any(|event| event.payload
+        == EventType::BumpedWithdrawalFee {
+            block_index,
+            burn_block_index,
+            amount: fee_bump,
+        }));
+
+    ckbtc.finalize_transaction(new_tx);
+    assert_eq!(ckbtc.await_finalization(block_index, 10), new_txid);
+    ckbtc.minter_self_check();
+}
+
+/// `retrieve_btc_status_v2` as declared before `Submitted` reported the replaced transactions.
+#[derive(Clone, Debug, PartialEq, Eq, CandidType, Deserialize)]
+enum RetrieveBtcStatusV2WithoutReplacedTxids {
+    Unknown,
+    Pending,
+    Signing,
+    Sending { txid: Txid },
+    Submitted { txid: Txid },
+    AmountTooLow,
+    Confirmed { txid: Txid },
+    Reimbursed(ReimbursedDeposit),
+    WillReimburse(ReimburseDepositTask),
+}
+
+#[test]
+fn should_keep_retrieve_btc_status_v2_decodable_by_existing_clients() {
+    let (ckbtc, block_index, old_txid, _tx) = test_transaction_resubmission_finalize_setup();
+
+    ckbtc
+        .env
+        .advance_time(MIN_RESUBMISSION_DELAY + Duration::from_secs(1));
+    ckbtc.tick_until("mempool has a replacement transaction", 10, |ckbtc| {
+        (ckbtc.mempool().len() > 1).then_some(())
+    });
+    let new_txid = ckbtc.await_btc_transaction(block_index, 10);
+    assert_eq!(
+        ckbtc.retrieve_btc_status_v2(block_index),
+        RetrieveBtcStatusV2::Submitted {
+            txid: new_txid,
+            replaced_txids: Some(vec![old_txid]),
+        }
+    );
+
+    // The replaced transactions are an optional record field, which clients built against the
+    // previous interface of ckBTC and ckDOGE skip when decoding the status.
+    let reply = assert_reply(
+        ckbtc
+            .env
+            .query(
+                ckbtc.minter_id,
+                "retrieve_btc_status_v2",
+                Encode!(&RetrieveBtcStatusRequest { block_index }).unwrap(),
+            )
+            .expect("failed to query retrieve_btc_status_v2"),
+    );
+    assert_eq!(
+        Decode!(&reply, RetrieveBtcStatusV2WithoutReplacedTxids).unwrap(),
+        RetrieveBtcStatusV2WithoutReplacedTxids::Submitted { txid: new_txid }
+    );
+}
@@ -1,18 +1,18 @@
 mod metrics;
 
 use crate::metrics::{
-    LABEL_GET_SUCCESSORS, LABEL_REQUEST_TYPE, LABEL_SEND_TRANSACTION, LABEL_STATUS, Metrics,
-    OK_LABEL, REQUESTS_LABEL_NAMES, UNKNOWN_LABEL,
+    LABEL_GET_BLOCK_FILTERS, LABEL_GET_SUCCESSORS, LABEL_REQUEST_TYPE, LABEL_SEND_TRANSACTION,
+    LABEL_STATUS, Metrics, OK_LABEL, REQUESTS_LABEL_NAMES, UNKNOWN_LABEL,
 };
 use ic_adapter_metrics_client::AdapterMetrics;
 use ic_btc_replica_types::{
-    AdapterClient, BitcoinAdapterRequestWrapper, BitcoinAdapterResponseWrapper,
-    GetSuccessorsRequestInitial, GetSuccessorsResponseComplete, SendTransactionRequest,
-    SendTransactionResponse,
+    AdapterClient, BitcoinAdapterRequestWrapper, BitcoinAdapterResponseWrapper, BlockFilter,
+    GetBlockFiltersRequest, GetBlockFiltersResponse, GetSuccessorsRequestInitial,
+    GetSuccessorsResponseComplete, SendTransactionRequest, SendTransactionResponse,
 };
 use ic_btc_service::{
-    BtcServiceGetSuccessorsRequest, BtcServiceSendTransactionRequest,
-    btc_service_client::BtcServiceClient,
+    BtcServiceGetBlockFiltersRequest, BtcServiceGetSuccessorsRequest,
+    BtcServiceSendTransactionRequest, btc_service_client::BtcServiceClient,
 };
 use ic_config::adapters::AdaptersConfig;
 use ic_http_endpoints_async_utils::ExecuteOnTokioRuntime;
@@ -117,6 +117,41 @@ impl RpcAdapterClient<BitcoinAdapterRequestWrapper> for BitcoinAdapterClientImpl
                         })
                         .map_err(convert_tonic_error)
                 }
+                BitcoinAdapterRequestWrapper::GetBlockFiltersRequest(GetBlockFiltersRequest {
+                    block_hashes,
+                    scripts,
+                    ..
+                }) => {
+                    request_timer.set_label(LABEL_REQUEST_TYPE, LABEL_GET_BLOCK_FILTERS);
+                    let get_block_filters_request = BtcServiceGetBlockFiltersRequest {
+                        block_hashes,
+                        scripts: scripts.into_iter().map(|s| s.into_vec()).collect(),
+                    };
+
+                    let mut tonic_request = tonic::Request::new(get_block_filters_request);
+                    tonic_request.set_timeout(opts.timeout);
+
+                    client
+                        .get_block_filters(tonic_request)
+                        .await
+                        .map(|tonic_response| {
+                            let inner = tonic_response.into_inner();
+                            BitcoinAdapterResponseWrapper::GetBlockFiltersResponse(
+                                GetBlockFiltersResponse {
+                                    filters: inner
+                                        .filters
+                                        .into_iter()
+                                        .map(|f| BlockFilter {
+                                            block_hash: f.block_hash,
+                                            filter: f.filter,
+                                            matches: f.matches,
+                                        })
+                                        .collect(),
+                                },
+                            )
+                        })
+                        .map_err(convert_tonic_error)
+                }
             };
             let mut timer = request_timer;
             timer.set_label(
@@ -161,6 +196,9 @@ impl RpcAdapterClient<BitcoinAdapterRequestWrapper> for BrokenConnectionBitcoinC
             BitcoinAdapterRequestWrapper::SendTransactionRequest(_) => {
                 request_timer.set_label(LABEL_REQUEST_TYPE, LABEL_SEND_TRANSACTION)
             }
+            BitcoinAdapterRequestWrapper::GetBlockFiltersRequest(_) => {
+                request_timer.set_label(LABEL_REQUEST_TYPE, LABEL_GET_BLOCK_FILTERS)
+            }
         }
         request_timer.set_label(LABEL_STATUS, RpcError::ConnectionBroken.into());
         Err(RpcError::ConnectionBroken)
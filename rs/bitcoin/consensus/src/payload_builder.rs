@@ -203,6 +203,14 @@ impl BitcoinPayloadBuilder {
                                     message: error_message,
                                 })
                             }
+                            BitcoinAdapterRequestWrapper::GetBlockFiltersRequest(context) => {
+                                BitcoinAdapterResponseWrapper::GetBlockFiltersReject(
+                                    BitcoinReject {
+                                        reject_code: RejectCode::SysTransient,
+                                        message: error_message,
+                                    },
+                                )
+                            }
                         }
                     }
                 },
@@ -326,6 +334,19 @@ fn bitcoin_requests_iter(
                     )
                 }),
         )
+        .chain(
+            subnet_call_context_manager
+                .bitcoin_get_block_filters_contexts
+                .iter()
+                .map(|(callback_id, context)| {
+                    (
+                        callback_id,
+                        BitcoinAdapterRequestWrapper::GetBlockFiltersRequest(
+                            context.payload.clone(),
+                        ),
+                    )
+                }),
+        )
 }
 
 impl BatchPayloadBuilder for BitcoinPayloadBuilder {
@@ -115,6 +115,7 @@ pub type AdapterClient = Box<
 pub enum BitcoinAdapterRequestWrapper {
     GetSuccessorsRequest(GetSuccessorsRequestInitial),
     SendTransactionRequest(SendTransactionRequest),
+    GetBlockFiltersRequest(GetBlockFiltersRequest),
 }
 
 impl BitcoinAdapterRequestWrapper {
@@ -122,6 +123,7 @@ impl BitcoinAdapterRequestWrapper {
         match self {
             BitcoinAdapterRequestWrapper::GetSuccessorsRequest(_) => "get_successors",
             BitcoinAdapterRequestWrapper::SendTransactionRequest(_) => "send_transaction",
+            BitcoinAdapterRequestWrapper::GetBlockFiltersRequest(_) => "get_block_filters",
         }
     }
 
@@ -136,6 +138,10 @@ impl BitcoinAdapterRequestWrapper {
                 network,
                 ..
             }) => *network,
+            BitcoinAdapterRequestWrapper::GetBlockFiltersRequest(GetBlockFiltersRequest {
+                network,
+                ..
+            }) => *network,
         }
     }
 }
@@ -161,6 +167,15 @@ impl From<&BitcoinAdapterRequestWrapper> for v1::BitcoinAdapterRequestWrapper {
                     ),
                 }
             }
+            BitcoinAdapterRequestWrapper::GetBlockFiltersRequest(request) => {
+                v1::BitcoinAdapterRequestWrapper {
+                    r: Some(
+                        v1::bitcoin_adapter_request_wrapper::R::GetBlockFiltersRequest(
+                            request.into(),
+                        ),
+                    ),
+                }
+            }
         }
     }
 }
@@ -177,6 +192,9 @@ impl TryFrom<v1::BitcoinAdapterRequestWrapper> for BitcoinAdapterRequestWrapper
             v1::bitcoin_adapter_request_wrapper::R::SendTransactionRequest(r) => Ok(
                 BitcoinAdapterRequestWrapper::SendTransactionRequest(r.try_into()?),
             ),
+            v1::bitcoin_adapter_request_wrapper::R::GetBlockFiltersRequest(r) => Ok(
+                BitcoinAdapterRequestWrapper::GetBlockFiltersRequest(r.try_into()?),
+            ),
         }
     }
 }
@@ -529,6 +547,32 @@ impl From<&BitcoinReject> for v1::SendTransactionReject {
     }
 }
 
+impl From<&BitcoinReject> for v1::GetBlockFiltersReject {
+    fn from(reject: &BitcoinReject) -> Self {
+        v1::GetBlockFiltersReject {
+            message: reject.message.clone(),
+            reject_code: pbRejectCode::from(reject.reject_code).into(),
+        }
+    }
+}
+
+impl TryFrom<v1::GetBlockFiltersReject> for BitcoinReject {
+    type Error = ProxyDecodeError;
+    fn try_from(reject: v1::GetBlockFiltersReject) -> Result<Self, Self::Error> {
+        Ok(BitcoinReject {
+            reject_code: RejectCode::try_from(
+                pbRejectCode::try_from(reject.reject_code).map_err(|_| {
+                    ProxyDecodeError::ValueOutOfRange {
+                        typ: "GetBlockFiltersReject::reject_code",
+                        err: format!("value out of range: {}", reject.reject_code),
+                    }
+                })?,
+            )?,
+            message: reject.message,
+        })
+    }
+}
+
 impl TryFrom<v1::SendTransactionReject> for BitcoinReject {
     type Error = ProxyDecodeError;
     fn try_from(reject: v1::SendTransactionReject) -> Result<Self, Self::Error> {
@@ -552,6 +596,8 @@ pub enum BitcoinAdapterResponseWrapper {
     SendTransactionResponse(SendTransactionResponse),
     GetSuccessorsReject(BitcoinReject),
     SendTransactionReject(BitcoinReject),
+    GetBlockFiltersResponse(GetBlockFiltersResponse),
+    GetBlockFiltersReject(BitcoinReject),
 }
 
 impl BitcoinAdapterResponseWrapper {
@@ -562,6 +608,8 @@ impl BitcoinAdapterResponseWrapper {
             BitcoinAdapterResponseWrapper::SendTransactionResponse(r) => r.count_bytes(),
             BitcoinAdapterResponseWrapper::GetSuccessorsReject(r) => r.count_bytes(),
             BitcoinAdapterResponseWrapper::SendTransactionReject(r) => r.count_bytes(),
+            BitcoinAdapterResponseWrapper::GetBlockFiltersResponse(r) => r.count_bytes(),
+            BitcoinAdapterResponseWrapper::GetBlockFiltersReject(r) => r.count_bytes(),
         }
     }
 }
@@ -603,6 +651,24 @@ impl From<&BitcoinAdapterResponseWrapper> for v1::BitcoinAdapterResponseWrapper
                     ),
                 }
             }
+            BitcoinAdapterResponseWrapper::GetBlockFiltersResponse(response) => {
+                v1::BitcoinAdapterResponseWrapper {
+                    r: Some(
+                        v1::bitcoin_adapter_response_wrapper::R::GetBlockFiltersResponse(
+                            response.into(),
+                        ),
+                    ),
+                }
+            }
+            BitcoinAdapterResponseWrapper::GetBlockFiltersReject(reject) => {
+                v1::BitcoinAdapterResponseWrapper {
+                    r: Some(
+                        v1::bitcoin_adapter_response_wrapper::R::GetBlockFiltersReject(
+                            reject.into(),
+                        ),
+                    ),
+                }
+            }
         }
     }
 }
@@ -625,6 +691,12 @@ impl TryFrom<v1::BitcoinAdapterResponseWrapper> for BitcoinAdapterResponseWrappe
             v1::bitcoin_adapter_response_wrapper::R::SendTransactionReject(r) => Ok(
                 BitcoinAdapterResponseWrapper::SendTransactionReject(r.try_into()?),
             ),
+            v1::bitcoin_adapter_response_wrapper::R::GetBlockFiltersResponse(r) => Ok(
+                BitcoinAdapterResponseWrapper::GetBlockFiltersResponse(r.into()),
+            ),
+            v1::bitcoin_adapter_response_wrapper::R::GetBlockFiltersReject(r) => Ok(
+                BitcoinAdapterResponseWrapper::GetBlockFiltersReject(r.try_into()?),
+            ),
         }
     }
 }
@@ -814,6 +886,128 @@ pub struct GetSuccessorsResponsePartial {
     pub remaining_follow_ups: u8,
 }
 
+/// A request to retrieve the BIP-158 basic filters of the given blocks and to
+/// match them against the given output scripts.
+///
+/// ```text
+/// record {
+///   network: network;
+///   block_hashes: vec blob;
+///   scripts: vec blob;
+/// }
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize, Serialize)]
+pub struct GetBlockFiltersRequest {
+    pub network: Network,
+    pub block_hashes: Vec<BlockHash>,
+    pub scripts: Vec<serde_bytes::ByteBuf>,
+}
+
+impl From<&GetBlockFiltersRequest> for v1::GetBlockFiltersRequest {
+    fn from(request: &GetBlockFiltersRequest) -> Self {
+        Self {
+            network: request.network.into(),
+            block_hashes: request.block_hashes.clone(),
+            scripts: request.scripts.iter().map(|s| s.to_vec()).collect(),
+        }
+    }
+}
+
+impl TryFrom<v1::GetBlockFiltersRequest> for GetBlockFiltersRequest {
+    type Error = ProxyDecodeError;
+    fn try_from(request: v1::GetBlockFiltersRequest) -> Result<Self, Self::Error> {
+        Ok(GetBlockFiltersRequest {
+            network: Network::try_from(request.network)?,
+            block_hashes: request.block_hashes,
+            scripts: request
+                .scripts
+                .into_iter()
+                .map(serde_bytes::ByteBuf::from)
+                .collect(),
+        })
+    }
+}
+
+/// The BIP-158 basic filter of a block.
+///
+/// ```text
+/// record {
+///   block_hash: blob;
+///   filter: blob;
+///   matches: bool;
+/// }
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, CandidType, Deserialize, Serialize)]
+pub struct BlockFilter {
+    #[serde(with = "serde_bytes")]
+    pub block_hash: BlockHash,
+    #[serde(with = "serde_bytes")]
+    pub filter: Vec<u8>,
+    /// Whether the filter matches any of the requested scripts. False
+    /// positives are possible, false negatives are not.
+    pub matches: bool,
+}
+
+impl BlockFilter {
+    /// Returns the size of this `BlockFilter` in bytes.
+    pub fn count_bytes(&self) -> usize {
+        self.block_hash.len() + self.filter.len() + size_of_val(&self.matches)
+    }
+}
+
+/// A response containing the filters the adapter has verified. Filters that
+/// the adapter has not downloaded yet are omitted and can be requested again
+/// later.
+///
+/// ```text
+/// record {
+///   filters: vec block_filter;
+/// }
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default, CandidType, Deserialize, Serialize)]
+pub struct GetBlockFiltersResponse {
+    pub filters: Vec<BlockFilter>,
+}
+
+impl GetBlockFiltersResponse {
+    /// Returns the size of this `GetBlockFiltersResponse` in bytes.
+    pub fn count_bytes(&self) -> usize {
+        self.filters.iter().map(|f| f.count_bytes()).sum::<usize>()
+    }
+}
+
+impl From<&GetBlockFiltersResponse> for v1::GetBlockFiltersResponse {
+    fn from(response: &GetBlockFiltersResponse) -> Self {
+        v1::GetBlockFiltersResponse {
+            filters: response
+                .filters
+                .iter()
+                .map(|f| v1::BlockFilter {
+                    block_hash: f.block_hash.clone(),
+                    filter: f.filter.clone(),
+                    matches: f.matches,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<v1::GetBlockFiltersResponse> for GetBlockFiltersResponse {
+    fn from(response: v1::GetBlockFiltersResponse) -> Self {
+        GetBlockFiltersResponse {
+            filters: response
+                .filters
+                .into_iter()
+                .map(|f| BlockFilter {
+                    block_hash: f.block_hash,
+                    filter: f.filter,
+                    matches: f.matches,
+                })
+                .collect(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
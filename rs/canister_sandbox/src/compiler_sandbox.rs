@@ -5,7 +5,9 @@ use std::sync::Arc;
 use crate::launcher_service::LauncherService;
 use crate::protocol::transport::{Message, WireMessage};
 use crate::{rpc, transport, transport::UnixStreamMuxWriter};
-use ic_embedders::{CompilationResult, SerializedModule, WasmtimeEmbedder, wasm_utils};
+use ic_embedders::{
+    CompilationResult, CompilationTier, SerializedModule, WasmtimeEmbedder, wasm_utils,
+};
 use ic_interfaces::execution_environment::{HypervisorError, HypervisorResult};
 use ic_logger::{ReplicaLogger, error, trace};
 use ic_wasm_types::WasmEngineError;
@@ -14,10 +16,11 @@ use ic_wasm_types::WasmEngineError;
 fn compile_and_serialize(
     embedder: &WasmtimeEmbedder,
     wasm_src: Vec<u8>,
+    tier: CompilationTier,
 ) -> HypervisorResult<(CompilationResult, SerializedModule)> {
     let wasm =
         wasm_utils::decoding::decode_wasm(embedder.config().wasm_max_size, Arc::new(wasm_src))?;
-    let (_cache, res) = wasm_utils::compile(embedder, &wasm);
+    let (_cache, res) = wasm_utils::compile_with_tier(embedder, &wasm, tier);
     res
 }
 
@@ -29,6 +32,7 @@ fn unexpected(desc: &str) -> HypervisorError {
 struct PlainWasm {
     #[serde(with = "serde_bytes")]
     pub wasm_src: Vec<u8>,
+    pub tier: CompilationTier,
 }
 
 impl crate::fdenum::EnumerateInnerFileDescriptors for PlainWasm {
@@ -135,8 +139,9 @@ impl WasmCompilerProxy {
     pub fn compile(
         &self,
         wasm_src: Vec<u8>,
+        tier: CompilationTier,
     ) -> HypervisorResult<(CompilationResult, SerializedModule)> {
-        let req = PlainWasm { wasm_src };
+        let req = PlainWasm { wasm_src, tier };
         match self.rpc.call(req, Ok).sync() {
             Ok(compiled_wasm) => compiled_wasm.result,
             Err(_rpc_err) => {
@@ -196,7 +201,7 @@ pub fn compiler_sandbox_main() {
         move |message: WireMessage<PlainWasm, CompiledWasm>| match message.msg {
             Message::Request(w) => {
                 trace!(log, "Compile request received. Cookie: {}", message.cookie);
-                let result = compile_and_serialize(&embedder, w.wasm_src);
+                let result = compile_and_serialize(&embedder, w.wasm_src, w.tier);
                 let cw = CompiledWasm { result };
                 let call = rpc::Call::new_resolved(Ok(cw));
                 let call = rpc::Call::new_wrap(call, |x| x);
@@ -13,7 +13,8 @@ use ic_embedders::wasm_executor::{
     WasmExecutionResult, WasmExecutor, get_wasm_reserved_pages, wasm_execution_error,
 };
 use ic_embedders::{
-    CompilationCache, CompilationResult, WasmExecutionInput, wasm_utils::WasmImportsDetails,
+    CompilationCache, CompilationResult, CompilationTier, SerializedModule, WasmExecutionInput,
+    wasm_utils::WasmImportsDetails,
 };
 use ic_interfaces::execution_environment::{HypervisorError, HypervisorResult, InstanceStats};
 use ic_interfaces_state_manager::StateReader;
@@ -905,6 +906,7 @@ impl WasmExecutor for SandboxedExecutionController {
             subnet_available_memory,
             func_ref,
             compilation_cache,
+            compilation_tier,
         }: WasmExecutionInput,
         execution_state: &ExecutionState,
     ) -> (Option<CompilationResult>, WasmExecutionResult) {
@@ -942,6 +944,7 @@ impl WasmExecutor for SandboxedExecutionController {
             &*self.launcher_service,
             &execution_state.wasm_binary,
             compilation_cache,
+            compilation_tier,
             &self.metrics,
             &self.logger,
         ) {
@@ -1073,7 +1076,7 @@ impl WasmExecutor for SandboxedExecutionController {
         let stable_memory_page_map = PageMap::new(Arc::clone(&self.fd_factory));
 
         let (memory_modifications, exported_globals, serialized_module, compilation_result) =
-            match compilation_cache.get(&wasm_binary.binary) {
+            match compilation_cache.get(&wasm_binary.binary, CompilationTier::Baseline) {
                 None => {
                     self.metrics.inc_cache_lookup(CACHE_MISS);
                     // TODO(MR-651): This metric tracks the number of times execution reads wasm from disk.
@@ -1100,14 +1103,21 @@ impl WasmExecutor for SandboxedExecutionController {
                         &compiler_command[0],
                         &compiler_command[1..],
                     )?;
-                    let reply = compiler.compile(wasm_binary.binary.as_slice().to_vec());
+                    let reply = compiler.compile(
+                        wasm_binary.binary.as_slice().to_vec(),
+                        CompilationTier::Baseline,
+                    );
                     // Let the compiler proxy know that it can start shutting down, since
                     // we are not planning to send any addtional requests to it.
                     compiler.initiate_stop();
 
                     match reply {
                         Err(err) => {
-                            compilation_cache.insert_err(&wasm_binary.binary, err.clone());
+                            compilation_cache.insert_err(
+                                &wasm_binary.binary,
+                                CompilationTier::Baseline,
+                                err.clone(),
+                            );
                             return Err(err);
                         }
                         Ok((compilation_result, serialized_module)) => {
@@ -1243,6 +1253,7 @@ impl WasmExecutor for SandboxedExecutionController {
             last_executed_round: ExecutionRound::from(0),
             next_scheduled_method: NextScheduledMethod::default(),
             wasm_execution_mode: WasmExecutionMode::from_is_wasm64(serialized_module.is_wasm64),
+            executed_instructions: NumInstructions::new(0),
         };
 
         Ok((
@@ -1251,6 +1262,29 @@ impl WasmExecutor for SandboxedExecutionController {
             compilation_result,
         ))
     }
+
+    fn compile_optimized(
+        &self,
+        canister_module: &CanisterModule,
+    ) -> HypervisorResult<SerializedModule> {
+        let compiler_command = create_compiler_sandbox_argv().ok_or_else(|| {
+            HypervisorError::WasmEngineError(ic_wasm_types::WasmEngineError::Unexpected(
+                "Couldn't find compiler binary".to_string(),
+            ))
+        })?;
+        let compiler = WasmCompilerProxy::start(
+            self.logger.clone(),
+            &*self.launcher_service,
+            &compiler_command[0],
+            &compiler_command[1..],
+        )?;
+        let reply = compiler.compile(
+            canister_module.as_slice().to_vec(),
+            CompilationTier::Optimized,
+        );
+        compiler.initiate_stop();
+        reply.map(|(_compilation_result, serialized_module)| serialized_module)
+    }
 }
 
 fn observe_metrics(metrics: &SandboxedExecutionMetrics, imports_details: &WasmImportsDetails) {
@@ -1828,10 +1862,11 @@ fn open_wasm(
     launcher: &dyn LauncherService,
     wasm_binary: &WasmBinary,
     compilation_cache: Arc<CompilationCache>,
+    compilation_tier: CompilationTier,
     metrics: &SandboxedExecutionMetrics,
     log: &ReplicaLogger,
 ) -> HypervisorResult<(WasmId, Option<CompilationResult>)> {
-    let mut embedder_cache = wasm_binary.embedder_cache.lock().unwrap();
+    let mut embedder_cache = wasm_binary.embedder_cache(compilation_tier).lock().unwrap();
     if let Some(cache) = embedder_cache.as_ref()
         && let Some(opened_wasm) = cache.downcast::<HypervisorResult<OpenedWasm>>()
     {
@@ -1854,7 +1889,7 @@ fn open_wasm(
     }
 
     let wasm_id = WasmId::new();
-    let compilation = match compilation_cache.get(&wasm_binary.binary) {
+    let compilation = match compilation_cache.get(&wasm_binary.binary, compilation_tier) {
         None => {
             metrics.inc_cache_lookup(CACHE_MISS);
             // TODO(MR-651): This metric tracks the number of times execution reads wasm from disk.
@@ -1874,7 +1909,7 @@ fn open_wasm(
                 &compiler_command[0],
                 &compiler_command[1..],
             )?;
-            let result = compiler.compile(wasm_binary.binary.as_slice().to_vec());
+            let result = compiler.compile(wasm_binary.binary.as_slice().to_vec(), compilation_tier);
             // Let the compiler proxy know that it can start shutting down, since
             // we are not planning to send any addtional requests to it.
             compiler.initiate_stop();
@@ -1886,7 +1921,11 @@ fn open_wasm(
                     Ok((serialized_module, Some(compilation_result)))
                 }
                 Err(err) => {
-                    compilation_cache.insert_err(&wasm_binary.binary, err.clone());
+                    compilation_cache.insert_err(
+                        &wasm_binary.binary,
+                        compilation_tier,
+                        err.clone(),
+                    );
                     Err(err)
                 }
             }
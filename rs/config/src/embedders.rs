@@ -59,6 +59,12 @@ pub(crate) const DEFAULT_COST_TO_COMPILE_WASM_INSTRUCTION: NumInstructions =
 /// The number of rayon threads used by wasmtime to compile wasm binaries
 const DEFAULT_WASMTIME_RAYON_COMPILATION_THREADS: usize = 10;
 
+/// Canisters whose module executed more than this many instructions in
+/// execution rounds switch to the optimized module if tiered compilation is
+/// enabled.
+pub(crate) const DEFAULT_TIERED_COMPILATION_THRESHOLD: NumInstructions =
+    NumInstructions::new(100_000_000_000);
+
 /// The number of rayon threads use for the parallel page copying optimization.
 const DEFAULT_PAGE_ALLOCATOR_THREADS: usize = 8;
 
@@ -115,6 +121,12 @@ const STABLE_MEMORY_ACCESSED_PAGE_LIMIT_MESSAGE: NumOsPages =
 const STABLE_MEMORY_ACCESSED_PAGE_LIMIT_QUERY: NumOsPages =
     NumOsPages::new(GiB / (PAGE_SIZE as u64));
 
+/// The maximum size in bytes of the Wasm GC heap of a single message execution.
+/// Objects on the GC heap are never freed during the execution and the heap is
+/// discarded afterwards, so this limits the sum of all GC allocations of the
+/// message.
+pub const MAX_GC_HEAP_SIZE: NumBytes = NumBytes::new(GiB);
+
 /// The maximum size in bytes for an uncompressed Wasm module. This value is
 /// also used as the maximum size for the Wasm chunk store of each canister.
 pub const WASM_MAX_SIZE: NumBytes = NumBytes::new(100 * 1024 * 1024); // 100 MiB
@@ -128,6 +140,24 @@ pub struct FeatureFlags {
     pub canister_backtrace: FlagStatus,
     /// If this flag is enabled, then the environment variables are supported.
     pub environment_variables: FlagStatus,
+    /// If this flag is enabled, then canisters can write structured log records
+    /// with `ic0.debug_print_structured`.
+    pub structured_logging: FlagStatus,
+    /// If this flag is enabled, then canisters can declare how long their query
+    /// replies may be cached with `ic0.query_cache_reply` and invalidate cached
+    /// replies with `ic0.query_cache_invalidate`.
+    pub query_cache_hints: FlagStatus,
+    /// If this flag is enabled, then canisters can use the Wasm GC, function
+    /// references, and exception-handling proposals. GC objects do not outlive
+    /// the message that allocated them: globals of reference type and tables
+    /// holding GC references are reset to their initial value in every message.
+    pub wasm_gc_and_exceptions: FlagStatus,
+    /// If this flag is enabled, then canisters whose module executes many
+    /// instructions switch to a recompilation of it with an optimizing compiler.
+    pub tiered_compilation: FlagStatus,
+    /// If this flag is enabled, then every call of a System API function is
+    /// counted (for execution profiling in testing environments only).
+    pub system_api_call_profiling: FlagStatus,
 }
 
 impl FeatureFlags {
@@ -136,6 +166,11 @@ impl FeatureFlags {
             rate_limiting_of_debug_prints: FlagStatus::Enabled,
             canister_backtrace: FlagStatus::Enabled,
             environment_variables: FlagStatus::Enabled,
+            structured_logging: FlagStatus::Disabled,
+            query_cache_hints: FlagStatus::Disabled,
+            wasm_gc_and_exceptions: FlagStatus::Disabled,
+            tiered_compilation: FlagStatus::Disabled,
+            system_api_call_profiling: FlagStatus::Disabled,
         }
     }
 }
@@ -198,6 +233,12 @@ pub struct Config {
     /// The number of the rayon threads used for the parallel page copying optimization.
     pub num_rayon_page_allocator_threads: usize,
 
+    /// With tiered compilation, a canister executes its module compiled with
+    /// optimizations once the module has executed this many instructions in
+    /// execution rounds. The count is part of the replicated state, so all
+    /// replicas switch at the same execution.
+    pub tiered_compilation_threshold: NumInstructions,
+
     /// Flags to enable or disable features that are still experimental.
     pub feature_flags: FeatureFlags,
 
@@ -274,6 +315,7 @@ impl Config {
             cost_to_compile_wasm_instruction: DEFAULT_COST_TO_COMPILE_WASM_INSTRUCTION,
             num_rayon_compilation_threads: DEFAULT_WASMTIME_RAYON_COMPILATION_THREADS,
             num_rayon_page_allocator_threads: DEFAULT_PAGE_ALLOCATOR_THREADS,
+            tiered_compilation_threshold: DEFAULT_TIERED_COMPILATION_THRESHOLD,
             feature_flags: FeatureFlags::const_default(),
             metering_type: MeteringType::New,
             stable_memory_dirty_page_limit: StableMemoryPageLimit {
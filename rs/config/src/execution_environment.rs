@@ -3,7 +3,7 @@ use crate::flag_status::FlagStatus;
 use ic_base_types::{CanisterId, NumSeconds};
 use ic_types::{Cycles, NumBytes, NumInstructions};
 use serde::{Deserialize, Serialize};
-use std::{str::FromStr, time::Duration};
+use std::{path::PathBuf, str::FromStr, time::Duration};
 
 const MIB: u64 = 1024 * 1024;
 const GIB: u64 = MIB * 1024;
@@ -318,6 +318,10 @@ pub struct Config {
     /// The capacity of the Wasm compilation cache.
     pub max_compilation_cache_size: NumBytes,
 
+    /// If set, compiled Wasm modules are additionally stored in this directory
+    /// so that they don't need to be recompiled after a replica restart.
+    pub persistent_compilation_cache_dir: Option<PathBuf>,
+
     /// Indicate whether query stats should be collected or not.
     pub query_stats_aggregation: FlagStatus,
 
@@ -367,6 +371,13 @@ pub struct Config {
 
     /// Enables filtering by range in `fetch_canister_logs`.
     pub fetch_canister_logs_filter: FlagStatus,
+
+    /// Enables recording a profile of every Wasm execution (for testing only,
+    /// e.g., in PocketIC).
+    pub execution_profiling: FlagStatus,
+
+    /// Enables HTTP outcalls whose responses are aggregated by the canister.
+    pub canister_http_aggregate_by_canister: FlagStatus,
 }
 
 impl Default for Config {
@@ -441,6 +452,7 @@ impl Default for Config {
             query_cache_max_expiry_time: QUERY_CACHE_MAX_EXPIRY_TIME,
             query_cache_data_certificate_expiry_time: QUERY_CACHE_DATA_CERTIFICATE_EXPIRY_TIME,
             max_compilation_cache_size: MAX_COMPILATION_CACHE_SIZE,
+            persistent_compilation_cache_dir: None,
             query_stats_aggregation: FlagStatus::Enabled,
             query_stats_epoch_length: QUERY_STATS_EPOCH_LENGTH,
             stop_canister_timeout_duration: STOP_CANISTER_TIMEOUT_DURATION,
@@ -456,6 +468,8 @@ impl Default for Config {
             max_environment_variable_value_length: MAX_ENVIRONMENT_VARIABLE_VALUE_LENGTH,
             replicated_inter_canister_log_fetch: FlagStatus::Disabled,
             fetch_canister_logs_filter: FlagStatus::Disabled,
+            execution_profiling: FlagStatus::Disabled,
+            canister_http_aggregate_by_canister: FlagStatus::Disabled,
         }
     }
 }
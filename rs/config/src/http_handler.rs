@@ -64,6 +64,13 @@ pub struct Config {
 
     /// Serving at most `max_tracing_flamegraph_concurrent_requests` requests concurrently for all endpoints under `/_/tracing/flamegraph`.
     pub max_tracing_flamegraph_concurrent_requests: usize,
+
+    /// Serving at most `max_log_stream_concurrent_requests` log streams concurrently for endpoint `/api/v3/canister/.../log_stream`.
+    pub max_log_stream_concurrent_requests: usize,
+
+    /// Log streams of endpoint `/api/v3/canister/.../log_stream` are closed after `log_stream_timeout_seconds`
+    /// (or earlier, once the ingress expiry of the request has passed).
+    pub log_stream_timeout_seconds: u64,
 }
 
 impl Default for Config {
@@ -85,6 +92,8 @@ impl Default for Config {
             max_pprof_concurrent_requests: 5,
             ingress_message_certificate_timeout_seconds: 10,
             max_tracing_flamegraph_concurrent_requests: 5,
+            max_log_stream_concurrent_requests: 100,
+            log_stream_timeout_seconds: 120, // 2 min
         }
     }
 }
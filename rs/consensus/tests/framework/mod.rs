@@ -226,6 +226,10 @@ pub(crate) fn test_master_public_key_ids() -> Vec<MasterPublicKeyId> {
             curve: EcdsaCurve::Secp256k1,
             name: "ecdsa_test_key".to_string(),
         }),
+        MasterPublicKeyId::Ecdsa(EcdsaKeyId {
+            curve: EcdsaCurve::Secp256r1,
+            name: "ecdsa_secp256r1_test_key".to_string(),
+        }),
         MasterPublicKeyId::Schnorr(SchnorrKeyId {
             algorithm: SchnorrAlgorithm::Ed25519,
             name: "ed25519_test_key".to_string(),
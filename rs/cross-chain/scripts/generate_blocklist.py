@@ -1,7 +1,7 @@
 import argparse
 import xml.etree.ElementTree as ET
 
-# The following steps need to be carried out to update the blocklist for BTC or ETH.
+# The following steps need to be carried out to update the blocklist for BTC, DOGE or ETH.
 #
 # 1) Download the latest version of the OFAC SDN list from their website:
 #  https://sanctionslist.ofac.treas.gov/Home/SdnList
@@ -14,7 +14,7 @@ import xml.etree.ElementTree as ET
 #
 # 2) Run this script as follows:
 #
-# python generate_blocklist.py --currency {BTC, ETH} --input [path to the SDN.XML file]
+# python generate_blocklist.py --currency {BTC, DOGE, ETH} --input [path to the SDN.XML file]
 #
 # The command will generate the file 'blocklist.rs' containing the retrieved addresses.
 #
@@ -64,6 +64,38 @@ pub const BTC_ADDRESS_BLOCKLIST: &[&str] = &[\n"""
         return sorted(addresses)
 
 
+class DogecoinBlocklistHandler:
+    def preamble(self):
+        return """//! The script to generate this file, including information about the source data, can be found here:
+//! /rs/cross-chain/scripts/generate_blocklist.py
+
+#[cfg(test)]
+mod tests;
+
+use bitcoin::dogecoin::Address;
+
+/// DOGE is not accepted from nor sent to addresses on this list.
+/// NOTE: Keep it sorted!
+pub const DOGE_ADDRESS_BLOCKLIST: &[&str] = &[\n"""
+
+    def postamble(self):
+        return """pub fn is_blocked(address: &Address) -> bool {
+    DOGE_ADDRESS_BLOCKLIST
+        .binary_search(&address.to_string().as_ref())
+        .is_ok()
+}
+"""
+
+    def format_address(self, address):
+        return f'"{address}"'
+
+    def currency_symbol(self):
+        return "DOGE"
+
+    def sort(self, addresses):
+        return sorted(addresses)
+
+
 class EthereumBlocklistHandler:
     def preamble(self):
         return """//! The script to generate this file, including information about the source data, can be found here:
@@ -141,7 +173,7 @@ def store_blocklist(blocklist_handler, addresses, filename):
 
 if __name__ == "__main__":
     parser = argparse.ArgumentParser()
-    parser.add_argument("--currency", "-c", type=str, required=True, choices=["BTC", "ETH"], help="select the currency")
+    parser.add_argument("--currency", "-c", type=str, required=True, choices=["BTC", "DOGE", "ETH"], help="select the currency")
     parser.add_argument("--input", "-i", type=str, required=True, help="read the provided SDN.XML file")
     parser.add_argument(
         "--output", "-o", type=str, default=DEFAULT_BLOCKLIST_FILENAME, help="write the output to the provided path"
@@ -151,6 +183,8 @@ if __name__ == "__main__":
 
     if args.currency == "BTC":
         blocklist_handler = BitcoinBlocklistHandler()
+    elif args.currency == "DOGE":
+        blocklist_handler = DogecoinBlocklistHandler()
     else:
         blocklist_handler = EthereumBlocklistHandler()
     print("Extracting addresses from " + args.input + "...")
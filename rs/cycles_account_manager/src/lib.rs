@@ -1335,6 +1335,30 @@ impl CyclesAccountManager {
         }
     }
 
+    /// Returns the fee for an http request whose responses are aggregated by the canister.
+    ///
+    /// Every node of the subnet delivers its own response of up to `response_size_limit`
+    /// bytes, so the response part is charged for all of them. If no limit is given,
+    /// the responses of all nodes together are bounded by the maximum response size.
+    pub fn http_request_fee_aggregated_by_canister(
+        &self,
+        request_size: NumBytes,
+        response_size_limit: Option<NumBytes>,
+        subnet_size: usize,
+        cost_schedule: CanisterCyclesCostSchedule,
+    ) -> Cycles {
+        let total_response_size = match response_size_limit {
+            Some(response_size) => response_size.get() * subnet_size as u64,
+            None => MAX_CANISTER_HTTP_RESPONSE_BYTES,
+        };
+        self.http_request_fee(
+            request_size,
+            Some(NumBytes::new(total_response_size)),
+            subnet_size,
+            cost_schedule,
+        )
+    }
+
     pub fn http_request_fee_v2(
         &self,
         request_size: NumBytes,
@@ -1571,3 +1571,42 @@ fn test_storage_reservation_cycles_free() {
         )
     );
 }
+
+#[test]
+fn http_request_fee_aggregated_by_canister_charges_for_all_responses() {
+    let cam = CyclesAccountManagerBuilder::new()
+        .with_subnet_type(SubnetType::Application)
+        .build();
+    let subnet_size = 13;
+    let request_size = NumBytes::new(1_000);
+
+    for cost_schedule in [
+        CanisterCyclesCostSchedule::Normal,
+        CanisterCyclesCostSchedule::Free,
+    ] {
+        assert_eq!(
+            cam.http_request_fee_aggregated_by_canister(
+                request_size,
+                Some(NumBytes::new(10_000)),
+                subnet_size,
+                cost_schedule,
+            ),
+            cam.http_request_fee(
+                request_size,
+                Some(NumBytes::new(10_000 * subnet_size as u64)),
+                subnet_size,
+                cost_schedule,
+            )
+        );
+        // Without a limit, all responses together are bounded by the maximum response size.
+        assert_eq!(
+            cam.http_request_fee_aggregated_by_canister(
+                request_size,
+                None,
+                subnet_size,
+                cost_schedule,
+            ),
+            cam.http_request_fee(request_size, None, subnet_size, cost_schedule)
+        );
+    }
+}
@@ -0,0 +1,17 @@
+//! The script to generate this file, including information about the source data, can be found here:
+//! /rs/cross-chain/scripts/generate_blocklist.py
+
+#[cfg(test)]
+mod tests;
+
+use bitcoin::dogecoin::Address;
+
+/// DOGE is not accepted from nor sent to addresses on this list.
+/// NOTE: Keep it sorted!
+pub const DOGE_ADDRESS_BLOCKLIST: &[&str] = &[];
+
+pub fn is_blocked(address: &Address) -> bool {
+    DOGE_ADDRESS_BLOCKLIST
+        .binary_search(&address.to_string().as_ref())
+        .is_ok()
+}
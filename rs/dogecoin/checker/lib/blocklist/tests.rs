@@ -0,0 +1,27 @@
+use crate::blocklist::DOGE_ADDRESS_BLOCKLIST;
+use bitcoin::dogecoin::{Address, Network};
+use std::str::FromStr;
+
+#[test]
+fn should_be_valid_dogecoin_address_in_canonical_format() {
+    for address in DOGE_ADDRESS_BLOCKLIST {
+        let parsed_address = Address::from_str(address)
+            .unwrap_or_else(|e| panic!("BUG: invalid dogecoin address '{address}', error '{e}'"))
+            .require_network(Network::Dogecoin)
+            .unwrap_or_else(|e| {
+                panic!("BUG: invalid address '{address}' for Mainnet Dogecoin, error '{e}'")
+            });
+
+        assert_eq!(address, &parsed_address.to_string())
+    }
+}
+
+#[test]
+fn blocklist_is_sorted() {
+    for (l, r) in DOGE_ADDRESS_BLOCKLIST
+        .iter()
+        .zip(DOGE_ADDRESS_BLOCKLIST.iter().skip(1))
+    {
+        assert!(l < r, "the block list is not sorted: {l} >= {r}");
+    }
+}
@@ -0,0 +1,14 @@
+pub mod blocklist;
+mod types;
+
+pub use types::*;
+
+// The checker shares its API and cycle accounting with the Bitcoin checker, so that
+// callers can handle responses of both canisters the same way.
+pub use ic_btc_checker::{
+    CHECK_TRANSACTION_CYCLES_REQUIRED, CHECK_TRANSACTION_CYCLES_SERVICE_FEE, CheckAddressArgs,
+    CheckAddressResponse, CheckMode, CheckTransactionArgs, CheckTransactionIrrecoverableError,
+    CheckTransactionQueryArgs, CheckTransactionQueryResponse, CheckTransactionResponse,
+    CheckTransactionRetriable, CheckTransactionStatus, CheckTransactionStrArgs,
+    INITIAL_MAX_RESPONSE_BYTES, RETRY_MAX_RESPONSE_BYTES, UpgradeArg, get_tx_cycle_cost,
+};
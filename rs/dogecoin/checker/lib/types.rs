@@ -0,0 +1,70 @@
+use crate::UpgradeArg;
+use crate::blocklist::is_blocked;
+use candid::{CandidType, Deserialize};
+use ic_btc_checker::{CheckMode, state::CheckerNetwork};
+use ic_btc_interface::Txid;
+use serde::Serialize;
+use std::fmt;
+
+#[derive(CandidType, Clone, Debug, Deserialize, Serialize)]
+pub struct InitArg {
+    pub doge_network: DogeNetwork,
+    pub check_mode: CheckMode,
+    pub num_subnet_nodes: u16,
+}
+
+#[derive(CandidType, Clone, Deserialize, Debug, Eq, PartialEq, Serialize, Hash)]
+pub enum DogeNetwork {
+    #[serde(rename = "mainnet")]
+    Mainnet,
+    #[serde(rename = "regtest")]
+    Regtest { json_rpc_url: String },
+}
+
+impl From<DogeNetwork> for bitcoin::dogecoin::Network {
+    fn from(doge_network: DogeNetwork) -> Self {
+        match doge_network {
+            DogeNetwork::Mainnet => Self::Dogecoin,
+            DogeNetwork::Regtest { .. } => Self::Regtest,
+        }
+    }
+}
+
+impl CheckerNetwork for DogeNetwork {
+    type Address = bitcoin::dogecoin::Address;
+
+    const CURRENCY: &'static str = "Dogecoin";
+
+    fn json_rpc_url(&self) -> Option<&str> {
+        match self {
+            Self::Regtest { json_rpc_url } => Some(json_rpc_url),
+            Self::Mainnet => None,
+        }
+    }
+
+    fn explorer_tx_url(&self, txid: &Txid) -> Option<String> {
+        match self {
+            Self::Mainnet => Some(format!("https://live.blockcypher.com/doge/tx/{txid}")),
+            Self::Regtest { .. } => None,
+        }
+    }
+
+    fn is_blocked(address: &bitcoin::dogecoin::Address) -> bool {
+        is_blocked(address)
+    }
+}
+
+impl fmt::Display for DogeNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Mainnet => write!(f, "mainnet"),
+            Self::Regtest { .. } => write!(f, "regtest"),
+        }
+    }
+}
+
+#[derive(CandidType, Debug, Deserialize, Serialize)]
+pub enum CheckArg {
+    InitArg(InitArg),
+    UpgradeArg(Option<UpgradeArg>),
+}
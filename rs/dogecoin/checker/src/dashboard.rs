@@ -0,0 +1,12 @@
+use crate::DogeNetwork;
+use crate::state::{self, DogeCheckerState};
+
+#[cfg(test)]
+mod tests;
+
+pub type DashboardTemplate = ic_btc_checker::dashboard::DashboardTemplate<DogeNetwork>;
+pub type Status = ic_btc_checker::dashboard::Status<bitcoin::dogecoin::Address>;
+
+pub fn dashboard(page_index: usize) -> DashboardTemplate {
+    ic_btc_checker::dashboard::dashboard::<_, DogeCheckerState>(state::get_config(), page_index)
+}
@@ -0,0 +1,97 @@
+use crate::dashboard::{DashboardTemplate, Status};
+use crate::state::Config;
+use askama::Template;
+use bitcoin::{PubkeyHash, ScriptBuf, dogecoin::Address, hashes::Hash};
+use ic_btc_checker::dashboard::{Fetched, filters};
+use ic_btc_interface::Txid;
+use ic_doge_checker::{CheckMode, DogeNetwork};
+use scraper::{Html, Selector};
+
+const TEST_SUBNET_NODES: u16 = 13;
+
+fn mock_address(v: u8) -> Address {
+    Address::from_script(
+        &ScriptBuf::new_p2pkh(&PubkeyHash::from_byte_array([v; 20])),
+        bitcoin::dogecoin::Network::Dogecoin,
+    )
+    .unwrap()
+}
+
+fn render(dashboard: DashboardTemplate) -> Html {
+    Html::parse_document(&dashboard.render().unwrap())
+}
+
+fn text(html: &Html, selector: &str) -> String {
+    let selector = Selector::parse(selector).unwrap();
+    let mut elements = html.select(&selector);
+    let element = elements.next().expect("expected one element, got zero");
+    assert!(elements.next().is_none(), "expected one element, got more");
+    element.text().collect::<String>()
+}
+
+#[test]
+fn should_display_metadata() {
+    let config =
+        Config::new_and_validate(DogeNetwork::Mainnet, CheckMode::Normal, TEST_SUBNET_NODES)
+            .unwrap();
+    let latest_entry_time = 1_000_000_000_000;
+    let html = render(DashboardTemplate {
+        config,
+        outcall_capacity: 50,
+        cached_entries: 0,
+        tx_table_page_size: 10,
+        tx_table_page_index: 0,
+        oldest_entry_time: None,
+        latest_entry_time: Some(latest_entry_time),
+        fetch_tx_status: Vec::new(),
+    });
+
+    assert_eq!(
+        text(&html, "title"),
+        "Dogecoin Checker Canister Dashboard for (mainnet)"
+    );
+    assert_eq!(text(&html, "#check-mode > td > code"), "Normal");
+    assert_eq!(text(&html, "#outcall-capacity > td > code"), "50");
+    assert_eq!(
+        text(&html, "#latest-entry-time > td > code"),
+        filters::timestamp_to_datetime(latest_entry_time).unwrap()
+    );
+}
+
+#[test]
+fn should_display_fetched_input_addresses() {
+    let txid = Txid::from([1; 32]);
+    let address = mock_address(1);
+    let html = render(DashboardTemplate {
+        config: Config::new_and_validate(
+            DogeNetwork::Mainnet,
+            CheckMode::Normal,
+            TEST_SUBNET_NODES,
+        )
+        .unwrap(),
+        outcall_capacity: 50,
+        cached_entries: 1,
+        tx_table_page_size: 10,
+        tx_table_page_index: 0,
+        oldest_entry_time: Some(0),
+        latest_entry_time: Some(0),
+        fetch_tx_status: vec![(
+            txid,
+            0,
+            Status::Fetched(Fetched {
+                input_addresses: vec![Some(address.clone()), None],
+            }),
+        )],
+    });
+
+    let row = "#fetch-tx-status + table > tbody > tr:nth-child(1)";
+    assert_eq!(
+        text(&html, &format!("{row} > td:nth-child(1)")),
+        txid.to_string()
+    );
+    assert_eq!(
+        text(&html, &format!("{row} li:nth-child(1) > code")),
+        address.to_string()
+    );
+    assert_eq!(text(&html, &format!("{row} li:nth-child(2) > code")), "N/A");
+}
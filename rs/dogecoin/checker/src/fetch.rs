@@ -0,0 +1,104 @@
+//! The Dogecoin specific parts of fetching transactions and checking their input
+//! addresses. The logic itself is shared with the Bitcoin checker, see
+//! [`ic_btc_checker::fetch::FetchEnv`].
+use crate::CheckMode;
+use crate::logs::{DEBUG, WARN};
+use crate::providers::{self, Provider};
+use crate::state::{
+    self, FetchGuardError, FetchTxStatus, HttpGetTxError, TransactionCheckData, get_config,
+};
+use bitcoin::{Transaction, consensus::Decodable, dogecoin::Address};
+use ic_btc_checker::{fetch::FetchEnv, outcall};
+use ic_btc_interface::Txid;
+use ic_canister_log::GlobalBuffer;
+use ic_doge_checker::blocklist::is_blocked;
+
+#[cfg(test)]
+mod tests;
+
+pub struct DogeCheckerCanisterEnv;
+
+impl FetchEnv for DogeCheckerCanisterEnv {
+    type FetchGuard = state::FetchGuard;
+    type Provider = Provider;
+    type Address = Address;
+
+    fn new_fetch_guard(&self, txid: Txid) -> Result<Self::FetchGuard, FetchGuardError> {
+        state::FetchGuard::new(txid)
+    }
+
+    fn check_mode(&self) -> CheckMode {
+        get_config().check_mode
+    }
+
+    fn num_subnet_nodes(&self) -> u16 {
+        get_config().num_subnet_nodes
+    }
+
+    fn next_provider(&self) -> Provider {
+        providers::next_provider(get_config().network())
+    }
+
+    fn retry_provider(&self, provider: &Provider) -> Provider {
+        provider.next()
+    }
+
+    fn get_fetch_status(&self, txid: Txid) -> Option<FetchTxStatus> {
+        state::get_fetch_status(txid)
+    }
+
+    fn set_fetch_status(&self, txid: Txid, status: FetchTxStatus) {
+        state::set_fetch_status(txid, status)
+    }
+
+    fn set_fetched_address(&self, txid: Txid, index: usize, address: Address) {
+        state::set_fetched_address(txid, index, address)
+    }
+
+    fn transaction_check_data(
+        &self,
+        provider: &Provider,
+        tx: &Transaction,
+    ) -> TransactionCheckData {
+        state::transaction_check_data(provider.doge_network(), tx)
+    }
+
+    fn is_blocked(&self, address: &Address) -> bool {
+        is_blocked(address)
+    }
+
+    fn warn_log(&self) -> &'static GlobalBuffer {
+        &WARN
+    }
+
+    async fn http_get_tx(
+        &self,
+        provider: &Provider,
+        txid: Txid,
+        max_response_bytes: u32,
+    ) -> Result<Transaction, HttpGetTxError> {
+        let request = provider
+            .create_request(txid, max_response_bytes)
+            .map_err(|err| HttpGetTxError::Rejected {
+                code: 1, //SYS_FATAL
+                message: err,
+            })?;
+        outcall::http_get_tx(
+            provider.name(),
+            request,
+            txid,
+            max_response_bytes,
+            &DEBUG,
+            // The transform function replaced the JSON response by the raw transaction.
+            |body| {
+                Transaction::consensus_decode(&mut &body[..])
+                    .map_err(|err| HttpGetTxError::TxEncoding(err.to_string()))
+            },
+        )
+        .await
+    }
+
+    fn cycles_accept(&self, cycles: u128) -> u128 {
+        ic_cdk::api::msg_cycles_accept(cycles)
+    }
+}
@@ -0,0 +1,294 @@
+use super::*;
+use crate::DogeNetwork;
+use crate::state::{Config, FetchedTx};
+use bitcoin::{
+    Amount, OutPoint, PubkeyHash, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+    absolute::LockTime, hashes::Hash, transaction::Version,
+};
+use ic_btc_checker::fetch::{FetchResult, TryFetchResult};
+use ic_doge_checker::{
+    CHECK_TRANSACTION_CYCLES_REQUIRED, CheckTransactionResponse, INITIAL_MAX_RESPONSE_BYTES,
+    RETRY_MAX_RESPONSE_BYTES, get_tx_cycle_cost,
+};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+// A mock environment that provides simulated `get_tx` implementation, with
+// mock states and transactions used for testing purpose.
+struct MockEnv {
+    high_load: bool,
+    calls: RefCell<VecDeque<(Txid, u32)>>,
+    replies: RefCell<VecDeque<Result<Transaction, HttpGetTxError>>>,
+    available_cycles: RefCell<u128>,
+}
+
+const TEST_SUBNET_NODES: u16 = 13;
+
+impl FetchEnv for MockEnv {
+    type FetchGuard = ();
+    type Provider = Provider;
+    type Address = Address;
+
+    fn new_fetch_guard(&self, _txid: Txid) -> Result<Self::FetchGuard, FetchGuardError> {
+        if self.high_load {
+            Err(FetchGuardError::NoCapacity)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_mode(&self) -> CheckMode {
+        self.config().check_mode
+    }
+
+    fn num_subnet_nodes(&self) -> u16 {
+        self.config().num_subnet_nodes
+    }
+
+    fn next_provider(&self) -> Provider {
+        providers::next_provider(self.config().network())
+    }
+
+    fn retry_provider(&self, provider: &Provider) -> Provider {
+        provider.next()
+    }
+
+    fn get_fetch_status(&self, txid: Txid) -> Option<FetchTxStatus> {
+        state::get_fetch_status(txid)
+    }
+
+    fn set_fetch_status(&self, txid: Txid, status: FetchTxStatus) {
+        state::set_fetch_status(txid, status)
+    }
+
+    fn set_fetched_address(&self, txid: Txid, index: usize, address: Address) {
+        state::set_fetched_address(txid, index, address)
+    }
+
+    fn transaction_check_data(
+        &self,
+        provider: &Provider,
+        tx: &Transaction,
+    ) -> TransactionCheckData {
+        state::transaction_check_data(provider.doge_network(), tx)
+    }
+
+    fn is_blocked(&self, address: &Address) -> bool {
+        is_blocked(address)
+    }
+
+    fn warn_log(&self) -> &'static GlobalBuffer {
+        &WARN
+    }
+
+    async fn http_get_tx(
+        &self,
+        _provider: &Provider,
+        txid: Txid,
+        max_response_bytes: u32,
+    ) -> Result<Transaction, HttpGetTxError> {
+        self.calls
+            .borrow_mut()
+            .push_back((txid, max_response_bytes));
+        self.replies
+            .borrow_mut()
+            .pop_front()
+            .unwrap_or(Err(HttpGetTxError::Rejected {
+                code: 2, //SYS_TRANSIENT
+                message: "no more reply".to_string(),
+            }))
+    }
+
+    fn cycles_accept(&self, cycles: u128) -> u128 {
+        let mut available = self.available_cycles.borrow_mut();
+        let cycles = cycles.min(*available);
+        *available -= cycles;
+        cycles
+    }
+}
+
+impl MockEnv {
+    fn config(&self) -> Config {
+        Config::new_and_validate(DogeNetwork::Mainnet, CheckMode::Normal, TEST_SUBNET_NODES)
+            .unwrap()
+    }
+
+    fn new(available_cycles: u128) -> Self {
+        Self {
+            high_load: false,
+            calls: RefCell::new(VecDeque::new()),
+            replies: RefCell::new(VecDeque::new()),
+            available_cycles: RefCell::new(available_cycles),
+        }
+    }
+    fn assert_get_tx_call(&self, txid: Txid, max_response_bytes: u32) {
+        assert_eq!(
+            self.calls.borrow_mut().pop_front(),
+            Some((txid, max_response_bytes))
+        )
+    }
+    fn assert_no_more_get_tx_call(&self) {
+        assert_eq!(self.calls.borrow_mut().pop_front(), None)
+    }
+    fn expect_get_tx_with_reply(&self, reply: Result<Transaction, HttpGetTxError>) {
+        self.replies.borrow_mut().push_back(reply)
+    }
+    fn cycles_available(&self) -> u128 {
+        *self.available_cycles.borrow()
+    }
+}
+
+fn mock_txid(v: u8) -> Txid {
+    Txid::from([v; 32])
+}
+
+fn mock_transaction() -> Transaction {
+    Transaction {
+        version: Version::ONE,
+        lock_time: LockTime::ZERO,
+        input: Vec::new(),
+        output: Vec::new(),
+    }
+}
+
+fn mock_transaction_with_outputs(num_outputs: usize) -> Transaction {
+    let mut tx = mock_transaction();
+    tx.output = (0..num_outputs)
+        .map(|i| TxOut {
+            value: Amount::ONE_SAT,
+            script_pubkey: ScriptBuf::new_p2pkh(&PubkeyHash::from_byte_array([i as u8; 20])),
+        })
+        .collect();
+    tx
+}
+
+fn mock_transaction_with_inputs(input_txids: Vec<(Txid, u32)>) -> Transaction {
+    let mut tx = mock_transaction();
+    tx.input = input_txids
+        .into_iter()
+        .enumerate()
+        .map(|(i, (txid, vout))| TxIn {
+            previous_output: OutPoint {
+                txid: bitcoin::Txid::from_slice(txid.as_ref()).unwrap(),
+                vout,
+            },
+            script_sig: ScriptBuf::from_bytes(vec![i as u8; 32]),
+            sequence: Sequence::ZERO,
+            witness: Witness::new(),
+        })
+        .collect();
+    tx
+}
+
+#[test]
+fn test_try_fetch_tx() {
+    let mut env = MockEnv::new(CHECK_TRANSACTION_CYCLES_REQUIRED);
+    let txid_0 = mock_txid(0);
+    let txid_1 = mock_txid(1);
+    let txid_2 = mock_txid(2);
+
+    // case Fetched
+    state::set_fetch_status(
+        txid_0,
+        FetchTxStatus::Fetched(FetchedTx {
+            tx: state::transaction_check_data(&env.config().network(), &mock_transaction()),
+            input_addresses: vec![],
+        }),
+    );
+    assert!(matches!(
+        env.try_fetch_tx(txid_0),
+        TryFetchResult::Fetched(_)
+    ));
+
+    // case Pending
+    state::set_fetch_status(txid_1, FetchTxStatus::PendingOutcall);
+    assert!(matches!(env.try_fetch_tx(txid_1), TryFetchResult::Pending));
+
+    // case HighLoad
+    env.high_load = true;
+    assert!(matches!(env.try_fetch_tx(txid_2), TryFetchResult::HighLoad));
+    env.high_load = false;
+
+    // case NotEnoughCycles
+    assert!(matches!(
+        MockEnv::new(0).try_fetch_tx(txid_2),
+        TryFetchResult::NotEnoughCycles
+    ));
+
+    // case ToFetch
+    let available = env.cycles_available();
+    assert!(matches!(
+        env.try_fetch_tx(txid_2),
+        TryFetchResult::ToFetch(_)
+    ));
+    assert_eq!(
+        env.cycles_available(),
+        available - get_tx_cycle_cost(INITIAL_MAX_RESPONSE_BYTES, TEST_SUBNET_NODES)
+    );
+}
+
+#[tokio::test]
+async fn test_fetch_tx() {
+    let env = MockEnv::new(CHECK_TRANSACTION_CYCLES_REQUIRED);
+    let provider = providers::next_provider(env.config().network());
+    let txid_0 = mock_txid(0);
+    let txid_1 = mock_txid(1);
+
+    // case Fetched
+    let tx_0 = mock_transaction_with_inputs(vec![(txid_1, 0)]);
+    env.expect_get_tx_with_reply(Ok(tx_0));
+    let result = env
+        .fetch_tx((), provider.clone(), txid_0, INITIAL_MAX_RESPONSE_BYTES)
+        .await;
+    assert!(matches!(
+        result,
+        Ok(FetchResult::Fetched(FetchedTx { input_addresses, .. })) if input_addresses == vec![None]
+    ));
+    env.assert_get_tx_call(txid_0, INITIAL_MAX_RESPONSE_BYTES);
+
+    // case RetryWithBiggerBuffer
+    env.expect_get_tx_with_reply(Err(HttpGetTxError::ResponseTooLarge));
+    let result = env
+        .fetch_tx((), provider.clone(), txid_1, INITIAL_MAX_RESPONSE_BYTES)
+        .await;
+    assert!(matches!(result, Ok(FetchResult::RetryWithBiggerBuffer)));
+    assert!(matches!(
+        state::get_fetch_status(txid_1),
+        Some(FetchTxStatus::PendingRetry { max_response_bytes }) if max_response_bytes == RETRY_MAX_RESPONSE_BYTES
+    ));
+}
+
+#[tokio::test]
+async fn test_check_fetched_fetches_input_transactions() {
+    let env = MockEnv::new(CHECK_TRANSACTION_CYCLES_REQUIRED);
+    let txid_0 = mock_txid(0);
+    let txid_1 = mock_txid(1);
+    let txid_2 = mock_txid(2);
+    let tx_0 = mock_transaction_with_inputs(vec![(txid_1, 0), (txid_2, 1)]);
+    let fetched = FetchedTx {
+        tx: state::transaction_check_data(&env.config().network(), &tx_0),
+        input_addresses: vec![None, None],
+    };
+    state::set_fetch_status(txid_0, FetchTxStatus::Fetched(fetched.clone()));
+
+    env.expect_get_tx_with_reply(Ok(mock_transaction_with_outputs(1)));
+    env.expect_get_tx_with_reply(Ok(mock_transaction_with_outputs(2)));
+    assert!(matches!(
+        env.check_fetched(txid_0, &fetched).await,
+        CheckTransactionResponse::Passed
+    ));
+    env.assert_get_tx_call(txid_1, INITIAL_MAX_RESPONSE_BYTES);
+    env.assert_get_tx_call(txid_2, INITIAL_MAX_RESPONSE_BYTES);
+    env.assert_no_more_get_tx_call();
+    match state::get_fetch_status(txid_0) {
+        Some(FetchTxStatus::Fetched(fetched)) => {
+            assert!(
+                fetched
+                    .input_addresses
+                    .iter()
+                    .all(|address| address.is_some())
+            )
+        }
+        status => panic!("unexpected status {status:?}"),
+    }
+}
@@ -0,0 +1,291 @@
+use bitcoin::dogecoin::Address;
+use fetch::DogeCheckerCanisterEnv;
+use ic_btc_checker::{fetch::FetchEnv, outcall};
+use ic_btc_interface::Txid;
+use ic_canister_log::export as export_logs;
+use ic_cdk::management_canister::TransformArgs;
+use ic_doge_checker::{
+    CheckAddressArgs, CheckAddressResponse, CheckArg, CheckMode, CheckTransactionArgs,
+    CheckTransactionQueryArgs, CheckTransactionQueryResponse, CheckTransactionResponse,
+    CheckTransactionStrArgs, DogeNetwork, blocklist::is_blocked,
+};
+use ic_http_types as http;
+use ic_management_canister_types::HttpRequestResult;
+use logs::{DEBUG, Log, LogEntry, Priority, WARN};
+use state::{Config, get_config, set_config};
+use std::str::FromStr;
+
+mod dashboard;
+mod fetch;
+mod logs;
+mod providers;
+mod state;
+
+/// Return `Passed` if the given Dogecoin address passed the check, or
+/// `Failed` otherwise.
+/// May throw error (trap) if the given address is malformed or not an address of the
+/// configured network.
+#[ic_cdk::query]
+fn check_address(args: CheckAddressArgs) -> CheckAddressResponse {
+    let config = get_config();
+    let doge_network = config.network();
+    let address = Address::from_str(args.address.trim())
+        .unwrap_or_else(|err| ic_cdk::trap(format!("Invalid Dogecoin address: {err}")))
+        .require_network(doge_network.clone().into())
+        .unwrap_or_else(|err| {
+            ic_cdk::trap(format!("Not a Dogecoin {doge_network} address: {err}"))
+        });
+
+    match config.check_mode {
+        CheckMode::AcceptAll => CheckAddressResponse::Passed,
+        CheckMode::RejectAll => CheckAddressResponse::Failed,
+        CheckMode::Normal => {
+            if is_blocked(&address) {
+                return CheckAddressResponse::Failed;
+            }
+            CheckAddressResponse::Passed
+        }
+    }
+}
+
+/// Return `Passed` if all input addresses of the transaction of the given
+/// transaction id passed the check, or `Failed` if any of them did not.
+///
+/// Every call to check_transaction must attach at least `CHECK_TRANSACTION_CYCLES_REQUIRED`.
+/// Return `NotEnoughCycles` if not enough cycles are attached.
+///
+/// The actual cycle cost may be well less than `CHECK_TRANSACTION_CYCLES_REQUIRED`, and
+/// unspent cycles will be refunded back to the caller, minus a
+/// `CHECK_TRANSACTION_CYCLES_SERVICE_FEE`, which is always deducted regardless.
+///
+/// In certain cases, it may also return `HighLoad` or `Pending` to indicate the
+/// caller needs to call again (with at least `CHECK_TRANSACTION_CYCLES_REQUIRED` cycles)
+/// in order to get the result.
+///
+/// If a permanent error occurred in the process, e.g, when a transaction data
+/// fails to decode or its transaction id does not match, then `Error` is returned
+/// together with a text description.
+#[ic_cdk::update]
+async fn check_transaction(args: CheckTransactionArgs) -> CheckTransactionResponse {
+    DogeCheckerCanisterEnv
+        .check_transaction_with(|| Txid::try_from(args))
+        .await
+}
+
+#[ic_cdk::update]
+async fn check_transaction_str(args: CheckTransactionStrArgs) -> CheckTransactionResponse {
+    DogeCheckerCanisterEnv
+        .check_transaction_with(|| Txid::try_from(args))
+        .await
+}
+
+/// Performs the same logic as `check_transaction`, but only returns `Pass` or `Failed` if the
+/// TXID and all of its inputs are available in the heap memory cache, meaning no HTTP outcalls are
+/// performed. Returns `Unknown` if the transaction cannot be checked with only cached information.
+#[ic_cdk::query]
+async fn check_transaction_query(args: CheckTransactionQueryArgs) -> CheckTransactionQueryResponse {
+    match Txid::try_from(args) {
+        Ok(txid) => DogeCheckerCanisterEnv.check_fetched_transaction_inputs(txid),
+        Err(err) => panic!("Invalid transaction ID: {err}"),
+    }
+}
+
+/// Replaces the JSON response of a provider by the raw transaction it contains, so that
+/// all replicas agree on the response. The context holds the JSON pointer to the hex
+/// encoded transaction. An empty body is returned if the transaction cannot be extracted.
+#[ic_cdk::query(hidden = true)]
+fn transform(raw: TransformArgs) -> HttpRequestResult {
+    let body = if raw.response.status == 200u32 {
+        let pointer = String::from_utf8_lossy(&raw.context);
+        providers::extract_raw_transaction(&raw.response.body, &pointer).unwrap_or_default()
+    } else {
+        vec![]
+    };
+    HttpRequestResult {
+        status: raw.response.status,
+        body,
+        headers: vec![],
+    }
+}
+
+#[ic_cdk::init]
+fn init(arg: Option<CheckArg>) {
+    match arg {
+        Some(CheckArg::InitArg(init_arg)) => set_config(
+            Config::new_and_validate(
+                init_arg.doge_network,
+                init_arg.check_mode,
+                init_arg.num_subnet_nodes,
+            )
+            .unwrap_or_else(|err| ic_cdk::trap(format!("error creating config: {err}"))),
+        ),
+        _ => {
+            ic_cdk::trap("cannot init canister state without init args");
+        }
+    }
+}
+
+#[ic_cdk::post_upgrade]
+fn post_upgrade(arg: Option<CheckArg>) {
+    match arg {
+        Some(CheckArg::UpgradeArg(arg)) => {
+            let old_config = get_config();
+            let num_subnet_nodes = arg
+                .as_ref()
+                .and_then(|arg| arg.num_subnet_nodes)
+                .unwrap_or(old_config.num_subnet_nodes);
+            let check_mode = arg
+                .as_ref()
+                .and_then(|arg| arg.check_mode)
+                .unwrap_or(old_config.check_mode);
+            let config =
+                Config::new_and_validate(old_config.network(), check_mode, num_subnet_nodes)
+                    .unwrap_or_else(|err| ic_cdk::trap(format!("error creating config: {err}")));
+            set_config(config);
+        }
+        Some(CheckArg::InitArg(_)) => {
+            ic_cdk::trap("cannot upgrade canister state without upgrade args")
+        }
+        _ => (), // config remains unchanged if no upgrade argument is specified
+    }
+}
+
+#[ic_cdk::query(hidden = true)]
+fn http_request(req: http::HttpRequest) -> http::HttpResponse {
+    if ic_cdk::api::in_replicated_execution() {
+        ic_cdk::trap("update call rejected");
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn heap_memory_size_bytes() -> usize {
+        const WASM_PAGE_SIZE_BYTES: usize = 65536;
+        core::arch::wasm32::memory_size(0) * WASM_PAGE_SIZE_BYTES
+    }
+
+    #[cfg(not(any(target_arch = "wasm32")))]
+    fn heap_memory_size_bytes() -> usize {
+        0
+    }
+
+    if req.path() == "/metrics" {
+        let mut writer =
+            ic_metrics_encoder::MetricsEncoder::new(vec![], ic_cdk::api::time() as i64 / 1_000_000);
+
+        let cycle_balance = ic_cdk::api::canister_cycle_balance() as f64;
+
+        writer
+            .gauge_vec("cycle_balance", "The canister cycle balance.")
+            .unwrap()
+            .value(&[("canister", "doge-checker")], cycle_balance)
+            .unwrap();
+
+        writer
+            .encode_gauge(
+                "heap_memory_bytes",
+                heap_memory_size_bytes() as f64,
+                "Size of the heap memory allocated by this canister.",
+            )
+            .unwrap();
+
+        writer
+            .encode_gauge(
+                "stable_memory_bytes",
+                ic_cdk::stable::stable_size() as f64 * 65536.0,
+                "Size of the stable memory allocated by this canister.",
+            )
+            .unwrap();
+
+        outcall::encode_stats_metrics(&mut writer, "doge");
+
+        http::HttpResponseBuilder::ok()
+            .header("Content-Type", "text/plain; version=0.0.4")
+            .header("Cache-Control", "no-store")
+            .with_body_and_content_length(writer.into_inner())
+            .build()
+    } else if req.path() == "/logs" {
+        use serde_json;
+
+        let max_skip_timestamp = match req.raw_query_param("time") {
+            Some(arg) => match u64::from_str(arg) {
+                Ok(value) => value,
+                Err(_) => {
+                    return http::HttpResponseBuilder::bad_request()
+                        .with_body_and_content_length("failed to parse the 'time' parameter")
+                        .build();
+                }
+            },
+            None => 0,
+        };
+
+        let mut entries: Log = Default::default();
+        for entry in export_logs(&WARN) {
+            if entry.timestamp >= max_skip_timestamp {
+                entries.entries.push(LogEntry {
+                    timestamp: entry.timestamp,
+                    counter: entry.counter,
+                    priority: Priority::Warn,
+                    file: entry.file.to_string(),
+                    line: entry.line,
+                    message: entry.message,
+                });
+            }
+        }
+        for entry in export_logs(&DEBUG) {
+            if entry.timestamp >= max_skip_timestamp {
+                entries.entries.push(LogEntry {
+                    timestamp: entry.timestamp,
+                    counter: entry.counter,
+                    priority: Priority::Debug,
+                    file: entry.file.to_string(),
+                    line: entry.line,
+                    message: entry.message,
+                });
+            }
+        }
+        http::HttpResponseBuilder::ok()
+            .header("Content-Type", "application/json; charset=utf-8")
+            .with_body_and_content_length(serde_json::to_string(&entries).unwrap_or_default())
+            .build()
+    } else if req.path() == "/dashboard" {
+        use askama::Template;
+        let page_index = match req.raw_query_param("page") {
+            Some(arg) => match usize::from_str(arg) {
+                Ok(value) => value,
+                Err(_) => {
+                    return http::HttpResponseBuilder::bad_request()
+                        .with_body_and_content_length("failed to parse the 'page' parameter")
+                        .build();
+                }
+            },
+            None => 0,
+        };
+        let dashboard = dashboard::dashboard(page_index).render().unwrap();
+        http::HttpResponseBuilder::ok()
+            .header("Content-Type", "text/html; charset=utf-8")
+            .with_body_and_content_length(dashboard)
+            .build()
+    } else {
+        http::HttpResponseBuilder::not_found().build()
+    }
+}
+
+fn main() {}
+
+#[test]
+fn check_candid_interface_compatibility() {
+    use candid_parser::utils::{CandidSource, service_equal};
+
+    candid::export_service!();
+
+    let new_interface = __export_service();
+
+    // check the public interface against the actual one
+    let old_interface = std::path::PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap())
+        .join("doge_checker_canister.did");
+
+    service_equal(
+        CandidSource::Text(dbg!(&new_interface)),
+        CandidSource::File(old_interface.as_path()),
+    )
+    .unwrap();
+}
@@ -0,0 +1,172 @@
+use crate::DogeNetwork;
+use ic_btc_checker::outcall::parse_authorization_header_from_url;
+use ic_btc_interface::Txid;
+use ic_cdk::management_canister::{HttpMethod, HttpRequestArgs, TransformContext, TransformFunc};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::fmt;
+
+#[cfg(test)]
+mod tests;
+
+/// Return the next Dogecoin API provider for the given `doge_network`.
+///
+/// Internally it remembers the previously used provider in a thread local
+/// state and would iterate through all providers in a round-robin manner.
+pub fn next_provider(doge_network: DogeNetwork) -> Provider {
+    PREVIOUS_PROVIDER_ID.with(|previous| {
+        let provider = (Provider {
+            doge_network,
+            provider_id: *previous.borrow(),
+        })
+        .next();
+        *previous.borrow_mut() = provider.provider_id;
+        provider
+    })
+}
+
+thread_local! {
+    static PREVIOUS_PROVIDER_ID: RefCell<ProviderId> = const { RefCell::new(ProviderId::Blockchair) };
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum ProviderId {
+    Blockchair,
+    BlockCypher,
+}
+
+impl fmt::Display for ProviderId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Blockchair => write!(f, "blockchair.com"),
+            Self::BlockCypher => write!(f, "blockcypher.com"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Provider {
+    doge_network: DogeNetwork,
+    provider_id: ProviderId,
+}
+
+impl Provider {
+    pub fn doge_network(&self) -> &DogeNetwork {
+        &self.doge_network
+    }
+
+    pub fn name(&self) -> String {
+        match self.doge_network {
+            DogeNetwork::Mainnet => self.provider_id.to_string(),
+            DogeNetwork::Regtest { .. } => "Regtest".to_string(),
+        }
+    }
+
+    // Return the next provider by cycling through all available providers.
+    pub fn next(&self) -> Self {
+        let doge_network = &self.doge_network;
+        let provider_id = match (doge_network, self.provider_id) {
+            (DogeNetwork::Mainnet, ProviderId::Blockchair) => ProviderId::BlockCypher,
+            (DogeNetwork::Mainnet, ProviderId::BlockCypher) => ProviderId::Blockchair,
+            (DogeNetwork::Regtest { .. }, _) => return self.clone(),
+        };
+        Self {
+            doge_network: doge_network.clone(),
+            provider_id,
+        }
+    }
+
+    pub fn create_request(
+        &self,
+        txid: Txid,
+        max_response_bytes: u32,
+    ) -> Result<HttpRequestArgs, String> {
+        let transform = param_transform(self.raw_transaction_pointer(txid));
+        match (self.provider_id, &self.doge_network) {
+            (_, DogeNetwork::Regtest { json_rpc_url }) => {
+                make_post_request(json_rpc_url, txid, max_response_bytes, transform)
+            }
+            (ProviderId::Blockchair, DogeNetwork::Mainnet) => Ok(make_get_request(
+                format!("https://api.blockchair.com/dogecoin/raw/transaction/{txid}"),
+                max_response_bytes,
+                transform,
+            )),
+            (ProviderId::BlockCypher, DogeNetwork::Mainnet) => Ok(make_get_request(
+                format!("https://api.blockcypher.com/v1/doge/main/txs/{txid}?includeHex=true"),
+                max_response_bytes,
+                transform,
+            )),
+        }
+    }
+
+    /// Return the JSON pointer to the hex encoded raw transaction in the response of this
+    /// provider. Unlike Bitcoin block explorers, Dogecoin providers do not serve raw
+    /// transactions as binary, so every response is JSON.
+    pub fn raw_transaction_pointer(&self, txid: Txid) -> String {
+        match (self.provider_id, &self.doge_network) {
+            (_, DogeNetwork::Regtest { .. }) => "/result/hex".to_string(),
+            (ProviderId::Blockchair, DogeNetwork::Mainnet) => {
+                format!("/data/{txid}/raw_transaction")
+            }
+            (ProviderId::BlockCypher, DogeNetwork::Mainnet) => "/hex".to_string(),
+        }
+    }
+}
+
+/// Extract the raw transaction from a JSON response given the JSON pointer to its hex
+/// encoding. This must happen in the transform function, because the other fields of the
+/// response, e.g., the number of confirmations, may differ between replicas.
+pub fn extract_raw_transaction(body: &[u8], pointer: &str) -> Result<Vec<u8>, String> {
+    let json: Value =
+        serde_json::from_slice(body).map_err(|err| format!("JSON parsing error {err}"))?;
+    let hex = json
+        .pointer(pointer)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("Missing {pointer} field in JSON response"))?;
+    hex::decode(hex).map_err(|err| format!("decode hex error: {err}"))
+}
+
+fn make_get_request(
+    url: String,
+    max_response_bytes: u32,
+    transform: Option<TransformContext>,
+) -> HttpRequestArgs {
+    HttpRequestArgs {
+        url,
+        method: HttpMethod::GET,
+        body: None,
+        max_response_bytes: Some(max_response_bytes as u64),
+        transform,
+        headers: vec![],
+        is_replicated: None,
+    }
+}
+
+fn make_post_request(
+    json_rpc_url: &str,
+    txid: Txid,
+    max_response_bytes: u32,
+    transform: Option<TransformContext>,
+) -> Result<HttpRequestArgs, String> {
+    let (url, header) = parse_authorization_header_from_url(json_rpc_url)?;
+    let body = format!("{{\"method\": \"gettransaction\", \"params\": [\"{txid}\"]}}");
+    Ok(HttpRequestArgs {
+        url: url.to_string(),
+        method: HttpMethod::POST,
+        body: Some(body.as_bytes().to_vec()),
+        max_response_bytes: Some(max_response_bytes as u64),
+        transform,
+        headers: vec![header],
+        is_replicated: None,
+    })
+}
+
+fn param_transform(raw_transaction_pointer: String) -> Option<TransformContext> {
+    Some(TransformContext {
+        function: TransformFunc(candid::Func {
+            principal: ic_cdk::api::canister_self(),
+            method: "transform".to_string(),
+        }),
+        context: raw_transaction_pointer.into_bytes(),
+    })
+}
@@ -0,0 +1,110 @@
+use crate::{DogeNetwork, providers::Provider};
+use bitcoin::{Transaction, dogecoin::Address};
+use ic_btc_checker::state::{ConfigState, FetchTxCache, FetchTxCacheStore, MAX_FETCH_TX_ENTRIES};
+use ic_btc_interface::Txid;
+use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
+use ic_stable_structures::{Cell, DefaultMemoryImpl};
+use std::cell::RefCell;
+
+#[cfg(test)]
+mod tests;
+
+pub use ic_btc_checker::fetch::{FetchGuardError, HttpGetTxError, PreviousOutput};
+pub use ic_btc_checker::state::Timestamp;
+
+pub type Config = ic_btc_checker::state::Config<DogeNetwork>;
+pub type FetchGuard = ic_btc_checker::state::FetchGuard<DogeCheckerState>;
+pub type FetchTxStatus = ic_btc_checker::fetch::FetchTxStatus<Provider, Address>;
+pub type FetchTxStatusError = ic_btc_checker::fetch::FetchTxStatusError<Provider>;
+pub type FetchedTx = ic_btc_checker::fetch::FetchedTx<Address>;
+pub type TransactionCheckData = ic_btc_checker::fetch::TransactionCheckData<Address>;
+
+/// Extracts the data needed to check the given transaction on the given network.
+pub fn transaction_check_data(
+    doge_network: &DogeNetwork,
+    tx: &Transaction,
+) -> TransactionCheckData {
+    TransactionCheckData::from_transaction(tx, |script| {
+        Address::from_script(
+            script,
+            bitcoin::dogecoin::Network::from(doge_network.clone()),
+        )
+        .ok()
+    })
+}
+
+// The fetch transaction status, indexed by transaction id.
+thread_local! {
+    static FETCH_TX_CACHE: RefCell<FetchTxCache<FetchTxStatus>> = RefCell::new(
+        FetchTxCache::new(MAX_FETCH_TX_ENTRIES)
+    );
+}
+
+pub struct DogeCheckerState;
+
+impl FetchTxCacheStore for DogeCheckerState {
+    type Provider = Provider;
+    type Address = Address;
+
+    fn with_fetch_tx_cache<R>(f: impl FnOnce(&mut FetchTxCache<FetchTxStatus>) -> R) -> R {
+        FETCH_TX_CACHE.with(|cache| f(&mut cache.borrow_mut()))
+    }
+
+    // Mock the time when running tests.
+    #[cfg(test)]
+    fn now() -> Timestamp {
+        0
+    }
+
+    #[cfg(not(test))]
+    fn now() -> Timestamp {
+        ic_cdk::api::time()
+    }
+}
+
+pub fn get_fetch_status(txid: Txid) -> Option<FetchTxStatus> {
+    ic_btc_checker::state::get_fetch_status::<DogeCheckerState>(txid)
+}
+
+pub fn set_fetch_status(txid: Txid, status: FetchTxStatus) {
+    ic_btc_checker::state::set_fetch_status::<DogeCheckerState>(txid, status)
+}
+
+pub fn clear_fetch_status(txid: Txid) {
+    ic_btc_checker::state::clear_fetch_status::<DogeCheckerState>(txid)
+}
+
+pub fn set_fetched_address(txid: Txid, index: usize, address: Address) {
+    ic_btc_checker::state::set_fetched_address::<DogeCheckerState>(txid, index, address)
+}
+
+type StableMemory = VirtualMemory<DefaultMemoryImpl>;
+
+const CONFIG_MEMORY_ID: MemoryId = MemoryId::new(0);
+
+// Configuration is stored in stable memory.
+thread_local! {
+    static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = RefCell::new(
+            MemoryManager::init(DefaultMemoryImpl::default())
+    );
+    static CONFIG: RefCell<Cell<ConfigState<DogeNetwork>, StableMemory>> = RefCell::new(
+        Cell::init(config_memory(), ConfigState::Uninitialized).expect("failed to initialize stable cell for config")
+    );
+}
+
+fn config_memory() -> StableMemory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(CONFIG_MEMORY_ID))
+}
+
+pub fn set_config(config: Config) {
+    CONFIG
+        .with(|c| c.borrow_mut().set(ConfigState::Initialized(config)))
+        .expect("failed to set config");
+}
+
+pub fn get_config() -> Config {
+    match CONFIG.with(|c| c.borrow().get().clone()) {
+        ConfigState::Uninitialized => panic!("config is uninitialized"),
+        ConfigState::Initialized(config) => config,
+    }
+}
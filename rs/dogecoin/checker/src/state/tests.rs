@@ -0,0 +1,47 @@
+use super::*;
+use bitcoin::{
+    Amount, OutPoint, PubkeyHash, ScriptBuf, Sequence, TxIn, TxOut, Witness, absolute::LockTime,
+    hashes::Hash, opcodes::all::OP_RETURN, script::Builder, transaction::Version,
+};
+
+#[test]
+fn test_transaction_check_data_uses_dogecoin_addresses() {
+    let previous_txid = bitcoin::Txid::from_byte_array([1; 32]);
+    let tx = Transaction {
+        version: Version::ONE,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(previous_txid, 3),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+        }],
+        output: vec![
+            TxOut {
+                value: Amount::from_sat(1_000),
+                script_pubkey: ScriptBuf::new_p2pkh(&PubkeyHash::from_byte_array([2; 20])),
+            },
+            TxOut {
+                value: Amount::ZERO,
+                script_pubkey: Builder::new().push_opcode(OP_RETURN).into_script(),
+            },
+        ],
+    };
+
+    let data = transaction_check_data(&DogeNetwork::Mainnet, &tx);
+
+    assert_eq!(
+        data.inputs,
+        vec![PreviousOutput {
+            txid: Txid::from([1; 32]),
+            vout: 3
+        }]
+    );
+    assert_eq!(data.outputs.len(), 2);
+    let address = data.outputs[0].as_ref().unwrap().to_string();
+    assert!(
+        address.starts_with('D'),
+        "{address} is not a Dogecoin mainnet P2PKH address"
+    );
+    assert_eq!(data.outputs[1], None);
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests;
+
+use crate::DOGECOIN_CANISTER_RUNTIME;
+use crate::address::DogecoinAddress;
+use crate::lifecycle::init::Network;
+use ic_ckbtc_minter::{
+    CanisterRuntime, Txid, address::BitcoinAddress, dashboard::DashboardNetwork,
+    state::CkBtcMinterState,
+};
+
+/// The dashboard of the ckDOGE minter.
+pub struct Dogecoin;
+
+impl DashboardNetwork for Dogecoin {
+    const TOKEN: &'static str = "ckDOGE";
+    const CURRENCY: &'static str = "DOGE";
+    const NETWORK_NAME: &'static str = "Dogecoin";
+    const TOKEN_URL: Option<&'static str> = None;
+
+    fn network(s: &CkBtcMinterState) -> String {
+        Network::from(s.btc_network).to_string()
+    }
+
+    fn main_address(s: &CkBtcMinterState) -> String {
+        s.ecdsa_public_key
+            .as_ref()
+            .map(|_| DOGECOIN_CANISTER_RUNTIME.derive_minter_address_str(s))
+            .unwrap_or_default()
+    }
+
+    /// Displays an address stored in the minter state as a Dogecoin address.
+    ///
+    /// The minter state stores Dogecoin addresses as [`BitcoinAddress`] (see
+    /// [`crate::DogeCanisterRuntime::parse_address`]), so only P2PKH and P2SH addresses can occur.
+    fn display_address(address: &BitcoinAddress, s: &CkBtcMinterState) -> String {
+        let network = Network::from(s.btc_network);
+        match address {
+            BitcoinAddress::P2pkh(bytes) => DogecoinAddress::P2pkh(*bytes).display(&network),
+            BitcoinAddress::P2sh(bytes) => DogecoinAddress::P2sh(*bytes).display(&network),
+            _ => "N/A".to_string(),
+        }
+    }
+
+    fn txid_link(txid: &Txid, s: &CkBtcMinterState) -> String {
+        txid_link_on(txid, Network::from(s.btc_network))
+    }
+}
+
+pub fn build_dashboard(account_to_utxos_start: u64) -> Vec<u8> {
+    ic_ckbtc_minter::dashboard::build_dashboard::<Dogecoin>(account_to_utxos_start)
+}
+
+/// Only transactions on the Dogecoin mainnet are linked to a block explorer.
+fn txid_link_on(txid: &Txid, network: Network) -> String {
+    match network {
+        Network::Mainnet => format!(
+            "<a target='_blank' href='https://blockchair.com/dogecoin/transaction/{txid}'><code>{txid}</code></a>",
+        ),
+        Network::Testnet | Network::Regtest => format!("<code>{txid}</code>"),
+    }
+}
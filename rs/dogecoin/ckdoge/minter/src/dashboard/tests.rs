@@ -0,0 +1,105 @@
+use crate::address::DogecoinAddress;
+use crate::dashboard::Dogecoin;
+use crate::lifecycle::init::{InitArgs, Mode, Network};
+use crate::{BitcoinAddress, OutPoint, RetrieveBtcRequest, Txid, Utxo};
+use candid::Principal;
+use ic_ckbtc_minter::dashboard;
+use ic_ckbtc_minter::state::CkBtcMinterState;
+use icrc_ledger_types::icrc1::account::Account;
+
+fn state(network: Network) -> CkBtcMinterState {
+    CkBtcMinterState::from(ic_ckbtc_minter::lifecycle::init::InitArgs::from(InitArgs {
+        doge_network: network,
+        ecdsa_key_name: "key_1".to_string(),
+        retrieve_doge_min_amount: 50 * 100_000_000,
+        ledger_id: Principal::from_slice(&[0, 0, 0, 0, 2, 48, 0, 7, 1, 1]),
+        max_time_in_queue_nanos: 600_000_000_000,
+        min_confirmations: Some(60),
+        mode: Mode::GeneralAvailability,
+        get_utxos_cache_expiration_seconds: None,
+    }))
+}
+
+fn utxo(v: u64) -> Utxo {
+    let mut bytes = [0u8; 32];
+    bytes[0..8].copy_from_slice(&v.to_be_bytes());
+    Utxo {
+        outpoint: OutPoint {
+            txid: bytes.into(),
+            vout: 0,
+        },
+        value: v,
+        height: 0,
+    }
+}
+
+#[test]
+fn should_display_dogecoin_addresses() {
+    for network in [Network::Mainnet, Network::Testnet, Network::Regtest] {
+        let mut state = state(network);
+        state.push_back_pending_request(RetrieveBtcRequest {
+            amount: 100 * 100_000_000,
+            address: BitcoinAddress::P2pkh([1; 20]),
+            block_index: 7,
+            received_at: 0,
+            kyt_provider: None,
+            reimbursement_account: None,
+        });
+
+        let table = dashboard::build_pending_request_tx::<Dogecoin>(&state);
+
+        let doge_address = DogecoinAddress::P2pkh([1; 20]).display(&network);
+        assert!(
+            table.contains(&format!("<code>{doge_address}</code>")),
+            "{table}"
+        );
+        assert!(table.contains("100"), "{table}");
+    }
+}
+
+#[test]
+fn should_link_only_mainnet_transactions() {
+    let txid = Txid::from([42; 32]);
+    let mut mainnet = state(Network::Mainnet);
+    let mut regtest = state(Network::Regtest);
+    for state in [&mut mainnet, &mut regtest] {
+        state
+            .requests_in_flight
+            .insert(1, ic_ckbtc_minter::state::InFlightStatus::Sending { txid });
+    }
+
+    assert!(
+        dashboard::build_requests_in_flight_tx::<Dogecoin>(&mainnet).contains(&format!(
+            "href='https://blockchair.com/dogecoin/transaction/{txid}'"
+        ))
+    );
+    let regtest_table = dashboard::build_requests_in_flight_tx::<Dogecoin>(&regtest);
+    assert!(regtest_table.contains(&format!("<code>{txid}</code>")));
+    assert!(!regtest_table.contains("href"));
+}
+
+#[test]
+fn should_paginate_account_to_utxos_table() {
+    let mut state = state(Network::Mainnet);
+    let account = Account::from(Principal::from_slice(&[1; 29]));
+    let utxos: Vec<_> = (1..=10).map(utxo).collect();
+    state
+        .utxos_state_addresses
+        .insert(account, utxos.iter().cloned().collect());
+
+    let pages = [
+        dashboard::build_account_to_utxos_table::<Dogecoin>(&state, 0, 4),
+        dashboard::build_account_to_utxos_table::<Dogecoin>(&state, 4, 4),
+        dashboard::build_account_to_utxos_table::<Dogecoin>(&state, 8, 4),
+    ];
+    for (i, utxo) in utxos.iter().enumerate() {
+        for (j, page) in pages.iter().enumerate() {
+            assert_eq!(
+                page.contains(&utxo.outpoint.txid.to_string()),
+                i / 4 == j,
+                "utxo {i} on page {j}"
+            );
+        }
+    }
+    assert!(pages[0].contains("<a href='?account_to_utxos_start=4#account_to_utxos'>2</a>"));
+}
@@ -102,6 +102,7 @@ impl From<InitArgs> for CkbtcMinterInitArgs {
             #[allow(deprecated)]
             kyt_principal: None,
             get_utxos_cache_expiration_seconds: args.get_utxos_cache_expiration_seconds,
+            utxo_consolidation_max_fee_per_vbyte: None,
         }
     }
 }
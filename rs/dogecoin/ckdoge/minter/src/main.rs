@@ -10,7 +10,7 @@ use ic_ckdoge_minter::{
     lifecycle::init::MinterArg,
     updates,
 };
-use ic_http_types::{HttpRequest, HttpResponse};
+use ic_http_types::{HttpRequest, HttpResponse, HttpResponseBuilder};
 
 #[init]
 fn init(args: MinterArg) {
@@ -167,7 +167,29 @@ fn http_request(req: HttpRequest) -> HttpResponse {
         ic_cdk::trap("update call rejected");
     }
 
-    ic_ckbtc_minter::queries::http_request(req)
+    if req.path() == "/dashboard" {
+        let account_to_utxos_start = match req.raw_query_param("account_to_utxos_start") {
+            Some(arg) => match arg.parse::<u64>() {
+                Ok(value) => value,
+                Err(_) => {
+                    return HttpResponseBuilder::bad_request()
+                        .with_body_and_content_length(
+                            "failed to parse the 'account_to_utxos_start' parameter",
+                        )
+                        .build();
+                }
+            },
+            None => 0,
+        };
+        let dashboard: Vec<u8> =
+            ic_ckdoge_minter::dashboard::build_dashboard(account_to_utxos_start);
+        HttpResponseBuilder::ok()
+            .header("Content-Type", "text/html; charset=utf-8")
+            .with_body_and_content_length(dashboard)
+            .build()
+    } else {
+        ic_ckbtc_minter::queries::http_request(req)
+    }
 }
 
 fn main() {}
@@ -0,0 +1,140 @@
+//! End-to-end tests of ckDOGE deposits and withdrawals against the Dogecoin canister
+//! syncing a regtest chain that is produced by the PocketIC server.
+use candid::Principal;
+use ic_ckdoge_minter::candid_api::{
+    GetDogeAddressArgs, RetrieveDogeStatus, RetrieveDogeWithApprovalArgs,
+};
+use ic_ckdoge_minter::{UpdateBalanceArgs, UpdateBalanceError, UtxoStatus};
+use ic_ckdoge_minter_test_utils::{DOGE, LEDGER_TRANSFER_FEE, MIN_CONFIRMATIONS, Setup};
+use pocket_ic::common::rest::LocalChain;
+use std::time::Duration;
+
+/// The block subsidy on the Dogecoin regtest network before the first halving.
+const COINBASE_REWARD: u64 = 500_000 * DOGE;
+/// The number of blocks after which a coinbase output can be spent on the Dogecoin
+/// regtest network.
+const COINBASE_MATURITY: u64 = 60;
+const WITHDRAWAL_AMOUNT: u64 = 100 * DOGE;
+/// The maximum number of rounds to wait for the canisters to make progress.
+const MAX_ROUNDS: usize = 500;
+
+const USER: Principal = Principal::from_slice(&[0_u8, 42]);
+const RECIPIENT: Principal = Principal::from_slice(&[0_u8, 43]);
+
+/// Executes rounds until `f` returns a value.
+fn wait_for<T>(setup: &Setup, what: &str, mut f: impl FnMut() -> Option<T>) -> T {
+    for _ in 0..MAX_ROUNDS {
+        if let Some(result) = f() {
+            return result;
+        }
+        setup.env.advance_time(Duration::from_secs(1));
+        setup.env.tick();
+    }
+    panic!("Timed out waiting for {what}");
+}
+
+fn get_doge_address(setup: &Setup, owner: Principal) -> String {
+    setup.minter().get_doge_address(
+        owner,
+        &GetDogeAddressArgs {
+            owner: None,
+            subaccount: None,
+        },
+    )
+}
+
+/// Calls `update_balance` until the minter mints ckDOGE for a new UTXO and returns
+/// the minted amount.
+fn wait_for_mint(setup: &Setup, owner: Principal) -> u64 {
+    let args = UpdateBalanceArgs {
+        owner: None,
+        subaccount: None,
+    };
+    wait_for(setup, "ckDOGE to be minted", || {
+        match setup.minter().update_balance(owner, &args) {
+            Ok(statuses) => statuses.into_iter().find_map(|status| match status {
+                UtxoStatus::Minted { minted_amount, .. } => Some(minted_amount),
+                _ => None,
+            }),
+            // The Dogecoin canister has not synced the deposit yet.
+            Err(UpdateBalanceError::NoNewUtxos { .. })
+            | Err(UpdateBalanceError::TemporarilyUnavailable(_))
+            | Err(UpdateBalanceError::AlreadyProcessing) => None,
+            Err(err) => panic!("unexpected update_balance error: {err:?}"),
+        }
+    })
+}
+
+#[test]
+fn should_deposit_and_withdraw_doge_on_local_chain() {
+    let setup = Setup::new_with_local_chain();
+    let env = &setup.env;
+    let minter = setup.minter();
+    let ledger = setup.ledger();
+
+    // Deposit the reward of a block mined to the deposit address of the user, and
+    // mine past the coinbase maturity so that the minter can spend it.
+    let deposit_address = get_doge_address(&setup, USER);
+    env.mine_blocks_to_address(LocalChain::Dogecoin, 1, &deposit_address);
+    env.mine_blocks(
+        LocalChain::Dogecoin,
+        COINBASE_MATURITY.max(MIN_CONFIRMATIONS as u64),
+    );
+
+    let minted_amount = wait_for_mint(&setup, USER);
+    assert_eq!(minted_amount, COINBASE_REWARD);
+    assert_eq!(ledger.icrc1_balance_of(USER), minted_amount);
+
+    // Withdraw to the deposit address of another user, so that the withdrawal can be
+    // observed on the chain by depositing it.
+    let withdrawal_address = get_doge_address(&setup, RECIPIENT);
+    ledger
+        .icrc2_approve(USER, WITHDRAWAL_AMOUNT + LEDGER_TRANSFER_FEE, minter.id())
+        .expect("failed to approve the minter");
+    let block_index = minter
+        .retrieve_doge_with_approval(
+            USER,
+            &RetrieveDogeWithApprovalArgs {
+                amount: WITHDRAWAL_AMOUNT,
+                address: withdrawal_address,
+                from_subaccount: None,
+            },
+        )
+        .expect("failed to retrieve DOGE")
+        .block_index;
+    assert_eq!(
+        ledger.icrc1_balance_of(USER),
+        minted_amount - WITHDRAWAL_AMOUNT - 2 * LEDGER_TRANSFER_FEE
+    );
+
+    let txid = wait_for(&setup, "the withdrawal to be submitted", || {
+        match minter.retrieve_doge_status(block_index) {
+            RetrieveDogeStatus::Submitted { txid, .. } => Some(txid),
+            _ => None,
+        }
+    });
+    wait_for(&setup, "the withdrawal to reach the mempool", || {
+        (!env.get_mempool(LocalChain::Dogecoin).is_empty()).then_some(())
+    });
+    assert_eq!(
+        env.get_mempool(LocalChain::Dogecoin),
+        vec![txid.to_string()]
+    );
+
+    env.mine_blocks(LocalChain::Dogecoin, MIN_CONFIRMATIONS as u64);
+    assert!(env.get_mempool(LocalChain::Dogecoin).is_empty());
+    wait_for(&setup, "the withdrawal to be confirmed", || {
+        match minter.retrieve_doge_status(block_index) {
+            RetrieveDogeStatus::Confirmed { txid: confirmed } => {
+                assert_eq!(confirmed, txid);
+                Some(())
+            }
+            _ => None,
+        }
+    });
+
+    // The withdrawn amount minus the Dogecoin transaction fee and the minter fee
+    // reached the recipient.
+    let received = wait_for_mint(&setup, RECIPIENT);
+    assert!(received < WITHDRAWAL_AMOUNT && received > WITHDRAWAL_AMOUNT - 10 * DOGE);
+}
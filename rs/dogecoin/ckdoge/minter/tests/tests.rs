@@ -449,3 +449,29 @@ fn should_get_logs() {
         init_log.message
     );
 }
+
+#[test]
+fn should_display_dashboard() {
+    use icrc_ledger_types::icrc1::account::Account;
+
+    let setup = Setup::default();
+    let account = Account {
+        owner: USER_PRINCIPAL,
+        subaccount: Some([42_u8; 32]),
+    };
+    let utxo = utxo_with_value(RETRIEVE_DOGE_MIN_AMOUNT + LEDGER_TRANSFER_FEE);
+
+    setup
+        .deposit_flow()
+        .minter_get_dogecoin_deposit_address(account)
+        .dogecoin_simulate_transaction(vec![utxo.clone()])
+        .minter_update_balance()
+        .expect_mint();
+
+    let dashboard = setup.minter().get_dashboard();
+    assert!(dashboard.contains("<title>ckDOGE Minter Dashboard</title>"));
+    assert!(
+        dashboard.contains(&utxo.outpoint.txid.to_string()),
+        "Expected dashboard to contain deposited UTXO {utxo:?}: {dashboard}"
+    );
+}
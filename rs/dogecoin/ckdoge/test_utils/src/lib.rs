@@ -20,11 +20,12 @@ use ic_management_canister_types::{CanisterId, CanisterSettings};
 use icrc_ledger_types::icrc1::account::Account;
 use pocket_ic::ErrorCode;
 use pocket_ic::RejectCode;
+use pocket_ic::common::rest::LocalChainConfig;
 use pocket_ic::{PocketIc, PocketIcBuilder, RejectResponse};
 use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 pub const NNS_ROOT_PRINCIPAL: Principal = Principal::from_slice(&[0_u8]);
 pub const USER_PRINCIPAL: Principal = Principal::from_slice(&[0_u8, 42]);
@@ -78,6 +79,59 @@ impl Setup {
         )
         .unwrap();
 
+        Self::install_minter_and_ledger(env, doge_network, dogecoin)
+    }
+
+    /// Creates a setup where the minter uses the Dogecoin canister configured for the
+    /// regtest network, which syncs a local chain produced by the PocketIC server.
+    ///
+    /// Blocks are mined with [`PocketIc::mine_blocks`] and [`PocketIc::mine_blocks_to_address`]
+    /// using `LocalChain::Dogecoin`.
+    pub fn new_with_local_chain() -> Self {
+        let doge_network = Network::Regtest;
+        let env = Arc::new(
+            PocketIcBuilder::new()
+                .with_bitcoin_subnet()
+                .with_fiduciary_subnet()
+                .with_dogecoin_local_chain(LocalChainConfig {
+                    initial_blocks: 1,
+                    coinbase_address: None,
+                })
+                .build(),
+        );
+        env.set_time(SystemTime::now().into());
+
+        let dogecoin = env
+            .create_canister_with_id(
+                None,
+                Some(CanisterSettings {
+                    controllers: Some(vec![NNS_ROOT_PRINCIPAL]),
+                    ..Default::default()
+                }),
+                get_dogecoin_canister_id(&doge_network),
+            )
+            .unwrap();
+        env.add_cycles(dogecoin, u128::MAX);
+        env.install_canister(
+            dogecoin,
+            dogecoin_canister_wasm(),
+            Encode!(&ic_doge_interface::InitConfig {
+                network: Some(ic_doge_interface::Network::Regtest),
+                fees: Some(ic_doge_interface::Fees::mainnet()),
+                ..Default::default()
+            })
+            .unwrap(),
+            Some(NNS_ROOT_PRINCIPAL),
+        );
+
+        Self::install_minter_and_ledger(env, doge_network, dogecoin)
+    }
+
+    fn install_minter_and_ledger(
+        env: Arc<PocketIc>,
+        doge_network: Network,
+        dogecoin: CanisterId,
+    ) -> Self {
         let fiduciary_subnet = env.topology().get_fiduciary().unwrap();
 
         let minter = env.create_canister_on_subnet(
@@ -236,6 +290,11 @@ fn bitcoin_canister_mock_wasm() -> Vec<u8> {
     std::fs::read(wasm_path).unwrap()
 }
 
+fn dogecoin_canister_wasm() -> Vec<u8> {
+    let wasm_path = std::env::var("IC_DOGECOIN_CANISTER_WASM_PATH").unwrap();
+    std::fs::read(wasm_path).unwrap()
+}
+
 pub fn assert_trap<T: Debug>(result: Result<T, RejectResponse>, message: &str) {
     assert_matches::assert_matches!(
         result,
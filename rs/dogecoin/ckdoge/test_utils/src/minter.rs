@@ -210,6 +210,29 @@ impl MinterCanister {
             .entries
     }
 
+    pub fn get_dashboard(&self) -> String {
+        use ic_http_types::{HttpRequest, HttpResponse};
+
+        let request = HttpRequest {
+            method: "".to_string(),
+            url: "/dashboard".to_string(),
+            headers: vec![],
+            body: vec![].into(),
+        };
+        let result = self
+            .env
+            .query_call(
+                self.id,
+                Principal::anonymous(),
+                "http_request",
+                Encode!(&request).unwrap(),
+            )
+            .expect("BUG: failed to call dashboard");
+        let response = Decode!(&result, HttpResponse).unwrap();
+        assert_eq!(response.status_code, 200_u16);
+        String::from_utf8(response.body.into_vec()).expect("dashboard is not valid UTF-8")
+    }
+
     pub fn assert_that_events(&self) -> MinterEventAssert<EventType> {
         MinterEventAssert {
             events: self
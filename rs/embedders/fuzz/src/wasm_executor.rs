@@ -5,7 +5,7 @@ use ic_config::{
 };
 use ic_cycles_account_manager::ResourceSaturation;
 use ic_embedders::{
-    CompilationCacheBuilder, WasmExecutionInput, WasmtimeEmbedder,
+    CompilationCacheBuilder, CompilationTier, WasmExecutionInput, WasmtimeEmbedder,
     wasm_executor::{WasmExecutor, WasmExecutorImpl},
     wasmtime_embedder::system_api::{
         ApiType, ExecutionParameters, InstructionLimits,
@@ -103,6 +103,7 @@ fn setup_wasm_execution_input(func_ref: FuncRef) -> WasmExecutionInput {
         subnet_available_memory: *MAX_SUBNET_AVAILABLE_MEMORY,
         func_ref,
         compilation_cache,
+        compilation_tier: CompilationTier::Baseline,
     }
 }
 
@@ -1,17 +1,22 @@
 use std::{
-    path::PathBuf,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
     sync::{
         Arc, Mutex,
         atomic::{AtomicU64, Ordering},
     },
+    time::SystemTime,
 };
 
 use tempfile::TempDir;
 
-use crate::{OnDiskSerializedModule, SerializedModule};
+use crate::{CompilationTier, OnDiskSerializedModule, SerializedModule};
+use ic_config::embedders::Config as EmbeddersConfig;
+use ic_crypto_sha2::Sha256;
 use ic_heap_bytes::HeapBytes;
 use ic_interfaces::execution_environment::{HypervisorError, HypervisorResult};
-use ic_types::{DiskBytes, NumBytes};
+use ic_types::{DiskBytes, NumBytes, ReplicaVersion};
 use ic_utils_lru_cache::LruCache;
 use ic_wasm_types::{CanisterModule, WasmHash};
 
@@ -28,19 +33,77 @@ const DEFAULT_DISK_CAPACITY: NumBytes = NumBytes::new(100 * GB);
 /// 10 GiB is already more than we can support with the entry count limit anyway.
 const DEFAULT_MEMORY_CAPACITY: NumBytes = NumBytes::new(10 * GB);
 
+/// Extension of the files holding persisted entries.
+const PERSISTED_ENTRY_EXTENSION: &str = "module";
+
+/// Length of the checksum prefixed to every persisted entry.
+const CHECKSUM_LENGTH: usize = 32;
+
+/// Returns a fingerprint of everything apart from the Wasm binary itself that
+/// influences the compilation result: the replica version (which pins the
+/// Wasmtime version and the instrumentation) and the embedder configuration.
+///
+/// Persisted entries are stored in a subdirectory named after the fingerprint,
+/// so entries produced by a different replica version or configuration are
+/// never loaded.
+pub fn compilation_cache_fingerprint(config: &EmbeddersConfig) -> String {
+    let mut hasher = Sha256::new();
+    hasher.write(ReplicaVersion::default().as_ref().as_bytes());
+    hasher.write(
+        &bincode::serialize(config)
+            .expect("Failed to serialize the embedders config for the cache fingerprint"),
+    );
+    hasher
+        .finish()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Counters describing how the persistent part of the cache has been used
+/// since it was created or since the counters were last taken.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct PersistentCacheStats {
+    /// Lookups that missed the in-memory cache but were served from disk.
+    pub hits: u64,
+    /// Lookups that found neither an in-memory nor a persisted entry.
+    pub misses: u64,
+    /// Persisted entries that failed validation and were deleted.
+    pub invalid_entries: u64,
+    /// Entries that could not be written to disk.
+    pub write_errors: u64,
+}
+
 /// Stores the serialized modules of wasm code that has already been compiled so
 /// that it can be used again without recompiling.
+///
+/// If a persistent directory is configured, successfully compiled modules are
+/// additionally written there, keyed by their `WasmHash`, so that a restarted
+/// replica can load them instead of compiling again. Compilation errors are
+/// only cached in memory.
+///
+/// Entries are keyed by the module and its [`CompilationTier`], so that the
+/// optimized recompilation of a module is cached alongside the baseline one
+/// instead of replacing it, see [`cache_key`].
 #[derive(HeapBytes)]
 pub struct CompilationCache {
     /// Directory holding all the temporary files. It will be deleted on
     /// drop.
     dir: TempDir,
+    /// Directory holding the entries that survive restarts. It already
+    /// includes the fingerprint of the current compilation configuration.
+    #[heap_bytes(with = |dir: &Option<PathBuf>| dir.as_ref().map_or(0, |d| d.as_os_str().len()))]
+    persistent_dir: Option<PathBuf>,
     /// Map from wasm hash to an open fd with the serialized Module result.
     cache: Mutex<LruCache<WasmHash, HypervisorResult<Arc<OnDiskSerializedModule>>>>,
     /// Atomic counter to deduplicate files in the case of concurrent compilations of the same module.
     counter: AtomicU64,
     /// Limit on the total number of entries in the cache.
     max_entries: usize,
+    persistent_hits: AtomicU64,
+    persistent_misses: AtomicU64,
+    persistent_invalid_entries: AtomicU64,
+    persistent_write_errors: AtomicU64,
 }
 
 impl DiskBytes for CompilationCache {
@@ -53,6 +116,7 @@ pub struct CompilationCacheBuilder {
     memory_capacity: NumBytes,
     disk_capacity: NumBytes,
     dir: Option<TempDir>,
+    persistent_dir: Option<(PathBuf, String)>,
     max_entries: usize,
 }
 
@@ -67,6 +131,7 @@ impl CompilationCacheBuilder {
             memory_capacity: DEFAULT_MEMORY_CAPACITY,
             disk_capacity: DEFAULT_DISK_CAPACITY,
             dir: None,
+            persistent_dir: None,
             max_entries: DEFAULT_MAX_ENTRIES,
         }
     }
@@ -91,35 +156,141 @@ impl CompilationCacheBuilder {
         self
     }
 
+    /// Persist compiled modules under `dir` so that they survive restarts.
+    /// Entries written with a different `fingerprint` (see
+    /// [`compilation_cache_fingerprint`]) are deleted when the cache is built.
+    pub fn with_persistent_dir(mut self, dir: PathBuf, fingerprint: String) -> Self {
+        self.persistent_dir = Some((dir, fingerprint));
+        self
+    }
+
     pub fn build(self) -> CompilationCache {
         let dir = self.dir.unwrap_or_else(|| tempfile::tempdir().unwrap());
+        let persistent_dir = self.persistent_dir.map(|(root, fingerprint)| {
+            prepare_persistent_dir(&root, &fingerprint, self.disk_capacity)
+        });
         CompilationCache {
             dir,
+            persistent_dir,
             cache: Mutex::new(LruCache::new(self.memory_capacity, self.disk_capacity)),
             counter: AtomicU64::new(0),
             max_entries: self.max_entries,
+            persistent_hits: AtomicU64::new(0),
+            persistent_misses: AtomicU64::new(0),
+            persistent_invalid_entries: AtomicU64::new(0),
+            persistent_write_errors: AtomicU64::new(0),
         }
     }
 }
 
-impl CompilationCache {
-    pub fn insert_err(&self, canister_module: &CanisterModule, err: HypervisorError) {
-        let mut cache = self.cache.lock().unwrap();
-        if cache.len() >= self.max_entries {
-            let _ = cache.pop_lru();
+/// Creates the directory for the given fingerprint under `root` and cleans up
+/// everything that cannot be used anymore: directories of other fingerprints,
+/// partially written entries, and the oldest entries exceeding the disk
+/// capacity.
+///
+/// Entries that are never looked up after a restart are not tracked by the
+/// in-memory LRU, so the persisted files may temporarily take up to twice the
+/// disk capacity until the next restart.
+fn prepare_persistent_dir(root: &Path, fingerprint: &str, disk_capacity: NumBytes) -> PathBuf {
+    let dir = root.join(fingerprint);
+    std::fs::create_dir_all(&dir).unwrap_or_else(|e| {
+        panic!("Unable to create persistent compilation cache directory {dir:?}: {e}")
+    });
+
+    if let Ok(entries) = std::fs::read_dir(root) {
+        for entry in entries.flatten() {
+            if entry.file_name() != fingerprint {
+                let path = entry.path();
+                let _ = if path.is_dir() {
+                    std::fs::remove_dir_all(&path)
+                } else {
+                    std::fs::remove_file(&path)
+                };
+            }
+        }
+    }
+
+    let mut persisted: Vec<(SystemTime, u64, PathBuf)> = vec![];
+    if let Ok(entries) = std::fs::read_dir(&dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_entry =
+                path.extension().and_then(|e| e.to_str()) == Some(PERSISTED_ENTRY_EXTENSION);
+            match entry.metadata() {
+                Ok(metadata) if is_entry && metadata.is_file() => {
+                    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                    persisted.push((modified, metadata.len(), path));
+                }
+                _ => {
+                    let _ = std::fs::remove_file(&path);
+                }
+            }
+        }
+    }
+
+    // Keep the most recently written entries within the disk capacity.
+    persisted.sort_by(|a, b| b.0.cmp(&a.0));
+    let mut total_size = 0;
+    for (_, size, path) in persisted {
+        total_size += size;
+        if total_size > disk_capacity.get() {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+    dir
+}
+
+/// Returns the key of the entry holding the given module compiled with the
+/// given tier. Baseline entries are keyed by the `WasmHash` of the module and
+/// optimized entries by a hash derived from it, so that the entries of the two
+/// tiers never replace each other, neither in memory nor on disk.
+fn cache_key(canister_module: &CanisterModule, tier: CompilationTier) -> WasmHash {
+    let hash = WasmHash::from(canister_module);
+    match tier {
+        CompilationTier::Baseline => hash,
+        CompilationTier::Optimized => {
+            let mut hasher = Sha256::new();
+            hasher.write(b"optimized");
+            hasher.write(&hash.to_slice());
+            WasmHash::from(hasher.finish())
         }
-        let _ = cache.push(WasmHash::from(canister_module), Err(err));
+    }
+}
+
+impl CompilationCache {
+    pub fn insert_err(
+        &self,
+        canister_module: &CanisterModule,
+        tier: CompilationTier,
+        err: HypervisorError,
+    ) {
+        self.push(cache_key(canister_module, tier), Err(err));
     }
 
     pub fn insert_ok(
         &self,
         canister_module: &CanisterModule,
         serialized_module: SerializedModule,
+    ) -> Arc<OnDiskSerializedModule> {
+        let hash = cache_key(canister_module, serialized_module.compilation_tier);
+        if let Some(persistent_dir) = &self.persistent_dir
+            && self
+                .persist(persistent_dir, &hash, &serialized_module)
+                .is_err()
+        {
+            self.persistent_write_errors.fetch_add(1, Ordering::Relaxed);
+        }
+        self.insert_in_memory(hash, serialized_module)
+    }
+
+    fn insert_in_memory(
+        &self,
+        hash: WasmHash,
+        serialized_module: SerializedModule,
     ) -> Arc<OnDiskSerializedModule> {
         // The file paths must not have existing files. To ensure this
         // we add a unique counter - otherwise concurent insertions for
         // the same Wasm would use the same file.
-        let hash = WasmHash::from(canister_module);
         let id = self.counter.fetch_add(1, Ordering::SeqCst);
         let mut bytes_path: PathBuf = self.dir.path().into();
         bytes_path.push(format!("{hash}-{id}.module_bytes"));
@@ -132,26 +303,126 @@ impl CompilationCache {
             &initial_state_path,
         ));
 
-        let mut cache = self.cache.lock().unwrap();
-        if cache.len() >= self.max_entries {
-            let _ = cache.pop_lru();
-        }
-        let _ = cache.push(hash, Ok(Arc::clone(&on_disk)));
+        self.push(hash, Ok(Arc::clone(&on_disk)));
         on_disk
     }
 
+    /// Pushes the entry into the in-memory LRU and deletes the persisted files
+    /// of all entries evicted as a consequence.
+    fn push(&self, hash: WasmHash, entry: HypervisorResult<Arc<OnDiskSerializedModule>>) {
+        let mut evicted = vec![];
+        {
+            let mut cache = self.cache.lock().unwrap();
+            if cache.len() >= self.max_entries {
+                evicted.extend(cache.pop_lru());
+            }
+            evicted.extend(cache.push(hash.clone(), entry));
+        }
+        if let Some(persistent_dir) = &self.persistent_dir {
+            for (evicted_hash, evicted_entry) in evicted {
+                // Replacing an existing entry for the same module also shows
+                // up as an eviction, but its persisted file is still valid.
+                if evicted_hash != hash && evicted_entry.is_ok() {
+                    let _ =
+                        std::fs::remove_file(persisted_entry_path(persistent_dir, &evicted_hash));
+                }
+            }
+        }
+    }
+
     pub fn get(
         &self,
         canister_module: &CanisterModule,
+        tier: CompilationTier,
     ) -> Option<HypervisorResult<Arc<OnDiskSerializedModule>>> {
-        self.cache
-            .lock()
-            .unwrap()
-            .get(&WasmHash::from(canister_module))
-            .map(|o| match o {
-                Ok(m) => Ok(Arc::clone(m)),
-                Err(e) => Err(e.clone()),
+        let hash = cache_key(canister_module, tier);
+        let cached = self.cache.lock().unwrap().get(&hash).map(|o| match o {
+            Ok(m) => Ok(Arc::clone(m)),
+            Err(e) => Err(e.clone()),
+        });
+        if cached.is_some() {
+            return cached;
+        }
+        let persistent_dir = self.persistent_dir.as_ref()?;
+        match self.load(persistent_dir, &hash) {
+            Some(serialized_module) => {
+                self.persistent_hits.fetch_add(1, Ordering::Relaxed);
+                Some(Ok(self.insert_in_memory(hash, serialized_module)))
+            }
+            None => {
+                self.persistent_misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Returns the counters of the persistent part of the cache.
+    pub fn persistent_stats(&self) -> PersistentCacheStats {
+        PersistentCacheStats {
+            hits: self.persistent_hits.load(Ordering::Relaxed),
+            misses: self.persistent_misses.load(Ordering::Relaxed),
+            invalid_entries: self.persistent_invalid_entries.load(Ordering::Relaxed),
+            write_errors: self.persistent_write_errors.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the counters of the persistent part of the cache accumulated
+    /// since the previous call and resets them, so that each event is reported
+    /// exactly once even when called from several threads.
+    pub fn take_persistent_stats(&self) -> PersistentCacheStats {
+        PersistentCacheStats {
+            hits: self.persistent_hits.swap(0, Ordering::Relaxed),
+            misses: self.persistent_misses.swap(0, Ordering::Relaxed),
+            invalid_entries: self.persistent_invalid_entries.swap(0, Ordering::Relaxed),
+            write_errors: self.persistent_write_errors.swap(0, Ordering::Relaxed),
+        }
+    }
+
+    /// Writes the entry to a temporary file and atomically moves it into place,
+    /// so that a crash never leaves a partially written entry behind.
+    ///
+    /// The file consists of a SHA-256 checksum followed by the bincode encoding
+    /// of the Wasm hash and the serialized module.
+    fn persist(
+        &self,
+        persistent_dir: &Path,
+        hash: &WasmHash,
+        serialized_module: &SerializedModule,
+    ) -> std::io::Result<()> {
+        let payload = bincode::serialize(&(hash.to_slice(), serialized_module))
+            .map_err(std::io::Error::other)?;
+        let checksum = Sha256::hash(&payload);
+        let id = self.counter.fetch_add(1, Ordering::SeqCst);
+        let tmp_path = persistent_dir.join(format!("{hash}-{id}.tmp"));
+        let result = write_entry(&tmp_path, &checksum, &payload)
+            .and_then(|()| std::fs::rename(&tmp_path, persisted_entry_path(persistent_dir, hash)));
+        if result.is_err() {
+            let _ = std::fs::remove_file(&tmp_path);
+        }
+        result
+    }
+
+    /// Reads and validates the persisted entry for the given hash. Entries
+    /// that fail validation are deleted.
+    fn load(&self, persistent_dir: &Path, hash: &WasmHash) -> Option<SerializedModule> {
+        let path = persisted_entry_path(persistent_dir, hash);
+        let contents = std::fs::read(&path).ok()?;
+        let validated = contents
+            .split_at_checked(CHECKSUM_LENGTH)
+            .filter(|(checksum, payload)| *checksum == Sha256::hash(payload).as_slice())
+            .and_then(|(_, payload)| {
+                bincode::deserialize::<([u8; 32], SerializedModule)>(payload).ok()
             })
+            .filter(|(persisted_hash, _)| *persisted_hash == hash.to_slice());
+        match validated {
+            Some((_, serialized_module)) => Some(serialized_module),
+            None => {
+                self.persistent_invalid_entries
+                    .fetch_add(1, Ordering::Relaxed);
+                let _ = std::fs::remove_file(&path);
+                None
+            }
+        }
     }
 
     #[doc(hidden)]
@@ -160,6 +431,17 @@ impl CompilationCache {
     }
 }
 
+fn write_entry(path: &Path, checksum: &[u8], payload: &[u8]) -> std::io::Result<()> {
+    let mut file = File::create_new(path)?;
+    file.write_all(checksum)?;
+    file.write_all(payload)?;
+    file.sync_all()
+}
+
+fn persisted_entry_path(persistent_dir: &Path, hash: &WasmHash) -> PathBuf {
+    persistent_dir.join(format!("{hash}.{PERSISTED_ENTRY_EXTENSION}"))
+}
+
 /// Check that multiple threads compiling the same wasm won't interfere with
 /// each other if they all try to insert in the cache at the same time.
 #[test]
@@ -186,3 +468,167 @@ fn concurrent_insertions() {
         }
     })
 }
+
+#[cfg(test)]
+fn compile_empty_module() -> SerializedModule {
+    let binary = ic_wasm_types::BinaryEncodedWasm::new(wat::parse_str("(module)").unwrap());
+    let config = ic_config::embedders::Config::default();
+    let embedder = crate::WasmtimeEmbedder::new(config, ic_logger::no_op_logger());
+    let (_, result) = crate::wasm_utils::compile(&embedder, &binary);
+    result.unwrap().1
+}
+
+#[cfg(test)]
+fn persistent_cache(root: &Path, fingerprint: &str) -> CompilationCache {
+    CompilationCacheBuilder::new()
+        .with_persistent_dir(root.to_path_buf(), fingerprint.to_string())
+        .with_max_entries(2)
+        .build()
+}
+
+/// Check that a new cache instance serves entries persisted by a previous one.
+#[test]
+fn persisted_entries_survive_restart() {
+    let root = tempfile::tempdir().unwrap();
+    let canister_module = CanisterModule::new(wat::parse_str("(module)").unwrap());
+    let serialized_module = compile_empty_module();
+
+    let cache = persistent_cache(root.path(), "fingerprint");
+    cache.insert_ok(&canister_module, serialized_module.clone());
+    drop(cache);
+
+    let cache = persistent_cache(root.path(), "fingerprint");
+    let on_disk = cache
+        .get(&canister_module, CompilationTier::Baseline)
+        .unwrap()
+        .unwrap();
+    assert_eq!(on_disk.compilation_cost, serialized_module.compilation_cost);
+    assert_eq!(on_disk.is_wasm64, serialized_module.is_wasm64);
+    assert_eq!(
+        on_disk.initial_state_data().exported_functions,
+        serialized_module.exported_functions
+    );
+    assert_eq!(
+        cache.persistent_stats(),
+        PersistentCacheStats {
+            hits: 1,
+            ..Default::default()
+        }
+    );
+    // The entry is now in memory, so it isn't loaded from disk again.
+    assert!(
+        cache
+            .get(&canister_module, CompilationTier::Baseline)
+            .unwrap()
+            .is_ok()
+    );
+    assert_eq!(cache.persistent_stats().hits, 1);
+    // Taking the counters reports each event once.
+    assert_eq!(cache.take_persistent_stats().hits, 1);
+    assert_eq!(
+        cache.take_persistent_stats(),
+        PersistentCacheStats::default()
+    );
+}
+
+#[test]
+fn corrupted_persisted_entry_is_discarded() {
+    let root = tempfile::tempdir().unwrap();
+    let canister_module = CanisterModule::new(wat::parse_str("(module)").unwrap());
+    let cache = persistent_cache(root.path(), "fingerprint");
+    cache.insert_ok(&canister_module, compile_empty_module());
+    drop(cache);
+
+    let path = persisted_entry_path(
+        &root.path().join("fingerprint"),
+        &WasmHash::from(&canister_module),
+    );
+    let mut contents = std::fs::read(&path).unwrap();
+    let last = contents.len() - 1;
+    contents[last] ^= 0xff;
+    std::fs::write(&path, contents).unwrap();
+
+    let cache = persistent_cache(root.path(), "fingerprint");
+    assert!(
+        cache
+            .get(&canister_module, CompilationTier::Baseline)
+            .is_none()
+    );
+    assert_eq!(cache.persistent_stats().invalid_entries, 1);
+    assert!(!path.exists());
+}
+
+#[test]
+fn persisted_entries_of_other_fingerprints_are_deleted() {
+    let root = tempfile::tempdir().unwrap();
+    let canister_module = CanisterModule::new(wat::parse_str("(module)").unwrap());
+    let cache = persistent_cache(root.path(), "old");
+    cache.insert_ok(&canister_module, compile_empty_module());
+    drop(cache);
+
+    let cache = persistent_cache(root.path(), "new");
+    assert!(!root.path().join("old").exists());
+    assert!(
+        cache
+            .get(&canister_module, CompilationTier::Baseline)
+            .is_none()
+    );
+    assert_eq!(cache.persistent_stats().misses, 1);
+}
+
+#[test]
+fn evicted_entries_are_deleted_from_disk() {
+    let root = tempfile::tempdir().unwrap();
+    let cache = persistent_cache(root.path(), "fingerprint");
+    let serialized_module = compile_empty_module();
+    let hashes: Vec<_> = (0..3_u64)
+        .map(|i| {
+            let canister_module = CanisterModule::new(i.to_le_bytes().to_vec());
+            cache.insert_ok(&canister_module, serialized_module.clone());
+            WasmHash::from(&canister_module)
+        })
+        .collect();
+
+    let dir = root.path().join("fingerprint");
+    assert!(!persisted_entry_path(&dir, &hashes[0]).exists());
+    assert!(persisted_entry_path(&dir, &hashes[1]).exists());
+    assert!(persisted_entry_path(&dir, &hashes[2]).exists());
+}
+
+#[cfg(test)]
+fn compile_empty_module_with_tier(tier: CompilationTier) -> SerializedModule {
+    let binary = ic_wasm_types::BinaryEncodedWasm::new(wat::parse_str("(module)").unwrap());
+    let config = ic_config::embedders::Config::default();
+    let embedder = crate::WasmtimeEmbedder::new(config, ic_logger::no_op_logger());
+    let (_, result) = crate::wasm_utils::compile_with_tier(&embedder, &binary, tier);
+    result.unwrap().1
+}
+
+/// The optimized recompilation of a module must not replace its baseline
+/// entry, as both tiers can be in use at the same time.
+#[test]
+fn tiers_are_cached_separately() {
+    let root = tempfile::tempdir().unwrap();
+    let canister_module = CanisterModule::new(wat::parse_str("(module)").unwrap());
+    let cache = persistent_cache(root.path(), "fingerprint");
+    cache.insert_ok(&canister_module, compile_empty_module());
+    assert!(
+        cache
+            .get(&canister_module, CompilationTier::Optimized)
+            .is_none()
+    );
+
+    cache.insert_ok(
+        &canister_module,
+        compile_empty_module_with_tier(CompilationTier::Optimized),
+    );
+    drop(cache);
+
+    // Both entries are persisted under their own key.
+    let cache = persistent_cache(root.path(), "fingerprint");
+    for tier in [CompilationTier::Baseline, CompilationTier::Optimized] {
+        let on_disk = cache.get(&canister_module, tier).unwrap().unwrap();
+        assert_eq!(on_disk.compilation_tier, tier);
+    }
+    assert_eq!(cache.persistent_stats().hits, 2);
+}
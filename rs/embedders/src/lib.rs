@@ -6,11 +6,14 @@ pub mod wasmtime_embedder;
 
 use std::{sync::Arc, time::Duration};
 
-pub use compilation_cache::{CompilationCache, CompilationCacheBuilder};
+pub use compilation_cache::{
+    CompilationCache, CompilationCacheBuilder, PersistentCacheStats, compilation_cache_fingerprint,
+};
 use ic_interfaces::execution_environment::{MessageMemoryUsage, SubnetAvailableMemory};
 use ic_management_canister_types_private::Global;
 use ic_replicated_state::PageIndex;
 use ic_types::{NumBytes, NumInstructions, methods::FuncRef};
+pub use ic_wasm_types::CompilationTier;
 use serde::{Deserialize, Serialize};
 pub use serialized_module::{
     InitialStateData, OnDiskSerializedModule, SerializedModule, SerializedModuleBytes,
@@ -37,6 +40,8 @@ pub struct WasmExecutionInput {
     pub subnet_available_memory: SubnetAvailableMemory,
     pub func_ref: FuncRef,
     pub compilation_cache: Arc<CompilationCache>,
+    /// The tier of the compiled module to execute, see `ExecutionState::executed_instructions`.
+    pub compilation_tier: CompilationTier,
 }
 
 #[derive(Clone, Debug)]
@@ -17,6 +17,7 @@ use nix::sys::mman::{MapFlags, ProtFlags, mmap};
 use serde::{Deserialize, Serialize};
 use wasmtime::Module;
 
+use crate::CompilationTier;
 use crate::wasm_utils::{
     InstrumentationOutput, Segments, WasmImportsDetails, WasmValidationDetails,
 };
@@ -80,6 +81,8 @@ pub struct SerializedModule {
     pub imports_details: WasmImportsDetails,
     /// Boolean value that indicates whether this is a Wasm64 module or not.
     pub is_wasm64: bool,
+    /// The optimization level the module was compiled with.
+    pub compilation_tier: CompilationTier,
 }
 
 impl SerializedModule {
@@ -88,6 +91,7 @@ impl SerializedModule {
         instrumentation_output: InstrumentationOutput,
         validation_details: WasmValidationDetails,
         is_wasm64: bool,
+        compilation_tier: CompilationTier,
     ) -> HypervisorResult<Self> {
         let bytes = SerializedModuleBytes::try_from(module)?;
         Ok(Self {
@@ -98,6 +102,7 @@ impl SerializedModule {
             compilation_cost: instrumentation_output.compilation_cost,
             imports_details: validation_details.imports_details,
             is_wasm64,
+            compilation_tier,
         })
     }
 
@@ -151,6 +156,8 @@ pub struct OnDiskSerializedModule {
     pub imports_details: WasmImportsDetails,
     /// Boolean value that indicates whether this is a Wasm64 module or not.
     pub is_wasm64: bool,
+    /// The optimization level the module was compiled with.
+    pub compilation_tier: CompilationTier,
 }
 
 impl DiskBytes for OnDiskSerializedModule {
@@ -230,6 +237,7 @@ impl OnDiskSerializedModule {
             compilation_cost: serialized_module.compilation_cost,
             imports_details: serialized_module.imports_details,
             is_wasm64: serialized_module.is_wasm64,
+            compilation_tier: serialized_module.compilation_tier,
         }
     }
 
@@ -329,6 +337,7 @@ mod test {
             imports_msg_cycles_accept: bool,
             imports_mint_cycles: bool,
             is_wasm64: bool,
+            optimized: bool,
         ) {
             let bytes = Arc::new(SerializedModuleBytes(bytes));
             let data_segments = data_segments.into_iter().collect();
@@ -350,6 +359,11 @@ mod test {
                 compilation_cost,
                 imports_details,
                 is_wasm64,
+                compilation_tier: if optimized {
+                    CompilationTier::Optimized
+                } else {
+                    CompilationTier::Baseline
+                },
             };
 
             let dir = tempfile::tempdir().unwrap();
@@ -371,6 +385,7 @@ mod test {
             assert_eq!(module.compilation_cost, on_disk.compilation_cost);
             assert_eq!(module.imports_details, on_disk.imports_details);
             assert_eq!(module.is_wasm64, on_disk.is_wasm64);
+            assert_eq!(module.compilation_tier, on_disk.compilation_tier);
         }
 
         // Check that multiple threads reading from an on disk serialized module
@@ -401,6 +416,7 @@ mod test {
                 compilation_cost,
                 imports_details,
                 is_wasm64: false,
+                compilation_tier: CompilationTier::Baseline,
             };
 
             let dir = tempfile::tempdir().unwrap();
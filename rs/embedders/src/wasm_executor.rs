@@ -21,8 +21,9 @@ use wasmtime::Module;
 use crate::OnDiskSerializedModule;
 use crate::wasmtime_embedder::CanisterMemoryType;
 use crate::{
-    CompilationCache, CompilationResult, WasmExecutionInput, WasmtimeEmbedder,
-    wasm_utils::{Segments, WasmImportsDetails, compile, decoding::decode_wasm},
+    CompilationCache, CompilationResult, CompilationTier, SerializedModule, WasmExecutionInput,
+    WasmtimeEmbedder,
+    wasm_utils::{Segments, WasmImportsDetails, compile_with_tier, decoding::decode_wasm},
     wasmtime_embedder::WasmtimeInstance,
 };
 use ic_config::flag_status::FlagStatus;
@@ -66,6 +67,13 @@ pub trait WasmExecutor: Send + Sync {
         canister_id: CanisterId,
         compilation_cache: Arc<CompilationCache>,
     ) -> HypervisorResult<(ExecutionState, NumInstructions, Option<CompilationResult>)>;
+
+    /// Recompiles the given module with optimizations for tiered compilation.
+    /// This is slow, so it should not be called on the execution thread.
+    fn compile_optimized(
+        &self,
+        canister_module: &CanisterModule,
+    ) -> HypervisorResult<SerializedModule>;
 }
 
 struct WasmExecutorMetrics {
@@ -190,6 +198,7 @@ impl WasmExecutor for WasmExecutorImpl {
             subnet_available_memory,
             func_ref,
             compilation_cache,
+            compilation_tier,
         }: WasmExecutionInput,
         execution_state: &ExecutionState,
     ) -> (Option<CompilationResult>, WasmExecutionResult) {
@@ -206,7 +215,11 @@ impl WasmExecutor for WasmExecutorImpl {
             cache: embedder_cache,
             serialized_module,
             compilation_result,
-        } = match self.get_embedder_cache(&execution_state.wasm_binary, compilation_cache) {
+        } = match self.get_embedder_cache(
+            &execution_state.wasm_binary,
+            compilation_cache,
+            compilation_tier,
+        ) {
             Ok(cache_result) => cache_result,
             Err(err) => {
                 return (
@@ -292,7 +305,7 @@ impl WasmExecutor for WasmExecutorImpl {
             cache: embedder_cache,
             serialized_module: Some(serialized_module),
             compilation_result,
-        } = self.get_embedder_cache(&wasm_binary, compilation_cache)?
+        } = self.get_embedder_cache(&wasm_binary, compilation_cache, CompilationTier::Baseline)?
         else {
             panic!("Newly created WasmBinary must be compiled or deserialized.")
         };
@@ -326,6 +339,7 @@ impl WasmExecutor for WasmExecutorImpl {
             last_executed_round: ExecutionRound::from(0),
             next_scheduled_method: NextScheduledMethod::default(),
             wasm_execution_mode: WasmExecutionMode::from_is_wasm64(serialized_module.is_wasm64),
+            executed_instructions: NumInstructions::new(0),
         };
 
         Ok((
@@ -334,6 +348,19 @@ impl WasmExecutor for WasmExecutorImpl {
             compilation_result,
         ))
     }
+
+    fn compile_optimized(
+        &self,
+        canister_module: &CanisterModule,
+    ) -> HypervisorResult<SerializedModule> {
+        let wasm = decode_wasm(
+            self.wasm_embedder.config().wasm_max_size,
+            canister_module.to_shared_vec(),
+        )?;
+        let (_cache, result) =
+            compile_with_tier(&self.wasm_embedder, &wasm, CompilationTier::Optimized);
+        result.map(|(_compilation_result, serialized_module)| serialized_module)
+    }
 }
 
 /// Result of checking for a compiled module in the `EmbedderCache` and `CompilationCache`.
@@ -385,8 +412,9 @@ impl WasmExecutorImpl {
         &self,
         wasm_binary: &WasmBinary,
         compilation_cache: Arc<CompilationCache>,
+        compilation_tier: CompilationTier,
     ) -> HypervisorResult<CacheLookup> {
-        let mut guard = wasm_binary.embedder_cache.lock().unwrap();
+        let mut guard = wasm_binary.embedder_cache(compilation_tier).lock().unwrap();
         if let Some(embedder_cache) = &*guard {
             Ok(CacheLookup {
                 cache: embedder_cache.clone(),
@@ -394,7 +422,7 @@ impl WasmExecutorImpl {
                 compilation_result: None,
             })
         } else {
-            match compilation_cache.get(&wasm_binary.binary) {
+            match compilation_cache.get(&wasm_binary.binary, compilation_tier) {
                 Some(Ok(on_disk_serialized_module)) => {
                     // This path is only used when sandboxing is disabled.
                     // Otherwise the fd is implicitly duplicated when passed to
@@ -427,7 +455,11 @@ impl WasmExecutorImpl {
                         self.wasm_embedder.config().wasm_max_size,
                         wasm_binary.binary.to_shared_vec(),
                     )?);
-                    let (cache, result) = compile(&self.wasm_embedder, decoded_wasm.as_ref());
+                    let (cache, result) = compile_with_tier(
+                        &self.wasm_embedder,
+                        decoded_wasm.as_ref(),
+                        compilation_tier,
+                    );
                     *guard = Some(cache.clone());
                     let (compilation_result, serialized_module) = result?;
                     let serialized_module =
@@ -16,10 +16,14 @@ use ic_wasm_types::{BinaryEncodedWasm, WasmInstrumentationError};
 use serde::{Deserialize, Serialize};
 
 use self::{
-    instrumentation::instrument, validation::has_wasm64_memory, validation::validate_wasm_binary,
+    instrumentation::{GcObjectSizes, instrument},
+    validation::has_wasm64_memory,
+    validation::validate_wasm_binary,
 };
 use crate::wasmtime_embedder::StoreData;
-use crate::{CompilationResult, WasmtimeEmbedder, serialized_module::SerializedModule};
+use crate::{
+    CompilationResult, CompilationTier, WasmtimeEmbedder, serialized_module::SerializedModule,
+};
 use wasmtime::InstancePre;
 
 pub mod decoding;
@@ -217,8 +221,10 @@ fn validate_and_instrument(
     } else {
         config.max_wasm_memory_size
     };
+    let gc_object_sizes = GcObjectSizes::new(wasm)?;
     let instrumentation_output = instrument(
         module,
+        &gc_object_sizes,
         config.cost_to_compile_wasm_instruction,
         config.metering_type,
         config.dirty_page_overhead,
@@ -241,11 +247,12 @@ pub fn validate_and_instrument_for_testing(
 fn compile_inner(
     embedder: &WasmtimeEmbedder,
     wasm: &BinaryEncodedWasm,
+    tier: CompilationTier,
 ) -> HypervisorResult<(InstancePre<StoreData>, CompilationResult, SerializedModule)> {
     let timer = Instant::now();
     let (wasm_validation_details, instrumentation_output) =
         validate_and_instrument(wasm, embedder.config())?;
-    let module = embedder.compile(&instrumentation_output.binary)?;
+    let module = embedder.compile_with_tier(&instrumentation_output.binary, tier)?;
     let instance_pre = embedder.pre_instantiate(&module)?;
     let largest_function_instruction_count =
         wasm_validation_details.largest_function_instruction_count;
@@ -261,6 +268,7 @@ fn compile_inner(
         instrumentation_output,
         wasm_validation_details,
         is_wasm64,
+        tier,
     )?;
     Ok((
         instance_pre,
@@ -281,7 +289,21 @@ pub fn compile(
     EmbedderCache,
     HypervisorResult<(CompilationResult, SerializedModule)>,
 ) {
-    let (cache, result) = match compile_inner(embedder, wasm) {
+    compile_with_tier(embedder, wasm, CompilationTier::Baseline)
+}
+
+/// Same as `compile`, but with the given optimization level. The instrumentation
+/// doesn't depend on the tier, so modules of all tiers execute the same number
+/// of instructions.
+pub fn compile_with_tier(
+    embedder: &WasmtimeEmbedder,
+    wasm: &BinaryEncodedWasm,
+    tier: CompilationTier,
+) -> (
+    EmbedderCache,
+    HypervisorResult<(CompilationResult, SerializedModule)>,
+) {
+    let (cache, result) = match compile_inner(embedder, wasm, tier) {
         Ok((module, result, serialized)) => (Ok(module), Ok((result, serialized))),
         Err(err) => (Err(err.clone()), Err(err)),
     };
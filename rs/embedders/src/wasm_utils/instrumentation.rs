@@ -109,6 +109,31 @@
 //! (memory (export "stable_memory_bytemap") i32 (i64.const STABLE_BYTEMAP_SIZE) (i64.const STABLE_BYTEMAP_SIZE))
 //! ```
 //!
+//! # Wasm GC heap
+//!
+//! If the module allocates on the GC heap (`struct.new`, `array.new`, `throw`,
+//! etc.), then an additional System API function is imported:
+//!
+//! ```wasm
+//! (import "__" "try_grow_gc_heap" (func ((param i64))))
+//! ```
+//!
+//! and a call to it is inserted before every allocating instruction. The size
+//! of the allocation is estimated from the type of the allocated object. For
+//! arrays whose length is only known at runtime, a helper function computes the
+//! size from the length at the top of the stack.
+//!
+//! The GC heap only lives for the duration of a single message execution: it is
+//! discarded afterwards and not persisted in the canister state. Globals of
+//! reference type and tables holding GC references are therefore not persisted
+//! either: they start from their initial value in every message. Toolchains such
+//! as Kotlin/Wasm, Dart, or `wasm_of_ocaml` initialize such globals lazily, so
+//! their objects are recreated on first use in each message.
+//!
+//! The null collector never frees objects, so the live size of the GC heap is
+//! the sum of all allocations of the message. It is charged like Wasm memory and
+//! limited to `MAX_GC_HEAP_SIZE` per message execution.
+//!
 
 use super::system_api_replacements::replacement_functions;
 use super::validation::API_VERSION_IC0;
@@ -165,6 +190,8 @@ pub(crate) struct InjectedFunctions {
     pub try_grow_stable_memory: u32,
     pub internal_trap: u32,
     pub stable_read_first_access: u32,
+    /// Only injected if the module allocates on the GC heap.
+    pub try_grow_gc_heap: Option<u32>,
 }
 
 // Gets the cost of an instruction.
@@ -173,7 +200,7 @@ pub fn instruction_to_cost(i: &wirm::wasmparser::Operator, mem_type: WasmMemoryT
 
     // This aims to be a complete list of all instructions that can be executed, with certain exceptions.
     // The exceptions are: atomic instructions, and the dynamic cost of
-    // of operations such as table/memory fill, copy, init. This
+    // of operations such as table/memory/array fill, copy, init. This
     // dynamic cost is treated separately. Here we only assign a static cost to these instructions.
     // Cost for certain instructions differ based on the memory type (Wasm32 vs. Wasm64).
     match i {
@@ -702,6 +729,54 @@ pub fn instruction_to_cost(i: &wirm::wasmparser::Operator, mem_type: WasmMemoryT
         Operator::F32x4DemoteF64x2Zero { .. } => 1,
         Operator::F64x2PromoteLowF32x4 { .. } => 1,
 
+        ////////////////////////////////////////////////////////////////
+        // Wasm GC and exception-handling Operators
+
+        // Allocations call into the GC heap allocator. Array allocations and
+        // bulk array operations additionally have a dynamic cost of one
+        // instruction per element, which is treated separately.
+        Operator::StructNew { .. }
+        | Operator::StructNewDefault { .. }
+        | Operator::ArrayNew { .. }
+        | Operator::ArrayNewDefault { .. }
+        | Operator::ArrayNewFixed { .. }
+        | Operator::ArrayNewData { .. }
+        | Operator::ArrayNewElem { .. } => 50,
+        Operator::ArrayFill { .. }
+        | Operator::ArrayCopy { .. }
+        | Operator::ArrayInitData { .. }
+        | Operator::ArrayInitElem { .. } => 100,
+
+        // Field and element accesses are loads and stores into the GC heap
+        // guarded by a null check, and a bounds check for arrays.
+        Operator::StructGet { .. }
+        | Operator::StructGetS { .. }
+        | Operator::StructGetU { .. }
+        | Operator::StructSet { .. } => 2,
+        Operator::ArrayGet { .. }
+        | Operator::ArrayGetS { .. }
+        | Operator::ArrayGetU { .. }
+        | Operator::ArraySet { .. }
+        | Operator::ArrayLen { .. } => 3,
+
+        // Casts and type tests may walk the supertype chain of the object.
+        Operator::RefTestNonNull { .. }
+        | Operator::RefTestNullable { .. }
+        | Operator::RefCastNonNull { .. }
+        | Operator::RefCastNullable { .. }
+        | Operator::BrOnCast { .. }
+        | Operator::BrOnCastFail { .. } => 10,
+        Operator::BrOnNull { .. } | Operator::BrOnNonNull { .. } => 2,
+
+        // Calls through typed function references are indirect calls without
+        // the table lookup.
+        Operator::CallRef { .. } | Operator::ReturnCallRef { .. } => 10,
+
+        // Throwing allocates an exception object and unwinds the stack in the
+        // runtime, which is much more expensive than a regular branch.
+        Operator::Throw { .. } | Operator::ThrowRef { .. } => 1_000,
+        Operator::TryTable { .. } => 0,
+
         // Default cost of an instruction is 1.
         _ => 1,
     }
@@ -713,6 +788,7 @@ const TRY_GROW_WASM_MEMORY_FUN_NAME: &str = "try_grow_wasm_memory";
 const TRY_GROW_STABLE_MEMORY_FUN_NAME: &str = "try_grow_stable_memory";
 const INTERNAL_TRAP_FUN_NAME: &str = "internal_trap";
 const STABLE_READ_FIRST_ACCESS_NAME: &str = "stable_read_first_access";
+const TRY_GROW_GC_HEAP_FUN_NAME: &str = "try_grow_gc_heap";
 const TABLE_STR: &str = "table";
 pub(crate) const INSTRUCTIONS_COUNTER_GLOBAL_NAME: &str = "canister counter_instructions";
 pub(crate) const DIRTY_PAGES_COUNTER_GLOBAL_NAME: &str = "canister counter_dirty_pages";
@@ -738,6 +814,7 @@ fn max_memory_size_in_wasm_pages(memory_size: NumBytes) -> u64 {
 fn inject_helper_functions(
     module: &mut wirm::Module,
     mem_type: WasmMemoryType,
+    allocates_on_gc_heap: bool,
 ) -> InjectedFunctions {
     let ooi_type_idx = module.types.add_func_type(&[], &[]);
     let (out_of_instructions_fn_id, _) = module.add_import_func(
@@ -783,12 +860,25 @@ fn inject_helper_functions(
         fr_type_idx,
     );
 
+    // Modules that don't allocate on the GC heap don't get this import to keep
+    // their instrumentation unchanged.
+    let try_grow_gc_heap = allocates_on_gc_heap.then(|| {
+        let tggh_type_idx = module.types.add_func_type(&[DataType::I64], &[]);
+        let (try_grow_gc_heap_fn_id, _) = module.add_import_func(
+            INSTRUMENTED_FUN_MODULE.to_string(),
+            TRY_GROW_GC_HEAP_FUN_NAME.to_string(),
+            tggh_type_idx,
+        );
+        *try_grow_gc_heap_fn_id
+    });
+
     InjectedFunctions {
         out_of_instructions: *out_of_instructions_fn_id,
         try_grow_wasm_memory: *try_grow_wasm_memory_fn_id,
         try_grow_stable_memory: *try_grow_stable_memory_fn_id,
         internal_trap: *internal_trap_fn_id,
         stable_read_first_access: *stable_read_first_access_fn_id,
+        try_grow_gc_heap,
     }
 }
 
@@ -891,14 +981,18 @@ fn update_memories(
     (module, *stable_index)
 }
 
-// Mutable globals must be exported to be persisted.
+// Mutable globals must be exported to be persisted. Globals of reference type
+// are not persisted, so they are not exported.
 fn export_mutable_globals<'a>(mut module: wirm::Module<'a>) -> wirm::Module<'a> {
     let mut mutable_exported: Vec<(bool, bool)> = module
         .globals
         .iter()
-        .map(|g| match g.kind() {
-            GlobalKind::Local(local_global) => local_global.ty.mutable,
-            GlobalKind::Import(imported_global) => imported_global.ty.mutable,
+        .map(|g| {
+            let ty = match g.kind() {
+                GlobalKind::Local(local_global) => local_global.ty,
+                GlobalKind::Import(imported_global) => imported_global.ty,
+            };
+            ty.mutable && !matches!(ty.content_type, wirm::wasmparser::ValType::Ref(_))
         })
         .zip(std::iter::repeat(false))
         .collect();
@@ -1232,14 +1326,23 @@ fn injections(
                 res.push(curr);
                 curr = InjectionPoint::new_static_cost(position + 1, Scope::ReentrantBlockStart, 0);
             }
-            // Start of a non re-entrant code block.
-            If { .. } => {
+            // Start of a non re-entrant code block. The body of a `try_table`
+            // is a separate block because an exception caught by one of its
+            // handlers continues execution at a branch target.
+            If { .. } | TryTable { .. } => {
                 res.push(curr);
                 curr =
                     InjectionPoint::new_static_cost(position + 1, Scope::NonReentrantBlockStart, 0);
             }
             // End of a code block but still more code left.
-            Else | Br { .. } | BrIf { .. } | BrTable { .. } => {
+            Else
+            | Br { .. }
+            | BrIf { .. }
+            | BrTable { .. }
+            | BrOnNull { .. }
+            | BrOnNonNull { .. }
+            | BrOnCast { .. }
+            | BrOnCastFail { .. } => {
                 res.push(curr);
                 curr = InjectionPoint::new_static_cost(position + 1, Scope::BlockEnd, 0);
             }
@@ -1247,7 +1350,13 @@ fn injections(
                 res.push(curr);
                 curr = InjectionPoint::new_static_cost(position + 1, Scope::BlockEnd, 0);
             }
-            Return | Unreachable | ReturnCall { .. } | ReturnCallIndirect { .. } => {
+            Return
+            | Unreachable
+            | ReturnCall { .. }
+            | ReturnCallIndirect { .. }
+            | ReturnCallRef { .. }
+            | Throw { .. }
+            | ThrowRef => {
                 res.push(curr);
                 // This injection point will be unreachable itself (most likely empty)
                 // but we create it to keep the algorithm uniform
@@ -1279,6 +1388,21 @@ fn injections(
                     CostOperandOnStack::X32Bit,
                 ));
             }
+            // The number of elements of array allocations and bulk array
+            // operations is an i32 at the top of the stack.
+            ArrayNew { .. }
+            | ArrayNewDefault { .. }
+            | ArrayNewData { .. }
+            | ArrayNewElem { .. }
+            | ArrayFill { .. }
+            | ArrayCopy { .. }
+            | ArrayInitData { .. }
+            | ArrayInitElem { .. } => {
+                res.push(InjectionPoint::new_dynamic_cost(
+                    position,
+                    CostOperandOnStack::X32Bit,
+                ));
+            }
             // Nothing special to be done for other instructions.
             _ => (),
         }
@@ -1444,6 +1568,272 @@ fn inject_try_grow_wasm_memory(
     }
 }
 
+/// The number of bytes charged for the header of every object on the GC heap.
+const GC_OBJECT_HEADER_BYTES: u64 = 16;
+
+/// Deterministic estimates of the sizes of the objects that the Wasm GC and
+/// exception-handling instructions allocate on the GC heap.
+///
+/// The estimates are computed from the type, import, and tag sections of the
+/// original Wasm binary, so they don't depend on the object layout chosen by
+/// the runtime.
+#[derive(Debug, Default)]
+pub(super) struct GcObjectSizes {
+    /// The size of a struct by its type index.
+    structs: BTreeMap<u32, u64>,
+    /// The size of an array element by the array type index.
+    array_elements: BTreeMap<u32, u64>,
+    /// The size of an exception object by its tag index.
+    exceptions: Vec<u64>,
+}
+
+impl GcObjectSizes {
+    pub(super) fn new(wasm: &BinaryEncodedWasm) -> Result<Self, WasmInstrumentationError> {
+        use wirm::wasmparser::{CompositeInnerType, Parser, Payload, TypeRef};
+
+        let to_error = |err: wirm::wasmparser::BinaryReaderError| {
+            WasmInstrumentationError::WasmDeserializeError(WasmError::new(err.to_string()))
+        };
+
+        let mut sizes = Self::default();
+        let mut func_params = BTreeMap::new();
+        let mut tag_types = Vec::new();
+        for payload in Parser::new(0).parse_all(wasm.as_slice()) {
+            match payload.map_err(to_error)? {
+                Payload::TypeSection(reader) => {
+                    let mut type_index = 0;
+                    for rec_group in reader {
+                        for sub_type in rec_group.map_err(to_error)?.into_types() {
+                            match &sub_type.composite_type.inner {
+                                CompositeInnerType::Struct(struct_type) => {
+                                    let fields: u64 = struct_type
+                                        .fields
+                                        .iter()
+                                        .map(|field| storage_type_size(field.element_type))
+                                        .sum();
+                                    sizes
+                                        .structs
+                                        .insert(type_index, GC_OBJECT_HEADER_BYTES + fields);
+                                }
+                                CompositeInnerType::Array(array_type) => {
+                                    sizes.array_elements.insert(
+                                        type_index,
+                                        storage_type_size(array_type.0.element_type),
+                                    );
+                                }
+                                CompositeInnerType::Func(func_type) => {
+                                    let params: u64 = func_type
+                                        .params()
+                                        .iter()
+                                        .map(|param| val_type_size(*param))
+                                        .sum();
+                                    func_params.insert(type_index, params);
+                                }
+                                _ => {}
+                            }
+                            type_index += 1;
+                        }
+                    }
+                }
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        if let TypeRef::Tag(tag) = import.map_err(to_error)?.ty {
+                            tag_types.push(tag.func_type_idx);
+                        }
+                    }
+                }
+                Payload::TagSection(reader) => {
+                    for tag in reader {
+                        tag_types.push(tag.map_err(to_error)?.func_type_idx);
+                    }
+                }
+                _ => {}
+            }
+        }
+        sizes.exceptions = tag_types
+            .into_iter()
+            .map(|func_type_idx| {
+                GC_OBJECT_HEADER_BYTES + func_params.get(&func_type_idx).copied().unwrap_or(0)
+            })
+            .collect();
+        Ok(sizes)
+    }
+
+    fn struct_size(&self, type_index: u32) -> u64 {
+        self.structs
+            .get(&type_index)
+            .copied()
+            .unwrap_or(GC_OBJECT_HEADER_BYTES)
+    }
+
+    fn array_element_size(&self, type_index: u32) -> u64 {
+        self.array_elements.get(&type_index).copied().unwrap_or(0)
+    }
+
+    fn exception_size(&self, tag_index: u32) -> u64 {
+        self.exceptions
+            .get(tag_index as usize)
+            .copied()
+            .unwrap_or(GC_OBJECT_HEADER_BYTES)
+    }
+}
+
+fn storage_type_size(storage_type: wirm::wasmparser::StorageType) -> u64 {
+    use wirm::wasmparser::StorageType;
+    match storage_type {
+        StorageType::I8 => 1,
+        StorageType::I16 => 2,
+        StorageType::Val(val_type) => val_type_size(val_type),
+    }
+}
+
+fn val_type_size(val_type: wirm::wasmparser::ValType) -> u64 {
+    use wirm::wasmparser::ValType;
+    match val_type {
+        ValType::I32 | ValType::F32 => 4,
+        ValType::I64 | ValType::F64 => 8,
+        ValType::V128 => 16,
+        // References into the GC heap are 32-bit indices.
+        ValType::Ref(_) => 4,
+    }
+}
+
+fn allocates_on_gc_heap(module: &wirm::Module<'_>) -> bool {
+    use wirm::wasmparser::Operator::*;
+    module
+        .functions
+        .iter()
+        .filter_map(|function| match function.kind() {
+            FuncKind::Local(function) => Some(function),
+            FuncKind::Import(_) => None,
+        })
+        .flat_map(|function| function.body.instructions.get_ops().iter())
+        .any(|op| {
+            matches!(
+                op,
+                StructNew { .. }
+                    | StructNewDefault { .. }
+                    | ArrayNew { .. }
+                    | ArrayNewDefault { .. }
+                    | ArrayNewFixed { .. }
+                    | ArrayNewData { .. }
+                    | ArrayNewElem { .. }
+                    | Throw { .. }
+            )
+        })
+}
+
+// Injects a function that charges the GC heap memory of an array allocation
+// whose number of elements is only known at runtime:
+//
+// ```wasm
+// (func (param $len i32) (param $element_size i64) (result i32)
+//   local.get $len
+//   i64.extend_i32_u
+//   local.get $element_size
+//   i64.mul
+//   i64.const GC_OBJECT_HEADER_BYTES
+//   i64.add
+//   call $try_grow_gc_heap
+//   local.get $len)
+// ```
+fn inject_charge_gc_array_function(module: &mut wirm::Module<'_>, try_grow_gc_heap: u32) -> u32 {
+    use wirm::wasmparser::Operator::*;
+
+    let instructions = vec![
+        LocalGet { local_index: 0 },
+        I64ExtendI32U,
+        LocalGet { local_index: 1 },
+        I64Mul,
+        I64Const {
+            value: GC_OBJECT_HEADER_BYTES as i64,
+        },
+        I64Add,
+        Call {
+            function_index: try_grow_gc_heap,
+        },
+        // Return the number of elements so this function doesn't alter the stack.
+        LocalGet { local_index: 0 },
+    ];
+    let num_instructions = instructions.len();
+    let mut builder = FunctionBuilder::new(&[DataType::I32, DataType::I64], &[DataType::I32]);
+    builder.body = Body {
+        locals: vec![],
+        num_locals: 0,
+        instructions: Instructions::new(instructions),
+        num_instructions,
+        name: None,
+    };
+    *builder.finish_module(module)
+}
+
+// Scans through the function and inserts a call to `try_grow_gc_heap` before
+// each instruction that allocates on the GC heap. The size of the allocation is
+// either known statically or computed by `charge_gc_array_fn` from the number
+// of elements at the top of the stack.
+fn inject_try_grow_gc_heap(
+    func_body: &mut wirm::ir::types::Body,
+    gc_object_sizes: &GcObjectSizes,
+    try_grow_gc_heap: u32,
+    charge_gc_array_fn: u32,
+) {
+    use wirm::wasmparser::Operator::*;
+
+    let static_size = |size: u64| {
+        [
+            I64Const { value: size as i64 },
+            Call {
+                function_index: try_grow_gc_heap,
+            },
+        ]
+    };
+
+    let orig_elems = func_body.instructions.get_ops();
+    let mut elems: Vec<wirm::wasmparser::Operator> = Vec::with_capacity(orig_elems.len());
+    for op in orig_elems.iter() {
+        match op {
+            StructNew { struct_type_index } | StructNewDefault { struct_type_index } => {
+                elems.extend(static_size(gc_object_sizes.struct_size(*struct_type_index)));
+            }
+            ArrayNewFixed {
+                array_type_index,
+                array_size,
+            } => {
+                elems.extend(static_size(
+                    GC_OBJECT_HEADER_BYTES
+                        + gc_object_sizes.array_element_size(*array_type_index)
+                            * (*array_size as u64),
+                ));
+            }
+            ArrayNew { array_type_index }
+            | ArrayNewDefault { array_type_index }
+            | ArrayNewData {
+                array_type_index, ..
+            }
+            | ArrayNewElem {
+                array_type_index, ..
+            } => {
+                elems.extend([
+                    I64Const {
+                        value: gc_object_sizes.array_element_size(*array_type_index) as i64,
+                    },
+                    Call {
+                        function_index: charge_gc_array_fn,
+                    },
+                ]);
+            }
+            Throw { tag_index } => {
+                elems.extend(static_size(gc_object_sizes.exception_size(*tag_index)));
+            }
+            _ => {}
+        }
+        elems.push(op.clone());
+    }
+    let num_instructions = elems.len();
+    func_body.instructions = Instructions::new(elems);
+    func_body.num_instructions = num_instructions;
+}
+
 fn calculate_api_indexes(module: &wirm::Module<'_>) -> BTreeMap<SystemApiFunc, u32> {
     module
         .imports
@@ -1551,6 +1941,7 @@ fn get_data(
 /// not be instrumented.
 pub(super) fn instrument(
     mut module: wirm::Module<'_>,
+    gc_object_sizes: &GcObjectSizes,
     cost_to_compile_wasm_instruction: NumInstructions,
     metering_type: MeteringType,
     dirty_page_overhead: NumInstructions,
@@ -1558,7 +1949,9 @@ pub(super) fn instrument(
     max_stable_memory_size: NumBytes,
 ) -> Result<InstrumentationOutput, WasmInstrumentationError> {
     let main_memory_type = main_memory_type(&module);
-    let injected_functions = inject_helper_functions(&mut module, main_memory_type);
+    let allocates_on_gc_heap = allocates_on_gc_heap(&module);
+    let injected_functions =
+        inject_helper_functions(&mut module, main_memory_type, allocates_on_gc_heap);
 
     module = export_table(module);
     let stable_memory_index;
@@ -1611,6 +2004,24 @@ pub(super) fn instrument(
         );
     }
 
+    // Inject `try_grow_gc_heap` before instructions that allocate on the GC heap.
+    if let Some(try_grow_gc_heap) = injected_functions.try_grow_gc_heap {
+        let charge_gc_array_fn = inject_charge_gc_array_function(&mut module, try_grow_gc_heap);
+        for func_body in &mut module
+            .functions
+            .iter_mut()
+            .filter(|f| f.is_local())
+            .map(Function::unwrap_local_mut)
+        {
+            inject_try_grow_gc_heap(
+                &mut func_body.body,
+                gc_object_sizes,
+                try_grow_gc_heap,
+                charge_gc_array_fn,
+            );
+        }
+    }
+
     replace_system_api_functions(
         &mut module,
         &injected_functions,
@@ -11,7 +11,7 @@ use ic_types::{MAX_STABLE_MEMORY_IN_BYTES, NumBytes, NumInstructions};
 use ic_wasm_types::{BinaryEncodedWasm, WasmValidationError};
 use std::{
     cmp,
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
 };
 
 use crate::wasmtime_embedder::{
@@ -462,6 +462,16 @@ fn get_valid_system_apis_common(
                 },
             )],
         ),
+        (
+            "debug_print_structured",
+            vec![(
+                API_VERSION_IC0,
+                FunctionSignature {
+                    param_types: vec![DataType::I32, I, I, I, I],
+                    return_type: vec![],
+                },
+            )],
+        ),
         (
             "stable64_size",
             vec![(
@@ -582,6 +592,26 @@ fn get_valid_system_apis_common(
                 },
             )],
         ),
+        (
+            "query_cache_reply",
+            vec![(
+                API_VERSION_IC0,
+                FunctionSignature {
+                    param_types: vec![DataType::I64, I, I],
+                    return_type: vec![],
+                },
+            )],
+        ),
+        (
+            "query_cache_invalidate",
+            vec![(
+                API_VERSION_IC0,
+                FunctionSignature {
+                    param_types: vec![I, I],
+                    return_type: vec![],
+                },
+            )],
+        ),
         (
             "data_certificate_present",
             vec![(
@@ -1203,7 +1233,14 @@ fn validate_data_section(module: &Module) -> Result<(), WasmValidationError> {
 
 // Checks that no more than `max_globals` are defined in the module
 // and all globals have supported type.
-fn validate_global_section(module: &Module, max_globals: usize) -> Result<(), WasmValidationError> {
+//
+// Globals of reference type are only supported if Wasm GC is enabled. They are
+// not persisted and hold their initial value at the start of every message.
+fn validate_global_section(
+    module: &Module,
+    max_globals: usize,
+    gc_and_exceptions: bool,
+) -> Result<(), WasmValidationError> {
     if module.globals.len() > max_globals {
         return Err(WasmValidationError::TooManyGlobals {
             defined: module.globals.len(),
@@ -1217,6 +1254,7 @@ fn validate_global_section(module: &Module, max_globals: usize) -> Result<(), Wa
         };
         match ty.content_type {
             ValType::I32 | ValType::I64 | ValType::F32 | ValType::F64 | ValType::V128 => (),
+            ValType::Ref(_) if gc_and_exceptions => (),
             other => {
                 return Err(WasmValidationError::InvalidGlobalSection(format!(
                     "Unsupported global type: {:?}",
@@ -1411,9 +1449,52 @@ fn validate_custom_section(
     Ok(WasmMetadata::new(validated_custom_sections))
 }
 
+// Returns the indices of the tables that hold references other than `funcref`
+// if Wasm GC is enabled. Like the GC heap, such tables are not persisted, so
+// they may be modified during a message and are reset to their initial
+// contents at the start of the next one.
+fn gc_reference_tables(
+    wasm: &BinaryEncodedWasm,
+    gc_and_exceptions: bool,
+) -> Result<BTreeSet<u32>, WasmValidationError> {
+    use wirm::wasmparser::{BinaryReaderError, Parser, Payload, RefType};
+
+    let mut tables = BTreeSet::new();
+    if !gc_and_exceptions {
+        return Ok(tables);
+    }
+    let to_error = |err: BinaryReaderError| WasmValidationError::DecodingError(err.to_string());
+    let mut table_index = 0;
+    let mut add_table = |element_type: RefType| {
+        if !element_type.is_func_ref() {
+            tables.insert(table_index);
+        }
+        table_index += 1;
+    };
+    for payload in Parser::new(0).parse_all(wasm.as_slice()) {
+        match payload.map_err(to_error)? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    if let TypeRef::Table(table_type) = import.map_err(to_error)?.ty {
+                        add_table(table_type.element_type);
+                    }
+                }
+            }
+            Payload::TableSection(reader) => {
+                for table in reader {
+                    add_table(table.map_err(to_error)?.ty.element_type);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(tables)
+}
+
 fn wasm_function_complexity(
     index: usize,
     body: &Body<'_>,
+    gc_reference_tables: &BTreeSet<u32>,
 ) -> Result<Complexity, WasmValidationError> {
     use Operator::*;
 
@@ -1429,6 +1510,23 @@ fn wasm_function_complexity(
             | Call { .. }
             | CallIndirect { .. }
             | MemoryGrow { .. } => 50,
+            // Wasm GC and exception-handling instructions that branch, call,
+            // or allocate on the GC heap.
+            TryTable { .. }
+            | Throw { .. }
+            | ThrowRef
+            | BrOnNull { .. }
+            | BrOnNonNull { .. }
+            | BrOnCast { .. }
+            | BrOnCastFail { .. }
+            | CallRef { .. }
+            | StructNew { .. }
+            | StructNewDefault { .. }
+            | ArrayNew { .. }
+            | ArrayNewDefault { .. }
+            | ArrayNewFixed { .. }
+            | ArrayNewData { .. }
+            | ArrayNewElem { .. } => 50,
             TableGrow { .. } => {
                 return Err(WasmValidationError::UnsupportedWasmInstruction {
                     index,
@@ -1437,6 +1535,7 @@ fn wasm_function_complexity(
             }
             TableGet { .. } => 14,
             RefFunc { .. } => 8,
+            TableSet { table } if gc_reference_tables.contains(table) => 14,
             TableSet { .. } => {
                 return Err(WasmValidationError::UnsupportedWasmInstruction {
                     index,
@@ -1444,6 +1543,7 @@ fn wasm_function_complexity(
                 });
             }
             RefIsNull => 6,
+            TableFill { table } if gc_reference_tables.contains(table) => 4,
             TableFill { .. } => {
                 return Err(WasmValidationError::UnsupportedWasmInstruction {
                     index,
@@ -1575,6 +1675,7 @@ fn wasm_function_complexity(
 
 fn validate_code_section(
     module: &Module,
+    gc_reference_tables: &BTreeSet<u32>,
 ) -> Result<(NumInstructions, Complexity), WasmValidationError> {
     let mut max_function_size = NumInstructions::new(0);
     let mut max_complexity = Complexity(0);
@@ -1585,7 +1686,7 @@ fn validate_code_section(
         };
         let func_body = &local_func.body;
         let size = func_body.instructions.len();
-        let complexity = wasm_function_complexity(index, func_body)?;
+        let complexity = wasm_function_complexity(index, func_body, gc_reference_tables)?;
         if complexity > WASM_FUNCTION_COMPLEXITY_LIMIT {
             return Err(WasmValidationError::FunctionComplexityTooHigh {
                 index,
@@ -1613,9 +1714,17 @@ fn validate_code_section(
 pub fn wasmtime_validation_config(embedders_config: &EmbeddersConfig) -> wasmtime::Config {
     let mut config = wasmtime::Config::default();
 
+    let gc_and_exceptions =
+        embedders_config.feature_flags.wasm_gc_and_exceptions == FlagStatus::Enabled;
+
     // Keep this in the alphabetical order to simplify comparison with new
     // `wasmtime::Config` methods in a new version of wasmtime.
 
+    // The null collector never frees objects, so the GC heap only grows during
+    // an execution and charging every allocation upfront bounds its size.
+    if gc_and_exceptions {
+        config.collector(wasmtime::Collector::Null);
+    }
     // NaN canonicalization is needed for determinism.
     config.cranelift_nan_canonicalization(true);
     // Disable optimizations to keep compilation simple and fast.
@@ -1628,8 +1737,11 @@ pub fn wasmtime_validation_config(embedders_config: &EmbeddersConfig) -> wasmtim
     config.wasm_backtrace(embedders_config.feature_flags.canister_backtrace == FlagStatus::Enabled);
     config.wasm_backtrace_details(wasmtime::WasmBacktraceDetails::Disable);
     config.wasm_bulk_memory(true);
-    config.wasm_function_references(false);
-    config.wasm_gc(false);
+    // Exception objects live on the GC heap, so the exception-handling
+    // proposal is enabled together with the GC proposal.
+    config.wasm_exceptions(gc_and_exceptions);
+    config.wasm_function_references(gc_and_exceptions);
+    config.wasm_gc(gc_and_exceptions);
     config.wasm_memory64(true);
     // Wasm multi-memory feature is disabled during validation,
     // but enabled during execution for the Wasm-native stable memory
@@ -1664,6 +1776,14 @@ fn can_create_engine_from_validation_config() {
     wasmtime::Engine::new(&config).expect("Cannot create engine from validation config");
 }
 
+#[test]
+fn can_create_engine_from_validation_config_with_gc_and_exceptions() {
+    let mut embedders_config = EmbeddersConfig::default();
+    embedders_config.feature_flags.wasm_gc_and_exceptions = FlagStatus::Enabled;
+    let config = wasmtime_validation_config(&embedders_config);
+    wasmtime::Engine::new(&config).expect("Cannot create engine from validation config");
+}
+
 fn can_compile(
     wasm: &BinaryEncodedWasm,
     embedders_config: &EmbeddersConfig,
@@ -1731,7 +1851,8 @@ pub(super) fn validate_wasm_binary<'a>(
         config.max_sum_exported_function_name_lengths,
     )?;
     validate_data_section(&module)?;
-    validate_global_section(&module, config.max_globals)?;
+    let gc_and_exceptions = config.feature_flags.wasm_gc_and_exceptions == FlagStatus::Enabled;
+    validate_global_section(&module, config.max_globals, gc_and_exceptions)?;
     validate_function_section(&module, config.max_functions)?;
     // The maximum Wasm memory size is different for Wasm32 and Wasm64 and
     // each needs to be validated accordingly.
@@ -1741,7 +1862,9 @@ pub(super) fn validate_wasm_binary<'a>(
         config.max_wasm_memory_size
     };
     validate_initial_wasm_memory_size(&module, max_wasm_memory_size)?;
-    let (largest_function_instruction_count, max_complexity) = validate_code_section(&module)?;
+    let gc_reference_tables = gc_reference_tables(wasm, gc_and_exceptions)?;
+    let (largest_function_instruction_count, max_complexity) =
+        validate_code_section(&module, &gc_reference_tables)?;
     let wasm_metadata = validate_custom_section(&module, config)?;
     Ok((
         WasmValidationDetails {
@@ -37,7 +37,8 @@ use crate::wasm_utils::instrumentation::{
     INSTRUCTIONS_COUNTER_GLOBAL_NAME, WasmMemoryType,
 };
 use crate::{
-    serialized_module::SerializedModuleBytes, wasm_utils::validation::wasmtime_validation_config,
+    CompilationTier, serialized_module::SerializedModuleBytes,
+    wasm_utils::validation::wasmtime_validation_config,
 };
 
 use super::InstanceRunResult;
@@ -163,8 +164,8 @@ fn get_exported_globals<T>(instance: &Instance, store: &mut Store<T>) -> Vec<was
         ACCESSED_PAGES_COUNTER_GLOBAL_NAME,
     ];
 
-    instance
-        .exports(store)
+    let globals: Vec<_> = instance
+        .exports(&mut *store)
         .filter_map(|e| {
             if TO_IGNORE.contains(&e.name()) {
                 None
@@ -172,6 +173,13 @@ fn get_exported_globals<T>(instance: &Instance, store: &mut Store<T>) -> Vec<was
                 e.into_global()
             }
         })
+        .collect();
+    // Globals of reference type point into the GC heap or into tables, which
+    // don't outlive the message execution. They are not persisted and start
+    // from their initial value in every message.
+    globals
+        .into_iter()
+        .filter(|g| !matches!(g.ty(&*store).content(), ValType::Ref(_)))
         .collect()
 }
 
@@ -233,7 +241,17 @@ impl WasmtimeEmbedder {
     }
 
     fn create_engine(&self) -> HypervisorResult<Engine> {
+        self.create_engine_for_tier(CompilationTier::Baseline)
+    }
+
+    /// Wasmtime doesn't check the optimization level when deserializing a
+    /// module, so modules of both tiers can be deserialized with the engine
+    /// returned by `create_engine`.
+    fn create_engine_for_tier(&self, tier: CompilationTier) -> HypervisorResult<Engine> {
         let mut config = Self::wasmtime_execution_config(&self.config);
+        if tier == CompilationTier::Optimized {
+            config.cranelift_opt_level(wasmtime::OptLevel::Speed);
+        }
         let mem_creator = Arc::new(WasmtimeMemoryCreator::new(Arc::clone(
             &self.created_memories,
         )));
@@ -245,12 +263,21 @@ impl WasmtimeEmbedder {
     }
 
     pub fn compile(&self, wasm_binary: &BinaryEncodedWasm) -> HypervisorResult<Module> {
-        let module = wasmtime::Module::new(&self.create_engine()?, wasm_binary.as_slice())
-            .map_err(|e| {
-                HypervisorError::WasmEngineError(WasmEngineError::FailedToInstantiateModule(
-                    format!("{e:?}"),
-                ))
-            })?;
+        self.compile_with_tier(wasm_binary, CompilationTier::Baseline)
+    }
+
+    pub fn compile_with_tier(
+        &self,
+        wasm_binary: &BinaryEncodedWasm,
+        tier: CompilationTier,
+    ) -> HypervisorResult<Module> {
+        let module =
+            wasmtime::Module::new(&self.create_engine_for_tier(tier)?, wasm_binary.as_slice())
+                .map_err(|e| {
+                    HypervisorError::WasmEngineError(WasmEngineError::FailedToInstantiateModule(
+                        format!("{e:?}"),
+                    ))
+                })?;
         Ok(module)
     }
 
@@ -191,6 +191,16 @@ fn charge_direct_fee(
     Ok(())
 }
 
+/// Counts a call of the given System API function if System API call
+/// profiling is enabled. The System API is not accessed otherwise, so that
+/// calls are not slowed down when profiling is disabled.
+#[inline(always)]
+fn count_system_api_call(caller: &mut Caller<'_, StoreData>, profile_calls: bool, function: &str) {
+    if profile_calls && let Ok(system_api) = caller.data_mut().system_api_mut() {
+        system_api.count_call(function);
+    }
+}
+
 /// A helper to pass wasmtime counters to the System API
 fn ic0_performance_counter_helper(
     caller: &mut Caller<'_, StoreData>,
@@ -317,9 +327,12 @@ pub fn syscalls<
         // LINT.ThenChange(logging_charge_bytes_rule)
     }
 
+    let profile_calls = feature_flags.system_api_call_profiling == FlagStatus::Enabled;
+
     linker
         .func_wrap("ic0", "msg_caller_copy", {
             move |mut caller: Caller<'_, StoreData>, dst: I, offset: I, size: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_caller_copy");
                 let dst: usize = dst.try_into().expect("Failed to convert I to usize");
                 let offset: usize = offset.try_into().expect("Failed to convert I to usize");
                 let size: usize = size.try_into().expect("Failed to convert I to usize");
@@ -335,6 +348,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_caller_size", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_caller_size");
                 charge_for_cpu(&mut caller, overhead::MSG_CALLER_SIZE)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_caller_size()).and_then(|s| {
                     I::try_from(s).map_err(|e| {
@@ -348,6 +362,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_arg_data_size", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_arg_data_size");
                 charge_for_cpu(&mut caller, overhead::MSG_ARG_DATA_SIZE)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_arg_data_size()).and_then(|s| {
                     I::try_from(s).map_err(|e| {
@@ -361,6 +376,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_arg_data_copy", {
             move |mut caller: Caller<'_, StoreData>, dst: I, offset: I, size: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_arg_data_copy");
                 let dst: usize = dst.try_into().expect("Failed to convert I to usize");
                 let offset: usize = offset.try_into().expect("Failed to convert I to usize");
                 let size: usize = size.try_into().expect("Failed to convert I to usize");
@@ -376,6 +392,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_method_name_size", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_method_name_size");
                 charge_for_cpu(&mut caller, overhead::MSG_METHOD_NAME_SIZE)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_method_name_size()).and_then(|s| {
                     I::try_from(s).map_err(|e| {
@@ -389,6 +406,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_method_name_copy", {
             move |mut caller: Caller<'_, StoreData>, dst: I, offset: I, size: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_method_name_copy");
                 let dst: usize = dst.try_into().expect("Failed to convert I to usize");
                 let offset: usize = offset.try_into().expect("Failed to convert I to usize");
                 let size: usize = size.try_into().expect("Failed to convert I to usize");
@@ -404,6 +422,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "accept_message", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.accept_message");
                 charge_for_cpu(&mut caller, overhead::ACCEPT_MESSAGE)?;
                 with_system_api(&mut caller, |s| s.ic0_accept_message())
             }
@@ -413,6 +432,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_reply_data_append", {
             move |mut caller: Caller<'_, StoreData>, src: I, size: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_reply_data_append");
                 let src: usize = src.try_into().expect("Failed to convert I to usize");
                 let size: usize = size.try_into().expect("Failed to convert I to usize");
                 charge_for_cpu_and_mem(
@@ -430,6 +450,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_reply", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_reply");
                 charge_for_cpu(&mut caller, overhead::MSG_REPLY)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_reply())
             }
@@ -439,6 +460,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_reject_code", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_reject_code");
                 charge_for_cpu(&mut caller, overhead::MSG_REJECT_CODE)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_reject_code())
             }
@@ -448,6 +470,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_reject", {
             move |mut caller: Caller<'_, StoreData>, src: I, size: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_reject");
                 let src: usize = src.try_into().expect("Failed to convert I to usize");
                 let size: usize = size.try_into().expect("Failed to convert I to usize");
                 charge_for_cpu_and_mem(
@@ -465,6 +488,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_reject_msg_size", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_reject_msg_size");
                 charge_for_cpu(&mut caller, overhead::MSG_REJECT_MSG_SIZE)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_reject_msg_size()).and_then(|s| {
                     I::try_from(s).map_err(|e| {
@@ -478,6 +502,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_reject_msg_copy", {
             move |mut caller: Caller<'_, StoreData>, dst: I, offset: I, size: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_reject_msg_copy");
                 let dst: usize = dst.try_into().expect("Failed to convert I to usize");
                 let offset: usize = offset.try_into().expect("Failed to convert I to usize");
                 let size: usize = size.try_into().expect("Failed to convert I to usize");
@@ -493,6 +518,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "canister_self_size", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.canister_self_size");
                 charge_for_cpu(&mut caller, overhead::CANISTER_SELF_SIZE)?;
                 with_system_api(&mut caller, |s| s.ic0_canister_self_size()).and_then(|s| {
                     I::try_from(s).map_err(|e| {
@@ -506,6 +532,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "canister_self_copy", {
             move |mut caller: Caller<'_, StoreData>, dst: I, offset: I, size: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.canister_self_copy");
                 let dst: usize = dst.try_into().expect("Failed to convert I to usize");
                 let offset: usize = offset.try_into().expect("Failed to convert I to usize");
                 let size: usize = size.try_into().expect("Failed to convert I to usize");
@@ -522,6 +549,7 @@ pub fn syscalls<
         linker
             .func_wrap("ic0", "env_var_count", {
                 move |mut caller: Caller<'_, StoreData>| {
+                    count_system_api_call(&mut caller, profile_calls, "ic0.env_var_count");
                     charge_for_cpu(&mut caller, overhead::ENV_VAR_COUNT)?;
 
                     with_system_api(&mut caller, |s| s.ic0_env_var_count()).and_then(|s| {
@@ -537,6 +565,7 @@ pub fn syscalls<
         linker
             .func_wrap("ic0", "env_var_name_size", {
                 move |mut caller: Caller<'_, StoreData>, index: I| {
+                    count_system_api_call(&mut caller, profile_calls, "ic0.env_var_name_size");
                     let index: usize = index.try_into().expect("Failed to convert I to usize");
                     charge_for_cpu(&mut caller, overhead::ENV_VAR_NAME_SIZE)?;
                     with_system_api(&mut caller, |s| s.ic0_env_var_name_size(index)).and_then(|s| {
@@ -553,6 +582,7 @@ pub fn syscalls<
         linker
             .func_wrap("ic0", "env_var_name_copy", {
                 move |mut caller: Caller<'_, StoreData>, index: I, dst: I, offset: I, size: I| {
+                    count_system_api_call(&mut caller, profile_calls, "ic0.env_var_name_copy");
                     let index: usize = index.try_into().expect("Failed to convert I to usize");
                     let dst: usize = dst.try_into().expect("Failed to convert I to usize");
                     let offset: usize = offset.try_into().expect("Failed to convert I to usize");
@@ -571,6 +601,7 @@ pub fn syscalls<
         linker
             .func_wrap("ic0", "env_var_name_exists", {
                 move |mut caller: Caller<'_, StoreData>, name_src: I, name_size: I| {
+                    count_system_api_call(&mut caller, profile_calls, "ic0.env_var_name_exists");
                     let name_src: usize =
                         name_src.try_into().expect("Failed to convert I to usize");
                     let name_size: usize =
@@ -588,6 +619,7 @@ pub fn syscalls<
         linker
             .func_wrap("ic0", "env_var_value_size", {
                 move |mut caller: Caller<'_, StoreData>, name_src: I, name_size: I| {
+                    count_system_api_call(&mut caller, profile_calls, "ic0.env_var_value_size");
                     let name_src: usize =
                         name_src.try_into().expect("Failed to convert I to usize");
                     let name_size: usize =
@@ -615,6 +647,7 @@ pub fn syscalls<
                       dst: I,
                       offset: I,
                       size: I| {
+                    count_system_api_call(&mut caller, profile_calls, "ic0.env_var_value_copy");
                     let name_src: usize =
                         name_src.try_into().expect("Failed to convert I to usize");
                     let name_size: usize =
@@ -637,6 +670,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "debug_print", {
             move |mut caller: Caller<'_, StoreData>, offset: I, length: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.debug_print");
                 let length: usize = length.try_into().expect("Failed to convert I to usize");
                 let mut num_bytes = logging_charge_bytes(&mut caller, length)?;
                 let debug_print_is_enabled = debug_print_is_enabled(&mut caller, &feature_flags)?;
@@ -657,9 +691,59 @@ pub fn syscalls<
         })
         .unwrap();
 
+    if feature_flags.structured_logging == FlagStatus::Enabled {
+        linker
+            .func_wrap("ic0", "debug_print_structured", {
+                move |mut caller: Caller<'_, StoreData>,
+                      severity: u32,
+                      offset: I,
+                      length: I,
+                      fields_offset: I,
+                      fields_length: I| {
+                    count_system_api_call(&mut caller, profile_calls, "ic0.debug_print_structured");
+                    let length: usize = length.try_into().expect("Failed to convert I to usize");
+                    let fields_length: usize = fields_length
+                        .try_into()
+                        .expect("Failed to convert I to usize");
+                    let mut num_bytes = logging_charge_bytes(&mut caller, length + fields_length)?;
+                    let debug_print_is_enabled =
+                        debug_print_is_enabled(&mut caller, &feature_flags)?;
+                    if debug_print_is_enabled {
+                        num_bytes += length;
+                    }
+                    charge_for_cpu_and_mem(
+                        &mut caller,
+                        overhead::DEBUG_PRINT_STRUCTURED,
+                        num_bytes,
+                    )?;
+                    let offset: usize = offset.try_into().expect("Failed to convert I to usize");
+                    let fields_offset: usize = fields_offset
+                        .try_into()
+                        .expect("Failed to convert I to usize");
+                    with_memory_and_system_api(&mut caller, |system_api, memory| {
+                        system_api.save_structured_log_message(
+                            severity,
+                            offset,
+                            length,
+                            fields_offset,
+                            fields_length,
+                            memory,
+                        );
+                        if debug_print_is_enabled {
+                            system_api.ic0_debug_print(offset, length, memory)
+                        } else {
+                            Ok(())
+                        }
+                    })
+                }
+            })
+            .unwrap();
+    }
+
     linker
         .func_wrap("ic0", "trap", {
             move |mut caller: Caller<'_, StoreData>, offset: I, length: I| -> Result<(), _> {
+                count_system_api_call(&mut caller, profile_calls, "ic0.trap");
                 let offset: usize = offset.try_into().expect("Failed to convert I to usize");
                 let length: usize = length.try_into().expect("Failed to convert I to usize");
                 let num_bytes = length + logging_charge_bytes(&mut caller, length)?;
@@ -682,6 +766,7 @@ pub fn syscalls<
                   reply_env: I,
                   reject_fun: I,
                   reject_env: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.call_new");
                 let callee_src: usize =
                     callee_src.try_into().expect("Failed to convert I to usize");
                 let callee_size: usize = callee_size
@@ -719,6 +804,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "call_data_append", {
             move |mut caller: Caller<'_, StoreData>, src: I, size: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.call_data_append");
                 let src: usize = src.try_into().expect("Failed to convert I to usize");
                 let size: usize = size.try_into().expect("Failed to convert I to usize");
                 charge_for_cpu_and_mem(
@@ -736,6 +822,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "call_on_cleanup", {
             move |mut caller: Caller<'_, StoreData>, fun: I, env: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.call_on_cleanup");
                 let env: u64 = env.try_into().expect("Failed to convert I to usize");
                 charge_for_cpu(&mut caller, overhead::CALL_ON_CLEANUP)?;
                 with_system_api(&mut caller, |s| {
@@ -750,6 +837,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "call_cycles_add", {
             move |mut caller: Caller<'_, StoreData>, amount: u64| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.call_cycles_add");
                 charge_for_cpu(&mut caller, overhead::CALL_CYCLES_ADD)?;
                 with_system_api(&mut caller, |s| s.ic0_call_cycles_add(amount))
             }
@@ -759,6 +847,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "call_cycles_add128", {
             move |mut caller: Caller<'_, StoreData>, amount_high: u64, amount_low: u64| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.call_cycles_add128");
                 charge_for_cpu(&mut caller, overhead::CALL_CYCLES_ADD128)?;
                 with_system_api(&mut caller, |s| {
                     s.ic0_call_cycles_add128(Cycles::from_parts(amount_high, amount_low))
@@ -770,6 +859,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "call_perform", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.call_perform");
                 charge_for_cpu(&mut caller, overhead::CALL_PERFORM)?;
                 with_system_api(&mut caller, |s| s.ic0_call_perform())
             }
@@ -790,6 +880,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "time", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.time");
                 charge_for_cpu(&mut caller, overhead::TIME)?;
                 with_system_api(&mut caller, |s| s.ic0_time())
                     .map(|s| s.as_nanos_since_unix_epoch())
@@ -800,6 +891,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "global_timer_set", {
             move |mut caller: Caller<'_, StoreData>, time: u64| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.global_timer_set");
                 charge_for_cpu(&mut caller, overhead::GLOBAL_TIMER_SET)?;
                 with_system_api(&mut caller, |s| {
                     s.ic0_global_timer_set(Time::from_nanos_since_unix_epoch(time))
@@ -812,6 +904,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "performance_counter", {
             move |mut caller: Caller<'_, StoreData>, counter_type: u32| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.performance_counter");
                 charge_for_cpu(&mut caller, overhead::PERFORMANCE_COUNTER)?;
                 ic0_performance_counter_helper(&mut caller, counter_type)
                     .map_err(|e| process_err(&mut caller, e))
@@ -822,6 +915,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "canister_version", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.canister_version");
                 charge_for_cpu(&mut caller, overhead::CANISTER_VERSION)?;
                 with_system_api(&mut caller, |s| s.ic0_canister_version())
             }
@@ -831,6 +925,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "canister_cycle_balance", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.canister_cycle_balance");
                 charge_for_cpu(&mut caller, overhead::CANISTER_CYCLE_BALANCE)?;
                 with_system_api(&mut caller, |s| s.ic0_canister_cycle_balance())
             }
@@ -840,6 +935,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "canister_cycle_balance128", {
             move |mut caller: Caller<'_, StoreData>, dst: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.canister_cycle_balance128");
                 let dst: usize = dst.try_into().expect("Failed to convert I to usize");
                 charge_for_cpu(&mut caller, overhead::CANISTER_CYCLE_BALANCE128)?;
                 with_memory_and_system_api(&mut caller, |s, memory| {
@@ -853,6 +949,11 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "canister_liquid_cycle_balance128", {
             move |mut caller: Caller<'_, StoreData>, dst: I| {
+                count_system_api_call(
+                    &mut caller,
+                    profile_calls,
+                    "ic0.canister_liquid_cycle_balance128",
+                );
                 let dst: usize = dst.try_into().expect("Failed to convert I to usize");
                 charge_for_cpu(&mut caller, overhead::CANISTER_LIQUID_CYCLE_BALANCE128)?;
                 with_memory_and_system_api(&mut caller, |s, memory| {
@@ -866,6 +967,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_cycles_available", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_cycles_available");
                 charge_for_cpu(&mut caller, overhead::MSG_CYCLES_AVAILABLE)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_cycles_available())
             }
@@ -875,6 +977,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_cycles_available128", {
             move |mut caller: Caller<'_, StoreData>, dst: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_cycles_available128");
                 let dst: usize = dst.try_into().expect("Failed to convert I to usize");
                 charge_for_cpu(&mut caller, overhead::MSG_CYCLES_AVAILABLE128)?;
                 with_memory_and_system_api(&mut caller, |system_api, memory| {
@@ -888,6 +991,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_cycles_refunded", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_cycles_refunded");
                 charge_for_cpu(&mut caller, overhead::MSG_CYCLES_REFUNDED)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_cycles_refunded())
             }
@@ -897,6 +1001,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_cycles_refunded128", {
             move |mut caller: Caller<'_, StoreData>, dst: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_cycles_refunded128");
                 let dst: usize = dst.try_into().expect("Failed to convert I to usize");
                 charge_for_cpu(&mut caller, overhead::MSG_CYCLES_REFUNDED128)?;
                 with_memory_and_system_api(&mut caller, |system_api, memory| {
@@ -910,6 +1015,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_cycles_accept", {
             move |mut caller: Caller<'_, StoreData>, amount: u64| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_cycles_accept");
                 charge_for_cpu(&mut caller, overhead::MSG_CYCLES_ACCEPT)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_cycles_accept(amount))
             }
@@ -919,6 +1025,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_cycles_accept128", {
             move |mut caller: Caller<'_, StoreData>, amount_high: u64, amount_low: u64, dst: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_cycles_accept128");
                 let dst: usize = dst.try_into().expect("Failed to convert I to usize");
                 charge_for_cpu(&mut caller, overhead::MSG_CYCLES_ACCEPT128)?;
                 with_memory_and_system_api(&mut caller, |system_api, memory| {
@@ -952,6 +1059,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "subnet_self_size", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.subnet_self_size");
                 charge_for_cpu(&mut caller, overhead::SUBNET_SELF_SIZE)?;
                 with_system_api(&mut caller, |s| s.ic0_subnet_self_size()).and_then(|s| {
                     I::try_from(s).map_err(|e| {
@@ -965,6 +1073,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "subnet_self_copy", {
             move |mut caller: Caller<'_, StoreData>, dst: I, offset: I, size: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.subnet_self_copy");
                 let dst: usize = dst.try_into().expect("Failed to convert I to usize");
                 let offset: usize = offset.try_into().expect("Failed to convert I to usize");
                 let size: usize = size.try_into().expect("Failed to convert I to usize");
@@ -1051,9 +1160,18 @@ pub fn syscalls<
         })
         .unwrap();
 
+    linker
+        .func_wrap("__", "try_grow_gc_heap", {
+            move |mut caller: Caller<'_, StoreData>, additional_bytes: i64| {
+                with_system_api(&mut caller, |s| s.try_grow_gc_heap(additional_bytes as u64))
+            }
+        })
+        .unwrap();
+
     linker
         .func_wrap("ic0", "canister_status", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.canister_status");
                 charge_for_cpu(&mut caller, overhead::CANISTER_STATUS)?;
                 with_system_api(&mut caller, |s| s.ic0_canister_status())
             }
@@ -1063,6 +1181,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "root_key_size", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.root_key_size");
                 charge_for_cpu(&mut caller, overhead::ROOT_KEY_SIZE)?;
                 with_system_api(&mut caller, |s| s.ic0_root_key_size()).and_then(|s| {
                     I::try_from(s)
@@ -1075,6 +1194,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "root_key_copy", {
             move |mut caller: Caller<'_, StoreData>, dst: I, offset: I, size: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.root_key_copy");
                 let dst: usize = dst.try_into().expect("Failed to convert I to usize");
                 let offset: usize = offset.try_into().expect("Failed to convert I to usize");
                 let size: usize = size.try_into().expect("Failed to convert I to usize");
@@ -1090,6 +1210,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "certified_data_set", {
             move |mut caller: Caller<'_, StoreData>, src: I, size: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.certified_data_set");
                 let src: usize = src.try_into().expect("Failed to convert I to usize");
                 let size: usize = size.try_into().expect("Failed to convert I to usize");
                 charge_for_cpu_and_mem(&mut caller, overhead::CERTIFIED_DATA_SET, size)?;
@@ -1100,9 +1221,40 @@ pub fn syscalls<
         })
         .unwrap();
 
+    if feature_flags.query_cache_hints == FlagStatus::Enabled {
+        linker
+            .func_wrap("ic0", "query_cache_reply", {
+                move |mut caller: Caller<'_, StoreData>, ttl_nanos: u64, src: I, size: I| {
+                    count_system_api_call(&mut caller, profile_calls, "ic0.query_cache_reply");
+                    let src: usize = src.try_into().expect("Failed to convert I to usize");
+                    let size: usize = size.try_into().expect("Failed to convert I to usize");
+                    charge_for_cpu_and_mem(&mut caller, overhead::QUERY_CACHE_REPLY, size)?;
+                    with_memory_and_system_api(&mut caller, |system_api, memory| {
+                        system_api.ic0_query_cache_reply(ttl_nanos, src, size, memory)
+                    })
+                }
+            })
+            .unwrap();
+
+        linker
+            .func_wrap("ic0", "query_cache_invalidate", {
+                move |mut caller: Caller<'_, StoreData>, src: I, size: I| {
+                    count_system_api_call(&mut caller, profile_calls, "ic0.query_cache_invalidate");
+                    let src: usize = src.try_into().expect("Failed to convert I to usize");
+                    let size: usize = size.try_into().expect("Failed to convert I to usize");
+                    charge_for_cpu_and_mem(&mut caller, overhead::QUERY_CACHE_INVALIDATE, size)?;
+                    with_memory_and_system_api(&mut caller, |system_api, memory| {
+                        system_api.ic0_query_cache_invalidate(src, size, memory)
+                    })
+                }
+            })
+            .unwrap();
+    }
+
     linker
         .func_wrap("ic0", "data_certificate_present", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.data_certificate_present");
                 charge_for_cpu(&mut caller, overhead::DATA_CERTIFICATE_PRESENT)?;
                 with_system_api(&mut caller, |s| s.ic0_data_certificate_present())
             }
@@ -1112,6 +1264,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "data_certificate_size", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.data_certificate_size");
                 charge_for_cpu(&mut caller, overhead::DATA_CERTIFICATE_SIZE)?;
                 with_system_api(&mut caller, |s| s.ic0_data_certificate_size()).and_then(|x| {
                     I::try_from(x).map_err(|e| {
@@ -1125,6 +1278,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "is_controller", {
             move |mut caller: Caller<'_, StoreData>, src: I, size: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.is_controller");
                 let src: usize = src.try_into().expect("Failed to convert I to usize");
                 let size: usize = size.try_into().expect("Failed to convert I to usize");
                 charge_for_cpu_and_mem(&mut caller, overhead::IS_CONTROLLER, size)?;
@@ -1138,6 +1292,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "in_replicated_execution", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.in_replicated_execution");
                 charge_for_cpu(&mut caller, overhead::IN_REPLICATED_EXECUTION)?;
                 with_system_api(&mut caller, |s| s.ic0_in_replicated_execution())
             }
@@ -1147,6 +1302,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "data_certificate_copy", {
             move |mut caller: Caller<'_, StoreData>, dst: I, offset: I, size: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.data_certificate_copy");
                 let dst: usize = dst.try_into().expect("Failed to convert I to usize");
                 let offset: usize = offset.try_into().expect("Failed to convert I to usize");
                 let size: usize = size.try_into().expect("Failed to convert I to usize");
@@ -1162,6 +1318,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "mint_cycles128", {
             move |mut caller: Caller<'_, StoreData>, amount_high: u64, amount_low: u64, dst: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.mint_cycles128");
                 with_memory_and_system_api(&mut caller, |s, memory| {
                     let dst: usize = dst.try_into().expect("Failed to convert I to usize");
                     s.ic0_mint_cycles128(Cycles::from_parts(amount_high, amount_low), dst, memory)
@@ -1174,6 +1331,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "cycles_burn128", {
             move |mut caller: Caller<'_, StoreData>, amount_high: u64, amount_low: u64, dst: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.cycles_burn128");
                 charge_for_cpu(&mut caller, overhead::CYCLES_BURN128)?;
                 with_memory_and_system_api(&mut caller, |s, memory| {
                     let dst: usize = dst.try_into().expect("Failed to convert I to usize");
@@ -1190,6 +1348,7 @@ pub fn syscalls<
                   method_name_size: u64,
                   payload_size: u64,
                   dst: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.cost_call");
                 charge_for_cpu(&mut caller, overhead::COST_CALL)?;
                 with_memory_and_system_api(&mut caller, |s, memory| {
                     let dst: usize = dst.try_into().expect("Failed to convert I to usize");
@@ -1203,6 +1362,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "cost_create_canister", {
             move |mut caller: Caller<'_, StoreData>, dst: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.cost_create_canister");
                 charge_for_cpu(&mut caller, overhead::COST_CREATE_CANISTER)?;
                 with_memory_and_system_api(&mut caller, |s, memory| {
                     let dst: usize = dst.try_into().expect("Failed to convert I to usize");
@@ -1219,6 +1379,7 @@ pub fn syscalls<
                   request_size: u64,
                   max_res_bytes: u64,
                   dst: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.cost_http_request");
                 charge_for_cpu(&mut caller, overhead::COST_HTTP_REQUEST)?;
                 with_memory_and_system_api(&mut caller, |s, memory| {
                     let dst: usize = dst.try_into().expect("Failed to convert I to usize");
@@ -1232,6 +1393,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "cost_http_request_v2", {
             move |mut caller: Caller<'_, StoreData>, params_src: I, params_size: I, dst: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.cost_http_request_v2");
                 charge_for_cpu(&mut caller, overhead::COST_HTTP_REQUEST_V2)?;
                 with_memory_and_system_api(&mut caller, |s, memory| {
                     let params_src: usize =
@@ -1250,6 +1412,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "cost_sign_with_ecdsa", {
             move |mut caller: Caller<'_, StoreData>, src: I, size: I, curve: u32, dst: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.cost_sign_with_ecdsa");
                 let src: usize = src.try_into().expect("Failed to convert I to usize");
                 let size: usize = size.try_into().expect("Failed to convert I to usize");
                 charge_for_cpu_and_mem(&mut caller, overhead::COST_ECDSA, size)?;
@@ -1265,6 +1428,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "cost_sign_with_schnorr", {
             move |mut caller: Caller<'_, StoreData>, src: I, size: I, algorithm: u32, dst: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.cost_sign_with_schnorr");
                 let src: usize = src.try_into().expect("Failed to convert I to usize");
                 let size: usize = size.try_into().expect("Failed to convert I to usize");
                 charge_for_cpu_and_mem(&mut caller, overhead::COST_SCHNORR, size)?;
@@ -1280,6 +1444,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "cost_vetkd_derive_key", {
             move |mut caller: Caller<'_, StoreData>, src: I, size: I, curve: u32, dst: I| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.cost_vetkd_derive_key");
                 let src: usize = src.try_into().expect("Failed to convert I to usize");
                 let size: usize = size.try_into().expect("Failed to convert I to usize");
                 charge_for_cpu_and_mem(&mut caller, overhead::COST_VETKD, size)?;
@@ -1295,6 +1460,11 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "call_with_best_effort_response", {
             move |mut caller: Caller<'_, StoreData>, timeout_seconds: u32| {
+                count_system_api_call(
+                    &mut caller,
+                    profile_calls,
+                    "ic0.call_with_best_effort_response",
+                );
                 charge_for_cpu(&mut caller, overhead::CALL_WITH_BEST_EFFORT_RESPONSE)?;
                 with_system_api(&mut caller, |system_api| {
                     system_api.ic0_call_with_best_effort_response(timeout_seconds)
@@ -1306,6 +1476,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "msg_deadline", {
             move |mut caller: Caller<'_, StoreData>| {
+                count_system_api_call(&mut caller, profile_calls, "ic0.msg_deadline");
                 charge_for_cpu(&mut caller, overhead::MSG_DEADLINE)?;
                 with_system_api(&mut caller, |system_api| system_api.ic0_msg_deadline())
             }
@@ -1321,6 +1492,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "stable_size", {
             move |mut caller: Caller<'_, StoreData>| -> Result<u32, _> {
+                count_system_api_call(&mut caller, profile_calls, "ic0.stable_size");
                 Err(process_err(
                     &mut caller,
                     HypervisorError::CalledTrap {
@@ -1335,6 +1507,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "stable_grow", {
             move |mut caller: Caller<'_, StoreData>, _pages: u32| -> Result<u32, _> {
+                count_system_api_call(&mut caller, profile_calls, "ic0.stable_grow");
                 Err(process_err(
                     &mut caller,
                     HypervisorError::CalledTrap {
@@ -1353,6 +1526,7 @@ pub fn syscalls<
                   _offset: u32,
                   _size: u32|
                   -> Result<(), _> {
+                count_system_api_call(&mut caller, profile_calls, "ic0.stable_read");
                 Err(process_err(
                     &mut caller,
                     HypervisorError::CalledTrap {
@@ -1371,6 +1545,7 @@ pub fn syscalls<
                   _src: u32,
                   _size: u32|
                   -> Result<(), _> {
+                count_system_api_call(&mut caller, profile_calls, "ic0.stable_write");
                 Err(process_err(
                     &mut caller,
                     HypervisorError::CalledTrap {
@@ -1385,6 +1560,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "stable64_size", {
             move |mut caller: Caller<'_, StoreData>| -> Result<u64, _> {
+                count_system_api_call(&mut caller, profile_calls, "ic0.stable64_size");
                 Err(process_err(
                     &mut caller,
                     HypervisorError::CalledTrap {
@@ -1399,6 +1575,7 @@ pub fn syscalls<
     linker
         .func_wrap("ic0", "stable64_grow", {
             move |mut caller: Caller<'_, StoreData>, _pages: u64| -> Result<u64, _> {
+                count_system_api_call(&mut caller, profile_calls, "ic0.stable64_grow");
                 Err(process_err(
                     &mut caller,
                     HypervisorError::CalledTrap {
@@ -1417,6 +1594,7 @@ pub fn syscalls<
                   _offset: u64,
                   _size: u64|
                   -> Result<(), _> {
+                count_system_api_call(&mut caller, profile_calls, "ic0.stable64_read");
                 Err(process_err(
                     &mut caller,
                     HypervisorError::CalledTrap {
@@ -1435,6 +1613,7 @@ pub fn syscalls<
                   _src: u64,
                   _size: u64|
                   -> Result<(), _> {
+                count_system_api_call(&mut caller, profile_calls, "ic0.stable64_write");
                 Err(process_err(
                     &mut caller,
                     HypervisorError::CalledTrap {
@@ -1,6 +1,6 @@
 use candid::{CandidType, DecoderConfig, decode_one_with_config};
 use ic_base_types::{InternalAddress, PrincipalIdBlobParseError};
-use ic_config::embedders::{Config as EmbeddersConfig, StableMemoryPageLimit};
+use ic_config::embedders::{Config as EmbeddersConfig, MAX_GC_HEAP_SIZE, StableMemoryPageLimit};
 use ic_config::flag_status::FlagStatus;
 use ic_cycles_account_manager::ResourceSaturation;
 use ic_error_types::RejectCode;
@@ -8,16 +8,18 @@ use ic_interfaces::execution_environment::{
     ExecutionMode,
     HypervisorError::{self, *},
     HypervisorResult, MessageMemoryUsage, OutOfInstructionsHandler, PerformanceCounterType,
-    StableGrowOutcome, StableMemoryApi, SubnetAvailableMemory, SystemApi, SystemApiCallCounters,
+    QueryCacheHint, StableGrowOutcome, StableMemoryApi, SubnetAvailableMemory, SystemApi,
+    SystemApiCallCounters,
     TrapCode::{self, CyclesAmountTooBigFor64Bit},
 };
 use ic_logger::{ReplicaLogger, error};
 use ic_management_canister_types_private::{
-    EcdsaCurve, EcdsaKeyId, IC_00, MasterPublicKeyId, SchnorrAlgorithm, SchnorrKeyId, VetKdCurve,
-    VetKdKeyId,
+    CanisterLogField, CanisterLogSeverity, EcdsaCurve, EcdsaKeyId, IC_00, MasterPublicKeyId,
+    SchnorrAlgorithm, SchnorrKeyId, VetKdCurve, VetKdKeyId,
 };
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::canister_state::execution_state::WasmExecutionMode;
+use ic_replicated_state::canister_state::system_state::MAX_QUERY_CACHE_TAG_SIZE;
 use ic_replicated_state::{
     Memory, NumWasmPages, canister_state::WASM_PAGE_SIZE_IN_BYTES, memory_usage_of_request,
 };
@@ -90,6 +92,59 @@ macro_rules! trace_syscall {
     }}
 }
 
+/// Decodes the fields passed to `ic0.debug_print_structured`: a sequence of
+/// key/value pairs where both the key and the value are UTF-8 strings prefixed
+/// with their length in bytes (as a little-endian `u32`).
+fn decode_log_fields(mut bytes: &[u8]) -> Result<Vec<CanisterLogField>, String> {
+    fn decode_string(bytes: &mut &[u8]) -> Result<String, String> {
+        let (len, rest) = bytes
+            .split_first_chunk::<4>()
+            .ok_or_else(|| "malformed fields".to_string())?;
+        let len = u32::from_le_bytes(*len) as usize;
+        if rest.len() < len {
+            return Err("malformed fields".to_string());
+        }
+        let (string, rest) = rest.split_at(len);
+        *bytes = rest;
+        String::from_utf8(string.to_vec()).map_err(|_| "field is not valid UTF-8".to_string())
+    }
+
+    let mut fields = vec![];
+    while !bytes.is_empty() {
+        let key = decode_string(&mut bytes)?;
+        let value = decode_string(&mut bytes)?;
+        fields.push(CanisterLogField { key, value });
+    }
+    Ok(fields)
+}
+
+/// Reads a tag passed to `ic0.query_cache_reply` or `ic0.query_cache_invalidate`
+/// from the heap.
+fn query_cache_tag(
+    method_name: &str,
+    src: usize,
+    size: usize,
+    heap: &[u8],
+) -> HypervisorResult<Vec<u8>> {
+    if size > MAX_QUERY_CACHE_TAG_SIZE {
+        return Err(UserContractViolation {
+            error: format!(
+                "{method_name} failed because the tag must be no larger than \
+                {MAX_QUERY_CACHE_TAG_SIZE} bytes. Found {size} bytes."
+            ),
+            suggestion: "Try using the hash of the tag instead.".to_string(),
+            doc_link: "".to_string(),
+        });
+    }
+    valid_subslice(
+        method_name,
+        InternalAddress::new(src),
+        InternalAddress::new(size),
+        heap,
+    )
+    .map(|tag| tag.to_vec())
+}
+
 // This helper is used in system calls for displaying a summary hash of a heap region.
 #[inline]
 fn summarize(heap: &[u8], start: usize, size: usize) -> u64 {
@@ -898,6 +953,15 @@ struct MemoryUsage {
 
     /// The memory allocation of the canister.
     memory_allocation: MemoryAllocation,
+
+    /// The estimated size of the GC heap of this message execution. The GC
+    /// heap is discarded after the execution, but while it exists it is
+    /// included in `current_usage`.
+    gc_heap_usage: NumBytes,
+
+    /// GC heap memory allocated during this message execution, i.e., the part
+    /// of `allocated_execution_memory` that is released after the execution.
+    allocated_gc_heap_memory: NumBytes,
 }
 
 impl MemoryUsage {
@@ -920,6 +984,8 @@ impl MemoryUsage {
             allocated_execution_memory: NumBytes::new(0),
             allocated_message_memory: MessageMemoryUsage::ZERO,
             memory_allocation,
+            gc_heap_usage: NumBytes::new(0),
+            allocated_gc_heap_memory: NumBytes::new(0),
         }
     }
 
@@ -1038,6 +1104,68 @@ impl MemoryUsage {
         Ok(())
     }
 
+    /// Tries to allocate the requested amount of GC heap memory.
+    ///
+    /// Similar to `allocate_execution_memory`, but no storage cycles are
+    /// reserved because the GC heap doesn't outlive the message execution.
+    /// The null collector never frees objects, so the sum of the allocations
+    /// is the live size of the GC heap.
+    ///
+    /// Returns `Err(HypervisorError::OutOfMemory)` and leaves `self` unchanged
+    /// if the GC heap would exceed `MAX_GC_HEAP_SIZE` or the subnet memory
+    /// limit would be exceeded.
+    ///
+    /// Returns `Err(HypervisorError::InsufficientCyclesInMemoryGrow)` and
+    /// leaves `self` unchanged if freezing threshold check is needed for the
+    /// given API type and canister would be frozen after the allocation.
+    fn allocate_gc_heap_memory(
+        &mut self,
+        usage_growth_bytes: NumBytes,
+        api_type: &ApiType,
+        sandbox_safe_system_state: &SandboxSafeSystemState,
+    ) -> HypervisorResult<()> {
+        if self
+            .gc_heap_usage
+            .get()
+            .saturating_add(usage_growth_bytes.get())
+            > MAX_GC_HEAP_SIZE.get()
+        {
+            return Err(HypervisorError::OutOfMemory);
+        }
+        let (new_usage, overflow) = self
+            .current_usage
+            .get()
+            .overflowing_add(usage_growth_bytes.get());
+        if overflow {
+            return Err(HypervisorError::OutOfMemory);
+        }
+
+        let old_allocated_bytes = self.memory_allocation.allocated_bytes(self.current_usage);
+        let new_allocated_bytes = self
+            .memory_allocation
+            .allocated_bytes(NumBytes::new(new_usage));
+        debug_assert!(old_allocated_bytes <= new_allocated_bytes);
+        let allocated_bytes = new_allocated_bytes - old_allocated_bytes; // subtraction on `NumBytes` is already saturating
+
+        sandbox_safe_system_state.check_freezing_threshold_for_memory_grow(
+            api_type,
+            self.current_message_usage,
+            old_allocated_bytes,
+            new_allocated_bytes,
+        )?;
+
+        if api_type.should_update_available_memory_and_reserved_cycles() {
+            self.subnet_available_memory
+                .try_decrement(allocated_bytes, NumBytes::new(0), NumBytes::new(0))
+                .map_err(|_err| HypervisorError::OutOfMemory)?;
+        }
+
+        self.current_usage = NumBytes::new(new_usage);
+        self.allocated_execution_memory += allocated_bytes;
+        self.allocated_gc_heap_memory += allocated_bytes;
+        add_memory(&mut self.gc_heap_usage, usage_growth_bytes)
+    }
+
     fn add_execution_memory(
         &mut self,
         execution_bytes: NumBytes,
@@ -1346,7 +1474,8 @@ impl SystemApiImpl {
     }
 
     pub fn get_current_memory_usage(&self) -> NumBytes {
-        self.memory_usage.current_usage
+        // The GC heap is discarded after the execution.
+        self.memory_usage.current_usage - self.memory_usage.gc_heap_usage
     }
 
     pub fn get_current_message_memory_usage(&self) -> MessageMemoryUsage {
@@ -1357,7 +1486,7 @@ impl SystemApiImpl {
     /// wasm/stable memory usage growth beyond
     /// the memory allocation of the canister.
     pub fn get_allocated_bytes(&self) -> NumBytes {
-        self.memory_usage.allocated_execution_memory
+        self.memory_usage.allocated_execution_memory - self.memory_usage.allocated_gc_heap_memory
     }
 
     /// Bytes used by or reserved for for guaranteed response messages.
@@ -1669,6 +1798,7 @@ impl SystemApiImpl {
             ApiType::InspectMessage { .. } | ApiType::NonReplicatedQuery { .. } => {
                 SystemStateModifications {
                     new_certified_data: None,
+                    query_cache_invalidations: vec![],
                     callback_updates: vec![],
                     cycles_balance_change: CyclesBalanceChange::zero(),
                     reserved_cycles: Cycles::zero(),
@@ -1692,6 +1822,7 @@ impl SystemApiImpl {
             | ApiType::CompositeCleanup { .. } => match &self.execution_error {
                 Some(_) => SystemStateModifications {
                     new_certified_data: None,
+                    query_cache_invalidations: vec![],
                     callback_updates: vec![],
                     cycles_balance_change: CyclesBalanceChange::zero(),
                     reserved_cycles: Cycles::zero(),
@@ -1706,6 +1837,7 @@ impl SystemApiImpl {
                 },
                 None => SystemStateModifications {
                     new_certified_data: None,
+                    query_cache_invalidations: vec![],
                     callback_updates: system_state_modifications.callback_updates,
                     cycles_balance_change: CyclesBalanceChange::zero(),
                     reserved_cycles: Cycles::zero(),
@@ -1727,6 +1859,7 @@ impl SystemApiImpl {
                     self.add_canister_log_for_trap(err, time, &mut system_state_modifications);
                     SystemStateModifications {
                         new_certified_data: None,
+                        query_cache_invalidations: vec![],
                         callback_updates: vec![],
                         cycles_balance_change: CyclesBalanceChange::zero(),
                         reserved_cycles: Cycles::zero(),
@@ -1742,6 +1875,7 @@ impl SystemApiImpl {
                 }
                 None => SystemStateModifications {
                     new_certified_data: None,
+                    query_cache_invalidations: vec![],
                     callback_updates: vec![],
                     cycles_balance_change: system_state_modifications.cycles_balance_change,
                     reserved_cycles: Cycles::zero(),
@@ -1769,6 +1903,7 @@ impl SystemApiImpl {
                     self.add_canister_log_for_trap(err, time, &mut system_state_modifications);
                     SystemStateModifications {
                         new_certified_data: None,
+                        query_cache_invalidations: vec![],
                         callback_updates: vec![],
                         cycles_balance_change: CyclesBalanceChange::zero(),
                         reserved_cycles: Cycles::zero(),
@@ -1800,6 +1935,7 @@ impl SystemApiImpl {
                     self.add_canister_log_for_trap(err, time, &mut system_state_modifications);
                     SystemStateModifications {
                         new_certified_data: None,
+                        query_cache_invalidations: vec![],
                         callback_updates: vec![],
                         cycles_balance_change: CyclesBalanceChange::zero(),
                         reserved_cycles: Cycles::zero(),
@@ -1877,6 +2013,18 @@ impl SystemApiImpl {
         self.call_counters.clone()
     }
 
+    /// Counts a call of the given System API function (e.g., `ic0.time`).
+    /// Only called by the linker if System API call profiling is enabled.
+    pub fn count_call(&mut self, function: &str) {
+        let calls_by_function = &mut self.call_counters.calls_by_function;
+        match calls_by_function.get_mut(function) {
+            Some(count) => *count = count.saturating_add(1),
+            None => {
+                calls_by_function.insert(function.to_string(), 1);
+            }
+        }
+    }
+
     /// Appends the specified bytes on the heap as a string to the canister's logs.
     pub fn save_log_message(&mut self, src: usize, size: usize, heap: &[u8]) {
         self.sandbox_safe_system_state.append_canister_log(
@@ -1896,6 +2044,47 @@ impl SystemApiImpl {
         );
     }
 
+    /// Appends a structured record to the canister's logs. The message and the
+    /// encoded fields (see `decode_log_fields`) are read from the heap.
+    pub fn save_structured_log_message(
+        &mut self,
+        severity: u32,
+        src: usize,
+        size: usize,
+        fields_src: usize,
+        fields_size: usize,
+        heap: &[u8],
+    ) {
+        let record = CanisterLogSeverity::try_from(severity).and_then(|severity| {
+            let content = valid_subslice(
+                "save_structured_log_message",
+                InternalAddress::new(src),
+                InternalAddress::new(size),
+                heap,
+            )
+            .map_err(|_| "message out of memory bounds".to_string())?;
+            let fields = valid_subslice(
+                "save_structured_log_message",
+                InternalAddress::new(fields_src),
+                InternalAddress::new(fields_size),
+                heap,
+            )
+            .map_err(|_| "fields out of memory bounds".to_string())?;
+            Ok((severity, content.to_vec(), decode_log_fields(fields)?))
+        });
+        match record {
+            Ok((severity, content, fields)) => self
+                .sandbox_safe_system_state
+                .append_structured_canister_log(self.api_type.time(), severity, content, fields),
+            // Do not trap here!
+            // If the arguments are invalid, ignore them and log the error message.
+            Err(err) => self.sandbox_safe_system_state.append_canister_log(
+                self.api_type.time(),
+                format!("(debug_print_structured: {err})").into_bytes(),
+            ),
+        }
+    }
+
     /// Takes collected canister log records.
     pub fn take_canister_log(&mut self) -> CanisterLog {
         self.sandbox_safe_system_state.take_canister_log()
@@ -3366,6 +3555,16 @@ impl SystemApi for SystemApiImpl {
         result
     }
 
+    fn try_grow_gc_heap(&mut self, additional_bytes: u64) -> HypervisorResult<()> {
+        let result = self.memory_usage.allocate_gc_heap_memory(
+            NumBytes::new(additional_bytes),
+            &self.api_type,
+            &self.sandbox_safe_system_state,
+        );
+        trace_syscall!(self, TryGrowGcHeap, result, additional_bytes);
+        result
+    }
+
     fn try_grow_stable_memory(
         &mut self,
         current_size: u64,
@@ -3884,6 +4083,95 @@ impl SystemApi for SystemApiImpl {
         result
     }
 
+    fn ic0_query_cache_reply(
+        &mut self,
+        ttl_nanos: u64,
+        tag_src: usize,
+        tag_size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<()> {
+        let result = match &self.api_type {
+            ApiType::Start { .. }
+            | ApiType::Init { .. }
+            | ApiType::SystemTask { .. }
+            | ApiType::Update { .. }
+            | ApiType::Cleanup { .. }
+            | ApiType::CompositeCleanup { .. }
+            | ApiType::InspectMessage { .. }
+            | ApiType::ReplyCallback { .. }
+            | ApiType::RejectCallback { .. }
+            | ApiType::CompositeReplyCallback { .. }
+            | ApiType::CompositeRejectCallback { .. }
+            | ApiType::PreUpgrade { .. } => Err(self.error_for("ic0_query_cache_reply")),
+            // Replies of replicated queries are never cached, so the hint is
+            // validated but has no effect.
+            ApiType::ReplicatedQuery { .. } => {
+                query_cache_tag("ic0_query_cache_reply", tag_src, tag_size, heap).map(|_| ())
+            }
+            ApiType::NonReplicatedQuery { .. } | ApiType::CompositeQuery { .. } => {
+                query_cache_tag("ic0_query_cache_reply", tag_src, tag_size, heap).map(|tag| {
+                    self.call_counters.query_cache_hint = Some(QueryCacheHint {
+                        ttl: Duration::from_nanos(ttl_nanos),
+                        tag: (!tag.is_empty()).then_some(tag),
+                    });
+                })
+            }
+        };
+        trace_syscall!(
+            self,
+            QueryCacheReply,
+            result,
+            ttl_nanos,
+            tag_src,
+            tag_size,
+            summarize(heap, tag_src, tag_size)
+        );
+        result
+    }
+
+    fn ic0_query_cache_invalidate(
+        &mut self,
+        tag_src: usize,
+        tag_size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<()> {
+        let result = match &self.api_type {
+            ApiType::Start { .. }
+            | ApiType::ReplicatedQuery { .. }
+            | ApiType::Cleanup { .. }
+            | ApiType::CompositeCleanup { .. }
+            | ApiType::NonReplicatedQuery { .. }
+            | ApiType::CompositeQuery { .. }
+            | ApiType::InspectMessage { .. }
+            | ApiType::CompositeReplyCallback { .. }
+            | ApiType::CompositeRejectCallback { .. } => {
+                Err(self.error_for("ic0_query_cache_invalidate"))
+            }
+            ApiType::Init { .. }
+            | ApiType::SystemTask { .. }
+            | ApiType::Update { .. }
+            | ApiType::ReplyCallback { .. }
+            | ApiType::RejectCallback { .. }
+            | ApiType::PreUpgrade { .. } => {
+                query_cache_tag("ic0_query_cache_invalidate", tag_src, tag_size, heap).map(|tag| {
+                    self.sandbox_safe_system_state
+                        .system_state_modifications
+                        .query_cache_invalidations
+                        .push(tag);
+                })
+            }
+        };
+        trace_syscall!(
+            self,
+            QueryCacheInvalidate,
+            result,
+            tag_src,
+            tag_size,
+            summarize(heap, tag_src, tag_size)
+        );
+        result
+    }
+
     fn ic0_canister_status(&self) -> HypervisorResult<u32> {
         let result = match &self.api_type {
             ApiType::Start { .. } => Err(self.error_for("ic0_canister_status")),
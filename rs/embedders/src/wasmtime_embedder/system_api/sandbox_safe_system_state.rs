@@ -13,10 +13,10 @@ use ic_interfaces::execution_environment::{HypervisorError, HypervisorResult, Me
 use ic_limits::{LOG_CANISTER_OPERATION_CYCLES_THRESHOLD, SMALL_APP_SUBNET_MAX_SIZE};
 use ic_logger::{ReplicaLogger, info};
 use ic_management_canister_types_private::{
-    CanisterStatusType, CreateCanisterArgs, IC_00, InstallChunkedCodeArgs, InstallCodeArgsV2,
-    LoadCanisterSnapshotArgs, MasterPublicKeyId, Method as Ic00Method, Payload,
-    ProvisionalCreateCanisterWithCyclesArgs, RenameCanisterArgs, UninstallCodeArgs,
-    UpdateSettingsArgs,
+    CanisterLogField, CanisterLogSeverity, CanisterStatusType, CreateCanisterArgs, IC_00,
+    InstallChunkedCodeArgs, InstallCodeArgsV2, LoadCanisterSnapshotArgs, MasterPublicKeyId,
+    Method as Ic00Method, Payload, ProvisionalCreateCanisterWithCyclesArgs, RenameCanisterArgs,
+    UninstallCodeArgs, UpdateSettingsArgs,
 };
 use ic_nns_constants::CYCLES_MINTING_CANISTER_ID;
 use ic_registry_subnet_type::SubnetType;
@@ -69,6 +69,7 @@ pub enum CallbackUpdate {
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
 pub struct SystemStateModifications {
     pub(super) new_certified_data: Option<Vec<u8>>,
+    pub(super) query_cache_invalidations: Vec<Vec<u8>>,
     // pub for testing
     pub callback_updates: Vec<CallbackUpdate>,
     pub(super) cycles_balance_change: CyclesBalanceChange,
@@ -90,6 +91,7 @@ impl Default for SystemStateModifications {
     fn default() -> Self {
         Self {
             new_certified_data: None,
+            query_cache_invalidations: vec![],
             callback_updates: vec![],
             cycles_balance_change: CyclesBalanceChange::zero(),
             reserved_cycles: Cycles::zero(),
@@ -276,6 +278,7 @@ impl SystemStateModifications {
             | Ok(Ic00Method::ProvisionalTopUpCanister)
             | Ok(Ic00Method::BitcoinSendTransactionInternal)
             | Ok(Ic00Method::BitcoinGetSuccessors)
+            | Ok(Ic00Method::BitcoinGetBlockFilters)
             | Ok(Ic00Method::BitcoinGetBalance)
             | Ok(Ic00Method::BitcoinGetUtxos)
             | Ok(Ic00Method::BitcoinGetBlockHeaders)
@@ -508,6 +511,11 @@ impl SystemStateModifications {
             system_state.certified_data.clone_from(certified_data);
         }
 
+        // Invalidate the query cache tags.
+        for tag in &self.query_cache_invalidations {
+            system_state.query_cache_tags.invalidate(tag);
+        }
+
         // Update canister global timer
         if let Some(new_global_timer) = self.new_global_timer {
             system_state.global_timer = new_global_timer;
@@ -1386,6 +1394,19 @@ impl SandboxSafeSystemState {
             .add_record(time.as_nanos_since_unix_epoch(), content);
     }
 
+    /// Appends a structured log record to the canister log.
+    pub fn append_structured_canister_log(
+        &mut self,
+        time: &Time,
+        severity: CanisterLogSeverity,
+        content: Vec<u8>,
+        fields: Vec<CanisterLogField>,
+    ) {
+        self.system_state_modifications
+            .canister_log
+            .add_structured_record(time.as_nanos_since_unix_epoch(), severity, content, fields);
+    }
+
     /// Takes collected canister log records.
     pub fn take_canister_log(&mut self) -> CanisterLog {
         self.system_state_modifications.canister_log.take()
@@ -49,6 +49,7 @@ pub mod overhead {
     pub const DATA_CERTIFICATE_PRESENT: NumInstructions = NumInstructions::new(500);
     pub const DATA_CERTIFICATE_SIZE: NumInstructions = NumInstructions::new(500);
     pub const DEBUG_PRINT: NumInstructions = NumInstructions::new(100);
+    pub const DEBUG_PRINT_STRUCTURED: NumInstructions = NumInstructions::new(100);
     pub const GLOBAL_TIMER_SET: NumInstructions = NumInstructions::new(500);
     pub const IS_CONTROLLER: NumInstructions = NumInstructions::new(1_000);
     pub const IN_REPLICATED_EXECUTION: NumInstructions = NumInstructions::new(500);
@@ -72,6 +73,8 @@ pub mod overhead {
     pub const MSG_REPLY_DATA_APPEND: NumInstructions = NumInstructions::new(500);
     pub const MSG_REPLY: NumInstructions = NumInstructions::new(500);
     pub const PERFORMANCE_COUNTER: NumInstructions = NumInstructions::new(200);
+    pub const QUERY_CACHE_INVALIDATE: NumInstructions = NumInstructions::new(500);
+    pub const QUERY_CACHE_REPLY: NumInstructions = NumInstructions::new(500);
     pub const SUBNET_SELF_SIZE: NumInstructions = NumInstructions::new(500);
     pub const SUBNET_SELF_COPY: NumInstructions = NumInstructions::new(500);
     pub const STABLE_GROW: NumInstructions = NumInstructions::new(500);
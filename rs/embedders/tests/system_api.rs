@@ -1,5 +1,8 @@
 use ic_base_types::{NumBytes, NumSeconds, PrincipalIdBlobParseError};
-use ic_config::{embedders::Config as EmbeddersConfig, subnet_config::SchedulerConfig};
+use ic_config::{
+    embedders::{Config as EmbeddersConfig, MAX_GC_HEAP_SIZE},
+    subnet_config::SchedulerConfig,
+};
 use ic_cycles_account_manager::CyclesAccountManager;
 use ic_embedders::wasmtime_embedder::system_api::{
     ApiType, DefaultOutOfInstructionsHandler, MAX_ENV_VAR_NAME_SIZE, SystemApiImpl,
@@ -234,6 +237,8 @@ fn is_supported(api_type: SystemApiCallId, context: &str) -> bool {
         SystemApiCallId::Time => vec!["*"],
         SystemApiCallId::GlobalTimerSet => vec!["I", "G", "U", "Ry", "Rt", "C", "T"],
         SystemApiCallId::PerformanceCounter => vec!["*", "s"],
+        SystemApiCallId::QueryCacheReply => vec!["RQ", "NRQ", "CQ"],
+        SystemApiCallId::QueryCacheInvalidate => vec!["I", "G", "U", "Ry", "Rt", "T"],
         SystemApiCallId::IsController => vec!["*", "s"],
         SystemApiCallId::InReplicatedExecution => vec!["*", "s"],
         SystemApiCallId::CostCall => vec!["*", "s"],
@@ -720,6 +725,26 @@ fn api_availability_test(
                 context,
             );
         }
+        SystemApiCallId::QueryCacheReply => {
+            assert_api_availability(
+                |mut api| api.ic0_query_cache_reply(0, 0, 0, &[42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::QueryCacheInvalidate => {
+            assert_api_availability(
+                |mut api| api.ic0_query_cache_invalidate(0, 0, &[42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
         SystemApiCallId::CanisterStatus => {
             assert_api_availability(
                 |api| api.ic0_canister_status(),
@@ -863,9 +888,10 @@ fn api_availability_test(
         | SystemApiCallId::Stable64Read
         | SystemApiCallId::Stable64Size
         | SystemApiCallId::Stable64Write => {}
-        // OutOfInstructions and TryGrowWasmMemory are private
+        // OutOfInstructions, TryGrowWasmMemory, and TryGrowGcHeap are private
         SystemApiCallId::OutOfInstructions => {}
         SystemApiCallId::TryGrowWasmMemory => {}
+        SystemApiCallId::TryGrowGcHeap => {}
         // These are available in all contexts
         SystemApiCallId::CostCall => {}
         SystemApiCallId::CostCreateCanister => {}
@@ -1486,6 +1512,101 @@ fn growing_wasm_memory_updates_subnet_available_memory() {
     );
 }
 
+#[test]
+fn growing_gc_heap_updates_subnet_available_memory() {
+    let wasm_page_size = 64 << 10;
+    let subnet_available_memory_bytes = 2 * wasm_page_size;
+    let subnet_available_memory =
+        SubnetAvailableMemory::new_for_testing(subnet_available_memory_bytes, 0, 0);
+    let system_state = SystemStateBuilder::default().build();
+    let cycles_account_manager = CyclesAccountManagerBuilder::new().build();
+    let api_type = ApiTypeBuilder::build_update_api();
+    let execution_parameters = execution_parameters(api_type.execution_mode());
+    let sandbox_safe_system_state = SandboxSafeSystemState::new_for_testing(
+        &system_state,
+        cycles_account_manager,
+        &NetworkTopology::default(),
+        SchedulerConfig::application_subnet().dirty_page_overhead,
+        execution_parameters.compute_allocation,
+        execution_parameters.canister_guaranteed_callback_quota,
+        Default::default(),
+        api_type.caller(),
+        api_type.call_context_id(),
+        CanisterCyclesCostSchedule::Normal,
+    );
+    let mut api = SystemApiImpl::new(
+        api_type,
+        sandbox_safe_system_state,
+        CANISTER_CURRENT_MEMORY_USAGE,
+        CANISTER_CURRENT_MESSAGE_MEMORY_USAGE,
+        execution_parameters,
+        subnet_available_memory,
+        &EmbeddersConfig::default(),
+        Memory::new_for_testing(),
+        NumWasmPages::from(0),
+        Rc::new(DefaultOutOfInstructionsHandler::default()),
+        no_op_logger(),
+    );
+
+    api.try_grow_gc_heap(wasm_page_size as u64).unwrap();
+    // The GC heap is discarded after the execution, so it is neither reported
+    // as allocated nor included in the memory usage after the execution.
+    assert_eq!(api.get_allocated_bytes().get(), 0);
+    assert_eq!(
+        api.get_current_memory_usage(),
+        CANISTER_CURRENT_MEMORY_USAGE
+    );
+
+    // The GC heap still counts against the subnet available memory during the
+    // execution.
+    assert_eq!(
+        api.try_grow_gc_heap(2 * wasm_page_size as u64),
+        Err(HypervisorError::OutOfMemory)
+    );
+    api.try_grow_wasm_memory(0, 2).unwrap_err();
+    api.try_grow_wasm_memory(0, 1).unwrap();
+    assert_eq!(api.get_allocated_bytes().get() as i64, wasm_page_size);
+}
+
+#[test]
+fn gc_heap_is_limited_per_message() {
+    let subnet_available_memory = SubnetAvailableMemory::new_for_testing(20 * GIB, 0, 0);
+    let system_state = SystemStateBuilder::default()
+        .initial_cycles(Cycles::from(10_000_000_000_000_000u128))
+        .build();
+    let cycles_account_manager = CyclesAccountManagerBuilder::new().build();
+    let api_type = ApiTypeBuilder::build_update_api();
+    let execution_parameters = execution_parameters(api_type.execution_mode());
+    let sandbox_safe_system_state = SandboxSafeSystemState::new_for_testing(
+        &system_state,
+        cycles_account_manager,
+        &NetworkTopology::default(),
+        SchedulerConfig::application_subnet().dirty_page_overhead,
+        execution_parameters.compute_allocation,
+        execution_parameters.canister_guaranteed_callback_quota,
+        Default::default(),
+        api_type.caller(),
+        api_type.call_context_id(),
+        CanisterCyclesCostSchedule::Normal,
+    );
+    let mut api = SystemApiImpl::new(
+        api_type,
+        sandbox_safe_system_state,
+        CANISTER_CURRENT_MEMORY_USAGE,
+        CANISTER_CURRENT_MESSAGE_MEMORY_USAGE,
+        execution_parameters,
+        subnet_available_memory,
+        &EmbeddersConfig::default(),
+        Memory::new_for_testing(),
+        NumWasmPages::from(0),
+        Rc::new(DefaultOutOfInstructionsHandler::default()),
+        no_op_logger(),
+    );
+
+    api.try_grow_gc_heap(MAX_GC_HEAP_SIZE.get()).unwrap();
+    assert_eq!(api.try_grow_gc_heap(1), Err(HypervisorError::OutOfMemory));
+}
+
 const GIB: i64 = 1 << 30;
 
 fn helper_test_on_low_wasm_memory(
@@ -1,8 +1,10 @@
+use ic_config::{embedders::Config as EmbeddersConfig, flag_status::FlagStatus};
 use ic_embedders::wasmtime_embedder::system_api::ApiType;
 use ic_interfaces::execution_environment::SystemApiCallCounters;
 use ic_test_utilities_embedders::WasmtimeInstanceBuilder;
 use ic_test_utilities_types::ids::{subnet_test_id, user_test_id};
 use ic_types::time::UNIX_EPOCH;
+use std::collections::BTreeMap;
 
 fn call_counters_on_ok_call(wat: &str) -> SystemApiCallCounters {
     let mut instance = WasmtimeInstanceBuilder::new()
@@ -134,3 +136,48 @@ fn track_other() {
     assert_eq!(call_counters.canister_cycle_balance128, 0);
     assert_eq!(call_counters.time, 0);
 }
+
+#[test]
+fn track_calls_by_function_if_profiling_enabled() {
+    let wat = r#"(module
+            (import "ic0" "time" (func $ic0_time (result i64)))
+            (import "ic0" "msg_reply" (func $ic0_msg_reply))
+            (memory 1)
+            (func (export "canister_composite_query call_system_api")
+                (drop (call $ic0_time))
+                (drop (call $ic0_time))
+                (call $ic0_msg_reply)
+            )
+        )"#;
+    let call_counters = call_counters_on_ok_call(wat);
+    assert!(call_counters.calls_by_function.is_empty());
+
+    let mut config = EmbeddersConfig::default();
+    config.feature_flags.system_api_call_profiling = FlagStatus::Enabled;
+    let mut instance = WasmtimeInstanceBuilder::new()
+        .with_config(config)
+        .with_wat(wat)
+        .with_api_type(ApiType::composite_query(
+            UNIX_EPOCH,
+            user_test_id(0).get(),
+            subnet_test_id(1),
+            vec![0; 1024],
+            Some(vec![]),
+            0.into(),
+        ))
+        .build();
+    instance
+        .run(ic_types::methods::FuncRef::Method(
+            ic_types::methods::WasmMethod::CompositeQuery("call_system_api".into()),
+        ))
+        .unwrap();
+    let call_counters = instance.store_data().system_api().unwrap().call_counters();
+    assert_eq!(
+        call_counters.calls_by_function,
+        BTreeMap::from([
+            ("ic0.msg_reply".to_string(), 1),
+            ("ic0.time".to_string(), 2)
+        ])
+    );
+    assert_eq!(call_counters.time, 2);
+}
@@ -1,7 +1,7 @@
 use std::borrow::Cow;
 
 use assert_matches::assert_matches;
-use ic_config::embedders::Config as EmbeddersConfig;
+use ic_config::{embedders::Config as EmbeddersConfig, flag_status::FlagStatus};
 use ic_embedders::{
     WasmtimeEmbedder,
     wasm_utils::{
@@ -1420,3 +1420,75 @@ fn test_validate_table64() {
         Ok(WasmValidationDetails::default())
     );
 }
+
+const GC_AND_EXCEPTIONS_WAT: &str = r#"
+    (module
+        (type $point (struct (field i32) (field i64)))
+        (tag $error (param i32))
+        (func (export "canister_update go") (result i32)
+            (block $handler (result i32)
+                (try_table (catch $error $handler)
+                    (drop (struct.new_default $point))
+                    (throw $error (i32.const 42))
+                )
+                (i32.const 0)
+            )
+        )
+    )"#;
+
+#[test]
+fn can_reject_wasm_gc_and_exceptions_by_default() {
+    let wasm = wat2wasm(GC_AND_EXCEPTIONS_WAT).unwrap();
+    assert_matches!(
+        validate_wasm_binary(&wasm, &EmbeddersConfig::default()),
+        Err(WasmValidationError::WasmtimeValidation(_))
+    );
+}
+
+#[test]
+fn can_validate_wasm_gc_and_exceptions_with_feature_flag() {
+    let wasm = wat2wasm(GC_AND_EXCEPTIONS_WAT).unwrap();
+    let mut config = EmbeddersConfig::default();
+    config.feature_flags.wasm_gc_and_exceptions = FlagStatus::Enabled;
+    assert_matches!(validate_wasm_binary(&wasm, &config), Ok(_));
+}
+
+#[test]
+fn can_validate_table_set_on_gc_reference_tables_with_feature_flag() {
+    let wasm = wat2wasm(
+        r#"
+        (module
+            (type $box (struct (field i32)))
+            (table $objects 1 anyref)
+            (func (export "canister_update go")
+                (table.set $objects (i32.const 0) (struct.new $box (i32.const 42)))
+            )
+        )"#,
+    )
+    .unwrap();
+    let mut config = EmbeddersConfig::default();
+    config.feature_flags.wasm_gc_and_exceptions = FlagStatus::Enabled;
+    assert_matches!(validate_wasm_binary(&wasm, &config), Ok(_));
+}
+
+#[test]
+fn can_reject_table_set_on_funcref_tables_with_feature_flag() {
+    let wasm = wat2wasm(
+        r#"
+        (module
+            (table $functions 1 funcref)
+            (func $f)
+            (elem declare func $f)
+            (func (export "canister_update go")
+                (table.set $functions (i32.const 0) (ref.func $f))
+            )
+        )"#,
+    )
+    .unwrap();
+    let mut config = EmbeddersConfig::default();
+    config.feature_flags.wasm_gc_and_exceptions = FlagStatus::Enabled;
+    assert_matches!(
+        validate_wasm_binary(&wasm, &config),
+        Err(WasmValidationError::UnsupportedWasmInstruction { .. })
+    );
+}
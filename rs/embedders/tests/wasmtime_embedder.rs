@@ -4,6 +4,7 @@ use ic_config::{
     flag_status::FlagStatus,
 };
 use ic_embedders::{
+    CompilationTier,
     wasm_utils::instrumentation::WasmMemoryType,
     wasm_utils::instrumentation::instruction_to_cost,
     wasmtime_embedder::{
@@ -325,6 +326,147 @@ fn stack_overflow_traps() {
     handler.join().unwrap();
 }
 
+/// Tiering up a canister must not change the outcome of its executions, so the
+/// same module compiled with either tier has to execute the same number of
+/// instructions and hit the same traps.
+#[test]
+fn compilation_tiers_execute_same_instructions_and_traps() {
+    let instruction_limit = NumInstructions::from(10_000_000);
+    let wat = r#"
+        (module
+            (memory 1)
+            (func $fib (param i64) (result i64)
+                (if (result i64) (i64.lt_u (local.get 0) (i64.const 2))
+                    (then (local.get 0))
+                    (else
+                        (i64.add
+                            (call $fib (i64.sub (local.get 0) (i64.const 1)))
+                            (call $fib (i64.sub (local.get 0) (i64.const 2)))
+                        )
+                    )
+                )
+            )
+            (func (export "canister_update compute")
+                (local $i i32)
+                (loop $loop
+                    (i32.store
+                        (i32.mul (local.get $i) (i32.const 4))
+                        (i32.wrap_i64 (call $fib (i64.extend_i32_u (local.get $i))))
+                    )
+                    (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                    (br_if $loop (i32.lt_u (local.get $i) (i32.const 20)))
+                )
+            )
+            (func (export "canister_update unreachable")
+                (unreachable)
+            )
+            (func (export "canister_update divide_by_zero")
+                (drop (i32.div_u (i32.const 1) (i32.const 0)))
+            )
+            (func (export "canister_update out_of_bounds")
+                (drop (i32.load (i32.const 65536)))
+            )
+            (func (export "canister_update out_of_instructions")
+                (loop $loop (br $loop))
+            )
+        )"#;
+    let run = |tier: CompilationTier, method: &str| {
+        let mut instance = WasmtimeInstanceBuilder::new()
+            .with_wat(wat)
+            .with_num_instructions(instruction_limit)
+            .with_compilation_tier(tier)
+            .build();
+        let result = instance
+            .run(FuncRef::Method(WasmMethod::Update(method.to_string())))
+            .map(|run_result| run_result.wasm_dirty_pages);
+        let instruction_counter = instance.instruction_counter();
+        let system_api = instance.store_data().system_api().unwrap();
+        (
+            result,
+            system_api.slice_instructions_executed(instruction_counter),
+        )
+    };
+
+    for method in [
+        "compute",
+        "unreachable",
+        "divide_by_zero",
+        "out_of_bounds",
+        "out_of_instructions",
+    ] {
+        assert_eq!(
+            run(CompilationTier::Baseline, method),
+            run(CompilationTier::Optimized, method),
+            "{method} executed differently with the optimized tier"
+        );
+    }
+
+    let (result, _) = run(CompilationTier::Optimized, "divide_by_zero");
+    assert_matches!(
+        result,
+        Err(HypervisorError::Trapped {
+            trap_code: TrapCode::IntegerDivByZero,
+            ..
+        })
+    );
+    let (result, _) = run(CompilationTier::Optimized, "out_of_instructions");
+    assert_eq!(
+        result.err(),
+        Some(HypervisorError::InstructionLimitExceeded(instruction_limit))
+    );
+}
+
+/// The optimized tier lays out stack frames differently, so the recursion
+/// depth at which a canister overflows its stack may differ between the tiers.
+/// Which tier executes is decided by the replicated state, so all replicas
+/// agree on it, and within a tier the depth must be deterministic.
+#[test]
+fn stack_overflow_depth_is_deterministic_in_each_tier() {
+    let wat = r#"
+        (module
+            (global $depth (export "depth") (mut i64) (i64.const 0))
+            (func $f (export "canister_update f")
+                (local i64) (local i64) (local i64) (local i64) (local i64)
+                (global.set $depth (i64.add (global.get $depth) (i64.const 1)))
+                (call $f)
+            )
+            (memory 0)
+        )"#;
+    let overflow_depth = |tier: CompilationTier| {
+        let mut instance = WasmtimeInstanceBuilder::new()
+            .with_wat(wat)
+            .with_compilation_tier(tier)
+            .build();
+        let err = instance
+            .run(FuncRef::Method(WasmMethod::Update("f".to_string())))
+            .unwrap_err();
+        assert_matches!(
+            err,
+            HypervisorError::Trapped {
+                trap_code: TrapCode::StackOverflow,
+                ..
+            }
+        );
+        let Global::I64(depth) = instance.get_exported_globals().unwrap()[0] else {
+            panic!("Unexpected type of the depth global");
+        };
+        depth
+    };
+
+    let handler = std::thread::Builder::new()
+        // Default thread stack gets overflowed before the wasmtime
+        .stack_size(8192000)
+        .spawn(move || {
+            for tier in [CompilationTier::Baseline, CompilationTier::Optimized] {
+                let depth = overflow_depth(tier);
+                assert!(depth > 0);
+                assert_eq!(depth, overflow_depth(tier), "{tier:?}");
+            }
+        })
+        .unwrap();
+    handler.join().unwrap();
+}
+
 #[test]
 // Takes a Wasm with two mutable globals and checks whether we can set and get
 // their values.
@@ -3550,3 +3692,214 @@ fn test_environment_variable_system_api_not_enabled() {
         HypervisorError::WasmEngineError { .. }
     );
 }
+
+fn gc_and_exceptions_config() -> Config {
+    let mut config = Config::default();
+    config.feature_flags.wasm_gc_and_exceptions = FlagStatus::Enabled;
+    config
+}
+
+#[test]
+fn can_allocate_gc_structs_and_arrays() {
+    let wat = r#"
+    (module
+        (import "ic0" "msg_reply" (func $msg_reply))
+        (import "ic0" "msg_reply_data_append" (func $msg_reply_data_append (param i32 i32)))
+
+        (type $point (struct (field $x i32) (field $y i64)))
+        (type $bytes (array (mut i8)))
+
+        (func $make_point (param i32) (result (ref $point))
+            (struct.new $point (local.get 0) (i64.const 0))
+        )
+
+        (func (export "canister_update go")
+            (i32.store (i32.const 0)
+                (i32.add
+                    (struct.get $point $x (call $make_point (i32.const 40)))
+                    (array.len (array.new_default $bytes (i32.const 2)))
+                )
+            )
+            (call $msg_reply_data_append (i32.const 0) (i32.const 4))
+            (call $msg_reply)
+        )
+
+        (memory (export "memory") 1)
+    )"#;
+
+    let mut instance = WasmtimeInstanceBuilder::new()
+        .with_config(gc_and_exceptions_config())
+        .with_wat(wat)
+        .build();
+    let run_result = instance.run(FuncRef::Method(WasmMethod::Update("go".to_string())));
+    let system_api = instance.store_data_mut().system_api_mut().unwrap();
+    // The GC heap is discarded after the execution.
+    assert_eq!(system_api.get_allocated_bytes(), NumBytes::new(0));
+    let result = system_api.take_execution_result(run_result.as_ref().err());
+    assert_eq!(
+        result,
+        Ok(Some(WasmResult::Reply(42_i32.to_le_bytes().to_vec())))
+    );
+}
+
+#[test]
+fn can_throw_and_catch_exceptions() {
+    let wat = r#"
+    (module
+        (import "ic0" "msg_reply" (func $msg_reply))
+        (import "ic0" "msg_reply_data_append" (func $msg_reply_data_append (param i32 i32)))
+
+        (tag $error (param i32))
+
+        (func $fail (param i32)
+            (throw $error (local.get 0))
+        )
+
+        (func (export "canister_update go")
+            (i32.store (i32.const 0)
+                (block $handler (result i32)
+                    (try_table (catch $error $handler)
+                        (call $fail (i32.const 42))
+                    )
+                    (i32.const 0)
+                )
+            )
+            (call $msg_reply_data_append (i32.const 0) (i32.const 4))
+            (call $msg_reply)
+        )
+
+        (memory (export "memory") 1)
+    )"#;
+
+    let mut instance = WasmtimeInstanceBuilder::new()
+        .with_config(gc_and_exceptions_config())
+        .with_wat(wat)
+        .build();
+    let run_result = instance.run(FuncRef::Method(WasmMethod::Update("go".to_string())));
+    let result = instance
+        .store_data_mut()
+        .system_api_mut()
+        .unwrap()
+        .take_execution_result(run_result.as_ref().err());
+    assert_eq!(
+        result,
+        Ok(Some(WasmResult::Reply(42_i32.to_le_bytes().to_vec())))
+    );
+}
+
+#[test]
+fn uncaught_exception_traps() {
+    let wat = r#"
+    (module
+        (tag $error)
+        (func (export "canister_update go")
+            (throw $error)
+        )
+    )"#;
+
+    let mut instance = WasmtimeInstanceBuilder::new()
+        .with_config(gc_and_exceptions_config())
+        .with_wat(wat)
+        .build();
+    let result = instance.run(FuncRef::Method(WasmMethod::Update("go".to_string())));
+    assert_matches!(result, Err(HypervisorError::Trapped { .. }));
+}
+
+#[test]
+fn gc_allocation_is_charged_deterministically() {
+    let wat = r#"
+    (module
+        (type $point (struct (field i32) (field i64)))
+        (func (export "canister_update go")
+            (drop (struct.new_default $point))
+        )
+    )"#;
+
+    let mut instance = WasmtimeInstanceBuilder::new()
+        .with_config(gc_and_exceptions_config())
+        .with_wat(wat)
+        .build();
+    instance
+        .run(FuncRef::Method(WasmMethod::Update("go".to_string())))
+        .unwrap();
+
+    let instruction_counter = instance.instruction_counter();
+    let system_api = &instance.store_data().system_api().unwrap();
+    let instructions_used = system_api.slice_instructions_executed(instruction_counter);
+
+    let struct_new_cost = instruction_to_cost(
+        &wasmparser::Operator::StructNewDefault {
+            struct_type_index: 0,
+        },
+        WasmMemoryType::Wasm32,
+    );
+    let drop_cost = instruction_to_cost(&wasmparser::Operator::Drop, WasmMemoryType::Wasm32);
+
+    // The injected call to `try_grow_gc_heap` is not charged.
+    let expected_instructions = 1 // Function is 1 instruction.
+            + struct_new_cost
+            + drop_cost;
+    assert_eq!(instructions_used.get(), expected_instructions);
+}
+
+#[test]
+fn try_table_is_metered_as_a_block() {
+    let wat = r#"
+    (module
+        (tag $error)
+        (func $fail
+            (throw $error)
+        )
+        (func (export "canister_update go")
+            (block $handler
+                (try_table (catch $error $handler)
+                    (call $fail)
+                    (drop (i32.const 1))
+                )
+            )
+        )
+    )"#;
+
+    let mut instance = WasmtimeInstanceBuilder::new()
+        .with_config(gc_and_exceptions_config())
+        .with_wat(wat)
+        .build();
+    instance
+        .run(FuncRef::Method(WasmMethod::Update("go".to_string())))
+        .unwrap();
+
+    let instruction_counter = instance.instruction_counter();
+    let system_api = &instance.store_data().system_api().unwrap();
+    let instructions_used = system_api.slice_instructions_executed(instruction_counter);
+
+    let cost = |op: &wasmparser::Operator| instruction_to_cost(op, WasmMemoryType::Wasm32);
+    let block_cost = cost(&wasmparser::Operator::Block {
+        blockty: wasmparser::BlockType::Empty,
+    });
+    let try_table_cost = cost(&wasmparser::Operator::TryTable {
+        try_table: wasmparser::TryTable {
+            ty: wasmparser::BlockType::Empty,
+            catches: vec![],
+        },
+    });
+    let call_cost = cost(&wasmparser::Operator::Call { function_index: 0 });
+    let const_cost = cost(&wasmparser::Operator::I32Const { value: 1 });
+    let drop_cost = cost(&wasmparser::Operator::Drop);
+    let throw_cost = cost(&wasmparser::Operator::Throw { tag_index: 0 });
+    let end_cost = cost(&wasmparser::Operator::End);
+
+    // The code up to and including `try_table` and the body of `try_table` are
+    // charged on entry, even though the body is left early by the exception.
+    // The end of the handler block is skipped by the branch to the handler.
+    let expected_instructions = 1 // Function `go` is 1 instruction.
+            + block_cost
+            + try_table_cost
+            + call_cost
+            + const_cost
+            + drop_cost
+            + end_cost // End of `try_table`.
+            + 1 // Function `fail` is 1 instruction.
+            + throw_cost
+            + end_cost; // End of function `go`.
+    assert_eq!(instructions_used.get(), expected_instructions);
+}
@@ -532,6 +532,7 @@ fn should_display_pending_transactions_sorted_by_decreasing_cketh_ledger_burn_in
                 &EventType::CreatedTransaction {
                     withdrawal_id: req.cketh_ledger_burn_index(),
                     transaction: tx,
+                    l1_data_fee: None,
                 },
             );
         }
@@ -556,6 +557,7 @@ fn should_display_pending_transactions_sorted_by_decreasing_cketh_ledger_burn_in
                 &EventType::CreatedTransaction {
                     withdrawal_id,
                     transaction: tx,
+                    l1_data_fee: None,
                 },
             );
             apply_state_transition(
@@ -670,6 +672,7 @@ fn should_display_finalized_transactions_sorted_by_decreasing_cketh_ledger_burn_
                 &EventType::CreatedTransaction {
                     withdrawal_id: id,
                     transaction: tx,
+                    l1_data_fee: None,
                 },
             );
             apply_state_transition(
@@ -839,6 +842,7 @@ fn should_display_reimbursed_requests() {
                 &EventType::CreatedTransaction {
                     withdrawal_id: id,
                     transaction: tx,
+                    l1_data_fee: None,
                 },
             );
             apply_state_transition(
@@ -1146,6 +1150,8 @@ fn initial_state() -> State {
         next_transaction_nonce: TransactionNonce::ZERO.into(),
         last_scraped_block_number: candid::Nat::from(INITIAL_LAST_SCRAPED_BLOCK_NUMBER),
         evm_rpc_id: None,
+        evm_rpc_custom_provider_urls: None,
+        custom_chain_is_op_stack: None,
     })
     .expect("valid init args")
 }
@@ -1214,6 +1220,7 @@ fn add_finalized_transactions(state: &mut State, num_transactions: u64) {
             &EventType::CreatedTransaction {
                 withdrawal_id: id,
                 transaction: tx,
+                l1_data_fee: None,
             },
         );
         apply_state_transition(
@@ -1265,6 +1272,7 @@ fn add_reimbursed_transactions(state: &mut State, num_transactions: u64) {
             &EventType::CreatedTransaction {
                 withdrawal_id: id,
                 transaction: tx,
+                l1_data_fee: None,
             },
         );
         apply_state_transition(
@@ -1442,6 +1450,7 @@ fn cketh_withdrawal_flow(
         gas_used: signed_tx.transaction().gas_limit,
         status: tx_status,
         transaction_hash: signed_tx.hash(),
+        l1_fee: None,
     };
     (
         withdrawal_request.into(),
@@ -1473,6 +1482,7 @@ fn ckerc20_withdrawal_flow(
         nonce,
         gas_fee,
         GasAmount::from(65_000_u32),
+        Wei::ZERO,
         EthereumNetwork::Sepolia,
     )
     .unwrap();
@@ -1495,6 +1505,7 @@ fn ckerc20_withdrawal_flow(
         gas_used: signed_tx.transaction().gas_limit,
         status: tx_status,
         transaction_hash: signed_tx.hash(),
+        l1_fee: None,
     };
     (
         withdrawal_request.into(),
@@ -1,7 +1,8 @@
 use crate::{
     eth_logs::{
         LogParser, LogScraping, ReceivedErc20LogScraping, ReceivedEthLogScraping,
-        ReceivedEthOrErc20LogScraping, ReceivedEvent, ReceivedEventError, report_transaction_error,
+        ReceivedEthOrErc20LogScraping, ReceivedEvent, ReceivedEventError,
+        last_block_number_to_scrape, report_transaction_error,
     },
     eth_rpc::{Topic, is_response_too_large},
     eth_rpc_client::{
@@ -142,7 +143,7 @@ pub async fn scrape_logs() {
         Ok(guard) => guard,
         Err(_) => return,
     };
-    let last_block_number = match update_last_observed_block_number().await {
+    let last_observed_block_number = match update_last_observed_block_number().await {
         Some(block_number) => block_number,
         None => {
             log!(
@@ -152,6 +153,18 @@ pub async fn scrape_logs() {
             return;
         }
     };
+    let last_block_number = match read_state(|s| {
+        last_block_number_to_scrape(s, last_observed_block_number)
+    }) {
+        Some(block_number) => block_number,
+        None => {
+            log!(
+                DEBUG,
+                "[scrape_logs]: skipping scrapping logs: last observed block number {last_observed_block_number} is not final"
+            );
+            return;
+        }
+    };
     let max_block_spread = read_state(|s| s.max_block_spread_for_logs_scraping());
     scrape_until_block::<ReceivedEthLogScraping>(last_block_number, max_block_spread).await;
     scrape_until_block::<ReceivedErc20LogScraping>(last_block_number, max_block_spread).await;
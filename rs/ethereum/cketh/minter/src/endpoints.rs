@@ -369,6 +369,7 @@ pub mod events {
         pub gas_used: Nat,
         pub status: TransactionStatus,
         pub transaction_hash: String,
+        pub l1_fee: Option<Nat>,
     }
 
     #[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
@@ -422,6 +423,7 @@ pub mod events {
         CreatedTransaction {
             withdrawal_id: Nat,
             transaction: UnsignedTransaction,
+            l1_data_fee: Option<Nat>,
         },
         SignedTransaction {
             withdrawal_id: Nat,
@@ -40,8 +40,13 @@ impl Display for CkTokenSymbol {
 impl CkTokenSymbol {
     pub fn cketh_symbol_from_state(state: &State) -> Self {
         match state.ethereum_network {
-            EthereumNetwork::Mainnet => Self::from_str("ckETH").unwrap(),
+            EthereumNetwork::Mainnet | EthereumNetwork::Custom { .. } => {
+                Self::from_str("ckETH").unwrap()
+            }
             EthereumNetwork::Sepolia => Self::from_str("ckSepoliaETH").unwrap(),
+            EthereumNetwork::ArbitrumOne => Self::from_str("ckArbETH").unwrap(),
+            EthereumNetwork::Base => Self::from_str("ckBaseETH").unwrap(),
+            EthereumNetwork::Optimism => Self::from_str("ckOPETH").unwrap(),
         }
     }
 }
@@ -71,13 +76,13 @@ impl TryFrom<AddCkErc20Token> for CkErc20Token {
     type Error = String;
 
     fn try_from(value: AddCkErc20Token) -> Result<Self, Self::Error> {
-        let erc20_ethereum_network = EthereumNetwork::try_from(
+        let erc20_ethereum_network = EthereumNetwork::from(
             value
                 .chain_id
                 .0
                 .to_u64()
                 .ok_or("ERROR: chain_id does not fit in a u64")?,
-        )?;
+        );
         let erc20_contract_address =
             Address::from_str(&value.address).map_err(|e| format!("ERROR: {e}"))?;
         Ok(Self {
@@ -19,6 +19,7 @@ pub use parser::{
 };
 pub use scraping::{
     LogScraping, ReceivedErc20LogScraping, ReceivedEthLogScraping, ReceivedEthOrErc20LogScraping,
+    last_block_number_to_scrape, log_scraping_confirmations,
 };
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
@@ -1,9 +1,11 @@
+use crate::endpoints::CandidBlockTag;
 use crate::eth_logs::{
     LogParser, RECEIVED_ERC20_EVENT_TOPIC, RECEIVED_ETH_EVENT_TOPIC,
     RECEIVED_ETH_OR_ERC20_WITH_SUBACCOUNT_EVENT_TOPIC, ReceivedErc20LogParser,
     ReceivedEthLogParser, ReceivedEthOrErc20LogParser,
 };
 use crate::eth_rpc::Topic;
+use crate::lifecycle::EthereumNetwork;
 use crate::numeric::BlockNumber;
 use crate::state::State;
 use crate::state::eth_logs_scraping::LogScrapingId;
@@ -127,3 +129,39 @@ fn erc20_smart_contracts_addresses_as_topics(state: &State) -> impl Iterator<Ite
         .alt_keys()
         .map(|address| Hex32::from(<[u8; 32]>::from(address)))
 }
+
+/// Number of confirmations required on top of the last observed block
+/// before the logs it contains are scraped.
+///
+/// The `safe` and `finalized` block tags already account for finality on all supported chains.
+/// For the `latest` block tag, blocks on Ethereum are scraped right away,
+/// while blocks produced by the sequencer of a layer-2 rollup are only scraped once they are
+/// sufficiently deep to make a reorganization of the unsafe head unlikely (about one minute).
+/// Custom EVM chains cannot be configured with the `latest` block tag.
+pub fn log_scraping_confirmations(
+    ethereum_network: EthereumNetwork,
+    block_tag: &CandidBlockTag,
+) -> u64 {
+    match block_tag {
+        CandidBlockTag::Safe | CandidBlockTag::Finalized => 0,
+        CandidBlockTag::Latest => match ethereum_network {
+            EthereumNetwork::Mainnet | EthereumNetwork::Sepolia => 0,
+            // Arbitrum produces a block every 250ms.
+            EthereumNetwork::ArbitrumOne => 240,
+            // OP Stack chains produce a block every 2s.
+            EthereumNetwork::Base | EthereumNetwork::Optimism => 30,
+            EthereumNetwork::Custom { .. } => 0,
+        },
+    }
+}
+
+/// Last block whose logs can be scraped, given the last observed block number
+/// and the finality rule of the chain the minter interacts with.
+pub fn last_block_number_to_scrape(
+    state: &State,
+    last_observed_block_number: BlockNumber,
+) -> Option<BlockNumber> {
+    let confirmations =
+        log_scraping_confirmations(state.ethereum_network(), &state.ethereum_block_height);
+    last_observed_block_number.checked_sub(BlockNumber::from(confirmations))
+}
@@ -342,6 +342,72 @@ mod scraping {
     }
 }
 
+mod log_scraping_finality {
+    use crate::endpoints::CandidBlockTag;
+    use crate::eth_logs::{last_block_number_to_scrape, log_scraping_confirmations};
+    use crate::lifecycle::EthereumNetwork;
+    use crate::numeric::BlockNumber;
+    use crate::test_fixtures::initial_state;
+
+    #[test]
+    fn should_not_require_confirmations_for_safe_and_finalized_blocks() {
+        for ethereum_network in [
+            EthereumNetwork::Mainnet,
+            EthereumNetwork::Sepolia,
+            EthereumNetwork::ArbitrumOne,
+            EthereumNetwork::Base,
+            EthereumNetwork::Optimism,
+            EthereumNetwork::Custom { chain_id: 81457 },
+        ] {
+            for block_tag in [CandidBlockTag::Safe, CandidBlockTag::Finalized] {
+                assert_eq!(log_scraping_confirmations(ethereum_network, &block_tag), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn should_require_confirmations_for_latest_block_on_layer_2() {
+        assert_eq!(
+            log_scraping_confirmations(EthereumNetwork::Mainnet, &CandidBlockTag::Latest),
+            0
+        );
+        assert_eq!(
+            log_scraping_confirmations(EthereumNetwork::ArbitrumOne, &CandidBlockTag::Latest),
+            240
+        );
+        assert_eq!(
+            log_scraping_confirmations(EthereumNetwork::Base, &CandidBlockTag::Latest),
+            30
+        );
+        assert_eq!(
+            log_scraping_confirmations(EthereumNetwork::Optimism, &CandidBlockTag::Latest),
+            30
+        );
+    }
+
+    #[test]
+    fn should_subtract_confirmations_from_last_observed_block() {
+        let mut state = initial_state();
+        state.ethereum_network = EthereumNetwork::Base;
+        state.ethereum_block_height = CandidBlockTag::Latest;
+
+        assert_eq!(
+            last_block_number_to_scrape(&state, BlockNumber::new(1_000)),
+            Some(BlockNumber::new(970))
+        );
+        assert_eq!(
+            last_block_number_to_scrape(&state, BlockNumber::new(29)),
+            None
+        );
+
+        state.ethereum_block_height = CandidBlockTag::Finalized;
+        assert_eq!(
+            last_block_number_to_scrape(&state, BlockNumber::new(1_000)),
+            Some(BlockNumber::new(1_000))
+        );
+    }
+}
+
 mod parse_principal_from_slice {
     use crate::eth_logs::parse_principal_from_slice;
     use assert_matches::assert_matches;
@@ -1,11 +1,18 @@
-use crate::{lifecycle::EthereumNetwork, logs::INFO, state::State};
+use crate::{
+    eth_rpc::Hash, eth_rpc_client::responses::TransactionReceipt, lifecycle::EthereumNetwork,
+    logs::INFO, state::State,
+};
+use candid::Principal;
 use evm_rpc_client::{CandidResponseConverter, DoubleCycles, EvmRpcClient};
 use evm_rpc_types::{
-    ConsensusStrategy, EthSepoliaService, HttpOutcallError, MultiRpcResult as EvmMultiRpcResult,
-    RpcError, RpcService as EvmRpcService, RpcServices as EvmRpcServices,
+    ConsensusStrategy, EthSepoliaService, HttpOutcallError, JsonRpcError, L2MainnetService,
+    MultiRpcResult as EvmMultiRpcResult, RpcApi, RpcError, RpcService as EvmRpcService,
+    RpcServices as EvmRpcServices, ValidationError,
 };
+use futures::future::join_all;
 use ic_canister_log::log;
 use ic_canister_runtime::IcRuntime;
+use serde::Deserialize;
 use std::{
     collections::{BTreeMap, BTreeSet},
     fmt::Debug,
@@ -26,13 +33,22 @@ pub const HEADER_SIZE_LIMIT: u64 = 2 * 1024;
 pub const ETH_GET_LOGS_INITIAL_RESPONSE_SIZE_ESTIMATE: u64 = 100;
 
 pub const MIN_ATTACHED_CYCLES: u128 = 500_000_000_000;
+// A receipt of a withdrawal transaction contains at most one log entry (ERC-20 transfer).
+const TRANSACTION_RECEIPT_MAX_RESPONSE_BYTES: u64 = 10 * 1024;
+
+const L2_PROVIDERS: [L2MainnetService; 4] = [
+    L2MainnetService::Alchemy,
+    L2MainnetService::Ankr,
+    L2MainnetService::BlockPi,
+    L2MainnetService::PublicNode,
+];
 
 pub fn rpc_client(state: &State) -> EvmRpcClient<IcRuntime, CandidResponseConverter, DoubleCycles> {
-    const TOTAL_NUMBER_OF_PROVIDERS: u8 = 4;
     const MAX_NUM_RETRIES: u32 = 10;
 
     let chain = state.ethereum_network();
     let evm_rpc_id = state.evm_rpc_id();
+    let l2_providers = || Some(L2_PROVIDERS.to_vec());
 
     let providers = match chain {
         EthereumNetwork::Mainnet => EvmRpcServices::EthMainnet(None),
@@ -42,27 +58,182 @@ pub fn rpc_client(state: &State) -> EvmRpcClient<IcRuntime, CandidResponseConver
             EthSepoliaService::Alchemy,
             EthSepoliaService::Ankr,
         ])),
+        EthereumNetwork::ArbitrumOne => EvmRpcServices::ArbitrumOne(l2_providers()),
+        EthereumNetwork::Base => EvmRpcServices::BaseMainnet(l2_providers()),
+        EthereumNetwork::Optimism => EvmRpcServices::OptimismMainnet(l2_providers()),
+        EthereumNetwork::Custom { chain_id } => EvmRpcServices::Custom {
+            chain_id,
+            services: custom_rpc_apis(state),
+        },
     };
 
-    let min_threshold = match chain {
-        EthereumNetwork::Mainnet => 3_u8,
-        EthereumNetwork::Sepolia => 2_u8,
-    };
-    assert!(
-        min_threshold <= TOTAL_NUMBER_OF_PROVIDERS,
-        "BUG: min_threshold too high"
-    );
-
+    let (total, min_threshold) = consensus_threshold(state);
     EvmRpcClient::builder(IcRuntime::new(), evm_rpc_id)
         .with_rpc_sources(providers)
         .with_consensus_strategy(ConsensusStrategy::Threshold {
-            total: Some(TOTAL_NUMBER_OF_PROVIDERS),
+            total: Some(total),
             min: min_threshold,
         })
         .with_retry_strategy(DoubleCycles::with_max_num_retries(MAX_NUM_RETRIES))
         .build()
 }
 
+/// Total number of providers queried and minimum number of providers that must agree on a result.
+fn consensus_threshold(state: &State) -> (u8, u8) {
+    const TOTAL_NUMBER_OF_PROVIDERS: u8 = 4;
+
+    let (total, min_threshold) = match state.ethereum_network() {
+        EthereumNetwork::Mainnet
+        | EthereumNetwork::ArbitrumOne
+        | EthereumNetwork::Base
+        | EthereumNetwork::Optimism => (TOTAL_NUMBER_OF_PROVIDERS, 3_u8),
+        EthereumNetwork::Sepolia => (TOTAL_NUMBER_OF_PROVIDERS, 2_u8),
+        EthereumNetwork::Custom { .. } => {
+            let total = u8::try_from(state.evm_rpc_custom_provider_urls().len())
+                .expect("BUG: too many custom providers");
+            // strict majority of the configured providers
+            (total, total / 2 + 1)
+        }
+    };
+    assert!(min_threshold <= total, "BUG: min_threshold too high");
+    (total, min_threshold)
+}
+
+fn custom_rpc_apis(state: &State) -> Vec<RpcApi> {
+    state
+        .evm_rpc_custom_provider_urls()
+        .iter()
+        .map(|url| RpcApi {
+            url: url.clone(),
+            headers: None,
+        })
+        .collect()
+}
+
+/// Providers and consensus threshold used to query an OP Stack rollup with raw JSON-RPC requests.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct OpStackRpcConfig {
+    pub evm_rpc_id: Principal,
+    pub providers: Vec<EvmRpcService>,
+    pub min_threshold: u8,
+}
+
+impl OpStackRpcConfig {
+    /// Returns the configuration for the chain of the minter,
+    /// or `None` if the chain is not an OP Stack rollup.
+    pub fn from_state(state: &State) -> Option<Self> {
+        if !state.is_op_stack() {
+            return None;
+        }
+        let providers = match state.ethereum_network() {
+            EthereumNetwork::Base => L2_PROVIDERS.map(EvmRpcService::BaseMainnet).to_vec(),
+            EthereumNetwork::Optimism => L2_PROVIDERS.map(EvmRpcService::OptimismMainnet).to_vec(),
+            EthereumNetwork::Custom { .. } => custom_rpc_apis(state)
+                .into_iter()
+                .map(EvmRpcService::Custom)
+                .collect(),
+            EthereumNetwork::Mainnet | EthereumNetwork::Sepolia | EthereumNetwork::ArbitrumOne => {
+                return None;
+            }
+        };
+        let (_total, min_threshold) = consensus_threshold(state);
+        Some(Self {
+            evm_rpc_id: state.evm_rpc_id(),
+            providers,
+            min_threshold,
+        })
+    }
+}
+
+/// Retrieves the receipt of a transaction on an OP Stack rollup.
+///
+/// Receipts on OP Stack rollups contain the L1 data fee paid by the transaction (`l1Fee`),
+/// which is not exposed by the typed `eth_getTransactionReceipt` endpoint of the EVM RPC canister.
+/// The receipt is therefore retrieved from each configured provider with a raw JSON-RPC request.
+pub async fn get_op_stack_transaction_receipt(
+    config: &OpStackRpcConfig,
+    hash: Hash,
+) -> EvmMultiRpcResult<Option<TransactionReceipt>> {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_getTransactionReceipt",
+        "params": [hash.to_string()],
+    })
+    .to_string();
+    let results = join_all(config.providers.iter().cloned().map(async |provider| {
+        let result = ic_cdk::call::Call::unbounded_wait(config.evm_rpc_id, "request")
+            .with_args(&(
+                provider.clone(),
+                request.clone(),
+                TRANSACTION_RECEIPT_MAX_RESPONSE_BYTES,
+            ))
+            .with_cycles(MIN_ATTACHED_CYCLES)
+            .await
+            .map_err(|e| format!("failed to call the EVM RPC canister: {e}"))
+            .and_then(|response| {
+                response
+                    .candid::<Result<String, RpcError>>()
+                    .map_err(|e| format!("failed to decode the EVM RPC canister response: {e}"))
+            })
+            .map_err(|e| RpcError::ValidationError(ValidationError::Custom(e)))
+            .and_then(|response| response)
+            .and_then(|response| parse_transaction_receipt_response(&response));
+        (provider, result)
+    }))
+    .await;
+    reduce_with_threshold(results, config.min_threshold)
+}
+
+/// Reduces the results of the providers like the `Threshold` consensus strategy of the EVM RPC canister:
+/// the result is consistent if at least `min_threshold` providers returned the same ok result,
+/// or if all providers returned the same result.
+pub fn reduce_with_threshold<T: PartialEq + Clone>(
+    results: Vec<(EvmRpcService, Result<T, RpcError>)>,
+    min_threshold: u8,
+) -> EvmMultiRpcResult<T> {
+    let agreeing_with = |value: &T| {
+        results
+            .iter()
+            .filter(|(_, result)| result.as_ref().is_ok_and(|other| other == value))
+            .count()
+    };
+    let consistent_ok = results.iter().find_map(|(_, result)| match result {
+        Ok(value) if agreeing_with(value) >= usize::from(min_threshold) => Some(value.clone()),
+        _ => None,
+    });
+    if let Some(value) = consistent_ok {
+        return EvmMultiRpcResult::Consistent(Ok(value));
+    }
+    match results.first() {
+        Some((_, first_result)) if results.iter().all(|(_, result)| result == first_result) => {
+            EvmMultiRpcResult::Consistent(first_result.clone())
+        }
+        _ => EvmMultiRpcResult::Inconsistent(results),
+    }
+}
+
+/// Parses the JSON-RPC response to `eth_getTransactionReceipt`.
+pub fn parse_transaction_receipt_response(
+    response: &str,
+) -> Result<Option<TransactionReceipt>, RpcError> {
+    #[derive(Deserialize)]
+    struct JsonRpcReply {
+        result: Option<TransactionReceipt>,
+        error: Option<JsonRpcError>,
+    }
+
+    let reply: JsonRpcReply = serde_json::from_str(response).map_err(|e| {
+        RpcError::ValidationError(ValidationError::Custom(format!(
+            "invalid transaction receipt response: {e}"
+        )))
+    })?;
+    match reply.error {
+        Some(error) => Err(RpcError::JsonRpcError(error)),
+        None => Ok(reply.result),
+    }
+}
+
 /// Aggregates responses of different providers to the same query.
 /// Guaranteed to be non-empty.
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -204,6 +375,29 @@ where
     }
 }
 
+pub struct MaxByKey<F> {
+    get_key: F,
+}
+
+impl<F> MaxByKey<F> {
+    pub fn new(get_key: F) -> Self {
+        Self { get_key }
+    }
+}
+
+impl<T, F, K> ReductionStrategy<T> for MaxByKey<F>
+where
+    T: Debug + PartialEq,
+    F: Fn(&T) -> K,
+    K: Ord,
+{
+    fn reduce(&self, results: EvmMultiRpcResult<T>) -> Result<T, MultiCallError<T>> {
+        consistent_result_or_reduce(results, |inconsistent| {
+            inconsistent.reduce_with_max_by_key(|result| (self.get_key)(result))
+        })
+    }
+}
+
 pub struct StrictMajorityByKey<F> {
     get_key: F,
 }
@@ -296,6 +490,18 @@ impl<T: Debug + PartialEq> MultiCallResults<T> {
         Ok(min)
     }
 
+    pub fn reduce_with_max_by_key<F: FnMut(&T) -> K, K: Ord>(
+        self,
+        extractor: F,
+    ) -> Result<T, MultiCallError<T>> {
+        let max = self
+            .at_least_two_ok()?
+            .into_values()
+            .max_by_key(extractor)
+            .expect("BUG: MultiCallResults is guaranteed to be non-empty");
+        Ok(max)
+    }
+
     pub fn reduce_with_strict_majority_by_key<F: Fn(&T) -> K, K: Ord>(
         self,
         extractor: F,
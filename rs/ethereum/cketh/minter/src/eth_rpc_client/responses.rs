@@ -32,12 +32,20 @@ pub struct TransactionReceipt {
     /// The hash of the transaction
     #[n(5)]
     pub transaction_hash: Hash,
+
+    /// The L1 data fee charged on OP Stack rollups on top of the L2 execution fee.
+    /// Not part of the receipts of other chains.
+    #[n(6)]
+    pub l1_fee: Option<Wei>,
 }
 
 impl TransactionReceipt {
+    /// The total fee paid for the transaction, i.e., the L2 execution fee
+    /// and, on OP Stack rollups, the L1 data fee.
     pub fn effective_transaction_fee(&self) -> Wei {
         self.effective_gas_price
             .transaction_cost(self.gas_used)
+            .and_then(|execution_fee| execution_fee.checked_add(self.l1_fee.unwrap_or(Wei::ZERO)))
             .expect("ERROR: overflow during transaction fee calculation")
     }
 }
@@ -57,6 +65,9 @@ impl From<EvmTransactionReceipt> for TransactionReceipt {
             )
             .expect("EvmTransactionReceipt.status should be Some(0) or Some(1)"),
             transaction_hash: Hash(transaction_receipt.transaction_hash.into()),
+            // The EVM RPC canister does not return the `l1Fee` field of OP Stack receipts,
+            // which are therefore retrieved with a raw JSON-RPC request.
+            l1_fee: None,
         }
     }
 }
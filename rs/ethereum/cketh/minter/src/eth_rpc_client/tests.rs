@@ -1,10 +1,11 @@
 use crate::{
     eth_rpc::Hash,
     eth_rpc_client::{
-        MinByKey, MultiCallError, MultiCallResults, StrictMajorityByKey, ToReducedWithStrategy,
+        MaxByKey, MinByKey, MultiCallError, MultiCallResults, StrictMajorityByKey,
+        ToReducedWithStrategy, parse_transaction_receipt_response, reduce_with_threshold,
         responses::{TransactionReceipt, TransactionStatus},
     },
-    numeric::{BlockNumber, GasAmount, TransactionCount, WeiPerGas},
+    numeric::{BlockNumber, GasAmount, TransactionCount, Wei, WeiPerGas},
 };
 use assert_matches::assert_matches;
 use candid::Nat;
@@ -40,6 +41,44 @@ mod multi_call_results {
         }
     }
 
+    mod reduce_with_max_by_key {
+        use super::*;
+
+        #[test]
+        fn should_get_maximum_l1_data_fee() {
+            let results: MultiRpcResult<Wei> = MultiRpcResult::Inconsistent(vec![
+                (BLOCK_PI, Ok(Wei::new(1_000_000))),
+                (PUBLIC_NODE, Ok(Wei::new(1_000_001))),
+                (LLAMA_NODES, Ok(Wei::new(999_999))),
+            ]);
+
+            let reduced: Result<Wei, _> =
+                results.reduce_with_strategy(MaxByKey::new(|fee: &Wei| *fee));
+
+            assert_eq!(reduced, Ok(Wei::new(1_000_001)));
+        }
+
+        #[test]
+        fn should_fail_with_a_single_ok_result() {
+            let results: MultiRpcResult<Wei> = MultiRpcResult::Inconsistent(vec![
+                (BLOCK_PI, Ok(Wei::new(1_000_000))),
+                (
+                    PUBLIC_NODE,
+                    Err(HttpOutcallError::IcError {
+                        code: LegacyRejectionCode::SysTransient,
+                        message: "no consensus".to_string(),
+                    }
+                    .into()),
+                ),
+            ]);
+
+            let reduced: Result<Wei, _> =
+                results.reduce_with_strategy(MaxByKey::new(|fee: &Wei| *fee));
+
+            assert_matches!(reduced, Err(MultiCallError::InconsistentResults(_)));
+        }
+    }
+
     mod reduce_with_stable_majority_by_key {
         use super::*;
 
@@ -378,6 +417,7 @@ mod eth_get_transaction_receipt {
                     "0x0e59bd032b9b22aca5e2784e4cf114783512db00988c716cf17a1cc755a0a93d"
                 )
                 .unwrap(),
+                l1_fee: None,
             }
         )
     }
@@ -419,6 +459,123 @@ mod eth_get_transaction_receipt {
             assert_matches!(error, Err(e) if e.to_string().contains("invalid transaction status"));
         }
     }
+
+    #[test]
+    fn should_parse_op_stack_transaction_receipt_with_l1_fee() {
+        const RESPONSE: &str = r#"{
+        "jsonrpc": "2.0",
+        "id": 1,
+        "result": {
+            "transactionHash": "0x0e59bd032b9b22aca5e2784e4cf114783512db00988c716cf17a1cc755a0a93d",
+            "blockHash": "0x82005d2f17b251900968f01b0ed482cb49b7e1d797342bc504904d442b64dbe4",
+            "blockNumber": "0x4132ec",
+            "logs": [],
+            "contractAddress": null,
+            "effectiveGasPrice": "0xfefbee3e",
+            "cumulativeGasUsed": "0x8b2e10",
+            "from": "0x1789f79e95324a47c5fd6693071188e82e9a3558",
+            "gasUsed": "0x5208",
+            "l1BaseFeeScalar": "0x8dd",
+            "l1BlobBaseFee": "0x1",
+            "l1BlobBaseFeeScalar": "0x101c12",
+            "l1Fee": "0x2540be400",
+            "l1GasPrice": "0x2e90edd000",
+            "l1GasUsed": "0x640",
+            "status": "0x1",
+            "to": "0xdd2851cdd40ae6536831558dd46db62fac7a844d",
+            "transactionIndex": "0x32",
+            "type": "0x2"
+        }
+    }"#;
+
+        let receipt = parse_transaction_receipt_response(RESPONSE)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(receipt.l1_fee, Some(Wei::new(10_000_000_000)));
+        assert_eq!(
+            receipt.effective_transaction_fee(),
+            Wei::new(0xfefbee3e * 0x5208 + 10_000_000_000)
+        );
+    }
+
+    #[test]
+    fn should_parse_missing_transaction_receipt() {
+        assert_eq!(
+            parse_transaction_receipt_response(r#"{"jsonrpc":"2.0","id":1,"result":null}"#),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn should_parse_json_rpc_error() {
+        assert_eq!(
+            parse_transaction_receipt_response(
+                r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"header not found"}}"#
+            ),
+            Err(RpcError::JsonRpcError(JsonRpcError {
+                code: -32000,
+                message: "header not found".to_string(),
+            }))
+        );
+        assert_matches!(
+            parse_transaction_receipt_response("not JSON"),
+            Err(RpcError::ValidationError(_))
+        );
+    }
+}
+
+mod reduce_with_threshold {
+    use super::*;
+
+    const ANKR: EvmRpcService = EvmRpcService::EthMainnet(EthMainnetService::Ankr);
+
+    fn header_not_found() -> RpcError {
+        RpcError::JsonRpcError(JsonRpcError {
+            code: -32000,
+            message: "header not found".to_string(),
+        })
+    }
+
+    #[test]
+    fn should_be_consistent_when_threshold_of_providers_agree() {
+        let results = vec![
+            (BLOCK_PI, Ok(1_u64)),
+            (PUBLIC_NODE, Ok(1_u64)),
+            (LLAMA_NODES, Ok(2_u64)),
+            (ANKR, Err(header_not_found())),
+        ];
+
+        assert_eq!(
+            reduce_with_threshold(results.clone(), 2),
+            MultiRpcResult::Consistent(Ok(1))
+        );
+        assert_eq!(
+            reduce_with_threshold(results.clone(), 3),
+            MultiRpcResult::Inconsistent(results)
+        );
+    }
+
+    #[test]
+    fn should_be_consistent_with_single_provider() {
+        assert_eq!(
+            reduce_with_threshold(vec![(BLOCK_PI, Ok(1_u64))], 1),
+            MultiRpcResult::Consistent(Ok(1))
+        );
+    }
+
+    #[test]
+    fn should_be_consistent_when_all_providers_return_same_error() {
+        let results: Vec<(EvmRpcService, Result<u64, RpcError>)> = vec![
+            (BLOCK_PI, Err(header_not_found())),
+            (PUBLIC_NODE, Err(header_not_found())),
+        ];
+
+        assert_eq!(
+            reduce_with_threshold(results, 2),
+            MultiRpcResult::Consistent(Err(header_not_found()))
+        );
+    }
 }
 
 mod eth_get_transaction_count {
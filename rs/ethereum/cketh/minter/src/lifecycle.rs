@@ -19,16 +19,21 @@ pub enum MinterArg {
     UpgradeArg(UpgradeArg),
 }
 
-#[derive(
-    Copy, Clone, Eq, PartialEq, Hash, Debug, Default, CandidType, Decode, Deserialize, Encode,
-)]
-#[cbor(index_only)]
+/// The EVM chain the minter interacts with.
+///
+/// Besides Ethereum, the minter supports layer-2 rollups settling on Ethereum
+/// as well as arbitrary EVM chains identified by their [EIP-155](https://eips.ethereum.org/EIPS/eip-155) chain ID.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, CandidType, Deserialize)]
 pub enum EthereumNetwork {
-    #[n(1)]
     Mainnet,
-    #[n(11155111)]
     #[default]
     Sepolia,
+    ArbitrumOne,
+    Base,
+    Optimism,
+    Custom {
+        chain_id: u64,
+    },
 }
 
 impl EthereumNetwork {
@@ -36,18 +41,44 @@ impl EthereumNetwork {
         match self {
             EthereumNetwork::Mainnet => 1,
             EthereumNetwork::Sepolia => 11155111,
+            EthereumNetwork::ArbitrumOne => 42161,
+            EthereumNetwork::Base => 8453,
+            EthereumNetwork::Optimism => 10,
+            EthereumNetwork::Custom { chain_id } => *chain_id,
         }
     }
-}
 
-impl TryFrom<u64> for EthereumNetwork {
-    type Error = String;
+    /// Base URL of the block explorer for the chain, if known.
+    pub fn block_explorer_url(&self) -> Option<&'static str> {
+        match self {
+            EthereumNetwork::Mainnet => Some("https://etherscan.io"),
+            EthereumNetwork::Sepolia => Some("https://sepolia.etherscan.io"),
+            EthereumNetwork::ArbitrumOne => Some("https://arbiscan.io"),
+            EthereumNetwork::Base => Some("https://basescan.org"),
+            EthereumNetwork::Optimism => Some("https://optimistic.etherscan.io"),
+            EthereumNetwork::Custom { .. } => None,
+        }
+    }
+
+    /// Whether the chain is a known [OP Stack](https://docs.optimism.io/stack/getting-started) rollup,
+    /// where transactions are charged an L1 data fee on top of the L2 execution fee.
+    ///
+    /// Whether a custom chain is an OP Stack rollup is configured at installation,
+    /// see [`State::is_op_stack`](crate::state::State::is_op_stack).
+    pub fn is_op_stack(&self) -> bool {
+        matches!(self, EthereumNetwork::Base | EthereumNetwork::Optimism)
+    }
+}
 
-    fn try_from(value: u64) -> Result<Self, Self::Error> {
-        match value {
-            1 => Ok(EthereumNetwork::Mainnet),
-            11155111 => Ok(EthereumNetwork::Sepolia),
-            _ => Err("Unknown Ethereum Network".to_string()),
+impl From<u64> for EthereumNetwork {
+    fn from(chain_id: u64) -> Self {
+        match chain_id {
+            1 => EthereumNetwork::Mainnet,
+            11155111 => EthereumNetwork::Sepolia,
+            42161 => EthereumNetwork::ArbitrumOne,
+            8453 => EthereumNetwork::Base,
+            10 => EthereumNetwork::Optimism,
+            chain_id => EthereumNetwork::Custom { chain_id },
         }
     }
 }
@@ -57,6 +88,32 @@ impl Display for EthereumNetwork {
         match self {
             EthereumNetwork::Mainnet => write!(f, "Ethereum Mainnet"),
             EthereumNetwork::Sepolia => write!(f, "Ethereum Testnet Sepolia"),
+            EthereumNetwork::ArbitrumOne => write!(f, "Arbitrum One"),
+            EthereumNetwork::Base => write!(f, "Base Mainnet"),
+            EthereumNetwork::Optimism => write!(f, "OP Mainnet"),
+            EthereumNetwork::Custom { chain_id } => write!(f, "EVM chain {chain_id}"),
         }
     }
 }
+
+// Encoded as the chain ID, which is backwards compatible with
+// the previous index-only encoding of the `Mainnet` and `Sepolia` variants.
+impl<C> Encode<C> for EthereumNetwork {
+    fn encode<W: minicbor::encode::Write>(
+        &self,
+        e: &mut minicbor::Encoder<W>,
+        _ctx: &mut C,
+    ) -> Result<(), minicbor::encode::Error<W::Error>> {
+        e.u64(self.chain_id())?;
+        Ok(())
+    }
+}
+
+impl<'b, C> Decode<'b, C> for EthereumNetwork {
+    fn decode(
+        d: &mut minicbor::Decoder<'b>,
+        _ctx: &mut C,
+    ) -> Result<Self, minicbor::decode::Error> {
+        d.u64().map(EthereumNetwork::from)
+    }
+}
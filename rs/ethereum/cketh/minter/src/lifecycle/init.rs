@@ -31,6 +31,10 @@ pub struct InitArg {
     pub last_scraped_block_number: Nat,
     #[cbor(n(9), with = "icrc_cbor::principal::option")]
     pub evm_rpc_id: Option<Principal>,
+    #[n(10)]
+    pub evm_rpc_custom_provider_urls: Option<Vec<String>>,
+    #[n(11)]
+    pub custom_chain_is_op_stack: Option<bool>,
 }
 
 impl TryFrom<InitArg> for State {
@@ -46,6 +50,8 @@ impl TryFrom<InitArg> for State {
             next_transaction_nonce,
             last_scraped_block_number,
             evm_rpc_id,
+            evm_rpc_custom_provider_urls,
+            custom_chain_is_op_stack,
         }: InitArg,
     ) -> Result<Self, Self::Error> {
         use std::str::FromStr;
@@ -72,8 +78,12 @@ impl TryFrom<InitArg> for State {
                     )
                 })?;
         let evm_rpc_id = evm_rpc_id.unwrap_or(match ethereum_network {
-            EthereumNetwork::Mainnet => EVM_RPC_ID_PRODUCTION,
             EthereumNetwork::Sepolia => EVM_RPC_ID_STAGING,
+            EthereumNetwork::Mainnet
+            | EthereumNetwork::ArbitrumOne
+            | EthereumNetwork::Base
+            | EthereumNetwork::Optimism
+            | EthereumNetwork::Custom { .. } => EVM_RPC_ID_PRODUCTION,
         });
         let mut log_scrapings = LogScrapings::new(last_scraped_block_number);
         if let Some(contract_address) = eth_helper_contract_address {
@@ -102,8 +112,12 @@ impl TryFrom<InitArg> for State {
             active_tasks: Default::default(),
             http_request_counter: 0,
             last_transaction_price_estimate: None,
+            last_l1_data_fee_estimate: None,
+            last_l1_data_gas_estimate: None,
             ledger_suite_orchestrator_id: None,
             evm_rpc_id,
+            evm_rpc_custom_provider_urls: evm_rpc_custom_provider_urls.unwrap_or_default(),
+            custom_chain_is_op_stack: custom_chain_is_op_stack.unwrap_or_default(),
             ckerc20_tokens: Default::default(),
             erc20_balances: Default::default(),
             log_scrapings,
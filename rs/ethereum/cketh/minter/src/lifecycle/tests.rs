@@ -98,3 +98,179 @@ mod init {
         );
     }
 }
+
+mod custom_network {
+    use crate::endpoints::CandidBlockTag;
+    use crate::lifecycle::EthereumNetwork;
+    use crate::lifecycle::init::InitArg;
+    use crate::state::{InvalidStateError, MAX_EVM_RPC_CUSTOM_PROVIDERS, State};
+    use crate::test_fixtures::valid_init_arg;
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn should_fail_when_custom_network_has_no_providers() {
+        assert_matches!(
+            State::try_from(InitArg {
+                ethereum_network: EthereumNetwork::Custom { chain_id: 81457 },
+                ethereum_block_height: CandidBlockTag::Finalized,
+                evm_rpc_custom_provider_urls: None,
+                ..valid_init_arg()
+            }),
+            Err(InvalidStateError::InvalidEvmRpcCustomProviders(_))
+        );
+
+        assert_matches!(
+            State::try_from(InitArg {
+                ethereum_network: EthereumNetwork::Custom { chain_id: 81457 },
+                ethereum_block_height: CandidBlockTag::Finalized,
+                evm_rpc_custom_provider_urls: Some(vec![
+                    "https://rpc.example.com".to_string();
+                    MAX_EVM_RPC_CUSTOM_PROVIDERS + 1
+                ]),
+                ..valid_init_arg()
+            }),
+            Err(InvalidStateError::InvalidEvmRpcCustomProviders(_))
+        );
+    }
+
+    #[test]
+    fn should_fail_when_custom_network_uses_latest_block() {
+        assert_matches!(
+            State::try_from(InitArg {
+                ethereum_network: EthereumNetwork::Custom { chain_id: 81457 },
+                ethereum_block_height: CandidBlockTag::Latest,
+                evm_rpc_custom_provider_urls: Some(vec!["https://rpc.example.com".to_string()]),
+                ..valid_init_arg()
+            }),
+            Err(InvalidStateError::InvalidEthereumBlockHeight(_))
+        );
+    }
+
+    #[test]
+    fn should_fail_when_providers_specified_for_supported_network() {
+        for ethereum_network in [
+            EthereumNetwork::Mainnet,
+            EthereumNetwork::Sepolia,
+            EthereumNetwork::ArbitrumOne,
+            EthereumNetwork::Base,
+            EthereumNetwork::Optimism,
+        ] {
+            assert_matches!(
+                State::try_from(InitArg {
+                    ethereum_network,
+                    evm_rpc_custom_provider_urls: Some(vec!["https://rpc.example.com".to_string()]),
+                    ..valid_init_arg()
+                }),
+                Err(InvalidStateError::InvalidEvmRpcCustomProviders(_))
+            );
+            assert_matches!(
+                State::try_from(InitArg {
+                    ethereum_network,
+                    custom_chain_is_op_stack: Some(true),
+                    ..valid_init_arg()
+                }),
+                Err(InvalidStateError::InvalidCustomChainConfig(_))
+            );
+        }
+    }
+
+    #[test]
+    fn should_succeed_for_custom_network() {
+        let state = State::try_from(InitArg {
+            ethereum_network: EthereumNetwork::Custom { chain_id: 81457 },
+            ethereum_block_height: CandidBlockTag::Safe,
+            evm_rpc_custom_provider_urls: Some(vec![
+                "https://rpc1.example.com".to_string(),
+                "https://rpc2.example.com".to_string(),
+            ]),
+            ..valid_init_arg()
+        })
+        .expect("valid init args");
+
+        assert_eq!(state.ethereum_network().chain_id(), 81457);
+        assert_eq!(
+            state.evm_rpc_custom_provider_urls(),
+            &[
+                "https://rpc1.example.com".to_string(),
+                "https://rpc2.example.com".to_string()
+            ]
+        );
+        assert!(!state.is_op_stack());
+    }
+
+    #[test]
+    fn should_configure_custom_network_as_op_stack_rollup() {
+        // Blast is an OP Stack rollup that is not natively supported by the EVM RPC canister.
+        let state = State::try_from(InitArg {
+            ethereum_network: EthereumNetwork::Custom { chain_id: 81457 },
+            ethereum_block_height: CandidBlockTag::Safe,
+            evm_rpc_custom_provider_urls: Some(vec!["https://rpc.blast.io".to_string()]),
+            custom_chain_is_op_stack: Some(true),
+            ..valid_init_arg()
+        })
+        .expect("valid init args");
+
+        assert!(state.is_op_stack());
+        assert!(!state.ethereum_network().is_op_stack());
+    }
+}
+
+mod ethereum_network {
+    use crate::lifecycle::EthereumNetwork;
+    use proptest::prelude::any;
+    use proptest::proptest;
+
+    fn encode<T: minicbor::Encode<()>>(value: &T) -> Vec<u8> {
+        let mut buf = vec![];
+        minicbor::encode(value, &mut buf).expect("encoding should succeed");
+        buf
+    }
+
+    #[test]
+    fn should_convert_from_chain_id() {
+        for (chain_id, network) in [
+            (1, EthereumNetwork::Mainnet),
+            (11155111, EthereumNetwork::Sepolia),
+            (42161, EthereumNetwork::ArbitrumOne),
+            (8453, EthereumNetwork::Base),
+            (10, EthereumNetwork::Optimism),
+            (81457, EthereumNetwork::Custom { chain_id: 81457 }),
+        ] {
+            assert_eq!(EthereumNetwork::from(chain_id), network);
+            assert_eq!(network.chain_id(), chain_id);
+        }
+    }
+
+    #[test]
+    fn should_be_backwards_compatible_with_index_only_encoding() {
+        #[derive(Eq, PartialEq, Debug, minicbor::Encode, minicbor::Decode)]
+        #[cbor(index_only)]
+        enum LegacyEthereumNetwork {
+            #[n(1)]
+            Mainnet,
+            #[n(11155111)]
+            Sepolia,
+        }
+
+        for (legacy, network) in [
+            (LegacyEthereumNetwork::Mainnet, EthereumNetwork::Mainnet),
+            (LegacyEthereumNetwork::Sepolia, EthereumNetwork::Sepolia),
+        ] {
+            let legacy_bytes = encode(&legacy);
+            assert_eq!(encode(&network), legacy_bytes);
+            assert_eq!(
+                minicbor::decode::<EthereumNetwork>(&legacy_bytes).unwrap(),
+                network
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn should_encode_decode_chain_id(chain_id in any::<u64>()) {
+            let network = EthereumNetwork::from(chain_id);
+            let bytes = encode(&network);
+            assert_eq!(minicbor::decode::<EthereumNetwork>(&bytes).unwrap(), network);
+        }
+    }
+}
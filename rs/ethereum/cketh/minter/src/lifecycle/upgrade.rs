@@ -30,6 +30,8 @@ pub struct UpgradeArg {
     pub deposit_with_subaccount_helper_contract_address: Option<String>,
     #[cbor(n(9), with = "icrc_cbor::nat::option")]
     pub last_deposit_with_subaccount_scraped_block_number: Option<Nat>,
+    #[n(10)]
+    pub evm_rpc_custom_provider_urls: Option<Vec<String>>,
 }
 
 pub fn post_upgrade(upgrade_args: Option<UpgradeArg>) {
@@ -39,7 +39,8 @@ use ic_cketh_minter::state::{
 use ic_cketh_minter::tx::lazy_refresh_gas_fee_estimate;
 use ic_cketh_minter::withdraw::{
     CKERC20_WITHDRAWAL_TRANSACTION_GAS_LIMIT, CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT,
-    process_reimbursement, process_retrieve_eth_requests,
+    process_reimbursement, process_retrieve_eth_requests, withdrawal_gas_limit,
+    withdrawal_l1_data_fee,
 };
 use ic_cketh_minter::{
     PROCESS_ETH_RETRIEVE_TRANSACTIONS_INTERVAL, PROCESS_REIMBURSEMENT, SCRAPING_ETH_LOGS_INTERVAL,
@@ -190,7 +191,24 @@ async fn eip_1559_transaction_price(
     };
     match read_state(|s| s.last_transaction_price_estimate.clone()) {
         Some((ts, estimate)) => {
-            let mut result = Eip1559TransactionPrice::from(estimate.to_price(gas_limit));
+            let (gas_limit, l1_data_fee) = read_state(|s| {
+                Option::zip(
+                    withdrawal_gas_limit(
+                        gas_limit,
+                        s.ethereum_network(),
+                        s.last_l1_data_gas_estimate,
+                    ),
+                    withdrawal_l1_data_fee(s.is_op_stack(), s.last_l1_data_fee_estimate),
+                )
+            })
+            .unwrap_or_else(|| ic_cdk::trap("ERROR: last L1 data fee estimate is not available"));
+            let transaction_price = estimate.to_price(gas_limit);
+            let max_transaction_fee = transaction_price
+                .max_transaction_fee()
+                .checked_add(l1_data_fee)
+                .unwrap_or_else(|| ic_cdk::trap("ERROR: max transaction fee overflowed"));
+            let mut result = Eip1559TransactionPrice::from(transaction_price);
+            result.max_transaction_fee = max_transaction_fee.into();
             result.timestamp = Some(ts);
             result
         }
@@ -544,13 +562,21 @@ async fn withdraw_erc20(
 }
 
 async fn estimate_erc20_transaction_fee() -> Option<Wei> {
-    lazy_refresh_gas_fee_estimate()
-        .await
-        .map(|gas_fee_estimate| {
-            gas_fee_estimate
-                .to_price(CKERC20_WITHDRAWAL_TRANSACTION_GAS_LIMIT)
-                .max_transaction_fee()
-        })
+    let gas_fee_estimate = lazy_refresh_gas_fee_estimate().await?;
+    let (gas_limit, l1_data_fee) = read_state(|s| {
+        Option::zip(
+            withdrawal_gas_limit(
+                CKERC20_WITHDRAWAL_TRANSACTION_GAS_LIMIT,
+                s.ethereum_network(),
+                s.last_l1_data_gas_estimate,
+            ),
+            withdrawal_l1_data_fee(s.is_op_stack(), s.last_l1_data_fee_estimate),
+        )
+    })?;
+    gas_fee_estimate
+        .to_price(gas_limit)
+        .max_transaction_fee()
+        .checked_add(l1_data_fee)
 }
 
 #[query]
@@ -666,6 +692,7 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
                 TransactionStatus::Failure => CandidTransactionStatus::Failure,
             },
             transaction_hash: receipt.transaction_hash.to_string(),
+            l1_fee: receipt.l1_fee.map(Nat::from),
         }
     }
 
@@ -755,9 +782,11 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
                 EventType::CreatedTransaction {
                     withdrawal_id,
                     transaction,
+                    l1_data_fee,
                 } => EP::CreatedTransaction {
                     withdrawal_id: withdrawal_id.get().into(),
                     transaction: map_unsigned_transaction(transaction),
+                    l1_data_fee: l1_data_fee.map(Nat::from),
                 },
                 EventType::SignedTransaction {
                     withdrawal_id,
@@ -9,7 +9,7 @@ use crate::lifecycle::upgrade::UpgradeArg;
 use crate::logs::DEBUG;
 use crate::map::DedupMultiKeyMap;
 use crate::numeric::{
-    BlockNumber, Erc20Value, LedgerBurnIndex, LedgerMintIndex, TransactionNonce, Wei,
+    BlockNumber, Erc20Value, GasAmount, LedgerBurnIndex, LedgerMintIndex, TransactionNonce, Wei,
 };
 use crate::state::eth_logs_scraping::{LogScrapingId, LogScrapings};
 use crate::state::transactions::{Erc20WithdrawalRequest, TransactionCallData, WithdrawalRequest};
@@ -33,6 +33,9 @@ pub mod transactions;
 #[cfg(test)]
 mod tests;
 
+/// Maximum number of JSON-RPC providers that can be configured for a custom EVM chain.
+pub const MAX_EVM_RPC_CUSTOM_PROVIDERS: usize = 8;
+
 thread_local! {
     pub static STATE: RefCell<Option<State>> = RefCell::default();
 }
@@ -88,6 +91,14 @@ pub struct State {
 
     pub last_transaction_price_estimate: Option<(u64, GasFeeEstimate)>,
 
+    /// Last estimate of the upper bound on the L1 data fee of a withdrawal transaction.
+    /// Only relevant for OP Stack rollups and refreshed together with `last_transaction_price_estimate`.
+    pub last_l1_data_fee_estimate: Option<Wei>,
+
+    /// Last estimate of the L2 gas charged on Arbitrum for posting the data of a withdrawal transaction to L1.
+    /// Only relevant for Arbitrum and refreshed together with `last_transaction_price_estimate`.
+    pub last_l1_data_gas_estimate: Option<GasAmount>,
+
     /// Canister ID of the ledger suite orchestrator that
     /// can add new ERC-20 token to the minter
     pub ledger_suite_orchestrator_id: Option<Principal>,
@@ -96,6 +107,14 @@ pub struct State {
     /// handles communication with Ethereum
     pub evm_rpc_id: Principal,
 
+    /// URLs of the JSON-RPC providers used to communicate with a custom EVM chain.
+    /// Empty for chains natively supported by the EVM RPC canister.
+    pub evm_rpc_custom_provider_urls: Vec<String>,
+
+    /// Whether the custom EVM chain is an OP Stack rollup.
+    /// Always `false` for chains natively supported by the EVM RPC canister.
+    pub custom_chain_is_op_stack: bool,
+
     /// ERC-20 tokens that the minter can mint:
     /// - primary key: ledger ID for the ckERC20 token
     /// - secondary key: ERC-20 contract address on Ethereum
@@ -113,6 +132,9 @@ pub enum InvalidStateError {
     InvalidMinimumWithdrawalAmount(String),
     InvalidLastScrapedBlockNumber(String),
     InvalidLastErc20ScrapedBlockNumber(String),
+    InvalidEvmRpcCustomProviders(String),
+    InvalidEthereumBlockHeight(String),
+    InvalidCustomChainConfig(String),
 }
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -160,8 +182,11 @@ impl State {
             ));
         }
         let cketh_ledger_transfer_fee = match self.ethereum_network {
-            EthereumNetwork::Mainnet => Wei::new(2_000_000_000_000),
-            EthereumNetwork::Sepolia => Wei::new(10_000_000_000),
+            EthereumNetwork::Mainnet
+            | EthereumNetwork::ArbitrumOne
+            | EthereumNetwork::Base
+            | EthereumNetwork::Optimism => Wei::new(2_000_000_000_000),
+            EthereumNetwork::Sepolia | EthereumNetwork::Custom { .. } => Wei::new(10_000_000_000),
         };
         if self.cketh_minimum_withdrawal_amount < cketh_ledger_transfer_fee {
             return Err(InvalidStateError::InvalidMinimumWithdrawalAmount(
@@ -170,6 +195,39 @@ impl State {
                     .to_string(),
             ));
         }
+        match self.ethereum_network {
+            EthereumNetwork::Custom { .. } => {
+                if self.evm_rpc_custom_provider_urls.is_empty() {
+                    return Err(InvalidStateError::InvalidEvmRpcCustomProviders(
+                        "custom EVM chains require at least one JSON-RPC provider".to_string(),
+                    ));
+                }
+                if self.evm_rpc_custom_provider_urls.len() > MAX_EVM_RPC_CUSTOM_PROVIDERS {
+                    return Err(InvalidStateError::InvalidEvmRpcCustomProviders(format!(
+                        "at most {MAX_EVM_RPC_CUSTOM_PROVIDERS} JSON-RPC providers are supported"
+                    )));
+                }
+                if self.ethereum_block_height == CandidBlockTag::Latest {
+                    return Err(InvalidStateError::InvalidEthereumBlockHeight(
+                        "custom EVM chains must use the safe or finalized block height".to_string(),
+                    ));
+                }
+            }
+            _ => {
+                if !self.evm_rpc_custom_provider_urls.is_empty() {
+                    return Err(InvalidStateError::InvalidEvmRpcCustomProviders(format!(
+                        "JSON-RPC providers for {} are chosen by the EVM RPC canister",
+                        self.ethereum_network
+                    )));
+                }
+                if self.custom_chain_is_op_stack {
+                    return Err(InvalidStateError::InvalidCustomChainConfig(format!(
+                        "{} is not a custom EVM chain",
+                        self.ethereum_network
+                    )));
+                }
+            }
+        }
         Ok(())
     }
 
@@ -360,9 +418,15 @@ impl State {
                 .expect("BUG: withdrawal amount MUST always be at least the transaction amount"),
             WithdrawalRequest::CkErc20(req) => req.max_transaction_fee,
         };
-        let unspent_tx_fee = charged_tx_fee.checked_sub(tx_fee).expect(
-            "BUG: charged transaction fee MUST always be at least the effective transaction fee",
-        );
+        let unspent_tx_fee = match charged_tx_fee.checked_sub(tx_fee) {
+            Some(unspent_tx_fee) => unspent_tx_fee,
+            // On OP Stack rollups, the L1 data fee depends on the L1 fees at the time the transaction is mined
+            // and may exceed the reserved L1 data fee, in which case the difference is covered by the minter.
+            None if receipt.l1_fee.is_some() => Wei::ZERO,
+            None => panic!(
+                "BUG: charged transaction fee MUST always be at least the effective transaction fee"
+            ),
+        };
         let debited_amount = match receipt.status {
             TransactionStatus::Success => tx
                 .transaction()
@@ -462,6 +526,7 @@ impl State {
             evm_rpc_id,
             deposit_with_subaccount_helper_contract_address,
             last_deposit_with_subaccount_scraped_block_number,
+            evm_rpc_custom_provider_urls,
         } = upgrade_args;
         if let Some(nonce) = next_transaction_nonce {
             let nonce = TransactionNonce::try_from(nonce)
@@ -535,6 +600,9 @@ impl State {
         if let Some(evm_id) = evm_rpc_id {
             self.evm_rpc_id = evm_id;
         }
+        if let Some(urls) = evm_rpc_custom_provider_urls {
+            self.evm_rpc_custom_provider_urls = urls;
+        }
         self.validate_config()
     }
 
@@ -570,6 +638,14 @@ impl State {
             other.ledger_suite_orchestrator_id
         );
         ensure_eq!(self.ckerc20_tokens, other.ckerc20_tokens);
+        ensure_eq!(
+            self.evm_rpc_custom_provider_urls,
+            other.evm_rpc_custom_provider_urls
+        );
+        ensure_eq!(
+            self.custom_chain_is_op_stack,
+            other.custom_chain_is_op_stack
+        );
 
         self.eth_transactions
             .is_equivalent_to(&other.eth_transactions)
@@ -588,6 +664,16 @@ impl State {
     pub const fn evm_rpc_id(&self) -> Principal {
         self.evm_rpc_id
     }
+
+    pub fn evm_rpc_custom_provider_urls(&self) -> &[String] {
+        &self.evm_rpc_custom_provider_urls
+    }
+
+    /// Whether the chain is an OP Stack rollup, either a known one or a custom chain
+    /// configured as such, where transactions are charged an L1 data fee.
+    pub fn is_op_stack(&self) -> bool {
+        self.ethereum_network.is_op_stack() || self.custom_chain_is_op_stack
+    }
 }
 
 pub fn read_state<R>(f: impl FnOnce(&State) -> R) -> R {
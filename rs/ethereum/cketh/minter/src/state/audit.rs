@@ -4,6 +4,7 @@ mod tests;
 use super::State;
 pub use super::event::{Event, EventType};
 use crate::erc20::CkTokenSymbol;
+use crate::numeric::Wei;
 use crate::state::eth_logs_scraping::LogScrapingId;
 use crate::state::eth_logs_scraping::LogScrapingId::Erc20DepositWithoutSubaccount;
 use crate::state::transactions::{Reimbursed, ReimbursementIndex};
@@ -77,10 +78,13 @@ pub fn apply_state_transition(state: &mut State, payload: &EventType) {
         EventType::CreatedTransaction {
             withdrawal_id,
             transaction,
+            l1_data_fee,
         } => {
-            state
-                .eth_transactions
-                .record_created_transaction(*withdrawal_id, transaction.clone());
+            state.eth_transactions.record_created_transaction(
+                *withdrawal_id,
+                transaction.clone(),
+                l1_data_fee.unwrap_or(Wei::ZERO),
+            );
         }
         EventType::SignedTransaction {
             withdrawal_id: _,
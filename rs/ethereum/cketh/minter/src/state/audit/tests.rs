@@ -316,9 +316,11 @@ impl GetEventsFile {
                 EventPayload::CreatedTransaction {
                     withdrawal_id,
                     transaction,
+                    l1_data_fee,
                 } => ET::CreatedTransaction {
                     withdrawal_id: map_nat(withdrawal_id),
                     transaction: map_unsigned_transaction(transaction),
+                    l1_data_fee: l1_data_fee.map(|fee| fee.try_into().unwrap()),
                 },
                 EventPayload::SignedTransaction {
                     withdrawal_id,
@@ -352,6 +354,9 @@ impl GetEventsFile {
                             CandidTransactionStatus::Failure => TransactionStatus::Failure,
                         },
                         transaction_hash: transaction_receipt.transaction_hash.parse().unwrap(),
+                        l1_fee: transaction_receipt
+                            .l1_fee
+                            .map(|fee| fee.try_into().unwrap()),
                     },
                 },
                 EventPayload::ReimbursedEthWithdrawal {
@@ -395,8 +400,7 @@ impl GetEventsFile {
                     ckerc20_token_symbol,
                     ckerc20_ledger_id,
                 } => ET::AddedCkErc20Token(CkErc20Token {
-                    erc20_ethereum_network: EthereumNetwork::try_from(chain_id.0.to_u64().unwrap())
-                        .unwrap(),
+                    erc20_ethereum_network: EthereumNetwork::from(chain_id.0.to_u64().unwrap()),
                     erc20_contract_address: address.parse().unwrap(),
                     ckerc20_token_symbol: ckerc20_token_symbol.parse().unwrap(),
                     ckerc20_ledger_id,
@@ -2,7 +2,7 @@ use crate::erc20::CkErc20Token;
 use crate::eth_logs::{EventSource, ReceivedErc20Event, ReceivedEthEvent, ReceivedEvent};
 use crate::eth_rpc_client::responses::TransactionReceipt;
 use crate::lifecycle::{init::InitArg, upgrade::UpgradeArg};
-use crate::numeric::{BlockNumber, LedgerBurnIndex, LedgerMintIndex};
+use crate::numeric::{BlockNumber, LedgerBurnIndex, LedgerMintIndex, Wei};
 use crate::state::transactions::{
     Erc20WithdrawalRequest, EthWithdrawalRequest, Reimbursed, ReimbursementIndex,
     ReimbursementRequest,
@@ -62,6 +62,9 @@ pub enum EventType {
         withdrawal_id: LedgerBurnIndex,
         #[n(1)]
         transaction: Eip1559TransactionRequest,
+        /// The L1 data fee reserved for the transaction on OP Stack rollups.
+        #[n(2)]
+        l1_data_fee: Option<Wei>,
     },
     /// The minter signed a transaction.
     #[n(9)]
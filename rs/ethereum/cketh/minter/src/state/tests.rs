@@ -357,6 +357,15 @@ mod upgrade {
             }),
             Err(InvalidStateError::InvalidEthereumContractAddress(_))
         );
+
+        let mut state = initial_state();
+        assert_matches!(
+            state.upgrade(UpgradeArg {
+                evm_rpc_custom_provider_urls: Some(vec!["https://rpc.example.com".to_string()]),
+                ..Default::default()
+            }),
+            Err(InvalidStateError::InvalidEvmRpcCustomProviders(_))
+        );
     }
 
     #[test]
@@ -545,6 +554,17 @@ fn arb_block_tag() -> impl Strategy<Value = CandidBlockTag> {
     ]
 }
 
+fn arb_ethereum_network() -> impl Strategy<Value = EthereumNetwork> {
+    prop_oneof![
+        Just(EthereumNetwork::Mainnet),
+        Just(EthereumNetwork::Sepolia),
+        Just(EthereumNetwork::ArbitrumOne),
+        Just(EthereumNetwork::Base),
+        Just(EthereumNetwork::Optimism),
+        any::<u64>().prop_map(EthereumNetwork::from),
+    ]
+}
+
 fn arb_nat() -> impl Strategy<Value = Nat> {
     any::<u128>().prop_map(Nat::from)
 }
@@ -568,6 +588,7 @@ fn arb_access_list() -> impl Strategy<Value = AccessList> {
 
 prop_compose! {
     fn arb_init_arg()(
+        ethereum_network in arb_ethereum_network(),
         contract_address in proptest::option::of(arb_address()),
         ethereum_block_height in arb_block_tag(),
         minimum_withdrawal_amount in arb_nat(),
@@ -576,9 +597,11 @@ prop_compose! {
         ecdsa_key_name in "[a-z_]*",
         last_scraped_block_number in arb_nat(),
         evm_rpc_id in proptest::option::of(arb_principal()),
+        evm_rpc_custom_provider_urls in proptest::option::of(pvec("https://[a-z]{1,10}\\.com", 0..4)),
+        custom_chain_is_op_stack in proptest::option::of(any::<bool>()),
     ) -> InitArg {
         InitArg {
-            ethereum_network: EthereumNetwork::Sepolia,
+            ethereum_network,
             ecdsa_key_name,
             ethereum_contract_address: contract_address.map(|addr| addr.to_string()),
             ledger_id,
@@ -587,6 +610,8 @@ prop_compose! {
             next_transaction_nonce,
             last_scraped_block_number,
             evm_rpc_id,
+            evm_rpc_custom_provider_urls,
+            custom_chain_is_op_stack,
         }
     }
 }
@@ -603,6 +628,7 @@ prop_compose! {
         evm_rpc_id in proptest::option::of(arb_principal()),
         deposit_with_subaccount_helper_contract_address in proptest::option::of(arb_address()),
         last_deposit_with_subaccount_scraped_block_number in proptest::option::of(arb_nat()),
+        evm_rpc_custom_provider_urls in proptest::option::of(pvec("https://[a-z]{1,10}\\.com", 0..4)),
     ) -> UpgradeArg {
         UpgradeArg {
             ethereum_contract_address: contract_address.map(|addr| addr.to_string()),
@@ -614,7 +640,8 @@ prop_compose! {
             last_erc20_scraped_block_number,
             evm_rpc_id,
             deposit_with_subaccount_helper_contract_address: deposit_with_subaccount_helper_contract_address.map(|addr| addr.to_string()),
-            last_deposit_with_subaccount_scraped_block_number
+            last_deposit_with_subaccount_scraped_block_number,
+            evm_rpc_custom_provider_urls,
         }
     }
 }
@@ -724,6 +751,7 @@ prop_compose! {
         gas_used in arb_checked_amount_of(),
         status in arb_transaction_status(),
         transaction_hash in arb_hash(),
+        l1_fee in proptest::option::of(arb_checked_amount_of()),
     ) -> TransactionReceipt {
         TransactionReceipt {
             block_hash,
@@ -732,6 +760,7 @@ prop_compose! {
             gas_used,
             status,
             transaction_hash,
+            l1_fee,
         }
     }
 }
@@ -755,12 +784,18 @@ fn arb_event_type() -> impl Strategy<Value = EventType> {
         arb_checked_amount_of().prop_map(|block_number| EventType::SyncedToBlock { block_number }),
         arb_checked_amount_of()
             .prop_map(|block_number| EventType::SyncedErc20ToBlock { block_number }),
-        (any::<u64>(), arb_unsigned_tx()).prop_map(|(withdrawal_id, transaction)| {
-            EventType::CreatedTransaction {
-                withdrawal_id: withdrawal_id.into(),
-                transaction,
-            }
-        }),
+        (
+            any::<u64>(),
+            arb_unsigned_tx(),
+            proptest::option::of(arb_checked_amount_of())
+        )
+            .prop_map(|(withdrawal_id, transaction, l1_data_fee)| {
+                EventType::CreatedTransaction {
+                    withdrawal_id: withdrawal_id.into(),
+                    transaction,
+                    l1_data_fee,
+                }
+            }),
         (any::<u64>(), arb_signed_tx()).prop_map(|(withdrawal_id, transaction)| {
             EventType::SignedTransaction {
                 withdrawal_id: withdrawal_id.into(),
@@ -952,6 +987,7 @@ fn state_equivalence() {
                     "0x06afc3c693dc2ba2c19b5c287c4dddce040d766bea5fd13c8a7268b04aa94f2d"
                         .parse()
                         .unwrap(),
+                l1_fee: None,
             })
             .expect("valid receipt"),
         ),
@@ -1040,8 +1076,12 @@ fn state_equivalence() {
         erc20_balances: Default::default(),
         skipped_blocks: Default::default(),
         last_transaction_price_estimate: None,
+        last_l1_data_fee_estimate: None,
+        last_l1_data_gas_estimate: None,
         ledger_suite_orchestrator_id: Some("2s5qh-7aaaa-aaaar-qadya-cai".parse().unwrap()),
         evm_rpc_id: EVM_RPC_ID_PRODUCTION,
+        evm_rpc_custom_provider_urls: vec![],
+        custom_chain_is_op_stack: false,
         ckerc20_tokens,
     };
 
@@ -1263,6 +1303,16 @@ fn state_equivalence() {
         "changing the last transaction price estimate should result in an equivalent state",
     );
 
+    assert_eq!(
+        Ok(()),
+        state.is_equivalent_to(&State {
+            last_l1_data_fee_estimate: Some(Wei::new(1_000_000_000)),
+            last_l1_data_gas_estimate: Some(GasAmount::new(50_000)),
+            ..state.clone()
+        }),
+        "changing the last L1 data fee and gas estimates should result in an equivalent state",
+    );
+
     assert_ne!(
         Ok(()),
         state.is_equivalent_to(&State {
@@ -1549,6 +1599,126 @@ mod eth_balance {
         );
     }
 
+    #[test]
+    fn should_include_l1_fee_in_effective_transaction_fee() {
+        let mut state = initial_state();
+        apply_state_transition(
+            &mut state,
+            &EventType::AcceptedDeposit(received_eth_event()),
+        );
+        let balance_before_withdrawal = state.eth_balance.clone();
+        let withdrawal_request = eth_withdrawal_request();
+        let l1_data_fee = Wei::new(1_000_000);
+        let effective_l1_fee = Wei::new(400_000);
+
+        let receipt = WithdrawalFlow {
+            l1_data_fee,
+            effective_l1_fee: Some(effective_l1_fee),
+            ..WithdrawalFlow::for_request(withdrawal_request.clone())
+        }
+        .apply(&mut state);
+
+        let tx = state
+            .eth_transactions
+            .get_finalized_transaction(&withdrawal_request.ledger_burn_index)
+            .unwrap();
+        let charged_tx_fee = withdrawal_request
+            .withdrawal_amount
+            .checked_sub(tx.transaction().amount)
+            .unwrap();
+        let execution_fee = WeiPerGas::ONE
+            .transaction_cost(GasAmount::from(21_000_u32))
+            .unwrap();
+        let effective_tx_fee = execution_fee.checked_add(effective_l1_fee).unwrap();
+        assert_eq!(receipt.effective_transaction_fee(), effective_tx_fee);
+        assert_eq!(
+            state.eth_balance,
+            EthBalance {
+                eth_balance: balance_before_withdrawal
+                    .eth_balance
+                    .checked_sub(tx.transaction().amount)
+                    .and_then(|balance| balance.checked_sub(effective_tx_fee))
+                    .unwrap(),
+                total_effective_tx_fees: balance_before_withdrawal
+                    .total_effective_tx_fees
+                    .checked_add(effective_tx_fee)
+                    .unwrap(),
+                total_unspent_tx_fees: balance_before_withdrawal
+                    .total_unspent_tx_fees
+                    .checked_add(charged_tx_fee.checked_sub(effective_tx_fee).unwrap())
+                    .unwrap(),
+            }
+        );
+    }
+
+    #[test]
+    fn should_cover_l1_fee_exceeding_reserved_l1_data_fee() {
+        let mut state = initial_state();
+        apply_state_transition(
+            &mut state,
+            &EventType::AcceptedDeposit(received_eth_event()),
+        );
+        let balance_before_withdrawal = state.eth_balance.clone();
+        let withdrawal_request = eth_withdrawal_request();
+        let tx_fee = GasFeeEstimate {
+            base_fee_per_gas: WeiPerGas::ONE,
+            max_priority_fee_per_gas: WeiPerGas::ONE,
+        };
+        let gas_limit = GasAmount::from(21_000_u32);
+        let l1_data_fee = Wei::new(1_000_000);
+        let max_execution_fee = tx_fee.to_price(gas_limit).max_transaction_fee();
+        // The L1 fee charged when the transaction is mined exceeds the reserved L1 data fee
+        // by more than the unspent execution fee.
+        let effective_l1_fee = l1_data_fee.checked_add(max_execution_fee).unwrap();
+
+        let receipt = WithdrawalFlow {
+            tx_fee,
+            gas_limit,
+            l1_data_fee,
+            effective_l1_fee: Some(effective_l1_fee),
+            ..WithdrawalFlow::for_request(withdrawal_request.clone())
+        }
+        .apply(&mut state);
+
+        let effective_tx_fee = receipt.effective_transaction_fee();
+        assert_eq!(
+            state.eth_balance,
+            EthBalance {
+                eth_balance: balance_before_withdrawal
+                    .eth_balance
+                    .checked_sub(
+                        withdrawal_request
+                            .withdrawal_amount
+                            .checked_sub(max_execution_fee)
+                            .and_then(|amount| amount.checked_sub(l1_data_fee))
+                            .unwrap()
+                    )
+                    .and_then(|balance| balance.checked_sub(effective_tx_fee))
+                    .unwrap(),
+                total_effective_tx_fees: balance_before_withdrawal
+                    .total_effective_tx_fees
+                    .checked_add(effective_tx_fee)
+                    .unwrap(),
+                total_unspent_tx_fees: balance_before_withdrawal.total_unspent_tx_fees,
+            }
+        );
+    }
+
+    fn eth_withdrawal_request() -> EthWithdrawalRequest {
+        EthWithdrawalRequest {
+            withdrawal_amount: Wei::new(10_000_000_000_000_000),
+            destination: "0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34"
+                .parse()
+                .unwrap(),
+            ledger_burn_index: LedgerBurnIndex::new(0),
+            from: "k2t6j-2nvnp-4zjm3-25dtz-6xhaa-c7boj-5gayf-oj3xs-i43lp-teztq-6ae"
+                .parse()
+                .unwrap(),
+            from_subaccount: None,
+            created_at: Some(1699527697000000000),
+        }
+    }
+
     #[derive(Clone)]
     struct WithdrawalFlow {
         withdrawal_request: WithdrawalRequest,
@@ -1558,6 +1728,8 @@ mod eth_balance {
         effective_gas_price: WeiPerGas,
         effective_gas_used: GasAmount,
         tx_status: TransactionStatus,
+        l1_data_fee: Wei,
+        effective_l1_fee: Option<Wei>,
     }
 
     impl WithdrawalFlow {
@@ -1573,6 +1745,8 @@ mod eth_balance {
                 effective_gas_price: WeiPerGas::ONE,
                 effective_gas_used: GasAmount::from(21_000_u32),
                 tx_status: TransactionStatus::Success,
+                l1_data_fee: Wei::ZERO,
+                effective_l1_fee: None,
             }
         }
 
@@ -1592,6 +1766,7 @@ mod eth_balance {
                 self.nonce,
                 self.tx_fee,
                 self.gas_limit,
+                self.l1_data_fee,
                 EthereumNetwork::Sepolia,
             )
             .expect("BUG: failed to create transaction");
@@ -1600,6 +1775,7 @@ mod eth_balance {
                 &EventType::CreatedTransaction {
                     withdrawal_id: self.withdrawal_request.cketh_ledger_burn_index(),
                     transaction: transaction.clone(),
+                    l1_data_fee: Some(self.l1_data_fee).filter(|fee| *fee > Wei::ZERO),
                 },
             );
 
@@ -1627,6 +1803,7 @@ mod eth_balance {
                 gas_used: self.effective_gas_used,
                 status: self.tx_status,
                 transaction_hash: signed_tx.hash(),
+                l1_fee: self.effective_l1_fee,
             };
             apply_state_transition(
                 state,
@@ -486,6 +486,7 @@ impl EthTransactions {
         &mut self,
         withdrawal_id: LedgerBurnIndex,
         transaction: Eip1559TransactionRequest,
+        l1_data_fee: Wei,
     ) {
         let withdrawal_request = self
             .pending_withdrawal_requests
@@ -527,12 +528,20 @@ impl EthTransactions {
         self.remove_withdrawal_request(&withdrawal_request);
         let transaction_request = TransactionRequest {
             transaction,
+            // The L1 data fee reserved when creating the transaction is not available
+            // to pay for the L2 execution fee of resubmitted transactions.
             resubmission: match &withdrawal_request {
                 WithdrawalRequest::CkEth(cketh) => ResubmissionStrategy::ReduceEthAmount {
-                    withdrawal_amount: cketh.withdrawal_amount,
+                    withdrawal_amount: cketh
+                        .withdrawal_amount
+                        .checked_sub(l1_data_fee)
+                        .expect("BUG: withdrawal amount MUST cover the L1 data fee"),
                 },
                 WithdrawalRequest::CkErc20(ckerc20) => ResubmissionStrategy::GuaranteeEthAmount {
-                    allowed_max_transaction_fee: ckerc20.max_transaction_fee,
+                    allowed_max_transaction_fee: ckerc20
+                        .max_transaction_fee
+                        .checked_sub(l1_data_fee)
+                        .expect("BUG: max transaction fee MUST cover the L1 data fee"),
                 },
             },
         };
@@ -1105,6 +1114,9 @@ impl EthTransactions {
 /// The transaction fees are paid by the beneficiary,
 /// meaning that the fees will be deducted from the withdrawal amount.
 ///
+/// The `l1_data_fee` is the fee charged on top of the L2 execution fee on OP Stack rollups
+/// (zero on other chains). It does not depend on the gas limit and is reserved separately.
+///
 /// # Errors
 /// * `CreateTransactionError::InsufficientTransactionFee` if the ETH withdrawal amount does not cover the transaction fee.
 pub fn create_transaction(
@@ -1112,6 +1124,7 @@ pub fn create_transaction(
     nonce: TransactionNonce,
     gas_fee_estimate: GasFeeEstimate,
     gas_limit: GasAmount,
+    l1_data_fee: Wei,
     ethereum_network: EthereumNetwork,
 ) -> Result<Eip1559TransactionRequest, CreateTransactionError> {
     assert!(
@@ -1121,7 +1134,10 @@ pub fn create_transaction(
     match withdrawal_request {
         WithdrawalRequest::CkEth(request) => {
             let transaction_price = gas_fee_estimate.to_price(gas_limit);
-            let max_transaction_fee = transaction_price.max_transaction_fee();
+            let max_transaction_fee = transaction_price
+                .max_transaction_fee()
+                .checked_add(l1_data_fee)
+                .unwrap_or(Wei::MAX);
             let tx_amount = match request.withdrawal_amount.checked_sub(max_transaction_fee) {
                 Some(tx_amount) => tx_amount,
                 None => {
@@ -1152,19 +1168,25 @@ pub fn create_transaction(
             // the transaction could still make it as long as `transaction.max_fee_per_gas >=  block.base_fee_per_gas`,
             // since the `priority_fee_per_gas` received by the miner is capped to (see https://eips.ethereum.org/EIPS/eip-1559)
             // min(transaction.max_priority_fee_per_gas, transaction.max_fee_per_gas - block.base_fee_per_gas).
-            let request_max_fee_per_gas = request
-                .max_transaction_fee
-                .into_wei_per_gas(gas_limit)
-                .expect("BUG: gas_limit should be non-zero");
+            // On OP Stack rollups, the L1 data fee is first deducted from the `max_transaction_fee`.
             let actual_min_max_fee_per_gas = gas_fee_estimate.min_max_fee_per_gas();
-            if actual_min_max_fee_per_gas > request_max_fee_per_gas {
-                return Err(CreateTransactionError::InsufficientTransactionFee {
+            let insufficient_transaction_fee =
+                || CreateTransactionError::InsufficientTransactionFee {
                     cketh_ledger_burn_index: request.cketh_ledger_burn_index,
                     allowed_max_transaction_fee: request.max_transaction_fee,
                     actual_max_transaction_fee: actual_min_max_fee_per_gas
                         .transaction_cost(gas_limit)
+                        .and_then(|execution_fee| execution_fee.checked_add(l1_data_fee))
                         .unwrap_or(Wei::MAX),
-                });
+                };
+            let request_max_fee_per_gas = request
+                .max_transaction_fee
+                .checked_sub(l1_data_fee)
+                .ok_or_else(insufficient_transaction_fee)?
+                .into_wei_per_gas(gas_limit)
+                .expect("BUG: gas_limit should be non-zero");
+            if actual_min_max_fee_per_gas > request_max_fee_per_gas {
+                return Err(insufficient_transaction_fee());
             }
             Ok(Eip1559TransactionRequest {
                 chain_id: ethereum_network.chain_id(),
@@ -325,13 +325,14 @@ mod eth_transactions {
                 TransactionNonce::ZERO,
                 gas_fee_estimate(),
                 estimate_gas_limit(&withdrawal_request),
+                Wei::ZERO,
                 EthereumNetwork::Sepolia,
             )
             .unwrap();
 
             let burn_index = withdrawal_request.cketh_ledger_burn_index();
             expect_panic_with_message(
-                || transactions.record_created_transaction(burn_index, tx),
+                || transactions.record_created_transaction(burn_index, tx, Wei::ZERO),
                 &format!("withdrawal request {burn_index} not found"),
             );
         }
@@ -346,6 +347,7 @@ mod eth_transactions {
                 TransactionNonce::ZERO,
                 gas_fee_estimate(),
                 estimate_gas_limit(&withdrawal_request.clone().into()),
+                Wei::ZERO,
                 EthereumNetwork::Sepolia,
             )
             .unwrap();
@@ -360,6 +362,7 @@ mod eth_transactions {
                     transactions.record_created_transaction(
                         withdrawal_request.ledger_burn_index,
                         tx_with_wrong_destination,
+                        Wei::ZERO,
                     )
                 },
                 "destination mismatch",
@@ -378,6 +381,7 @@ mod eth_transactions {
                     transactions.record_created_transaction(
                         withdrawal_request.ledger_burn_index,
                         tx_with_wrong_amount,
+                        Wei::ZERO,
                     )
                 },
                 "amount deducted from transaction fees",
@@ -397,6 +401,7 @@ mod eth_transactions {
                 TransactionNonce::ZERO,
                 gas_fee_estimate(),
                 estimate_gas_limit(&withdrawal_request.clone().into()),
+                Wei::ZERO,
                 EthereumNetwork::Sepolia,
             )
             .unwrap();
@@ -410,6 +415,7 @@ mod eth_transactions {
                     transactions.record_created_transaction(
                         withdrawal_request.cketh_ledger_burn_index,
                         tx_mixing_payee_address_with_erc20_address,
+                        Wei::ZERO,
                     )
                 },
                 "destination mismatch",
@@ -425,6 +431,7 @@ mod eth_transactions {
                     transactions.record_created_transaction(
                         withdrawal_request.cketh_ledger_burn_index,
                         tx_with_wrong_amount,
+                        Wei::ZERO,
                     )
                 },
                 "amount should be zero",
@@ -445,12 +452,13 @@ mod eth_transactions {
                     wrong_nonce,
                     gas_fee_estimate(),
                     CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT,
+                    Wei::ZERO,
                     EthereumNetwork::Sepolia,
                 )
                 .unwrap();
 
                 expect_panic_with_message(
-                    || transactions.record_created_transaction(withdrawal_request.cketh_ledger_burn_index(), tx_with_wrong_nonce),
+                    || transactions.record_created_transaction(withdrawal_request.cketh_ledger_burn_index(), tx_with_wrong_nonce, Wei::ZERO),
                     "nonce mismatch",
                 );
             }
@@ -594,6 +602,65 @@ mod eth_transactions {
 
             assert_eq!(transactions.withdrawal_requests_batch(1), vec![]);
         }
+
+        #[test]
+        fn should_not_use_l1_data_fee_for_resubmissions() {
+            use crate::tx::ResubmissionStrategy;
+
+            let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+            let l1_data_fee = Wei::from(1_000_000_000_u64);
+            let cketh_request = cketh_withdrawal_request_with_index(LedgerBurnIndex::new(15));
+            let ckerc20_request = ckerc20_withdrawal_request_with_index(
+                LedgerBurnIndex::new(16),
+                LedgerBurnIndex::new(2),
+            );
+            for request in [cketh_request.clone().into(), ckerc20_request.clone().into()] {
+                transactions.record_withdrawal_request(request);
+            }
+            for request in transactions.withdrawal_requests_batch(2) {
+                let tx = create_transaction(
+                    &request,
+                    transactions.next_transaction_nonce(),
+                    gas_fee_estimate(),
+                    estimate_gas_limit(&request),
+                    l1_data_fee,
+                    EthereumNetwork::Optimism,
+                )
+                .unwrap();
+                transactions.record_created_transaction(
+                    request.cketh_ledger_burn_index(),
+                    tx,
+                    l1_data_fee,
+                );
+            }
+
+            assert_eq!(
+                transactions
+                    .created_tx
+                    .get_alt(&cketh_request.ledger_burn_index)
+                    .unwrap()
+                    .resubmission,
+                ResubmissionStrategy::ReduceEthAmount {
+                    withdrawal_amount: cketh_request
+                        .withdrawal_amount
+                        .checked_sub(l1_data_fee)
+                        .unwrap(),
+                }
+            );
+            assert_eq!(
+                transactions
+                    .created_tx
+                    .get_alt(&ckerc20_request.cketh_ledger_burn_index)
+                    .unwrap()
+                    .resubmission,
+                ResubmissionStrategy::GuaranteeEthAmount {
+                    allowed_max_transaction_fee: ckerc20_request
+                        .max_transaction_fee
+                        .checked_sub(l1_data_fee)
+                        .unwrap(),
+                }
+            );
+        }
     }
 
     mod record_signed_transaction {
@@ -2291,6 +2358,7 @@ mod create_transaction {
                 TransactionNonce::TWO,
                 gas_fee.clone(),
                 gas_limit,
+                Wei::ZERO,
                 EthereumNetwork::Sepolia,
             );
             prop_assert_eq!(
@@ -2313,6 +2381,7 @@ mod create_transaction {
                 TransactionNonce::TWO,
                 gas_fee,
                 gas_limit,
+                Wei::ZERO,
                 EthereumNetwork::Sepolia,
             );
             prop_assert_eq!(
@@ -2349,6 +2418,7 @@ mod create_transaction {
                 TransactionNonce::TWO,
                 gas_fee,
                 gas_limit,
+                Wei::ZERO,
                 EthereumNetwork::Sepolia,
             );
 
@@ -2403,6 +2473,7 @@ mod create_transaction {
                 TransactionNonce::from(0x57_u32),
                 gas_fee.clone(),
                 gas_limit,
+                Wei::ZERO,
                 EthereumNetwork::Mainnet,
             ).unwrap();
             let tx_max_fee_per_gas = result.max_fee_per_gas;
@@ -2424,6 +2495,109 @@ mod create_transaction {
         }
     }
 
+    #[test]
+    fn should_deduct_l1_data_fee_from_withdrawal_amount() {
+        let gas_fee = gas_fee_estimate();
+        let gas_limit = CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT;
+        let max_execution_fee = gas_fee.clone().to_price(gas_limit).max_transaction_fee();
+        let l1_data_fee = Wei::from(1_000_000_000_u64);
+        let withdrawal_request = EthWithdrawalRequest {
+            withdrawal_amount: Wei::from(100_000_000_000_000_u64),
+            ..cketh_withdrawal_request_with_index(LedgerBurnIndex::new(15))
+        };
+
+        let tx = create_transaction(
+            &withdrawal_request.clone().into(),
+            TransactionNonce::TWO,
+            gas_fee.clone(),
+            gas_limit,
+            l1_data_fee,
+            EthereumNetwork::Optimism,
+        )
+        .unwrap();
+
+        assert_eq!(tx.gas_limit, gas_limit);
+        assert_eq!(
+            tx.amount,
+            withdrawal_request
+                .withdrawal_amount
+                .checked_sub(max_execution_fee)
+                .and_then(|amount| amount.checked_sub(l1_data_fee))
+                .unwrap()
+        );
+
+        let insufficient_withdrawal_request = EthWithdrawalRequest {
+            withdrawal_amount: max_execution_fee,
+            ..withdrawal_request
+        };
+        assert_eq!(
+            create_transaction(
+                &insufficient_withdrawal_request.clone().into(),
+                TransactionNonce::TWO,
+                gas_fee,
+                gas_limit,
+                l1_data_fee,
+                EthereumNetwork::Optimism,
+            ),
+            Err(CreateTransactionError::InsufficientTransactionFee {
+                cketh_ledger_burn_index: insufficient_withdrawal_request.ledger_burn_index,
+                allowed_max_transaction_fee: max_execution_fee,
+                actual_max_transaction_fee: max_execution_fee.checked_add(l1_data_fee).unwrap(),
+            })
+        );
+    }
+
+    #[test]
+    fn should_reserve_l1_data_fee_from_ckerc20_max_transaction_fee() {
+        let gas_fee = gas_fee_estimate();
+        let gas_limit = GasAmount::from(65_000_u32);
+        let l1_data_fee = Wei::from(1_000_000_000_u64);
+        let min_execution_fee = gas_fee
+            .min_max_fee_per_gas()
+            .transaction_cost(gas_limit)
+            .unwrap();
+        let withdrawal_request = Erc20WithdrawalRequest {
+            max_transaction_fee: min_execution_fee.checked_add(l1_data_fee).unwrap(),
+            ..ckerc20_withdrawal_request_with_index(
+                LedgerBurnIndex::new(15),
+                LedgerBurnIndex::new(2),
+            )
+        };
+
+        let tx = create_transaction(
+            &withdrawal_request.clone().into(),
+            TransactionNonce::TWO,
+            gas_fee.clone(),
+            gas_limit,
+            l1_data_fee,
+            EthereumNetwork::Base,
+        )
+        .unwrap();
+
+        assert_eq!(tx.gas_limit, gas_limit);
+        assert_eq!(tx.max_fee_per_gas, gas_fee.min_max_fee_per_gas());
+
+        let insufficient_withdrawal_request = Erc20WithdrawalRequest {
+            max_transaction_fee: l1_data_fee,
+            ..withdrawal_request
+        };
+        assert_eq!(
+            create_transaction(
+                &insufficient_withdrawal_request.clone().into(),
+                TransactionNonce::TWO,
+                gas_fee,
+                gas_limit,
+                l1_data_fee,
+                EthereumNetwork::Base,
+            ),
+            Err(CreateTransactionError::InsufficientTransactionFee {
+                cketh_ledger_burn_index: insufficient_withdrawal_request.cketh_ledger_burn_index,
+                allowed_max_transaction_fee: l1_data_fee,
+                actual_max_transaction_fee: min_execution_fee.checked_add(l1_data_fee).unwrap(),
+            })
+        );
+    }
+
     proptest! {
          #[test]
          fn should_encode_decode_transaction_call_data(to in arb_address(), value in arb_checked_amount_of()) {
@@ -2443,7 +2617,7 @@ mod create_transaction {
 
 mod withdrawal_flow {
     use super::arbitrary::{arb_checked_amount_of, arb_gas_fee_estimate, arb_withdrawal_request};
-    use crate::numeric::TransactionNonce;
+    use crate::numeric::{TransactionNonce, Wei};
     use crate::state::transactions::tests::sign_transaction;
     use crate::state::transactions::{EthTransactions, EthereumNetwork, create_transaction};
     use crate::withdraw::estimate_gas_limit;
@@ -2474,9 +2648,10 @@ mod withdrawal_flow {
                     nonce,
                     gas_fee_estimate.clone(),
                     estimate_gas_limit(&request),
+                    Wei::ZERO,
                     EthereumNetwork::Sepolia,
                 ){
-                    wrapped_txs.borrow_mut().record_created_transaction(request.cketh_ledger_burn_index(), created_tx);
+                    wrapped_txs.borrow_mut().record_created_transaction(request.cketh_ledger_burn_index(), created_tx, Wei::ZERO);
                 }
             }
 
@@ -2854,10 +3029,15 @@ fn create_and_record_transaction<R: Into<WithdrawalRequest>>(
         transactions.next_transaction_nonce(),
         gas_fee_estimate,
         estimate_gas_limit(&withdrawal_request),
+        Wei::ZERO,
         EthereumNetwork::Sepolia,
     )
     .expect("failed to create transaction");
-    transactions.record_created_transaction(withdrawal_request.cketh_ledger_burn_index(), tx);
+    transactions.record_created_transaction(
+        withdrawal_request.cketh_ledger_burn_index(),
+        tx,
+        Wei::ZERO,
+    );
     transactions
         .created_tx
         .get_alt(&burn_index)
@@ -2907,6 +3087,7 @@ fn transaction_receipt(
         gas_used: signed_tx.transaction().gas_limit,
         status,
         transaction_hash: signed_tx.hash(),
+        l1_fee: None,
     }
 }
 
@@ -42,6 +42,8 @@ pub fn valid_init_arg() -> InitArg {
         next_transaction_nonce: Default::default(),
         last_scraped_block_number: Default::default(),
         evm_rpc_id: Some(EVM_RPC_ID_STAGING),
+        evm_rpc_custom_provider_urls: None,
+        custom_chain_is_op_stack: None,
     }
 }
 
@@ -4,18 +4,22 @@ mod tests;
 use crate::{
     eth_rpc::Hash,
     eth_rpc_client::{
-        MIN_ATTACHED_CYCLES, MultiCallError, StrictMajorityByKey, ToReducedWithStrategy,
+        MIN_ATTACHED_CYCLES, MaxByKey, MultiCallError, StrictMajorityByKey, ToReducedWithStrategy,
         responses::{TransactionReceipt, TransactionStatus},
         rpc_client,
     },
     guard::TimerGuard,
+    lifecycle::EthereumNetwork,
     logs::{DEBUG, INFO},
-    numeric::{BlockNumber, GasAmount, TransactionNonce, Wei, WeiPerGas},
-    state::{TaskType, lazy_call_ecdsa_public_key, mutate_state, read_state},
+    numeric::{BlockNumber, Erc20Value, GasAmount, TransactionNonce, Wei, WeiPerGas},
+    state::{
+        TaskType, lazy_call_ecdsa_public_key, mutate_state, read_state,
+        transactions::TransactionCallData,
+    },
 };
 use candid::Nat;
 use ethnum::u256;
-use evm_rpc_types::{BlockTag, FeeHistory};
+use evm_rpc_types::{BlockTag, CallArgs, FeeHistory, Hex, Hex20, TransactionRequest};
 use ic_canister_log::log;
 use ic_ethereum_types::Address;
 use ic_management_canister_types_private::DerivationPath;
@@ -25,6 +29,29 @@ use rlp::RlpStream;
 
 const EIP1559_TX_ID: u8 = 2;
 
+/// Address of the `GasPriceOracle` predeploy on OP Stack chains,
+/// see <https://specs.optimism.io/protocol/predeploys.html#gaspriceoracle>.
+pub const OP_STACK_GAS_PRICE_ORACLE_ADDRESS: Address = Address::new(hex_literal::hex!(
+    "420000000000000000000000000000000000000f"
+));
+
+// First 4 bytes of keccak256(getL1FeeUpperBound(uint256))
+const GET_L1_FEE_UPPER_BOUND_FUNCTION_SELECTOR: [u8; 4] = hex_literal::hex!("f1c7a58b");
+
+/// Address of the `NodeInterface` precompile on Arbitrum,
+/// see <https://docs.arbitrum.io/build-decentralized-apps/nodeinterface/reference>.
+pub const ARBITRUM_NODE_INTERFACE_ADDRESS: Address = Address::new(hex_literal::hex!(
+    "00000000000000000000000000000000000000c8"
+));
+
+// First 4 bytes of keccak256(gasEstimateL1Component(address,bool,bytes))
+const GAS_ESTIMATE_L1_COMPONENT_FUNCTION_SELECTOR: [u8; 4] = hex_literal::hex!("77d488a2");
+
+/// Upper bound on the size in bytes of an unsigned withdrawal transaction,
+/// used to estimate the L1 data fee on OP Stack chains.
+/// An ERC-20 transfer with the maximal values for all fields is less than 200 bytes.
+pub const MAX_UNSIGNED_WITHDRAWAL_TRANSACTION_SIZE: u32 = 256;
+
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Decode, Encode)]
 #[cbor(transparent)]
 pub struct AccessList(#[n(0)] pub Vec<AccessListItem>);
@@ -634,13 +661,7 @@ pub async fn lazy_refresh_gas_fee_estimate() -> Option<GasFeeEstimate> {
         };
 
         let gas_fee_estimate = match estimate_transaction_fee(&fee_history) {
-            Ok(estimate) => {
-                mutate_state(|s| {
-                    s.last_transaction_price_estimate =
-                        Some((ic_cdk::api::time(), estimate.clone()));
-                });
-                estimate
-            }
+            Ok(estimate) => estimate,
             Err(e) => {
                 log!(
                     INFO,
@@ -649,10 +670,64 @@ pub async fn lazy_refresh_gas_fee_estimate() -> Option<GasFeeEstimate> {
                 return None;
             }
         };
+        // The L1 data fee is refreshed together with the gas fee estimate,
+        // so that both are always consistent with each other.
+        let (ethereum_network, is_op_stack) =
+            read_state(|s| (s.ethereum_network(), s.is_op_stack()));
+        let l1_data_fee = if is_op_stack {
+            match eth_l1_data_fee_upper_bound().await {
+                Ok(Some(l1_data_fee)) => Some(l1_data_fee),
+                Ok(None) => {
+                    log!(
+                        INFO,
+                        "[refresh_gas_fee_estimate]: Invalid L1 data fee returned by the gas price oracle",
+                    );
+                    return None;
+                }
+                Err(e) => {
+                    log!(
+                        INFO,
+                        "[refresh_gas_fee_estimate]: Failed retrieving L1 data fee: {e:?}",
+                    );
+                    return None;
+                }
+            }
+        } else {
+            None
+        };
+        let l1_data_gas = if ethereum_network == EthereumNetwork::ArbitrumOne {
+            match eth_l1_data_gas_estimate().await {
+                Ok(Some(l1_data_gas)) => Some(with_l1_data_gas_margin(l1_data_gas)),
+                Ok(None) => {
+                    log!(
+                        INFO,
+                        "[refresh_gas_fee_estimate]: Invalid L1 gas estimate returned by the node interface",
+                    );
+                    return None;
+                }
+                Err(e) => {
+                    log!(
+                        INFO,
+                        "[refresh_gas_fee_estimate]: Failed retrieving L1 gas estimate: {e:?}",
+                    );
+                    return None;
+                }
+            }
+        } else {
+            None
+        };
+        mutate_state(|s| {
+            s.last_transaction_price_estimate =
+                Some((ic_cdk::api::time(), gas_fee_estimate.clone()));
+            s.last_l1_data_fee_estimate = l1_data_fee;
+            s.last_l1_data_gas_estimate = l1_data_gas;
+        });
         log!(
             INFO,
-            "[refresh_gas_fee_estimate]: Estimated transaction fee: {:?}",
+            "[refresh_gas_fee_estimate]: Estimated transaction fee: {:?}, L1 data fee: {:?}, L1 data gas: {:?}",
             gas_fee_estimate,
+            l1_data_fee,
+            l1_data_gas,
         );
         Some(gas_fee_estimate)
     }
@@ -669,6 +744,50 @@ pub async fn lazy_refresh_gas_fee_estimate() -> Option<GasFeeEstimate> {
             }))
     }
 
+    async fn eth_l1_data_fee_upper_bound() -> Result<Option<Wei>, MultiCallError<Option<Wei>>> {
+        read_state(rpc_client)
+            .call(CallArgs {
+                transaction: TransactionRequest {
+                    to: Some(Hex20::from(OP_STACK_GAS_PRICE_ORACLE_ADDRESS.into_bytes())),
+                    input: Some(Hex::from(encode_get_l1_fee_upper_bound(
+                        MAX_UNSIGNED_WITHDRAWAL_TRANSACTION_SIZE,
+                    ))),
+                    ..Default::default()
+                },
+                block: Some(BlockTag::Latest),
+            })
+            .with_cycles(MIN_ATTACHED_CYCLES)
+            .send()
+            .await
+            .map(|data: Hex| decode_l1_data_fee(&Vec::<u8>::from(data)))
+            // Providers may be at different L1 origin blocks and thus report different fees.
+            // Since it's an upper bound, we take the maximum.
+            .reduce_with_strategy(MaxByKey::new(|fee: &Option<Wei>| *fee))
+    }
+
+    async fn eth_l1_data_gas_estimate()
+    -> Result<Option<GasAmount>, MultiCallError<Option<GasAmount>>> {
+        read_state(rpc_client)
+            .call(CallArgs {
+                transaction: TransactionRequest {
+                    to: Some(Hex20::from(ARBITRUM_NODE_INTERFACE_ADDRESS.into_bytes())),
+                    input: Some(Hex::from(encode_gas_estimate_l1_component(
+                        &MAX_WITHDRAWAL_CALL_DATA_DESTINATION,
+                        &max_withdrawal_call_data(),
+                    ))),
+                    ..Default::default()
+                },
+                block: Some(BlockTag::Latest),
+            })
+            .with_cycles(MIN_ATTACHED_CYCLES)
+            .send()
+            .await
+            .map(|data: Hex| decode_l1_data_gas(&Vec::<u8>::from(data)))
+            // Providers may have seen different L1 base fees and thus report different estimates.
+            // We take the maximum to avoid underestimating the gas limit.
+            .reduce_with_strategy(MaxByKey::new(|gas: &Option<GasAmount>| *gas))
+    }
+
     let now_ns = ic_cdk::api::time();
     match read_state(|s| s.last_transaction_price_estimate.clone()) {
         Some((last_estimate_timestamp_ns, estimate))
@@ -733,6 +852,75 @@ pub fn estimate_transaction_fee(
     Ok(gas_fee_estimate)
 }
 
+/// Encode the call data of `getL1FeeUpperBound(uint256 _unsignedTxSize)` on the OP Stack `GasPriceOracle`.
+/// See the [Contract ABI Specification](https://docs.soliditylang.org/en/develop/abi-spec.html#contract-abi-specification).
+pub fn encode_get_l1_fee_upper_bound(unsigned_tx_size: u32) -> Vec<u8> {
+    let mut data = Vec::with_capacity(36);
+    data.extend(GET_L1_FEE_UPPER_BOUND_FUNCTION_SELECTOR);
+    data.extend(u256::from(unsigned_tx_size).to_be_bytes());
+    data
+}
+
+/// Decode the `uint256` returned by `getL1FeeUpperBound`.
+/// Returns `None` if the data is not exactly 32 bytes long.
+pub fn decode_l1_data_fee(data: &[u8]) -> Option<Wei> {
+    <[u8; 32]>::try_from(data).ok().map(Wei::from_be_bytes)
+}
+
+/// Destination used to estimate the L1 data gas of a withdrawal transaction on Arbitrum.
+/// Non-zero bytes are the most expensive to post to L1.
+const MAX_WITHDRAWAL_CALL_DATA_DESTINATION: Address = Address::new([0xff; 20]);
+
+/// Call data of the most expensive withdrawal transaction to post to L1,
+/// i.e., an ERC-20 transfer with only non-zero bytes.
+fn max_withdrawal_call_data() -> Vec<u8> {
+    TransactionCallData::Erc20Transfer {
+        to: MAX_WITHDRAWAL_CALL_DATA_DESTINATION,
+        value: Erc20Value::MAX,
+    }
+    .encode()
+}
+
+/// Encode the call data of `gasEstimateL1Component(address to, bool contractCreation, bytes data)`
+/// on the Arbitrum `NodeInterface`.
+/// See the [Contract ABI Specification](https://docs.soliditylang.org/en/develop/abi-spec.html#contract-abi-specification).
+pub fn encode_gas_estimate_l1_component(to: &Address, data: &[u8]) -> Vec<u8> {
+    let padded_data_len = data.len().div_ceil(32) * 32;
+    let mut encoded = Vec::with_capacity(4 + 4 * 32 + padded_data_len);
+    encoded.extend(GAS_ESTIMATE_L1_COMPONENT_FUNCTION_SELECTOR);
+    encoded.extend([0_u8; 12]);
+    encoded.extend(to.as_ref());
+    // contractCreation = false
+    encoded.extend([0_u8; 32]);
+    // offset of the dynamic `data` argument
+    encoded.extend(u256::from(3_u8 * 32).to_be_bytes());
+    encoded.extend(u256::from(data.len() as u64).to_be_bytes());
+    encoded.extend(data);
+    encoded.resize(encoded.len() + padded_data_len - data.len(), 0);
+    encoded
+}
+
+/// Decode the `gasEstimateForL1` returned by `gasEstimateL1Component`,
+/// which returns `(uint64 gasEstimateForL1, uint256 baseFee, uint256 l1BaseFeeEstimate)`.
+/// Returns `None` if the data is not exactly 96 bytes long.
+pub fn decode_l1_data_gas(data: &[u8]) -> Option<GasAmount> {
+    if data.len() != 3 * 32 {
+        return None;
+    }
+    <[u8; 32]>::try_from(&data[..32])
+        .ok()
+        .map(GasAmount::from_be_bytes)
+}
+
+/// The L1 gas estimate depends on the current L1 base fee and L2 base fee,
+/// which may change until the transaction is mined.
+/// A margin of 50% is added to avoid underestimating the gas limit.
+pub fn with_l1_data_gas_margin(l1_data_gas: GasAmount) -> GasAmount {
+    l1_data_gas
+        .checked_add(l1_data_gas.div_by_two())
+        .unwrap_or(GasAmount::MAX)
+}
+
 fn median<T: Ord>(values: &mut [T]) -> Option<&T> {
     if values.is_empty() {
         return None;
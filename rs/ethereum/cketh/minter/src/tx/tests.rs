@@ -218,6 +218,134 @@ fn should_cbor_encoding_be_stable() {
     assert_eq!(decoded_signed_tx, signed_tx);
 }
 
+mod l1_data_fee {
+    use crate::lifecycle::EthereumNetwork;
+    use crate::numeric::{GasAmount, Wei};
+    use crate::tx::{
+        MAX_UNSIGNED_WITHDRAWAL_TRANSACTION_SIZE, decode_l1_data_fee, decode_l1_data_gas,
+        encode_gas_estimate_l1_component, encode_get_l1_fee_upper_bound, max_withdrawal_call_data,
+        with_l1_data_gas_margin,
+    };
+    use crate::withdraw::{
+        CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT, withdrawal_gas_limit, withdrawal_l1_data_fee,
+    };
+    use ethers_core::abi::Token;
+    use hex_literal::hex;
+    use ic_ethereum_types::Address;
+
+    #[test]
+    fn should_encode_get_l1_fee_upper_bound_call_data() {
+        assert_eq!(
+            encode_get_l1_fee_upper_bound(MAX_UNSIGNED_WITHDRAWAL_TRANSACTION_SIZE),
+            hex!("f1c7a58b0000000000000000000000000000000000000000000000000000000000000100")
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn should_decode_l1_data_fee() {
+        assert_eq!(
+            decode_l1_data_fee(&hex!(
+                "00000000000000000000000000000000000000000000000000000002540be400"
+            )),
+            Some(Wei::new(10_000_000_000))
+        );
+        assert_eq!(decode_l1_data_fee(&[]), None);
+        assert_eq!(decode_l1_data_fee(&[0_u8; 33]), None);
+    }
+
+    #[test]
+    fn should_encode_gas_estimate_l1_component_call_data() {
+        let to: Address = "0xaf88d065e77c8cc2239327c5edb3a432268e5831"
+            .parse()
+            .unwrap();
+        for data in [vec![], vec![0xab; 4], max_withdrawal_call_data()] {
+            let mut expected =
+                ethers_core::utils::id("gasEstimateL1Component(address,bool,bytes)").to_vec();
+            expected.extend(ethers_core::abi::encode(&[
+                Token::Address(to.to_string().parse().unwrap()),
+                Token::Bool(false),
+                Token::Bytes(data.clone()),
+            ]));
+
+            assert_eq!(encode_gas_estimate_l1_component(&to, &data), expected);
+        }
+    }
+
+    #[test]
+    fn should_decode_l1_data_gas() {
+        let mut data = vec![0_u8; 96];
+        data[24..32].copy_from_slice(&100_000_u64.to_be_bytes());
+        data[63] = 1;
+        data[95] = 2;
+        assert_eq!(decode_l1_data_gas(&data), Some(GasAmount::new(100_000)));
+        assert_eq!(decode_l1_data_gas(&data[..32]), None);
+        assert_eq!(decode_l1_data_gas(&[]), None);
+    }
+
+    #[test]
+    fn should_add_margin_to_l1_data_gas() {
+        assert_eq!(
+            with_l1_data_gas_margin(GasAmount::new(100_000)),
+            GasAmount::new(150_000)
+        );
+        assert_eq!(with_l1_data_gas_margin(GasAmount::MAX), GasAmount::MAX);
+    }
+
+    #[test]
+    fn should_not_charge_l1_costs_on_ethereum() {
+        for network in [EthereumNetwork::Mainnet, EthereumNetwork::Sepolia] {
+            assert_eq!(
+                withdrawal_gas_limit(CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT, network, None),
+                Some(CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT)
+            );
+            assert_eq!(
+                withdrawal_l1_data_fee(network.is_op_stack(), None),
+                Some(Wei::ZERO)
+            );
+        }
+    }
+
+    #[test]
+    fn should_add_l1_gas_on_arbitrum() {
+        assert_eq!(
+            withdrawal_gas_limit(
+                CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT,
+                EthereumNetwork::ArbitrumOne,
+                None
+            ),
+            None
+        );
+        assert_eq!(
+            withdrawal_gas_limit(
+                CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT,
+                EthereumNetwork::ArbitrumOne,
+                Some(GasAmount::new(30_000))
+            ),
+            Some(GasAmount::new(21_000 + 30_000))
+        );
+        assert_eq!(
+            withdrawal_l1_data_fee(EthereumNetwork::ArbitrumOne.is_op_stack(), None),
+            Some(Wei::ZERO)
+        );
+    }
+
+    #[test]
+    fn should_charge_l1_data_fee_separately_on_op_stack() {
+        for network in [EthereumNetwork::Base, EthereumNetwork::Optimism] {
+            assert_eq!(
+                withdrawal_gas_limit(CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT, network, None),
+                Some(CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT)
+            );
+            assert_eq!(withdrawal_l1_data_fee(network.is_op_stack(), None), None);
+            assert_eq!(
+                withdrawal_l1_data_fee(network.is_op_stack(), Some(Wei::new(10_000_000_001))),
+                Some(Wei::new(10_000_000_001))
+            );
+        }
+    }
+}
+
 fn arb_transaction_price() -> impl Strategy<Value = TransactionPrice> {
     use crate::numeric::WeiPerGas;
     use crate::test_fixtures::arb::arb_checked_amount_of;
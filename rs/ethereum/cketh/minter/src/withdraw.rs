@@ -1,14 +1,16 @@
 use crate::{
     eth_logs::LedgerSubaccount,
     eth_rpc_client::{
-        AnyOf, MIN_ATTACHED_CYCLES, MinByKey, MultiCallError, NoReduction, ToReducedWithStrategy,
+        AnyOf, MIN_ATTACHED_CYCLES, MinByKey, MultiCallError, NoReduction, OpStackRpcConfig,
+        ToReducedWithStrategy, get_op_stack_transaction_receipt, responses::TransactionReceipt,
         rpc_client,
     },
     guard::TimerGuard,
+    lifecycle::EthereumNetwork,
     logs::{DEBUG, INFO},
-    numeric::{GasAmount, LedgerBurnIndex, LedgerMintIndex, TransactionCount},
+    numeric::{GasAmount, LedgerBurnIndex, LedgerMintIndex, TransactionCount, Wei},
     state::{
-        State, TaskType,
+        TaskType,
         audit::{EventType, process_event},
         minter_address, mutate_state, read_state,
         transactions::{
@@ -19,9 +21,7 @@ use crate::{
     tx::{GasFeeEstimate, lazy_refresh_gas_fee_estimate},
 };
 use candid::Nat;
-use evm_rpc_types::{
-    BlockTag, SendRawTransactionStatus, TransactionReceipt as EvmTransactionReceipt,
-};
+use evm_rpc_types::{BlockTag, SendRawTransactionStatus};
 use futures::future::join_all;
 use ic_canister_log::log;
 use icrc_ledger_client_cdk::{CdkRuntime, ICRC1Client};
@@ -252,14 +252,34 @@ fn create_transactions_batch(gas_fee_estimate: GasFeeEstimate) {
             .withdrawal_requests_batch(WITHDRAWAL_REQUESTS_BATCH_SIZE)
     }) {
         log!(DEBUG, "[create_transactions_batch]: processing {request:?}",);
-        let ethereum_network = read_state(State::ethereum_network);
+        let (ethereum_network, is_op_stack) =
+            read_state(|s| (s.ethereum_network(), s.is_op_stack()));
         let nonce = read_state(|s| s.eth_transactions.next_transaction_nonce());
-        let gas_limit = estimate_gas_limit(&request);
+        let (l1_data_fee_estimate, l1_data_gas_estimate) =
+            read_state(|s| (s.last_l1_data_fee_estimate, s.last_l1_data_gas_estimate));
+        let (gas_limit, l1_data_fee) = match (
+            withdrawal_gas_limit(
+                estimate_gas_limit(&request),
+                ethereum_network,
+                l1_data_gas_estimate,
+            ),
+            withdrawal_l1_data_fee(is_op_stack, l1_data_fee_estimate),
+        ) {
+            (Some(gas_limit), Some(l1_data_fee)) => (gas_limit, l1_data_fee),
+            _ => {
+                log!(
+                    INFO,
+                    "[create_transactions_batch]: Failed to determine L1 data costs for {request:?} on {ethereum_network}",
+                );
+                return;
+            }
+        };
         match create_transaction(
             &request,
             nonce,
             gas_fee_estimate.clone(),
             gas_limit,
+            l1_data_fee,
             ethereum_network,
         ) {
             Ok(transaction) => {
@@ -274,6 +294,7 @@ fn create_transactions_batch(gas_fee_estimate: GasFeeEstimate) {
                         EventType::CreatedTransaction {
                             withdrawal_id: request.cketh_ledger_burn_index(),
                             transaction,
+                            l1_data_fee: Some(l1_data_fee).filter(|fee| *fee > Wei::ZERO),
                         },
                     );
                 });
@@ -300,6 +321,37 @@ pub fn estimate_gas_limit(withdrawal_request: &WithdrawalRequest) -> GasAmount {
     }
 }
 
+/// Gas limit of a withdrawal transaction on the given chain.
+///
+/// On Arbitrum, the cost of posting the transaction data to L1 is charged as additional L2 gas,
+/// see <https://docs.arbitrum.io/build-decentralized-apps/how-to-estimate-gas>.
+///
+/// Returns `None` if the L1 data gas is required but unknown.
+pub fn withdrawal_gas_limit(
+    execution_gas_limit: GasAmount,
+    ethereum_network: EthereumNetwork,
+    l1_data_gas: Option<GasAmount>,
+) -> Option<GasAmount> {
+    match ethereum_network {
+        EthereumNetwork::ArbitrumOne => execution_gas_limit.checked_add(l1_data_gas?),
+        _ => Some(execution_gas_limit),
+    }
+}
+
+/// L1 data fee charged for a withdrawal transaction, depending on whether the chain is an OP Stack rollup.
+///
+/// OP Stack rollups charge an L1 data fee on top of the L2 execution fee, which is independent of the gas limit,
+/// see <https://docs.optimism.io/stack/transactions/fees#l1-data-fee>. It is zero on other chains.
+///
+/// Returns `None` if the L1 data fee is required but unknown.
+pub fn withdrawal_l1_data_fee(is_op_stack: bool, l1_data_fee: Option<Wei>) -> Option<Wei> {
+    if is_op_stack {
+        l1_data_fee
+    } else {
+        Some(Wei::ZERO)
+    }
+}
+
 async fn sign_transactions_batch() {
     let transactions_batch: Vec<_> = read_state(|s| {
         s.eth_transactions
@@ -397,16 +449,24 @@ async fn finalize_transactions_batch() {
             let expected_finalized_withdrawal_ids: BTreeSet<_> =
                 txs_to_finalize.values().cloned().collect();
             let rpc_client = read_state(rpc_client);
+            let op_stack_rpc_config = read_state(OpStackRpcConfig::from_state);
             let results = join_all(txs_to_finalize.keys().map(async |hash| {
-                rpc_client
-                    .get_transaction_receipt(*hash)
-                    .with_cycles(MIN_ATTACHED_CYCLES)
-                    .send()
-                    .await
-                    .reduce_with_strategy(NoReduction)
+                if let Some(op_stack_rpc_config) = &op_stack_rpc_config {
+                    get_op_stack_transaction_receipt(op_stack_rpc_config, *hash)
+                        .await
+                        .reduce_with_strategy(NoReduction)
+                } else {
+                    rpc_client
+                        .get_transaction_receipt(*hash)
+                        .with_cycles(MIN_ATTACHED_CYCLES)
+                        .send()
+                        .await
+                        .map(|receipt| receipt.map(TransactionReceipt::from))
+                        .reduce_with_strategy(NoReduction)
+                }
             }))
             .await;
-            let mut receipts: BTreeMap<LedgerBurnIndex, EvmTransactionReceipt> = BTreeMap::new();
+            let mut receipts: BTreeMap<LedgerBurnIndex, TransactionReceipt> = BTreeMap::new();
             for ((hash, withdrawal_id), result) in zip(txs_to_finalize, results) {
                 match result {
                     Ok(Some(receipt)) => {
@@ -454,7 +514,7 @@ async fn finalize_transactions_batch() {
                         s,
                         EventType::FinalizedTransaction {
                             withdrawal_id,
-                            transaction_receipt: transaction_receipt.into(),
+                            transaction_receipt,
                         },
                     );
                 });
@@ -166,6 +166,7 @@ fn should_deposit_and_withdraw() {
                     status: TransactionStatus::Success,
                     transaction_hash:
                     "0x2cf1763e8ee3990103a31a5709b17b83f167738abb400844e67f608a98b0bdb5".to_string(),
+                    l1_fee: None,
                 },
             },
         ]);
@@ -586,6 +587,7 @@ fn should_reimburse() {
                     status: TransactionStatus::Failure,
                     transaction_hash:
                     "0x2cf1763e8ee3990103a31a5709b17b83f167738abb400844e67f608a98b0bdb5".to_string(),
+                    l1_fee: None,
                 },
             },
             EventPayload::ReimbursedEthWithdrawal {
@@ -693,6 +695,7 @@ fn should_resubmit_new_transaction_when_price_increased() {
                     gas_used: Nat::from(21_000_u32),
                     status: TransactionStatus::Success,
                     transaction_hash: format!("{resubmitted_tx_hash:?}"),
+                    l1_fee: None,
                 },
             },
         ]);
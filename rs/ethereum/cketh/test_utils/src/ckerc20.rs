@@ -91,6 +91,7 @@ impl CkErc20Setup {
                 more_controller_ids: vec![],
                 minter_id: Some(cketh.minter_id.get_ref().0),
                 cycles_management: None,
+                additional_minters: None,
             },
         )
         .register_embedded_wasms();
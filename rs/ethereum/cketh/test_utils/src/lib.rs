@@ -691,6 +691,8 @@ fn install_minter(
         minimum_withdrawal_amount: CKETH_MINIMUM_WITHDRAWAL_AMOUNT.into(),
         last_scraped_block_number: LAST_SCRAPED_BLOCK_NUMBER_AT_INSTALL.into(),
         evm_rpc_id: Some(evm_rpc_id.into()),
+        evm_rpc_custom_provider_urls: None,
+        custom_chain_is_op_stack: None,
     };
     let minter_arg = MinterArg::InitArg(args);
     env.install_existing_canister(minter_id, minter_wasm(), Encode!(&minter_arg).unwrap())
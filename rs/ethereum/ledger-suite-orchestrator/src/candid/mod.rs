@@ -16,6 +16,7 @@ pub struct InitArg {
     pub more_controller_ids: Vec<Principal>,
     pub minter_id: Option<Principal>,
     pub cycles_management: Option<CyclesManagement>,
+    pub additional_minters: Option<Vec<AdditionalMinter>>,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize)]
@@ -26,6 +27,7 @@ pub struct UpgradeArg {
     pub archive_compressed_wasm_hash: Option<String>,
     pub cycles_management: Option<UpdateCyclesManagement>,
     pub manage_ledger_suites: Option<Vec<InstalledLedgerSuite>>,
+    pub additional_minters: Option<Vec<AdditionalMinter>>,
 }
 
 impl UpgradeArg {
@@ -36,6 +38,12 @@ impl UpgradeArg {
     }
 }
 
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct AdditionalMinter {
+    pub chain_id: Nat,
+    pub minter_id: Principal,
+}
+
 #[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
 pub struct AddErc20Arg {
     pub contract: Erc20Contract,
@@ -268,6 +276,7 @@ pub struct OrchestratorInfo {
     pub minter_id: Option<Principal>,
     pub ledger_suite_version: Option<LedgerSuiteVersion>,
     pub managed_pre_existing_ledger_suites: Option<Vec<ManagedLedgerSuite>>,
+    pub additional_minters: Option<Vec<AdditionalMinter>>,
 }
 
 #[derive(
@@ -284,6 +284,7 @@ fn initial_state() -> State {
         more_controller_ids: vec![],
         minter_id: None,
         cycles_management: None,
+        additional_minters: None,
     })
     .unwrap()
 }
@@ -1,7 +1,8 @@
 use crate::candid::{AddErc20Arg, InitArg, UpgradeArg};
 use crate::logs::INFO;
 use crate::scheduler::{
-    IC_CANISTER_RUNTIME, InstallLedgerSuiteArgs, Task, UpgradeOrchestratorArgs, schedule_now,
+    ChainId, IC_CANISTER_RUNTIME, InstallLedgerSuiteArgs, Task, UpgradeOrchestratorArgs,
+    schedule_now,
 };
 use crate::state::{
     GitCommitHash, InstalledLedgerSuite, State, init_state, mutate_state, read_state,
@@ -46,6 +47,19 @@ pub fn post_upgrade(upgrade_arg: Option<UpgradeArg>) {
                 );
             }
         }
+        if let Some(additional_minters) = arg.additional_minters.clone() {
+            for minter in additional_minters {
+                let chain_id = ChainId::try_from(minter.chain_id)
+                    .expect("ERROR: invalid chain id of additional minter");
+                log!(
+                    INFO,
+                    "[post_upgrade]: recorded minter {} for chain {}",
+                    minter.minter_id,
+                    chain_id.as_ref()
+                );
+                mutate_state(|s| s.record_additional_minter(chain_id, minter.minter_id));
+            }
+        }
         match read_wasm_store(|w| UpgradeOrchestratorArgs::validate_upgrade_arg(w, arg.clone())) {
             Ok(valid_upgrade_args) => {
                 if valid_upgrade_args.upgrade_ledger_suite() {
@@ -1,10 +1,13 @@
 #![allow(deprecated)]
+use candid::Nat;
 use ic_cdk::api::management_canister::main::{
     CanisterIdRecord, CanisterStatusResponse, canister_status,
 };
 use ic_cdk::{init, post_upgrade, query, update};
 use ic_ledger_suite_orchestrator::candid::Erc20Contract as CandidErc20Contract;
-use ic_ledger_suite_orchestrator::candid::{ManagedCanisterIds, OrchestratorArg, OrchestratorInfo};
+use ic_ledger_suite_orchestrator::candid::{
+    AdditionalMinter, ManagedCanisterIds, OrchestratorArg, OrchestratorInfo,
+};
 use ic_ledger_suite_orchestrator::lifecycle;
 use ic_ledger_suite_orchestrator::scheduler::{
     Erc20Token, IC_CANISTER_RUNTIME, encode_orchestrator_metrics,
@@ -54,6 +57,21 @@ fn get_orchestrator_info() -> OrchestratorInfo {
                     Some(canisters)
                 }
             },
+            additional_minters: {
+                let minters: Vec<_> = s
+                    .additional_minters()
+                    .iter()
+                    .map(|(chain_id, minter_id)| AdditionalMinter {
+                        chain_id: Nat::from(*chain_id.as_ref()),
+                        minter_id: *minter_id,
+                    })
+                    .collect();
+                if minters.is_empty() {
+                    None
+                } else {
+                    Some(minters)
+                }
+            },
         }
     })
 }
@@ -550,13 +550,13 @@ impl InstallLedgerSuiteArgs {
         let contract = Erc20Token::try_from(args.contract.clone())
             .map_err(|e| InvalidAddErc20ArgError::InvalidErc20Contract(e.to_string()))?;
         let token_id = TokenId::from(contract.clone());
-        let minter_id =
-            state
-                .minter_id()
-                .cloned()
-                .ok_or(InvalidAddErc20ArgError::InternalError(
-                    "ERROR: minter principal not set in state".to_string(),
-                ))?;
+        let minter_id = state
+            .minter_id_for_chain(contract.chain_id())
+            .cloned()
+            .ok_or(InvalidAddErc20ArgError::InternalError(format!(
+                "ERROR: no minter principal set in state for chain {}",
+                contract.chain_id().as_ref()
+            )))?;
         if let Some(_canisters) = state.managed_canisters(&token_id) {
             return Err(InvalidAddErc20ArgError::Erc20ContractAlreadyManaged(
                 contract,
@@ -872,18 +872,13 @@ async fn install_ledger_suite<R: CanisterRuntime>(
         runtime,
     )
     .await?;
-    read_state(|s| {
-        let erc20_token = args.erc20_contract().clone();
-        if let Some(&minter_id) = s.minter_id() {
-            schedule_now(
-                Task::NotifyErc20Added {
-                    erc20_token,
-                    minter_id,
-                },
-                runtime,
-            );
-        }
-    });
+    schedule_now(
+        Task::NotifyErc20Added {
+            erc20_token: args.erc20_contract().clone(),
+            minter_id: args.minter_id,
+        },
+        runtime,
+    );
     Ok(())
 }
 
@@ -1340,12 +1335,31 @@ impl AsRef<u64> for ChainId {
     }
 }
 
+impl ChainId {
+    /// Whether the chain is Ethereum Mainnet or the Ethereum Sepolia testnet.
+    pub fn is_ethereum(&self) -> bool {
+        matches!(self.0, 1 | 11_155_111)
+    }
+}
+
+impl TryFrom<Nat> for ChainId {
+    type Error = String;
+
+    fn try_from(chain_id: Nat) -> Result<Self, Self::Error> {
+        chain_id
+            .0
+            .to_u64()
+            .map(Self)
+            .ok_or_else(|| "chain_id is not u64".to_string())
+    }
+}
+
 impl TryFrom<crate::candid::Erc20Contract> for Erc20Token {
     type Error = String;
 
     fn try_from(contract: crate::candid::Erc20Contract) -> Result<Self, Self::Error> {
         Ok(Self(
-            ChainId(contract.chain_id.0.to_u64().ok_or("chain_id is not u64")?),
+            ChainId::try_from(contract.chain_id)?,
             Address::from_str(&contract.address)?,
         ))
     }
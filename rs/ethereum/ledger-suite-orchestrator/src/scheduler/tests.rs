@@ -146,6 +146,7 @@ async fn should_install_ledger_suite_with_additional_controllers() {
             more_controller_ids: vec![OTHER_PRINCIPAL],
             minter_id: None,
             cycles_management: None,
+            additional_minters: None,
         })
         .unwrap(),
     );
@@ -1748,7 +1749,7 @@ mod mock {
 }
 
 mod install_ledger_suite_args {
-    use crate::candid::{AddErc20Arg, InitArg, LedgerInitArg};
+    use crate::candid::{AddErc20Arg, AdditionalMinter, InitArg, LedgerInitArg};
     use crate::scheduler::tests::{MINTER_PRINCIPAL, usdc_metadata};
     use crate::scheduler::{ChainId, Erc20Token, InstallLedgerSuiteArgs, InvalidAddErc20ArgError};
     use crate::state::test_fixtures::{expect_panic_with_message, new_state, new_state_from};
@@ -1758,7 +1759,7 @@ mod install_ledger_suite_args {
     };
     use crate::storage::{WasmStore, record_icrc1_ledger_suite_wasms};
     use assert_matches::assert_matches;
-    use candid::Nat;
+    use candid::{Nat, Principal};
     use proptest::collection::vec;
     use proptest::{prop_assert_eq, proptest};
 
@@ -1934,6 +1935,48 @@ mod install_ledger_suite_args {
         );
     }
 
+    #[test]
+    fn should_use_minter_of_erc20_chain() {
+        const ARBITRUM_MINTER_PRINCIPAL: Principal = Principal::from_slice(&[10_u8; 29]);
+        let mut state = new_state_from(InitArg {
+            minter_id: Some(MINTER_PRINCIPAL),
+            additional_minters: Some(vec![AdditionalMinter {
+                chain_id: Nat::from(42_161_u64),
+                minter_id: ARBITRUM_MINTER_PRINCIPAL,
+            }]),
+            ..Default::default()
+        });
+        let wasm_store = wasm_store_with_icrc1_ledger_suite();
+        state.update_ledger_suite_version(embedded_ledger_suite_version());
+        let mut arg = valid_add_erc20_arg();
+        arg.contract.chain_id = Nat::from(42_161_u64);
+
+        let result = InstallLedgerSuiteArgs::validate_add_erc20(&state, &wasm_store, arg).unwrap();
+
+        assert_eq!(
+            result.contract,
+            Erc20Token(ChainId(42_161), ERC20_CONTRACT_ADDRESS.parse().unwrap())
+        );
+        assert_eq!(result.minter_id, ARBITRUM_MINTER_PRINCIPAL);
+    }
+
+    #[test]
+    fn should_error_when_chain_has_no_minter() {
+        let mut state = new_state_from(InitArg {
+            minter_id: Some(MINTER_PRINCIPAL),
+            ..Default::default()
+        });
+        let wasm_store = wasm_store_with_icrc1_ledger_suite();
+        state.update_ledger_suite_version(embedded_ledger_suite_version());
+        let mut arg = valid_add_erc20_arg();
+        arg.contract.chain_id = Nat::from(8_453_u64);
+
+        assert_matches!(
+            InstallLedgerSuiteArgs::validate_add_erc20(&state, &wasm_store, arg),
+            Err(InvalidAddErc20ArgError::InternalError(_))
+        );
+    }
+
     fn valid_add_erc20_arg() -> AddErc20Arg {
         AddErc20Arg {
             contract: crate::candid::Erc20Contract {
@@ -4,7 +4,7 @@ pub mod test_fixtures;
 mod tests;
 
 use crate::candid::{CyclesManagement, InitArg};
-use crate::scheduler::{Erc20Token, InvalidManageInstalledCanistersError, Task};
+use crate::scheduler::{ChainId, Erc20Token, InvalidManageInstalledCanistersError, Task};
 use crate::storage::WasmHashError;
 use crate::storage::memory::{StableMemory, state_memory};
 use candid::Principal;
@@ -523,6 +523,10 @@ pub struct State {
     cycles_management: CyclesManagement,
     more_controller_ids: Vec<Principal>,
     minter_id: Option<Principal>,
+    /// Minters of other EVM chains, e.g., layer-2 networks.
+    /// Tokens on Ethereum without an entry use `minter_id`.
+    #[serde(default)]
+    additional_minters: BTreeMap<ChainId, Principal>,
     /// Locks preventing concurrent execution timer tasks
     pub active_tasks: BTreeSet<Task>,
     #[serde(default)]
@@ -538,6 +542,28 @@ impl State {
         self.minter_id.as_ref()
     }
 
+    pub fn additional_minters(&self) -> &BTreeMap<ChainId, Principal> {
+        &self.additional_minters
+    }
+
+    /// Returns the minter that handles ERC-20 tokens on the given chain.
+    ///
+    /// `minter_id` is the ckETH minter and therefore only handles tokens on Ethereum,
+    /// the minters of other chains must be registered as additional minters.
+    pub fn minter_id_for_chain(&self, chain_id: &ChainId) -> Option<&Principal> {
+        self.additional_minters.get(chain_id).or_else(|| {
+            if chain_id.is_ethereum() {
+                self.minter_id.as_ref()
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn record_additional_minter(&mut self, chain_id: ChainId, minter_id: Principal) {
+        self.additional_minters.insert(chain_id, minter_id);
+    }
+
     pub fn cycles_management(&self) -> &CyclesManagement {
         &self.cycles_management
     }
@@ -763,6 +789,7 @@ impl ManageSingleCanister<Index> for Canisters {
 #[derive(Eq, PartialEq, Debug)]
 pub enum InvalidStateError {
     TooManyAdditionalControllers { max: usize, actual: usize },
+    InvalidAdditionalMinter(String),
 }
 
 impl TryFrom<InitArg> for State {
@@ -772,14 +799,25 @@ impl TryFrom<InitArg> for State {
             more_controller_ids,
             minter_id,
             cycles_management,
+            additional_minters,
         }: InitArg,
     ) -> Result<Self, Self::Error> {
+        let additional_minters = additional_minters
+            .unwrap_or_default()
+            .into_iter()
+            .map(|minter| {
+                ChainId::try_from(minter.chain_id)
+                    .map(|chain_id| (chain_id, minter.minter_id))
+                    .map_err(InvalidStateError::InvalidAdditionalMinter)
+            })
+            .collect::<Result<_, _>>()?;
         let state = Self {
             managed_canisters: Default::default(),
             completed_upgrades: Default::default(),
             cycles_management: cycles_management.unwrap_or_default(),
             more_controller_ids,
             minter_id,
+            additional_minters,
             ledger_suite_version: Default::default(),
             active_tasks: Default::default(),
         };
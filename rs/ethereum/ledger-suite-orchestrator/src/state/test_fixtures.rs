@@ -1,4 +1,4 @@
-use crate::candid::{CyclesManagement, InitArg};
+use crate::candid::{AdditionalMinter, CyclesManagement, InitArg};
 use crate::state::State;
 use candid::Principal;
 use proptest::arbitrary::any;
@@ -47,16 +47,25 @@ pub fn arb_init_arg(size: impl Into<SizeRange>) -> impl Strategy<Value = InitArg
         vec(arb_principal(), size),
         option::of(arb_principal()),
         option::of(arb_cycles_management()),
+        option::of(vec(arb_additional_minter(), 0..=3)),
     )
         .prop_map(
-            |(more_controller_ids, minter_id, cycles_management)| InitArg {
+            |(more_controller_ids, minter_id, cycles_management, additional_minters)| InitArg {
                 more_controller_ids,
                 minter_id,
                 cycles_management,
+                additional_minters,
             },
         )
 }
 
+fn arb_additional_minter() -> impl Strategy<Value = AdditionalMinter> {
+    (any::<u64>(), arb_principal()).prop_map(|(chain_id, minter_id)| AdditionalMinter {
+        chain_id: candid::Nat::from(chain_id),
+        minter_id,
+    })
+}
+
 pub fn arb_principal() -> impl Strategy<Value = Principal> {
     vec(any::<u8>(), 0..=29).prop_map(|bytes| Principal::from_slice(&bytes))
 }
@@ -370,9 +370,10 @@ mod git_commit_hash {
 }
 
 mod validate_config {
-    use crate::candid::InitArg;
+    use crate::candid::{AdditionalMinter, InitArg};
     use crate::state::test_fixtures::{arb_init_arg, arb_principal};
     use crate::state::{InvalidStateError, State};
+    use candid::{Nat, Principal};
     use proptest::collection::vec;
     use proptest::proptest;
 
@@ -390,6 +391,7 @@ mod validate_config {
                 more_controller_ids: additional_controllers.clone(),
                 minter_id: None,
                 cycles_management: None,
+                additional_minters: None,
             };
 
             let result = State::try_from(init_arg);
@@ -397,6 +399,93 @@ mod validate_config {
            assert_eq!(result, Err(InvalidStateError::TooManyAdditionalControllers{max: 9, actual: additional_controllers.len()}));
         }
     }
+
+    #[test]
+    fn should_error_when_additional_minter_chain_id_is_not_u64() {
+        let init_arg = InitArg {
+            additional_minters: Some(vec![AdditionalMinter {
+                chain_id: Nat::from(u128::from(u64::MAX) + 1),
+                minter_id: Principal::management_canister(),
+            }]),
+            ..Default::default()
+        };
+
+        let result = State::try_from(init_arg);
+
+        assert_eq!(
+            result,
+            Err(InvalidStateError::InvalidAdditionalMinter(
+                "chain_id is not u64".to_string()
+            ))
+        );
+    }
+}
+
+mod minter_id_for_chain {
+    use crate::candid::{AdditionalMinter, InitArg};
+    use crate::scheduler::ChainId;
+    use crate::state::State;
+    use candid::{Nat, Principal};
+
+    const ETHEREUM_MINTER: Principal = Principal::from_slice(&[0_u8; 29]);
+    const ARBITRUM_MINTER: Principal = Principal::from_slice(&[1_u8; 29]);
+    const BASE_MINTER: Principal = Principal::from_slice(&[2_u8; 29]);
+
+    #[test]
+    fn should_default_to_minter_id_on_ethereum() {
+        let state = State::try_from(InitArg {
+            minter_id: Some(ETHEREUM_MINTER),
+            additional_minters: Some(vec![AdditionalMinter {
+                chain_id: Nat::from(42_161_u64),
+                minter_id: ARBITRUM_MINTER,
+            }]),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(
+            state.minter_id_for_chain(&chain_id(1)),
+            Some(&ETHEREUM_MINTER)
+        );
+        assert_eq!(
+            state.minter_id_for_chain(&chain_id(11_155_111)),
+            Some(&ETHEREUM_MINTER)
+        );
+        assert_eq!(
+            state.minter_id_for_chain(&chain_id(42_161)),
+            Some(&ARBITRUM_MINTER)
+        );
+    }
+
+    #[test]
+    fn should_not_default_to_minter_id_on_other_chains() {
+        let state = State::try_from(InitArg {
+            minter_id: Some(ETHEREUM_MINTER),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(state.minter_id_for_chain(&chain_id(8_453)), None);
+        assert_eq!(state.minter_id_for_chain(&chain_id(81_457)), None);
+    }
+
+    #[test]
+    fn should_return_none_when_no_minter() {
+        let mut state = State::try_from(InitArg::default()).unwrap();
+        assert_eq!(state.minter_id_for_chain(&chain_id(1)), None);
+
+        state.record_additional_minter(chain_id(8_453), BASE_MINTER);
+
+        assert_eq!(state.minter_id_for_chain(&chain_id(1)), None);
+        assert_eq!(
+            state.minter_id_for_chain(&chain_id(8_453)),
+            Some(&BASE_MINTER)
+        );
+    }
+
+    fn chain_id(id: u64) -> ChainId {
+        ChainId::try_from(Nat::from(id)).unwrap()
+    }
 }
 
 mod schema_upgrades {
@@ -484,6 +573,7 @@ mod schema_upgrades {
                 cycles_management,
                 more_controller_ids,
                 minter_id,
+                additional_minters: _,
                 active_tasks,
                 ledger_suite_version,
             }: State,
@@ -514,6 +604,7 @@ mod schema_upgrades {
                 state_after_upgrade.ledger_suite_version,
                 None
             );
+            assert!(state_after_upgrade.additional_minters.is_empty());
         }
     }
 }
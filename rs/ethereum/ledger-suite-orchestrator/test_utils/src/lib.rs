@@ -120,6 +120,7 @@ impl LedgerSuiteOrchestrator {
                 archive_compressed_wasm_hash: None,
                 cycles_management: None,
                 manage_ledger_suites: None,
+                additional_minters: None,
             },
         ))
     }
@@ -195,6 +196,7 @@ impl LedgerSuiteOrchestrator {
                 archive_compressed_wasm_hash: None,
                 cycles_management: None,
                 manage_ledger_suites: Some(manage_installed_canister),
+                additional_minters: None,
             },
         ))
     }
@@ -354,6 +356,7 @@ pub fn default_init_arg() -> InitArg {
         more_controller_ids: vec![NNS_ROOT_PRINCIPAL],
         minter_id: Some(MINTER_PRINCIPAL),
         cycles_management: None,
+        additional_minters: None,
     }
 }
 
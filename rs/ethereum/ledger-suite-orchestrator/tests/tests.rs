@@ -88,6 +88,14 @@ fn should_spawn_ledger_with_correct_init_args() {
                 "icrc103:max_take_value".to_string(),
                 LedgerMetadataValue::from(500u64),
             ),
+            (
+                "icrc4:max_update_batch_size".to_string(),
+                LedgerMetadataValue::from(1000u64),
+            ),
+            (
+                "icrc4:max_query_batch_size".to_string(),
+                LedgerMetadataValue::from(1000u64),
+            ),
             (
                 "icrc106:index_principal".to_string(),
                 LedgerMetadataValue::from("ryjl3-tyaaa-aaaaa-aaaba-cai"),
@@ -119,6 +127,7 @@ fn should_change_cycles_for_canister_creation() {
                     ..Default::default()
                 }),
                 manage_ledger_suites: None,
+                additional_minters: None,
             },
         ))
         .unwrap();
@@ -267,6 +276,7 @@ fn should_reject_upgrade_with_invalid_args() {
         archive_compressed_wasm_hash: None,
         cycles_management: None,
         manage_ledger_suites: None,
+        additional_minters: None,
     };
 
     test_upgrade_with_invalid_args(
@@ -404,6 +414,7 @@ fn should_retrieve_orchestrator_info() {
                 archive_compressed_wasm_hash: embedded_archive_wasm_hash.to_string(),
             }),
             managed_pre_existing_ledger_suites: None,
+            additional_minters: None,
         }
     );
 
@@ -538,6 +549,7 @@ fn should_not_change_ledger_suite_version_when_registering_embedded_wasms_a_seco
             archive_compressed_wasm_hash: None,
             cycles_management: None,
             manage_ledger_suites: None,
+            additional_minters: None,
         },
     );
 
@@ -652,6 +664,7 @@ mod upgrade {
                 archive_compressed_wasm_hash: None,
                 cycles_management: None,
                 manage_ledger_suites: None,
+                additional_minters: None,
             },
         );
 
@@ -733,6 +746,7 @@ mod upgrade {
                 archive_compressed_wasm_hash: Some(embedded_archive_wasm_hash.to_string()),
                 cycles_management: None,
                 manage_ledger_suites: None,
+                additional_minters: None,
             },
         );
         orchestrator.advance_time_for_upgrade();
@@ -829,6 +843,7 @@ mod upgrade {
                 archive_compressed_wasm_hash: Some(embedded_archive_wasm_hash.to_string()),
                 cycles_management: None,
                 manage_ledger_suites: None,
+                additional_minters: None,
             },
         );
 
@@ -900,6 +915,7 @@ mod upgrade {
                 archive_compressed_wasm_hash: None,
                 cycles_management: None,
                 manage_ledger_suites: None,
+                additional_minters: None,
             },
         );
 
@@ -962,6 +978,7 @@ mod upgrade {
                     archive_compressed_wasm_hash: None,
                     cycles_management: None,
                     manage_ledger_suites: None,
+                    additional_minters: None,
                 },
             );
 
@@ -1038,6 +1055,7 @@ mod upgrade {
                 archive_compressed_wasm_hash: Some(embedded_archive_wasm_hash.to_string()),
                 cycles_management: None,
                 manage_ledger_suites: None,
+                additional_minters: None,
             },
         );
 
@@ -1126,6 +1144,7 @@ mod upgrade {
                     index: index.clone(),
                     archives: None,
                 }]),
+                additional_minters: None,
             },
         );
 
@@ -1,12 +1,13 @@
 use ic_error_types::{ErrorCode, UserError};
 use ic_management_canister_types_private::{
-    BitcoinGetSuccessorsArgs, BitcoinGetSuccessorsResponse, BitcoinSendTransactionInternalArgs,
-    Payload,
+    BitcoinGetBlockFiltersArgs, BitcoinGetSuccessorsArgs, BitcoinGetSuccessorsResponse,
+    BitcoinSendTransactionInternalArgs, Payload,
 };
 use ic_replicated_state::{
     ReplicatedState,
     metadata_state::subnet_call_context_manager::{
-        BitcoinGetSuccessorsContext, BitcoinSendTransactionInternalContext, SubnetCallContext,
+        BitcoinGetBlockFiltersContext, BitcoinGetSuccessorsContext,
+        BitcoinSendTransactionInternalContext, SubnetCallContext,
     },
 };
 use ic_types::{CanisterId, messages::Request};
@@ -109,10 +110,42 @@ pub fn send_transaction_internal(
     }
 }
 
+/// Handles a `bitcoin_get_block_filters` request.
+/// Returns Ok if the request has been accepted, and an error otherwise.
+pub fn get_block_filters(
+    privileged_access: &[CanisterId],
+    request: &Request,
+    state: &mut ReplicatedState,
+) -> Result<(), UserError> {
+    if !privileged_access.contains(&request.sender()) {
+        return Err(UserError::new(
+            ErrorCode::CanisterRejectedMessage,
+            String::from("Permission denied."),
+        ));
+    }
+
+    match BitcoinGetBlockFiltersArgs::decode(request.method_payload()) {
+        Ok(get_block_filters_request) => {
+            // Insert request into subnet call contexts.
+            state.metadata.subnet_call_context_manager.push_context(
+                SubnetCallContext::BitcoinGetBlockFilters(BitcoinGetBlockFiltersContext {
+                    request: request.clone(),
+                    payload: get_block_filters_request,
+                    time: state.time(),
+                }),
+            );
+
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use ic_management_canister_types_private::{
-        BitcoinGetSuccessorsArgs, IC_00, Method, Payload as Ic00Payload,
+        BitcoinGetBlockFiltersArgs, BitcoinGetSuccessorsArgs, BitcoinNetwork, IC_00, Method,
+        Payload as Ic00Payload,
     };
     use ic_test_utilities::universal_canister::{call_args, wasm};
     use ic_test_utilities_execution_environment::ExecutionTestBuilder;
@@ -163,4 +196,45 @@ mod tests {
             maplit::btreemap! { bitcoin_canister_id => vec![vec![1], vec![2]] }
         );
     }
+
+    #[test]
+    fn get_block_filters_pushes_a_subnet_call_context() {
+        let bitcoin_canister_id = CanisterId::unchecked_from_principal(
+            PrincipalId::from_str("rwlgt-iiaaa-aaaaa-aaaaa-cai").unwrap(),
+        );
+
+        let mut test = ExecutionTestBuilder::new()
+            .with_bitcoin_privileged_access(bitcoin_canister_id)
+            .with_provisional_whitelist_all()
+            .build();
+
+        let uni = test.universal_canister().unwrap();
+        assert_eq!(uni.get_ref(), &bitcoin_canister_id.get());
+
+        let args = BitcoinGetBlockFiltersArgs {
+            network: BitcoinNetwork::BitcoinRegtest,
+            block_hashes: vec![vec![1; 32]],
+            scripts: vec![serde_bytes::ByteBuf::from(vec![0x51])],
+        };
+        let call = wasm()
+            .call_simple(
+                IC_00,
+                Method::BitcoinGetBlockFilters,
+                call_args().other_side(args.encode()),
+            )
+            .build();
+
+        // The request stays pending until the adapter response is delivered in a batch.
+        let _ = test.ingress_raw(uni, "update", call);
+
+        let contexts = &test
+            .state()
+            .metadata
+            .subnet_call_context_manager
+            .bitcoin_get_block_filters_contexts;
+        assert_eq!(contexts.len(), 1);
+        let context = contexts.values().next().unwrap();
+        assert_eq!(context.payload, args);
+        assert_eq!(context.request.sender(), bitcoin_canister_id);
+    }
 }
@@ -1,10 +1,8 @@
-use std::collections::VecDeque;
-
 use ic_config::flag_status::FlagStatus;
 use ic_error_types::{ErrorCode, UserError};
 use ic_management_canister_types_private::{
-    CanisterLogRecord, FetchCanisterLogsFilter, FetchCanisterLogsRange, FetchCanisterLogsRequest,
-    FetchCanisterLogsResponse, LogVisibilityV2,
+    CanisterLogRecord, ControllerRole, ControllerRoles, FetchCanisterLogsFilter,
+    FetchCanisterLogsRange, FetchCanisterLogsRequest, FetchCanisterLogsResponse, LogVisibilityV2,
 };
 use ic_replicated_state::ReplicatedState;
 use ic_types::PrincipalId;
@@ -24,11 +22,23 @@ pub(crate) fn fetch_canister_logs(
     })?;
 
     // Check if the sender has permission to access logs
-    check_log_visibility_permission(&sender, canister.log_visibility(), canister.controllers())?;
+    check_log_visibility_permission(
+        &sender,
+        canister.log_visibility(),
+        canister.controllers(),
+        &canister.system_state.controller_roles,
+    )?;
 
-    let records = canister.system_state.canister_log.records();
+    // The severity and field filters only apply to structured records, which
+    // are gated by their own feature flag, so they are always applied.
+    let records = canister
+        .system_state
+        .canister_log
+        .records()
+        .iter()
+        .filter(|r| matches_structured_filters(&args, r));
     let canister_log_records = match fetch_canister_logs_filter {
-        FlagStatus::Disabled => records.iter().cloned().collect(),
+        FlagStatus::Disabled => records.cloned().collect(),
         FlagStatus::Enabled => filter_records(&args, records)?,
     };
 
@@ -38,16 +48,20 @@ pub(crate) fn fetch_canister_logs(
 }
 
 /// Checks if the caller has permission to access the logs based on the canister's log visibility settings.
+/// Controllers and principals holding the observer role can always access the logs.
 pub(crate) fn check_log_visibility_permission(
     caller: &PrincipalId,
     log_visibility: &LogVisibilityV2,
     controllers: &std::collections::BTreeSet<PrincipalId>,
+    controller_roles: &ControllerRoles,
 ) -> Result<(), UserError> {
+    let is_privileged =
+        controllers.contains(caller) || controller_roles.has_role(caller, ControllerRole::Observer);
     let has_access = match log_visibility {
         LogVisibilityV2::Public => true,
-        LogVisibilityV2::Controllers => controllers.contains(caller),
+        LogVisibilityV2::Controllers => is_privileged,
         LogVisibilityV2::AllowedViewers(principals) => {
-            principals.get().contains(caller) || controllers.contains(caller)
+            principals.get().contains(caller) || is_privileged
         }
     };
 
@@ -61,12 +75,12 @@ pub(crate) fn check_log_visibility_permission(
     }
 }
 
-fn filter_records(
+fn filter_records<'a>(
     args: &FetchCanisterLogsRequest,
-    records: &VecDeque<CanisterLogRecord>,
+    records: impl Iterator<Item = &'a CanisterLogRecord>,
 ) -> Result<Vec<CanisterLogRecord>, UserError> {
     let Some(filter) = &args.filter else {
-        return Ok(records.iter().cloned().collect());
+        return Ok(records.cloned().collect());
     };
 
     let (range, key): (&FetchCanisterLogsRange, fn(&CanisterLogRecord) -> u64) = match filter {
@@ -79,8 +93,25 @@ fn filter_records(
     }
 
     Ok(records
-        .iter()
         .filter(|r| range.contains(key(r)))
         .cloned()
         .collect())
 }
+
+/// Checks the minimum severity and the fields requested in `args`.
+/// Unstructured records (without a severity) never match these filters.
+fn matches_structured_filters(args: &FetchCanisterLogsRequest, record: &CanisterLogRecord) -> bool {
+    if args.min_severity.is_none() && args.fields.is_none() {
+        return true;
+    }
+    let Some(severity) = record.severity else {
+        return false;
+    };
+    args.min_severity
+        .is_none_or(|min_severity| severity >= min_severity)
+        && args
+            .fields
+            .iter()
+            .flatten()
+            .all(|field| record.fields().contains(field))
+}
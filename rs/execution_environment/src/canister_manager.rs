@@ -1,5 +1,7 @@
 use crate::as_round_instructions;
-use crate::execution::install_code::{OriginalContext, validate_controller};
+use crate::execution::install_code::{
+    OriginalContext, validate_controller, validate_controller_or_role, validate_install_code_sender,
+};
 use crate::execution::{install::execute_install, upgrade::execute_upgrade};
 use crate::execution_environment::{
     CompilationCostHandling, RoundContext, RoundCounters, RoundLimits,
@@ -8,6 +10,7 @@ use crate::util::MIGRATION_CANISTER_ID;
 use crate::{
     canister_settings::{CanisterSettings, ValidatedCanisterSettings},
     hypervisor::Hypervisor,
+    ic00_permissions::Ic00MethodPermissions,
     types::{IngressResponse, Response},
     util::GOVERNANCE_CANISTER_ID,
 };
@@ -26,7 +29,7 @@ use ic_logger::{ReplicaLogger, error, fatal, info};
 use ic_management_canister_types_private::{
     CanisterChangeDetails, CanisterChangeOrigin, CanisterInstallModeV2, CanisterMetadataResponse,
     CanisterSnapshotDataKind, CanisterSnapshotDataOffset, CanisterSnapshotResponse,
-    CanisterStatusResultV2, CanisterStatusType, ChunkHash, Global, GlobalTimer,
+    CanisterStatusResultV2, CanisterStatusType, ChunkHash, ControllerRole, Global, GlobalTimer,
     Method as Ic00Method, ReadCanisterSnapshotDataResponse, ReadCanisterSnapshotMetadataResponse,
     SnapshotSource, StoredChunksReply, UploadCanisterSnapshotDataArgs,
     UploadCanisterSnapshotMetadataArgs, UploadChunkReply,
@@ -72,6 +75,7 @@ use prometheus::IntCounter;
 use std::iter::zip;
 use std::path::PathBuf;
 use std::{convert::TryFrom, str::FromStr, sync::Arc};
+use strum::IntoEnumIterator;
 
 use types::*;
 pub(crate) mod types;
@@ -167,6 +171,7 @@ impl CanisterManager {
             | Ok(Ic00Method::BitcoinGetBlockHeaders)
             | Ok(Ic00Method::BitcoinSendTransaction)
             | Ok(Ic00Method::BitcoinSendTransactionInternal)
+            | Ok(Ic00Method::BitcoinGetBlockFilters)
             | Ok(Ic00Method::BitcoinGetCurrentFeePercentiles)
             | Ok(Ic00Method::NodeMetricsHistory)
             | Ok(Ic00Method::SubnetInfo)
@@ -177,8 +182,9 @@ impl CanisterManager {
             )),
 
             // These methods are only valid if they are sent by the controller
-            // of the canister. We assume that the canister always wants to
-            // accept messages from its controller.
+            // of the canister or by a principal holding the controller role
+            // granting access to the method. We assume that the canister always
+            // wants to accept messages from its controllers.
             Ok(Ic00Method::CanisterStatus)
             | Ok(Ic00Method::StartCanister)
             | Ok(Ic00Method::UninstallCode)
@@ -204,7 +210,14 @@ impl CanisterManager {
                             ErrorCode::CanisterNotFound,
                             format!("Canister {canister_id} not found"),
                         ))?;
-                        match canister.controllers().contains(&sender.get()) {
+                        let has_controller_role = method
+                            .as_ref()
+                            .ok()
+                            .and_then(|method| Ic00MethodPermissions::new(*method).controller_role())
+                            .is_some_and(|role| {
+                                canister.system_state.controller_roles.has_role(&sender.get(), role)
+                            });
+                        match canister.controllers().contains(&sender.get()) || has_controller_role {
                             true => Ok(()),
                             false => Err(UserError::new(
                                 ErrorCode::CanisterInvalidController,
@@ -368,6 +381,18 @@ impl CanisterManager {
             });
         }
 
+        if let Some(controller_roles) = settings.controller_roles()
+            && ControllerRole::iter()
+                .any(|role| controller_roles.principals(role).len() > self.config.max_controllers)
+        {
+            return Err(CanisterManagerError::InvalidSettings {
+                message: format!(
+                    "Invalid settings: 'controller_roles' length exceeds maximum size allowed of {} per role.",
+                    self.config.max_controllers
+                ),
+            });
+        }
+
         let new_memory_allocation = settings
             .memory_allocation
             .unwrap_or(canister_memory_allocation);
@@ -491,6 +516,7 @@ impl CanisterManager {
             log_memory_limit,
             settings.wasm_memory_limit(),
             settings.environment_variables().cloned(),
+            settings.controller_roles().cloned(),
         ))
     }
 
@@ -537,6 +563,9 @@ impl CanisterManager {
                 canister.system_state.controllers.insert(principal);
             }
         }
+        if let Some(controller_roles) = settings.controller_roles() {
+            canister.system_state.controller_roles = controller_roles.clone();
+        }
         if let Some(compute_allocation) = settings.compute_allocation() {
             canister.scheduler_state.compute_allocation = compute_allocation;
         }
@@ -674,12 +703,24 @@ impl CanisterManager {
             }
             FlagStatus::Disabled => {
         */
-        if let Some(new_controllers) = new_controllers {
-            let available_execution_memory_change = canister.add_canister_change(
-                timestamp_nanos,
-                origin,
-                CanisterChangeDetails::controllers_change(new_controllers),
-            );
+        let controllers_change = match (new_controllers, validated_settings.controller_roles()) {
+            // Role changes are recorded together with the full set of controllers.
+            (new_controllers, Some(controller_roles)) => {
+                Some(CanisterChangeDetails::controllers_and_roles_change(
+                    new_controllers.unwrap_or_else(|| {
+                        canister.system_state.controllers.iter().copied().collect()
+                    }),
+                    controller_roles.clone(),
+                ))
+            }
+            (Some(new_controllers), None) => {
+                Some(CanisterChangeDetails::controllers_change(new_controllers))
+            }
+            (None, None) => None,
+        };
+        if let Some(controllers_change) = controllers_change {
+            let available_execution_memory_change =
+                canister.add_canister_change(timestamp_nanos, origin, controllers_change);
             round_limits
                 .subnet_available_memory
                 .update_execution_memory_unchecked(available_execution_memory_change);
@@ -853,7 +894,8 @@ impl CanisterManager {
         cost_schedule: CanisterCyclesCostSchedule,
         log_dirty_pages: FlagStatus,
     ) -> DtsInstallCodeResult {
-        if let Err(err) = validate_controller(&canister, &context.sender()) {
+        if let Err(err) = validate_install_code_sender(&canister, &context.sender(), &context.mode)
+        {
             return DtsInstallCodeResult::Finished {
                 canister,
                 message,
@@ -1009,7 +1051,9 @@ impl CanisterManager {
             Some(canister) => canister,
         };
 
-        if let Err(err) = validate_controller(canister, stop_context.sender()) {
+        if let Err(err) =
+            validate_controller_or_role(canister, stop_context.sender(), Ic00Method::StopCanister)
+        {
             return StopCanisterResult::Failure {
                 error: err,
                 cycles_to_return: stop_context.take_cycles(),
@@ -1042,7 +1086,7 @@ impl CanisterManager {
         sender: PrincipalId,
         canister: &mut CanisterState,
     ) -> Result<Vec<StopCanisterContext>, CanisterManagerError> {
-        validate_controller(canister, &sender)?;
+        validate_controller_or_role(canister, &sender, Ic00Method::StartCanister)?;
 
         let stop_contexts = canister.system_state.start_canister();
         canister.system_state.canister_version += 1;
@@ -1062,7 +1106,7 @@ impl CanisterManager {
         // Skip the controller check if the canister itself is requesting its
         // own status, as the canister is considered in the same trust domain.
         if sender != canister.canister_id().get() {
-            validate_controller(canister, &sender)?
+            validate_controller_or_role(canister, &sender, Ic00Method::CanisterStatus)?
         }
 
         let controller = canister.system_state.controller();
@@ -1143,6 +1187,7 @@ impl CanisterManager {
             wasm_memory_limit.map(|x| x.get()),
             wasm_memory_threshold.get(),
             canister.system_state.environment_variables.clone(),
+            canister.system_state.controller_roles.clone(),
         ))
     }
 
@@ -1576,7 +1621,7 @@ impl CanisterManager {
     ) -> Result<UploadChunkResult, CanisterManagerError> {
         // Allow the canister itself to perform this operation.
         if sender != canister.system_state.canister_id.into() {
-            validate_controller(canister, &sender)?
+            validate_controller_or_role(canister, &sender, Ic00Method::UploadChunk)?
         }
 
         // Charge for the upload. We charge before checking if the chunk has already been uploaded
@@ -1681,7 +1726,7 @@ impl CanisterManager {
     ) -> Result<(), CanisterManagerError> {
         // Allow the canister itself to perform this operation.
         if sender != canister.system_state.canister_id.into() {
-            validate_controller(canister, &sender)?
+            validate_controller_or_role(canister, &sender, Ic00Method::ClearChunkStore)?
         }
 
         let memory_usage = canister.memory_usage();
@@ -1720,7 +1765,7 @@ impl CanisterManager {
     ) -> Result<StoredChunksReply, CanisterManagerError> {
         // Allow the canister itself to perform this operation.
         if sender != canister.system_state.canister_id.into() {
-            validate_controller(canister, &sender)?
+            validate_controller_or_role(canister, &sender, Ic00Method::StoredChunks)?
         }
 
         let keys = canister
@@ -1919,8 +1964,8 @@ impl CanisterManager {
         round_limits: &mut RoundLimits,
         resource_saturation: &ResourceSaturation,
     ) -> Result<(CanisterSnapshotResponse, NumInstructions), CanisterManagerError> {
-        // Check sender is a controller.
-        validate_controller(canister, &sender)?;
+        // Check sender is a controller or an operator.
+        validate_controller_or_role(canister, &sender, Ic00Method::TakeCanisterSnapshot)?;
         let canister_id = canister.canister_id();
 
         let replace_snapshot_size = match replace_snapshot {
@@ -2446,8 +2491,8 @@ impl CanisterManager {
         canister: &CanisterState,
         state: &ReplicatedState,
     ) -> Result<Vec<CanisterSnapshotResponse>, CanisterManagerError> {
-        // Check sender is a controller.
-        validate_controller(canister, &sender)?;
+        // Check sender is a controller or an operator.
+        validate_controller_or_role(canister, &sender, Ic00Method::ListCanisterSnapshots)?;
 
         let mut responses = vec![];
         for (snapshot_id, snapshot) in state
@@ -2479,8 +2524,8 @@ impl CanisterManager {
         subnet_size: usize,
         resource_saturation: &ResourceSaturation,
     ) -> Result<(), CanisterManagerError> {
-        // Check sender is a controller.
-        validate_controller(canister, &sender)?;
+        // Check sender is a controller or an operator.
+        validate_controller_or_role(canister, &sender, Ic00Method::DeleteCanisterSnapshot)?;
 
         // perform access validation, but don't use the result
         let snapshot = self.get_snapshot(canister.canister_id(), delete_snapshot_id, state)?;
@@ -2536,8 +2581,8 @@ impl CanisterManager {
         canister: &CanisterState,
         state: &ReplicatedState,
     ) -> Result<ReadCanisterSnapshotMetadataResponse, CanisterManagerError> {
-        // Check sender is a controller.
-        validate_controller(canister, &sender)?;
+        // Check sender is a controller or an operator.
+        validate_controller_or_role(canister, &sender, Ic00Method::ReadCanisterSnapshotMetadata)?;
         let snapshot = self.get_snapshot(canister.canister_id(), snapshot_id, state)?;
         // A snapshot also contains the instruction counter as the last global
         // (because it is *appended* during WASM instrumentation).
@@ -2583,8 +2628,8 @@ impl CanisterManager {
         state: &ReplicatedState,
         subnet_size: usize,
     ) -> Result<ReadCanisterSnapshotDataResponse, CanisterManagerError> {
-        // Check sender is a controller.
-        validate_controller(canister, &sender)?;
+        // Check sender is a controller or an operator.
+        validate_controller_or_role(canister, &sender, Ic00Method::ReadCanisterSnapshotData)?;
         let snapshot = self.get_snapshot(canister.canister_id(), snapshot_id, state)?;
 
         // Charge upfront for the baseline plus the maximum possible size of the returned slice or fail.
@@ -38,12 +38,14 @@ use ic_logger::replica_logger::no_op_logger;
 use ic_management_canister_types_private::{
     CanisterChange, CanisterChangeDetails, CanisterChangeOrigin, CanisterIdRecord,
     CanisterInstallMode, CanisterInstallModeV2, CanisterSettingsArgsBuilder,
-    CanisterStatusResultV2, CanisterStatusType, CanisterUpgradeOptions, ChunkHash,
-    ClearChunkStoreArgs, CreateCanisterArgs, EmptyBlob, EnvironmentVariable, IC_00,
-    InstallCodeArgsV2, Method, NodeMetricsHistoryArgs, NodeMetricsHistoryResponse,
+    CanisterSnapshotDataOffset, CanisterSnapshotResponse, CanisterStatusResultV2,
+    CanisterStatusType, CanisterUpgradeOptions, ChunkHash, ClearChunkStoreArgs, ControllerRole,
+    ControllerRoles, CreateCanisterArgs, EmptyBlob, EnvironmentVariable, IC_00, InstallCodeArgsV2,
+    LoadCanisterSnapshotArgs, Method, NodeMetricsHistoryArgs, NodeMetricsHistoryResponse,
     OnLowWasmMemoryHookStatus, Payload, ProvisionalCreateCanisterWithCyclesArgs,
     RenameCanisterArgs, RenameToArgs, StoredChunksArgs, StoredChunksReply, SubnetInfoArgs,
-    SubnetInfoResponse, TakeCanisterSnapshotArgs, UpdateSettingsArgs, UploadChunkArgs,
+    SubnetInfoResponse, TakeCanisterSnapshotArgs, UpdateSettingsArgs,
+    UploadCanisterSnapshotDataArgs, UploadCanisterSnapshotMetadataArgs, UploadChunkArgs,
     UploadChunkReply, WasmMemoryPersistence,
 };
 use ic_metrics::MetricsRegistry;
@@ -1783,6 +1785,252 @@ fn too_many_controllers() {
     )));
 }
 
+fn set_controller_roles(test: &mut ExecutionTest, canister_id: CanisterId, roles: ControllerRoles) {
+    test.update_settings(
+        canister_id,
+        CanisterSettingsArgsBuilder::new()
+            .with_controller_roles(roles)
+            .build(),
+    )
+    .unwrap();
+}
+
+fn assert_invalid_controller(err: UserError, canister_id: CanisterId) {
+    assert_eq!(err.code(), ErrorCode::CanisterInvalidController);
+    assert!(err.description().contains(&format!(
+        "Only the controllers of the canister {canister_id} can control it"
+    )));
+}
+
+#[test]
+fn controller_roles_are_set_via_update_settings() {
+    let mut test = ExecutionTestBuilder::new().build();
+
+    let canister_id = test.create_canister(*INITIAL_CYCLES);
+    let roles = ControllerRoles::new(
+        vec![user_test_id(1).get()],
+        vec![user_test_id(2).get(), user_test_id(3).get()],
+        vec![user_test_id(4).get()],
+    );
+    set_controller_roles(&mut test, canister_id, roles.clone());
+
+    assert_eq!(
+        test.canister_state(canister_id)
+            .system_state
+            .controller_roles,
+        roles
+    );
+    // Roles do not make their holders controllers.
+    assert_eq!(
+        *test.canister_state(canister_id).controllers(),
+        btreeset! {test.user_id().get()}
+    );
+
+    let status = test.canister_status(canister_id).unwrap();
+    assert_eq!(status.settings().controller_roles(), &roles);
+
+    // The roles are recorded in the canister history together with the controllers.
+    let last_change = test
+        .canister_state(canister_id)
+        .system_state
+        .get_canister_history()
+        .get_changes(1)
+        .next()
+        .unwrap()
+        .clone();
+    assert_eq!(
+        last_change.details(),
+        &CanisterChangeDetails::controllers_and_roles_change(vec![test.user_id().get()], roles)
+    );
+}
+
+#[test]
+fn upgrader_can_only_upgrade_canister() {
+    let mut test = ExecutionTestBuilder::new().build();
+
+    let canister_id = test
+        .universal_canister_with_cycles(*INITIAL_CYCLES)
+        .unwrap();
+    let upgrader = user_test_id(42);
+    set_controller_roles(
+        &mut test,
+        canister_id,
+        ControllerRoles::new(vec![upgrader.get()], vec![], vec![]),
+    );
+
+    test.set_user_id(upgrader);
+    test.upgrade_canister(canister_id, UNIVERSAL_CANISTER_WASM.to_vec())
+        .unwrap();
+
+    for mode in [
+        CanisterInstallModeV2::Install,
+        CanisterInstallModeV2::Reinstall,
+    ] {
+        let err = test
+            .install_code_v2(InstallCodeArgsV2::new(
+                mode,
+                canister_id,
+                UNIVERSAL_CANISTER_WASM.to_vec(),
+                vec![],
+            ))
+            .unwrap_err();
+        assert_invalid_controller(err, canister_id);
+    }
+
+    let err = test
+        .subnet_message(
+            Method::StopCanister,
+            CanisterIdRecord::from(canister_id).encode(),
+        )
+        .unwrap_err();
+    assert_invalid_controller(err, canister_id);
+}
+
+#[test]
+fn operator_can_stop_and_start_canister() {
+    let mut test = ExecutionTestBuilder::new().build();
+
+    let canister_id = test.create_canister(*INITIAL_CYCLES);
+    let operator = user_test_id(42);
+    set_controller_roles(
+        &mut test,
+        canister_id,
+        ControllerRoles::new(vec![], vec![operator.get()], vec![]),
+    );
+
+    test.set_user_id(operator);
+    test.stop_canister(canister_id);
+    test.process_stopping_canisters();
+    assert_eq!(
+        test.canister_state(canister_id).status(),
+        CanisterStatusType::Stopped
+    );
+    test.start_canister(canister_id).unwrap();
+    assert_eq!(
+        test.canister_state(canister_id).status(),
+        CanisterStatusType::Running
+    );
+
+    let err = test.canister_status(canister_id).unwrap_err();
+    assert_invalid_controller(err, canister_id);
+    let err = test
+        .update_settings(
+            canister_id,
+            CanisterSettingsArgsBuilder::new()
+                .with_controllers(vec![operator.get()])
+                .build(),
+        )
+        .unwrap_err();
+    assert_invalid_controller(err, canister_id);
+}
+
+#[test]
+fn operator_cannot_upload_or_load_canister_snapshot() {
+    let mut test = ExecutionTestBuilder::new().build();
+
+    let canister_id = test
+        .universal_canister_with_cycles(*INITIAL_CYCLES)
+        .unwrap();
+    let operator = user_test_id(42);
+    set_controller_roles(
+        &mut test,
+        canister_id,
+        ControllerRoles::new(vec![], vec![operator.get()], vec![]),
+    );
+
+    test.set_user_id(operator);
+    let response = test
+        .subnet_message(
+            Method::TakeCanisterSnapshot,
+            TakeCanisterSnapshotArgs::new(canister_id, None).encode(),
+        )
+        .unwrap();
+    let snapshot_id = CanisterSnapshotResponse::decode(&response.bytes())
+        .unwrap()
+        .snapshot_id();
+
+    // Loading a snapshot replaces the code of the canister.
+    let err = test
+        .subnet_message(
+            Method::LoadCanisterSnapshot,
+            LoadCanisterSnapshotArgs::new(canister_id, snapshot_id, None).encode(),
+        )
+        .unwrap_err();
+    assert_invalid_controller(err, canister_id);
+
+    // An uploaded snapshot can contain arbitrary code.
+    let err = test
+        .subnet_message(
+            Method::UploadCanisterSnapshotMetadata,
+            UploadCanisterSnapshotMetadataArgs::new(
+                canister_id,
+                None,
+                0,
+                vec![],
+                0,
+                0,
+                vec![],
+                None,
+                None,
+            )
+            .encode(),
+        )
+        .unwrap_err();
+    assert_invalid_controller(err, canister_id);
+    let err = test
+        .subnet_message(
+            Method::UploadCanisterSnapshotData,
+            UploadCanisterSnapshotDataArgs::new(
+                canister_id,
+                snapshot_id,
+                CanisterSnapshotDataOffset::WasmModule { offset: 0 },
+                vec![0; 8],
+            )
+            .encode(),
+        )
+        .unwrap_err();
+    assert_invalid_controller(err, canister_id);
+
+    assert_eq!(
+        test.state()
+            .canister_snapshots
+            .list_snapshots(canister_id)
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn observer_can_only_read_canister_status() {
+    let mut test = ExecutionTestBuilder::new().build();
+
+    let canister_id = test.create_canister(*INITIAL_CYCLES);
+    let observer = user_test_id(42);
+    set_controller_roles(
+        &mut test,
+        canister_id,
+        ControllerRoles::new(vec![], vec![], vec![observer.get()]),
+    );
+
+    test.set_user_id(observer);
+    let status = test.canister_status(canister_id).unwrap();
+    assert_eq!(
+        status
+            .settings()
+            .controller_roles()
+            .principals(ControllerRole::Observer),
+        &[observer.get()]
+    );
+
+    let err = test
+        .subnet_message(
+            Method::StartCanister,
+            CanisterIdRecord::from(canister_id).encode(),
+        )
+        .unwrap_err();
+    assert_invalid_controller(err, canister_id);
+}
+
 #[test]
 fn delete_non_existing_canister_fails() {
     let mut test = ExecutionTestBuilder::new().build();
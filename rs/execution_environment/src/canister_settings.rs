@@ -1,6 +1,8 @@
 use ic_base_types::{EnvironmentVariables, NumBytes, NumSeconds};
 use ic_error_types::{ErrorCode, UserError};
-use ic_management_canister_types_private::{CanisterSettingsArgs, LogVisibilityV2};
+use ic_management_canister_types_private::{
+    CanisterSettingsArgs, ControllerRoles, LogVisibilityV2,
+};
 use ic_types::{
     ComputeAllocation, Cycles, InvalidComputeAllocationError, MemoryAllocation, PrincipalId,
 };
@@ -28,6 +30,7 @@ pub(crate) struct CanisterSettings {
     pub(crate) log_memory_limit: Option<NumBytes>,
     pub(crate) wasm_memory_limit: Option<NumBytes>,
     pub(crate) environment_variables: Option<EnvironmentVariables>,
+    pub(crate) controller_roles: Option<ControllerRoles>,
 }
 
 impl CanisterSettings {
@@ -42,6 +45,7 @@ impl CanisterSettings {
         log_memory_limit: Option<NumBytes>,
         wasm_memory_limit: Option<NumBytes>,
         environment_variables: Option<EnvironmentVariables>,
+        controller_roles: Option<ControllerRoles>,
     ) -> Self {
         Self {
             controllers,
@@ -54,6 +58,7 @@ impl CanisterSettings {
             log_memory_limit,
             wasm_memory_limit,
             environment_variables,
+            controller_roles,
         }
     }
 
@@ -96,6 +101,10 @@ impl CanisterSettings {
     pub fn environment_variables(&self) -> Option<&EnvironmentVariables> {
         self.environment_variables.as_ref()
     }
+
+    pub fn controller_roles(&self) -> Option<&ControllerRoles> {
+        self.controller_roles.as_ref()
+    }
 }
 
 impl TryFrom<CanisterSettingsArgs> for CanisterSettings {
@@ -194,6 +203,9 @@ impl TryFrom<CanisterSettingsArgs> for CanisterSettings {
             log_memory_limit,
             wasm_memory_limit,
             environment_variables,
+            input
+                .controller_roles
+                .map(|controller_roles| controller_roles.normalized()),
         ))
     }
 }
@@ -220,6 +232,7 @@ pub(crate) struct CanisterSettingsBuilder {
     log_memory_limit: Option<NumBytes>,
     wasm_memory_limit: Option<NumBytes>,
     environment_variables: Option<EnvironmentVariables>,
+    controller_roles: Option<ControllerRoles>,
 }
 
 #[allow(dead_code)]
@@ -236,6 +249,7 @@ impl CanisterSettingsBuilder {
             log_memory_limit: None,
             wasm_memory_limit: None,
             environment_variables: None,
+            controller_roles: None,
         }
     }
 
@@ -251,6 +265,7 @@ impl CanisterSettingsBuilder {
             log_memory_limit: self.log_memory_limit,
             wasm_memory_limit: self.wasm_memory_limit,
             environment_variables: self.environment_variables,
+            controller_roles: self.controller_roles,
         }
     }
 
@@ -323,6 +338,13 @@ impl CanisterSettingsBuilder {
             ..self
         }
     }
+
+    pub fn with_controller_roles(self, controller_roles: ControllerRoles) -> Self {
+        Self {
+            controller_roles: Some(controller_roles),
+            ..self
+        }
+    }
 }
 
 pub enum UpdateSettingsError {
@@ -408,6 +430,7 @@ pub(crate) struct ValidatedCanisterSettings {
     log_memory_limit: Option<NumBytes>,
     wasm_memory_limit: Option<NumBytes>,
     environment_variables: Option<EnvironmentVariables>,
+    controller_roles: Option<ControllerRoles>,
 }
 
 impl ValidatedCanisterSettings {
@@ -423,6 +446,7 @@ impl ValidatedCanisterSettings {
         log_memory_limit: Option<NumBytes>,
         wasm_memory_limit: Option<NumBytes>,
         environment_variables: Option<EnvironmentVariables>,
+        controller_roles: Option<ControllerRoles>,
     ) -> Self {
         Self {
             controllers,
@@ -436,6 +460,7 @@ impl ValidatedCanisterSettings {
             log_memory_limit,
             wasm_memory_limit,
             environment_variables,
+            controller_roles,
         }
     }
 
@@ -482,4 +507,8 @@ impl ValidatedCanisterSettings {
     pub fn environment_variables(&self) -> Option<&EnvironmentVariables> {
         self.environment_variables.as_ref()
     }
+
+    pub fn controller_roles(&self) -> Option<&ControllerRoles> {
+        self.controller_roles.as_ref()
+    }
 }
@@ -16,7 +16,7 @@ use ic_interfaces::execution_environment::{
 };
 use ic_logger::{error, fatal, info, warn};
 use ic_management_canister_types_private::{
-    CanisterChangeDetails, CanisterChangeOrigin, CanisterInstallModeV2,
+    CanisterChangeDetails, CanisterChangeOrigin, CanisterInstallModeV2, Method as Ic00Method,
 };
 use ic_replicated_state::canister_state::system_state::ReservationError;
 use ic_replicated_state::metadata_state::subnet_call_context_manager::InstallCodeCallId;
@@ -35,6 +35,7 @@ use crate::{
         CanisterManagerError, CanisterMgrConfig, DtsInstallCodeResult, InstallCodeResult,
     },
     execution_environment::RoundContext,
+    ic00_permissions::Ic00MethodPermissions,
 };
 use ic_replicated_state::canister_state::execution_state::WasmExecutionMode;
 
@@ -489,7 +490,7 @@ impl InstallCodeHelper {
         let config = &original.config;
         let id = self.canister.system_state.canister_id;
 
-        validate_controller(&self.canister, &original.sender)?;
+        validate_install_code_sender(&self.canister, &original.sender, &original.mode)?;
 
         match original.mode {
             CanisterInstallModeV2::Install => {
@@ -826,6 +827,41 @@ pub(crate) fn validate_controller(
     Ok(())
 }
 
+/// Validates that the sender is either a controller of the canister or holds
+/// the controller role that grants access to the given management method.
+pub(crate) fn validate_controller_or_role(
+    canister: &CanisterState,
+    sender: &PrincipalId,
+    method: Ic00Method,
+) -> Result<(), CanisterManagerError> {
+    if let Some(role) = Ic00MethodPermissions::new(method).controller_role()
+        && canister
+            .system_state
+            .controller_roles
+            .has_role(sender, role)
+    {
+        return Ok(());
+    }
+    validate_controller(canister, sender)
+}
+
+/// Validates that the sender may install code in the given mode. Controllers
+/// may use any mode, while upgraders may only upgrade the canister.
+pub(crate) fn validate_install_code_sender(
+    canister: &CanisterState,
+    sender: &PrincipalId,
+    mode: &CanisterInstallModeV2,
+) -> Result<(), CanisterManagerError> {
+    match mode {
+        CanisterInstallModeV2::Upgrade(..) => {
+            validate_controller_or_role(canister, sender, Ic00Method::InstallCode)
+        }
+        CanisterInstallModeV2::Install | CanisterInstallModeV2::Reinstall => {
+            validate_controller(canister, sender)
+        }
+    }
+}
+
 pub(crate) fn get_wasm_hash(canister: &CanisterState) -> Option<[u8; 32]> {
     canister
         .execution_state
@@ -9,12 +9,13 @@ use crate::{
     },
     canister_settings::CanisterSettings,
     execution::{
-        call_or_task::execute_call_or_task, inspect_message, install_code::validate_controller,
-        response::execute_response,
+        call_or_task::execute_call_or_task, inspect_message,
+        install_code::validate_controller_or_role, response::execute_response,
     },
     execution_environment_metrics::{
         ExecutionEnvironmentMetrics, SUBMITTED_OUTCOME_LABEL, SUCCESS_STATUS_LABEL,
     },
+    execution_profiler::ExecutionProfiler,
     hypervisor::Hypervisor,
     ic00_permissions::Ic00MethodPermissions,
     metrics::{CallTreeMetrics, CallTreeMetricsImpl, IngressFilterMetrics},
@@ -70,7 +71,7 @@ use ic_types::{
     CanisterId, Cycles, ExecutionRound, Height, NumBytes, NumInstructions, RegistryVersion,
     ReplicaVersion, SubnetId, Time,
     batch::{CanisterCyclesCostSchedule, ChainKeyData},
-    canister_http::{CanisterHttpRequestContext, MAX_CANISTER_HTTP_RESPONSE_BYTES},
+    canister_http::{CanisterHttpRequestContext, MAX_CANISTER_HTTP_RESPONSE_BYTES, Replication},
     crypto::{
         ExtendedDerivationPath,
         canister_threshold_sig::{MasterPublicKey, PublicKey},
@@ -1008,6 +1009,19 @@ impl ExecutionEnvironment {
                                 response: Err(err),
                                 refund: msg.take_cycles(),
                             },
+                            Ok(args)
+                                if args.is_aggregated_by_canister()
+                                    && self.config.canister_http_aggregate_by_canister
+                                        == FlagStatus::Disabled =>
+                            {
+                                ExecuteSubnetMessageResult::Finished {
+                                    response: Err(UserError::new(
+                                        ErrorCode::CanisterContractViolation,
+                                        "Aggregating HTTP outcall responses by the canister is not enabled on this subnet".to_string(),
+                                    )),
+                                    refund: msg.take_cycles(),
+                                }
+                            }
                             Ok(args) => {
                                 match CanisterHttpRequestContext::generate_from_args(
                                     state.time(),
@@ -1425,6 +1439,25 @@ impl ExecutionEnvironment {
                 }
             },
 
+            Ok(Ic00Method::BitcoinGetBlockFilters) => match &msg {
+                CanisterCall::Request(request) => {
+                    match crate::bitcoin::get_block_filters(
+                        &self.config.bitcoin.privileged_access,
+                        request,
+                        &mut state,
+                    ) {
+                        Ok(()) => ExecuteSubnetMessageResult::Processing,
+                        Err(err) => ExecuteSubnetMessageResult::Finished {
+                            response: Err(err),
+                            refund: msg.take_cycles(),
+                        },
+                    }
+                }
+                CanisterCall::Ingress(_) => {
+                    self.reject_unexpected_ingress(Ic00Method::BitcoinGetBlockFilters)
+                }
+            },
+
             Ok(Ic00Method::BitcoinGetSuccessors) => match &msg {
                 CanisterCall::Request(request) => {
                     match crate::bitcoin::get_successors(
@@ -1863,12 +1896,24 @@ impl ExecutionEnvironment {
         registry_settings: &RegistryExecutionSettings,
         since: Instant,
     ) -> Result<(), UserError> {
-        let http_request_fee = self.cycles_account_manager.http_request_fee(
-            canister_http_request_context.variable_parts_size(),
-            canister_http_request_context.max_response_bytes,
-            registry_settings.subnet_size,
-            state.get_own_cost_schedule(),
-        );
+        let http_request_fee = match canister_http_request_context.replication {
+            Replication::AggregatedByCanister => self
+                .cycles_account_manager
+                .http_request_fee_aggregated_by_canister(
+                    canister_http_request_context.variable_parts_size(),
+                    canister_http_request_context.max_response_bytes,
+                    registry_settings.subnet_size,
+                    state.get_own_cost_schedule(),
+                ),
+            Replication::FullyReplicated | Replication::NonReplicated(_) => {
+                self.cycles_account_manager.http_request_fee(
+                    canister_http_request_context.variable_parts_size(),
+                    canister_http_request_context.max_response_bytes,
+                    registry_settings.subnet_size,
+                    state.get_own_cost_schedule(),
+                )
+            }
+        };
         // Here we make sure that we do not let upper layers open new
         // http calls while the maximum number of calls is in-flight.
         // Later, in the http adapter we also have a bounded queue of
@@ -3553,9 +3598,14 @@ impl ExecutionEnvironment {
                             )
                         })?;
                 // If the `store_canister` is different from the caller, we need
-                // to verify that the caller is a controller of the store.
+                // to verify that the caller is a controller of the store or
+                // an upgrader allowed to read its chunks.
                 if store_canister.canister_id().get() != origin.origin() {
-                    validate_controller(store_canister, &origin.origin())?;
+                    validate_controller_or_role(
+                        store_canister,
+                        &origin.origin(),
+                        Ic00Method::StoredChunks,
+                    )?;
                 }
                 InstallCodeContext::chunked_install(
                     origin,
@@ -4291,6 +4341,17 @@ impl ExecutionEnvironment {
         self.config.default_wasm_memory_limit
     }
 
+    /// Compiles the modules of canisters that are about to switch to the
+    /// optimized tier in the background.
+    pub fn prefetch_optimized_modules(&self, state: &ReplicatedState) {
+        self.hypervisor.prefetch_optimized_modules(state)
+    }
+
+    /// Returns the execution profiler if execution profiling is enabled.
+    pub fn execution_profiler(&self) -> Option<Arc<ExecutionProfiler>> {
+        self.hypervisor.execution_profiler()
+    }
+
     /// For testing purposes only.
     #[doc(hidden)]
     pub fn hypervisor_for_testing(&self) -> &Hypervisor {
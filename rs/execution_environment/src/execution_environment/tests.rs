@@ -29,7 +29,7 @@ use ic_test_utilities_metrics::{fetch_histogram_vec_count, metric_vec};
 use ic_types::{
     CanisterId, CountBytes, Cycles, PrincipalId, RegistryVersion,
     batch::CanisterCyclesCostSchedule,
-    canister_http::{CanisterHttpMethod, Transform},
+    canister_http::{CanisterHttpMethod, Replication, Transform},
     ingress::{IngressState, IngressStatus, WasmResult},
     messages::{
         CallbackId, MAX_RESPONSE_COUNT_BYTES, NO_DEADLINE, Payload, RejectContext,
@@ -40,7 +40,7 @@ use ic_types::{
 };
 use ic_types_test_utils::ids::{canister_test_id, node_test_id, subnet_test_id, user_test_id};
 use ic_universal_canister::{CallArgs, UNIVERSAL_CANISTER_WASM, call_args, wasm};
-use maplit::btreemap;
+use maplit::{btreemap, btreeset};
 use more_asserts::assert_gt;
 use std::mem::size_of;
 
@@ -1989,6 +1989,7 @@ fn http_request_bound_holds() {
         }),
         is_replicated: None,
         pricing_version: None,
+        aggregate_by_canister: None,
     };
 
     // Create request to HTTP_REQUEST method.
@@ -2771,6 +2772,7 @@ fn execute_canister_http_request() {
         }),
         is_replicated: None,
         pricing_version: None,
+        aggregate_by_canister: None,
     };
 
     // Create request to HTTP_REQUEST method.
@@ -2852,6 +2854,7 @@ fn execute_canister_http_request_disabled() {
         }),
         is_replicated: None,
         pricing_version: None,
+        aggregate_by_canister: None,
     };
 
     // Create request to HTTP_REQUEST method.
@@ -2866,6 +2869,96 @@ fn execute_canister_http_request_disabled() {
     assert_eq!(canister_http_request_contexts.len(), 0);
 }
 
+fn aggregated_by_canister_http_request_args(
+    caller_canister: CanisterId,
+) -> CanisterHttpRequestArgs {
+    CanisterHttpRequestArgs {
+        url: "https://".to_string(),
+        max_response_bytes: Some(1000),
+        headers: BoundedHttpHeaders::new(vec![]),
+        body: None,
+        method: HttpMethod::GET,
+        transform: Some(TransformContext {
+            function: TransformFunc(candid::Func {
+                principal: caller_canister.get().0,
+                method: "transform".to_string(),
+            }),
+            context: vec![0, 1, 2],
+        }),
+        is_replicated: None,
+        pricing_version: None,
+        aggregate_by_canister: Some(true),
+    }
+}
+
+#[test]
+fn execute_canister_http_request_aggregated_by_canister_disabled() {
+    let own_subnet = subnet_test_id(1);
+    let caller_canister = canister_test_id(10);
+    let mut test = ExecutionTestBuilder::new()
+        .with_own_subnet_id(own_subnet)
+        .with_caller(own_subnet, caller_canister)
+        .with_node_ids(btreeset! {node_test_id(1), node_test_id(2)})
+        .build();
+    test.state_mut().metadata.own_subnet_features.http_requests = true;
+
+    let args = aggregated_by_canister_http_request_args(caller_canister);
+    test.inject_call_to_ic00(
+        Method::HttpRequest,
+        args.encode(),
+        Cycles::new(1_000_000_000),
+    );
+    test.execute_all();
+
+    assert!(
+        test.state()
+            .metadata
+            .subnet_call_context_manager
+            .canister_http_request_contexts
+            .is_empty()
+    );
+    let response = test.xnet_messages()[0].clone();
+    assert_eq!(
+        get_reject_message(response),
+        "Aggregating HTTP outcall responses by the canister is not enabled on this subnet"
+    );
+}
+
+#[test]
+fn execute_canister_http_request_aggregated_by_canister_enabled() {
+    let own_subnet = subnet_test_id(1);
+    let caller_canister = canister_test_id(10);
+    let mut test = ExecutionTestBuilder::new()
+        .with_own_subnet_id(own_subnet)
+        .with_caller(own_subnet, caller_canister)
+        .with_node_ids(btreeset! {node_test_id(1), node_test_id(2)})
+        .with_canister_http_aggregate_by_canister()
+        .build();
+    test.state_mut().metadata.own_subnet_features.http_requests = true;
+
+    let args = aggregated_by_canister_http_request_args(caller_canister);
+    test.inject_call_to_ic00(
+        Method::HttpRequest,
+        args.encode(),
+        Cycles::new(1_000_000_000),
+    );
+    test.execute_all();
+
+    let canister_http_request_contexts = &test
+        .state()
+        .metadata
+        .subnet_call_context_manager
+        .canister_http_request_contexts;
+    assert_eq!(canister_http_request_contexts.len(), 1);
+    assert_eq!(
+        canister_http_request_contexts
+            .get(&CallbackId::from(0))
+            .unwrap()
+            .replication,
+        Replication::AggregatedByCanister
+    );
+}
+
 fn get_reject_message(response: RequestOrResponse) -> String {
     match response {
         RequestOrResponse::Request(_) => panic!("Expected Response"),
@@ -3338,6 +3431,26 @@ fn bitcoin_get_successors_cannot_be_called_by_non_bitcoin_canisters() {
     assert_eq!(result, WasmResult::Reject("Permission denied.".to_string()));
 }
 
+#[test]
+fn bitcoin_get_block_filters_cannot_be_called_by_non_bitcoin_canisters() {
+    let mut test = ExecutionTestBuilder::new()
+        .with_provisional_whitelist_all()
+        .build();
+    let uni = test.universal_canister().unwrap();
+    let call = wasm()
+        .call_simple(
+            ic00::IC_00,
+            Method::BitcoinGetBlockFilters,
+            call_args()
+                .other_side(vec![])
+                .on_reject(wasm().reject_message().reject()),
+        )
+        .build();
+
+    let result = test.ingress(uni, "update", call).unwrap();
+    assert_eq!(result, WasmResult::Reject("Permission denied.".to_string()));
+}
+
 #[test]
 fn replicated_query_refunds_all_sent_cycles() {
     let mut test = ExecutionTestBuilder::new().with_manual_execution().build();
@@ -2919,6 +2919,7 @@ fn canister_snapshot_change_guard_do_not_modify_without_reading_doc_comment() {
         last_executed_round: _,
         next_scheduled_method: _,
         wasm_execution_mode: _,
+        executed_instructions: _,
     } = execution_state.unwrap();
 
     //
@@ -2927,6 +2928,7 @@ fn canister_snapshot_change_guard_do_not_modify_without_reading_doc_comment() {
     let WasmBinary {
         binary: _,
         embedder_cache: _,
+        optimized_embedder_cache: _,
     } = wasm_binary.borrow();
 
     //
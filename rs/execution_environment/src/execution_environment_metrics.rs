@@ -332,7 +332,8 @@ impl ExecutionEnvironmentMetrics {
                     | ic00::Method::VetKdDeriveKey
                     | ic00::Method::ReshareChainKey
                     | ic00::Method::BitcoinSendTransactionInternal
-                    | ic00::Method::BitcoinGetSuccessors => String::from("slow"),
+                    | ic00::Method::BitcoinGetSuccessors
+                    | ic00::Method::BitcoinGetBlockFilters => String::from("slow"),
                 };
                 (format!("ic00_{method_name}"), speed_label)
             }
@@ -0,0 +1,132 @@
+//! Recording of per-execution profiles for testing purposes.
+//!
+//! If the execution profiling is enabled in the execution environment config,
+//! then the `Hypervisor` reports every finished Wasm execution to an
+//! [`ExecutionProfiler`] which keeps the profiles of all executions between
+//! a call to [`ExecutionProfiler::start`] and [`ExecutionProfiler::stop`].
+//! Execution profiling also enables System API call profiling in the
+//! embedders so that the profiles count the calls of every System API function.
+
+use ic_embedders::wasm_executor::{PausedWasmExecution, WasmExecutionResult};
+use ic_interfaces::execution_environment::{SystemApiCallCounters, WasmExecutionOutput};
+use ic_replicated_state::ExecutionState;
+use ic_sys::PAGE_SIZE;
+use ic_types::{CanisterId, NumBytes, NumInstructions};
+use std::sync::{Arc, Mutex};
+
+/// The profile of a single (possibly sliced) Wasm execution.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ExecutionProfile {
+    pub canister_id: CanisterId,
+    /// The kind of the execution, e.g., "update", "reply callback", or "cleanup".
+    pub api_type: &'static str,
+    /// The executed function: the exported method (e.g., "canister_update foo")
+    /// or, for callbacks and cleanups, the kind of the execution.
+    pub function: String,
+    /// The number of instructions executed over all slices of the execution.
+    pub instructions: NumInstructions,
+    /// How many times each System API function was called (see
+    /// `SystemApiCallCounters::calls_by_function`).
+    pub system_api_call_counters: SystemApiCallCounters,
+    pub dirty_heap_bytes: NumBytes,
+    pub dirty_stable_memory_bytes: NumBytes,
+}
+
+/// Collects the profiles of all finished executions while profiling is active.
+#[derive(Default)]
+pub struct ExecutionProfiler {
+    profiles: Mutex<Option<Vec<ExecutionProfile>>>,
+}
+
+impl ExecutionProfiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts recording profiles, discarding all profiles recorded so far.
+    pub fn start(&self) {
+        *self.profiles.lock().unwrap() = Some(vec![]);
+    }
+
+    /// Stops recording profiles and returns the profiles of all executions
+    /// that finished since the last call to `start` (in the order in which
+    /// they finished).
+    pub fn stop(&self) -> Vec<ExecutionProfile> {
+        self.profiles.lock().unwrap().take().unwrap_or_default()
+    }
+
+    fn record(&self, context: &ProfilingContext, output: &WasmExecutionOutput) {
+        if let Some(profiles) = self.profiles.lock().unwrap().as_mut() {
+            let stats = &output.instance_stats;
+            profiles.push(ExecutionProfile {
+                canister_id: context.canister_id,
+                api_type: context.api_type,
+                function: context.function.clone(),
+                instructions: context.message_instruction_limit - output.num_instructions_left,
+                system_api_call_counters: output.system_api_call_counters.clone(),
+                dirty_heap_bytes: NumBytes::new((stats.wasm_dirty_pages * PAGE_SIZE) as u64),
+                dirty_stable_memory_bytes: NumBytes::new(
+                    (stats.stable_dirty_pages * PAGE_SIZE) as u64,
+                ),
+            });
+        }
+    }
+}
+
+impl std::fmt::Debug for ExecutionProfiler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExecutionProfiler").finish_non_exhaustive()
+    }
+}
+
+/// The information about an execution that is needed to record its profile
+/// once it finishes.
+#[derive(Clone, Debug)]
+pub(crate) struct ProfilingContext {
+    pub canister_id: CanisterId,
+    pub api_type: &'static str,
+    pub function: String,
+    pub message_instruction_limit: NumInstructions,
+}
+
+/// Records the profile of the given execution result if the execution has
+/// finished. Otherwise, the paused execution is wrapped so that the profile is
+/// recorded once the execution finishes in a later slice.
+pub(crate) fn profile_execution_result(
+    profiler: &Arc<ExecutionProfiler>,
+    context: ProfilingContext,
+    result: WasmExecutionResult,
+) -> WasmExecutionResult {
+    match result {
+        WasmExecutionResult::Finished(slice, output, state_changes) => {
+            profiler.record(&context, &output);
+            WasmExecutionResult::Finished(slice, output, state_changes)
+        }
+        WasmExecutionResult::Paused(slice, paused) => WasmExecutionResult::Paused(
+            slice,
+            Box::new(ProfiledPausedWasmExecution {
+                inner: paused,
+                profiler: Arc::clone(profiler),
+                context,
+            }),
+        ),
+    }
+}
+
+#[derive(Debug)]
+struct ProfiledPausedWasmExecution {
+    inner: Box<dyn PausedWasmExecution>,
+    profiler: Arc<ExecutionProfiler>,
+    context: ProfilingContext,
+}
+
+impl PausedWasmExecution for ProfiledPausedWasmExecution {
+    fn resume(self: Box<Self>, execution_state: &ExecutionState) -> WasmExecutionResult {
+        let result = self.inner.resume(execution_state);
+        profile_execution_result(&self.profiler, self.context, result)
+    }
+
+    fn abort(self: Box<Self>) {
+        self.inner.abort()
+    }
+}
@@ -3,8 +3,8 @@ use ic_config::execution_environment::{Config, MAX_COMPILATION_CACHE_SIZE};
 use ic_config::flag_status::FlagStatus;
 use ic_cycles_account_manager::CyclesAccountManager;
 use ic_embedders::{
-    CompilationCache, CompilationCacheBuilder, CompilationResult, WasmExecutionInput,
-    WasmtimeEmbedder,
+    CompilationCache, CompilationCacheBuilder, CompilationResult, CompilationTier,
+    WasmExecutionInput, WasmtimeEmbedder, compilation_cache_fingerprint,
     wasm_executor::{WasmExecutionResult, WasmExecutor, WasmExecutorImpl},
     wasm_utils::decoding::decoded_wasm_size,
     wasmtime_embedder::system_api::{
@@ -16,7 +16,7 @@ use ic_interfaces::execution_environment::{
     HypervisorError, HypervisorResult, MessageMemoryUsage, WasmExecutionOutput,
 };
 use ic_interfaces_state_manager::StateReader;
-use ic_logger::ReplicaLogger;
+use ic_logger::{ReplicaLogger, warn};
 use ic_metrics::MetricsRegistry;
 use ic_metrics::buckets::{decimal_buckets_with_zero, linear_buckets};
 use ic_replicated_state::{ExecutionState, NetworkTopology, ReplicatedState, SystemState};
@@ -25,16 +25,21 @@ use ic_types::{
     CanisterId, DiskBytes, NumBytes, NumInstructions, SubnetId, Time, messages::RequestMetadata,
     methods::FuncRef,
 };
-use ic_wasm_types::CanisterModule;
-use prometheus::{Histogram, IntCounter, IntGaugeVec};
+use ic_wasm_types::{CanisterModule, WasmHash};
+use prometheus::{Histogram, IntCounter, IntCounterVec, IntGaugeVec};
 use std::{
+    collections::BTreeSet,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
 };
 
 use crate::canister_logs::check_log_visibility_permission;
 use crate::execution::common::{apply_canister_state_changes, update_round_limits};
 use crate::execution_environment::{CompilationCostHandling, RoundLimits, as_round_instructions};
+use crate::execution_profiler::{ExecutionProfiler, ProfilingContext, profile_execution_result};
 use crate::metrics::CallTreeMetrics;
 use ic_replicated_state::page_map::PageAllocatorFileDescriptor;
 
@@ -44,6 +49,8 @@ pub struct HypervisorMetrics {
     compile: Histogram,
     max_complexity: Histogram,
     compilation_cache_size: IntGaugeVec,
+    persistent_compilation_cache_events: IntCounterVec,
+    optimized_compilations_prefetched: IntCounter,
     code_section_size: Histogram,
 }
 
@@ -73,6 +80,18 @@ impl HypervisorMetrics {
                 "Bytes in memory and on disk used by the compilation cache.",
                 &["location"],
             ),
+            persistent_compilation_cache_events: metrics_registry.int_counter_vec(
+                "hypervisor_persistent_compilation_cache_events_total",
+                "Number of events in the persistent compilation cache: lookups \
+                    served from disk (hit) or not (miss), discarded invalid entries \
+                    and failed writes.",
+                &["event"],
+            ),
+            optimized_compilations_prefetched: metrics_registry.int_counter(
+                "hypervisor_optimized_compilations_prefetched_total",
+                "Number of Wasm modules compiled with optimizations in the \
+                    background before their canisters switched tiers.",
+            ),
             code_section_size: metrics_registry.histogram(
                 "hypervisor_code_section_size",
                 "Size of the code section in bytes for a canister Wasm. Only Wasms that \
@@ -108,6 +127,69 @@ impl HypervisorMetrics {
             .with_label_values(&["disk"])
             .set(cache_disk_size as i64);
     }
+
+    fn observe_persistent_compilation_cache_metrics(&self, compilation_cache: &CompilationCache) {
+        let stats = compilation_cache.take_persistent_stats();
+        for (outcome, count) in [
+            ("hit", stats.hits),
+            ("miss", stats.misses),
+            ("invalid_entry", stats.invalid_entries),
+            ("write_error", stats.write_errors),
+        ] {
+            self.persistent_compilation_cache_events
+                .with_label_values(&[outcome])
+                .inc_by(count);
+        }
+    }
+}
+
+/// Number of threads compiling optimized modules in the background.
+const TIERED_COMPILATION_THREADS: usize = 2;
+
+/// Maximum number of optimized compilations that are queued or running.
+const MAX_TIERED_COMPILATIONS_IN_FLIGHT: usize = 16;
+
+/// Maximum number of modules remembered as prefetched. Once reached, the set
+/// is cleared, so that each module is prefetched at most once more.
+const MAX_PREFETCHED_MODULES: usize = 10_000;
+
+/// State of tiered compilation, which is only present if it is enabled.
+///
+/// Which tier a canister executes is decided by the replicated state, see
+/// `ExecutionState::executed_instructions`. If the optimized module is not in
+/// the compilation cache yet, the execution compiles it like any other cache
+/// miss. To keep such misses rare, the optimized modules of canisters that
+/// approach the threshold are compiled ahead of time on a bounded thread pool.
+///
+/// Both tiers compile the same instrumented module with the same Wasmtime
+/// stack limit. The optimized tier may lay out stack frames differently, so the
+/// recursion depth at which a canister overflows its stack can change when it
+/// tiers up, but like the tier itself it is the same on all replicas.
+struct TieredCompilation {
+    threshold: NumInstructions,
+    thread_pool: Mutex<threadpool::ThreadPool>,
+    /// Modules whose optimized compilation has been scheduled. They are not
+    /// scheduled again, even if the cache evicts them later, because the
+    /// execution switching tiers compiles them on a miss anyway.
+    prefetched: Mutex<BTreeSet<WasmHash>>,
+    /// Number of optimized compilations that are queued or running.
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl TieredCompilation {
+    fn new(threshold: NumInstructions) -> Self {
+        Self {
+            threshold,
+            thread_pool: Mutex::new(
+                threadpool::Builder::new()
+                    .num_threads(TIERED_COMPILATION_THREADS)
+                    .thread_name("TieredCompilation".to_string())
+                    .build(),
+            ),
+            prefetched: Mutex::new(BTreeSet::new()),
+            in_flight: Arc::new(AtomicUsize::new(0)),
+        }
+    }
 }
 
 #[doc(hidden)]
@@ -121,6 +203,8 @@ pub struct Hypervisor {
     cost_to_compile_wasm_instruction: NumInstructions,
     dirty_page_overhead: NumInstructions,
     canister_guaranteed_callback_quota: usize,
+    execution_profiler: Option<Arc<ExecutionProfiler>>,
+    tiered_compilation: Option<TieredCompilation>,
 }
 
 impl Hypervisor {
@@ -148,8 +232,11 @@ impl Hypervisor {
         let compilation_cost = self.cost_to_compile_wasm_instruction * wasm_size as u64;
         if let Err(err) = wasm_size_result {
             round_limits.instructions -= as_round_instructions(compilation_cost);
-            self.compilation_cache
-                .insert_err(&canister_module, err.clone().into());
+            self.compilation_cache.insert_err(
+                &canister_module,
+                CompilationTier::Baseline,
+                err.clone().into(),
+            );
             return (compilation_cost, Err(err.into()));
         }
 
@@ -159,6 +246,8 @@ impl Hypervisor {
             canister_id,
             Arc::clone(&self.compilation_cache),
         );
+        self.metrics
+            .observe_persistent_compilation_cache_metrics(&self.compilation_cache);
         match creation_result {
             Ok((execution_state, compilation_cost, compilation_result)) => {
                 if let Some(compilation_result) = compilation_result {
@@ -193,6 +282,23 @@ impl Hypervisor {
     ) -> Self {
         let mut embedder_config = config.embedders_config.clone();
         embedder_config.dirty_page_overhead = dirty_page_overhead;
+        if config.execution_profiling == FlagStatus::Enabled {
+            embedder_config.feature_flags.system_api_call_profiling = FlagStatus::Enabled;
+        }
+
+        let mut compilation_cache_builder = CompilationCacheBuilder::new()
+            .with_memory_capacity(MAX_COMPILATION_CACHE_SIZE)
+            .with_dir(tempfile::tempdir_in(temp_dir).unwrap());
+        if let Some(dir) = &config.persistent_compilation_cache_dir {
+            compilation_cache_builder = compilation_cache_builder
+                .with_persistent_dir(dir.clone(), compilation_cache_fingerprint(&embedder_config));
+        }
+        let tiered_compilation = match embedder_config.feature_flags.tiered_compilation {
+            FlagStatus::Enabled => Some(TieredCompilation::new(
+                embedder_config.tiered_compilation_threshold,
+            )),
+            FlagStatus::Disabled => None,
+        };
 
         let wasm_executor: Arc<dyn WasmExecutor> = match config.canister_sandboxing_flag {
             FlagStatus::Enabled => {
@@ -223,17 +329,17 @@ impl Hypervisor {
             own_subnet_id,
             log,
             cycles_account_manager,
-            compilation_cache: Arc::new(
-                CompilationCacheBuilder::new()
-                    .with_memory_capacity(MAX_COMPILATION_CACHE_SIZE)
-                    .with_dir(tempfile::tempdir_in(temp_dir).unwrap())
-                    .build(),
-            ),
+            compilation_cache: Arc::new(compilation_cache_builder.build()),
             cost_to_compile_wasm_instruction: config
                 .embedders_config
                 .cost_to_compile_wasm_instruction,
             dirty_page_overhead,
             canister_guaranteed_callback_quota: config.canister_guaranteed_callback_quota,
+            execution_profiler: match config.execution_profiling {
+                FlagStatus::Enabled => Some(Arc::new(ExecutionProfiler::new())),
+                FlagStatus::Disabled => None,
+            },
+            tiered_compilation,
         }
     }
 
@@ -262,9 +368,16 @@ impl Hypervisor {
             cost_to_compile_wasm_instruction,
             dirty_page_overhead,
             canister_guaranteed_callback_quota,
+            execution_profiler: None,
+            tiered_compilation: None,
         }
     }
 
+    /// Returns the execution profiler if execution profiling is enabled.
+    pub fn execution_profiler(&self) -> Option<Arc<ExecutionProfiler>> {
+        self.execution_profiler.clone()
+    }
+
     #[cfg(test)]
     pub fn compile_count(&self) -> u64 {
         self.metrics.compile.get_sample_count()
@@ -369,6 +482,17 @@ impl Hypervisor {
         ) as u64;
         // Maximum between remaining canister quota and available subnet shared pool.
         let available_callbacks = subnet_available_callbacks.max(remaining_canister_callback_quota);
+        let profiling_context = self.execution_profiler.as_ref().map(|_| ProfilingContext {
+            canister_id: system_state.canister_id(),
+            api_type: api_type.as_str(),
+            function: match &func_ref {
+                FuncRef::Method(method) => method.to_string(),
+                FuncRef::UpdateClosure(_) | FuncRef::QueryClosure(_) => {
+                    api_type.as_str().to_string()
+                }
+            },
+            message_instruction_limit: execution_parameters.instruction_limits.message(),
+        });
         let static_system_state = SandboxSafeSystemState::new(
             system_state,
             *self.cycles_account_manager,
@@ -392,6 +516,7 @@ impl Hypervisor {
                 subnet_available_memory: round_limits.subnet_available_memory,
                 func_ref,
                 compilation_cache: Arc::clone(&self.compilation_cache),
+                compilation_tier: self.compilation_tier(execution_state),
             },
             execution_state,
         );
@@ -402,6 +527,8 @@ impl Hypervisor {
                 self.compilation_cache.disk_bytes(),
             );
         }
+        self.metrics
+            .observe_persistent_compilation_cache_metrics(&self.compilation_cache);
 
         // If the caller does not have permission to view this canister's logs,
         // then it shouldn't get a backtrace either. So in that case we remove
@@ -427,6 +554,7 @@ impl Hypervisor {
                     &caller,
                     &system_state.log_visibility,
                     &system_state.controllers,
+                    &system_state.controller_roles,
                 )
                 .is_err()
             {
@@ -434,9 +562,107 @@ impl Hypervisor {
             }
         }
 
+        if let (Some(profiler), Some(context)) = (&self.execution_profiler, profiling_context) {
+            execution_result = profile_execution_result(profiler, context, execution_result);
+        }
+
         execution_result
     }
 
+    /// Returns the tier of the module the given execution state executes.
+    fn compilation_tier(&self, execution_state: &ExecutionState) -> CompilationTier {
+        match &self.tiered_compilation {
+            Some(tiered_compilation)
+                if execution_state.executed_instructions >= tiered_compilation.threshold =>
+            {
+                CompilationTier::Optimized
+            }
+            _ => CompilationTier::Baseline,
+        }
+    }
+
+    /// Compiles the modules of canisters that have executed at least half of
+    /// the tiered compilation threshold with optimizations in the background.
+    ///
+    /// This only warms up the compilation cache: the tier of an execution
+    /// never depends on whether a background compilation has finished.
+    pub(crate) fn prefetch_optimized_modules(&self, state: &ReplicatedState) {
+        let Some(tiered_compilation) = &self.tiered_compilation else {
+            return;
+        };
+        let prefetch_threshold = NumInstructions::new(tiered_compilation.threshold.get() / 2);
+        for canister in state.canisters_iter() {
+            let Some(execution_state) = &canister.execution_state else {
+                continue;
+            };
+            let wasm_binary = &execution_state.wasm_binary;
+            if execution_state.executed_instructions < prefetch_threshold
+                || wasm_binary
+                    .optimized_embedder_cache
+                    .lock()
+                    .unwrap()
+                    .is_some()
+                || tiered_compilation.in_flight.load(Ordering::Relaxed)
+                    >= MAX_TIERED_COMPILATIONS_IN_FLIGHT
+            {
+                continue;
+            }
+
+            let hash = WasmHash::from(&wasm_binary.binary);
+            {
+                let mut prefetched = tiered_compilation.prefetched.lock().unwrap();
+                if prefetched.contains(&hash) {
+                    continue;
+                }
+                if prefetched.len() >= MAX_PREFETCHED_MODULES {
+                    prefetched.clear();
+                }
+                prefetched.insert(hash);
+            }
+            tiered_compilation.in_flight.fetch_add(1, Ordering::Relaxed);
+            self.metrics.optimized_compilations_prefetched.inc();
+
+            let canister_module = wasm_binary.binary.clone();
+            let wasm_executor = Arc::clone(&self.wasm_executor);
+            let compilation_cache = Arc::clone(&self.compilation_cache);
+            let in_flight = Arc::clone(&tiered_compilation.in_flight);
+            let log = self.log.clone();
+            tiered_compilation
+                .thread_pool
+                .lock()
+                .unwrap()
+                .execute(move || {
+                    // The cache is only consulted here, once per module, as
+                    // looking it up may read the persisted entry from disk.
+                    if compilation_cache
+                        .get(&canister_module, CompilationTier::Optimized)
+                        .is_none()
+                    {
+                        // Errors are not cached: the execution switching tiers
+                        // compiles the module again and caches the outcome.
+                        match wasm_executor.compile_optimized(&canister_module) {
+                            Ok(serialized_module) => {
+                                compilation_cache.insert_ok(&canister_module, serialized_module);
+                            }
+                            Err(err) => warn!(
+                                log,
+                                "Failed to compile Wasm module with optimizations: {}", err
+                            ),
+                        }
+                    }
+                    in_flight.fetch_sub(1, Ordering::Relaxed);
+                });
+        }
+    }
+
+    /// Blocks until all background compilations of optimized modules finished.
+    #[doc(hidden)]
+    pub fn wait_for_optimized_compilations_for_testing(&self) {
+        if let Some(tiered_compilation) = &self.tiered_compilation {
+            tiered_compilation.thread_pool.lock().unwrap().join();
+        }
+    }
+
     pub(crate) fn clear_compilation_cache_for_testing(&self) {
         self.compilation_cache.clear_for_testing()
     }
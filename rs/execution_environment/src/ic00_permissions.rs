@@ -1,5 +1,5 @@
 use ic_error_types::{ErrorCode, UserError};
-use ic_management_canister_types_private::Method as Ic00Method;
+use ic_management_canister_types_private::{ControllerRole, Method as Ic00Method};
 use ic_replicated_state::ReplicatedState;
 use ic_types::messages::CanisterCall;
 use ic_types::{CanisterId, SubnetId};
@@ -44,6 +44,7 @@ impl Ic00MethodPermissions {
             | Ic00Method::BitcoinGetCurrentFeePercentiles
             | Ic00Method::BitcoinSendTransactionInternal
             | Ic00Method::BitcoinGetSuccessors
+            | Ic00Method::BitcoinGetBlockFilters
             | Ic00Method::NodeMetricsHistory
             | Ic00Method::SubnetInfo
             | Ic00Method::ProvisionalCreateCanisterWithCycles
@@ -192,6 +193,67 @@ impl Ic00MethodPermissions {
         Ok(())
     }
 
+    /// Returns the controller role that allows a principal which is not a
+    /// controller of the target canister to call the management method.
+    ///
+    /// Additional restrictions (e.g., upgraders may only upgrade the code)
+    /// are enforced where the method is executed.
+    pub(crate) fn controller_role(&self) -> Option<ControllerRole> {
+        match self.method {
+            Ic00Method::InstallCode
+            | Ic00Method::InstallChunkedCode
+            | Ic00Method::UploadChunk
+            | Ic00Method::StoredChunks
+            | Ic00Method::ClearChunkStore => Some(ControllerRole::Upgrader),
+            Ic00Method::StartCanister
+            | Ic00Method::StopCanister
+            | Ic00Method::TakeCanisterSnapshot
+            | Ic00Method::ListCanisterSnapshots
+            | Ic00Method::DeleteCanisterSnapshot
+            | Ic00Method::ReadCanisterSnapshotMetadata
+            | Ic00Method::ReadCanisterSnapshotData => Some(ControllerRole::Operator),
+            // Loading a snapshot replaces the code and the state of the canister,
+            // and an uploaded snapshot can contain arbitrary code, so these are
+            // as powerful as reinstalling the canister and reserved for controllers.
+            Ic00Method::LoadCanisterSnapshot
+            | Ic00Method::UploadCanisterSnapshotMetadata
+            | Ic00Method::UploadCanisterSnapshotData => None,
+            Ic00Method::CanisterStatus | Ic00Method::FetchCanisterLogs => {
+                Some(ControllerRole::Observer)
+            }
+            Ic00Method::CanisterInfo
+            | Ic00Method::CanisterMetadata
+            | Ic00Method::CreateCanister
+            | Ic00Method::DeleteCanister
+            | Ic00Method::DepositCycles
+            | Ic00Method::HttpRequest
+            | Ic00Method::ECDSAPublicKey
+            | Ic00Method::RawRand
+            | Ic00Method::SetupInitialDKG
+            | Ic00Method::SignWithECDSA
+            | Ic00Method::ReshareChainKey
+            | Ic00Method::SchnorrPublicKey
+            | Ic00Method::SignWithSchnorr
+            | Ic00Method::VetKdPublicKey
+            | Ic00Method::VetKdDeriveKey
+            | Ic00Method::UninstallCode
+            | Ic00Method::UpdateSettings
+            | Ic00Method::RenameCanister
+            | Ic00Method::BitcoinGetBalance
+            | Ic00Method::BitcoinGetUtxos
+            | Ic00Method::BitcoinGetBlockHeaders
+            | Ic00Method::BitcoinSendTransaction
+            | Ic00Method::BitcoinGetCurrentFeePercentiles
+            | Ic00Method::BitcoinSendTransactionInternal
+            | Ic00Method::BitcoinGetSuccessors
+            | Ic00Method::BitcoinGetBlockFilters
+            | Ic00Method::NodeMetricsHistory
+            | Ic00Method::SubnetInfo
+            | Ic00Method::ProvisionalCreateCanisterWithCycles
+            | Ic00Method::ProvisionalTopUpCanister => None,
+        }
+    }
+
     pub(crate) fn can_be_executed(
         &self,
         instructions_reached: bool,
@@ -5,6 +5,7 @@ mod canister_settings;
 pub mod execution;
 mod execution_environment;
 mod execution_environment_metrics;
+mod execution_profiler;
 mod history;
 mod hypervisor;
 mod ic00_permissions;
@@ -21,10 +22,13 @@ pub use execution_environment::{
     CompilationCostHandling, ExecuteMessageResult, ExecutionEnvironment, ExecutionResponse,
     RoundInstructions, RoundLimits, as_num_instructions, as_round_instructions, execute_canister,
 };
+pub use execution_profiler::{ExecutionProfile, ExecutionProfiler};
 pub use history::{IngressHistoryReaderImpl, IngressHistoryWriterImpl};
 pub use hypervisor::{Hypervisor, HypervisorMetrics};
 use ic_base_types::PrincipalId;
-use ic_config::{execution_environment::Config, subnet_config::SubnetConfig};
+use ic_config::{
+    execution_environment::Config, flag_status::FlagStatus, subnet_config::SubnetConfig,
+};
 use ic_cycles_account_manager::CyclesAccountManager;
 use ic_embedders::wasm_executor::WasmExecutor;
 use ic_interfaces::execution_environment::{
@@ -91,6 +95,7 @@ pub struct ExecutionServices {
     pub scheduler: Box<dyn Scheduler<State = ReplicatedState>>,
     pub query_stats_payload_builder: QueryStatsPayloadBuilderParams,
     pub cycles_account_manager: Arc<CyclesAccountManager>,
+    pub execution_profiler: Option<Arc<ExecutionProfiler>>,
 }
 
 impl ExecutionServices {
@@ -108,6 +113,70 @@ impl ExecutionServices {
         fd_factory: Arc<dyn PageAllocatorFileDescriptor>,
         completed_execution_messages_tx: Sender<(MessageId, Height)>,
         temp_dir: &Path,
+    ) -> ExecutionServices {
+        Self::setup_execution_impl(
+            logger,
+            metrics_registry,
+            own_subnet_id,
+            own_subnet_type,
+            config,
+            subnet_config,
+            state_reader,
+            fd_factory,
+            completed_execution_messages_tx,
+            temp_dir,
+            FlagStatus::Enabled,
+        )
+    }
+
+    /// Same as `setup_execution()`, except that the scheduler does not induct
+    /// messages between canisters on the same subnet within a round. Instead,
+    /// all inter-canister messages are routed via streams, so that they can be
+    /// intercepted between rounds.
+    ///
+    /// Used by state machine tests to inject faults into inter-canister calls.
+    #[doc(hidden)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn setup_execution_without_same_subnet_message_induction(
+        logger: ReplicaLogger,
+        metrics_registry: &MetricsRegistry,
+        own_subnet_id: SubnetId,
+        own_subnet_type: SubnetType,
+        config: Config,
+        subnet_config: SubnetConfig,
+        state_reader: Arc<dyn StateReader<State = ReplicatedState>>,
+        fd_factory: Arc<dyn PageAllocatorFileDescriptor>,
+        completed_execution_messages_tx: Sender<(MessageId, Height)>,
+        temp_dir: &Path,
+    ) -> ExecutionServices {
+        Self::setup_execution_impl(
+            logger,
+            metrics_registry,
+            own_subnet_id,
+            own_subnet_type,
+            config,
+            subnet_config,
+            state_reader,
+            fd_factory,
+            completed_execution_messages_tx,
+            temp_dir,
+            FlagStatus::Disabled,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn setup_execution_impl(
+        logger: ReplicaLogger,
+        metrics_registry: &MetricsRegistry,
+        own_subnet_id: SubnetId,
+        own_subnet_type: SubnetType,
+        config: Config,
+        subnet_config: SubnetConfig,
+        state_reader: Arc<dyn StateReader<State = ReplicatedState>>,
+        fd_factory: Arc<dyn PageAllocatorFileDescriptor>,
+        completed_execution_messages_tx: Sender<(MessageId, Height)>,
+        temp_dir: &Path,
+        same_subnet_message_induction: FlagStatus,
     ) -> ExecutionServices {
         let (
             ingress_filter,
@@ -154,7 +223,9 @@ impl ExecutionServices {
             false,
         );
 
-        let scheduler = Box::new(SchedulerImpl::new(
+        let execution_profiler = execution_environment.execution_profiler();
+
+        let scheduler = SchedulerImpl::new(
             subnet_config.scheduler_config,
             config.embedders_config,
             own_subnet_id,
@@ -166,7 +237,11 @@ impl ExecutionServices {
             config.rate_limiting_of_heap_delta,
             config.rate_limiting_of_instructions,
             Arc::clone(&fd_factory),
-        ));
+        );
+        let scheduler = match same_subnet_message_induction {
+            FlagStatus::Enabled => Box::new(scheduler),
+            FlagStatus::Disabled => Box::new(scheduler.without_same_subnet_message_induction()),
+        };
 
         Self {
             ingress_filter,
@@ -177,6 +252,7 @@ impl ExecutionServices {
             scheduler,
             query_stats_payload_builder,
             cycles_account_manager,
+            execution_profiler,
         }
     }
 
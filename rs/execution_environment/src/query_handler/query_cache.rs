@@ -27,7 +27,9 @@ pub(crate) struct QueryCacheMetrics {
     pub hits: IntCounter,
     pub hits_with_ignored_time: IntCounter,
     pub hits_with_ignored_canister_balance: IntCounter,
+    pub hits_with_canister_hint: IntCounter,
     pub misses: IntCounter,
+    pub misses_with_canister_hint: IntCounter,
     pub evicted_entries: IntCounter,
     pub evicted_entries_duration: Histogram,
     pub invalidated_entries: IntCounter,
@@ -37,6 +39,8 @@ pub(crate) struct QueryCacheMetrics {
     pub invalidated_entries_by_canister_version: IntCounter,
     pub invalidated_entries_by_canister_balance: IntCounter,
     pub invalidated_entries_by_transient_error: IntCounter,
+    pub invalidated_entries_by_cache_tag: IntCounter,
+    pub invalidated_entries_by_canister_ttl: IntCounter,
     pub invalidated_entries_duration: Histogram,
     pub count_bytes: IntGauge,
     pub len: IntGauge,
@@ -59,10 +63,18 @@ impl QueryCacheMetrics {
                 "execution_query_cache_hits_with_ignored_canister_balance_total",
                 "The total number of cache hits into entries with ignored canister balance",
             ),
+            hits_with_canister_hint: metrics_registry.int_counter(
+                "execution_query_cache_hits_with_canister_hint_total",
+                "The total number of cache hits into entries with a canister caching hint",
+            ),
             misses: metrics_registry.int_counter(
                 "execution_query_cache_misses_total",
                 "The total number of replica side query cache misses",
             ),
+            misses_with_canister_hint: metrics_registry.int_counter(
+                "execution_query_cache_misses_with_canister_hint_total",
+                "The total number of cache misses of replies with a canister caching hint",
+            ),
             evicted_entries: metrics_registry.int_counter(
                 "execution_query_cache_evicted_entries_total",
                 "The total number of evicted entries in the replica side query cache",
@@ -100,6 +112,14 @@ impl QueryCacheMetrics {
                 "execution_query_cache_invalidated_entries_by_transient_error_total",
                 "The total number of invalidated entries due to a transient error",
             ),
+            invalidated_entries_by_cache_tag: metrics_registry.int_counter(
+                "execution_query_cache_invalidated_entries_by_cache_tag_total",
+                "The total number of invalidated entries due to an invalidated canister cache tag",
+            ),
+            invalidated_entries_by_canister_ttl: metrics_registry.int_counter(
+                "execution_query_cache_invalidated_entries_by_canister_ttl_total",
+                "The total number of invalidated entries due to an expired canister caching hint",
+            ),
             invalidated_entries_duration: duration_histogram(
                 "execution_query_cache_invalidated_entries_duration_seconds",
                 "The duration of invalidated cache entries in seconds",
@@ -198,6 +218,49 @@ impl EntryEnv {
     }
 }
 
+////////////////////////////////////////////////////////////////////////
+/// Query Cache entry caching hint.
+///
+/// Declared by the canister with `ic0.query_cache_reply()`. Within the TTL,
+/// the entry stays valid even if the batch time, the canister version or the
+/// canister balance change, unless the canister invalidates the tag of the
+/// entry, or the canister code or settings change.
+#[derive(DeterministicHeapBytes)]
+pub(crate) struct EntryHint {
+    /// How long the entry may be served regardless of the canister changes.
+    ttl: Duration,
+    /// The tag the entry was cached with, if any.
+    tag: Option<Vec<u8>>,
+    /// The canister's query cache tags counter when the entry was cached.
+    tags_counter: u64,
+    /// The total number of canister history changes when the entry was cached.
+    total_num_changes: u64,
+}
+
+impl EntryHint {
+    /// Capture the hint and the state of the only evaluated canister.
+    ///
+    /// Hints are ignored for composite queries evaluating several canisters,
+    /// as the hint of one canister does not cover the state of the others.
+    fn new(
+        state: &ReplicatedState,
+        evaluated_stats: &BTreeMap<CanisterId, QueryStats>,
+        system_api_call_counters: &SystemApiCallCounters,
+    ) -> Option<Self> {
+        let hint = system_api_call_counters.query_cache_hint.as_ref()?;
+        let [id] = evaluated_stats.keys().collect::<Vec<_>>()[..] else {
+            return None;
+        };
+        let system_state = &state.get_active_canister(id).ok()?.system_state;
+        Some(EntryHint {
+            ttl: hint.ttl,
+            tag: hint.tag.clone(),
+            tags_counter: system_state.query_cache_tags.counter(),
+            total_num_changes: system_state.get_canister_history().get_total_num_changes(),
+        })
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////
 /// Query Cache entry value.
 #[derive(DeterministicHeapBytes)]
@@ -212,6 +275,8 @@ pub(crate) struct EntryValue {
     ignore_batch_time: bool,
     /// If set, the canister balance changes might be ignored.
     ignore_canister_balances: bool,
+    /// The caching hint declared by the canister, if any.
+    hint: Option<EntryHint>,
 }
 
 impl DiskBytes for EntryValue {}
@@ -221,6 +286,7 @@ impl EntryValue {
         env: EntryEnv,
         result: Result<WasmResult, UserError>,
         system_api_call_counters: &SystemApiCallCounters,
+        hint: Option<EntryHint>,
     ) -> EntryValue {
         // The cached entry should be expired after `data_certificate_expiry_time`.
         let includes_data_certificate = system_api_call_counters.data_certificate_copy > 0;
@@ -235,6 +301,7 @@ impl EntryValue {
             includes_data_certificate,
             ignore_batch_time,
             ignore_canister_balances,
+            hint,
         }
     }
 
@@ -249,6 +316,8 @@ impl EntryValue {
         // Iterate over the captured data and validate it against the current state.
         let mut all_canister_versions_are_valid = true;
         let mut all_canister_balances_are_valid = true;
+        let mut is_invalidated_by_cache_tag = false;
+        let mut is_canister_history_changed = false;
         let mut canisters_stats =
             Vec::with_capacity(self.env.canisters_versions_balances_stats.len());
         for (id, version, balance, stats) in &self.env.canisters_versions_balances_stats {
@@ -264,6 +333,20 @@ impl EntryValue {
             if &canister.system_state.balance() != balance {
                 all_canister_balances_are_valid = false;
             }
+            if let Some(hint) = &self.hint {
+                let system_state = &canister.system_state;
+                if hint.tag.as_ref().is_some_and(|tag| {
+                    system_state.query_cache_tags.last_invalidation(tag) > hint.tags_counter
+                }) {
+                    is_invalidated_by_cache_tag = true;
+                }
+                // Code installs and settings changes are never covered by the hint.
+                if system_state.get_canister_history().get_total_num_changes()
+                    != hint.total_num_changes
+                {
+                    is_canister_history_changed = true;
+                }
+            }
         }
 
         // Validate the rest of the cached value.
@@ -271,13 +354,27 @@ impl EntryValue {
         let is_expired = self.is_expired(now, max_expiry_time);
         let is_expired_data_certificate =
             self.is_expired_data_certificate(now, data_certificate_expiry_time);
+        let is_expired_canister_ttl = self
+            .hint
+            .as_ref()
+            .is_some_and(|hint| self.is_expired(now, hint.ttl));
+        // Within its TTL, the canister caching hint covers the changes of the
+        // batch time, the canister version and the canister balance.
+        let is_covered_by_hint = self.hint.is_some() && !is_expired_canister_ttl;
+        let is_batch_time_valid =
+            self.env.batch_time == now || self.ignore_batch_time || is_covered_by_hint;
+        let is_canister_version_valid =
+            all_canister_versions_are_valid || (is_covered_by_hint && !is_canister_history_changed);
+        let is_canister_balance_valid =
+            all_canister_balances_are_valid || self.ignore_canister_balances || is_covered_by_hint;
 
         // Check if the cache entry value is valid.
         if !is_expired
             && !is_expired_data_certificate
-            && (self.env.batch_time == now || self.ignore_batch_time)
-            && all_canister_versions_are_valid
-            && (all_canister_balances_are_valid || self.ignore_canister_balances)
+            && !is_invalidated_by_cache_tag
+            && is_batch_time_valid
+            && is_canister_version_valid
+            && is_canister_balance_valid
         {
             // The value is still valid.
             metrics.hits.inc();
@@ -296,6 +393,9 @@ impl EntryValue {
             if !all_canister_balances_are_valid && self.ignore_canister_balances {
                 metrics.hits_with_ignored_canister_balance.inc();
             }
+            if self.hint.is_some() {
+                metrics.hits_with_canister_hint.inc();
+            }
             true
         } else {
             // The value is invalid.
@@ -313,13 +413,19 @@ impl EntryValue {
                     .invalidated_entries_by_data_certificate_expiry_time
                     .inc();
             }
-            if !(self.env.batch_time == now || self.ignore_batch_time) {
+            if is_invalidated_by_cache_tag {
+                metrics.invalidated_entries_by_cache_tag.inc();
+            }
+            if is_expired_canister_ttl {
+                metrics.invalidated_entries_by_canister_ttl.inc();
+            }
+            if !is_batch_time_valid {
                 metrics.invalidated_entries_by_time.inc();
             }
-            if !all_canister_versions_are_valid {
+            if !is_canister_version_valid {
                 metrics.invalidated_entries_by_canister_version.inc();
             }
-            if !(all_canister_balances_are_valid || self.ignore_canister_balances) {
+            if !is_canister_balance_valid {
                 metrics.invalidated_entries_by_canister_balance.inc();
             }
             false
@@ -452,7 +558,11 @@ impl QueryCache {
             return;
         };
 
-        let value = EntryValue::new(env, result.clone(), system_api_counters);
+        let hint = EntryHint::new(state, evaluated_stats, system_api_counters);
+        if hint.is_some() {
+            self.metrics.misses_with_canister_hint.inc();
+        }
+        let value = EntryValue::new(env, result.clone(), system_api_counters, hint);
         let mut cache = self.cache.lock().unwrap();
         let evicted_entries = cache.push(key, value);
 
@@ -61,6 +61,49 @@ const QUERY_CACHE_WAT: &str = r#"
     (export "canister_query f2" (func $f))
 )"#;
 
+const QUERY_CACHE_HINT_WAT: &str = r#"
+(module
+    (import "ic0" "msg_reply" (func $msg_reply))
+    (import "ic0" "msg_reply_data_append"
+        (func $msg_reply_data_append (param i32 i32)))
+    (import "ic0" "query_cache_reply"
+        (func $query_cache_reply (param i64 i32 i32)))
+    (import "ic0" "query_cache_invalidate"
+        (func $query_cache_invalidate (param i32 i32)))
+
+    (memory 1)
+    (data (i32.const 0) "tag")
+
+    (func $inc
+        (i32.store (i32.const 100) (i32.add (i32.load (i32.const 100)) (i32.const 1)))
+    )
+
+    (func $reply_counter
+        (call $msg_reply_data_append (i32.const 100) (i32.const 4))
+        (call $msg_reply)
+    )
+
+    (func (export "canister_query get")
+        ;; The reply may be cached for 5 seconds or until the tag is invalidated.
+        (call $query_cache_reply (i64.const 5000000000) (i32.const 0) (i32.const 3))
+        (call $reply_counter)
+    )
+
+    (func (export "canister_update inc")
+        (call $inc)
+        (call $reply_counter)
+    )
+
+    (func (export "canister_update inc_and_invalidate")
+        (call $inc)
+        (call $query_cache_invalidate (i32.const 0) (i32.const 3))
+        (call $reply_counter)
+    )
+)"#;
+
+/// The TTL declared by the `QUERY_CACHE_HINT_WAT` canister.
+const QUERY_CACHE_HINT_TTL: Duration = Duration::from_secs(5);
+
 fn downcast_query_handler(query_handler: &dyn std::any::Any) -> &InternalHttpQueryHandler {
     // SAFETY:
     //
@@ -139,6 +182,7 @@ fn query_cache_entry_value_counts_elapsed_seconds() {
         entry_env,
         Result::Ok(WasmResult::Reply(vec![])),
         &SystemApiCallCounters::default(),
+        None,
     );
     let forward_time = current_time + Duration::from_secs(2);
     assert_eq!(2.0, entry_value.elapsed_seconds(forward_time));
@@ -1505,6 +1549,81 @@ fn query_cache_supports_query_stats() {
     });
 }
 
+#[test]
+fn query_cache_hint_keeps_entry_valid_after_canister_changes() {
+    let mut test = builder_with_query_cache_expiry_times()
+        .with_query_cache_hints()
+        .build();
+    let id = test.canister_from_wat(QUERY_CACHE_HINT_WAT).unwrap();
+
+    let res_1 = test.non_replicated_query(id, "get", vec![]);
+    assert_eq!(res_1, Ok(WasmResult::Reply(0_u32.to_le_bytes().to_vec())));
+    assert_eq!(query_cache_metrics(&test).misses.get(), 1);
+
+    // Change the canister version, balance and time.
+    test.ingress(id, "inc", vec![]).unwrap();
+    test.state_mut().metadata.batch_time += Duration::from_secs(1);
+
+    // Within the TTL, the stale reply is served from the cache.
+    let res_2 = test.non_replicated_query(id, "get", vec![]);
+    assert_eq!(res_1, res_2);
+    let m = query_cache_metrics(&test);
+    assert_eq!(m.hits.get(), 1);
+    assert_eq!(m.misses.get(), 1);
+    assert_eq!(m.hits_with_canister_hint.get(), 1);
+    assert_eq!(m.misses_with_canister_hint.get(), 1);
+
+    // Once the TTL expires, the entry is invalidated.
+    test.state_mut().metadata.batch_time += QUERY_CACHE_HINT_TTL;
+    let res_3 = test.non_replicated_query(id, "get", vec![]);
+    assert_eq!(res_3, Ok(WasmResult::Reply(1_u32.to_le_bytes().to_vec())));
+    let m = query_cache_metrics(&test);
+    assert_eq!(m.hits.get(), 1);
+    assert_eq!(m.misses.get(), 2);
+    assert_eq!(m.invalidated_entries_by_canister_ttl.get(), 1);
+    assert_eq!(m.invalidated_entries_by_canister_version.get(), 1);
+}
+
+#[test]
+fn query_cache_invalidate_drops_tagged_entries() {
+    let mut test = builder_with_query_cache_expiry_times()
+        .with_query_cache_hints()
+        .build();
+    let id = test.canister_from_wat(QUERY_CACHE_HINT_WAT).unwrap();
+
+    let res_1 = test.non_replicated_query(id, "get", vec![]);
+    assert_eq!(res_1, Ok(WasmResult::Reply(0_u32.to_le_bytes().to_vec())));
+
+    test.ingress(id, "inc_and_invalidate", vec![]).unwrap();
+
+    let res_2 = test.non_replicated_query(id, "get", vec![]);
+    assert_eq!(res_2, Ok(WasmResult::Reply(1_u32.to_le_bytes().to_vec())));
+    let m = query_cache_metrics(&test);
+    assert_eq!(m.hits.get(), 0);
+    assert_eq!(m.misses.get(), 2);
+    assert_eq!(m.invalidated_entries_by_cache_tag.get(), 1);
+    assert_eq!(m.invalidated_entries_by_canister_ttl.get(), 0);
+}
+
+#[test]
+fn query_cache_hint_does_not_cover_code_upgrades() {
+    let mut test = builder_with_query_cache_expiry_times()
+        .with_query_cache_hints()
+        .build();
+    let id = test.canister_from_wat(QUERY_CACHE_HINT_WAT).unwrap();
+
+    test.non_replicated_query(id, "get", vec![]).unwrap();
+
+    let wasm = wat::parse_str(QUERY_CACHE_HINT_WAT).unwrap();
+    test.upgrade_canister(id, wasm).unwrap();
+
+    test.non_replicated_query(id, "get", vec![]).unwrap();
+    let m = query_cache_metrics(&test);
+    assert_eq!(m.hits.get(), 0);
+    assert_eq!(m.misses.get(), 2);
+    assert_eq!(m.invalidated_entries_by_canister_version.get(), 1);
+}
+
 #[test]
 fn query_cache_future_proof_test() {
     match SystemApiCallId::AcceptMessage {
@@ -1563,6 +1682,8 @@ fn query_cache_future_proof_test() {
         | SystemApiCallId::MsgReplyDataAppend
         | SystemApiCallId::OutOfInstructions
         | SystemApiCallId::PerformanceCounter
+        | SystemApiCallId::QueryCacheInvalidate
+        | SystemApiCallId::QueryCacheReply
         | SystemApiCallId::SubnetSelfSize
         | SystemApiCallId::SubnetSelfCopy
         | SystemApiCallId::Stable64Grow
@@ -1581,7 +1702,8 @@ fn query_cache_future_proof_test() {
         | SystemApiCallId::EnvVarValueCopy
         | SystemApiCallId::Time
         | SystemApiCallId::Trap
-        | SystemApiCallId::TryGrowWasmMemory => {
+        | SystemApiCallId::TryGrowWasmMemory
+        | SystemApiCallId::TryGrowGcHeap => {
             ////////////////////////////////////////////////////////////////////
             // ATTENTION!
             ////////////////////////////////////////////////////////////////////
@@ -1646,8 +1768,9 @@ fn total_bytes_future_proof_guard() {
         env,
         Result::Ok(WasmResult::Reply(vec![])),
         &SystemApiCallCounters::default(),
+        None,
     );
-    assert_eq!(size_of_val(&value), 80);
+    assert_eq!(size_of_val(&value), 136);
     assert_eq!(total_bytes(&value), size_of_val(&value));
 
     // Value with some heap data.
@@ -1668,8 +1791,9 @@ fn total_bytes_future_proof_guard() {
         env,
         Result::Ok(WasmResult::Reply(vec![42; HEAP_BYTES])),
         &SystemApiCallCounters::default(),
+        None,
     );
-    assert_eq!(size_of_val(&value), 80);
+    assert_eq!(size_of_val(&value), 136);
     assert_eq!(
         total_bytes(&value),
         size_of_val(&value) + env_vec_size + HEAP_BYTES
@@ -161,6 +161,7 @@ pub(crate) struct SchedulerImpl {
     rate_limiting_of_heap_delta: FlagStatus,
     rate_limiting_of_instructions: FlagStatus,
     fd_factory: Arc<dyn PageAllocatorFileDescriptor>,
+    same_subnet_message_induction: FlagStatus,
 }
 
 impl SchedulerImpl {
@@ -192,6 +193,20 @@ impl SchedulerImpl {
             rate_limiting_of_heap_delta,
             rate_limiting_of_instructions,
             fd_factory,
+            same_subnet_message_induction: FlagStatus::Enabled,
+        }
+    }
+
+    /// Stops inducting messages between canisters on the same subnet between
+    /// the iterations of a round. All such messages are then routed via the
+    /// loopback stream and delivered in a later round.
+    ///
+    /// Only used by test environments that intercept inter-canister messages
+    /// between rounds (e.g. to inject faults).
+    pub(crate) fn without_same_subnet_message_induction(self) -> Self {
+        Self {
+            same_subnet_message_induction: FlagStatus::Disabled,
+            ..self
         }
     }
 
@@ -589,7 +604,7 @@ impl SchedulerImpl {
             if total_heap_delta >= self.config.max_heap_delta_per_iteration {
                 break state;
             }
-            {
+            if self.same_subnet_message_induction == FlagStatus::Enabled {
                 let _induction_timer = self.metrics.round_inner_iteration_fin_induct.start_timer();
                 self.induct_messages_on_same_subnet(&mut state);
             }
@@ -1152,6 +1167,7 @@ impl SchedulerImpl {
         }
         self.initialize_wasm_memory_limit(state);
         self.check_dts_invariants(state, current_round_type);
+        self.exec_env.prefetch_optimized_modules(state);
     }
 
     fn initialize_wasm_memory_limit(&self, state: &mut ReplicatedState) {
@@ -1910,6 +1926,7 @@ fn execute_canisters_on_thread(
         }
         if let Some(es) = &mut canister.execution_state {
             es.last_executed_round = round_id;
+            es.executed_instructions += total_instructions_used;
         }
         RoundSchedule::finish_canister_execution(
             &mut canister,
@@ -2287,6 +2304,7 @@ fn get_instructions_limits_for_subnet_message(
             | BitcoinSendTransactionInternal
             | BitcoinGetCurrentFeePercentiles
             | BitcoinGetSuccessors
+            | BitcoinGetBlockFilters
             | NodeMetricsHistory
             | SubnetInfo
             | FetchCanisterLogs
@@ -13,7 +13,7 @@ use ic_config::{
 };
 use ic_cycles_account_manager::CyclesAccountManager;
 use ic_embedders::{
-    CompilationCache, CompilationResult, WasmExecutionInput,
+    CompilationCache, CompilationResult, SerializedModule, WasmExecutionInput,
     wasm_executor::{
         CanisterStateChanges, ExecutionStateChanges, PausedWasmExecution, SliceExecutionOutput,
         WasmExecutionResult, WasmExecutor,
@@ -62,7 +62,7 @@ use ic_types::{
     },
     methods::{Callback, FuncRef, SystemMethod, WasmClosure, WasmMethod},
 };
-use ic_wasm_types::CanisterModule;
+use ic_wasm_types::{CanisterModule, WasmEngineError};
 use maplit::btreemap;
 use std::time::Duration;
 
@@ -686,6 +686,7 @@ pub(crate) struct SchedulerTestBuilder {
     allocatable_compute_capacity_in_percent: usize,
     rate_limiting_of_instructions: bool,
     rate_limiting_of_heap_delta: bool,
+    same_subnet_message_induction: bool,
     log: ReplicaLogger,
     master_public_key_ids: Vec<MasterPublicKeyId>,
     metrics_registry: MetricsRegistry,
@@ -715,6 +716,7 @@ impl Default for SchedulerTestBuilder {
             allocatable_compute_capacity_in_percent: 100,
             rate_limiting_of_instructions: false,
             rate_limiting_of_heap_delta: false,
+            same_subnet_message_induction: true,
             log: no_op_logger(),
             master_public_key_ids: vec![],
             metrics_registry: MetricsRegistry::new(),
@@ -786,6 +788,13 @@ impl SchedulerTestBuilder {
         }
     }
 
+    pub fn without_same_subnet_message_induction(self) -> Self {
+        Self {
+            same_subnet_message_induction: false,
+            ..self
+        }
+    }
+
     pub fn with_chain_key(self, key_id: MasterPublicKeyId) -> Self {
         Self {
             master_public_key_ids: vec![key_id],
@@ -948,6 +957,11 @@ impl SchedulerTestBuilder {
             rate_limiting_of_instructions,
             Arc::new(TestPageAllocatorFileDescriptorImpl::new()),
         );
+        let scheduler = if self.same_subnet_message_induction {
+            scheduler
+        } else {
+            scheduler.without_same_subnet_message_induction()
+        };
 
         SchedulerTest {
             state: Some(state),
@@ -1132,6 +1146,17 @@ impl WasmExecutor for TestWasmExecutor {
         let mut guard = self.core.lock().unwrap();
         guard.create_execution_state(canister_module, canister_id)
     }
+
+    fn compile_optimized(
+        &self,
+        _canister_module: &CanisterModule,
+    ) -> HypervisorResult<SerializedModule> {
+        // Test canisters have no Wasm to compile, so the optimized tier is
+        // never cached and prefetching it only logs this error.
+        Err(HypervisorError::WasmEngineError(WasmEngineError::Other(
+            "Test canisters are not compiled".to_string(),
+        )))
+    }
 }
 
 // A fake Wasm executor that works as follows:
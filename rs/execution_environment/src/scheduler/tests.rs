@@ -665,6 +665,43 @@ fn basic_induct_messages_on_same_subnet_works() {
     assert_eq!(test.state().metadata.subnet_metrics.num_canisters, 2);
 }
 
+#[test]
+fn same_subnet_message_induction_can_be_disabled() {
+    // Creates two canisters: caller and callee. The caller calls the callee.
+    // With same-subnet message induction disabled, the request should remain
+    // in the output queue of the caller and should not be executed.
+    let mut test = SchedulerTestBuilder::new()
+        .with_scheduler_config(SchedulerConfig {
+            scheduler_cores: 2,
+            max_instructions_per_round: NumInstructions::new(1000),
+            max_instructions_per_message: NumInstructions::new(50),
+            max_instructions_per_message_without_dts: NumInstructions::from(50),
+            max_instructions_per_slice: NumInstructions::new(50),
+            instruction_overhead_per_execution: NumInstructions::from(0),
+            instruction_overhead_per_canister: NumInstructions::from(0),
+            instruction_overhead_per_canister_for_finalization: NumInstructions::from(0),
+            ..SchedulerConfig::application_subnet()
+        })
+        .without_same_subnet_message_induction()
+        .build();
+
+    let caller = test.create_canister();
+    let callee = test.create_canister();
+    let message = ingress(50).call(other_side(callee, 50), on_response(50));
+    test.send_ingress(caller, message);
+    test.execute_round(ExecutionRoundType::OrdinaryRound);
+
+    assert!(test.canister_state(caller).has_output());
+    assert!(!test.canister_state(callee).has_input());
+    assert_eq!(
+        test.state()
+            .metadata
+            .subnet_metrics
+            .update_transactions_total,
+        1
+    );
+}
+
 #[test]
 fn induct_messages_on_same_subnet_handles_foreign_subnet() {
     // Creates one canister. The canister performs a cross-net call. The
@@ -3043,6 +3080,7 @@ fn canister_is_stopped_if_timeout_occurs_and_ready_to_stop() {
             max_response_bytes: None,
             is_replicated: None,
             pricing_version: None,
+            aggregate_by_canister: None,
         })
         .unwrap();
 
@@ -4117,6 +4155,7 @@ fn consumed_cycles_http_outcalls_are_added_to_consumed_cycles_total() {
         }),
         is_replicated: None,
         pricing_version: None,
+        aggregate_by_canister: None,
     };
 
     // Create request to `HttpRequest` method.
@@ -4207,6 +4246,7 @@ fn http_outcalls_free() {
         }),
         is_replicated: None,
         pricing_version: None,
+        aggregate_by_canister: None,
     };
 
     // Create request to `HttpRequest` method.
@@ -5216,6 +5256,29 @@ fn filter_after_long_executions() {
     }
 }
 
+#[test]
+fn executed_instructions_are_accumulated_in_execution_state() {
+    let mut test = SchedulerTestBuilder::new().build();
+    let canister_id = test.create_canister();
+    let executed_instructions = |test: &SchedulerTest| {
+        test.canister_state(canister_id)
+            .execution_state
+            .as_ref()
+            .unwrap()
+            .executed_instructions
+    };
+    assert_eq!(executed_instructions(&test), NumInstructions::new(0));
+
+    test.send_ingress(canister_id, ingress(1_000));
+    test.execute_round(ExecutionRoundType::OrdinaryRound);
+    let after_first_round = executed_instructions(&test);
+    assert!(after_first_round >= NumInstructions::new(1_000));
+
+    test.send_ingress(canister_id, ingress(2_000));
+    test.execute_round(ExecutionRoundType::OrdinaryRound);
+    assert!(executed_instructions(&test) >= after_first_round + NumInstructions::new(2_000));
+}
+
 #[test]
 fn dts_allow_only_one_long_install_code_execution_at_any_time() {
     let mut test = SchedulerTestBuilder::new()
@@ -3,9 +3,9 @@ use ic_config::execution_environment::Config as ExecutionConfig;
 use ic_config::flag_status::FlagStatus;
 use ic_config::subnet_config::SubnetConfig;
 use ic_management_canister_types_private::{
-    self as ic00, BoundedAllowedViewers, CanisterIdRecord, CanisterInstallMode, CanisterLogRecord,
-    CanisterSettingsArgs, CanisterSettingsArgsBuilder, DataSize, EmptyBlob,
-    FetchCanisterLogsFilter, FetchCanisterLogsRange, FetchCanisterLogsRequest,
+    self as ic00, BoundedAllowedViewers, CanisterIdRecord, CanisterInstallMode, CanisterLogField,
+    CanisterLogRecord, CanisterLogSeverity, CanisterSettingsArgs, CanisterSettingsArgsBuilder,
+    DataSize, EmptyBlob, FetchCanisterLogsFilter, FetchCanisterLogsRange, FetchCanisterLogsRequest,
     FetchCanisterLogsResponse, LogVisibilityV2, Payload,
 };
 use ic_registry_subnet_type::SubnetType;
@@ -44,6 +44,8 @@ fn canister_log_response(data: Vec<(u64, u64, Vec<u8>)>) -> FetchCanisterLogsRes
                 idx,
                 timestamp_nanos,
                 content,
+                severity: None,
+                fields: None,
             })
             .collect(),
     }
@@ -1809,3 +1811,177 @@ fn test_canister_log_on_cleanup() {
         ]
     );
 }
+
+fn setup_env_with_structured_logging(fetch_canister_logs_filter: FlagStatus) -> StateMachine {
+    let mut execution_config = ExecutionConfig {
+        fetch_canister_logs_filter,
+        ..Default::default()
+    };
+    execution_config
+        .embedders_config
+        .feature_flags
+        .structured_logging = FlagStatus::Enabled;
+    StateMachineBuilder::new()
+        .with_config(Some(StateMachineConfig::new(
+            SubnetConfig::new(SubnetType::Application),
+            execution_config,
+        )))
+        .with_checkpoints_enabled(false)
+        .build()
+}
+
+const STRUCTURED_LOGGING_WAT: &str = r#"
+    (module
+        (import "ic0" "debug_print" (func $debug_print (param i32 i32)))
+        (import "ic0" "debug_print_structured"
+            (func $debug_print_structured (param i32 i32 i32 i32 i32)))
+        (import "ic0" "msg_reply" (func $msg_reply))
+        (func (export "canister_update test")
+            (call $debug_print (i32.const 0) (i32.const 5))
+            ;; Severity `debug` without fields.
+            (call $debug_print_structured
+                (i32.const 1) (i32.const 10) (i32.const 5) (i32.const 0) (i32.const 0))
+            ;; Severity `error` with the field `user=alice`.
+            (call $debug_print_structured
+                (i32.const 4) (i32.const 20) (i32.const 5) (i32.const 100) (i32.const 17))
+            ;; Invalid severity.
+            (call $debug_print_structured
+                (i32.const 42) (i32.const 20) (i32.const 5) (i32.const 0) (i32.const 0))
+            (call $msg_reply)
+        )
+        (memory 1)
+        (data (i32.const 0) "plain")
+        (data (i32.const 10) "debug")
+        (data (i32.const 20) "error")
+        (data (i32.const 100) "\04\00\00\00user\05\00\00\00alice")
+    )"#;
+
+fn fetch_structured_canister_logs(
+    env: &StateMachine,
+    canister_id: CanisterId,
+    min_severity: Option<CanisterLogSeverity>,
+    fields: Option<Vec<CanisterLogField>>,
+) -> Vec<CanisterLogRecord> {
+    let args = FetchCanisterLogsRequest {
+        min_severity,
+        fields,
+        ..FetchCanisterLogsRequest::new(canister_id)
+    };
+    let result = env.query_as(
+        PrincipalId::new_anonymous(),
+        CanisterId::ic_00(),
+        "fetch_canister_logs",
+        args.encode(),
+    );
+    FetchCanisterLogsResponse::decode(&get_reply(result))
+        .unwrap()
+        .canister_log_records
+}
+
+#[test]
+fn test_fetch_structured_canister_logs_with_severity_and_fields() {
+    // The severity and field filters do not depend on the index and timestamp
+    // filters being enabled.
+    for fetch_canister_logs_filter in [FlagStatus::Disabled, FlagStatus::Enabled] {
+        let env = setup_env_with_structured_logging(fetch_canister_logs_filter);
+        let canister_id = create_and_install_canister(
+            &env,
+            CanisterSettingsArgsBuilder::new()
+                .with_log_visibility(LogVisibilityV2::Public)
+                .build(),
+            wat::parse_str(STRUCTURED_LOGGING_WAT).unwrap(),
+        );
+        env.execute_ingress(canister_id, "test", vec![]).unwrap();
+
+        let summary = |records: Vec<CanisterLogRecord>| {
+            records
+                .into_iter()
+                .map(|r| {
+                    (
+                        String::from_utf8(r.content.clone()).unwrap(),
+                        r.severity,
+                        r.fields().to_vec(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let user_alice = CanisterLogField::new("user", "alice");
+
+        assert_eq!(
+            summary(fetch_structured_canister_logs(
+                &env,
+                canister_id,
+                None,
+                None
+            )),
+            vec![
+                ("plain".to_string(), None, vec![]),
+                (
+                    "debug".to_string(),
+                    Some(CanisterLogSeverity::Debug),
+                    vec![]
+                ),
+                (
+                    "error".to_string(),
+                    Some(CanisterLogSeverity::Error),
+                    vec![user_alice.clone()]
+                ),
+                (
+                    "(debug_print_structured: Invalid canister log severity: 42)".to_string(),
+                    None,
+                    vec![]
+                ),
+            ]
+        );
+        let expected_error_record = vec![(
+            "error".to_string(),
+            Some(CanisterLogSeverity::Error),
+            vec![user_alice.clone()],
+        )];
+        assert_eq!(
+            summary(fetch_structured_canister_logs(
+                &env,
+                canister_id,
+                Some(CanisterLogSeverity::Warning),
+                None
+            )),
+            expected_error_record
+        );
+        assert_eq!(
+            summary(fetch_structured_canister_logs(
+                &env,
+                canister_id,
+                None,
+                Some(vec![user_alice])
+            )),
+            expected_error_record
+        );
+        assert_eq!(
+            summary(fetch_structured_canister_logs(
+                &env,
+                canister_id,
+                Some(CanisterLogSeverity::Debug),
+                Some(vec![CanisterLogField::new("user", "bob")])
+            )),
+            vec![]
+        );
+    }
+}
+
+#[test]
+fn test_structured_logging_is_disabled_by_default() {
+    let env = setup_env();
+    let canister_id = create_canister(
+        &env,
+        CanisterSettingsArgsBuilder::new()
+            .with_log_visibility(LogVisibilityV2::Public)
+            .build(),
+    );
+    let result = env.install_wasm_in_mode(
+        canister_id,
+        CanisterInstallMode::Install,
+        wat::parse_str(STRUCTURED_LOGGING_WAT).unwrap(),
+        vec![],
+    );
+    assert!(result.is_err());
+}
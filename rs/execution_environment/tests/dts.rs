@@ -1148,6 +1148,7 @@ fn dts_aborted_execution_does_not_block_subnet_messages() {
             | Method::BitcoinGetCurrentFeePercentiles
             | Method::BitcoinSendTransactionInternal
             | Method::BitcoinGetSuccessors
+            | Method::BitcoinGetBlockFilters
             | Method::NodeMetricsHistory
             | Method::SubnetInfo
             | Method::ProvisionalCreateCanisterWithCycles
@@ -33,7 +33,7 @@ use ic_test_utilities_execution_environment::{
     wat_compilation_cost,
 };
 use ic_test_utilities_metrics::{
-    HistogramStats, fetch_histogram_vec_stats, fetch_int_counter, metric_vec,
+    HistogramStats, fetch_histogram_vec_stats, fetch_int_counter, fetch_int_counter_vec, metric_vec,
 };
 use ic_test_utilities_types::ids::{subnet_test_id, user_test_id};
 use ic_types::time::CoarseTime;
@@ -10492,3 +10492,226 @@ fn parallel_callbacks() {
     assert_eq!(err.code(), ErrorCode::CanisterCalledTrap);
     assert!(err.description().contains("trap in second callback"));
 }
+
+#[test]
+fn persistent_compilation_cache_serves_modules_after_restart() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let wat = r#"(module (func (export "canister_query go")))"#;
+
+    let mut test = ExecutionTestBuilder::new()
+        .with_persistent_compilation_cache_dir(cache_dir.path().to_path_buf())
+        .build();
+    test.canister_from_wat(wat).unwrap();
+
+    // A fresh instance (as after a replica restart) loads the module from disk
+    // instead of compiling it again.
+    let mut test = ExecutionTestBuilder::new()
+        .with_persistent_compilation_cache_dir(cache_dir.path().to_path_buf())
+        .build();
+    test.canister_from_wat(wat).unwrap();
+    assert_eq!(
+        fetch_int_counter_vec(
+            test.metrics_registry(),
+            "hypervisor_persistent_compilation_cache_events_total"
+        ),
+        metric_vec(&[
+            (&[("event", "hit")], 1),
+            (&[("event", "miss")], 0),
+            (&[("event", "invalid_entry")], 0),
+            (&[("event", "write_error")], 0),
+        ])
+    );
+}
+
+#[test]
+fn can_deploy_module_allocating_gc_objects_within_a_message() {
+    let mut test = ExecutionTestBuilder::new()
+        .with_wasm_gc_and_exceptions()
+        .build();
+    let wat = r#"
+        (module
+            (import "ic0" "msg_reply" (func $msg_reply))
+            (import "ic0" "msg_reply_data_append" (func $msg_reply_data_append (param i32 i32)))
+            (type $box (struct (field $value (mut i32))))
+            (func (export "canister_update go")
+                (local $box (ref $box))
+                (local.set $box (struct.new $box (i32.const 41)))
+                (struct.set $box $value (local.get $box)
+                    (i32.add (struct.get $box $value (local.get $box)) (i32.const 1)))
+                (i32.store (i32.const 0) (struct.get $box $value (local.get $box)))
+                (call $msg_reply_data_append (i32.const 0) (i32.const 4))
+                (call $msg_reply)
+            )
+            (memory 1)
+        )"#;
+    let canister_id = test.canister_from_wat(wat).unwrap();
+    let result = test.ingress(canister_id, "go", vec![]);
+    assert_eq!(get_reply(result), 42_i32.to_le_bytes());
+}
+
+#[test]
+fn can_deploy_module_with_gc_references_in_globals_and_tables() {
+    // Toolchains such as Kotlin/Wasm keep singletons and interned strings on the
+    // GC heap and reference them from globals that are initialized lazily.
+    // References are not persisted, so the singleton is recreated on first use
+    // in every message, while numeric globals keep their values.
+    let mut test = ExecutionTestBuilder::new()
+        .with_wasm_gc_and_exceptions()
+        .build();
+    let wat = r#"
+        (module
+            (import "ic0" "msg_reply" (func $msg_reply))
+            (import "ic0" "msg_reply_data_append" (func $msg_reply_data_append (param i32 i32)))
+            (type $kotlin.Any (sub (struct (field $typeInfo i32))))
+            (type $kotlin.Unit (sub final $kotlin.Any (struct (field $typeInfo i32))))
+            (global $kotlin.Unit_instance (mut (ref null $kotlin.Unit)) (ref.null $kotlin.Unit))
+            (global $instances_created (mut i32) (i32.const 0))
+            (table $objects 1 anyref)
+            (func $kotlin.Unit_getInstance (result (ref $kotlin.Unit))
+                (if (ref.is_null (global.get $kotlin.Unit_instance))
+                    (then
+                        (global.set $kotlin.Unit_instance (struct.new $kotlin.Unit (i32.const 7)))
+                        (global.set $instances_created
+                            (i32.add (global.get $instances_created) (i32.const 1)))
+                    )
+                )
+                (ref.as_non_null (global.get $kotlin.Unit_instance))
+            )
+            (func (export "canister_init")
+                (drop (call $kotlin.Unit_getInstance))
+            )
+            (func (export "canister_update go")
+                ;; The table is reset at the start of every message as well.
+                (if (i32.eqz (ref.is_null (table.get $objects (i32.const 0))))
+                    (then unreachable)
+                )
+                (table.set $objects (i32.const 0) (call $kotlin.Unit_getInstance))
+                (drop (call $kotlin.Unit_getInstance))
+                (i32.store (i32.const 0) (global.get $instances_created))
+                (i32.store (i32.const 4)
+                    (struct.get $kotlin.Unit $typeInfo
+                        (ref.cast (ref $kotlin.Unit) (table.get $objects (i32.const 0)))))
+                (call $msg_reply_data_append (i32.const 0) (i32.const 8))
+                (call $msg_reply)
+            )
+            (memory 1)
+        )"#;
+    let canister_id = test.canister_from_wat(wat).unwrap();
+    for instances_created in [2_i32, 3] {
+        let result = test.ingress(canister_id, "go", vec![]);
+        assert_eq!(
+            get_reply(result),
+            [instances_created.to_le_bytes(), 7_i32.to_le_bytes()].concat()
+        );
+    }
+}
+
+#[test]
+fn cannot_deploy_module_with_reference_typed_globals_without_wasm_gc() {
+    let mut test = ExecutionTestBuilder::new().build();
+    let wat = r#"
+        (module
+            (global $f (mut funcref) (ref.null func))
+        )"#;
+    let err = test.canister_from_wat(wat).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::CanisterInvalidWasm);
+}
+
+const TIERED_COMPILATION_WAT: &str = r#"
+    (module
+        (import "ic0" "msg_reply_data_append"
+            (func $msg_reply_data_append (param i32 i32)))
+        (import "ic0" "msg_reply" (func $msg_reply))
+        (func $fib (param i64) (result i64)
+            (if (result i64) (i64.lt_u (local.get 0) (i64.const 2))
+                (then (local.get 0))
+                (else
+                    (i64.add
+                        (call $fib (i64.sub (local.get 0) (i64.const 1)))
+                        (call $fib (i64.sub (local.get 0) (i64.const 2)))
+                    )
+                )
+            )
+        )
+        (func (export "canister_update compute")
+            (i64.store (i32.const 0) (call $fib (i64.const 20)))
+            (call $msg_reply_data_append (i32.const 0) (i32.const 8))
+            (call $msg_reply)
+        )
+        (memory 1)
+    )"#;
+
+#[test]
+fn canister_switches_to_optimized_tier_with_identical_results() {
+    let threshold = NumInstructions::new(1_000_000);
+    let mut test = ExecutionTestBuilder::new()
+        .with_tiered_compilation(threshold)
+        .build();
+    let canister_id = test.canister_from_wat(TIERED_COMPILATION_WAT).unwrap();
+    let optimized = |test: &ExecutionTest| {
+        test.execution_state(canister_id)
+            .wasm_binary
+            .optimized_embedder_cache
+            .lock()
+            .unwrap()
+            .is_some()
+    };
+
+    let before = test.canister_executed_instructions(canister_id);
+    let baseline_result = test.ingress(canister_id, "compute", vec![]).unwrap();
+    let baseline_instructions = test.canister_executed_instructions(canister_id) - before;
+    assert!(!optimized(&test));
+
+    // The scheduler accumulates the executed instructions, which the execution
+    // test bypasses, so the threshold is crossed by hand.
+    test.canister_state_mut(canister_id)
+        .execution_state
+        .as_mut()
+        .unwrap()
+        .executed_instructions = threshold;
+
+    let before = test.canister_executed_instructions(canister_id);
+    let optimized_result = test.ingress(canister_id, "compute", vec![]).unwrap();
+    let optimized_instructions = test.canister_executed_instructions(canister_id) - before;
+    assert!(optimized(&test));
+
+    assert_eq!(baseline_result, optimized_result);
+    assert_eq!(
+        baseline_result,
+        WasmResult::Reply(6765_i64.to_le_bytes().to_vec())
+    );
+    assert_eq!(baseline_instructions, optimized_instructions);
+}
+
+#[test]
+fn optimized_modules_are_prefetched_once() {
+    let threshold = NumInstructions::new(1_000_000);
+    let mut test = ExecutionTestBuilder::new()
+        .with_tiered_compilation(threshold)
+        .build();
+    let canister_id = test.canister_from_wat(TIERED_COMPILATION_WAT).unwrap();
+    let prefetch = |test: &ExecutionTest| {
+        test.execution_environment()
+            .prefetch_optimized_modules(test.state());
+        test.hypervisor_deprecated()
+            .wait_for_optimized_compilations_for_testing();
+        fetch_int_counter(
+            test.metrics_registry(),
+            "hypervisor_optimized_compilations_prefetched_total",
+        )
+        .unwrap()
+    };
+
+    // Canisters below half of the threshold are not prefetched.
+    assert_eq!(prefetch(&test), 0);
+
+    test.canister_state_mut(canister_id)
+        .execution_state
+        .as_mut()
+        .unwrap()
+        .executed_instructions = NumInstructions::new(threshold.get() / 2);
+    assert_eq!(prefetch(&test), 1);
+    // The module is remembered as prefetched, so later rounds skip it.
+    assert_eq!(prefetch(&test), 1);
+    assert_eq!(prefetch(&test), 1);
+}
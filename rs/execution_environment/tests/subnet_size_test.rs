@@ -535,6 +535,7 @@ fn simulate_http_request_cost(subnet_type: SubnetType, subnet_size: usize) -> Cy
                     }),
                     is_replicated: None,
                     pricing_version: None,
+                    aggregate_by_canister: None,
                 })
                 .unwrap(),
             ),
@@ -34,6 +34,13 @@ fn make_ecdsa_key(name: &str) -> MasterPublicKeyId {
     })
 }
 
+fn make_ecdsa_secp256r1_key(name: &str) -> MasterPublicKeyId {
+    MasterPublicKeyId::Ecdsa(EcdsaKeyId {
+        curve: EcdsaCurve::Secp256r1,
+        name: name.to_string(),
+    })
+}
+
 fn make_ed25519_key(name: &str) -> MasterPublicKeyId {
     MasterPublicKeyId::Schnorr(SchnorrKeyId {
         algorithm: SchnorrAlgorithm::Ed25519,
@@ -392,6 +399,12 @@ fn test_sign_with_threshold_key_fee_charged() {
             1_000_000,
             2_000_000,
         ),
+        (
+            Method::SignWithECDSA,
+            make_ecdsa_secp256r1_key("some_key"),
+            1_000_000,
+            2_000_000,
+        ),
         (
             Method::SignWithSchnorr,
             make_ed25519_key("some_key"),
@@ -903,3 +916,32 @@ fn test_sign_with_threshold_key_queue_fills_up() {
         );
     }
 }
+
+#[test]
+fn test_sign_with_ecdsa_secp256r1_verifies_with_public_key() {
+    let key_id = make_ecdsa_secp256r1_key("some_key");
+    let env = StateMachineBuilder::new()
+        .with_checkpoints_enabled(false)
+        .with_chain_key(key_id.clone())
+        .build();
+    let canister_id = create_universal_canister(&env);
+
+    let public_key = expect_reply::<ECDSAPublicKeyResponse>(execute_threshold_public_key(
+        &env,
+        canister_id,
+        Method::ECDSAPublicKey,
+        key_id.clone(),
+    ))
+    .public_key;
+    let signature = expect_reply::<SignWithECDSAReply>(execute_sign_with_threshold(
+        &env,
+        canister_id,
+        Method::SignWithECDSA,
+        key_id,
+    ))
+    .signature;
+
+    let public_key = ic_secp256r1::PublicKey::deserialize_sec1(&public_key).unwrap();
+    // The message hash must match the one in `sign_with_threshold_key_payload`.
+    assert!(public_key.verify_signature_prehashed(&[1; 32], &signature));
+}
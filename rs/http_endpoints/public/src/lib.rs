@@ -9,6 +9,7 @@ mod catch_up_package;
 mod common;
 mod dashboard;
 mod health_status_refresher;
+mod log_stream;
 pub mod metrics;
 mod pprof;
 pub mod query;
@@ -305,7 +306,7 @@ pub fn start_server(
         rt_handle.clone(),
         log.clone(),
         metrics.clone(),
-        certified_height_watcher,
+        certified_height_watcher.clone(),
         completed_execution_messages_rx,
         cancellation_token,
     );
@@ -341,6 +342,11 @@ pub fn start_server(
         )
         .with_health_status(health_status.clone())
         .with_malicious_flags(malicious_flags.clone())
+        .with_certified_height_watcher(certified_height_watcher.clone())
+        .with_log_stream_limits(
+            config.max_log_stream_concurrent_requests,
+            Duration::from_secs(config.log_stream_timeout_seconds),
+        )
         .build_router()
     };
 
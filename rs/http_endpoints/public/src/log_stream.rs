@@ -0,0 +1,298 @@
+//! Module that deals with requests to /api/v3/canister/.../log_stream
+//!
+//! A log stream is opened with a signed query to the `fetch_canister_logs`
+//! method of the management canister (the same envelope as sent to the query
+//! endpoint). Instead of answering once, the replica keeps the connection open
+//! and re-executes the query with an updated index range whenever a new state
+//! gets certified, until the ingress expiry of the request passes or the log
+//! stream times out. The query is executed at most once per certified height.
+//! The number of concurrently open log streams is limited.
+//!
+//! The response body is a CBOR sequence of [`HttpLogStreamItem`]s. Every item
+//! with new log records carries a signed query response so that clients can
+//! verify the records in the same way as for a regular query. The index filter
+//! of the re-executed query is ignored unless the `fetch_canister_logs_filter`
+//! feature is enabled, so a signed response may also contain records that have
+//! already been streamed: clients must skip records with an index below the
+//! start of the index range in the query argument. An item is only sent if the
+//! response contains at least one new record.
+//!
+//! Gap items are not signed. Clients can check a gap against the signed
+//! response that follows it, whose index range starts at the beginning of the
+//! gap and whose first new record has the index of the end of the gap.
+
+use crate::{
+    common::{CONTENT_TYPE_CBOR, Cbor, into_cbor},
+    query::{QueryService, execute_and_sign_query, validate_query},
+};
+
+use axum::{
+    body::Body,
+    extract::State,
+    response::{IntoResponse, Response},
+};
+use bytes::Bytes;
+use futures::stream;
+use http::{HeaderValue, header};
+use hyper::StatusCode;
+use ic_interfaces::time_source::TimeSource;
+use ic_logger::warn;
+use ic_management_canister_types_private::{
+    FetchCanisterLogsFilter, FetchCanisterLogsRange, FetchCanisterLogsRequest,
+    FetchCanisterLogsResponse, Method, Payload,
+};
+use ic_types::{
+    CanisterId, Height, Time,
+    messages::{
+        Blob, HttpLogStreamItem, HttpQueryContent, HttpQueryResponse, HttpRequestEnvelope, Query,
+    },
+};
+use std::{convert::Infallible, str::FromStr, time::Duration};
+use tokio::sync::{OwnedSemaphorePermit, watch};
+
+/// The maximum time to wait for a new certified state before checking again
+/// whether the stream has ended (e.g., because the ingress expiry has passed).
+const MAX_CERTIFIED_HEIGHT_WAIT: Duration = Duration::from_secs(10);
+
+pub(crate) struct LogStreamService;
+
+impl LogStreamService {
+    pub(crate) fn route() -> &'static str {
+        "/api/v3/canister/{effective_canister_id}/log_stream"
+    }
+}
+
+pub(crate) async fn log_stream(
+    axum::extract::Path(effective_canister_id): axum::extract::Path<CanisterId>,
+    State(service): State<QueryService>,
+    Cbor(request): Cbor<HttpRequestEnvelope<HttpQueryContent>>,
+) -> Response {
+    let HttpQueryContent::Query { query } = &request.content;
+    let ingress_expiry = Time::from_nanos_since_unix_epoch(query.ingress_expiry);
+
+    let user_query = match validate_query(&service, effective_canister_id, request).await {
+        Ok((user_query, _registry_version)) => user_query,
+        Err(response) => return response,
+    };
+
+    let (args, next_idx) = match log_stream_start(&user_query, effective_canister_id) {
+        Ok(start) => start,
+        Err(text) => return (StatusCode::BAD_REQUEST, text).into_response(),
+    };
+
+    let Some(certified_height_watcher) = service.certified_height_watcher() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let Some(permit) = service.try_acquire_log_stream_permit() else {
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many concurrent log streams, try again later",
+        )
+            .into_response();
+    };
+
+    let deadline = ingress_expiry
+        .min(service.time_source().get_relative_time() + service.log_stream_timeout());
+    let log_stream = LogStream {
+        service,
+        effective_canister_id,
+        user_query,
+        args,
+        deadline,
+        next_idx,
+        certified_height_watcher,
+        executed_height: None,
+        finished: false,
+        _permit: permit,
+    };
+    let body = stream::unfold(log_stream, |mut log_stream| async move {
+        let items = log_stream.next_items().await?;
+        let bytes: Vec<u8> = items.iter().flat_map(into_cbor).collect();
+        Some((Ok::<_, Infallible>(Bytes::from(bytes)), log_stream))
+    });
+
+    (
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(CONTENT_TYPE_CBOR),
+        )],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
+
+/// Checks that the query is a call to `fetch_canister_logs` for the effective
+/// canister ID and returns its arguments and the index of the first log record
+/// to stream.
+fn log_stream_start(
+    query: &Query,
+    effective_canister_id: CanisterId,
+) -> Result<(FetchCanisterLogsRequest, u64), String> {
+    if query.receiver != CanisterId::ic_00()
+        || Method::from_str(&query.method_name) != Ok(Method::FetchCanisterLogs)
+    {
+        return Err(format!(
+            "Log streams must be opened with a query to method {} of the management canister",
+            Method::FetchCanisterLogs
+        ));
+    }
+    let args = FetchCanisterLogsRequest::decode(&query.method_payload)
+        .map_err(|err| format!("Failed to decode the log stream arguments: {err}"))?;
+    if args.canister_id != effective_canister_id.get() {
+        return Err(format!(
+            "Specified CanisterId {} does not match effective canister id in URL {}",
+            args.canister_id, effective_canister_id
+        ));
+    }
+    let next_idx = match &args.filter {
+        None => 0,
+        Some(FetchCanisterLogsFilter::ByIdx(range)) => range.start(),
+        Some(FetchCanisterLogsFilter::ByTimestampNanos(_)) => {
+            return Err("Log streams can only be filtered by index".to_string());
+        }
+    };
+    Ok((args, next_idx))
+}
+
+struct LogStream {
+    service: QueryService,
+    effective_canister_id: CanisterId,
+    user_query: Query,
+    /// The arguments of the query (including the severity and field filters).
+    args: FetchCanisterLogsRequest,
+    /// The stream ends once this time has passed.
+    deadline: Time,
+    /// The index of the next log record to be sent to the client.
+    next_idx: u64,
+    certified_height_watcher: watch::Receiver<Height>,
+    /// The certified height at which the query was last executed. The query is
+    /// not executed again until a higher height gets certified.
+    executed_height: Option<Height>,
+    finished: bool,
+    /// Counts the stream towards the limit of concurrent log streams until the
+    /// stream is dropped.
+    _permit: OwnedSemaphorePermit,
+}
+
+impl LogStream {
+    /// Waits until there are new log records (or the stream ends) and returns
+    /// the items to be sent next. Returns `None` once the stream has ended.
+    async fn next_items(&mut self) -> Option<Vec<HttpLogStreamItem>> {
+        loop {
+            if self.finished || self.service.time_source().get_relative_time() > self.deadline {
+                return None;
+            }
+
+            let certified_height = *self.certified_height_watcher.borrow_and_update();
+            if self
+                .executed_height
+                .is_none_or(|executed_height| certified_height > executed_height)
+            {
+                self.executed_height = Some(certified_height);
+                let items = self.fetch_new_records().await;
+                if !items.is_empty() {
+                    return Some(items);
+                }
+                if self.finished {
+                    return None;
+                }
+            }
+
+            match tokio::time::timeout(
+                MAX_CERTIFIED_HEIGHT_WAIT,
+                self.certified_height_watcher.changed(),
+            )
+            .await
+            {
+                // The sender is gone, i.e., the replica is shutting down.
+                Ok(Err(_)) => return None,
+                Ok(Ok(())) | Err(_) => {}
+            }
+        }
+    }
+
+    /// Executes the query for all log records starting at `next_idx`.
+    async fn fetch_new_records(&mut self) -> Vec<HttpLogStreamItem> {
+        let args = FetchCanisterLogsRequest {
+            filter: Some(FetchCanisterLogsFilter::ByIdx(FetchCanisterLogsRange::new(
+                self.next_idx,
+                u64::MAX,
+            ))),
+            ..self.args.clone()
+        }
+        .encode();
+        let user_query = Query {
+            method_payload: args.clone(),
+            ..self.user_query.clone()
+        };
+
+        let signed_response = match execute_and_sign_query(
+            &self.service,
+            self.effective_canister_id,
+            user_query,
+            // The stream may outlive the registry version used to validate
+            // the request, so the latest version is used for signing.
+            self.service.latest_registry_version(),
+        )
+        .await
+        {
+            Ok(signed_response) => signed_response,
+            Err(response) => {
+                warn!(
+                    self.service.log(),
+                    "Closing log stream of canister {} after failed query: {}",
+                    self.effective_canister_id,
+                    response.status()
+                );
+                self.finished = true;
+                return vec![];
+            }
+        };
+
+        let records = match &signed_response.response {
+            HttpQueryResponse::Replied { reply } => {
+                match FetchCanisterLogsResponse::decode(&reply.arg.0) {
+                    Ok(response) => response.canister_log_records,
+                    Err(_) => {
+                        self.finished = true;
+                        return vec![];
+                    }
+                }
+            }
+            // Forward the reject (e.g., the log visibility no longer allows
+            // the caller to read the logs) and end the stream.
+            HttpQueryResponse::Rejected { .. } => {
+                self.finished = true;
+                return vec![HttpLogStreamItem::Records {
+                    arg: Blob(args),
+                    response: signed_response,
+                }];
+            }
+        };
+        // The index filter is ignored if the `fetch_canister_logs_filter`
+        // feature is disabled, so records that have already been streamed are
+        // skipped here.
+        let mut new_records = records.iter().filter(|record| record.idx >= self.next_idx);
+        let Some(first) = new_records.next() else {
+            return vec![];
+        };
+        let last = new_records.last().unwrap_or(first);
+
+        let mut items = vec![];
+        if first.idx > self.next_idx {
+            // The records in between have already been dropped from the
+            // canister log buffer.
+            items.push(HttpLogStreamItem::Gap {
+                from_idx: self.next_idx,
+                to_idx: first.idx,
+            });
+        }
+        self.next_idx = last.idx + 1;
+        items.push(HttpLogStreamItem::Records {
+            arg: Blob(args),
+            response: signed_response,
+        });
+        items
+    }
+}
@@ -6,6 +6,7 @@ use crate::{
         Cbor, WithTimeout, build_validator, certified_state_unavailable_error,
         validation_error_to_http_error,
     },
+    log_stream::{LogStreamService, log_stream},
 };
 
 use axum::{
@@ -17,6 +18,7 @@ use axum::{
 use crossbeam::atomic::AtomicCell;
 use http::Request;
 use hyper::StatusCode;
+use ic_config::http_handler::Config;
 use ic_crypto_interfaces_sig_verification::IngressSigVerifier;
 use ic_error_types::{ErrorCode, RejectCode};
 use ic_interfaces::{
@@ -29,7 +31,7 @@ use ic_logger::{ReplicaLogger, error};
 use ic_nns_delegation_manager::{CanisterRangesFilter, NNSDelegationReader};
 use ic_registry_client_helpers::crypto::root_of_trust::RegistryRootOfTrustProvider;
 use ic_types::{
-    CanisterId, NodeId,
+    CanisterId, Height, NodeId, RegistryVersion,
     ingress::WasmResult,
     malicious_flags::MaliciousFlags,
     messages::{
@@ -43,7 +45,9 @@ use std::sync::Arc;
 use std::{
     convert::{Infallible, TryFrom},
     sync::Mutex,
+    time::Duration,
 };
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, watch};
 use tower::{ServiceBuilder, ServiceExt, util::BoxCloneService};
 
 #[derive(Copy, Clone, Debug)]
@@ -66,6 +70,9 @@ pub struct QueryService {
     registry_client: Arc<dyn RegistryClient>,
     query_execution_service: Arc<Mutex<QueryExecutionService>>,
     version: Version,
+    certified_height_watcher: Option<watch::Receiver<Height>>,
+    log_streams: Arc<Semaphore>,
+    log_stream_timeout: Duration,
 }
 
 pub struct QueryServiceBuilder {
@@ -80,6 +87,9 @@ pub struct QueryServiceBuilder {
     registry_client: Arc<dyn RegistryClient>,
     query_execution_service: QueryExecutionService,
     version: Version,
+    certified_height_watcher: Option<watch::Receiver<Height>>,
+    max_concurrent_log_streams: usize,
+    log_stream_timeout: Duration,
 }
 
 impl QueryService {
@@ -89,6 +99,32 @@ impl QueryService {
             Version::V3 => "/api/v3/canister/{effective_canister_id}/query",
         }
     }
+
+    pub(crate) fn log(&self) -> &ReplicaLogger {
+        &self.log
+    }
+
+    pub(crate) fn time_source(&self) -> &Arc<dyn TimeSource> {
+        &self.time_source
+    }
+
+    pub(crate) fn latest_registry_version(&self) -> RegistryVersion {
+        self.registry_client.get_latest_version()
+    }
+
+    pub(crate) fn certified_height_watcher(&self) -> Option<watch::Receiver<Height>> {
+        self.certified_height_watcher.clone()
+    }
+
+    /// Returns a permit for a new log stream, or `None` if the maximum number
+    /// of concurrent log streams has been reached.
+    pub(crate) fn try_acquire_log_stream_permit(&self) -> Option<OwnedSemaphorePermit> {
+        self.log_streams.clone().try_acquire_owned().ok()
+    }
+
+    pub(crate) fn log_stream_timeout(&self) -> Duration {
+        self.log_stream_timeout
+    }
 }
 
 impl QueryServiceBuilder {
@@ -102,6 +138,7 @@ impl QueryServiceBuilder {
         query_execution_service: QueryExecutionService,
         version: Version,
     ) -> Self {
+        let default_config = Config::default();
         Self {
             log,
             node_id,
@@ -114,6 +151,9 @@ impl QueryServiceBuilder {
             registry_client,
             query_execution_service,
             version,
+            certified_height_watcher: None,
+            max_concurrent_log_streams: default_config.max_log_stream_concurrent_requests,
+            log_stream_timeout: Duration::from_secs(default_config.log_stream_timeout_seconds),
         }
     }
 
@@ -135,6 +175,28 @@ impl QueryServiceBuilder {
         self
     }
 
+    /// Enables the log stream endpoint (for `Version::V3`) which waits
+    /// for new certified states using the given watcher.
+    pub fn with_certified_height_watcher(
+        mut self,
+        certified_height_watcher: watch::Receiver<Height>,
+    ) -> Self {
+        self.certified_height_watcher = Some(certified_height_watcher);
+        self
+    }
+
+    /// Limits the number of concurrently open log streams and how long
+    /// each log stream is kept open.
+    pub fn with_log_stream_limits(
+        mut self,
+        max_concurrent_log_streams: usize,
+        log_stream_timeout: Duration,
+    ) -> Self {
+        self.max_concurrent_log_streams = max_concurrent_log_streams;
+        self.log_stream_timeout = log_stream_timeout;
+        self
+    }
+
     pub fn build_router(self) -> Router {
         let log = self.log;
         let state = QueryService {
@@ -150,13 +212,25 @@ impl QueryServiceBuilder {
             registry_client: self.registry_client,
             query_execution_service: Arc::new(Mutex::new(self.query_execution_service)),
             version: self.version,
+            certified_height_watcher: self.certified_height_watcher,
+            log_streams: Arc::new(Semaphore::new(self.max_concurrent_log_streams)),
+            log_stream_timeout: self.log_stream_timeout,
         };
-        Router::new().route_service(
+        let router = Router::new().route_service(
             QueryService::route(self.version),
             axum::routing::post(query)
-                .with_state(state)
+                .with_state(state.clone())
                 .layer(ServiceBuilder::new().layer(DefaultBodyLimit::disable())),
-        )
+        );
+        match (self.version, &state.certified_height_watcher) {
+            (Version::V3, Some(_)) => router.route_service(
+                LogStreamService::route(),
+                axum::routing::post(log_stream)
+                    .with_state(state)
+                    .layer(ServiceBuilder::new().layer(DefaultBodyLimit::disable())),
+            ),
+            _ => router,
+        }
     }
 
     pub fn build_service(self) -> BoxCloneService<Request<Body>, Response, Infallible> {
@@ -167,30 +241,45 @@ impl QueryServiceBuilder {
 
 pub(crate) async fn query(
     axum::extract::Path(effective_canister_id): axum::extract::Path<CanisterId>,
-    State(QueryService {
-        log,
-        node_id,
-        registry_client,
-        time_source,
-        validator,
-        health_status,
-        signer,
-        nns_delegation_reader,
-        query_execution_service,
-        version,
-    }): State<QueryService>,
+    State(service): State<QueryService>,
     WithTimeout(Cbor(request)): WithTimeout<Cbor<HttpRequestEnvelope<HttpQueryContent>>>,
 ) -> impl IntoResponse {
-    if health_status.load() != ReplicaHealthStatus::Healthy {
+    let (user_query, registry_version) =
+        match validate_query(&service, effective_canister_id, request).await {
+            Ok(validated) => validated,
+            Err(response) => return response,
+        };
+    match execute_and_sign_query(
+        &service,
+        effective_canister_id,
+        user_query,
+        registry_version,
+    )
+    .await
+    {
+        Ok(signed_query_response) => Cbor(signed_query_response).into_response(),
+        Err(response) => response,
+    }
+}
+
+/// Checks the health of the replica, converts the given envelope into a query,
+/// and validates the query (including its signature).
+/// Returns the query and the registry version used to validate it.
+pub(crate) async fn validate_query(
+    service: &QueryService,
+    effective_canister_id: CanisterId,
+    request: HttpRequestEnvelope<HttpQueryContent>,
+) -> Result<(Query, RegistryVersion), Response> {
+    let health_status = service.health_status.load();
+    if health_status != ReplicaHealthStatus::Healthy {
         let status = StatusCode::SERVICE_UNAVAILABLE;
         let text = format!(
-            "Replica is unhealthy: {:?}. Check the /api/v2/status for more information.",
-            health_status.load(),
+            "Replica is unhealthy: {health_status:?}. Check the /api/v2/status for more information.",
         );
-        return (status, text).into_response();
+        return Err((status, text).into_response());
     }
 
-    let registry_version = registry_client.get_latest_version();
+    let registry_version = service.registry_client.get_latest_version();
 
     // Convert the message to a strongly-typed struct, making structural validations
     // on the way.
@@ -199,7 +288,7 @@ pub(crate) async fn query(
         Err(e) => {
             let status = StatusCode::BAD_REQUEST;
             let text = format!("Malformed request: {e:?}");
-            return (status, text).into_response();
+            return Err((status, text).into_response());
         }
     };
     let canister_id = request.content().canister_id();
@@ -208,13 +297,15 @@ pub(crate) async fn query(
         let text = format!(
             "Specified CanisterId {canister_id} does not match effective canister id in URL {effective_canister_id}"
         );
-        return (status, text).into_response();
+        return Err((status, text).into_response());
     }
 
     let root_of_trust_provider =
-        RegistryRootOfTrustProvider::new(Arc::clone(&registry_client), registry_version);
+        RegistryRootOfTrustProvider::new(Arc::clone(&service.registry_client), registry_version);
     // Since spawn blocking requires 'static we can't use any references
     let request_c = request.clone();
+    let validator = Arc::clone(&service.validator);
+    let time_source = Arc::clone(&service.time_source);
     match tokio::task::spawn_blocking(move || {
         validator.validate_request(
             &request_c,
@@ -226,23 +317,32 @@ pub(crate) async fn query(
     {
         Ok(Ok(_)) => {}
         Ok(Err(err)) => {
-            let http_err = validation_error_to_http_error(&request, err, &log);
-            return (http_err.status, http_err.message).into_response();
+            let http_err = validation_error_to_http_error(&request, err, &service.log);
+            return Err((http_err.status, http_err.message).into_response());
         }
         Err(_) => {
-            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
         }
     };
 
-    let user_query = request.take_content();
+    Ok((request.take_content(), registry_version))
+}
 
-    let query_execution_service = query_execution_service.lock().unwrap().clone();
+/// Executes the given (validated) query and signs its response.
+pub(crate) async fn execute_and_sign_query(
+    service: &QueryService,
+    effective_canister_id: CanisterId,
+    user_query: Query,
+    registry_version: RegistryVersion,
+) -> Result<HttpSignedQueryResponse, Response> {
+    let query_execution_service = service.query_execution_service.lock().unwrap().clone();
 
-    let delegation_from_nns = match version {
-        Version::V2 => {
-            nns_delegation_reader.get_delegation_with_metadata(CanisterRangesFilter::Flat)
-        }
-        Version::V3 => nns_delegation_reader
+    let delegation_from_nns = match service.version {
+        Version::V2 => service
+            .nns_delegation_reader
+            .get_delegation_with_metadata(CanisterRangesFilter::Flat),
+        Version::V3 => service
+            .nns_delegation_reader
             .get_delegation_with_metadata(CanisterRangesFilter::Tree(effective_canister_id)),
     };
     let query_execution_input = QueryExecutionInput {
@@ -256,7 +356,7 @@ pub(crate) async fn query(
 
     let (response, timestamp) = match query_execution_response {
         Err(QueryExecutionError::CertifiedStateUnavailable) => {
-            return certified_state_unavailable_error().into_response();
+            return Err(certified_state_unavailable_error().into_response());
         }
         Ok((response, time)) => (response, time),
     };
@@ -284,6 +384,8 @@ pub(crate) async fn query(
 
     // We wrap `sign_basic` into `spawn_blocking`, otherwise calling `sign_basic` will panic
     // if called from the tokio runtime.
+    let signer = Arc::clone(&service.signer);
+    let node_id = service.node_id;
     let signature = tokio::task::spawn_blocking(move || {
         signer.sign_basic(&response_hash, node_id, registry_version)
     })
@@ -301,21 +403,19 @@ pub(crate) async fn query(
                 identity: node_id,
             };
 
-            let signed_query_response = HttpSignedQueryResponse {
+            Ok(HttpSignedQueryResponse {
                 response: query_response,
                 node_signature,
-            };
-
-            Cbor(signed_query_response).into_response()
+            })
         }
         Err(signing_error) => {
             error!(
-                log,
+                service.log,
                 "Failed to sign the Query response: `{:?}`.", signing_error
             );
             let status = StatusCode::INTERNAL_SERVER_ERROR;
             let text = "Failed to sign the Query response.".to_string();
-            (status, text).into_response()
+            Err((status, text).into_response())
         }
     }
 }
@@ -379,6 +379,7 @@ pub struct HttpEndpointBuilder {
     certified_height: Option<Height>,
     ingress_pool_throttler: Arc<RwLock<dyn IngressPoolThrottler + Send + Sync>>,
     ingress_channel_capacity: usize,
+    query_execution_service: Option<QueryExecutionService>,
 }
 
 impl HttpEndpointBuilder {
@@ -395,6 +396,7 @@ impl HttpEndpointBuilder {
             tls_config: Arc::new(MockTlsConfig::new()),
             certified_height: None,
             ingress_channel_capacity: MAX_P2P_IO_CHANNEL_SIZE,
+            query_execution_service: None,
         }
     }
 
@@ -452,6 +454,16 @@ impl HttpEndpointBuilder {
         self
     }
 
+    /// Executes queries with the given service instead of the mock
+    /// (whose handle is then never called).
+    pub fn with_query_execution_service(
+        mut self,
+        query_execution_service: QueryExecutionService,
+    ) -> Self {
+        self.query_execution_service = Some(query_execution_service);
+        self
+    }
+
     pub fn run(self) -> HttpEndpointHandles {
         let metrics = MetricsRegistry::new();
         let log = no_op_logger();
@@ -461,7 +473,8 @@ impl HttpEndpointBuilder {
         let nns_subnet_id = subnet_test_id(1);
 
         let (ingress_filter, ingress_filter_handle) = setup_ingress_filter_mock();
-        let (query_exe, query_exe_handler) = setup_query_execution_mock();
+        let (query_exe_mock, query_exe_handler) = setup_query_execution_mock();
+        let query_exe = self.query_execution_service.unwrap_or(query_exe_mock);
         let (certified_height_watcher_tx, certified_height_watcher_rx) =
             watch::channel(self.certified_height.unwrap_or_default());
         let builder = self.delegation_from_nns.map(|delegation| {
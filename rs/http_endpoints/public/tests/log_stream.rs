@@ -0,0 +1,390 @@
+// Using a `pub mod` works around spurious dead code warnings; see
+// https://users.rust-lang.org/t/invalid-dead-code-warning-for-submodule-in-integration-test/80259/2 and
+// https://github.com/rust-lang/rust/issues/46379
+pub mod common;
+
+use crate::common::{
+    HttpEndpointBuilder, HttpEndpointHandles, QueryExecutionHandle, get_free_localhost_socket_addr,
+};
+use hyper::StatusCode;
+use ic_config::http_handler::Config;
+use ic_http_endpoints_test_agent::{APPLICATION_CBOR, wait_for_status_healthy};
+use ic_management_canister_types_private::{
+    CanisterInstallMode, CanisterSettingsArgsBuilder, FetchCanisterLogsFilter,
+    FetchCanisterLogsRange, FetchCanisterLogsRequest, FetchCanisterLogsResponse, LogVisibilityV2,
+    Payload,
+};
+use ic_state_machine_tests::StateMachineBuilder;
+use ic_test_utilities_types::ids::canister_test_id;
+use ic_types::{
+    CanisterId, Cycles, PrincipalId,
+    ingress::WasmResult,
+    messages::{
+        Blob, HttpLogStreamItem, HttpQueryContent, HttpQueryResponse, HttpRequestEnvelope,
+        HttpSignedQueryResponse, HttpUserQuery,
+    },
+    time::current_time,
+};
+use reqwest::header::CONTENT_TYPE;
+use std::net::SocketAddr;
+use tokio::{runtime::Runtime, time::Duration};
+
+/// A canister that appends the argument of its `log` method to its log.
+const LOGGING_CANISTER_WAT: &str = r#"
+(module
+    (import "ic0" "msg_arg_data_size" (func $msg_arg_data_size (result i32)))
+    (import "ic0" "msg_arg_data_copy" (func $msg_arg_data_copy (param i32 i32 i32)))
+    (import "ic0" "debug_print" (func $debug_print (param i32 i32)))
+    (import "ic0" "msg_reply" (func $msg_reply))
+    (memory 1)
+    (func (export "canister_update log")
+        (call $msg_arg_data_copy (i32.const 0) (i32.const 0) (call $msg_arg_data_size))
+        (call $debug_print (i32.const 0) (call $msg_arg_data_size))
+        (call $msg_reply)
+    )
+)"#;
+
+fn log_stream_request(
+    canister_id: CanisterId,
+    filter: Option<FetchCanisterLogsFilter>,
+) -> HttpRequestEnvelope<HttpQueryContent> {
+    let args = FetchCanisterLogsRequest {
+        canister_id: canister_id.get(),
+        filter,
+        min_severity: None,
+        fields: None,
+    };
+    HttpRequestEnvelope {
+        content: HttpQueryContent::Query {
+            query: HttpUserQuery {
+                canister_id: Blob(CanisterId::ic_00().get().into_vec()),
+                method_name: "fetch_canister_logs".to_string(),
+                arg: Blob(args.encode()),
+                sender: Blob(PrincipalId::new_anonymous().into_vec()),
+                ingress_expiry: (current_time() + Duration::from_secs(60))
+                    .as_nanos_since_unix_epoch(),
+                nonce: None,
+            },
+        },
+        sender_pubkey: None,
+        sender_sig: None,
+        sender_delegation: None,
+    }
+}
+
+async fn open_log_stream(
+    addr: SocketAddr,
+    canister_id: CanisterId,
+    filter: Option<FetchCanisterLogsFilter>,
+) -> reqwest::Response {
+    let body = serde_cbor::to_vec(&log_stream_request(canister_id, filter)).unwrap();
+    reqwest::Client::new()
+        .post(format!(
+            "http://{addr}/api/v3/canister/{canister_id}/log_stream"
+        ))
+        .body(body)
+        .header(CONTENT_TYPE, APPLICATION_CBOR)
+        .send()
+        .await
+        .unwrap()
+}
+
+/// Reads from the log stream until it contains at least `count` items and
+/// returns all items received so far.
+async fn read_items(
+    response: &mut reqwest::Response,
+    bytes: &mut Vec<u8>,
+    count: usize,
+) -> Vec<HttpLogStreamItem> {
+    loop {
+        // A chunk may end in the middle of an item, which is then parsed once
+        // the remaining bytes have been received.
+        let items: Vec<_> = serde_cbor::Deserializer::from_slice(bytes)
+            .into_iter::<HttpLogStreamItem>()
+            .map_while(Result::ok)
+            .collect();
+        if items.len() >= count {
+            return items;
+        }
+        let chunk = tokio::time::timeout(Duration::from_secs(60), response.chunk())
+            .await
+            .expect("Timed out waiting for log stream items")
+            .unwrap()
+            .expect("Log stream ended unexpectedly");
+        bytes.extend(chunk);
+    }
+}
+
+/// Returns the indices of the records in the item that the client has not
+/// seen yet, i.e., the records starting at the index range in the query
+/// argument.
+fn new_record_idxs(item: &HttpLogStreamItem) -> Vec<u64> {
+    let HttpLogStreamItem::Records {
+        arg,
+        response:
+            HttpSignedQueryResponse {
+                response: HttpQueryResponse::Replied { reply },
+                ..
+            },
+    } = item
+    else {
+        panic!("Unexpected log stream item: {item:?}");
+    };
+    let start = match FetchCanisterLogsRequest::decode(&arg.0).unwrap().filter {
+        Some(FetchCanisterLogsFilter::ByIdx(range)) => range.start(),
+        filter => panic!("Unexpected filter: {filter:?}"),
+    };
+    FetchCanisterLogsResponse::decode(&reply.arg.0)
+        .unwrap()
+        .canister_log_records
+        .into_iter()
+        .map(|record| record.idx)
+        .filter(|idx| *idx >= start)
+        .collect()
+}
+
+/// Answers every query with an empty list of log records.
+fn reply_without_log_records(mut handlers: HttpEndpointHandles, rt: &Runtime) {
+    rt.spawn(async move {
+        loop {
+            let (_, resp) = handlers.query_execution.next_request().await.unwrap();
+            let reply = FetchCanisterLogsResponse {
+                canister_log_records: vec![],
+            }
+            .encode();
+            resp.send_response(Ok((Ok(WasmResult::Reply(reply)), current_time())));
+        }
+    });
+}
+
+/// Answers the next query with an empty list of log records, returning `false`
+/// if no query is executed within 5 seconds.
+async fn answer_next_query(query_execution: &mut QueryExecutionHandle) -> bool {
+    let Ok(request) =
+        tokio::time::timeout(Duration::from_secs(5), query_execution.next_request()).await
+    else {
+        return false;
+    };
+    let (_, resp) = request.unwrap();
+    let reply = FetchCanisterLogsResponse {
+        canister_log_records: vec![],
+    }
+    .encode();
+    resp.send_response(Ok((Ok(WasmResult::Reply(reply)), current_time())));
+    true
+}
+
+/// Tests that the log stream executes the actual `fetch_canister_logs` query
+/// (with the default feature flags, i.e., without index filtering in the
+/// execution environment) and streams every log record exactly once,
+/// reporting records dropped from the log buffer as gaps.
+#[test]
+fn test_log_stream_reports_new_records_and_gaps() {
+    let env = StateMachineBuilder::new().build();
+    let canister_id = env.create_canister_with_cycles(
+        None,
+        Cycles::new(1_000_000_000_000),
+        Some(
+            CanisterSettingsArgsBuilder::new()
+                .with_log_visibility(LogVisibilityV2::Public)
+                .build(),
+        ),
+    );
+    env.install_wasm_in_mode(
+        canister_id,
+        CanisterInstallMode::Install,
+        wat::parse_str(LOGGING_CANISTER_WAT).unwrap(),
+        vec![],
+    )
+    .unwrap();
+    let log = |message: &[u8]| {
+        env.execute_ingress(canister_id, "log", message.to_vec())
+            .unwrap();
+        env.certify_latest_state();
+    };
+    log(b"record 0");
+    log(b"record 1");
+
+    let rt = Runtime::new().unwrap();
+    let addr = get_free_localhost_socket_addr();
+    let config = Config {
+        listen_addr: addr,
+        ..Default::default()
+    };
+    let handlers = HttpEndpointBuilder::new(rt.handle().clone(), config)
+        .with_query_execution_service(env.query_handler.lock().unwrap().clone())
+        .run();
+    let notify_new_certified_state = || {
+        let height = *handlers.certified_height_watcher.borrow();
+        handlers
+            .certified_height_watcher
+            .send(height.increment())
+            .unwrap();
+    };
+
+    // The state machine blocks on its own runtime, so records are logged
+    // outside of the runtime of the HTTP endpoint.
+    let (mut response, mut bytes) = rt.block_on(async {
+        wait_for_status_healthy(&addr).await.unwrap();
+        let response = open_log_stream(addr, canister_id, None).await;
+        assert_eq!(StatusCode::OK, response.status());
+        (response, vec![])
+    });
+
+    let items = rt.block_on(read_items(&mut response, &mut bytes, 1));
+    assert_eq!(new_record_idxs(&items[0]), vec![0, 1]);
+
+    // Re-executing the query without any new records does not send the
+    // records again.
+    notify_new_certified_state();
+    log(b"record 2");
+    notify_new_certified_state();
+    let items = rt.block_on(read_items(&mut response, &mut bytes, 2));
+    assert_eq!(new_record_idxs(&items[1]), vec![2]);
+
+    // Record 3 is evicted from the 4 KiB log buffer by record 4.
+    log(&[b'3'; 3 * 1024]);
+    log(&[b'4'; 3 * 1024]);
+    notify_new_certified_state();
+    let items = rt.block_on(read_items(&mut response, &mut bytes, 4));
+    assert_eq!(
+        items[2],
+        HttpLogStreamItem::Gap {
+            from_idx: 3,
+            to_idx: 4
+        }
+    );
+    assert_eq!(new_record_idxs(&items[3]), vec![4]);
+}
+
+/// Tests that the log stream executes the query at most once per certified
+/// height, i.e., not again if the same height is announced again.
+#[test]
+fn test_log_stream_executes_query_once_per_certified_height() {
+    let rt = Runtime::new().unwrap();
+    let addr = get_free_localhost_socket_addr();
+    let config = Config {
+        listen_addr: addr,
+        ..Default::default()
+    };
+
+    let mut handlers = HttpEndpointBuilder::new(rt.handle().clone(), config).run();
+    let canister_id = canister_test_id(1);
+
+    rt.block_on(async {
+        wait_for_status_healthy(&addr).await.unwrap();
+
+        // The stream keeps the connection open, so the response is awaited
+        // concurrently with the queries it executes.
+        let stream = tokio::spawn(open_log_stream(addr, canister_id, None));
+        assert!(
+            answer_next_query(&mut handlers.query_execution).await,
+            "The query was not executed when the stream was opened"
+        );
+
+        let height = *handlers.certified_height_watcher.borrow();
+        handlers.certified_height_watcher.send(height).unwrap();
+        assert!(
+            !answer_next_query(&mut handlers.query_execution).await,
+            "The query was executed again at the same height"
+        );
+
+        handlers
+            .certified_height_watcher
+            .send(height.increment())
+            .unwrap();
+        assert!(
+            answer_next_query(&mut handlers.query_execution).await,
+            "The query was not executed at the new height"
+        );
+
+        stream.abort();
+    });
+}
+
+/// Tests that log streams cannot be opened with a filter by timestamp.
+#[test]
+fn test_log_stream_rejects_timestamp_filter() {
+    let rt = Runtime::new().unwrap();
+    let addr = get_free_localhost_socket_addr();
+    let config = Config {
+        listen_addr: addr,
+        ..Default::default()
+    };
+
+    HttpEndpointBuilder::new(rt.handle().clone(), config).run();
+    let canister_id = canister_test_id(1);
+
+    rt.block_on(async {
+        wait_for_status_healthy(&addr).await.unwrap();
+
+        let filter = FetchCanisterLogsFilter::ByTimestampNanos(FetchCanisterLogsRange::new(0, 10));
+        let response = open_log_stream(addr, canister_id, Some(filter)).await;
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        assert_eq!(
+            "Log streams can only be filtered by index",
+            response.text().await.unwrap()
+        );
+    });
+}
+
+/// Tests that no more than the configured number of log streams can be open
+/// at the same time.
+#[test]
+fn test_log_stream_concurrency_limit() {
+    let rt = Runtime::new().unwrap();
+    let addr = get_free_localhost_socket_addr();
+    let config = Config {
+        listen_addr: addr,
+        max_log_stream_concurrent_requests: 1,
+        ..Default::default()
+    };
+
+    let handlers = HttpEndpointBuilder::new(rt.handle().clone(), config).run();
+    reply_without_log_records(handlers, &rt);
+    let canister_id = canister_test_id(1);
+
+    rt.block_on(async {
+        wait_for_status_healthy(&addr).await.unwrap();
+
+        let first = open_log_stream(addr, canister_id, None).await;
+        assert_eq!(StatusCode::OK, first.status());
+
+        let second = open_log_stream(addr, canister_id, None).await;
+        assert_eq!(StatusCode::TOO_MANY_REQUESTS, second.status());
+        assert_eq!(
+            "Too many concurrent log streams, try again later",
+            second.text().await.unwrap()
+        );
+    });
+}
+
+/// Tests that a log stream ends once the configured timeout has passed, even
+/// if the ingress expiry of the request lies further in the future.
+#[test]
+fn test_log_stream_times_out() {
+    let rt = Runtime::new().unwrap();
+    let addr = get_free_localhost_socket_addr();
+    let config = Config {
+        listen_addr: addr,
+        log_stream_timeout_seconds: 0,
+        ..Default::default()
+    };
+
+    let handlers = HttpEndpointBuilder::new(rt.handle().clone(), config).run();
+    reply_without_log_records(handlers, &rt);
+    let canister_id = canister_test_id(1);
+
+    rt.block_on(async {
+        wait_for_status_healthy(&addr).await.unwrap();
+
+        let response = open_log_stream(addr, canister_id, None).await;
+        assert_eq!(StatusCode::OK, response.status());
+        let body = tokio::time::timeout(Duration::from_secs(60), response.bytes())
+            .await
+            .expect("Log stream did not time out")
+            .unwrap();
+        assert!(body.is_empty());
+    });
+}
@@ -24,6 +24,10 @@ use ic_interfaces::{
 use ic_interfaces_registry::RegistryClient;
 use ic_interfaces_state_manager::StateReader;
 use ic_logger::{ReplicaLogger, warn};
+use ic_management_canister_types_private::{
+    CanisterHttpAggregatedResponsePayload, CanisterHttpNodeResponse, CanisterHttpResponsePayload,
+    Payload as _,
+};
 use ic_metrics::MetricsRegistry;
 use ic_registry_client_helpers::subnet::SubnetRegistry;
 use ic_replicated_state::ReplicatedState;
@@ -34,9 +38,9 @@ use ic_types::{
     },
     canister_http::{
         CANISTER_HTTP_MAX_RESPONSES_PER_BLOCK, CANISTER_HTTP_TIMEOUT_INTERVAL,
-        CanisterHttpRequestContext, CanisterHttpResponse, CanisterHttpResponseContent,
-        CanisterHttpResponseDivergence, CanisterHttpResponseMetadata, CanisterHttpResponseProof,
-        CanisterHttpResponseWithConsensus, Replication,
+        CanisterHttpAggregatedResponses, CanisterHttpRequestContext, CanisterHttpResponse,
+        CanisterHttpResponseContent, CanisterHttpResponseDivergence, CanisterHttpResponseMetadata,
+        CanisterHttpResponseProof, CanisterHttpResponseWithConsensus, Replication,
     },
     consensus::Committee,
     crypto::Signed,
@@ -66,6 +70,7 @@ pub struct CanisterHttpBatchStats {
     pub timeouts: usize,
     pub divergence_responses: usize,
     pub single_signature_responses: usize,
+    pub aggregated_responses: usize,
     pub payload_bytes: usize,
 }
 
@@ -78,6 +83,18 @@ enum CandidateOrDivergence {
         ),
     ),
     Divergence(CanisterHttpResponseDivergence),
+    /// The individual responses of distinct nodes to a request whose responses are
+    /// aggregated by the canister, each with the signature of the node that received it.
+    Aggregated(
+        (
+            CallbackId,
+            Vec<(
+                CanisterHttpResponseMetadata,
+                BasicSignature<CanisterHttpResponseMetadata>,
+                CanisterHttpResponse,
+            )>,
+        ),
+    ),
 }
 
 /// Implementation of the [`BatchPayloadBuilder`] for the canister http feature.
@@ -207,6 +224,7 @@ impl CanisterHttpPayloadBuilderImpl {
         let mut candidates = vec![];
         let mut timeouts = vec![];
         let mut divergence_responses = vec![];
+        let mut aggregated_candidates = vec![];
 
         // Metrics counters
         let mut unique_includable_responses = 0;
@@ -291,6 +309,37 @@ impl CanisterHttpPayloadBuilderImpl {
             let candidates_and_divergences = response_candidates_by_callback_id
                 .into_iter()
                 .filter_map(|(id, grouped_shares)| {
+                    if let Some(Replication::AggregatedByCanister) = canister_http_request_contexts
+                        .get(&id)
+                        .map(|context| &context.replication)
+                    {
+                        // The responses are not required to agree, so there is no divergence.
+                        // Instead, we pick one share per signer, for which we have the response,
+                        // and include them once sufficiently many nodes have responded.
+                        unique_responses_count += grouped_shares.len();
+                        let mut signers = BTreeSet::new();
+                        let responses: Vec<_> = grouped_shares
+                            .iter()
+                            .filter_map(|(metadata, shares)| {
+                                pool_access
+                                    .get_response_content_by_hash(&metadata.content_hash)
+                                    .map(|content| (metadata, shares, content))
+                            })
+                            .flat_map(|(metadata, shares, content)| {
+                                shares
+                                    .iter()
+                                    .map(move |share| (metadata, share, content.clone()))
+                            })
+                            .filter(|(_, share, _)| signers.insert(share.signature.signer))
+                            .map(|(metadata, share, content)| {
+                                (metadata.clone(), share.signature.clone(), content)
+                            })
+                            .collect();
+
+                        return (responses.len() >= threshold)
+                            .then_some(CandidateOrDivergence::Aggregated((id, responses)));
+                    }
+
                     let consensus_candidate =
                         grouped_shares.iter().find_map(|(metadata, shares)| {
                             unique_responses_count += 1;
@@ -315,6 +364,8 @@ impl CanisterHttpPayloadBuilderImpl {
                                         None
                                     }
                                 }
+                                // Handled above
+                                Some(Replication::AggregatedByCanister) => None,
                             }
                         });
 
@@ -372,6 +423,21 @@ impl CanisterHttpPayloadBuilderImpl {
                             accumulated_size += divergence_size;
                         }
                     }
+                    CandidateOrDivergence::Aggregated((id, responses)) => {
+                        let aggregated_size = id.count_bytes()
+                            + responses
+                                .iter()
+                                .map(|(_, _, content)| {
+                                    size_of::<CanisterHttpResponseProof>() + content.count_bytes()
+                                })
+                                .sum::<usize>();
+                        let size = NumBytes::new((accumulated_size + aggregated_size) as u64);
+                        if size < max_payload_size {
+                            aggregated_candidates.push((id, responses));
+                            responses_included += 1;
+                            accumulated_size += aggregated_size;
+                        }
+                    }
                 }
 
                 if responses_included >= CANISTER_HTTP_MAX_RESPONSES_PER_BLOCK {
@@ -397,6 +463,23 @@ impl CanisterHttpPayloadBuilderImpl {
                 .collect(),
             timeouts,
             divergence_responses,
+            aggregated_responses: aggregated_candidates
+                .drain(..)
+                .filter_map(|(id, responses)| {
+                    let responses = responses
+                        .into_iter()
+                        .map(|(metadata, share, content)| {
+                            self.aggregate(
+                                consensus_registry_version,
+                                metadata,
+                                BTreeSet::from([share]),
+                                content,
+                            )
+                        })
+                        .collect::<Option<Vec<_>>>()?;
+                    Some(CanisterHttpAggregatedResponses { id, responses })
+                })
+                .collect(),
         }
     }
 
@@ -489,6 +572,77 @@ impl CanisterHttpPayloadBuilderImpl {
                     response.content.id,
                 ));
             }
+
+            // Responses to requests aggregated by the canister must be delivered together
+            if let Some(&CanisterHttpRequestContext {
+                replication: Replication::AggregatedByCanister,
+                ..
+            }) = http_contexts.get(&response.content.id)
+            {
+                return invalid_artifact(InvalidCanisterHttpPayloadReason::UnexpectedReplication(
+                    response.content.id,
+                ));
+            }
+        }
+
+        // Check conditions on responses aggregated by the canister
+        let mut aggregated_ids = HashSet::new();
+        for aggregated in &payload.aggregated_responses {
+            let callback_id = aggregated.id;
+
+            if !matches!(
+                http_contexts.get(&callback_id),
+                Some(&CanisterHttpRequestContext {
+                    replication: Replication::AggregatedByCanister,
+                    ..
+                })
+            ) {
+                return invalid_artifact(InvalidCanisterHttpPayloadReason::UnexpectedReplication(
+                    callback_id,
+                ));
+            }
+
+            if delivered_ids.contains(&callback_id) || !aggregated_ids.insert(callback_id) {
+                return invalid_artifact(InvalidCanisterHttpPayloadReason::DuplicateResponse(
+                    callback_id,
+                ));
+            }
+
+            let mut signers = BTreeSet::new();
+            for response in &aggregated.responses {
+                if response.content.id != callback_id {
+                    return invalid_artifact(
+                        InvalidCanisterHttpPayloadReason::AggregatedResponsesIdMismatch {
+                            expected: callback_id,
+                            received: response.content.id,
+                        },
+                    );
+                }
+
+                utils::check_response_consistency(response)
+                    .map_err(CanisterHttpPayloadValidationError::InvalidArtifact)?;
+
+                utils::check_response_against_context(
+                    consensus_registry_version,
+                    response,
+                    validation_context,
+                )
+                .map_err(CanisterHttpPayloadValidationError::InvalidArtifact)?;
+
+                // Each response must be signed by exactly one node, and no node may
+                // contribute more than one response
+                let signatures_map = &response.proof.signature.signatures_map;
+                match signatures_map.keys().next() {
+                    Some(signer) if signatures_map.len() == 1 && signers.insert(*signer) => (),
+                    _ => {
+                        return invalid_artifact(
+                            InvalidCanisterHttpPayloadReason::InvalidAggregatedResponseSigner(
+                                callback_id,
+                            ),
+                        );
+                    }
+                }
+            }
         }
 
         // Check that there are no duplicate responses among non-replicated requests.
@@ -547,6 +701,15 @@ impl CanisterHttpPayloadBuilderImpl {
                     };
                     (committee.clone(), threshold)
                 }
+                // Rejected above
+                Some(&CanisterHttpRequestContext {
+                    replication: Replication::AggregatedByCanister,
+                    ..
+                }) => {
+                    return invalid_artifact(
+                        InvalidCanisterHttpPayloadReason::UnexpectedReplication(callback_id),
+                    );
+                }
             };
 
             let (valid_signers, invalid_signers): (Vec<NodeId>, Vec<NodeId>) = response
@@ -581,6 +744,54 @@ impl CanisterHttpPayloadBuilderImpl {
                 })?;
         }
 
+        if !payload.aggregated_responses.is_empty() {
+            let threshold = match self
+                .membership
+                .get_committee_threshold(height, Committee::CanisterHttp)
+            {
+                Ok(threshold) => threshold,
+                Err(err) => {
+                    warn!(self.log, "Failed to get membership: {:?}", err);
+                    return validation_failed(CanisterHttpPayloadValidationFailure::Membership);
+                }
+            };
+
+            for aggregated in &payload.aggregated_responses {
+                // The signers are distinct, as checked above
+                let (valid_signers, invalid_signers): (Vec<NodeId>, Vec<NodeId>) = aggregated
+                    .responses
+                    .iter()
+                    .flat_map(|response| response.proof.signature.signatures_map.keys())
+                    .cloned()
+                    .partition(|signer| committee.iter().any(|id| id == signer));
+                if !invalid_signers.is_empty() {
+                    return invalid_artifact(InvalidCanisterHttpPayloadReason::SignersNotMembers {
+                        invalid_signers,
+                        committee,
+                        valid_signers,
+                    });
+                }
+
+                if valid_signers.len() < threshold {
+                    return invalid_artifact(InvalidCanisterHttpPayloadReason::NotEnoughSigners {
+                        committee,
+                        signers: valid_signers,
+                        expected_threshold: threshold,
+                    });
+                }
+
+                for response in &aggregated.responses {
+                    self.crypto
+                        .verify_aggregate(&response.proof, consensus_registry_version)
+                        .map_err(|err| {
+                            CanisterHttpPayloadValidationError::InvalidArtifact(
+                                InvalidCanisterHttpPayloadReason::SignatureError(Box::new(err)),
+                            )
+                        })?;
+                }
+            }
+        }
+
         let faults_tolerated = match self.membership.get_canister_http_committee(height) {
             Ok(members) => ic_types::consensus::get_faults_tolerated(members.len()),
             _ => {
@@ -620,7 +831,17 @@ impl CanisterHttpPayloadBuilderImpl {
                     InvalidCanisterHttpPayloadReason::DivergenceProofContainsMultipleCallbackIds,
                 );
             }
-            for (_, grouped_shares) in grouped_shares {
+            for (callback_id, grouped_shares) in grouped_shares {
+                // Diverging responses are expected for requests aggregated by the canister
+                if let Some(&CanisterHttpRequestContext {
+                    replication: Replication::AggregatedByCanister,
+                    ..
+                }) = http_contexts.get(&callback_id)
+                {
+                    return invalid_artifact(
+                        InvalidCanisterHttpPayloadReason::UnexpectedReplication(callback_id),
+                    );
+                }
                 if !grouped_shares_meet_divergence_criteria(&grouped_shares, faults_tolerated) {
                     return invalid_artifact(
                         InvalidCanisterHttpPayloadReason::DivergenceProofDoesNotMeetDivergenceCriteria,
@@ -756,9 +977,18 @@ impl IntoMessages<(Vec<ConsensusResponse>, CanisterHttpBatchStats)>
             .filter_map(divergence_response_into_reject)
             .inspect(|_| stats.divergence_responses += 1);
 
+        let aggregated_responses = messages.aggregated_responses.into_iter().map(|aggregated| {
+            stats.aggregated_responses += 1;
+            ConsensusResponse::new(
+                aggregated.id,
+                aggregated_responses_into_payload(aggregated.responses),
+            )
+        });
+
         let responses = responses
             .chain(timeouts)
             .chain(divergece_responses)
+            .chain(aggregated_responses)
             .collect();
 
         (responses, stats)
@@ -830,6 +1060,35 @@ fn divergence_response_into_reject(
     ))
 }
 
+/// Turns the individual responses to a request aggregated by the canister into a
+/// [`Payload`] containing a candid encoded [`CanisterHttpAggregatedResponsePayload`].
+///
+/// Responses that were rejected by the node, or that can not be decoded, are delivered
+/// as errors, such that the canister still learns which nodes have responded.
+fn aggregated_responses_into_payload(responses: Vec<CanisterHttpResponseWithConsensus>) -> Payload {
+    let responses = responses
+        .into_iter()
+        .filter_map(|response| {
+            // NOTE: Every response is signed by exactly one node, as checked during validation
+            let node_id = *response.proof.signature.signatures_map.keys().next()?;
+            let result = match response.content.content {
+                CanisterHttpResponseContent::Success(data) => {
+                    CanisterHttpResponsePayload::decode(&data).map_err(|err| err.to_string())
+                }
+                CanisterHttpResponseContent::Reject(canister_http_reject) => {
+                    Err(canister_http_reject.message)
+                }
+            };
+            Some(CanisterHttpNodeResponse {
+                node_id: node_id.get(),
+                result,
+            })
+        })
+        .collect();
+
+    Payload::Data(CanisterHttpAggregatedResponsePayload { responses }.encode())
+}
+
 fn validation_failed(
     err: CanisterHttpPayloadValidationFailure,
 ) -> Result<(), PayloadValidationError> {
@@ -24,6 +24,9 @@ pub(crate) fn bytes_to_payload(data: &[u8]) -> Result<CanisterHttpPayload, Proxy
             Some(MessageType::DivergenceResponse(response)) => {
                 payload.divergence_responses.push(response.try_into()?)
             }
+            Some(MessageType::AggregatedResponses(response)) => {
+                payload.aggregated_responses.push(response.try_into()?)
+            }
             None => return Err(ProxyDecodeError::MissingField("message_type")),
         }
     }
@@ -55,7 +58,14 @@ pub(crate) fn payload_to_bytes(payload: &CanisterHttpPayload, max_size: NumBytes
                             pb::CanisterHttpResponseWithConsensus::from(response),
                         )),
                     }),
-            );
+            )
+            .chain(payload.aggregated_responses.iter().map(|response| {
+                CanisterHttpResponseMessage {
+                    message_type: Some(MessageType::AggregatedResponses(
+                        pb::CanisterHttpAggregatedResponses::from(response),
+                    )),
+                }
+            }));
 
     iterator_to_bytes(message_iterator, max_size)
 }
@@ -94,6 +104,7 @@ fn get_id_from_message(message: CanisterHttpResponseMessage) -> Option<u64> {
             .shares
             .first()
             .and_then(|share| share.metadata.as_ref().map(|md| md.id)),
+        Some(MessageType::AggregatedResponses(response)) => Some(response.id),
         Some(MessageType::Timeout(id)) => Some(id),
         None => None,
     }
@@ -22,6 +22,10 @@ use ic_interfaces::{
     validation::ValidationError,
 };
 use ic_logger::replica_logger::no_op_logger;
+use ic_management_canister_types_private::{
+    CanisterHttpAggregatedResponsePayload, CanisterHttpNodeResponse, CanisterHttpResponsePayload,
+    Payload as _,
+};
 use ic_metrics::MetricsRegistry;
 use ic_registry_subnet_features::SubnetFeatures;
 use ic_test_utilities::state_manager::RefMockStateManager;
@@ -34,7 +38,8 @@ use ic_types::{
     Height, NumBytes, RegistryVersion, ReplicaVersion, Time,
     batch::{CanisterHttpPayload, MAX_CANISTER_HTTP_PAYLOAD_SIZE, ValidationContext},
     canister_http::{
-        CANISTER_HTTP_MAX_RESPONSES_PER_BLOCK, CANISTER_HTTP_TIMEOUT_INTERVAL, CanisterHttpMethod,
+        CANISTER_HTTP_MAX_RESPONSES_PER_BLOCK, CANISTER_HTTP_TIMEOUT_INTERVAL,
+        CanisterHttpAggregatedResponses, CanisterHttpMethod, CanisterHttpReject,
         CanisterHttpRequestContext, CanisterHttpResponse, CanisterHttpResponseArtifact,
         CanisterHttpResponseContent, CanisterHttpResponseDivergence, CanisterHttpResponseMetadata,
         CanisterHttpResponseShare, CanisterHttpResponseWithConsensus,
@@ -49,7 +54,7 @@ use ic_types::{
 use rand::Rng;
 use rand_chacha::{ChaCha20Rng, rand_core::SeedableRng};
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     ops::DerefMut,
     sync::{Arc, RwLock},
     time::Duration,
@@ -217,6 +222,7 @@ fn multiple_payload_test() {
                 }],
                 timeouts: vec![],
                 divergence_responses: vec![],
+                aggregated_responses: vec![],
             };
             let past_payload = payload_to_bytes(&past_payload, NumBytes::new(4 * 1024 * 1024));
 
@@ -643,6 +649,7 @@ fn duplicate_validation() {
             responses: vec![response_and_metadata_to_proof(&response, &metadata)],
             timeouts: vec![],
             divergence_responses: vec![],
+            aggregated_responses: vec![],
         };
         let payload = payload_to_bytes(&payload, NumBytes::new(4 * 1024 * 1024));
         let past_payloads = vec![PastPayload {
@@ -696,6 +703,7 @@ fn divergence_response_validation_test() {
                         }))
                         .collect(),
                 }],
+                aggregated_responses: vec![],
             };
             let payload = payload_to_bytes(&payload, NumBytes::new(4 * 1024 * 1024));
 
@@ -716,6 +724,7 @@ fn divergence_response_validation_test() {
                         .map(|node_id| metadata_to_share(node_id.try_into().unwrap(), &metadata))
                         .collect(),
                 }],
+                aggregated_responses: vec![],
             };
             let payload = payload_to_bytes(&payload, NumBytes::new(4 * 1024 * 1024));
 
@@ -753,6 +762,7 @@ fn divergence_response_validation_test() {
                         }))
                         .collect(),
                 }],
+                aggregated_responses: vec![],
             };
             let payload = payload_to_bytes(&payload, NumBytes::new(4 * 1024 * 1024));
 
@@ -1532,6 +1542,301 @@ fn validate_payload_fails_for_duplicate_non_replicated_response() {
     });
 }
 
+#[test]
+fn aggregated_request_includes_responses_of_all_nodes() {
+    // This test ensures that the diverging responses of the individual nodes to a
+    // request aggregated by the canister are included together, each with the
+    // signature of the node that received it.
+
+    // ARRANGE
+    test_config_with_http_feature(true, 4, |mut payload_builder, canister_http_pool| {
+        let callback_id = CallbackId::from(42);
+        set_aggregated_request_context(&mut payload_builder, callback_id);
+
+        // Every node receives a different response.
+        {
+            let mut pool_access = canister_http_pool.write().unwrap();
+            let artifacts = (0..4)
+                .map(|node| {
+                    let (response, metadata) = test_response_and_metadata_with_content(
+                        callback_id.get(),
+                        CanisterHttpResponseContent::Success(vec![node as u8]),
+                    );
+                    CanisterHttpResponseArtifact {
+                        share: metadata_to_share(node, &metadata),
+                        response: Some(response),
+                    }
+                })
+                .collect();
+            add_received_artifacts_to_pool(pool_access.deref_mut(), artifacts);
+        }
+
+        // ACT
+        let payload = payload_builder.build_payload(
+            Height::new(1),
+            NumBytes::new(MAX_CANISTER_HTTP_PAYLOAD_SIZE as u64),
+            &[],
+            &default_validation_context(),
+        );
+
+        // ASSERT
+        payload_builder
+            .validate_payload(
+                Height::from(1),
+                &test_proposal_context(&default_validation_context()),
+                &payload,
+                &[],
+            )
+            .unwrap();
+
+        let parsed_payload = bytes_to_payload(&payload).expect("Failed to parse payload");
+        assert!(parsed_payload.responses.is_empty());
+        assert!(parsed_payload.divergence_responses.is_empty());
+        assert_eq!(parsed_payload.aggregated_responses.len(), 1);
+
+        let aggregated = &parsed_payload.aggregated_responses[0];
+        assert_eq!(aggregated.id, callback_id);
+        let signers = aggregated
+            .responses
+            .iter()
+            .flat_map(|response| {
+                assert_eq!(response.proof.signature.signatures_map.len(), 1);
+                response.proof.signature.signatures_map.keys().cloned()
+            })
+            .collect::<BTreeSet<_>>();
+        assert_eq!(signers, (0..4).map(node_test_id).collect());
+    });
+}
+
+#[test]
+fn aggregated_request_is_not_included_without_enough_responses() {
+    // ARRANGE
+    test_config_with_http_feature(true, 4, |mut payload_builder, canister_http_pool| {
+        let callback_id = CallbackId::from(42);
+        set_aggregated_request_context(&mut payload_builder, callback_id);
+
+        // Only a single node has responded.
+        {
+            let mut pool_access = canister_http_pool.write().unwrap();
+            let (response, metadata) = test_response_and_metadata(callback_id.get());
+            add_received_artifacts_to_pool(
+                pool_access.deref_mut(),
+                vec![CanisterHttpResponseArtifact {
+                    share: metadata_to_share(1, &metadata),
+                    response: Some(response),
+                }],
+            );
+        }
+
+        // ACT
+        let payload = payload_builder.build_payload(
+            Height::new(1),
+            NumBytes::new(MAX_CANISTER_HTTP_PAYLOAD_SIZE as u64),
+            &[],
+            &default_validation_context(),
+        );
+
+        // ASSERT
+        let parsed_payload = bytes_to_payload(&payload).expect("Failed to parse payload");
+        assert_eq!(parsed_payload, CanisterHttpPayload::default());
+    });
+}
+
+#[test]
+fn validate_payload_fails_for_aggregated_responses_with_duplicate_signer() {
+    // ARRANGE
+    test_config_with_http_feature(true, 4, |mut payload_builder, _| {
+        let callback_id = CallbackId::from(42);
+        set_aggregated_request_context(&mut payload_builder, callback_id);
+
+        // All responses are signed by the same node.
+        let responses = (0..4)
+            .map(|node| {
+                let (response, metadata) = test_response_and_metadata_with_content(
+                    callback_id.get(),
+                    CanisterHttpResponseContent::Success(vec![node]),
+                );
+                let mut proof = response_and_metadata_to_proof(&response, &metadata);
+                proof
+                    .proof
+                    .signature
+                    .signatures_map
+                    .insert(node_test_id(1), BasicSigOf::new(BasicSig(vec![])));
+                proof
+            })
+            .collect();
+        let payload = CanisterHttpPayload {
+            aggregated_responses: vec![CanisterHttpAggregatedResponses {
+                id: callback_id,
+                responses,
+            }],
+            ..Default::default()
+        };
+        let payload_bytes = payload_to_bytes(&payload, NumBytes::new(4 * 1024 * 1024));
+
+        // ACT
+        let validation_result = payload_builder.validate_payload(
+            Height::from(1),
+            &test_proposal_context(&default_validation_context()),
+            &payload_bytes,
+            &[],
+        );
+
+        // ASSERT
+        match validation_result {
+            Err(ValidationError::InvalidArtifact(
+                InvalidPayloadReason::InvalidCanisterHttpPayload(
+                    InvalidCanisterHttpPayloadReason::InvalidAggregatedResponseSigner(id),
+                ),
+            )) => assert_eq!(id, callback_id),
+            res => panic!("Expected InvalidAggregatedResponseSigner error, but got {res:?}"),
+        }
+    });
+}
+
+#[test]
+fn validate_payload_fails_for_single_response_to_aggregated_request() {
+    // ARRANGE
+    test_config_with_http_feature(true, 4, |mut payload_builder, _| {
+        let callback_id = CallbackId::from(42);
+        set_aggregated_request_context(&mut payload_builder, callback_id);
+
+        let (response, metadata) = test_response_and_metadata(callback_id.get());
+        let mut proof = response_and_metadata_to_proof(&response, &metadata);
+        for node in 0..4 {
+            proof
+                .proof
+                .signature
+                .signatures_map
+                .insert(node_test_id(node), BasicSigOf::new(BasicSig(vec![])));
+        }
+        let payload = CanisterHttpPayload {
+            responses: vec![proof],
+            ..Default::default()
+        };
+        let payload_bytes = payload_to_bytes(&payload, NumBytes::new(4 * 1024 * 1024));
+
+        // ACT
+        let validation_result = payload_builder.validate_payload(
+            Height::from(1),
+            &test_proposal_context(&default_validation_context()),
+            &payload_bytes,
+            &[],
+        );
+
+        // ASSERT
+        match validation_result {
+            Err(ValidationError::InvalidArtifact(
+                InvalidPayloadReason::InvalidCanisterHttpPayload(
+                    InvalidCanisterHttpPayloadReason::UnexpectedReplication(id),
+                ),
+            )) => assert_eq!(id, callback_id),
+            res => panic!("Expected UnexpectedReplication error, but got {res:?}"),
+        }
+    });
+}
+
+#[test]
+fn aggregated_responses_are_delivered_as_single_reply() {
+    let callback_id = CallbackId::from(42);
+    let http_response = CanisterHttpResponsePayload {
+        status: 200,
+        headers: vec![],
+        body: b"abc".to_vec(),
+    };
+    let proof = |node: u64, content: CanisterHttpResponseContent| {
+        let (response, metadata) =
+            test_response_and_metadata_with_content(callback_id.get(), content);
+        let mut proof = response_and_metadata_to_proof(&response, &metadata);
+        proof
+            .proof
+            .signature
+            .signatures_map
+            .insert(node_test_id(node), BasicSigOf::new(BasicSig(vec![])));
+        proof
+    };
+    let payload = CanisterHttpPayload {
+        aggregated_responses: vec![CanisterHttpAggregatedResponses {
+            id: callback_id,
+            responses: vec![
+                proof(
+                    0,
+                    CanisterHttpResponseContent::Success(http_response.encode()),
+                ),
+                proof(
+                    1,
+                    CanisterHttpResponseContent::Reject(CanisterHttpReject {
+                        reject_code: RejectCode::SysTransient,
+                        message: "connection refused".to_string(),
+                    }),
+                ),
+            ],
+        }],
+        ..Default::default()
+    };
+    let payload_bytes = payload_to_bytes(&payload, NumBytes::new(4 * 1024 * 1024));
+
+    let (responses, stats) = CanisterHttpPayloadBuilderImpl::into_messages(&payload_bytes);
+
+    assert_eq!(stats.aggregated_responses, 1);
+    assert_eq!(responses.len(), 1);
+    assert_eq!(responses[0].callback, callback_id);
+    let Payload::Data(data) = &responses[0].payload else {
+        panic!("Expected a reply, got {:?}", responses[0].payload);
+    };
+    assert_eq!(
+        CanisterHttpAggregatedResponsePayload::decode(data).unwrap(),
+        CanisterHttpAggregatedResponsePayload {
+            responses: vec![
+                CanisterHttpNodeResponse {
+                    node_id: node_test_id(0).get(),
+                    result: Ok(http_response),
+                },
+                CanisterHttpNodeResponse {
+                    node_id: node_test_id(1).get(),
+                    result: Err("connection refused".to_string()),
+                },
+            ],
+        }
+    );
+}
+
+/// Inserts the context of a request aggregated by the canister into the state
+/// used by the payload builder.
+fn set_aggregated_request_context(
+    payload_builder: &mut CanisterHttpPayloadBuilderImpl,
+    callback_id: CallbackId,
+) {
+    let request_context = CanisterHttpRequestContext {
+        request: RequestBuilder::default().build(),
+        url: "https://example.com".to_string(),
+        max_response_bytes: None,
+        headers: vec![],
+        body: None,
+        http_method: CanisterHttpMethod::GET,
+        transform: None,
+        time: UNIX_EPOCH,
+        replication: ic_types::canister_http::Replication::AggregatedByCanister,
+        pricing_version: ic_types::canister_http::PricingVersion::Legacy,
+    };
+
+    let mut init_state = ic_test_utilities_state::get_initial_state(0, 0);
+    init_state
+        .metadata
+        .subnet_call_context_manager
+        .canister_http_request_contexts
+        .insert(callback_id, request_context);
+    let state_manager = Arc::new(RefMockStateManager::default());
+    state_manager
+        .get_mut()
+        .expect_get_state_at()
+        .return_const(Ok(ic_interfaces_state_manager::Labeled::new(
+            Height::new(0),
+            Arc::new(init_state),
+        )));
+    payload_builder.state_reader = state_manager;
+}
+
 /// Build some test metadata and response, which is valid and can be used in
 /// different tests
 pub(crate) fn test_response_and_metadata(
@@ -1781,6 +2086,7 @@ where
             responses: vec![response_and_metadata_to_proof(&response, &metadata)],
             timeouts: vec![],
             divergence_responses: vec![],
+            aggregated_responses: vec![],
         };
 
         let payload = payload_to_bytes(&payload, NumBytes::new(4 * 1024 * 1024));
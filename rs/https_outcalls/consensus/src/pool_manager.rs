@@ -392,8 +392,13 @@ impl CanisterHttpPoolManagerImpl {
                     self.requested_id_cache.borrow_mut().remove(&response.id);
                     self.metrics.shares_signed.inc();
 
+                    // Responses that are not agreed upon by all nodes need to be gossiped,
+                    // since the other nodes can not obtain them otherwise.
                     if let Some(context) = active_contexts.get(&response.id)
-                        && matches!(context.replication, Replication::NonReplicated(_))
+                        && matches!(
+                            context.replication,
+                            Replication::NonReplicated(_) | Replication::AggregatedByCanister
+                        )
                     {
                         if let Err(err) =
                             validate_response_size(&response, context.max_response_bytes)
@@ -481,18 +486,20 @@ impl CanisterHttpPoolManagerImpl {
                 };
 
                 match context.replication {
-                    Replication::NonReplicated(node_id) => {
-                        if node_id != share.signature.signer {
+                    Replication::NonReplicated(node_id) if node_id != share.signature.signer => {
+                        return Some(CanisterHttpChangeAction::HandleInvalid(
+                            share.clone(),
+                            "Share signed by node that is not the delegated node for the request"
+                                .to_string(),
+                        ));
+                    }
+                    Replication::NonReplicated(_) | Replication::AggregatedByCanister => {
+                        let Some(response) = &artifact.response else {
+                            // The request is not fully replicated, but the response is missing.
                             return Some(CanisterHttpChangeAction::HandleInvalid(
                                 share.clone(),
-                                "Share signed by node that is not the delegated node for the request".to_string(),
+                                "Artifact should contain response".to_string(),
                             ));
-                        }
-
-                        let Some(response) = &artifact.response else {
-                            // The request is not fully replicated, but the response is missing.
-                            return Some(CanisterHttpChangeAction::HandleInvalid(share.clone(),
-                                "Artifact should contain response".to_string()));
                         };
 
                         if share.content.content_hash != ic_types::crypto::crypto_hash(response) {
@@ -509,10 +516,15 @@ impl CanisterHttpPoolManagerImpl {
                         // As we still want to set a limit for failure, we enforce 1KB, which is reasonable for
                         // an error message.
 
-                        if let Err(err) = validate_response_size(response, context.max_response_bytes) {
+                        if let Err(err) =
+                            validate_response_size(response, context.max_response_bytes)
+                        {
                             return Some(CanisterHttpChangeAction::HandleInvalid(
                                 share.clone(),
-                                format!("Http Response for request ID {} is too large: {}", response.id, err),
+                                format!(
+                                    "Http Response for request ID {} is too large: {}",
+                                    response.id, err
+                                ),
                             ));
                         }
                     }
@@ -1209,6 +1221,130 @@ pub mod test {
         });
     }
 
+    #[test]
+    fn test_aggregated_share_validation_logic() {
+        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+            with_test_replica_logger(|log| {
+                let Dependencies {
+                    pool,
+                    replica_config,
+                    crypto,
+                    state_manager,
+                    registry,
+                    ..
+                } = dependencies(pool_config.clone(), 4);
+
+                // Any node of the committee may sign a response to an aggregated request.
+                let signer_id = ic_test_utilities_types::ids::node_test_id(2);
+                let callback_id = CallbackId::from(0);
+
+                let request_context = CanisterHttpRequestContext {
+                    request: ic_test_utilities_types::messages::RequestBuilder::new().build(),
+                    url: "".to_string(),
+                    max_response_bytes: None,
+                    headers: vec![],
+                    body: None,
+                    http_method: CanisterHttpMethod::GET,
+                    transform: None,
+                    time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    replication: Replication::AggregatedByCanister,
+                    pricing_version: PricingVersion::Legacy,
+                };
+                state_manager
+                    .get_mut()
+                    .expect_get_latest_state()
+                    .return_const(Labeled::new(
+                        Height::from(1),
+                        Arc::new(state_with_pending_http_calls(BTreeMap::from([(
+                            callback_id,
+                            request_context,
+                        )]))),
+                    ));
+
+                let response = empty_canister_http_response(callback_id.get());
+                let response_metadata = CanisterHttpResponseMetadata {
+                    id: callback_id,
+                    timeout: response.timeout,
+                    registry_version: RegistryVersion::from(1),
+                    content_hash: ic_types::crypto::crypto_hash(&response),
+                    replica_version: ReplicaVersion::default(),
+                };
+                let share = Signed {
+                    content: response_metadata.clone(),
+                    signature: crypto
+                        .sign(&response_metadata, signer_id, RegistryVersion::from(1))
+                        .unwrap(),
+                };
+
+                let pool_manager = CanisterHttpPoolManagerImpl::new(
+                    state_manager,
+                    Arc::new(Mutex::new(Box::new(MockNonBlockingChannel::new()))),
+                    crypto,
+                    pool.get_cache(),
+                    replica_config,
+                    SubnetType::Application,
+                    Arc::clone(&registry) as Arc<_>,
+                    MetricsRegistry::new(),
+                    log,
+                );
+
+                // TEST 1: The artifact is missing the response.
+                // It should be marked as invalid.
+                {
+                    let mut canister_http_pool =
+                        CanisterHttpPoolImpl::new(MetricsRegistry::new(), no_op_logger());
+                    canister_http_pool.insert(UnvalidatedArtifact {
+                        message: CanisterHttpResponseArtifact {
+                            share: share.clone(),
+                            response: None,
+                        },
+                        peer_id: signer_id,
+                        timestamp: UNIX_EPOCH,
+                    });
+
+                    let change_set = pool_manager.validate_shares(
+                        pool.get_cache().as_ref(),
+                        &canister_http_pool,
+                        Height::from(1),
+                    );
+
+                    assert_eq!(change_set.len(), 1, "Expected exactly one change action");
+                    assert_matches!(
+                        &change_set[0],
+                        CanisterHttpChangeAction::HandleInvalid(_, reason) if reason == "Artifact should contain response"
+                    );
+                }
+
+                // TEST 2: The artifact contains the response.
+                // It should be moved to the validated pool.
+                {
+                    let mut canister_http_pool =
+                        CanisterHttpPoolImpl::new(MetricsRegistry::new(), no_op_logger());
+                    canister_http_pool.insert(UnvalidatedArtifact {
+                        message: CanisterHttpResponseArtifact {
+                            share: share.clone(),
+                            response: Some(response),
+                        },
+                        peer_id: signer_id,
+                        timestamp: UNIX_EPOCH,
+                    });
+
+                    let change_set = pool_manager.validate_shares(
+                        pool.get_cache().as_ref(),
+                        &canister_http_pool,
+                        Height::from(1),
+                    );
+
+                    assert_eq!(change_set.len(), 1, "Expected exactly one change action");
+                    assert_matches!(
+                        &change_set[0],
+                        CanisterHttpChangeAction::MoveToValidated(validated) if *validated == share
+                    );
+                }
+            })
+        });
+    }
+
     #[test]
     fn test_non_replicated_share_from_wrong_signer_is_invalid() {
         ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
@@ -301,6 +301,7 @@ mod tests {
         GuestVMType, ICOSSettings, Ipv6Config, NetworkSettings,
     };
     use ic_config::{ConfigOptional, config_parser::ConfigSource};
+    use std::path::PathBuf;
 
     #[test]
     fn test_generate_ipv6_prefix() {
@@ -368,6 +369,10 @@ mod tests {
 
         let hypervisor_config = parsed_config.hypervisor.as_ref().unwrap();
         assert_eq!(hypervisor_config.query_stats_epoch_length, 600);
+        assert_eq!(
+            hypervisor_config.persistent_compilation_cache_dir,
+            Some(PathBuf::from("/var/lib/ic/data/ic_compilation_cache"))
+        );
 
         let tracing_config = parsed_config.tracing.as_ref().unwrap();
         assert_eq!(tracing_config.jaeger_addr, Some("".to_string()));
@@ -70,6 +70,17 @@ pub enum InvalidCanisterHttpPayloadReason {
     DuplicateResponse(CallbackId),
     DivergenceProofContainsMultipleCallbackIds,
     DivergenceProofDoesNotMeetDivergenceCriteria,
+    /// The replication mode of the request does not allow this kind of response,
+    /// e.g. a single response to a request whose responses are aggregated by the canister
+    UnexpectedReplication(CallbackId),
+    /// The responses aggregated by the canister do not all belong to the same request
+    AggregatedResponsesIdMismatch {
+        expected: CallbackId,
+        received: CallbackId,
+    },
+    /// A response aggregated by the canister is not signed by exactly one node,
+    /// or multiple responses are signed by the same node
+    InvalidAggregatedResponseSigner(CallbackId),
     /// The payload could not be deserialized
     DecodeError(ProxyDecodeError),
 }
@@ -253,6 +253,10 @@ pub enum SystemApiCallId {
     OutOfInstructions,
     /// Tracker for `ic0.performance_counter()`
     PerformanceCounter,
+    /// Tracker for `ic0.query_cache_invalidate()`
+    QueryCacheInvalidate,
+    /// Tracker for `ic0.query_cache_reply()`
+    QueryCacheReply,
     /// Tracker for `ic0.subnet_self_size()`
     SubnetSelfSize,
     /// Tracker for `ic0.subnet_self_copy()`
@@ -279,6 +283,8 @@ pub enum SystemApiCallId {
     Trap,
     /// Tracker for `__.try_grow_wasm_memory()`
     TryGrowWasmMemory,
+    /// Tracker for `__.try_grow_gc_heap()`
+    TryGrowGcHeap,
     /// Tracker for `ic0.env_var_count()`
     EnvVarCount,
     /// Tracker for `ic0.env_var_name_size()`
@@ -307,6 +313,11 @@ pub struct SystemApiCallCounters {
     pub canister_liquid_cycle_balance128: usize,
     /// Counter for `ic0.time()`
     pub time: usize,
+    /// The caching hint declared with `ic0.query_cache_reply()`, if any.
+    pub query_cache_hint: Option<QueryCacheHint>,
+    /// How many times each System API function (e.g., `ic0.time`) was called.
+    /// Only recorded if System API call profiling is enabled.
+    pub calls_by_function: BTreeMap<String, usize>,
 }
 
 impl SystemApiCallCounters {
@@ -324,9 +335,27 @@ impl SystemApiCallCounters {
             .canister_liquid_cycle_balance128
             .saturating_add(rhs.canister_liquid_cycle_balance128);
         self.time = self.time.saturating_add(rhs.time);
+        if rhs.query_cache_hint.is_some() {
+            self.query_cache_hint = rhs.query_cache_hint;
+        }
+        for (function, count) in rhs.calls_by_function {
+            let total = self.calls_by_function.entry(function).or_default();
+            *total = total.saturating_add(count);
+        }
     }
 }
 
+/// A canister's declaration that its reply to a query may be served from the
+/// query cache even if the canister state changes in the meantime.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct QueryCacheHint {
+    /// How long the reply may be served from the cache.
+    pub ttl: Duration,
+    /// If set, the cached reply is dropped once an update call invalidates
+    /// the tag with `ic0.query_cache_invalidate()`.
+    pub tag: Option<Vec<u8>>,
+}
+
 /// Tracks the available memory on a subnet. The main idea is to separately track
 /// the execution available memory, the message available memory and the wasm custom
 /// sections available memory. The different flavors of memory are independent of each
@@ -1082,6 +1111,12 @@ pub trait SystemApi {
         stable_memory_api: StableMemoryApi,
     ) -> HypervisorResult<StableGrowOutcome>;
 
+    /// This system call is not part of the public spec. It's called before a
+    /// Wasm GC or exception-handling instruction allocates an object of the
+    /// given estimated size on the GC heap to check whether there's enough
+    /// available memory left.
+    fn try_grow_gc_heap(&mut self, additional_bytes: u64) -> HypervisorResult<()>;
+
     /// (deprecated) Please use `ic0_canister_cycle_balance128` instead.
     /// This API supports only 64-bit values.
     ///
@@ -1216,6 +1251,26 @@ pub trait SystemApi {
         heap: &[u8],
     ) -> HypervisorResult<()>;
 
+    /// Declares that the reply to the current query may be served from the
+    /// query cache for `ttl_nanos` nanoseconds, or until the tag (if
+    /// `tag_size > 0`) is invalidated with `ic0_query_cache_invalidate`.
+    /// The declaration only has an effect in non-replicated execution.
+    fn ic0_query_cache_reply(
+        &mut self,
+        ttl_nanos: u64,
+        tag_src: usize,
+        tag_size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<()>;
+
+    /// Invalidates all cached query replies of the canister with the given tag.
+    fn ic0_query_cache_invalidate(
+        &mut self,
+        tag_src: usize,
+        tag_size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<()>;
+
     /// If run in non-replicated execution (i.e. query),
     /// returns 1 if the data certificate is present, 0 otherwise.
     /// If run in replicated execution (i.e. an update call or a certified
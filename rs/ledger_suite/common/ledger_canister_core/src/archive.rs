@@ -9,7 +9,7 @@ use std::collections::VecDeque;
 use std::marker::PhantomData;
 use std::sync::{Arc, RwLock};
 
-use crate::ledger::{LedgerAccess, LedgerData};
+use crate::ledger::BlockchainAccess;
 use ic_ledger_core::block::EncodedBlock;
 
 /// 10 trillion cycles.
@@ -48,17 +48,14 @@ struct ArchivingGuard<Rt: Runtime, Wasm: ArchiveCanisterWasm>(
 );
 
 /// Wraps around `ArchivingGuard` to abstract away the two generic parameters with the single
-/// `LedgerAccess` trait.
-pub struct LedgerArchivingGuard<LA: LedgerAccess> {
-    _guard: ArchivingGuard<
-        <LA::Ledger as LedgerData>::Runtime,
-        <LA::Ledger as LedgerData>::ArchiveWasm,
-    >,
+/// `BlockchainAccess` trait.
+pub struct LedgerArchivingGuard<LA: BlockchainAccess> {
+    _guard: ArchivingGuard<LA::Runtime, LA::ArchiveWasm>,
 }
 
-impl<LA: LedgerAccess> LedgerArchivingGuard<LA> {
+impl<LA: BlockchainAccess> LedgerArchivingGuard<LA> {
     pub fn new() -> Result<Self, ArchivingGuardError> {
-        let archive_arc = LA::with_ledger(|ledger| ledger.blockchain().archive.clone());
+        let archive_arc = LA::with_blockchain(|blockchain| blockchain.archive.clone());
         ArchivingGuard::new(Arc::clone(&archive_arc)).map(|guard| Self { _guard: guard })
     }
 }
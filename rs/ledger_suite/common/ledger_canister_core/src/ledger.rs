@@ -136,6 +136,51 @@ pub trait LedgerAccess {
     fn with_ledger_mut<R>(f: impl FnOnce(&mut Self::Ledger) -> R) -> R;
 }
 
+/// Provides access to the block log of a ledger, i.e., the part of the ledger state that the
+/// archiving machinery needs.
+///
+/// Every [LedgerAccess] implements this trait. Ledgers that do not keep fungible balances (e.g.,
+/// NFT ledgers) can implement it directly to reuse the archiving machinery.
+pub trait BlockchainAccess {
+    type Runtime: Runtime;
+    type ArchiveWasm: ArchiveCanisterWasm;
+    type BlockDataContainer: BlockDataContainer + Default;
+
+    /// Executes a function on a reference to the block log.
+    fn with_blockchain<R>(
+        f: impl FnOnce(&Blockchain<Self::Runtime, Self::ArchiveWasm, Self::BlockDataContainer>) -> R,
+    ) -> R;
+
+    /// Executes a function on a mutable reference to the block log.
+    fn with_blockchain_mut<R>(
+        f: impl FnOnce(&mut Blockchain<Self::Runtime, Self::ArchiveWasm, Self::BlockDataContainer>) -> R,
+    ) -> R;
+
+    fn increment_archiving_failure_metric();
+}
+
+impl<LA: LedgerAccess> BlockchainAccess for LA {
+    type Runtime = <LA::Ledger as LedgerData>::Runtime;
+    type ArchiveWasm = <LA::Ledger as LedgerData>::ArchiveWasm;
+    type BlockDataContainer = <LA::Ledger as LedgerData>::BlockDataContainer;
+
+    fn with_blockchain<R>(
+        f: impl FnOnce(&Blockchain<Self::Runtime, Self::ArchiveWasm, Self::BlockDataContainer>) -> R,
+    ) -> R {
+        LA::with_ledger(|ledger| f(ledger.blockchain()))
+    }
+
+    fn with_blockchain_mut<R>(
+        f: impl FnOnce(&mut Blockchain<Self::Runtime, Self::ArchiveWasm, Self::BlockDataContainer>) -> R,
+    ) -> R {
+        LA::with_ledger_mut(|ledger| f(ledger.blockchain_mut()))
+    }
+
+    fn increment_archiving_failure_metric() {
+        LA::with_ledger_mut(|ledger| ledger.increment_archiving_failure_metric())
+    }
+}
+
 pub trait LedgerData: LedgerContext {
     type ArchiveWasm: ArchiveCanisterWasm;
     type Runtime: Runtime;
@@ -407,10 +452,10 @@ pub fn purge_old_transactions<L: LedgerData>(ledger: &mut L, now: TimeStamp) ->
 ///
 /// NOTE: only one archiving task can run at each point in time.
 /// If archiving is already in process, this function returns immediately.
-pub async fn archive_blocks<LA: LedgerAccess>(sink: impl Sink + Clone, max_message_size: u64) {
+pub async fn archive_blocks<LA: BlockchainAccess>(sink: impl Sink + Clone, max_message_size: u64) {
     use crate::archive::{ArchivingGuardError, send_blocks_to_archive};
 
-    let archive_arc = LA::with_ledger(|ledger| ledger.blockchain().archive.clone());
+    let archive_arc = LA::with_blockchain(|blockchain| blockchain.archive.clone());
 
     // NOTE: this guard will prevent another logical thread to start the archiving process.
     let (archiving_guard, blocks_to_archive) = match blocks_to_archive::<LA>(&sink) {
@@ -438,7 +483,7 @@ pub async fn archive_blocks<LA: LedgerAccess>(sink: impl Sink + Clone, max_messa
     .await;
 
     if result.is_err() {
-        LA::with_ledger_mut(|ledger| ledger.increment_archiving_failure_metric());
+        LA::increment_archiving_failure_metric();
     }
 
     remove_archived_blocks::<LA>(archiving_guard, num_blocks, &sink, result)
@@ -448,16 +493,16 @@ pub async fn archive_blocks<LA: LedgerAccess>(sink: impl Sink + Clone, max_messa
 // being archived into multiple messages to the archive canister.
 pub const MAX_BLOCKS_TO_ARCHIVE: usize = 18_000;
 
-pub fn blocks_to_archive<LA: LedgerAccess>(
+pub fn blocks_to_archive<LA: BlockchainAccess>(
     sink: &impl Sink,
 ) -> Result<(LedgerArchivingGuard<LA>, VecDeque<EncodedBlock>), ArchivingGuardError> {
     // NOTE: this guard will prevent another logical thread to start the archiving process.
     let archiving_guard = LedgerArchivingGuard::new()?;
 
-    let blocks_to_archive = LA::with_ledger(|ledger| {
-        let archive_guard = ledger.blockchain().archive.read().unwrap();
+    let blocks_to_archive = LA::with_blockchain(|blockchain| {
+        let archive_guard = blockchain.archive.read().unwrap();
         let archive = archive_guard.as_ref().unwrap();
-        ledger.blockchain().get_blocks_for_archiving(
+        blockchain.get_blocks_for_archiving(
             archive.trigger_threshold,
             archive.num_blocks_to_archive.min(MAX_BLOCKS_TO_ARCHIVE),
         )
@@ -472,20 +517,16 @@ pub fn blocks_to_archive<LA: LedgerAccess>(
     Ok((archiving_guard, blocks_to_archive))
 }
 
-pub fn remove_archived_blocks<LA: LedgerAccess>(
+pub fn remove_archived_blocks<LA: BlockchainAccess>(
     _archiving_guard: LedgerArchivingGuard<LA>,
     expected_num_blocks: usize,
     sink: &impl Sink,
     result: Result<usize, (usize, FailedToArchiveBlocks)>,
 ) {
-    LA::with_ledger_mut(|ledger| match result {
-        Ok(num_sent_blocks) => ledger
-            .blockchain_mut()
-            .remove_archived_blocks(num_sent_blocks),
+    LA::with_blockchain_mut(|blockchain| match result {
+        Ok(num_sent_blocks) => blockchain.remove_archived_blocks(num_sent_blocks),
         Err((num_sent_blocks, FailedToArchiveBlocks(err))) => {
-            ledger
-                .blockchain_mut()
-                .remove_archived_blocks(num_sent_blocks);
+            blockchain.remove_archived_blocks(num_sent_blocks);
             log!(
                 sink,
                 "[ledger] archived only {} out of {} blocks; error: {}",
@@ -507,12 +548,26 @@ pub struct BlockLocations {
 
 /// Returns the locations of the specified block range.
 pub fn block_locations<L: LedgerData>(ledger: &L, start: u64, length: usize) -> BlockLocations {
+    blockchain_block_locations(ledger.blockchain(), start, length)
+}
+
+/// Returns the locations of the specified block range of a block log.
+pub fn blockchain_block_locations<Rt, Wasm, BDC>(
+    blockchain: &Blockchain<Rt, Wasm, BDC>,
+    start: u64,
+    length: usize,
+) -> BlockLocations
+where
+    Rt: Runtime,
+    Wasm: ArchiveCanisterWasm,
+    BDC: BlockDataContainer + Default,
+{
     let requested_range = range_utils::make_range(start, length);
-    let local_range = ledger.blockchain().local_block_range();
+    let local_range = blockchain.local_block_range();
     let local_blocks = range_utils::intersect(&requested_range, &local_range)
         .unwrap_or_else(|_| range_utils::make_range(local_range.start, 0));
 
-    let archive = ledger.blockchain().archive.read().unwrap();
+    let archive = blockchain.archive.read().unwrap();
 
     // Collect the ranges of blocks stored in the archive canisters. The archives are sorted, so
     // that the oldest archive (with the lowest block IDs) is first, and the newest archive (with
@@ -7,6 +7,12 @@ use icrc_ledger_types::icrc3::transactions::Transaction;
 /// The maximum number of blocks to return in a single [get_blocks] request.
 pub const DEFAULT_MAX_BLOCKS_PER_RESPONSE: u64 = 2000;
 
+/// The maximum length of the memo prefix that [SearchAccountTransactionsArgs] can filter by.
+pub const MAX_MEMO_PREFIX_LENGTH: usize = 32;
+
+/// The maximum number of days returned by a single [get_account_daily_flows] request.
+pub const MAX_DAILY_FLOWS_PER_RESPONSE: u64 = 366;
+
 #[derive(Clone, Debug, CandidType, Deserialize)]
 pub enum IndexArg {
     Init(InitArg),
@@ -68,6 +74,75 @@ pub struct GetAccountTransactionsError {
 pub type GetAccountTransactionsResult =
     Result<GetAccountTransactionsResponse, GetAccountTransactionsError>;
 
+#[derive(Copy, Clone, Eq, PartialEq, Debug, CandidType, Deserialize, serde::Serialize)]
+pub enum OperationKind {
+    Mint,
+    Burn,
+    Transfer,
+    Approve,
+}
+
+#[derive(Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct SearchAccountTransactionsArgs {
+    pub account: Account,
+    // The txid of the last transaction seen by the client, see
+    // [GetAccountTransactionsArgs::start].
+    pub start: Option<BlockIndex>,
+    // Maximum number of transactions to fetch.
+    pub max_results: Nat,
+    // If set then only the transactions with a block timestamp greater
+    // than or equal to from_timestamp are returned.
+    pub from_timestamp: Option<u64>,
+    // If set then only the transactions with a block timestamp lower
+    // than to_timestamp are returned.
+    pub to_timestamp: Option<u64>,
+    // If set then only the transactions of this kind are returned.
+    pub kind: Option<OperationKind>,
+    // If set then only the transfers from or to this account and the
+    // approvals for this spender are returned.
+    pub counterparty: Option<Account>,
+    // If set then only the transactions whose memo starts with these
+    // bytes are returned. At most [MAX_MEMO_PREFIX_LENGTH] bytes long.
+    pub memo_prefix: Option<Vec<u8>>,
+}
+
+#[derive(Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct SearchAccountTransactionsResponse {
+    pub transactions: Vec<TransactionWithId>,
+    // The txid to use as start to continue the search or None
+    // if there are no more transactions matching the filters.
+    // The search may stop before max_results transactions are
+    // found in order to bound the cost of the query.
+    pub next_start: Option<BlockIndex>,
+}
+
+pub type SearchAccountTransactionsResult =
+    Result<SearchAccountTransactionsResponse, GetAccountTransactionsError>;
+
+#[derive(Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct GetAccountDailyFlowsArgs {
+    pub account: Account,
+    // The first day to return as number of days since the UNIX epoch
+    // in UTC. If None then the results start from the first day in
+    // which the account had a transaction.
+    pub start_day: Option<u64>,
+    // Maximum number of days to fetch.
+    pub max_results: Nat,
+}
+
+/// The tokens that flowed in and out of an account in a day.
+/// Fees credited to a fee collector are not included.
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct DailyFlow {
+    // The number of days since the UNIX epoch in UTC.
+    pub day: u64,
+    pub inflow: Nat,
+    // Includes the fees paid by the account.
+    pub outflow: Nat,
+}
+
+pub type GetAccountDailyFlowsResult = Result<Vec<DailyFlow>, GetAccountTransactionsError>;
+
 #[derive(Eq, PartialEq, Debug, CandidType, Deserialize)]
 pub struct ListSubaccountsArgs {
     pub owner: Principal,
@@ -11,9 +11,13 @@ use ic_icrc1::blocks::{encoded_block_to_generic_block, generic_block_to_encoded_
 use ic_icrc1::endpoints::StandardRecord;
 use ic_icrc1::{Block, Operation};
 use ic_icrc1_index_ng::{
-    DEFAULT_MAX_BLOCKS_PER_RESPONSE, FeeCollectorRanges, GetAccountTransactionsArgs,
+    DEFAULT_MAX_BLOCKS_PER_RESPONSE, DailyFlow, FeeCollectorRanges, GetAccountDailyFlowsArgs,
+    GetAccountDailyFlowsResult, GetAccountTransactionsArgs, GetAccountTransactionsError,
     GetAccountTransactionsResponse, GetAccountTransactionsResult, GetBlocksMethod, IndexArg,
-    InitArg, ListSubaccountsArgs, Log, LogEntry, Status, TransactionWithId, UpgradeArg,
+    InitArg, ListSubaccountsArgs, Log, LogEntry, MAX_DAILY_FLOWS_PER_RESPONSE,
+    MAX_MEMO_PREFIX_LENGTH, OperationKind, SearchAccountTransactionsArgs,
+    SearchAccountTransactionsResponse, SearchAccountTransactionsResult, Status, TransactionWithId,
+    UpgradeArg,
 };
 use ic_ledger_canister_core::runtime::heap_memory_size_bytes;
 use ic_ledger_core::block::{BlockIndex as BlockIndex64, BlockType, EncodedBlock};
@@ -32,7 +36,7 @@ use icrc_ledger_types::icrc3::blocks::{
     GetBlocksResult,
 };
 use icrc_ledger_types::icrc3::transactions::Transaction;
-use num_traits::ToPrimitive;
+use num_traits::{Bounded, ToPrimitive};
 use scopeguard::guard;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
@@ -56,9 +60,21 @@ const BLOCK_LOG_INDEX_MEMORY_ID: MemoryId = MemoryId::new(1);
 const BLOCK_LOG_DATA_MEMORY_ID: MemoryId = MemoryId::new(2);
 const ACCOUNT_BLOCK_IDS_MEMORY_ID: MemoryId = MemoryId::new(3);
 const ACCOUNT_DATA_MEMORY_ID: MemoryId = MemoryId::new(4);
+const ACCOUNT_BLOCK_DETAILS_MEMORY_ID: MemoryId = MemoryId::new(5);
+const ACCOUNT_DAILY_FLOWS_MEMORY_ID: MemoryId = MemoryId::new(6);
 
 const DEFAULT_RETRIEVE_BLOCKS_FROM_LEDGER_INTERVAL: Duration = Duration::from_secs(1);
 
+/// The maximum number of blocks added to the details indices by a single
+/// [backfill_block_details] call.
+const MAX_BLOCKS_TO_BACKFILL_PER_ROUND: u64 = 5_000;
+
+/// The maximum number of transactions of an account that a single
+/// [search_account_transactions] call inspects.
+const MAX_TRANSACTIONS_TO_SEARCH_PER_QUERY: usize = 10_000;
+
+const NANOS_PER_DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+
 #[cfg(not(feature = "u256-tokens"))]
 type Tokens = ic_icrc1_tokens_u64::U64;
 
@@ -78,6 +94,13 @@ type AccountBlockIdsMap = StableBTreeMap<AccountBlockIdsMapKey, (), VM>;
 type AccountDataMapKey = (AccountDataType, (Blob<29>, [u8; 32]));
 type AccountDataMap = StableBTreeMap<AccountDataMapKey, Tokens, VM>;
 
+// Same keys as [AccountBlockIdsMap].
+type AccountBlockDetailsMap = StableBTreeMap<AccountBlockIdsMapKey, AccountBlockDetails, VM>;
+
+// The second element of this tuple is the number of days since the UNIX epoch.
+type AccountDailyFlowsMapKey = ([u8; Sha256::DIGEST_LEN], u64);
+type AccountDailyFlowsMap = StableBTreeMap<AccountDailyFlowsMapKey, DailyFlows, VM>;
+
 thread_local! {
     /// Static memory manager to manage the memory available for stable structures.
     static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = RefCell::new(MemoryManager::init(DefaultMemoryImpl::default()));
@@ -105,6 +128,17 @@ thread_local! {
         RefCell::new(AccountDataMap::init(memory_manager.get(ACCOUNT_DATA_MEMORY_ID)))
     });
 
+    /// Map that contains the details of the blocks of an account
+    /// used to search the transactions of the account.
+    static ACCOUNT_BLOCK_DETAILS: RefCell<AccountBlockDetailsMap> = with_memory_manager(|memory_manager| {
+        RefCell::new(AccountBlockDetailsMap::init(memory_manager.get(ACCOUNT_BLOCK_DETAILS_MEMORY_ID)))
+    });
+
+    /// Map that contains the tokens that flowed in and out of an account per day.
+    static ACCOUNT_DAILY_FLOWS: RefCell<AccountDailyFlowsMap> = with_memory_manager(|memory_manager| {
+        RefCell::new(AccountDailyFlowsMap::init(memory_manager.get(ACCOUNT_DAILY_FLOWS_MEMORY_ID)))
+    });
+
     /// Profiling data to understand cycles usage
     static PROFILING_DATA: RefCell<SpanStats> = RefCell::new(SpanStats::default());
 
@@ -146,6 +180,13 @@ struct State {
     /// - `Some(None)` - 107 fee collector is enabled but fees are burned.
     /// - `Some(Some(account1))` - 107 fee collector is enabled, `account1` collects the fees.
     fee_collector_107: Option<Option<Account>>,
+
+    /// The number of blocks, starting from the first one, that have been added to
+    /// [ACCOUNT_BLOCK_DETAILS] and [ACCOUNT_DAILY_FLOWS]. This is lower than the
+    /// number of blocks only while an index upgraded from a version without these
+    /// maps backfills them, see [backfill_block_details].
+    #[serde(default)]
+    num_blocks_with_details: u64,
 }
 
 impl State {
@@ -168,6 +209,7 @@ impl Default for State {
             last_fee: None,
             retrieve_blocks_from_ledger_interval: None,
             fee_collector_107: None,
+            num_blocks_with_details: 0,
         }
     }
 }
@@ -219,6 +261,85 @@ impl Storable for AccountDataType {
     };
 }
 
+/// The details of a block of an account that [search_account_transactions]
+/// filters by, so that the query doesn't need to decode the blocks it skips.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+struct AccountBlockDetails {
+    #[serde(rename = "ts")]
+    timestamp: u64,
+    kind: OperationKind,
+    /// The hash of the other account of a transfer or of the spender of an approval.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    counterparty: Option<[u8; Sha256::DIGEST_LEN]>,
+    /// The first [MAX_MEMO_PREFIX_LENGTH] bytes of the memo.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "serde_bytes")]
+    memo_prefix: Option<Vec<u8>>,
+}
+
+impl AccountBlockDetails {
+    fn matches(&self, arg: &SearchAccountTransactionsArgs) -> bool {
+        if arg.kind.is_some_and(|kind| kind != self.kind) {
+            return false;
+        }
+        if let Some(counterparty) = arg.counterparty
+            && self.counterparty != Some(account_sha256(counterparty))
+        {
+            return false;
+        }
+        if let Some(memo_prefix) = &arg.memo_prefix {
+            let memo = self.memo_prefix.as_deref().unwrap_or_default();
+            if !memo.starts_with(memo_prefix) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl Storable for AccountBlockDetails {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let mut buf = vec![];
+        ciborium::ser::into_writer(self, &mut buf).expect("failed to encode block details");
+        Cow::Owned(buf)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        ciborium::de::from_reader(&bytes[..]).expect("failed to decode block details")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// The tokens that flowed in and out of an account in a day.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+struct DailyFlows {
+    inflow: Tokens,
+    outflow: Tokens,
+}
+
+impl Default for DailyFlows {
+    fn default() -> Self {
+        Self {
+            inflow: Tokens::zero(),
+            outflow: Tokens::zero(),
+        }
+    }
+}
+
+impl Storable for DailyFlows {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let mut buf = vec![];
+        ciborium::ser::into_writer(self, &mut buf).expect("failed to encode daily flows");
+        Cow::Owned(buf)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        ciborium::de::from_reader(&bytes[..]).expect("failed to decode daily flows")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
 // Ephemeral data that doesn't need to be saved between upgrades
 #[derive(Clone, Debug, Default)]
 struct Cache {
@@ -238,6 +359,35 @@ fn test_account_data_type_storable() {
     );
 }
 
+#[test]
+fn test_account_block_details_storable() {
+    for details in [
+        AccountBlockDetails {
+            timestamp: 1,
+            kind: OperationKind::Mint,
+            counterparty: None,
+            memo_prefix: None,
+        },
+        AccountBlockDetails {
+            timestamp: u64::MAX,
+            kind: OperationKind::Transfer,
+            counterparty: Some([1; Sha256::DIGEST_LEN]),
+            memo_prefix: Some(vec![0xca, 0xfe]),
+        },
+    ] {
+        assert_eq!(details, AccountBlockDetails::from_bytes(details.to_bytes()));
+    }
+}
+
+#[test]
+fn test_daily_flows_storable() {
+    let flows = DailyFlows {
+        inflow: Tokens::max_value(),
+        outflow: Tokens::zero(),
+    };
+    assert_eq!(flows, DailyFlows::from_bytes(flows.to_bytes()));
+}
+
 /// A helper function to access the scalar state.
 fn with_state<R>(f: impl FnOnce(&State) -> R) -> R {
     STATE.with(|cell| f(cell.borrow().get()))
@@ -275,6 +425,16 @@ fn with_account_data<R>(f: impl FnOnce(&mut AccountDataMap) -> R) -> R {
     ACCOUNT_DATA.with(|cell| f(&mut cell.borrow_mut()))
 }
 
+/// A helper function to access the account block details.
+fn with_account_block_details<R>(f: impl FnOnce(&mut AccountBlockDetailsMap) -> R) -> R {
+    ACCOUNT_BLOCK_DETAILS.with(|cell| f(&mut cell.borrow_mut()))
+}
+
+/// A helper function to access the account daily flows.
+fn with_account_daily_flows<R>(f: impl FnOnce(&mut AccountDailyFlowsMap) -> R) -> R {
+    ACCOUNT_DAILY_FLOWS.with(|cell| f(&mut cell.borrow_mut()))
+}
+
 /// A helper function that returns a decoded block stored in the
 /// block log at the given index or None if there is no block at that index.
 /// This function can trap if the index at the given block cannot be decoded
@@ -595,6 +755,7 @@ pub async fn build_index() -> Option<()> {
             state.is_build_index_running = false;
         });
     });
+    backfill_block_details();
     let num_indexed = match find_get_blocks_method().await {
         GetBlocksMethod::GetBlocks => fetch_blocks_via_get_blocks().await,
         GetBlocksMethod::ICRC3GetBlocks => fetch_blocks_via_icrc3().await,
@@ -797,6 +958,12 @@ fn append_block(block_index: BlockIndex64, block: GenericBlock) -> Result<(), Sy
         // change the balance of the involved accounts
         process_balance_changes(block_index, &decoded_block);
 
+        // add the block to the details indices unless they are
+        // still being backfilled
+        if with_state(|state| state.num_blocks_with_details) == block_index {
+            index_block_details(block_index, &decoded_block);
+        }
+
         Ok(())
     })
 }
@@ -1003,6 +1170,139 @@ fn credit(block_index: BlockIndex64, account: Account, amount: Tokens) {
     });
 }
 
+/// Adds the details of the blocks appended before [ACCOUNT_BLOCK_DETAILS] and
+/// [ACCOUNT_DAILY_FLOWS] existed to these maps.
+fn backfill_block_details() {
+    let start = with_state(|state| state.num_blocks_with_details);
+    let end = with_blocks(|blocks| blocks.len()).min(start + MAX_BLOCKS_TO_BACKFILL_PER_ROUND);
+    if start >= end {
+        return;
+    }
+    measure_span(&PROFILING_DATA, "backfill_block_details", || {
+        for block_index in start..end {
+            let block = get_decoded_block(block_index)
+                .unwrap_or_else(|| trap(format!("Block {block_index} not found in the block log")));
+            index_block_details(block_index, &block);
+        }
+    });
+    log!(
+        P1,
+        "[backfill_block_details]: added the details of blocks {}..{}",
+        start,
+        end
+    );
+}
+
+/// Adds the block to [ACCOUNT_BLOCK_DETAILS] and [ACCOUNT_DAILY_FLOWS]. Blocks
+/// must be added in order.
+fn index_block_details(block_index: BlockIndex64, block: &Block<Tokens>) {
+    let operation = &block.transaction.operation;
+    let kind = match operation {
+        Operation::Mint { .. } => Some(OperationKind::Mint),
+        Operation::Burn { .. } => Some(OperationKind::Burn),
+        Operation::Transfer { .. } => Some(OperationKind::Transfer),
+        Operation::Approve { .. } => Some(OperationKind::Approve),
+        Operation::FeeCollector { .. } => None,
+    };
+    if let Some(kind) = kind {
+        let memo_prefix = block.transaction.memo.as_ref().map(|memo| {
+            let memo = memo.0.as_slice();
+            memo[..memo.len().min(MAX_MEMO_PREFIX_LENGTH)].to_vec()
+        });
+        with_account_block_details(|account_block_details| {
+            for account in get_accounts(block) {
+                let details = AccountBlockDetails {
+                    timestamp: block.timestamp,
+                    kind,
+                    counterparty: get_counterparty(operation, account).map(account_sha256),
+                    memo_prefix: memo_prefix.clone(),
+                };
+                account_block_details.insert(account_block_ids_key(account, block_index), details);
+            }
+        });
+    }
+
+    // Only the fees paid are counted because which account collected the fee
+    // of a block depends on the blocks before it.
+    let fee = |fee: Option<Tokens>| fee.or(block.effective_fee).unwrap_or_else(Tokens::zero);
+    let day = block.timestamp / NANOS_PER_DAY;
+    match operation {
+        Operation::Mint {
+            to,
+            amount,
+            fee: mint_fee,
+        } => {
+            let amount = amount
+                .checked_sub(&fee(*mint_fee))
+                .unwrap_or_else(Tokens::zero);
+            add_daily_flows(*to, day, amount, Tokens::zero());
+        }
+        Operation::Burn {
+            from,
+            amount,
+            fee: burn_fee,
+            ..
+        } => add_daily_flows(
+            *from,
+            day,
+            Tokens::zero(),
+            saturating_add(*amount, fee(*burn_fee)),
+        ),
+        Operation::Transfer {
+            from,
+            to,
+            amount,
+            fee: transfer_fee,
+            ..
+        } => {
+            add_daily_flows(
+                *from,
+                day,
+                Tokens::zero(),
+                saturating_add(*amount, fee(*transfer_fee)),
+            );
+            add_daily_flows(*to, day, *amount, Tokens::zero());
+        }
+        Operation::Approve {
+            from,
+            fee: approve_fee,
+            ..
+        } => add_daily_flows(*from, day, Tokens::zero(), fee(*approve_fee)),
+        Operation::FeeCollector { .. } => {}
+    }
+
+    mutate_state(|state| state.num_blocks_with_details = block_index + 1);
+}
+
+fn add_daily_flows(account: Account, day: u64, inflow: Tokens, outflow: Tokens) {
+    let key = (account_sha256(account), day);
+    with_account_daily_flows(|account_daily_flows| {
+        let flows = account_daily_flows.get(&key).unwrap_or_default();
+        account_daily_flows.insert(
+            key,
+            DailyFlows {
+                inflow: saturating_add(flows.inflow, inflow),
+                outflow: saturating_add(flows.outflow, outflow),
+            },
+        );
+    });
+}
+
+// The flows of an account can exceed the total supply, so they saturate
+// rather than trap.
+fn saturating_add(a: Tokens, b: Tokens) -> Tokens {
+    a.checked_add(&b).unwrap_or_else(Tokens::max_value)
+}
+
+/// Returns the other account of a transfer or the spender of an approval.
+fn get_counterparty(operation: &Operation<Tokens>, account: Account) -> Option<Account> {
+    match operation {
+        Operation::Transfer { from, to, .. } => Some(if account == *from { *to } else { *from }),
+        Operation::Approve { spender, .. } => Some(*spender),
+        Operation::Mint { .. } | Operation::Burn { .. } | Operation::FeeCollector { .. } => None,
+    }
+}
+
 fn decode_encoded_block_or_trap(block_index: BlockIndex64, block: EncodedBlock) -> Block<Tokens> {
     Block::<Tokens>::decode(block).unwrap_or_else(|e| {
         trap(format!(
@@ -1133,19 +1433,7 @@ fn get_account_transactions(arg: GetAccountTransactionsArgs) -> GetAccountTransa
             .collect::<Vec<BlockIndex64>>()
     });
     for id in indices {
-        let block = with_blocks(|blocks| {
-            blocks.get(id).unwrap_or_else(|| {
-                trap(format!(
-                    "Block {id} not found in the block log, account blocks map is corrupted!"
-                ))
-            })
-        });
-        let transaction = encoded_block_bytes_to_flat_transaction(id, block);
-        let transaction_with_idx = TransactionWithId {
-            id: id.into(),
-            transaction,
-        };
-        transactions.push(transaction_with_idx);
+        transactions.push(get_transaction_with_id(id));
     }
     let oldest_tx_id = get_oldest_tx_id(arg.account).map(|tx_id| tx_id.into());
     let balance = get_balance(arg.account).into();
@@ -1156,6 +1444,155 @@ fn get_account_transactions(arg: GetAccountTransactionsArgs) -> GetAccountTransa
     })
 }
 
+fn get_transaction_with_id(id: BlockIndex64) -> TransactionWithId {
+    let block = with_blocks(|blocks| {
+        blocks.get(id).unwrap_or_else(|| {
+            trap(format!(
+                "Block {id} not found in the block log, account blocks map is corrupted!"
+            ))
+        })
+    });
+    let transaction = encoded_block_bytes_to_flat_transaction(id, block);
+    TransactionWithId {
+        id: id.into(),
+        transaction,
+    }
+}
+
+/// Returns an error if the details indices don't contain all the blocks yet.
+fn check_block_details_are_indexed() -> Result<(), GetAccountTransactionsError> {
+    let num_blocks = with_blocks(|blocks| blocks.len());
+    let num_blocks_with_details = with_state(|state| state.num_blocks_with_details);
+    if num_blocks_with_details < num_blocks {
+        return Err(GetAccountTransactionsError {
+            message: format!(
+                "The index is adding the details of the blocks indexed before the upgrade ({num_blocks_with_details} of {num_blocks} blocks), try again later"
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Returns the index of the first block with a timestamp greater than or
+/// equal to `timestamp`, or the number of blocks if there is no such block.
+/// The timestamps of the blocks of a ledger never decrease.
+fn first_block_at_or_after(timestamp: u64) -> BlockIndex64 {
+    let mut low = 0;
+    let mut high = with_blocks(|blocks| blocks.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let block = get_decoded_block(mid)
+            .unwrap_or_else(|| trap(format!("Block {mid} not found in the block log")));
+        if block.timestamp < timestamp {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+#[query]
+fn search_account_transactions(
+    arg: SearchAccountTransactionsArgs,
+) -> SearchAccountTransactionsResult {
+    check_block_details_are_indexed()?;
+    if let Some(memo_prefix) = &arg.memo_prefix
+        && memo_prefix.len() > MAX_MEMO_PREFIX_LENGTH
+    {
+        return Err(GetAccountTransactionsError {
+            message: format!(
+                "The memo prefix is {} bytes long but the maximum is {MAX_MEMO_PREFIX_LENGTH}",
+                memo_prefix.len()
+            ),
+        });
+    }
+    let length = arg
+        .max_results
+        .0
+        .to_u64()
+        .unwrap_or(u64::MAX)
+        .min(with_state(|opts| opts.max_blocks_per_response))
+        .min(usize::MAX as u64) as usize;
+    let mut start = match &arg.start {
+        None => u64::MAX,
+        Some(start) => start
+            .0
+            .to_u64()
+            .ok_or_else(|| GetAccountTransactionsError {
+                message: format!("The start {start} is not a valid block index"),
+            })?,
+    };
+    // The blocks at or after the end of the time range are skipped without
+    // looking at the transactions of the account.
+    if let Some(to_timestamp) = arg.to_timestamp {
+        start = start.min(first_block_at_or_after(to_timestamp));
+    }
+
+    let key = account_block_ids_key(arg.account, start);
+    let mut indices = vec![];
+    let mut num_searched = 0;
+    let mut last_searched = None;
+    let mut searched_all = true;
+    with_account_block_details(|account_block_details| {
+        for (k, details) in account_block_details
+            .range(key..)
+            .take_while(|(k, _)| k.0 == key.0)
+            .filter(|(k, _)| k.1.0 < start)
+        {
+            // The older transactions are all before the time range.
+            if arg
+                .from_timestamp
+                .is_some_and(|from_timestamp| details.timestamp < from_timestamp)
+            {
+                break;
+            }
+            if indices.len() == length || num_searched == MAX_TRANSACTIONS_TO_SEARCH_PER_QUERY {
+                searched_all = false;
+                break;
+            }
+            num_searched += 1;
+            last_searched = Some(k.1.0);
+            if details.matches(&arg) {
+                indices.push(k.1.0);
+            }
+        }
+    });
+    let transactions = indices.into_iter().map(get_transaction_with_id).collect();
+    Ok(SearchAccountTransactionsResponse {
+        transactions,
+        next_start: if searched_all {
+            None
+        } else {
+            last_searched.map(|id| id.into())
+        },
+    })
+}
+
+#[query]
+fn get_account_daily_flows(arg: GetAccountDailyFlowsArgs) -> GetAccountDailyFlowsResult {
+    check_block_details_are_indexed()?;
+    let length = arg
+        .max_results
+        .0
+        .to_u64()
+        .unwrap_or(u64::MAX)
+        .min(MAX_DAILY_FLOWS_PER_RESPONSE) as usize;
+    let account = account_sha256(arg.account);
+    let start_day = arg.start_day.unwrap_or(0);
+    Ok(with_account_daily_flows(|account_daily_flows| {
+        account_daily_flows
+            .range((account, start_day)..=(account, u64::MAX))
+            .take(length)
+            .map(|((_, day), flows)| DailyFlow {
+                day,
+                inflow: flows.inflow.into(),
+                outflow: flows.outflow.into(),
+            })
+            .collect()
+    }))
+}
+
 fn encoded_block_bytes_to_flat_transaction(
     block_index: BlockIndex64,
     block: Vec<u8>,
@@ -1294,6 +1731,11 @@ pub fn encode_metrics(w: &mut ic_metrics_encoder::MetricsEncoder<Vec<u8>>) -> st
         with_blocks(|blocks| blocks.len()) as f64,
         "Total number of blocks stored in the stable memory.",
     )?;
+    w.encode_gauge(
+        "index_number_of_blocks_with_details",
+        with_state(|state| state.num_blocks_with_details) as f64,
+        "Total number of blocks added to the indices used to search transactions and compute daily flows.",
+    )?;
     w.encode_gauge(
         "index_last_wait_time",
         with_state(|state| state.last_wait_time)
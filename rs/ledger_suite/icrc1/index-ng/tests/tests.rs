@@ -7,9 +7,11 @@ use candid::{Decode, Encode, Nat, Principal};
 use ic_agent::identity::Identity;
 use ic_base_types::{CanisterId, PrincipalId};
 use ic_icrc1_index_ng::{
-    DEFAULT_MAX_BLOCKS_PER_RESPONSE, FeeCollectorRanges, GetAccountTransactionsArgs,
-    GetAccountTransactionsResponse, GetAccountTransactionsResult, GetBlocksResponse, IndexArg,
-    InitArg as IndexInitArg, ListSubaccountsArgs, TransactionWithId,
+    DEFAULT_MAX_BLOCKS_PER_RESPONSE, DailyFlow, FeeCollectorRanges, GetAccountDailyFlowsArgs,
+    GetAccountDailyFlowsResult, GetAccountTransactionsArgs, GetAccountTransactionsResponse,
+    GetAccountTransactionsResult, GetBlocksResponse, IndexArg, InitArg as IndexInitArg,
+    ListSubaccountsArgs, OperationKind, SearchAccountTransactionsArgs,
+    SearchAccountTransactionsResponse, SearchAccountTransactionsResult, TransactionWithId,
 };
 use ic_icrc1_ledger::{
     ChangeFeeCollector, LedgerArgument, Tokens, UpgradeArgs as LedgerUpgradeArgs,
@@ -25,7 +27,7 @@ use ic_ledger_suite_state_machine_tests::test_http_request_decoding_quota;
 use ic_state_machine_tests::StateMachine;
 use icrc_ledger_types::icrc::generic_value::ICRC3Value;
 use icrc_ledger_types::icrc1::account::{Account, Subaccount};
-use icrc_ledger_types::icrc1::transfer::{BlockIndex, TransferArg, TransferError};
+use icrc_ledger_types::icrc1::transfer::{BlockIndex, Memo, TransferArg, TransferError};
 use icrc_ledger_types::icrc2::approve::{ApproveArgs, ApproveError};
 use icrc_ledger_types::icrc2::transfer_from::{TransferFromArgs, TransferFromError};
 use icrc_ledger_types::icrc3::blocks::GetBlocksRequest;
@@ -1553,6 +1555,314 @@ fn test_index_http_request_decoding_quota() {
     test_http_request_decoding_quota(env, index_id);
 }
 
+fn search_account_transactions(
+    env: &StateMachine,
+    index_id: CanisterId,
+    arg: SearchAccountTransactionsArgs,
+) -> SearchAccountTransactionsResponse {
+    let req = Encode!(&arg).expect("Failed to encode SearchAccountTransactionsArgs");
+    let res = env
+        .query(index_id, "search_account_transactions", req)
+        .expect("Failed to search_account_transactions")
+        .bytes();
+    Decode!(&res, SearchAccountTransactionsResult)
+        .expect("Failed to decode SearchAccountTransactionsResult")
+        .expect("Failed to perform search_account_transactions")
+}
+
+fn search_args(account: Account) -> SearchAccountTransactionsArgs {
+    SearchAccountTransactionsArgs {
+        account,
+        start: None,
+        max_results: 100u64.into(),
+        from_timestamp: None,
+        to_timestamp: None,
+        kind: None,
+        counterparty: None,
+        memo_prefix: None,
+    }
+}
+
+fn searched_ids(response: &SearchAccountTransactionsResponse) -> Vec<u64> {
+    response
+        .transactions
+        .iter()
+        .map(|tx| tx.id.0.to_u64().unwrap())
+        .collect()
+}
+
+fn get_account_daily_flows(
+    env: &StateMachine,
+    index_id: CanisterId,
+    account: Account,
+    start_day: Option<u64>,
+) -> Vec<DailyFlow> {
+    let arg = GetAccountDailyFlowsArgs {
+        account,
+        start_day,
+        max_results: 100u64.into(),
+    };
+    let req = Encode!(&arg).expect("Failed to encode GetAccountDailyFlowsArgs");
+    let res = env
+        .query(index_id, "get_account_daily_flows", req)
+        .expect("Failed to get_account_daily_flows")
+        .bytes();
+    Decode!(&res, GetAccountDailyFlowsResult)
+        .expect("Failed to decode GetAccountDailyFlowsResult")
+        .expect("Failed to perform get_account_daily_flows")
+}
+
+fn transfer_with_memo(
+    env: &StateMachine,
+    ledger_id: CanisterId,
+    from: Account,
+    to: Account,
+    amount: u64,
+    memo: &[u8],
+) -> BlockIndex {
+    let req = TransferArg {
+        from_subaccount: from.subaccount,
+        to,
+        amount: amount.into(),
+        created_at_time: None,
+        fee: None,
+        memo: Some(Memo::from(memo.to_vec())),
+    };
+    icrc1_transfer(env, ledger_id, from.owner.into(), req)
+}
+
+const NANOS_PER_DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+// Sets up the following blocks, where blocks 2, 3 and 4 are one day after blocks 0 and 1:
+// 0. mint to account(1, 0)
+// 1. transfer from account(1, 0) to account(2, 0) with memo "order-1"
+// 2. transfer from account(1, 0) to account(3, 0) with memo "order-2"
+// 3. approve from account(1, 0) to account(2, 0)
+// 4. transfer from account(2, 0) to account(1, 0) with memo "refund"
+fn setup_account_history(env: &StateMachine) -> (CanisterId, CanisterId, u64) {
+    let minter = minter_identity().sender().unwrap();
+    let ledger_id = install_ledger(
+        env,
+        vec![(account(1, 0), 1_000_000_000)],
+        default_archive_options(),
+        None,
+        minter,
+    );
+    let index_id = install_index_ng(env, index_init_arg_without_interval(ledger_id));
+
+    transfer_with_memo(
+        env,
+        ledger_id,
+        account(1, 0),
+        account(2, 0),
+        1_000_000,
+        b"order-1",
+    );
+    env.advance_time(Duration::from_secs(24 * 60 * 60));
+    let second_day_start = env
+        .time()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64;
+    transfer_with_memo(
+        env,
+        ledger_id,
+        account(1, 0),
+        account(3, 0),
+        2_000_000,
+        b"order-2",
+    );
+    approve(env, ledger_id, account(1, 0), account(2, 0), 5_000_000);
+    transfer_with_memo(
+        env,
+        ledger_id,
+        account(2, 0),
+        account(1, 0),
+        500_000,
+        b"refund",
+    );
+    wait_until_sync_is_completed(env, index_id, ledger_id);
+    (ledger_id, index_id, second_day_start)
+}
+
+#[test]
+fn test_search_account_transactions() {
+    let env = &StateMachine::new();
+    let (_ledger_id, index_id, second_day_start) = setup_account_history(env);
+
+    let search = |arg| searched_ids(&search_account_transactions(env, index_id, arg));
+
+    assert_eq!(search(search_args(account(1, 0))), vec![4, 3, 2, 1, 0]);
+    assert_eq!(
+        search(SearchAccountTransactionsArgs {
+            kind: Some(OperationKind::Transfer),
+            ..search_args(account(1, 0))
+        }),
+        vec![4, 2, 1]
+    );
+    assert_eq!(
+        search(SearchAccountTransactionsArgs {
+            kind: Some(OperationKind::Mint),
+            ..search_args(account(1, 0))
+        }),
+        vec![0]
+    );
+    assert_eq!(
+        search(SearchAccountTransactionsArgs {
+            counterparty: Some(account(2, 0)),
+            ..search_args(account(1, 0))
+        }),
+        vec![4, 3, 1]
+    );
+    assert_eq!(
+        search(SearchAccountTransactionsArgs {
+            counterparty: Some(account(1, 0)),
+            ..search_args(account(2, 0))
+        }),
+        // The approval is only indexed for the approver.
+        vec![4, 1]
+    );
+    assert_eq!(
+        search(SearchAccountTransactionsArgs {
+            memo_prefix: Some(b"order".to_vec()),
+            ..search_args(account(1, 0))
+        }),
+        vec![2, 1]
+    );
+    assert_eq!(
+        search(SearchAccountTransactionsArgs {
+            from_timestamp: Some(second_day_start),
+            ..search_args(account(1, 0))
+        }),
+        vec![4, 3, 2]
+    );
+    assert_eq!(
+        search(SearchAccountTransactionsArgs {
+            to_timestamp: Some(second_day_start),
+            ..search_args(account(1, 0))
+        }),
+        vec![1, 0]
+    );
+    assert_eq!(
+        search(SearchAccountTransactionsArgs {
+            kind: Some(OperationKind::Transfer),
+            from_timestamp: Some(second_day_start),
+            counterparty: Some(account(3, 0)),
+            ..search_args(account(1, 0))
+        }),
+        vec![2]
+    );
+}
+
+#[test]
+fn test_search_account_transactions_pagination() {
+    let env = &StateMachine::new();
+    let (_ledger_id, index_id, _second_day_start) = setup_account_history(env);
+
+    let mut ids = vec![];
+    let mut start = None;
+    loop {
+        let response = search_account_transactions(
+            env,
+            index_id,
+            SearchAccountTransactionsArgs {
+                start: start.clone(),
+                max_results: 1u64.into(),
+                kind: Some(OperationKind::Transfer),
+                ..search_args(account(1, 0))
+            },
+        );
+        assert!(response.transactions.len() <= 1);
+        ids.extend(searched_ids(&response));
+        match response.next_start {
+            Some(next_start) => start = Some(next_start),
+            None => break,
+        }
+    }
+    assert_eq!(ids, vec![4, 2, 1]);
+}
+
+#[test]
+fn test_search_account_transactions_rejects_long_memo_prefix() {
+    let env = &StateMachine::new();
+    let (_ledger_id, index_id, _second_day_start) = setup_account_history(env);
+
+    let arg = SearchAccountTransactionsArgs {
+        memo_prefix: Some(vec![0; 33]),
+        ..search_args(account(1, 0))
+    };
+    let res = env
+        .query(
+            index_id,
+            "search_account_transactions",
+            Encode!(&arg).unwrap(),
+        )
+        .expect("Failed to search_account_transactions")
+        .bytes();
+    let err = Decode!(&res, SearchAccountTransactionsResult)
+        .unwrap()
+        .expect_err("a memo prefix longer than 32 bytes should be rejected");
+    assert!(err.message.contains("memo prefix"), "{}", err.message);
+}
+
+#[test]
+fn test_get_account_daily_flows() {
+    let env = &StateMachine::new();
+    let (_ledger_id, index_id, second_day_start) = setup_account_history(env);
+    let first_day = get_account_transactions(env, index_id, account(1, 0), None, 100)
+        .transactions
+        .last()
+        .unwrap()
+        .transaction
+        .timestamp
+        / NANOS_PER_DAY;
+    let second_day = second_day_start / NANOS_PER_DAY;
+    assert_eq!(second_day, first_day + 1);
+
+    assert_eq!(
+        get_account_daily_flows(env, index_id, account(1, 0), None),
+        vec![
+            DailyFlow {
+                day: first_day,
+                inflow: 1_000_000_000u64.into(),
+                outflow: (1_000_000 + FEE).into(),
+            },
+            DailyFlow {
+                day: second_day,
+                inflow: 500_000u64.into(),
+                outflow: (2_000_000 + FEE + FEE).into(),
+            },
+        ]
+    );
+    assert_eq!(
+        get_account_daily_flows(env, index_id, account(1, 0), Some(second_day)),
+        vec![DailyFlow {
+            day: second_day,
+            inflow: 500_000u64.into(),
+            outflow: (2_000_000 + FEE + FEE).into(),
+        }]
+    );
+    assert_eq!(
+        get_account_daily_flows(env, index_id, account(2, 0), None),
+        vec![
+            DailyFlow {
+                day: first_day,
+                inflow: 1_000_000u64.into(),
+                outflow: 0u64.into(),
+            },
+            DailyFlow {
+                day: second_day,
+                inflow: 0u64.into(),
+                outflow: (500_000 + FEE).into(),
+            },
+        ]
+    );
+    assert_eq!(
+        get_account_daily_flows(env, index_id, account(4, 0), None),
+        vec![]
+    );
+}
+
 mod metrics {
     use crate::index_wasm;
     use candid::Principal;
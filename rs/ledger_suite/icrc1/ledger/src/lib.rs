@@ -78,6 +78,12 @@ const METADATA_MAX_MEMO_LENGTH: &str = "icrc1:max_memo_length";
 const METADATA_PUBLIC_ALLOWANCES: &str = "icrc103:public_allowances";
 const METADATA_MAX_TAKE_ALLOWANCES: &str = "icrc103:max_take_value";
 const MAX_TAKE_ALLOWANCES: u64 = 500;
+const METADATA_MAX_UPDATE_BATCH_SIZE: &str = "icrc4:max_update_batch_size";
+const METADATA_MAX_QUERY_BATCH_SIZE: &str = "icrc4:max_query_batch_size";
+/// The maximum number of transfers in a single ICRC-4 `icrc4_transfer_batch` call.
+pub const MAX_UPDATE_BATCH_SIZE: u64 = 1_000;
+/// The maximum number of accounts in a single ICRC-4 `icrc4_balance_of_batch` call.
+pub const MAX_QUERY_BATCH_SIZE: u64 = 1_000;
 
 #[cfg(not(feature = "u256-tokens"))]
 pub type Tokens = ic_icrc1_tokens_u64::U64;
@@ -648,7 +654,7 @@ pub fn wasm_token_type() -> String {
 }
 
 fn map_metadata_or_trap(arg_metadata: Vec<(String, Value)>) -> Vec<(String, StoredValue)> {
-    const DISALLOWED_METADATA_FIELDS: [&str; 7] = [
+    const DISALLOWED_METADATA_FIELDS: [&str; 9] = [
         METADATA_DECIMALS,
         METADATA_NAME,
         METADATA_SYMBOL,
@@ -656,6 +662,8 @@ fn map_metadata_or_trap(arg_metadata: Vec<(String, Value)>) -> Vec<(String, Stor
         METADATA_MAX_MEMO_LENGTH,
         METADATA_PUBLIC_ALLOWANCES,
         METADATA_MAX_TAKE_ALLOWANCES,
+        METADATA_MAX_UPDATE_BATCH_SIZE,
+        METADATA_MAX_QUERY_BATCH_SIZE,
     ];
     arg_metadata
         .into_iter()
@@ -930,6 +938,14 @@ impl Ledger {
             METADATA_MAX_TAKE_ALLOWANCES,
             Nat::from(self.max_take_allowances()),
         ));
+        records.push(Value::entry(
+            METADATA_MAX_UPDATE_BATCH_SIZE,
+            Nat::from(MAX_UPDATE_BATCH_SIZE),
+        ));
+        records.push(Value::entry(
+            METADATA_MAX_QUERY_BATCH_SIZE,
+            Nat::from(MAX_QUERY_BATCH_SIZE),
+        ));
         // When adding new entries that cannot be set by the user
         // (e.g. because they are fixed or computed dynamically)
         // please also add them to `map_metadata_or_trap` to prevent
@@ -18,9 +18,10 @@ use ic_icrc1::{
 };
 use ic_icrc1_ledger::{InitArgs, Ledger, LedgerArgument, LedgerField, LedgerState};
 use ic_icrc1_ledger::{
-    LEDGER_VERSION, UPGRADES_MEMORY, balances_len, clear_stable_allowance_data,
-    clear_stable_balances_data, clear_stable_blocks_data, get_allowances, is_ready, ledger_state,
-    panic_if_not_ready, read_first_balance, set_ledger_state, wasm_token_type,
+    LEDGER_VERSION, MAX_QUERY_BATCH_SIZE, MAX_UPDATE_BATCH_SIZE, UPGRADES_MEMORY, balances_len,
+    clear_stable_allowance_data, clear_stable_balances_data, clear_stable_blocks_data,
+    get_allowances, is_ready, ledger_state, panic_if_not_ready, read_first_balance,
+    set_ledger_state, wasm_token_type,
 };
 use ic_ledger_canister_core::ledger::{
     LedgerAccess, LedgerContext, LedgerData, TransferError as CoreTransferError, apply_transaction,
@@ -37,6 +38,10 @@ use icrc_ledger_types::icrc3::blocks::DataCertificate;
 #[cfg(not(feature = "get-blocks-disabled"))]
 use icrc_ledger_types::icrc3::blocks::GetBlocksResponse;
 use icrc_ledger_types::icrc3::blocks::ICRC3DataCertificate;
+use icrc_ledger_types::icrc4::transfer_batch::{
+    BalanceQueryArgs, BalanceQueryResult, TransferBatchArgs, TransferBatchError,
+    TransferBatchResults,
+};
 use icrc_ledger_types::icrc21::{
     errors::Icrc21Error, lib::build_icrc21_consent_info_for_icrc1_and_icrc2_endpoints,
     requests::ConsentMessageRequest, responses::ConsentInfo,
@@ -822,6 +827,68 @@ async fn icrc1_transfer(arg: TransferArg) -> Result<Nat, TransferError> {
     })
 }
 
+/// Executes a batch of ICRC-1 transfers from accounts of the caller.
+///
+/// The transfers are executed in order within a single message, i.e., no other
+/// call is interleaved with the batch. Each transfer succeeds or fails on its
+/// own, and results in a regular ICRC-1 block. Batches that exceed
+/// `MAX_UPDATE_BATCH_SIZE` are rejected as a whole before any transfer is
+/// executed, and so are batches with a transfer that `icrc1_transfer` would
+/// trap on (e.g., because of a too long memo).
+#[update]
+async fn icrc4_transfer_batch(args: TransferBatchArgs) -> TransferBatchResults {
+    panic_if_not_ready();
+    if args.len() as u64 > MAX_UPDATE_BATCH_SIZE {
+        let limit = Nat::from(MAX_UPDATE_BATCH_SIZE);
+        return args
+            .iter()
+            .map(|_| {
+                Some(Err(TransferBatchError::TooManyRequests {
+                    limit: limit.clone(),
+                }))
+            })
+            .collect();
+    }
+    let caller = ic_cdk::api::caller();
+    let results: TransferBatchResults = args
+        .into_iter()
+        .map(|arg| {
+            let from_account = Account {
+                owner: caller,
+                subaccount: arg.from_subaccount,
+            };
+            let result = execute_transfer_not_async(
+                from_account,
+                arg.to,
+                None,
+                arg.fee,
+                arg.amount,
+                arg.memo,
+                arg.created_at_time,
+            )
+            .map(Nat::from)
+            .map_err(convert_transfer_error)
+            .map_err(|err| {
+                let err: TransferError = match err.try_into() {
+                    Ok(err) => err,
+                    Err(err) => ic_cdk::trap(&err),
+                };
+                TransferBatchError::from(err)
+            });
+            Some(result)
+        })
+        .collect();
+
+    if results.iter().any(|result| matches!(result, Some(Ok(_)))) {
+        // NB. we need to set the certified data before the first async call to make sure that the
+        // blockchain state agrees with the certificate while archiving is in progress.
+        ic_cdk::api::set_certified_data(&Access::with_ledger(Ledger::root_hash));
+
+        archive_blocks::<Access>(&LOG, MAX_MESSAGE_SIZE).await;
+    }
+    results
+}
+
 #[update]
 async fn icrc2_transfer_from(arg: TransferFromArgs) -> Result<Nat, TransferFromError> {
     panic_if_not_ready();
@@ -888,6 +955,10 @@ fn supported_standards() -> Vec<StandardRecord> {
             name: "ICRC-3".to_string(),
             url: "https://github.com/dfinity/ICRC-1/tree/main/standards/ICRC-3".to_string(),
         },
+        StandardRecord {
+            name: "ICRC-4".to_string(),
+            url: "https://github.com/dfinity/ICRC/tree/main/ICRCs/ICRC-4".to_string(),
+        },
         StandardRecord {
             name: "ICRC-10".to_string(),
             url: "https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-10/ICRC-10.md".to_string(),
@@ -1096,6 +1167,31 @@ fn icrc3_get_blocks(args: Vec<GetBlocksRequest>) -> GetBlocksResult {
     Access::with_ledger(|ledger| ledger.icrc3_get_blocks(args))
 }
 
+#[query]
+fn icrc4_balance_of_batch(accounts: BalanceQueryArgs) -> BalanceQueryResult {
+    if accounts.len() as u64 > MAX_QUERY_BATCH_SIZE {
+        ic_cdk::trap(format!(
+            "the batch contains more than the maximum of {MAX_QUERY_BATCH_SIZE} accounts"
+        ))
+    }
+    Access::with_ledger(|ledger| {
+        accounts
+            .iter()
+            .map(|account| ledger.balances().account_balance(account).into())
+            .collect()
+    })
+}
+
+#[query]
+fn icrc4_maximum_update_batch_size() -> Option<Nat> {
+    Some(Nat::from(MAX_UPDATE_BATCH_SIZE))
+}
+
+#[query]
+fn icrc4_maximum_query_batch_size() -> Option<Nat> {
+    Some(Nat::from(MAX_QUERY_BATCH_SIZE))
+}
+
 #[query]
 fn icrc10_supported_standards() -> Vec<StandardRecord> {
     supported_standards()
@@ -309,6 +309,11 @@ fn test_tx_deduplication() {
     ic_ledger_suite_state_machine_tests::test_tx_deduplication(ledger_wasm(), encode_init_args);
 }
 
+#[test]
+fn test_icrc4_transfer_batch() {
+    ic_ledger_suite_state_machine_tests::test_icrc4_transfer_batch(ledger_wasm(), encode_init_args);
+}
+
 #[test]
 fn test_mint_burn() {
     ic_ledger_suite_state_machine_tests::test_mint_burn(ledger_wasm(), encode_init_args);
@@ -1,7 +1,11 @@
 use crate::common::{index_ng_wasm, ledger_wasm, load_wasm_using_env_var};
-use candid::{Encode, Principal};
+use candid::{Decode, Encode, Nat, Principal};
 use ic_base_types::{CanisterId, PrincipalId};
-use ic_icrc1_index_ng::{IndexArg, InitArg as IndexInitArg, UpgradeArg as IndexUpgradeArg};
+use ic_icrc1_index_ng::{
+    DailyFlow, GetAccountDailyFlowsArgs, GetAccountDailyFlowsResult, IndexArg,
+    InitArg as IndexInitArg, SearchAccountTransactionsArgs, SearchAccountTransactionsResult,
+    Status, TransactionWithId, UpgradeArg as IndexUpgradeArg,
+};
 use ic_icrc1_ledger::{FeatureFlags, InitArgsBuilder, LedgerArgument};
 use ic_ledger_canister_core::archive::ArchiveOptions;
 use ic_ledger_suite_state_machine_tests_constants::{
@@ -20,6 +24,10 @@ const STARTING_CYCLES_PER_CANISTER: u128 = 2_000_000_000_000_000;
 const ARCHIVE_TRIGGER_THRESHOLD: u64 = 10;
 const NUM_BLOCKS_TO_ARCHIVE: usize = 5;
 const MAX_BLOCKS_FROM_ARCHIVE: u64 = 10;
+// More blocks than the index adds to its search and daily flow indices in a
+// single round after an upgrade, so that the backfill takes several rounds.
+const NUM_BLOCKS_TO_BACKFILL: u64 = 6_000;
+const MAX_ATTEMPTS_FOR_INDEX_SYNC_WAIT: u8 = 100;
 
 #[test]
 fn should_upgrade_and_downgrade_ledger_canister_suite() {
@@ -84,6 +92,162 @@ fn should_upgrade_and_downgrade_ledger_canister_suite() {
     ).expect("Downgrading ledger to the mainnet version should succeed, since there are no breaking changes");
 }
 
+#[test]
+fn should_backfill_block_details_after_upgrading_index_from_mainnet_version() {
+    let env = &StateMachineBuilder::new()
+        .with_subnet_type(SubnetType::Application)
+        .build();
+    env.set_time(SystemTime::now());
+
+    let accounts: Vec<_> = (0..NUM_BLOCKS_TO_BACKFILL)
+        .map(|i| Account::from(PrincipalId::new_user_test_id(i).0))
+        .collect();
+    let ledger_id = install_ledger(
+        env,
+        accounts
+            .iter()
+            .enumerate()
+            .map(|(i, account)| (*account, 1_000_000 + i as u64))
+            .collect(),
+        ArchiveOptions {
+            trigger_threshold: 2 * NUM_BLOCKS_TO_BACKFILL as usize,
+            ..default_archive_options()
+        },
+        None,
+        MINTER_PRINCIPAL,
+    );
+    let index_id = install_index_ng(
+        env,
+        IndexInitArg {
+            ledger_id: Principal::from(ledger_id),
+            retrieve_blocks_from_ledger_interval_seconds: None,
+        },
+    );
+    wait_until_index_num_blocks_synced(env, index_id, NUM_BLOCKS_TO_BACKFILL);
+
+    env.upgrade_canister(
+        index_id,
+        index_ng_wasm(),
+        Encode!(&IndexArg::Upgrade(IndexUpgradeArg {
+            ledger_id: None,
+            retrieve_blocks_from_ledger_interval_seconds: None,
+        }))
+        .unwrap(),
+    )
+    .unwrap();
+
+    // The blocks indexed by the mainnet version have no details until the
+    // backfill completes, so the queries that depend on them fail.
+    let first = accounts[0];
+    let last = accounts[accounts.len() - 1];
+    assert!(search_account_transactions(env, index_id, first).is_err());
+    assert!(get_account_daily_flows(env, index_id, last).is_err());
+
+    let mut rounds = 0;
+    while search_account_transactions(env, index_id, last).is_err() {
+        rounds += 1;
+        assert!(
+            rounds <= MAX_ATTEMPTS_FOR_INDEX_SYNC_WAIT,
+            "The index did not backfill the block details"
+        );
+        env.advance_time(Duration::from_secs(60));
+        env.tick();
+    }
+    assert!(
+        rounds > 1,
+        "The backfill should take more than one round, took {rounds}"
+    );
+    assert_eq!(
+        status(env, index_id).num_blocks_synced,
+        NUM_BLOCKS_TO_BACKFILL
+    );
+
+    for (i, account) in [(0, first), (accounts.len() - 1, last)] {
+        let transactions = search_account_transactions(env, index_id, account)
+            .unwrap_or_else(|err| panic!("Failed to search the transactions of {account}: {err}"));
+        assert_eq!(transactions.len(), 1);
+        let mint = transactions[0]
+            .transaction
+            .mint
+            .as_ref()
+            .expect("The initial balance should be a mint");
+        assert_eq!(mint.to, account);
+
+        let flows = get_account_daily_flows(env, index_id, account)
+            .unwrap_or_else(|err| panic!("Failed to get the daily flows of {account}: {err}"));
+        assert_eq!(flows.len(), 1);
+        assert_eq!(flows[0].inflow, Nat::from(1_000_000 + i as u64));
+        assert_eq!(flows[0].outflow, Nat::from(0_u64));
+    }
+}
+
+fn status(env: &StateMachine, index_id: CanisterId) -> Status {
+    let res = env
+        .query(index_id, "status", Encode!(&()).unwrap())
+        .expect("Failed to send status")
+        .bytes();
+    Decode!(&res, Status).expect("Failed to decode status response")
+}
+
+fn wait_until_index_num_blocks_synced(env: &StateMachine, index_id: CanisterId, num_blocks: u64) {
+    for _ in 0..MAX_ATTEMPTS_FOR_INDEX_SYNC_WAIT {
+        env.advance_time(Duration::from_secs(60));
+        env.tick();
+        if status(env, index_id).num_blocks_synced == Nat::from(num_blocks) {
+            return;
+        }
+    }
+    panic!("The index canister was unable to sync {num_blocks} blocks with the ledger");
+}
+
+fn search_account_transactions(
+    env: &StateMachine,
+    index_id: CanisterId,
+    account: Account,
+) -> Result<Vec<TransactionWithId>, String> {
+    let arg = SearchAccountTransactionsArgs {
+        account,
+        start: None,
+        max_results: 100_u64.into(),
+        from_timestamp: None,
+        to_timestamp: None,
+        kind: None,
+        counterparty: None,
+        memo_prefix: None,
+    };
+    let res = env
+        .query(
+            index_id,
+            "search_account_transactions",
+            Encode!(&arg).unwrap(),
+        )
+        .expect("Failed to search_account_transactions")
+        .bytes();
+    Decode!(&res, SearchAccountTransactionsResult)
+        .expect("Failed to decode SearchAccountTransactionsResult")
+        .map(|response| response.transactions)
+        .map_err(|err| err.message)
+}
+
+fn get_account_daily_flows(
+    env: &StateMachine,
+    index_id: CanisterId,
+    account: Account,
+) -> Result<Vec<DailyFlow>, String> {
+    let arg = GetAccountDailyFlowsArgs {
+        account,
+        start_day: None,
+        max_results: 100_u64.into(),
+    };
+    let res = env
+        .query(index_id, "get_account_daily_flows", Encode!(&arg).unwrap())
+        .expect("Failed to get_account_daily_flows")
+        .bytes();
+    Decode!(&res, GetAccountDailyFlowsResult)
+        .expect("Failed to decode GetAccountDailyFlowsResult")
+        .map_err(|err| err.message)
+}
+
 fn default_archive_options() -> ArchiveOptions {
     ArchiveOptions {
         trigger_threshold: ARCHIVE_TRIGGER_THRESHOLD as usize,
@@ -0,0 +1,41 @@
+use candid::{CandidType, Deserialize, Nat, Principal};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::Serialize;
+
+/// The default number of tokens returned by the `get_tokens_of` endpoint if the request does not specify
+/// `take`.
+pub const DEFAULT_TAKE_VALUE: u64 = 100;
+/// The maximum number of tokens returned by a single `get_tokens_of` request.
+pub const MAX_TAKE_VALUE: u64 = 1_000;
+
+#[derive(CandidType, Debug, Deserialize, Clone, Serialize)]
+pub enum IndexArg {
+    Init(InitArg),
+    Upgrade(UpgradeArg),
+}
+
+#[derive(CandidType, Debug, Deserialize, Clone, Serialize)]
+pub struct InitArg {
+    pub ledger_id: Principal,
+    // The interval in seconds in which to retrieve blocks from the ledger. A lower value makes
+    // the index more responsive in showing new blocks, but increases costs.
+    pub retrieve_blocks_from_ledger_interval_seconds: Option<u64>,
+}
+
+#[derive(CandidType, Debug, Deserialize, Clone, Serialize)]
+pub struct UpgradeArg {
+    pub retrieve_blocks_from_ledger_interval_seconds: Option<u64>,
+}
+
+#[derive(CandidType, Debug, Deserialize, Clone, Serialize, PartialEq, Eq)]
+pub struct GetTokensOfArgs {
+    pub account: Account,
+    // The last token id seen by the caller. The index returns the tokens with greater ids.
+    pub prev: Option<Nat>,
+    pub take: Option<Nat>,
+}
+
+#[derive(CandidType, Debug, Deserialize, Clone, Serialize, PartialEq, Eq)]
+pub struct Status {
+    pub num_blocks_synced: Nat,
+}
@@ -0,0 +1,390 @@
+#![allow(deprecated)]
+use candid::{CandidType, Nat, Principal};
+use ic_canister_log::{declare_log_buffer, export as export_logs, log};
+use ic_cdk::{init, post_upgrade, pre_upgrade, query};
+use ic_cdk_timers::TimerId;
+use ic_http_types::{HttpRequest, HttpResponse, HttpResponseBuilder};
+use ic_icrc7::{Block, Operation, TokenId};
+use ic_icrc7_index::{
+    DEFAULT_TAKE_VALUE, GetTokensOfArgs, IndexArg, InitArg, MAX_TAKE_VALUE, Status,
+};
+use ic_stable_structures::DefaultMemoryImpl;
+use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
+use ic_stable_structures::reader::{BufferedReader, Reader};
+use ic_stable_structures::writer::{BufferedWriter, Writer};
+use icrc_ledger_types::icrc::generic_value::Value;
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc3::blocks::{
+    ArchivedBlocks, BlockWithId, GetBlocksRequest, GetBlocksResult,
+};
+use num_traits::ToPrimitive;
+use scopeguard::guard;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Debug;
+use std::ops::Bound;
+use std::time::Duration;
+
+const DEFAULT_RETRIEVE_BLOCKS_FROM_LEDGER_INTERVAL: Duration = Duration::from_secs(1);
+/// The number of blocks the index requests from the ledger in a single call.
+const MAX_BLOCKS_PER_REQUEST: u64 = 100;
+const UPGRADES_MEMORY_ID: MemoryId = MemoryId::new(0);
+// We use 8MiB buffer
+const BUFFER_SIZE: usize = 8388608;
+
+declare_log_buffer!(name = LOG, capacity = 1000);
+
+thread_local! {
+    static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = RefCell::new(
+        MemoryManager::init(DefaultMemoryImpl::default())
+    );
+
+    static UPGRADES_MEMORY: RefCell<VirtualMemory<DefaultMemoryImpl>> = MEMORY_MANAGER.with(|memory_manager|
+        RefCell::new(memory_manager.borrow().get(UPGRADES_MEMORY_ID)));
+
+    static STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+
+    static TIMER_ID: RefCell<Option<TimerId>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct State {
+    ledger_id: Principal,
+    retrieve_blocks_from_ledger_interval: Duration,
+    // The index of the next block to fetch from the ledger.
+    next_block_index: u64,
+    owners: BTreeMap<TokenId, Account>,
+    tokens_by_owner: BTreeSet<(Account, TokenId)>,
+    #[serde(skip)]
+    is_build_index_running: bool,
+}
+
+impl State {
+    fn apply(&mut self, block: Block) {
+        match block.transaction.operation {
+            Operation::Mint { token_id, to, .. } => self.set_owner(token_id, Some(to)),
+            Operation::Burn { token_id, .. } => self.set_owner(token_id, None),
+            Operation::Transfer { token_id, to, .. }
+            | Operation::TransferFrom { token_id, to, .. } => self.set_owner(token_id, Some(to)),
+            Operation::Approve { .. }
+            | Operation::ApproveCollection { .. }
+            | Operation::Revoke { .. }
+            | Operation::RevokeCollection { .. } => {}
+        }
+        self.next_block_index += 1;
+    }
+
+    fn set_owner(&mut self, token_id: TokenId, owner: Option<Account>) {
+        if let Some(previous_owner) = self.owners.remove(&token_id) {
+            self.tokens_by_owner.remove(&(previous_owner, token_id));
+        }
+        if let Some(owner) = owner {
+            self.owners.insert(token_id, owner);
+            self.tokens_by_owner.insert((owner, token_id));
+        }
+    }
+}
+
+fn with_state<R>(f: impl FnOnce(&State) -> R) -> R {
+    STATE.with(|cell| f(cell.borrow().as_ref().expect("index state not initialized")))
+}
+
+fn mutate_state<R>(f: impl FnOnce(&mut State) -> R) -> R {
+    STATE.with(|cell| {
+        f(cell
+            .borrow_mut()
+            .as_mut()
+            .expect("index state not initialized"))
+    })
+}
+
+#[init]
+fn init(index_arg: Option<IndexArg>) {
+    let InitArg {
+        ledger_id,
+        retrieve_blocks_from_ledger_interval_seconds,
+    } = match index_arg {
+        Some(IndexArg::Init(arg)) => arg,
+        _ => ic_cdk::trap("Index initialization must take in input an InitArg argument"),
+    };
+    STATE.with(|cell| {
+        *cell.borrow_mut() = Some(State {
+            ledger_id,
+            retrieve_blocks_from_ledger_interval: retrieve_blocks_from_ledger_interval_seconds
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_RETRIEVE_BLOCKS_FROM_LEDGER_INTERVAL),
+            next_block_index: 0,
+            owners: BTreeMap::new(),
+            tokens_by_owner: BTreeSet::new(),
+            is_build_index_running: false,
+        })
+    });
+    set_build_index_timer();
+}
+
+#[pre_upgrade]
+fn pre_upgrade() {
+    UPGRADES_MEMORY.with_borrow_mut(|memory| {
+        with_state(|state| {
+            let writer = Writer::new(memory, 0);
+            let mut buffered_writer = BufferedWriter::new(BUFFER_SIZE, writer);
+            ciborium::ser::into_writer(state, &mut buffered_writer)
+                .expect("Failed to write the index state in stable memory");
+        })
+    });
+}
+
+#[post_upgrade]
+fn post_upgrade(index_arg: Option<IndexArg>) {
+    let mut state: State = UPGRADES_MEMORY.with_borrow(|memory| {
+        let reader = Reader::new(memory, 0);
+        let mut buffered_reader = BufferedReader::new(BUFFER_SIZE, reader);
+        ciborium::de::from_reader(&mut buffered_reader)
+            .expect("Failed to read the index state from stable memory")
+    });
+    match index_arg {
+        Some(IndexArg::Init(_)) => ic_cdk::trap("Index upgrade argument cannot be of variant Init"),
+        Some(IndexArg::Upgrade(arg)) => {
+            if let Some(interval) = arg.retrieve_blocks_from_ledger_interval_seconds {
+                state.retrieve_blocks_from_ledger_interval = Duration::from_secs(interval);
+            }
+        }
+        None => {}
+    }
+    STATE.with(|cell| *cell.borrow_mut() = Some(state));
+    set_build_index_timer();
+}
+
+fn set_build_index_timer() {
+    let interval = with_state(|state| state.retrieve_blocks_from_ledger_interval);
+    let timer_id = ic_cdk_timers::set_timer_interval(interval, async || {
+        build_index().await;
+    });
+    TIMER_ID.with(|tid| *tid.borrow_mut() = Some(timer_id));
+}
+
+async fn call<I, O>(id: Principal, method: &str, arg: I) -> Result<O, String>
+where
+    I: CandidType + Debug,
+    O: CandidType + for<'a> Deserialize<'a>,
+{
+    let (res,): (O,) = ic_cdk::api::call::call(id, method, (&arg,)).await.map_err(
+        |(code, message)| {
+            format!(
+                "failed to call {method} on {id} with {arg:?}: code: {code:#?} message: {message}"
+            )
+        },
+    )?;
+    Ok(res)
+}
+
+async fn build_index() {
+    if with_state(|state| state.is_build_index_running) {
+        return;
+    }
+    mutate_state(|state| state.is_build_index_running = true);
+    let _reset_is_build_index_running_flag_guard = guard((), |_| {
+        mutate_state(|state| state.is_build_index_running = false);
+    });
+    if let Err(error) = fetch_blocks().await {
+        log!(LOG, "[build_index]: {error}");
+    }
+}
+
+async fn fetch_blocks() -> Result<(), String> {
+    let (ledger_id, start) = with_state(|state| (state.ledger_id, state.next_block_index));
+    let res: GetBlocksResult = call(
+        ledger_id,
+        "icrc3_get_blocks",
+        vec![GetBlocksRequest {
+            start: Nat::from(start),
+            length: Nat::from(MAX_BLOCKS_PER_REQUEST),
+        }],
+    )
+    .await?;
+
+    // The archived blocks precede the blocks held by the ledger. Sort them by start index, as the
+    // ledger gives no guarantee about their order.
+    let mut archived_blocks = BTreeMap::new();
+    for ArchivedBlocks { args, callback } in res.archived_blocks {
+        for arg in args {
+            archived_blocks.insert(arg, callback.clone());
+        }
+    }
+    for (mut arg, callback) in archived_blocks {
+        // The archive can return fewer blocks than requested.
+        while arg.length != 0u64 {
+            let archived: GetBlocksResult =
+                call(callback.canister_id, &callback.method, vec![arg.clone()]).await?;
+            if archived.blocks.is_empty() {
+                return Err(format!(
+                    "the archive {} returned no blocks for {arg:?}",
+                    callback.canister_id
+                ));
+            }
+            arg.start += archived.blocks.len();
+            arg.length -= archived.blocks.len();
+            append_blocks(archived.blocks)?;
+        }
+    }
+    append_blocks(res.blocks)
+}
+
+fn append_blocks(blocks: Vec<BlockWithId>) -> Result<(), String> {
+    for BlockWithId { id, block } in blocks {
+        let expected_id = with_state(|state| state.next_block_index);
+        if id != expected_id {
+            return Err(format!(
+                "unexpected block id: expected {expected_id}, got {id}"
+            ));
+        }
+        let block = Block::try_from(Value::from(block))
+            .map_err(|err| format!("failed to decode block {id}: {err}"))?;
+        mutate_state(|state| state.apply(block));
+    }
+    Ok(())
+}
+
+#[query]
+fn ledger_id() -> Principal {
+    with_state(|state| state.ledger_id)
+}
+
+#[query]
+fn status() -> Status {
+    with_state(|state| Status {
+        num_blocks_synced: Nat::from(state.next_block_index),
+    })
+}
+
+#[query]
+fn get_owner_of(token_ids: Vec<Nat>) -> Vec<Option<Account>> {
+    with_state(|state| {
+        token_ids
+            .iter()
+            .map(|token_id| {
+                let token_id = token_id.0.to_u64()?;
+                state.owners.get(&token_id).copied()
+            })
+            .collect()
+    })
+}
+
+#[query]
+fn get_tokens_of(args: GetTokensOfArgs) -> Vec<Nat> {
+    let take = args
+        .take
+        .map_or(DEFAULT_TAKE_VALUE, |take| {
+            take.0.to_u64().unwrap_or(MAX_TAKE_VALUE)
+        })
+        .min(MAX_TAKE_VALUE) as usize;
+    let start = match args.prev {
+        None => Bound::Included((args.account, TokenId::MIN)),
+        Some(prev) => match prev.0.to_u64() {
+            Some(prev) => Bound::Excluded((args.account, prev)),
+            None => return vec![],
+        },
+    };
+    with_state(|state| {
+        state
+            .tokens_by_owner
+            .range((start, Bound::Included((args.account, TokenId::MAX))))
+            .take(take)
+            .map(|(_, token_id)| Nat::from(*token_id))
+            .collect()
+    })
+}
+
+#[query(
+    hidden = true,
+    decode_with = "candid::decode_one_with_decoding_quota::<100000,_>"
+)]
+fn http_request(req: HttpRequest) -> HttpResponse {
+    if req.path() == "/metrics" {
+        let mut writer =
+            ic_metrics_encoder::MetricsEncoder::new(vec![], ic_cdk::api::time() as i64 / 1_000_000);
+        match encode_metrics(&mut writer) {
+            Ok(()) => HttpResponseBuilder::ok()
+                .header("Content-Type", "text/plain; version=0.0.4")
+                .header("Cache-Control", "no-store")
+                .with_body_and_content_length(writer.into_inner())
+                .build(),
+            Err(err) => {
+                HttpResponseBuilder::server_error(format!("Failed to encode metrics: {err}"))
+                    .build()
+            }
+        }
+    } else if req.path() == "/logs" {
+        use std::io::Write;
+        let mut buf = vec![];
+        for entry in export_logs(&LOG) {
+            writeln!(
+                &mut buf,
+                "{} {}:{} {}",
+                entry.timestamp, entry.file, entry.line, entry.message
+            )
+            .unwrap();
+        }
+        HttpResponseBuilder::ok()
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .with_body_and_content_length(buf)
+            .build()
+    } else {
+        HttpResponseBuilder::not_found().build()
+    }
+}
+
+fn encode_metrics(w: &mut ic_metrics_encoder::MetricsEncoder<Vec<u8>>) -> std::io::Result<()> {
+    w.encode_gauge(
+        "index_stable_memory_pages",
+        ic_cdk::api::stable::stable_size() as f64,
+        "Size of the stable memory allocated by this canister measured in 64K Wasm pages.",
+    )?;
+    w.encode_gauge(
+        "index_cycle_balance",
+        ic_cdk::api::canister_balance128() as f64,
+        "Cycle balance on this canister.",
+    )?;
+    with_state(|state| {
+        w.encode_gauge(
+            "index_num_blocks_synced",
+            state.next_block_index as f64,
+            "Total number of blocks synced from the ledger.",
+        )?;
+        w.encode_gauge(
+            "index_num_tokens",
+            state.owners.len() as f64,
+            "Total number of tokens tracked by the index.",
+        )
+    })
+}
+
+candid::export_service!();
+
+#[query]
+fn __get_candid_interface_tmp_hack() -> String {
+    __export_service()
+}
+
+fn main() {}
+
+#[test]
+fn check_candid_interface() {
+    use candid_parser::utils::{CandidSource, service_equal};
+
+    let new_interface = __export_service();
+    let manifest_dir = std::path::PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let old_interface = manifest_dir.join("index.did");
+    service_equal(
+        CandidSource::Text(&new_interface),
+        CandidSource::File(old_interface.as_path()),
+    )
+    .unwrap_or_else(|e| {
+        panic!(
+            "the index interface is not compatible with {}: {:?}",
+            old_interface.display(),
+            e
+        )
+    });
+}
@@ -0,0 +1,230 @@
+use candid::{Decode, Encode, Nat};
+use ic_base_types::{CanisterId, PrincipalId};
+use ic_icrc7_index::{GetTokensOfArgs, IndexArg, InitArg, Status};
+use ic_icrc7_ledger::{InitArgs, LedgerArgument, MintArg, MintError};
+use ic_ledger_canister_core::archive::ArchiveOptions;
+use ic_state_machine_tests::StateMachine;
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc7::transfer::{TransferArg, TransferResult};
+use num_traits::ToPrimitive;
+use std::time::Duration;
+
+const MAX_ATTEMPTS_FOR_INDEX_SYNC_WAIT: u8 = 100;
+
+fn minter() -> Account {
+    Account::from(PrincipalId::new_user_test_id(1).0)
+}
+
+fn account(id: u64) -> Account {
+    Account::from(PrincipalId::new_user_test_id(id).0)
+}
+
+fn index_wasm() -> Vec<u8> {
+    ic_test_utilities_load_wasm::load_wasm(
+        std::env::var("CARGO_MANIFEST_DIR").unwrap(),
+        "ic-icrc7-index",
+        &[],
+    )
+}
+
+fn ledger_wasm() -> Vec<u8> {
+    let ledger_wasm_path = std::env::var("IC_ICRC7_LEDGER_WASM_PATH").expect(
+        "The Ledger wasm path must be set using the env variable IC_ICRC7_LEDGER_WASM_PATH",
+    );
+    std::fs::read(&ledger_wasm_path).unwrap_or_else(|e| {
+        panic!(
+            "failed to load Wasm file from path {ledger_wasm_path} (env var IC_ICRC7_LEDGER_WASM_PATH): {e}"
+        )
+    })
+}
+
+fn install_ledger_and_index(env: &StateMachine) -> (CanisterId, CanisterId) {
+    let args = LedgerArgument::Init(InitArgs {
+        minting_account: minter(),
+        symbol: "NFT".to_string(),
+        name: "Test NFT collection".to_string(),
+        description: None,
+        logo: None,
+        supply_cap: None,
+        archive_options: ArchiveOptions {
+            trigger_threshold: 10,
+            num_blocks_to_archive: 5,
+            node_max_memory_size_bytes: None,
+            max_message_size_bytes: None,
+            controller_id: PrincipalId::new_user_test_id(100),
+            more_controller_ids: None,
+            cycles_for_archive_creation: Some(0),
+            max_transactions_per_response: Some(3),
+        },
+    });
+    let ledger_id = env
+        .install_canister(ledger_wasm(), Encode!(&args).unwrap(), None)
+        .unwrap();
+    let args = Some(IndexArg::Init(InitArg {
+        ledger_id: ledger_id.get().0,
+        retrieve_blocks_from_ledger_interval_seconds: None,
+    }));
+    let index_id = env
+        .install_canister(index_wasm(), Encode!(&args).unwrap(), None)
+        .unwrap();
+    (ledger_id, index_id)
+}
+
+fn mint(env: &StateMachine, ledger_id: CanisterId, token_id: u64, to: Account) {
+    let arg = MintArg {
+        token_id: Nat::from(token_id),
+        to,
+        metadata: vec![],
+        memo: None,
+        created_at_time: None,
+    };
+    let res = env
+        .execute_ingress_as(
+            PrincipalId(minter().owner),
+            ledger_id,
+            "mint",
+            Encode!(&arg).unwrap(),
+        )
+        .expect("failed to mint")
+        .bytes();
+    Decode!(&res, Result<Nat, MintError>)
+        .unwrap()
+        .expect("failed to mint");
+}
+
+fn transfer(env: &StateMachine, ledger_id: CanisterId, from: Account, token_id: u64, to: Account) {
+    let args = vec![TransferArg {
+        from_subaccount: from.subaccount,
+        to,
+        token_id: Nat::from(token_id),
+        memo: None,
+        created_at_time: None,
+    }];
+    let res = env
+        .execute_ingress_as(
+            PrincipalId(from.owner),
+            ledger_id,
+            "icrc7_transfer",
+            Encode!(&args).unwrap(),
+        )
+        .expect("failed to transfer")
+        .bytes();
+    let res = Decode!(&res, Vec<Option<TransferResult>>).unwrap();
+    assert!(
+        matches!(res[..], [Some(Ok(_))]),
+        "failed to transfer: {res:?}"
+    );
+}
+
+fn burn(env: &StateMachine, ledger_id: CanisterId, from: Account, token_id: u64) {
+    let arg = ic_icrc7_ledger::BurnArg {
+        from_subaccount: from.subaccount,
+        token_id: Nat::from(token_id),
+        memo: None,
+        created_at_time: None,
+    };
+    let res = env
+        .execute_ingress_as(
+            PrincipalId(from.owner),
+            ledger_id,
+            "burn",
+            Encode!(&arg).unwrap(),
+        )
+        .expect("failed to burn")
+        .bytes();
+    Decode!(&res, Result<Nat, ic_icrc7_ledger::BurnError>)
+        .unwrap()
+        .expect("failed to burn");
+}
+
+fn wait_until_sync_is_completed(env: &StateMachine, index_id: CanisterId, num_blocks: u64) {
+    for _ in 0..MAX_ATTEMPTS_FOR_INDEX_SYNC_WAIT {
+        env.advance_time(Duration::from_secs(60));
+        env.tick();
+        let res = env
+            .query(index_id, "status", Encode!(&()).unwrap())
+            .expect("failed to query status")
+            .bytes();
+        let status = Decode!(&res, Status).unwrap();
+        if status.num_blocks_synced.0.to_u64().unwrap() == num_blocks {
+            return;
+        }
+    }
+    panic!("the index canister was unable to sync {num_blocks} blocks with the ledger");
+}
+
+fn get_tokens_of(env: &StateMachine, index_id: CanisterId, account: Account) -> Vec<Nat> {
+    let args = GetTokensOfArgs {
+        account,
+        prev: None,
+        take: None,
+    };
+    let res = env
+        .query(index_id, "get_tokens_of", Encode!(&args).unwrap())
+        .expect("failed to query get_tokens_of")
+        .bytes();
+    Decode!(&res, Vec<Nat>).unwrap()
+}
+
+fn get_owner_of(
+    env: &StateMachine,
+    index_id: CanisterId,
+    token_ids: Vec<Nat>,
+) -> Vec<Option<Account>> {
+    let res = env
+        .query(index_id, "get_owner_of", Encode!(&token_ids).unwrap())
+        .expect("failed to query get_owner_of")
+        .bytes();
+    Decode!(&res, Vec<Option<Account>>).unwrap()
+}
+
+#[test]
+fn should_index_owners_from_ledger_and_archives() {
+    let env = StateMachine::new();
+    let (ledger_id, index_id) = install_ledger_and_index(&env);
+
+    // Enough blocks for the ledger to spawn an archive.
+    for token_id in 0..12 {
+        mint(&env, ledger_id, token_id, account(2));
+    }
+    transfer(&env, ledger_id, account(2), 3, account(3));
+    burn(&env, ledger_id, account(2), 4);
+    wait_until_sync_is_completed(&env, index_id, 14);
+
+    let expected: Vec<Nat> = [0u64, 1, 2, 5, 6, 7, 8, 9, 10, 11]
+        .into_iter()
+        .map(Nat::from)
+        .collect::<Vec<_>>();
+    assert_eq!(get_tokens_of(&env, index_id, account(2)), expected);
+    assert_eq!(
+        get_tokens_of(&env, index_id, account(3)),
+        vec![Nat::from(3u64)]
+    );
+    assert_eq!(
+        get_owner_of(
+            &env,
+            index_id,
+            vec![Nat::from(3u64), Nat::from(4u64), Nat::from(5u64)]
+        ),
+        vec![Some(account(3)), None, Some(account(2))]
+    );
+}
+
+#[test]
+fn should_keep_the_index_across_upgrades() {
+    let env = StateMachine::new();
+    let (ledger_id, index_id) = install_ledger_and_index(&env);
+
+    mint(&env, ledger_id, 1, account(2));
+    wait_until_sync_is_completed(&env, index_id, 1);
+
+    env.upgrade_canister(index_id, index_wasm(), Encode!(&None::<IndexArg>).unwrap())
+        .expect("failed to upgrade the index");
+    mint(&env, ledger_id, 2, account(2));
+    wait_until_sync_is_completed(&env, index_id, 2);
+
+    assert_eq!(
+        get_tokens_of(&env, index_id, account(2)),
+        vec![Nat::from(1u64), Nat::from(2u64)]
+    );
+}
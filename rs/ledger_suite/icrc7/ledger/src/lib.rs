@@ -0,0 +1,1433 @@
+//! The ICRC-7/ICRC-37 non-fungible token ledger.
+//!
+//! The ledger records its transactions in an ICRC-3 block log managed by the ledger suite core,
+//! so that blocks are archived, certified and preserved across upgrades in the same way as the
+//! blocks of the ICRC-1 ledger.
+
+#[cfg(test)]
+mod tests;
+
+use candid::{CandidType, Nat, Principal};
+use ic_certification::{
+    HashTree,
+    hash_tree::{Label, empty, fork, label, leaf},
+};
+use ic_icrc1::blocks::{encoded_block_to_generic_block, generic_block_to_encoded_block};
+use ic_icrc7::{Block, Operation, TokenId, Transaction};
+pub use ic_ledger_canister_core::archive::ArchiveOptions;
+use ic_ledger_canister_core::{
+    archive::ArchiveCanisterWasm,
+    blockchain::{BlockDataContainer, Blockchain},
+    ledger::blockchain_block_locations,
+    range_utils,
+    runtime::CdkRuntime,
+};
+use ic_ledger_core::{
+    block::{BlockIndex, BlockType, EncodedBlock},
+    timestamp::TimeStamp,
+};
+use ic_ledger_hash_of::HashOf;
+use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
+use ic_stable_structures::{DefaultMemoryImpl, StableBTreeMap};
+use ic_stable_structures::{Storable, storable::Bound as StorableBound};
+use icrc_ledger_types::{
+    icrc::generic_value::{ICRC3Value, Map, Value},
+    icrc1::{
+        account::{Account, Subaccount},
+        transfer::Memo,
+    },
+    icrc3::{
+        archive::{GetArchivesArgs, GetArchivesResult, ICRC3ArchiveInfo, QueryArchiveFn},
+        blocks::{ArchivedBlocks, BlockWithId, GetBlocksRequest, GetBlocksResult},
+    },
+    icrc7::transfer::{TransferArg, TransferError},
+    icrc37::{
+        approve::{
+            ApprovalInfo, ApproveCollectionArg, ApproveCollectionError, ApproveTokenArg,
+            ApproveTokenError, CollectionApproval, IsApprovedArg, TokenApproval,
+        },
+        revoke::{
+            RevokeCollectionApprovalArg, RevokeCollectionApprovalError, RevokeTokenApprovalArg,
+            RevokeTokenApprovalError,
+        },
+        transfer_from::{TransferFromArg, TransferFromError},
+    },
+};
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_bytes::ByteBuf;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::Bound;
+use std::time::Duration;
+
+pub const MAX_QUERY_BATCH_SIZE: u64 = 1_000;
+pub const MAX_UPDATE_BATCH_SIZE: u64 = 100;
+pub const DEFAULT_TAKE_VALUE: u64 = 100;
+pub const MAX_TAKE_VALUE: u64 = 1_000;
+pub const MAX_MEMO_SIZE: u64 = 32;
+pub const MAX_APPROVALS_PER_TOKEN_OR_COLLECTION: u64 = 100;
+pub const MAX_REVOKE_APPROVALS: u64 = 100;
+
+pub const TRANSACTION_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+pub const PERMITTED_DRIFT: Duration = ic_limits::PERMITTED_DRIFT;
+const MAX_TRANSACTIONS_TO_PURGE: usize = 100_000;
+/// The maximum number of blocks the ledger returns for a single icrc3_get_blocks request.
+const MAX_BLOCKS_PER_RESPONSE: u64 = 100;
+const MAX_U64_ENCODING_BYTES: usize = 10;
+
+/// The blocks of this ledger have the same encoding as the blocks of the ICRC-1 ledger, so the
+/// ledger spawns ICRC-1 archive canisters.
+#[derive(Clone, Debug)]
+pub struct Icrc7ArchiveWasm;
+
+impl ArchiveCanisterWasm for Icrc7ArchiveWasm {
+    fn archive_wasm() -> Cow<'static, [u8]> {
+        Cow::Borrowed(include_bytes!(env!("IC_ICRC1_ARCHIVE_WASM_PATH")))
+    }
+}
+
+const UPGRADES_MEMORY_ID: MemoryId = MemoryId::new(0);
+const BLOCKS_MEMORY_ID: MemoryId = MemoryId::new(1);
+const TOKENS_MEMORY_ID: MemoryId = MemoryId::new(2);
+const TOKENS_BY_OWNER_MEMORY_ID: MemoryId = MemoryId::new(3);
+const TOKEN_APPROVALS_MEMORY_ID: MemoryId = MemoryId::new(4);
+const COLLECTION_APPROVALS_MEMORY_ID: MemoryId = MemoryId::new(5);
+
+type ApprovalsMap<K> = StableBTreeMap<K, StoredApproval, VirtualMemory<DefaultMemoryImpl>>;
+
+thread_local! {
+    static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = RefCell::new(
+        MemoryManager::init(DefaultMemoryImpl::default())
+    );
+
+    // The memory where the ledger must write and read its state during an upgrade.
+    pub static UPGRADES_MEMORY: RefCell<VirtualMemory<DefaultMemoryImpl>> = MEMORY_MANAGER.with(|memory_manager|
+        RefCell::new(memory_manager.borrow().get(UPGRADES_MEMORY_ID)));
+
+    // block_index -> block
+    pub static BLOCKS_MEMORY: RefCell<StableBTreeMap<u64, Vec<u8>, VirtualMemory<DefaultMemoryImpl>>> =
+        MEMORY_MANAGER.with(|memory_manager| RefCell::new(StableBTreeMap::init(memory_manager.borrow().get(BLOCKS_MEMORY_ID))));
+
+    // token_id -> token
+    static TOKENS_MEMORY: RefCell<StableBTreeMap<TokenId, StoredToken, VirtualMemory<DefaultMemoryImpl>>> =
+        MEMORY_MANAGER.with(|memory_manager| RefCell::new(StableBTreeMap::init(memory_manager.borrow().get(TOKENS_MEMORY_ID))));
+
+    // (owner, token_id) -> ()
+    static TOKENS_BY_OWNER_MEMORY: RefCell<StableBTreeMap<OwnerToken, (), VirtualMemory<DefaultMemoryImpl>>> =
+        MEMORY_MANAGER.with(|memory_manager| RefCell::new(StableBTreeMap::init(memory_manager.borrow().get(TOKENS_BY_OWNER_MEMORY_ID))));
+
+    // (token_id, spender) -> approval; cleared when the token changes hands.
+    static TOKEN_APPROVALS_MEMORY: RefCell<ApprovalsMap<TokenSpender>> =
+        MEMORY_MANAGER.with(|memory_manager| RefCell::new(StableBTreeMap::init(memory_manager.borrow().get(TOKEN_APPROVALS_MEMORY_ID))));
+
+    // (owner, spender) -> approval
+    static COLLECTION_APPROVALS_MEMORY: RefCell<ApprovalsMap<OwnerSpender>> =
+        MEMORY_MANAGER.with(|memory_manager| RefCell::new(StableBTreeMap::init(memory_manager.borrow().get(COLLECTION_APPROVALS_MEMORY_ID))));
+}
+
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct StableBlockDataContainer {}
+
+impl BlockDataContainer for StableBlockDataContainer {
+    fn with_blocks<R>(
+        f: impl FnOnce(&StableBTreeMap<u64, Vec<u8>, VirtualMemory<DefaultMemoryImpl>>) -> R,
+    ) -> R {
+        BLOCKS_MEMORY.with(|cell| f(&cell.borrow()))
+    }
+
+    fn with_blocks_mut<R>(
+        f: impl FnOnce(&mut StableBTreeMap<u64, Vec<u8>, VirtualMemory<DefaultMemoryImpl>>) -> R,
+    ) -> R {
+        BLOCKS_MEMORY.with(|cell| f(&mut cell.borrow_mut()))
+    }
+}
+
+pub type CdkBlockchain = Blockchain<CdkRuntime, Icrc7ArchiveWasm, StableBlockDataContainer>;
+
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize)]
+pub struct InitArgs {
+    /// The account allowed to mint new tokens.
+    pub minting_account: Account,
+    pub symbol: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub logo: Option<String>,
+    pub supply_cap: Option<Nat>,
+    pub archive_options: ArchiveOptions,
+}
+
+#[derive(Clone, Debug, Default, CandidType, Deserialize, Serialize)]
+pub struct UpgradeArgs {
+    pub symbol: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub logo: Option<String>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize)]
+pub enum LedgerArgument {
+    Init(InitArgs),
+    Upgrade(Option<UpgradeArgs>),
+}
+
+/// The arguments of the `mint` endpoint, which is not part of ICRC-7.
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct MintArg {
+    pub token_id: Nat,
+    pub to: Account,
+    pub metadata: Vec<(String, ICRC3Value)>,
+    pub memo: Option<Memo>,
+    pub created_at_time: Option<u64>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub enum MintError {
+    TokenIdAlreadyExists,
+    SupplyCapReached,
+    Unauthorized,
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    Duplicate { duplicate_of: Nat },
+    GenericError { error_code: Nat, message: String },
+}
+
+/// The arguments of the `burn` endpoint, which is not part of ICRC-7.
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct BurnArg {
+    pub from_subaccount: Option<Subaccount>,
+    pub token_id: Nat,
+    pub memo: Option<Memo>,
+    pub created_at_time: Option<u64>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub enum BurnError {
+    NonExistingTokenId,
+    Unauthorized,
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    Duplicate { duplicate_of: Nat },
+    GenericError { error_code: Nat, message: String },
+}
+
+/// The reasons for rejecting a transaction. The endpoints convert them into the error types of
+/// the corresponding standards.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TxError {
+    NonExistingTokenId,
+    TokenIdAlreadyExists,
+    TokenIdTooLarge,
+    SupplyCapReached,
+    Unauthorized,
+    InvalidRecipient,
+    InvalidSpender,
+    ApprovalDoesNotExist,
+    TooManyApprovals,
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    Duplicate { duplicate_of: BlockIndex },
+}
+
+impl TxError {
+    /// The error code of errors that a standard error type can only report as a generic error.
+    const GENERIC_ERROR_CODE: u64 = 0;
+
+    fn generic_error(&self) -> (Nat, String) {
+        let message = match self {
+            Self::TokenIdTooLarge => "the token id does not fit into 64 bits".to_string(),
+            Self::TooManyApprovals => format!(
+                "the number of approvals would exceed the maximum of {MAX_APPROVALS_PER_TOKEN_OR_COLLECTION}"
+            ),
+            Self::Duplicate { duplicate_of } => {
+                format!("the transaction is a duplicate of block {duplicate_of}")
+            }
+            other => format!("{other:?}"),
+        };
+        (Nat::from(Self::GENERIC_ERROR_CODE), message)
+    }
+}
+
+impl From<TxError> for TransferError {
+    fn from(error: TxError) -> Self {
+        match error {
+            TxError::NonExistingTokenId => Self::NonExistingTokenId,
+            TxError::InvalidRecipient => Self::InvalidRecipient,
+            TxError::Unauthorized => Self::Unauthorized,
+            TxError::TooOld => Self::TooOld,
+            TxError::CreatedInFuture { ledger_time } => Self::CreatedInFuture { ledger_time },
+            TxError::Duplicate { duplicate_of } => Self::Duplicate {
+                duplicate_of: Nat::from(duplicate_of),
+            },
+            other => {
+                let (error_code, message) = other.generic_error();
+                Self::GenericError {
+                    error_code,
+                    message,
+                }
+            }
+        }
+    }
+}
+
+impl From<TxError> for TransferFromError {
+    fn from(error: TxError) -> Self {
+        match error {
+            TxError::NonExistingTokenId => Self::NonExistingTokenId,
+            TxError::InvalidRecipient => Self::InvalidRecipient,
+            TxError::Unauthorized => Self::Unauthorized,
+            TxError::TooOld => Self::TooOld,
+            TxError::CreatedInFuture { ledger_time } => Self::CreatedInFuture { ledger_time },
+            TxError::Duplicate { duplicate_of } => Self::Duplicate {
+                duplicate_of: Nat::from(duplicate_of),
+            },
+            other => {
+                let (error_code, message) = other.generic_error();
+                Self::GenericError {
+                    error_code,
+                    message,
+                }
+            }
+        }
+    }
+}
+
+impl From<TxError> for ApproveTokenError {
+    fn from(error: TxError) -> Self {
+        match error {
+            TxError::InvalidSpender => Self::InvalidSpender,
+            TxError::Unauthorized => Self::Unauthorized,
+            TxError::NonExistingTokenId => Self::NonExistingTokenId,
+            TxError::TooOld => Self::TooOld,
+            TxError::CreatedInFuture { ledger_time } => Self::CreatedInFuture { ledger_time },
+            other => {
+                let (error_code, message) = other.generic_error();
+                Self::GenericError {
+                    error_code,
+                    message,
+                }
+            }
+        }
+    }
+}
+
+impl From<TxError> for ApproveCollectionError {
+    fn from(error: TxError) -> Self {
+        match error {
+            TxError::InvalidSpender => Self::InvalidSpender,
+            TxError::TooOld => Self::TooOld,
+            TxError::CreatedInFuture { ledger_time } => Self::CreatedInFuture { ledger_time },
+            other => {
+                let (error_code, message) = other.generic_error();
+                Self::GenericError {
+                    error_code,
+                    message,
+                }
+            }
+        }
+    }
+}
+
+impl From<TxError> for RevokeTokenApprovalError {
+    fn from(error: TxError) -> Self {
+        match error {
+            TxError::ApprovalDoesNotExist => Self::ApprovalDoesNotExist,
+            TxError::Unauthorized => Self::Unauthorized,
+            TxError::NonExistingTokenId => Self::NonExistingTokenId,
+            TxError::TooOld => Self::TooOld,
+            TxError::CreatedInFuture { ledger_time } => Self::CreatedInFuture { ledger_time },
+            other => {
+                let (error_code, message) = other.generic_error();
+                Self::GenericError {
+                    error_code,
+                    message,
+                }
+            }
+        }
+    }
+}
+
+impl From<TxError> for RevokeCollectionApprovalError {
+    fn from(error: TxError) -> Self {
+        match error {
+            TxError::ApprovalDoesNotExist => Self::ApprovalDoesNotExist,
+            TxError::TooOld => Self::TooOld,
+            TxError::CreatedInFuture { ledger_time } => Self::CreatedInFuture { ledger_time },
+            other => {
+                let (error_code, message) = other.generic_error();
+                Self::GenericError {
+                    error_code,
+                    message,
+                }
+            }
+        }
+    }
+}
+
+impl From<TxError> for MintError {
+    fn from(error: TxError) -> Self {
+        match error {
+            TxError::TokenIdAlreadyExists => Self::TokenIdAlreadyExists,
+            TxError::SupplyCapReached => Self::SupplyCapReached,
+            TxError::Unauthorized => Self::Unauthorized,
+            TxError::TooOld => Self::TooOld,
+            TxError::CreatedInFuture { ledger_time } => Self::CreatedInFuture { ledger_time },
+            TxError::Duplicate { duplicate_of } => Self::Duplicate {
+                duplicate_of: Nat::from(duplicate_of),
+            },
+            other => {
+                let (error_code, message) = other.generic_error();
+                Self::GenericError {
+                    error_code,
+                    message,
+                }
+            }
+        }
+    }
+}
+
+impl From<TxError> for BurnError {
+    fn from(error: TxError) -> Self {
+        match error {
+            TxError::NonExistingTokenId => Self::NonExistingTokenId,
+            TxError::Unauthorized => Self::Unauthorized,
+            TxError::TooOld => Self::TooOld,
+            TxError::CreatedInFuture { ledger_time } => Self::CreatedInFuture { ledger_time },
+            TxError::Duplicate { duplicate_of } => Self::Duplicate {
+                duplicate_of: Nat::from(duplicate_of),
+            },
+            other => {
+                let (error_code, message) = other.generic_error();
+                Self::GenericError {
+                    error_code,
+                    message,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct StoredToken {
+    owner: Account,
+    /// The token metadata, encoded like an ICRC-3 block so that it survives the CBOR
+    /// encoding of the stable token map.
+    metadata: ByteBuf,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct StoredApproval {
+    /// The subaccount of the owner that granted the approval.
+    from_subaccount: Option<Subaccount>,
+    expires_at: Option<u64>,
+    memo: Option<Memo>,
+    created_at_time: Option<u64>,
+}
+
+impl StoredApproval {
+    fn is_active(&self, now: TimeStamp) -> bool {
+        self.expires_at
+            .is_none_or(|expires_at| expires_at > now.as_nanos_since_unix_epoch())
+    }
+
+    fn to_approval_info(&self, spender: Account) -> ApprovalInfo {
+        ApprovalInfo {
+            spender,
+            from_subaccount: self.from_subaccount,
+            expires_at: self.expires_at,
+            memo: self.memo.clone(),
+            created_at_time: self.created_at_time.unwrap_or_default(),
+        }
+    }
+}
+
+/// The key of `TOKENS_BY_OWNER_MEMORY`, ordered by owner first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+struct OwnerToken {
+    owner: Account,
+    token_id: TokenId,
+}
+
+/// The key of `TOKEN_APPROVALS_MEMORY`, ordered by token first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+struct TokenSpender {
+    token_id: TokenId,
+    spender: Account,
+}
+
+/// The key of `COLLECTION_APPROVALS_MEMORY`, ordered by owner first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+struct OwnerSpender {
+    owner: Account,
+    spender: Account,
+}
+
+/// Implements [Storable] for a type by CBOR-encoding it. The stable maps compare keys by
+/// decoding them, so the encoding does not need to preserve the order of the keys.
+macro_rules! impl_storable_with_cbor {
+    ($($ty:ty),*) => {
+        $(
+            impl Storable for $ty {
+                fn to_bytes(&self) -> Cow<'_, [u8]> {
+                    Cow::Owned(encode_cbor(self))
+                }
+
+                fn from_bytes(bytes: Cow<[u8]>) -> Self {
+                    decode_cbor(bytes.as_ref(), stringify!($ty))
+                }
+
+                const BOUND: StorableBound = StorableBound::Unbounded;
+            }
+        )*
+    };
+}
+
+impl_storable_with_cbor!(
+    StoredToken,
+    StoredApproval,
+    OwnerToken,
+    TokenSpender,
+    OwnerSpender
+);
+
+fn encode_cbor<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut buf = vec![];
+    ciborium::ser::into_writer(value, &mut buf).expect("bug: failed to encode a stable map entry");
+    buf
+}
+
+fn decode_cbor<T: DeserializeOwned>(bytes: &[u8], type_name: &str) -> T {
+    ciborium::de::from_reader(bytes)
+        .unwrap_or_else(|e| panic!("failed to decode {type_name} bytes {bytes:?}: {e}"))
+}
+
+/// The key of a stable approval map: the token or the owner the approval applies to (the
+/// scope), and the spender.
+trait ApprovalKey: Storable + Ord + Clone {
+    type Scope: Copy + PartialEq;
+
+    fn new(scope: Self::Scope, spender: Account) -> Self;
+    fn scope(&self) -> Self::Scope;
+    fn spender(&self) -> Account;
+}
+
+impl ApprovalKey for TokenSpender {
+    type Scope = TokenId;
+
+    fn new(token_id: TokenId, spender: Account) -> Self {
+        Self { token_id, spender }
+    }
+
+    fn scope(&self) -> TokenId {
+        self.token_id
+    }
+
+    fn spender(&self) -> Account {
+        self.spender
+    }
+}
+
+impl ApprovalKey for OwnerSpender {
+    type Scope = Account;
+
+    fn new(owner: Account, spender: Account) -> Self {
+        Self { owner, spender }
+    }
+
+    fn scope(&self) -> Account {
+        self.owner
+    }
+
+    fn spender(&self) -> Account {
+        self.spender
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RecentTransaction {
+    block_timestamp: TimeStamp,
+    transaction_hash: HashOf<Transaction>,
+}
+
+/// The ledger state that is serialized into `UPGRADES_MEMORY` on upgrades. The tokens and the
+/// approvals live in stable maps, so the size of this state does not grow with the collection.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Ledger {
+    blockchain: CdkBlockchain,
+
+    minting_account: Account,
+    symbol: String,
+    name: String,
+    description: Option<String>,
+    logo: Option<String>,
+    supply_cap: Option<u64>,
+
+    transactions_by_hash: BTreeMap<HashOf<Transaction>, BlockIndex>,
+    transactions_by_height: VecDeque<RecentTransaction>,
+}
+
+impl Ledger {
+    pub fn from_init_args(
+        InitArgs {
+            minting_account,
+            symbol,
+            name,
+            description,
+            logo,
+            supply_cap,
+            archive_options,
+        }: InitArgs,
+    ) -> Self {
+        Self {
+            blockchain: Blockchain::new_with_archive(archive_options),
+            minting_account,
+            symbol,
+            name,
+            description,
+            logo,
+            supply_cap: supply_cap.map(|cap| cap.0.to_u64().unwrap_or(u64::MAX)),
+            transactions_by_hash: BTreeMap::new(),
+            transactions_by_height: VecDeque::new(),
+        }
+    }
+
+    pub fn upgrade(&mut self, args: UpgradeArgs) {
+        if let Some(symbol) = args.symbol {
+            self.symbol = symbol;
+        }
+        if let Some(name) = args.name {
+            self.name = name;
+        }
+        if let Some(description) = args.description {
+            self.description = Some(description);
+        }
+        if let Some(logo) = args.logo {
+            self.logo = Some(logo);
+        }
+    }
+
+    pub fn blockchain(&self) -> &CdkBlockchain {
+        &self.blockchain
+    }
+
+    pub fn blockchain_mut(&mut self) -> &mut CdkBlockchain {
+        &mut self.blockchain
+    }
+
+    pub fn minting_account(&self) -> &Account {
+        &self.minting_account
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn logo(&self) -> Option<&str> {
+        self.logo.as_deref()
+    }
+
+    pub fn supply_cap(&self) -> Option<u64> {
+        self.supply_cap
+    }
+
+    pub fn total_supply(&self) -> u64 {
+        TOKENS_MEMORY.with_borrow(|tokens| tokens.len())
+    }
+
+    pub fn collection_metadata(&self) -> Vec<(String, ICRC3Value)> {
+        let text = |key: &str, value: &str| (key.to_string(), ICRC3Value::Text(value.to_string()));
+        let nat = |key: &str, value: u64| (key.to_string(), ICRC3Value::Nat(Nat::from(value)));
+
+        let mut metadata = vec![
+            text("icrc7:symbol", &self.symbol),
+            text("icrc7:name", &self.name),
+        ];
+        if let Some(description) = &self.description {
+            metadata.push(text("icrc7:description", description));
+        }
+        if let Some(logo) = &self.logo {
+            metadata.push(text("icrc7:logo", logo));
+        }
+        metadata.push(nat("icrc7:total_supply", self.total_supply()));
+        if let Some(supply_cap) = self.supply_cap {
+            metadata.push(nat("icrc7:supply_cap", supply_cap));
+        }
+        metadata.extend([
+            nat("icrc7:max_query_batch_size", MAX_QUERY_BATCH_SIZE),
+            nat("icrc7:max_update_batch_size", MAX_UPDATE_BATCH_SIZE),
+            nat("icrc7:default_take_value", DEFAULT_TAKE_VALUE),
+            nat("icrc7:max_take_value", MAX_TAKE_VALUE),
+            nat("icrc7:max_memo_size", MAX_MEMO_SIZE),
+            nat("icrc7:tx_window", TRANSACTION_WINDOW.as_nanos() as u64),
+            nat("icrc7:permitted_drift", PERMITTED_DRIFT.as_nanos() as u64),
+        ]);
+        metadata
+    }
+
+    pub fn owner_of(&self, token_id: &Nat) -> Option<Account> {
+        let token_id = token_id.0.to_u64()?;
+        TOKENS_MEMORY
+            .with_borrow(|tokens| tokens.get(&token_id))
+            .map(|token| token.owner)
+    }
+
+    pub fn balance_of(&self, account: &Account) -> u64 {
+        TOKENS_BY_OWNER_MEMORY.with_borrow(|tokens_by_owner| {
+            tokens_by_owner.range(owner_range(*account, None)).count() as u64
+        })
+    }
+
+    pub fn token_metadata(&self, token_id: &Nat) -> Option<Vec<(String, ICRC3Value)>> {
+        let token_id = token_id.0.to_u64()?;
+        let token = TOKENS_MEMORY.with_borrow(|tokens| tokens.get(&token_id))?;
+        Some(
+            decode_metadata(&token.metadata)
+                .into_iter()
+                .map(|(key, value)| (key, ICRC3Value::from(value)))
+                .collect(),
+        )
+    }
+
+    /// Returns up to `take` token ids in ascending order, starting after `prev`.
+    pub fn tokens(&self, prev: Option<&Nat>, take: usize) -> Vec<TokenId> {
+        let start = match prev {
+            None => Bound::Unbounded,
+            Some(prev) => match prev.0.to_u64() {
+                Some(prev) => Bound::Excluded(prev),
+                None => return vec![],
+            },
+        };
+        TOKENS_MEMORY.with_borrow(|tokens| {
+            tokens
+                .range((start, Bound::Unbounded))
+                .map(|(token_id, _)| token_id)
+                .take(take)
+                .collect()
+        })
+    }
+
+    /// Returns up to `take` ids of tokens owned by `account` in ascending order, starting after
+    /// `prev`.
+    pub fn tokens_of(&self, account: Account, prev: Option<&Nat>, take: usize) -> Vec<TokenId> {
+        let prev = match prev {
+            None => None,
+            Some(prev) => match prev.0.to_u64() {
+                Some(prev) => Some(prev),
+                None => return vec![],
+            },
+        };
+        TOKENS_BY_OWNER_MEMORY.with_borrow(|tokens_by_owner| {
+            tokens_by_owner
+                .range(owner_range(account, prev))
+                .map(|(key, ())| key.token_id)
+                .take(take)
+                .collect()
+        })
+    }
+
+    pub fn is_approved(&self, arg: &IsApprovedArg, now: TimeStamp) -> bool {
+        let Some((token_id, token)) = nat_to_token_id(&arg.token_id)
+            .and_then(|token_id| Some((token_id, get_token(token_id)?)))
+        else {
+            return false;
+        };
+        let from = Account {
+            owner: token.owner.owner,
+            subaccount: arg.from_subaccount,
+        };
+        from == token.owner && self.has_active_approval(&arg.spender, &from, token_id, now)
+    }
+
+    /// Returns up to `take` active approvals of the token, ordered by spender and starting after
+    /// the spender of `prev`.
+    pub fn token_approvals(
+        &self,
+        token_id: &Nat,
+        prev: Option<&TokenApproval>,
+        take: usize,
+        now: TimeStamp,
+    ) -> Vec<TokenApproval> {
+        let Some(scope) = token_id.0.to_u64() else {
+            return vec![];
+        };
+        let prev = prev.map(|prev| prev.approval_info.spender);
+        TOKEN_APPROVALS_MEMORY.with_borrow(|approvals| {
+            approvals_of(approvals, scope, prev)
+                .filter(|(_, approval)| approval.is_active(now))
+                .take(take)
+                .map(|(spender, approval)| TokenApproval {
+                    token_id: token_id.clone(),
+                    approval_info: approval.to_approval_info(spender),
+                })
+                .collect()
+        })
+    }
+
+    /// Returns up to `take` active collection approvals granted by `owner`, ordered by spender and
+    /// starting after the spender of `prev`.
+    pub fn collection_approvals(
+        &self,
+        owner: &Account,
+        prev: Option<&CollectionApproval>,
+        take: usize,
+        now: TimeStamp,
+    ) -> Vec<CollectionApproval> {
+        let prev = prev.map(|prev| prev.spender);
+        COLLECTION_APPROVALS_MEMORY.with_borrow(|approvals| {
+            approvals_of(approvals, *owner, prev)
+                .filter(|(_, approval)| approval.is_active(now))
+                .take(take)
+                .map(|(spender, approval)| approval.to_approval_info(spender))
+                .collect()
+        })
+    }
+
+    pub fn transfer(
+        &mut self,
+        caller: Principal,
+        arg: TransferArg,
+        now: TimeStamp,
+    ) -> Result<BlockIndex, TxError> {
+        let token_id = nat_to_token_id(&arg.token_id).ok_or(TxError::NonExistingTokenId)?;
+        let operation = Operation::Transfer {
+            token_id,
+            from: Account {
+                owner: caller,
+                subaccount: arg.from_subaccount,
+            },
+            to: arg.to,
+        };
+        self.execute(operation, arg.memo, arg.created_at_time, now)
+    }
+
+    pub fn transfer_from(
+        &mut self,
+        caller: Principal,
+        arg: TransferFromArg,
+        now: TimeStamp,
+    ) -> Result<BlockIndex, TxError> {
+        let token_id = nat_to_token_id(&arg.token_id).ok_or(TxError::NonExistingTokenId)?;
+        let operation = Operation::TransferFrom {
+            token_id,
+            spender: Account {
+                owner: caller,
+                subaccount: arg.spender_subaccount,
+            },
+            from: arg.from,
+            to: arg.to,
+        };
+        self.execute(operation, arg.memo, arg.created_at_time, now)
+    }
+
+    pub fn approve_token(
+        &mut self,
+        caller: Principal,
+        arg: ApproveTokenArg,
+        now: TimeStamp,
+    ) -> Result<BlockIndex, TxError> {
+        let token_id = nat_to_token_id(&arg.token_id).ok_or(TxError::NonExistingTokenId)?;
+        let approval_info = arg.approval_info;
+        let operation = Operation::Approve {
+            token_id,
+            from: Account {
+                owner: caller,
+                subaccount: approval_info.from_subaccount,
+            },
+            spender: approval_info.spender,
+            expires_at: approval_info.expires_at,
+        };
+        self.execute(
+            operation,
+            approval_info.memo,
+            Some(approval_info.created_at_time),
+            now,
+        )
+    }
+
+    pub fn approve_collection(
+        &mut self,
+        caller: Principal,
+        arg: ApproveCollectionArg,
+        now: TimeStamp,
+    ) -> Result<BlockIndex, TxError> {
+        let approval_info = arg.approval_info;
+        let operation = Operation::ApproveCollection {
+            from: Account {
+                owner: caller,
+                subaccount: approval_info.from_subaccount,
+            },
+            spender: approval_info.spender,
+            expires_at: approval_info.expires_at,
+        };
+        self.execute(
+            operation,
+            approval_info.memo,
+            Some(approval_info.created_at_time),
+            now,
+        )
+    }
+
+    pub fn revoke_token_approval(
+        &mut self,
+        caller: Principal,
+        arg: RevokeTokenApprovalArg,
+        now: TimeStamp,
+    ) -> Result<BlockIndex, TxError> {
+        let token_id = nat_to_token_id(&arg.token_id).ok_or(TxError::NonExistingTokenId)?;
+        let operation = Operation::Revoke {
+            token_id,
+            from: Account {
+                owner: caller,
+                subaccount: arg.from_subaccount,
+            },
+            spender: arg.spender,
+        };
+        self.execute(operation, arg.memo, arg.created_at_time, now)
+    }
+
+    pub fn revoke_collection_approval(
+        &mut self,
+        caller: Principal,
+        arg: RevokeCollectionApprovalArg,
+        now: TimeStamp,
+    ) -> Result<BlockIndex, TxError> {
+        let operation = Operation::RevokeCollection {
+            from: Account {
+                owner: caller,
+                subaccount: arg.from_subaccount,
+            },
+            spender: arg.spender,
+        };
+        self.execute(operation, arg.memo, arg.created_at_time, now)
+    }
+
+    /// Mints a new token. Only the owner of the minting account can mint tokens.
+    pub fn mint(
+        &mut self,
+        caller: Principal,
+        arg: MintArg,
+        now: TimeStamp,
+    ) -> Result<BlockIndex, TxError> {
+        if caller != self.minting_account.owner {
+            return Err(TxError::Unauthorized);
+        }
+        let token_id = nat_to_token_id(&arg.token_id).ok_or(TxError::TokenIdTooLarge)?;
+        let metadata: Map = arg
+            .metadata
+            .into_iter()
+            .map(|(key, value)| (key, Value::from(value)))
+            .collect();
+        let operation = Operation::Mint {
+            token_id,
+            to: arg.to,
+            metadata,
+        };
+        self.execute(operation, arg.memo, arg.created_at_time, now)
+    }
+
+    pub fn burn(
+        &mut self,
+        caller: Principal,
+        arg: BurnArg,
+        now: TimeStamp,
+    ) -> Result<BlockIndex, TxError> {
+        let token_id = nat_to_token_id(&arg.token_id).ok_or(TxError::NonExistingTokenId)?;
+        let operation = Operation::Burn {
+            token_id,
+            from: Account {
+                owner: caller,
+                subaccount: arg.from_subaccount,
+            },
+        };
+        self.execute(operation, arg.memo, arg.created_at_time, now)
+    }
+
+    /// Validates the transaction, applies it to the ledger state and records it in a new block.
+    fn execute(
+        &mut self,
+        operation: Operation,
+        memo: Option<Memo>,
+        created_at_time: Option<u64>,
+        now: TimeStamp,
+    ) -> Result<BlockIndex, TxError> {
+        self.purge_old_transactions(now);
+
+        let transaction = Transaction {
+            operation,
+            memo,
+            created_at_time,
+        };
+
+        let transaction_hash = match created_at_time {
+            None => None,
+            Some(created_at_time) => {
+                // The caller requested deduplication.
+                let created_at_time = TimeStamp::from_nanos_since_unix_epoch(created_at_time);
+                if created_at_time + TRANSACTION_WINDOW < now {
+                    return Err(TxError::TooOld);
+                }
+                if created_at_time > now + PERMITTED_DRIFT {
+                    return Err(TxError::CreatedInFuture {
+                        ledger_time: now.as_nanos_since_unix_epoch(),
+                    });
+                }
+                let transaction_hash = transaction.hash();
+                if let Some(duplicate_of) = self.transactions_by_hash.get(&transaction_hash) {
+                    return Err(TxError::Duplicate {
+                        duplicate_of: *duplicate_of,
+                    });
+                }
+                Some(transaction_hash)
+            }
+        };
+
+        self.apply(&transaction, now)?;
+
+        let block = Block::from_transaction(self.blockchain.last_hash, transaction, now, (), None);
+        let block_index = self
+            .blockchain
+            .add_block(block)
+            .expect("failed to add block");
+
+        if let Some(transaction_hash) = transaction_hash {
+            self.transactions_by_hash
+                .insert(transaction_hash, block_index);
+            self.transactions_by_height.push_back(RecentTransaction {
+                block_timestamp: now,
+                transaction_hash,
+            });
+        }
+        Ok(block_index)
+    }
+
+    /// Applies the transaction to the ledger state. The state is left unchanged if the
+    /// transaction is invalid.
+    fn apply(&mut self, transaction: &Transaction, now: TimeStamp) -> Result<(), TxError> {
+        let new_approval = |from: &Account, expires_at: &Option<u64>| StoredApproval {
+            from_subaccount: from.subaccount,
+            expires_at: *expires_at,
+            memo: transaction.memo.clone(),
+            created_at_time: transaction.created_at_time,
+        };
+        match &transaction.operation {
+            Operation::Mint {
+                token_id,
+                to,
+                metadata,
+            } => {
+                if TOKENS_MEMORY.with_borrow(|tokens| tokens.contains_key(token_id)) {
+                    return Err(TxError::TokenIdAlreadyExists);
+                }
+                if self
+                    .supply_cap
+                    .is_some_and(|supply_cap| self.total_supply() >= supply_cap)
+                {
+                    return Err(TxError::SupplyCapReached);
+                }
+                let token = StoredToken {
+                    owner: *to,
+                    metadata: encode_metadata(metadata.clone()),
+                };
+                TOKENS_MEMORY.with_borrow_mut(|tokens| tokens.insert(*token_id, token));
+                TOKENS_BY_OWNER_MEMORY.with_borrow_mut(|tokens_by_owner| {
+                    tokens_by_owner.insert(
+                        OwnerToken {
+                            owner: *to,
+                            token_id: *token_id,
+                        },
+                        (),
+                    )
+                });
+            }
+            Operation::Burn { token_id, from } => {
+                self.check_owner(*token_id, from)?;
+                TOKENS_MEMORY.with_borrow_mut(|tokens| tokens.remove(token_id));
+                TOKENS_BY_OWNER_MEMORY.with_borrow_mut(|tokens_by_owner| {
+                    tokens_by_owner.remove(&OwnerToken {
+                        owner: *from,
+                        token_id: *token_id,
+                    })
+                });
+                TOKEN_APPROVALS_MEMORY
+                    .with_borrow_mut(|approvals| clear_approvals(approvals, *token_id));
+            }
+            Operation::Transfer { token_id, from, to } => {
+                self.check_owner(*token_id, from)?;
+                if to == from {
+                    return Err(TxError::InvalidRecipient);
+                }
+                self.move_token(*token_id, from, to);
+            }
+            Operation::TransferFrom {
+                token_id,
+                spender,
+                from,
+                to,
+            } => {
+                self.check_owner(*token_id, from)?;
+                if !self.has_active_approval(spender, from, *token_id, now) {
+                    return Err(TxError::Unauthorized);
+                }
+                if to == from {
+                    return Err(TxError::InvalidRecipient);
+                }
+                self.move_token(*token_id, from, to);
+            }
+            Operation::Approve {
+                token_id,
+                from,
+                spender,
+                expires_at,
+            } => {
+                self.check_owner(*token_id, from)?;
+                if spender == from {
+                    return Err(TxError::InvalidSpender);
+                }
+                let approval = new_approval(from, expires_at);
+                TOKEN_APPROVALS_MEMORY.with_borrow_mut(|approvals| {
+                    insert_approval(approvals, *token_id, *spender, approval, now)
+                })?;
+            }
+            Operation::ApproveCollection {
+                from,
+                spender,
+                expires_at,
+            } => {
+                if spender == from {
+                    return Err(TxError::InvalidSpender);
+                }
+                let approval = new_approval(from, expires_at);
+                COLLECTION_APPROVALS_MEMORY.with_borrow_mut(|approvals| {
+                    insert_approval(approvals, *from, *spender, approval, now)
+                })?;
+            }
+            Operation::Revoke {
+                token_id,
+                from,
+                spender,
+            } => {
+                self.check_owner(*token_id, from)?;
+                TOKEN_APPROVALS_MEMORY.with_borrow_mut(|approvals| {
+                    remove_approvals(approvals, *token_id, spender.as_ref())
+                })?;
+            }
+            Operation::RevokeCollection { from, spender } => {
+                COLLECTION_APPROVALS_MEMORY.with_borrow_mut(|approvals| {
+                    remove_approvals(approvals, *from, spender.as_ref())
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_owner(&self, token_id: TokenId, from: &Account) -> Result<(), TxError> {
+        match get_token(token_id) {
+            None => Err(TxError::NonExistingTokenId),
+            Some(token) if token.owner != *from => Err(TxError::Unauthorized),
+            Some(_) => Ok(()),
+        }
+    }
+
+    fn has_active_approval(
+        &self,
+        spender: &Account,
+        owner: &Account,
+        token_id: TokenId,
+        now: TimeStamp,
+    ) -> bool {
+        let token_approval = TOKEN_APPROVALS_MEMORY
+            .with_borrow(|approvals| approvals.get(&TokenSpender::new(token_id, *spender)));
+        let collection_approval = COLLECTION_APPROVALS_MEMORY
+            .with_borrow(|approvals| approvals.get(&OwnerSpender::new(*owner, *spender)));
+        token_approval
+            .into_iter()
+            .chain(collection_approval)
+            .any(|approval| approval.is_active(now))
+    }
+
+    fn move_token(&mut self, token_id: TokenId, from: &Account, to: &Account) {
+        let mut token = get_token(token_id).expect("bug: moving a non-existing token");
+        token.owner = *to;
+        TOKENS_MEMORY.with_borrow_mut(|tokens| tokens.insert(token_id, token));
+        TOKENS_BY_OWNER_MEMORY.with_borrow_mut(|tokens_by_owner| {
+            tokens_by_owner.remove(&OwnerToken {
+                owner: *from,
+                token_id,
+            });
+            tokens_by_owner.insert(
+                OwnerToken {
+                    owner: *to,
+                    token_id,
+                },
+                (),
+            );
+        });
+        TOKEN_APPROVALS_MEMORY.with_borrow_mut(|approvals| clear_approvals(approvals, token_id));
+    }
+
+    /// Removes at most [MAX_TRANSACTIONS_TO_PURGE] transactions that are too old to be
+    /// deduplicated.
+    fn purge_old_transactions(&mut self, now: TimeStamp) {
+        for _ in 0..MAX_TRANSACTIONS_TO_PURGE {
+            match self.transactions_by_height.front() {
+                Some(tx_info)
+                    if tx_info.block_timestamp + TRANSACTION_WINDOW + PERMITTED_DRIFT < now =>
+                {
+                    self.transactions_by_hash.remove(&tx_info.transaction_hash);
+                    self.transactions_by_height.pop_front();
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Returns the root hash of the certified ledger state.
+    /// The canister code must call set_certified_data with the value this function returns after
+    /// each successful modification of the ledger.
+    pub fn root_hash(&self) -> [u8; 32] {
+        self.construct_hash_tree().digest()
+    }
+
+    pub fn construct_hash_tree(&self) -> HashTree {
+        match self.blockchain.last_hash {
+            Some(last_block_hash) => {
+                let last_block_index = self.blockchain.chain_length().checked_sub(1).unwrap();
+                let mut last_block_index_encoded = Vec::with_capacity(MAX_U64_ENCODING_BYTES);
+                leb128::write::unsigned(&mut last_block_index_encoded, last_block_index)
+                    .expect("Failed to write LEB128");
+                fork(
+                    label(
+                        Label::from("last_block_hash"),
+                        leaf(last_block_hash.as_slice().to_vec()),
+                    ),
+                    label(
+                        Label::from("last_block_index"),
+                        leaf(last_block_index_encoded),
+                    ),
+                )
+            }
+            None => empty(),
+        }
+    }
+
+    pub fn icrc3_get_archives(&self, args: GetArchivesArgs) -> GetArchivesResult {
+        self.blockchain
+            .archive
+            .read()
+            .expect("Unable to access the archives")
+            .iter()
+            .flat_map(|archive| {
+                archive
+                    .index()
+                    .into_iter()
+                    .filter_map(|((start, end), canister_id)| {
+                        let canister_id = Principal::from(canister_id);
+                        if let Some(from) = args.from
+                            && canister_id <= from
+                        {
+                            return None;
+                        }
+                        Some(ICRC3ArchiveInfo {
+                            canister_id,
+                            start: Nat::from(start),
+                            end: Nat::from(end),
+                        })
+                    })
+            })
+            .collect()
+    }
+
+    pub fn icrc3_get_blocks(&self, args: Vec<GetBlocksRequest>) -> GetBlocksResult {
+        let mut blocks = vec![];
+        let mut archived_blocks_by_callback = BTreeMap::new();
+        for arg in args {
+            let (start, length) = arg
+                .as_start_and_length()
+                .unwrap_or_else(|msg| ic_cdk::api::trap(&msg));
+            let max_length = MAX_BLOCKS_PER_RESPONSE.saturating_sub(blocks.len() as u64);
+            if max_length == 0 {
+                break;
+            }
+            let length = max_length.min(length).min(usize::MAX as u64) as usize;
+            let locations = blockchain_block_locations(&self.blockchain, start, length);
+            let local_blocks = self.blockchain.get_blocks(locations.local_blocks.clone());
+            for (id, block) in (locations.local_blocks.start..).zip(local_blocks.iter()) {
+                blocks.push(BlockWithId {
+                    id: Nat::from(id),
+                    block: ICRC3Value::from(encoded_block_to_generic_block(block)),
+                });
+            }
+            for (canister_id, slice) in locations.archived_blocks {
+                let callback = QueryArchiveFn::<Vec<GetBlocksRequest>, GetBlocksResult>::new(
+                    Principal::from(canister_id),
+                    "icrc3_get_blocks",
+                );
+                archived_blocks_by_callback
+                    .entry(callback)
+                    .or_insert(vec![])
+                    .push(GetBlocksRequest {
+                        start: Nat::from(slice.start),
+                        length: Nat::from(range_utils::range_len(&slice)),
+                    });
+            }
+        }
+        GetBlocksResult {
+            log_length: Nat::from(self.blockchain.chain_length()),
+            blocks,
+            archived_blocks: archived_blocks_by_callback
+                .into_iter()
+                .map(|(callback, args)| ArchivedBlocks { args, callback })
+                .collect(),
+        }
+    }
+}
+
+pub fn icrc37_metadata() -> Vec<(String, ICRC3Value)> {
+    vec![
+        (
+            "icrc37:max_approvals_per_token_or_collection".to_string(),
+            ICRC3Value::Nat(Nat::from(MAX_APPROVALS_PER_TOKEN_OR_COLLECTION)),
+        ),
+        (
+            "icrc37:max_revoke_approvals".to_string(),
+            ICRC3Value::Nat(Nat::from(MAX_REVOKE_APPROVALS)),
+        ),
+    ]
+}
+
+/// Converts an ICRC-7 token id into a ledger token id, returning `None` if the id does not fit
+/// into 64 bits.
+pub fn nat_to_token_id(token_id: &Nat) -> Option<TokenId> {
+    token_id.0.to_u64()
+}
+
+/// Returns the number of elements to return for a query with the given `take` argument.
+pub fn effective_take(take: Option<&Nat>) -> usize {
+    take.map_or(DEFAULT_TAKE_VALUE, |take| {
+        take.0.to_u64().unwrap_or(MAX_TAKE_VALUE)
+    })
+    .min(MAX_TAKE_VALUE) as usize
+}
+
+fn get_token(token_id: TokenId) -> Option<StoredToken> {
+    TOKENS_MEMORY.with_borrow(|tokens| tokens.get(&token_id))
+}
+
+/// Returns the range of `TOKENS_BY_OWNER_MEMORY` keys of `owner` with token ids greater than
+/// `prev`.
+fn owner_range(owner: Account, prev: Option<TokenId>) -> (Bound<OwnerToken>, Bound<OwnerToken>) {
+    let start = match prev {
+        None => Bound::Included(OwnerToken {
+            owner,
+            token_id: TokenId::MIN,
+        }),
+        Some(prev) => Bound::Excluded(OwnerToken {
+            owner,
+            token_id: prev,
+        }),
+    };
+    let end = Bound::Included(OwnerToken {
+        owner,
+        token_id: TokenId::MAX,
+    });
+    (start, end)
+}
+
+/// The smallest account, which precedes all spenders in the order of approval keys.
+fn min_account() -> Account {
+    Account {
+        owner: Principal::from_slice(&[]),
+        subaccount: None,
+    }
+}
+
+/// Returns the approvals in `scope` ordered by spender, starting after the spender `prev`.
+fn approvals_of<K: ApprovalKey>(
+    approvals: &ApprovalsMap<K>,
+    scope: K::Scope,
+    prev: Option<Account>,
+) -> impl Iterator<Item = (Account, StoredApproval)> + '_ {
+    let start = match prev {
+        None => Bound::Included(K::new(scope, min_account())),
+        Some(prev) => Bound::Excluded(K::new(scope, prev)),
+    };
+    approvals
+        .range((start, Bound::Unbounded))
+        .take_while(move |(key, _)| key.scope() == scope)
+        .map(|(key, approval)| (key.spender(), approval))
+}
+
+/// Inserts or replaces the approval of `spender` in `scope`, dropping expired approvals to make
+/// room.
+fn insert_approval<K: ApprovalKey>(
+    approvals: &mut ApprovalsMap<K>,
+    scope: K::Scope,
+    spender: Account,
+    approval: StoredApproval,
+    now: TimeStamp,
+) -> Result<(), TxError> {
+    let (active, expired): (Vec<_>, Vec<_>) =
+        approvals_of(approvals, scope, None).partition(|(_, approval)| approval.is_active(now));
+    for (expired_spender, _) in expired {
+        approvals.remove(&K::new(scope, expired_spender));
+    }
+    if !active
+        .iter()
+        .any(|(active_spender, _)| *active_spender == spender)
+        && active.len() as u64 >= MAX_APPROVALS_PER_TOKEN_OR_COLLECTION
+    {
+        return Err(TxError::TooManyApprovals);
+    }
+    approvals.insert(K::new(scope, spender), approval);
+    Ok(())
+}
+
+/// Removes the approval of `spender` in `scope`, or all approvals in `scope` if `spender` is
+/// `None`.
+fn remove_approvals<K: ApprovalKey>(
+    approvals: &mut ApprovalsMap<K>,
+    scope: K::Scope,
+    spender: Option<&Account>,
+) -> Result<(), TxError> {
+    let removed = match spender {
+        Some(spender) => approvals.remove(&K::new(scope, *spender)).is_some(),
+        None => clear_approvals(approvals, scope) > 0,
+    };
+    if removed {
+        Ok(())
+    } else {
+        Err(TxError::ApprovalDoesNotExist)
+    }
+}
+
+/// Removes all approvals in `scope` and returns their number.
+fn clear_approvals<K: ApprovalKey>(approvals: &mut ApprovalsMap<K>, scope: K::Scope) -> usize {
+    let spenders: Vec<Account> = approvals_of(approvals, scope, None)
+        .map(|(spender, _)| spender)
+        .collect();
+    for spender in &spenders {
+        approvals.remove(&K::new(scope, *spender));
+    }
+    spenders.len()
+}
+
+fn encode_metadata(metadata: Map) -> ByteBuf {
+    let encoded = generic_block_to_encoded_block(Value::Map(metadata))
+        .expect("bug: failed to encode token metadata");
+    ByteBuf::from(encoded.into_vec())
+}
+
+fn decode_metadata(metadata: &ByteBuf) -> Map {
+    encoded_block_to_generic_block(&EncodedBlock::from_vec(metadata.to_vec()))
+        .as_map()
+        .expect("bug: token metadata is not a map")
+}
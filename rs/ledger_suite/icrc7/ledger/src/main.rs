@@ -0,0 +1,701 @@
+#![allow(deprecated)]
+use candid::{Nat, Principal};
+use ic_canister_log::{declare_log_buffer, export};
+use ic_cdk::{init, post_upgrade, pre_upgrade, query, update};
+use ic_http_types::{HttpRequest, HttpResponse, HttpResponseBuilder};
+use ic_icrc1::endpoints::StandardRecord;
+use ic_icrc7_ledger::{
+    BurnArg, BurnError, Icrc7ArchiveWasm, Ledger, LedgerArgument, MAX_MEMO_SIZE,
+    MAX_QUERY_BATCH_SIZE, MAX_REVOKE_APPROVALS, MAX_UPDATE_BATCH_SIZE, MintArg, MintError,
+    PERMITTED_DRIFT, StableBlockDataContainer, TRANSACTION_WINDOW, TxError, UPGRADES_MEMORY,
+    effective_take,
+};
+use ic_ledger_canister_core::blockchain::Blockchain;
+use ic_ledger_canister_core::ledger::{BlockchainAccess, archive_blocks};
+use ic_ledger_canister_core::runtime::{CdkRuntime, heap_memory_size_bytes};
+use ic_ledger_core::block::BlockIndex;
+use ic_ledger_core::timestamp::TimeStamp;
+use ic_stable_structures::reader::{BufferedReader, Reader};
+use ic_stable_structures::writer::{BufferedWriter, Writer};
+use icrc_ledger_types::icrc::generic_value::ICRC3Value;
+use icrc_ledger_types::icrc1::{account::Account, transfer::Memo};
+use icrc_ledger_types::icrc3::{
+    archive::{GetArchivesArgs, GetArchivesResult},
+    blocks::{GetBlocksRequest, GetBlocksResult, ICRC3DataCertificate, SupportedBlockType},
+};
+use icrc_ledger_types::icrc7::transfer::{TransferArg, TransferError, TransferResult};
+use icrc_ledger_types::icrc37::{
+    approve::{
+        ApproveCollectionArg, ApproveCollectionError, ApproveCollectionResult, ApproveTokenArg,
+        ApproveTokenError, ApproveTokenResult, CollectionApproval, IsApprovedArg, TokenApproval,
+    },
+    revoke::{
+        RevokeCollectionApprovalArg, RevokeCollectionApprovalError, RevokeCollectionApprovalResult,
+        RevokeTokenApprovalArg, RevokeTokenApprovalError, RevokeTokenApprovalResponse,
+    },
+    transfer_from::{TransferFromArg, TransferFromError, TransferFromResult},
+};
+use serde_bytes::ByteBuf;
+use std::cell::{Cell, RefCell};
+
+const MAX_MESSAGE_SIZE: u64 = 1024 * 1024;
+
+// We use 8MiB buffer
+const BUFFER_SIZE: usize = 8388608;
+
+/// The error code of the `GenericBatchError` returned for batches that are too large.
+const BATCH_TOO_LARGE_ERROR_CODE: u64 = 0;
+
+thread_local! {
+    static LEDGER: RefCell<Option<Ledger>> = const { RefCell::new(None) };
+    static ARCHIVING_FAILURES: Cell<u64> = Cell::default();
+}
+
+declare_log_buffer!(name = LOG, capacity = 1000);
+
+fn with_ledger<R>(f: impl FnOnce(&Ledger) -> R) -> R {
+    LEDGER.with(|cell| {
+        f(cell
+            .borrow()
+            .as_ref()
+            .expect("ledger state not initialized"))
+    })
+}
+
+fn with_ledger_mut<R>(f: impl FnOnce(&mut Ledger) -> R) -> R {
+    LEDGER.with(|cell| {
+        f(cell
+            .borrow_mut()
+            .as_mut()
+            .expect("ledger state not initialized"))
+    })
+}
+
+struct Access;
+impl BlockchainAccess for Access {
+    type Runtime = CdkRuntime;
+    type ArchiveWasm = Icrc7ArchiveWasm;
+    type BlockDataContainer = StableBlockDataContainer;
+
+    fn with_blockchain<R>(
+        f: impl FnOnce(&Blockchain<Self::Runtime, Self::ArchiveWasm, Self::BlockDataContainer>) -> R,
+    ) -> R {
+        with_ledger(|ledger| f(ledger.blockchain()))
+    }
+
+    fn with_blockchain_mut<R>(
+        f: impl FnOnce(&mut Blockchain<Self::Runtime, Self::ArchiveWasm, Self::BlockDataContainer>) -> R,
+    ) -> R {
+        with_ledger_mut(|ledger| f(ledger.blockchain_mut()))
+    }
+
+    fn increment_archiving_failure_metric() {
+        ARCHIVING_FAILURES.with(|cell| cell.set(cell.get() + 1));
+    }
+}
+
+#[init]
+fn init(args: LedgerArgument) {
+    match args {
+        LedgerArgument::Init(init_args) => {
+            LEDGER.with(|cell| *cell.borrow_mut() = Some(Ledger::from_init_args(init_args)));
+        }
+        LedgerArgument::Upgrade(_) => {
+            panic!(
+                "Cannot initialize the canister with an Upgrade argument. Please provide an Init argument."
+            );
+        }
+    }
+    ic_cdk::api::set_certified_data(&with_ledger(Ledger::root_hash));
+}
+
+#[pre_upgrade]
+fn pre_upgrade() {
+    UPGRADES_MEMORY.with_borrow_mut(|bs| {
+        with_ledger(|ledger| {
+            let writer = Writer::new(bs, 0);
+            let mut buffered_writer = BufferedWriter::new(BUFFER_SIZE, writer);
+            ciborium::ser::into_writer(ledger, &mut buffered_writer)
+                .expect("Failed to write the Ledger state in stable memory");
+        })
+    });
+}
+
+#[post_upgrade]
+fn post_upgrade(args: Option<LedgerArgument>) {
+    let mut ledger: Ledger = UPGRADES_MEMORY.with_borrow(|bs| {
+        let reader = Reader::new(bs, 0);
+        let mut buffered_reader = BufferedReader::new(BUFFER_SIZE, reader);
+        ciborium::de::from_reader(&mut buffered_reader)
+            .expect("Failed to read the Ledger state from stable memory")
+    });
+    match args {
+        Some(LedgerArgument::Init(_)) => panic!(
+            "Cannot upgrade the canister with an Init argument. Please provide an Upgrade argument."
+        ),
+        Some(LedgerArgument::Upgrade(Some(upgrade_args))) => ledger.upgrade(upgrade_args),
+        Some(LedgerArgument::Upgrade(None)) | None => {}
+    }
+    LEDGER.with(|cell| *cell.borrow_mut() = Some(ledger));
+    ic_cdk::api::set_certified_data(&with_ledger(Ledger::root_hash));
+}
+
+fn now() -> TimeStamp {
+    TimeStamp::from_nanos_since_unix_epoch(ic_cdk::api::time())
+}
+
+fn check_memo(memo: Option<&Memo>) {
+    if let Some(memo) = memo
+        && memo.0.len() as u64 > MAX_MEMO_SIZE
+    {
+        ic_cdk::trap(format!(
+            "the memo field size of {} bytes is above the allowed limit of {MAX_MEMO_SIZE} bytes",
+            memo.0.len()
+        ))
+    }
+}
+
+fn check_query_batch_size<T>(batch: &[T]) {
+    if batch.len() as u64 > MAX_QUERY_BATCH_SIZE {
+        ic_cdk::trap(format!(
+            "the batch contains more than the maximum of {MAX_QUERY_BATCH_SIZE} entries"
+        ))
+    }
+}
+
+/// Executes a batch of transactions on behalf of the caller.
+///
+/// Batches are not atomic: every entry succeeds or fails on its own. A batch with more than
+/// `max_batch_size` entries is rejected as a whole with a single `GenericBatchError`.
+async fn execute_batch<Arg, Error: From<TxError>>(
+    args: Vec<Arg>,
+    max_batch_size: u64,
+    memo: impl Fn(&Arg) -> Option<&Memo>,
+    execute: impl Fn(&mut Ledger, Principal, Arg, TimeStamp) -> Result<BlockIndex, TxError>,
+    batch_error: impl FnOnce(Nat, String) -> Error,
+) -> Vec<Option<Result<Nat, Error>>> {
+    if args.len() as u64 > max_batch_size {
+        return vec![Some(Err(batch_error(
+            Nat::from(BATCH_TOO_LARGE_ERROR_CODE),
+            format!("the batch contains more than the maximum of {max_batch_size} entries"),
+        )))];
+    }
+    for arg in &args {
+        check_memo(memo(arg));
+    }
+
+    let caller = ic_cdk::api::caller();
+    let now = now();
+    let results = with_ledger_mut(|ledger| {
+        args.into_iter()
+            .map(|arg| {
+                Some(
+                    execute(ledger, caller, arg, now)
+                        .map(Nat::from)
+                        .map_err(Error::from),
+                )
+            })
+            .collect()
+    });
+
+    // NB. we need to set the certified data before the first async call to make sure that the
+    // blockchain state agrees with the certificate while archiving is in progress.
+    ic_cdk::api::set_certified_data(&with_ledger(Ledger::root_hash));
+
+    archive_blocks::<Access>(&LOG, MAX_MESSAGE_SIZE).await;
+    results
+}
+
+#[update]
+async fn icrc7_transfer(args: Vec<TransferArg>) -> Vec<Option<TransferResult>> {
+    execute_batch(
+        args,
+        MAX_UPDATE_BATCH_SIZE,
+        |arg| arg.memo.as_ref(),
+        Ledger::transfer,
+        |error_code, message| TransferError::GenericBatchError {
+            error_code,
+            message,
+        },
+    )
+    .await
+}
+
+#[update]
+async fn icrc37_transfer_from(args: Vec<TransferFromArg>) -> Vec<Option<TransferFromResult>> {
+    execute_batch(
+        args,
+        MAX_UPDATE_BATCH_SIZE,
+        |arg| arg.memo.as_ref(),
+        Ledger::transfer_from,
+        |error_code, message| TransferFromError::GenericBatchError {
+            error_code,
+            message,
+        },
+    )
+    .await
+}
+
+#[update]
+async fn icrc37_approve_tokens(args: Vec<ApproveTokenArg>) -> Vec<Option<ApproveTokenResult>> {
+    execute_batch(
+        args,
+        MAX_UPDATE_BATCH_SIZE,
+        |arg| arg.approval_info.memo.as_ref(),
+        Ledger::approve_token,
+        |error_code, message| ApproveTokenError::GenericBatchError {
+            error_code,
+            message,
+        },
+    )
+    .await
+}
+
+#[update]
+async fn icrc37_approve_collection(
+    args: Vec<ApproveCollectionArg>,
+) -> Vec<Option<ApproveCollectionResult>> {
+    execute_batch(
+        args,
+        MAX_UPDATE_BATCH_SIZE,
+        |arg| arg.approval_info.memo.as_ref(),
+        Ledger::approve_collection,
+        |error_code, message| ApproveCollectionError::GenericBatchError {
+            error_code,
+            message,
+        },
+    )
+    .await
+}
+
+#[update]
+async fn icrc37_revoke_token_approvals(
+    args: Vec<RevokeTokenApprovalArg>,
+) -> Vec<Option<RevokeTokenApprovalResponse>> {
+    execute_batch(
+        args,
+        MAX_REVOKE_APPROVALS,
+        |arg| arg.memo.as_ref(),
+        Ledger::revoke_token_approval,
+        |error_code, message| RevokeTokenApprovalError::GenericBatchError {
+            error_code,
+            message,
+        },
+    )
+    .await
+}
+
+#[update]
+async fn icrc37_revoke_collection_approvals(
+    args: Vec<RevokeCollectionApprovalArg>,
+) -> Vec<Option<RevokeCollectionApprovalResult>> {
+    execute_batch(
+        args,
+        MAX_REVOKE_APPROVALS,
+        |arg| arg.memo.as_ref(),
+        Ledger::revoke_collection_approval,
+        |error_code, message| RevokeCollectionApprovalError::GenericBatchError {
+            error_code,
+            message,
+        },
+    )
+    .await
+}
+
+#[update]
+async fn mint(arg: MintArg) -> Result<Nat, MintError> {
+    check_memo(arg.memo.as_ref());
+    let block_index = with_ledger_mut(|ledger| ledger.mint(ic_cdk::api::caller(), arg, now()))?;
+    ic_cdk::api::set_certified_data(&with_ledger(Ledger::root_hash));
+    archive_blocks::<Access>(&LOG, MAX_MESSAGE_SIZE).await;
+    Ok(Nat::from(block_index))
+}
+
+#[update]
+async fn burn(arg: BurnArg) -> Result<Nat, BurnError> {
+    check_memo(arg.memo.as_ref());
+    let block_index = with_ledger_mut(|ledger| ledger.burn(ic_cdk::api::caller(), arg, now()))?;
+    ic_cdk::api::set_certified_data(&with_ledger(Ledger::root_hash));
+    archive_blocks::<Access>(&LOG, MAX_MESSAGE_SIZE).await;
+    Ok(Nat::from(block_index))
+}
+
+#[query]
+fn icrc7_collection_metadata() -> Vec<(String, ICRC3Value)> {
+    with_ledger(|ledger| ledger.collection_metadata())
+}
+
+#[query]
+fn icrc7_symbol() -> String {
+    with_ledger(|ledger| ledger.symbol().to_string())
+}
+
+#[query]
+fn icrc7_name() -> String {
+    with_ledger(|ledger| ledger.name().to_string())
+}
+
+#[query]
+fn icrc7_description() -> Option<String> {
+    with_ledger(|ledger| ledger.description().map(str::to_string))
+}
+
+#[query]
+fn icrc7_logo() -> Option<String> {
+    with_ledger(|ledger| ledger.logo().map(str::to_string))
+}
+
+#[query]
+fn icrc7_total_supply() -> Nat {
+    with_ledger(|ledger| Nat::from(ledger.total_supply()))
+}
+
+#[query]
+fn icrc7_supply_cap() -> Option<Nat> {
+    with_ledger(|ledger| ledger.supply_cap().map(Nat::from))
+}
+
+#[query]
+fn icrc7_max_query_batch_size() -> Option<Nat> {
+    Some(Nat::from(MAX_QUERY_BATCH_SIZE))
+}
+
+#[query]
+fn icrc7_max_update_batch_size() -> Option<Nat> {
+    Some(Nat::from(MAX_UPDATE_BATCH_SIZE))
+}
+
+#[query]
+fn icrc7_default_take_value() -> Option<Nat> {
+    Some(Nat::from(ic_icrc7_ledger::DEFAULT_TAKE_VALUE))
+}
+
+#[query]
+fn icrc7_max_take_value() -> Option<Nat> {
+    Some(Nat::from(ic_icrc7_ledger::MAX_TAKE_VALUE))
+}
+
+#[query]
+fn icrc7_max_memo_size() -> Option<Nat> {
+    Some(Nat::from(MAX_MEMO_SIZE))
+}
+
+#[query]
+fn icrc7_atomic_batch_transfers() -> Option<bool> {
+    Some(false)
+}
+
+#[query]
+fn icrc7_tx_window() -> Option<Nat> {
+    Some(Nat::from(TRANSACTION_WINDOW.as_nanos() as u64))
+}
+
+#[query]
+fn icrc7_permitted_drift() -> Option<Nat> {
+    Some(Nat::from(PERMITTED_DRIFT.as_nanos() as u64))
+}
+
+#[query]
+fn icrc7_token_metadata(token_ids: Vec<Nat>) -> Vec<Option<Vec<(String, ICRC3Value)>>> {
+    check_query_batch_size(&token_ids);
+    with_ledger(|ledger| {
+        token_ids
+            .iter()
+            .map(|token_id| ledger.token_metadata(token_id))
+            .collect()
+    })
+}
+
+#[query]
+fn icrc7_owner_of(token_ids: Vec<Nat>) -> Vec<Option<Account>> {
+    check_query_batch_size(&token_ids);
+    with_ledger(|ledger| {
+        token_ids
+            .iter()
+            .map(|token_id| ledger.owner_of(token_id))
+            .collect()
+    })
+}
+
+#[query]
+fn icrc7_balance_of(accounts: Vec<Account>) -> Vec<Nat> {
+    check_query_batch_size(&accounts);
+    with_ledger(|ledger| {
+        accounts
+            .iter()
+            .map(|account| Nat::from(ledger.balance_of(account)))
+            .collect()
+    })
+}
+
+#[query]
+fn icrc7_tokens(prev: Option<Nat>, take: Option<Nat>) -> Vec<Nat> {
+    with_ledger(|ledger| {
+        ledger
+            .tokens(prev.as_ref(), effective_take(take.as_ref()))
+            .into_iter()
+            .map(Nat::from)
+            .collect()
+    })
+}
+
+#[query]
+fn icrc7_tokens_of(account: Account, prev: Option<Nat>, take: Option<Nat>) -> Vec<Nat> {
+    with_ledger(|ledger| {
+        ledger
+            .tokens_of(account, prev.as_ref(), effective_take(take.as_ref()))
+            .into_iter()
+            .map(Nat::from)
+            .collect()
+    })
+}
+
+#[query]
+fn icrc37_metadata() -> Vec<(String, ICRC3Value)> {
+    ic_icrc7_ledger::icrc37_metadata()
+}
+
+#[query]
+fn icrc37_max_approvals_per_token_or_collection() -> Option<Nat> {
+    Some(Nat::from(
+        ic_icrc7_ledger::MAX_APPROVALS_PER_TOKEN_OR_COLLECTION,
+    ))
+}
+
+#[query]
+fn icrc37_max_revoke_approvals() -> Option<Nat> {
+    Some(Nat::from(MAX_REVOKE_APPROVALS))
+}
+
+#[query]
+fn icrc37_is_approved(args: Vec<IsApprovedArg>) -> Vec<bool> {
+    check_query_batch_size(&args);
+    let now = now();
+    with_ledger(|ledger| {
+        args.iter()
+            .map(|arg| ledger.is_approved(arg, now))
+            .collect()
+    })
+}
+
+#[query]
+fn icrc37_get_token_approvals(
+    token_id: Nat,
+    prev: Option<TokenApproval>,
+    take: Option<Nat>,
+) -> Vec<TokenApproval> {
+    with_ledger(|ledger| {
+        ledger.token_approvals(
+            &token_id,
+            prev.as_ref(),
+            effective_take(take.as_ref()),
+            now(),
+        )
+    })
+}
+
+#[query]
+fn icrc37_get_collection_approvals(
+    owner: Account,
+    prev: Option<CollectionApproval>,
+    take: Option<Nat>,
+) -> Vec<CollectionApproval> {
+    with_ledger(|ledger| {
+        ledger.collection_approvals(&owner, prev.as_ref(), effective_take(take.as_ref()), now())
+    })
+}
+
+#[query]
+fn icrc3_get_archives(args: GetArchivesArgs) -> GetArchivesResult {
+    with_ledger(|ledger| ledger.icrc3_get_archives(args))
+}
+
+#[query]
+fn icrc3_get_tip_certificate() -> Option<ICRC3DataCertificate> {
+    let certificate = ByteBuf::from(ic_cdk::api::data_certificate()?);
+    let hash_tree = with_ledger(|ledger| ledger.construct_hash_tree());
+    let mut tree_buf = vec![];
+    ciborium::ser::into_writer(&hash_tree, &mut tree_buf).unwrap();
+    Some(ICRC3DataCertificate {
+        certificate,
+        hash_tree: ByteBuf::from(tree_buf),
+    })
+}
+
+#[query]
+fn icrc3_supported_block_types() -> Vec<SupportedBlockType> {
+    const ICRC7_URL: &str = "https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-7/ICRC-7.md";
+    const ICRC37_URL: &str = "https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-37/ICRC-37.md";
+
+    [
+        (ic_icrc7::BTYPE_MINT, ICRC7_URL),
+        (ic_icrc7::BTYPE_BURN, ICRC7_URL),
+        (ic_icrc7::BTYPE_TRANSFER, ICRC7_URL),
+        (ic_icrc7::BTYPE_APPROVE, ICRC37_URL),
+        (ic_icrc7::BTYPE_APPROVE_COLLECTION, ICRC37_URL),
+        (ic_icrc7::BTYPE_REVOKE, ICRC37_URL),
+        (ic_icrc7::BTYPE_REVOKE_COLLECTION, ICRC37_URL),
+        (ic_icrc7::BTYPE_TRANSFER_FROM, ICRC37_URL),
+    ]
+    .into_iter()
+    .map(|(block_type, url)| SupportedBlockType {
+        block_type: block_type.to_string(),
+        url: url.to_string(),
+    })
+    .collect()
+}
+
+#[query]
+fn icrc3_get_blocks(args: Vec<GetBlocksRequest>) -> GetBlocksResult {
+    with_ledger(|ledger| ledger.icrc3_get_blocks(args))
+}
+
+#[query]
+fn icrc10_supported_standards() -> Vec<StandardRecord> {
+    [
+        (
+            "ICRC-3",
+            "https://github.com/dfinity/ICRC-1/tree/main/standards/ICRC-3",
+        ),
+        (
+            "ICRC-7",
+            "https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-7/ICRC-7.md",
+        ),
+        (
+            "ICRC-10",
+            "https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-10/ICRC-10.md",
+        ),
+        (
+            "ICRC-37",
+            "https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-37/ICRC-37.md",
+        ),
+    ]
+    .into_iter()
+    .map(|(name, url)| StandardRecord {
+        name: name.to_string(),
+        url: url.to_string(),
+    })
+    .collect()
+}
+
+fn encode_metrics(w: &mut ic_metrics_encoder::MetricsEncoder<Vec<u8>>) -> std::io::Result<()> {
+    w.encode_gauge(
+        "ledger_stable_memory_pages",
+        ic_cdk::api::stable::stable_size() as f64,
+        "Size of the stable memory allocated by this canister measured in 64K Wasm pages.",
+    )?;
+    w.encode_gauge(
+        "heap_memory_bytes",
+        heap_memory_size_bytes() as f64,
+        "Size of the heap memory allocated by this canister measured in bytes.",
+    )?;
+    w.encode_gauge(
+        "ledger_cycle_balance",
+        ic_cdk::api::canister_balance128() as f64,
+        "Cycle balance on the ledger canister.",
+    )?;
+    w.encode_counter(
+        "ledger_archiving_failures",
+        ARCHIVING_FAILURES.get() as f64,
+        "Number of failed attempts to archive blocks.",
+    )?;
+    with_ledger(|ledger| {
+        w.encode_gauge(
+            "ledger_total_supply",
+            ledger.total_supply() as f64,
+            "Number of tokens in existence.",
+        )?;
+        w.encode_gauge(
+            "ledger_transactions",
+            ledger.blockchain().chain_length() as f64,
+            "Total number of transactions stored in the main memory, plus total number of transactions sent to the archive.",
+        )?;
+        w.encode_gauge(
+            "ledger_archived_blocks",
+            ledger.blockchain().num_archived_blocks() as f64,
+            "Total number of blocks sent to the archive.",
+        )?;
+        w.encode_gauge(
+            "ledger_num_archives",
+            ledger
+                .blockchain()
+                .archive
+                .read()
+                .unwrap()
+                .as_ref()
+                .map(|archive| archive.nodes().len())
+                .unwrap_or(0) as f64,
+            "Total number of archives.",
+        )?;
+        Ok(())
+    })
+}
+
+#[query(
+    hidden = true,
+    decode_with = "candid::decode_one_with_decoding_quota::<100000,_>"
+)]
+fn http_request(req: HttpRequest) -> HttpResponse {
+    if req.path() == "/metrics" {
+        let mut writer =
+            ic_metrics_encoder::MetricsEncoder::new(vec![], ic_cdk::api::time() as i64 / 1_000_000);
+
+        match encode_metrics(&mut writer) {
+            Ok(()) => HttpResponseBuilder::ok()
+                .header("Content-Type", "text/plain; version=0.0.4")
+                .header("Cache-Control", "no-store")
+                .with_body_and_content_length(writer.into_inner())
+                .build(),
+            Err(err) => {
+                HttpResponseBuilder::server_error(format!("Failed to encode metrics: {err}"))
+                    .build()
+            }
+        }
+    } else if req.path() == "/logs" {
+        use std::io::Write;
+        let mut buf = vec![];
+        for entry in export(&LOG) {
+            writeln!(
+                &mut buf,
+                "{} {}:{} {}",
+                entry.timestamp, entry.file, entry.line, entry.message
+            )
+            .unwrap();
+        }
+        HttpResponseBuilder::ok()
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .with_body_and_content_length(buf)
+            .build()
+    } else {
+        HttpResponseBuilder::not_found().build()
+    }
+}
+
+candid::export_service!();
+
+#[query]
+fn __get_candid_interface_tmp_hack() -> String {
+    __export_service()
+}
+
+fn main() {}
+
+#[test]
+fn check_candid_interface() {
+    use candid_parser::utils::{CandidSource, service_equal};
+
+    let new_interface = __export_service();
+    let manifest_dir = std::path::PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let old_interface = manifest_dir.join("ledger.did");
+    service_equal(
+        CandidSource::Text(&new_interface),
+        CandidSource::File(old_interface.as_path()),
+    )
+    .unwrap_or_else(|e| {
+        panic!(
+            "the ledger interface is not compatible with {}: {:?}",
+            old_interface.display(),
+            e
+        )
+    });
+}
@@ -0,0 +1,448 @@
+use super::*;
+use ic_base_types::PrincipalId;
+use ic_icrc7::BTYPE_TRANSFER_FROM;
+
+const MINTER: Principal = Principal::from_slice(&[1]);
+const ALICE: Principal = Principal::from_slice(&[2]);
+const BOB: Principal = Principal::from_slice(&[3]);
+const CHARLIE: Principal = Principal::from_slice(&[4]);
+
+fn ts(secs: u64) -> TimeStamp {
+    TimeStamp::from_nanos_since_unix_epoch(secs * 1_000_000_000)
+}
+
+fn test_ledger(supply_cap: Option<u64>) -> Ledger {
+    Ledger::from_init_args(InitArgs {
+        minting_account: Account::from(MINTER),
+        symbol: "NFT".to_string(),
+        name: "Test Collection".to_string(),
+        description: None,
+        logo: None,
+        supply_cap: supply_cap.map(Nat::from),
+        archive_options: ArchiveOptions {
+            trigger_threshold: 1_000,
+            num_blocks_to_archive: 100,
+            node_max_memory_size_bytes: None,
+            max_message_size_bytes: None,
+            controller_id: PrincipalId::new_user_test_id(100),
+            more_controller_ids: None,
+            cycles_for_archive_creation: None,
+            max_transactions_per_response: None,
+        },
+    })
+}
+
+fn mint(ledger: &mut Ledger, token_id: u64, to: Principal) -> Result<BlockIndex, TxError> {
+    ledger.mint(
+        MINTER,
+        MintArg {
+            token_id: Nat::from(token_id),
+            to: Account::from(to),
+            metadata: vec![("name".to_string(), ICRC3Value::Text(format!("#{token_id}")))],
+            memo: None,
+            created_at_time: None,
+        },
+        ts(1),
+    )
+}
+
+fn transfer_arg(token_id: u64, to: Principal) -> TransferArg {
+    TransferArg {
+        from_subaccount: None,
+        to: Account::from(to),
+        token_id: Nat::from(token_id),
+        memo: None,
+        created_at_time: None,
+    }
+}
+
+fn approval_info(spender: Principal, expires_at: Option<u64>) -> ApprovalInfo {
+    ApprovalInfo {
+        spender: Account::from(spender),
+        from_subaccount: None,
+        expires_at,
+        memo: None,
+        created_at_time: ts(1).as_nanos_since_unix_epoch(),
+    }
+}
+
+fn transfer_from_arg(token_id: u64, from: Principal, to: Principal) -> TransferFromArg {
+    TransferFromArg {
+        spender_subaccount: None,
+        from: Account::from(from),
+        to: Account::from(to),
+        token_id: Nat::from(token_id),
+        memo: None,
+        created_at_time: None,
+    }
+}
+
+#[test]
+fn should_mint_transfer_and_burn_tokens() {
+    let mut ledger = test_ledger(None);
+
+    assert_eq!(mint(&mut ledger, 1, ALICE), Ok(0));
+    assert_eq!(mint(&mut ledger, 2, ALICE), Ok(1));
+    assert_eq!(ledger.total_supply(), 2);
+    assert_eq!(ledger.balance_of(&Account::from(ALICE)), 2);
+    assert_eq!(
+        ledger.token_metadata(&Nat::from(1_u64)),
+        Some(vec![(
+            "name".to_string(),
+            ICRC3Value::Text("#1".to_string())
+        )])
+    );
+
+    assert_eq!(ledger.transfer(ALICE, transfer_arg(1, BOB), ts(2)), Ok(2));
+    assert_eq!(ledger.owner_of(&Nat::from(1_u64)), Some(Account::from(BOB)));
+    assert_eq!(ledger.tokens_of(Account::from(ALICE), None, 10), vec![2]);
+    assert_eq!(ledger.tokens_of(Account::from(BOB), None, 10), vec![1]);
+
+    let burn_arg = BurnArg {
+        from_subaccount: None,
+        token_id: Nat::from(1_u64),
+        memo: None,
+        created_at_time: None,
+    };
+    assert_eq!(
+        ledger.burn(ALICE, burn_arg.clone(), ts(3)),
+        Err(TxError::Unauthorized)
+    );
+    assert_eq!(ledger.burn(BOB, burn_arg, ts(3)), Ok(3));
+    assert_eq!(ledger.owner_of(&Nat::from(1_u64)), None);
+    assert_eq!(ledger.total_supply(), 1);
+    assert_eq!(ledger.blockchain().chain_length(), 4);
+}
+
+#[test]
+fn should_reject_invalid_mints() {
+    let mut ledger = test_ledger(Some(1));
+
+    let arg = MintArg {
+        token_id: Nat::from(1_u64),
+        to: Account::from(ALICE),
+        metadata: vec![],
+        memo: None,
+        created_at_time: None,
+    };
+    assert_eq!(
+        ledger.mint(ALICE, arg.clone(), ts(1)),
+        Err(TxError::Unauthorized)
+    );
+    assert_eq!(ledger.mint(MINTER, arg.clone(), ts(1)), Ok(0));
+    assert_eq!(
+        ledger.mint(MINTER, arg, ts(1)),
+        Err(TxError::TokenIdAlreadyExists)
+    );
+    assert_eq!(mint(&mut ledger, 2, ALICE), Err(TxError::SupplyCapReached));
+    assert_eq!(ledger.blockchain().chain_length(), 1);
+}
+
+#[test]
+fn should_reject_invalid_transfers() {
+    let mut ledger = test_ledger(None);
+    mint(&mut ledger, 1, ALICE).unwrap();
+
+    assert_eq!(
+        ledger.transfer(ALICE, transfer_arg(2, BOB), ts(2)),
+        Err(TxError::NonExistingTokenId)
+    );
+    assert_eq!(
+        ledger.transfer(BOB, transfer_arg(1, CHARLIE), ts(2)),
+        Err(TxError::Unauthorized)
+    );
+    assert_eq!(
+        ledger.transfer(ALICE, transfer_arg(1, ALICE), ts(2)),
+        Err(TxError::InvalidRecipient)
+    );
+    assert_eq!(ledger.blockchain().chain_length(), 1);
+}
+
+#[test]
+fn should_deduplicate_transactions() {
+    let mut ledger = test_ledger(None);
+    mint(&mut ledger, 1, ALICE).unwrap();
+    mint(&mut ledger, 2, ALICE).unwrap();
+
+    let now = ts(1_000_000);
+    let arg = TransferArg {
+        created_at_time: Some(now.as_nanos_since_unix_epoch()),
+        ..transfer_arg(1, BOB)
+    };
+    assert_eq!(ledger.transfer(ALICE, arg.clone(), now), Ok(2));
+    // The token now belongs to BOB, but the duplicate check takes precedence.
+    assert_eq!(
+        ledger.transfer(ALICE, arg, now),
+        Err(TxError::Duplicate { duplicate_of: 2 })
+    );
+
+    let too_old = TransferArg {
+        created_at_time: Some(
+            (now.as_nanos_since_unix_epoch()) - 2 * TRANSACTION_WINDOW.as_nanos() as u64,
+        ),
+        ..transfer_arg(2, BOB)
+    };
+    assert_eq!(ledger.transfer(ALICE, too_old, now), Err(TxError::TooOld));
+
+    let in_future = TransferArg {
+        created_at_time: Some(
+            now.as_nanos_since_unix_epoch() + 2 * PERMITTED_DRIFT.as_nanos() as u64,
+        ),
+        ..transfer_arg(2, BOB)
+    };
+    assert_eq!(
+        ledger.transfer(ALICE, in_future, now),
+        Err(TxError::CreatedInFuture {
+            ledger_time: now.as_nanos_since_unix_epoch()
+        })
+    );
+}
+
+#[test]
+fn should_transfer_from_with_token_approval() {
+    let mut ledger = test_ledger(None);
+    mint(&mut ledger, 1, ALICE).unwrap();
+
+    assert_eq!(
+        ledger.transfer_from(BOB, transfer_from_arg(1, ALICE, CHARLIE), ts(2)),
+        Err(TxError::Unauthorized)
+    );
+    assert_eq!(
+        ledger.approve_token(
+            ALICE,
+            ApproveTokenArg {
+                token_id: Nat::from(1_u64),
+                approval_info: approval_info(ALICE, None),
+            },
+            ts(2)
+        ),
+        Err(TxError::InvalidSpender)
+    );
+    ledger
+        .approve_token(
+            ALICE,
+            ApproveTokenArg {
+                token_id: Nat::from(1_u64),
+                approval_info: approval_info(BOB, None),
+            },
+            ts(2),
+        )
+        .unwrap();
+    let is_approved = IsApprovedArg {
+        spender: Account::from(BOB),
+        from_subaccount: None,
+        token_id: Nat::from(1_u64),
+    };
+    assert!(ledger.is_approved(&is_approved, ts(2)));
+    assert_eq!(
+        ledger
+            .token_approvals(&Nat::from(1_u64), None, 10, ts(2))
+            .len(),
+        1
+    );
+
+    let block_index = ledger
+        .transfer_from(BOB, transfer_from_arg(1, ALICE, CHARLIE), ts(3))
+        .unwrap();
+    assert_eq!(
+        ledger.owner_of(&Nat::from(1_u64)),
+        Some(Account::from(CHARLIE))
+    );
+    // Token approvals do not survive a change of ownership.
+    assert!(!ledger.is_approved(&is_approved, ts(3)));
+    assert!(
+        ledger
+            .token_approvals(&Nat::from(1_u64), None, 10, ts(3))
+            .is_empty()
+    );
+
+    let block = Block::decode(
+        ledger
+            .blockchain()
+            .get_blocks(block_index..block_index + 1)
+            .remove(0),
+    )
+    .unwrap();
+    assert_eq!(block.transaction.operation.btype(), BTYPE_TRANSFER_FROM);
+}
+
+#[test]
+fn should_transfer_from_with_collection_approval_until_expiration() {
+    let mut ledger = test_ledger(None);
+    mint(&mut ledger, 1, ALICE).unwrap();
+    mint(&mut ledger, 2, ALICE).unwrap();
+
+    ledger
+        .approve_collection(
+            ALICE,
+            ApproveCollectionArg {
+                approval_info: approval_info(BOB, Some(ts(10).as_nanos_since_unix_epoch())),
+            },
+            ts(2),
+        )
+        .unwrap();
+    assert_eq!(
+        ledger
+            .collection_approvals(&Account::from(ALICE), None, 10, ts(2))
+            .len(),
+        1
+    );
+
+    ledger
+        .transfer_from(BOB, transfer_from_arg(1, ALICE, CHARLIE), ts(3))
+        .unwrap();
+    assert_eq!(
+        ledger.transfer_from(BOB, transfer_from_arg(2, ALICE, CHARLIE), ts(10)),
+        Err(TxError::Unauthorized)
+    );
+    assert!(
+        ledger
+            .collection_approvals(&Account::from(ALICE), None, 10, ts(10))
+            .is_empty()
+    );
+}
+
+#[test]
+fn should_revoke_approvals() {
+    let mut ledger = test_ledger(None);
+    mint(&mut ledger, 1, ALICE).unwrap();
+
+    for spender in [BOB, CHARLIE] {
+        ledger
+            .approve_token(
+                ALICE,
+                ApproveTokenArg {
+                    token_id: Nat::from(1_u64),
+                    approval_info: approval_info(spender, None),
+                },
+                ts(2),
+            )
+            .unwrap();
+        ledger
+            .approve_collection(
+                ALICE,
+                ApproveCollectionArg {
+                    approval_info: approval_info(spender, None),
+                },
+                ts(2),
+            )
+            .unwrap();
+    }
+
+    let revoke_token = |spender: Option<Principal>| RevokeTokenApprovalArg {
+        spender: spender.map(Account::from),
+        from_subaccount: None,
+        token_id: Nat::from(1_u64),
+        memo: None,
+        created_at_time: None,
+    };
+    assert_eq!(
+        ledger.revoke_token_approval(BOB, revoke_token(Some(BOB)), ts(3)),
+        Err(TxError::Unauthorized)
+    );
+    ledger
+        .revoke_token_approval(ALICE, revoke_token(Some(BOB)), ts(3))
+        .unwrap();
+    assert_eq!(
+        ledger.revoke_token_approval(ALICE, revoke_token(Some(BOB)), ts(3)),
+        Err(TxError::ApprovalDoesNotExist)
+    );
+    ledger
+        .revoke_token_approval(ALICE, revoke_token(None), ts(3))
+        .unwrap();
+    assert!(
+        ledger
+            .token_approvals(&Nat::from(1_u64), None, 10, ts(3))
+            .is_empty()
+    );
+
+    let revoke_collection = RevokeCollectionApprovalArg {
+        spender: None,
+        from_subaccount: None,
+        memo: None,
+        created_at_time: None,
+    };
+    ledger
+        .revoke_collection_approval(ALICE, revoke_collection.clone(), ts(3))
+        .unwrap();
+    assert_eq!(
+        ledger.revoke_collection_approval(ALICE, revoke_collection, ts(3)),
+        Err(TxError::ApprovalDoesNotExist)
+    );
+}
+
+#[test]
+fn should_limit_the_number_of_approvals() {
+    let mut ledger = test_ledger(None);
+
+    for i in 0..MAX_APPROVALS_PER_TOKEN_OR_COLLECTION {
+        let spender = Principal::from_slice(&(1_000 + i).to_be_bytes());
+        ledger
+            .approve_collection(
+                ALICE,
+                ApproveCollectionArg {
+                    approval_info: approval_info(spender, None),
+                },
+                ts(2),
+            )
+            .unwrap();
+    }
+    assert_eq!(
+        ledger.approve_collection(
+            ALICE,
+            ApproveCollectionArg {
+                approval_info: approval_info(BOB, None),
+            },
+            ts(2),
+        ),
+        Err(TxError::TooManyApprovals)
+    );
+}
+
+#[test]
+fn should_paginate_tokens() {
+    let mut ledger = test_ledger(None);
+    for token_id in 0..10 {
+        let owner = if token_id % 2 == 0 { ALICE } else { BOB };
+        mint(&mut ledger, token_id, owner).unwrap();
+    }
+
+    assert_eq!(ledger.tokens(None, 3), vec![0, 1, 2]);
+    assert_eq!(ledger.tokens(Some(&Nat::from(2_u64)), 3), vec![3, 4, 5]);
+    assert_eq!(
+        ledger.tokens_of(Account::from(ALICE), Some(&Nat::from(4_u64)), 10),
+        vec![6, 8]
+    );
+    assert!(
+        ledger
+            .tokens_of(Account::from(BOB), Some(&Nat::from(u128::MAX)), 10)
+            .is_empty()
+    );
+    assert_eq!(effective_take(None), DEFAULT_TAKE_VALUE as usize);
+    assert_eq!(
+        effective_take(Some(&Nat::from(u128::MAX))),
+        MAX_TAKE_VALUE as usize
+    );
+}
+
+#[test]
+fn should_certify_the_last_block() {
+    let mut ledger = test_ledger(None);
+    assert_eq!(ledger.construct_hash_tree(), empty());
+
+    mint(&mut ledger, 1, ALICE).unwrap();
+    let root_hash = ledger.root_hash();
+    mint(&mut ledger, 2, ALICE).unwrap();
+    assert_ne!(ledger.root_hash(), root_hash);
+
+    let result = ledger.icrc3_get_blocks(vec![GetBlocksRequest {
+        start: Nat::from(0_u64),
+        length: Nat::from(10_u64),
+    }]);
+    assert_eq!(result.log_length, Nat::from(2_u64));
+    assert_eq!(result.blocks.len(), 2);
+    assert_eq!(
+        HashOf::<EncodedBlock>::new(result.blocks[1].block.clone().hash()),
+        ledger.blockchain().last_hash.unwrap()
+    );
+}
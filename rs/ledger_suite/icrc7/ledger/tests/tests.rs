@@ -0,0 +1,304 @@
+use candid::{Decode, Encode, Nat, Principal};
+use ic_base_types::{CanisterId, PrincipalId};
+use ic_icrc7_ledger::{InitArgs, LedgerArgument, MintArg, MintError};
+use ic_ledger_canister_core::archive::ArchiveOptions;
+use ic_state_machine_tests::StateMachine;
+use icrc_ledger_types::icrc::generic_value::ICRC3Value;
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc3::archive::{GetArchivesArgs, GetArchivesResult};
+use icrc_ledger_types::icrc3::blocks::{GetBlocksRequest, GetBlocksResult};
+use icrc_ledger_types::icrc7::transfer::{TransferArg, TransferResult};
+use icrc_ledger_types::icrc37::approve::{
+    ApprovalInfo, ApproveTokenArg, ApproveTokenResult, IsApprovedArg,
+};
+
+const ARCHIVE_TRIGGER_THRESHOLD: usize = 10;
+const NUM_BLOCKS_TO_ARCHIVE: usize = 5;
+
+fn minter() -> Account {
+    Account::from(PrincipalId::new_user_test_id(1).0)
+}
+
+fn account(id: u64) -> Account {
+    Account::from(PrincipalId::new_user_test_id(id).0)
+}
+
+fn ledger_wasm() -> Vec<u8> {
+    ic_test_utilities_load_wasm::load_wasm(
+        std::env::var("CARGO_MANIFEST_DIR").unwrap(),
+        "ic-icrc7-ledger",
+        &[],
+    )
+}
+
+fn install_ledger(env: &StateMachine) -> CanisterId {
+    let args = LedgerArgument::Init(InitArgs {
+        minting_account: minter(),
+        symbol: "NFT".to_string(),
+        name: "Test NFT collection".to_string(),
+        description: None,
+        logo: None,
+        supply_cap: None,
+        archive_options: ArchiveOptions {
+            trigger_threshold: ARCHIVE_TRIGGER_THRESHOLD,
+            num_blocks_to_archive: NUM_BLOCKS_TO_ARCHIVE,
+            node_max_memory_size_bytes: None,
+            max_message_size_bytes: None,
+            controller_id: PrincipalId::new_user_test_id(100),
+            more_controller_ids: None,
+            cycles_for_archive_creation: Some(0),
+            max_transactions_per_response: None,
+        },
+    });
+    env.install_canister(ledger_wasm(), Encode!(&args).unwrap(), None)
+        .unwrap()
+}
+
+fn mint(env: &StateMachine, ledger_id: CanisterId, token_id: u64, to: Account) -> Nat {
+    let arg = MintArg {
+        token_id: Nat::from(token_id),
+        to,
+        metadata: vec![(
+            "icrc7:name".to_string(),
+            ICRC3Value::Text(format!("token #{token_id}")),
+        )],
+        memo: None,
+        created_at_time: None,
+    };
+    let res = env
+        .execute_ingress_as(
+            PrincipalId(minter().owner),
+            ledger_id,
+            "mint",
+            Encode!(&arg).unwrap(),
+        )
+        .expect("failed to mint")
+        .bytes();
+    Decode!(&res, Result<Nat, MintError>)
+        .unwrap()
+        .expect("failed to mint")
+}
+
+fn transfer(
+    env: &StateMachine,
+    ledger_id: CanisterId,
+    from: Account,
+    token_id: u64,
+    to: Account,
+) -> Vec<Option<TransferResult>> {
+    let args = vec![TransferArg {
+        from_subaccount: from.subaccount,
+        to,
+        token_id: Nat::from(token_id),
+        memo: None,
+        created_at_time: None,
+    }];
+    let res = env
+        .execute_ingress_as(
+            PrincipalId(from.owner),
+            ledger_id,
+            "icrc7_transfer",
+            Encode!(&args).unwrap(),
+        )
+        .expect("failed to transfer")
+        .bytes();
+    Decode!(&res, Vec<Option<TransferResult>>).unwrap()
+}
+
+fn approve_token(
+    env: &StateMachine,
+    ledger_id: CanisterId,
+    from: Account,
+    token_id: u64,
+    spender: Account,
+) -> Vec<Option<ApproveTokenResult>> {
+    let args = vec![ApproveTokenArg {
+        token_id: Nat::from(token_id),
+        approval_info: ApprovalInfo {
+            spender,
+            from_subaccount: from.subaccount,
+            expires_at: None,
+            memo: None,
+            created_at_time: env
+                .time()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos() as u64,
+        },
+    }];
+    let res = env
+        .execute_ingress_as(
+            PrincipalId(from.owner),
+            ledger_id,
+            "icrc37_approve_tokens",
+            Encode!(&args).unwrap(),
+        )
+        .expect("failed to approve")
+        .bytes();
+    Decode!(&res, Vec<Option<ApproveTokenResult>>).unwrap()
+}
+
+fn is_approved(env: &StateMachine, ledger_id: CanisterId, token_id: u64, spender: Account) -> bool {
+    let args = vec![IsApprovedArg {
+        spender,
+        from_subaccount: None,
+        token_id: Nat::from(token_id),
+    }];
+    let res = env
+        .query(ledger_id, "icrc37_is_approved", Encode!(&args).unwrap())
+        .expect("failed to query icrc37_is_approved")
+        .bytes();
+    Decode!(&res, Vec<bool>).unwrap()[0]
+}
+
+fn balance_of(env: &StateMachine, ledger_id: CanisterId, account: Account) -> Nat {
+    let res = env
+        .query(
+            ledger_id,
+            "icrc7_balance_of",
+            Encode!(&vec![account]).unwrap(),
+        )
+        .expect("failed to query icrc7_balance_of")
+        .bytes();
+    Decode!(&res, Vec<Nat>).unwrap().remove(0)
+}
+
+fn owner_of(
+    env: &StateMachine,
+    ledger_id: CanisterId,
+    token_ids: Vec<Nat>,
+) -> Vec<Option<Account>> {
+    let res = env
+        .query(ledger_id, "icrc7_owner_of", Encode!(&token_ids).unwrap())
+        .expect("failed to query icrc7_owner_of")
+        .bytes();
+    Decode!(&res, Vec<Option<Account>>).unwrap()
+}
+
+fn get_blocks(
+    env: &StateMachine,
+    canister_id: Principal,
+    start: u64,
+    length: u64,
+) -> GetBlocksResult {
+    let args = vec![GetBlocksRequest {
+        start: Nat::from(start),
+        length: Nat::from(length),
+    }];
+    let res = env
+        .query(
+            CanisterId::unchecked_from_principal(PrincipalId(canister_id)),
+            "icrc3_get_blocks",
+            Encode!(&args).unwrap(),
+        )
+        .expect("failed to query icrc3_get_blocks")
+        .bytes();
+    Decode!(&res, GetBlocksResult).unwrap()
+}
+
+#[test]
+fn should_mint_and_transfer_tokens() {
+    let env = StateMachine::new();
+    let ledger_id = install_ledger(&env);
+
+    assert_eq!(mint(&env, ledger_id, 1, account(2)), Nat::from(0u64));
+    assert_eq!(
+        transfer(&env, ledger_id, account(2), 1, account(3)),
+        vec![Some(Ok(Nat::from(1u64)))]
+    );
+    assert_eq!(
+        owner_of(&env, ledger_id, vec![Nat::from(1u64), Nat::from(2u64)]),
+        vec![Some(account(3)), None]
+    );
+}
+
+#[test]
+fn should_archive_blocks_and_serve_them_via_icrc3() {
+    let env = StateMachine::new();
+    let ledger_id = install_ledger(&env);
+
+    let num_blocks = ARCHIVE_TRIGGER_THRESHOLD as u64 + 1;
+    for token_id in 0..num_blocks {
+        mint(&env, ledger_id, token_id, account(2));
+    }
+
+    let res = env
+        .query(
+            ledger_id,
+            "icrc3_get_archives",
+            Encode!(&GetArchivesArgs { from: None }).unwrap(),
+        )
+        .expect("failed to query icrc3_get_archives")
+        .bytes();
+    let archives = Decode!(&res, GetArchivesResult).unwrap();
+    assert_eq!(archives.len(), 1);
+    assert_eq!(archives[0].start, Nat::from(0u64));
+    assert_eq!(archives[0].end, Nat::from(NUM_BLOCKS_TO_ARCHIVE as u64 - 1));
+
+    let res = get_blocks(&env, ledger_id.get().0, 0, num_blocks);
+    assert_eq!(res.log_length, Nat::from(num_blocks));
+    assert_eq!(
+        res.blocks.len(),
+        num_blocks as usize - NUM_BLOCKS_TO_ARCHIVE
+    );
+    assert_eq!(res.blocks[0].id, Nat::from(NUM_BLOCKS_TO_ARCHIVE as u64));
+    assert_eq!(res.archived_blocks.len(), 1);
+
+    let archived = &res.archived_blocks[0];
+    assert_eq!(archived.callback.canister_id, archives[0].canister_id);
+    let archived_blocks = get_blocks(
+        &env,
+        archived.callback.canister_id,
+        0,
+        NUM_BLOCKS_TO_ARCHIVE as u64,
+    );
+    let ids: Vec<_> = archived_blocks
+        .blocks
+        .iter()
+        .map(|b| b.id.clone())
+        .collect();
+    assert_eq!(
+        ids,
+        (0..NUM_BLOCKS_TO_ARCHIVE as u64)
+            .map(Nat::from)
+            .collect::<Vec<_>>()
+    );
+    for block in archived_blocks.blocks.into_iter().chain(res.blocks) {
+        ic_icrc7::Block::try_from(icrc_ledger_types::icrc::generic_value::Value::from(
+            block.block,
+        ))
+        .expect("failed to decode an ICRC-7 block");
+    }
+}
+
+#[test]
+fn should_keep_tokens_and_approvals_across_upgrades() {
+    let env = StateMachine::new();
+    let ledger_id = install_ledger(&env);
+
+    mint(&env, ledger_id, 1, account(2));
+    mint(&env, ledger_id, 2, account(2));
+    assert_eq!(
+        transfer(&env, ledger_id, account(2), 2, account(3)),
+        vec![Some(Ok(Nat::from(2u64)))]
+    );
+    assert_eq!(
+        approve_token(&env, ledger_id, account(2), 1, account(4)),
+        vec![Some(Ok(Nat::from(3u64)))]
+    );
+    env.upgrade_canister(
+        ledger_id,
+        ledger_wasm(),
+        Encode!(&LedgerArgument::Upgrade(None)).unwrap(),
+    )
+    .expect("failed to upgrade the ledger");
+
+    assert_eq!(
+        owner_of(&env, ledger_id, vec![Nat::from(1u64), Nat::from(2u64)]),
+        vec![Some(account(2)), Some(account(3))]
+    );
+    assert_eq!(balance_of(&env, ledger_id, account(2)), Nat::from(1u64));
+    assert_eq!(balance_of(&env, ledger_id, account(3)), Nat::from(1u64));
+    assert!(is_approved(&env, ledger_id, 1, account(4)));
+    assert_eq!(mint(&env, ledger_id, 3, account(2)), Nat::from(4u64));
+}
@@ -0,0 +1,393 @@
+//! Blocks and transactions of the ICRC-7 non-fungible token ledger.
+//!
+//! The blocks follow the ICRC-3 block schemas of
+//! [ICRC-7](https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-7/ICRC-7.md#icrc-3-block-schema)
+//! and [ICRC-37](https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-37/ICRC-37.md#icrc-3-block-schema)
+//! and are stored as CBOR-encoded ICRC-3 values, i.e., with the same encoding as the blocks of the
+//! ICRC-1 ledger. This allows the ICRC-1 archive canister to store and serve them.
+
+#[cfg(test)]
+mod tests;
+
+use ic_icrc1::blocks::{generic_block_to_encoded_block, try_encoded_block_to_generic_block};
+use ic_ledger_core::block::{BlockType, EncodedBlock, FeeCollector};
+use ic_ledger_core::timestamp::TimeStamp;
+use ic_ledger_hash_of::HashOf;
+use icrc_ledger_types::icrc::generic_value::{Map, Value};
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc1::transfer::Memo;
+use num_traits::ToPrimitive;
+use serde_bytes::ByteBuf;
+
+/// The identifier of a token.
+///
+/// ICRC-7 token ids are arbitrary natural numbers, but this ledger only supports ids that fit
+/// into 64 bits.
+pub type TokenId = u64;
+
+pub const BTYPE_MINT: &str = "7mint";
+pub const BTYPE_BURN: &str = "7burn";
+pub const BTYPE_TRANSFER: &str = "7xfer";
+pub const BTYPE_APPROVE: &str = "37approve";
+pub const BTYPE_APPROVE_COLLECTION: &str = "37approve_coll";
+pub const BTYPE_REVOKE: &str = "37revoke";
+pub const BTYPE_REVOKE_COLLECTION: &str = "37revoke_coll";
+pub const BTYPE_TRANSFER_FROM: &str = "37xfer";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Mint {
+        token_id: TokenId,
+        to: Account,
+        metadata: Map,
+    },
+    Burn {
+        token_id: TokenId,
+        from: Account,
+    },
+    Transfer {
+        token_id: TokenId,
+        from: Account,
+        to: Account,
+    },
+    Approve {
+        token_id: TokenId,
+        from: Account,
+        spender: Account,
+        expires_at: Option<u64>,
+    },
+    ApproveCollection {
+        from: Account,
+        spender: Account,
+        expires_at: Option<u64>,
+    },
+    /// Revokes the approval of `spender`, or of all spenders if `spender` is `None`.
+    Revoke {
+        token_id: TokenId,
+        from: Account,
+        spender: Option<Account>,
+    },
+    /// Revokes the collection approval of `spender`, or of all spenders if `spender` is `None`.
+    RevokeCollection {
+        from: Account,
+        spender: Option<Account>,
+    },
+    TransferFrom {
+        token_id: TokenId,
+        spender: Account,
+        from: Account,
+        to: Account,
+    },
+}
+
+impl Operation {
+    /// Returns the ICRC-3 block type of this operation.
+    pub fn btype(&self) -> &'static str {
+        match self {
+            Self::Mint { .. } => BTYPE_MINT,
+            Self::Burn { .. } => BTYPE_BURN,
+            Self::Transfer { .. } => BTYPE_TRANSFER,
+            Self::Approve { .. } => BTYPE_APPROVE,
+            Self::ApproveCollection { .. } => BTYPE_APPROVE_COLLECTION,
+            Self::Revoke { .. } => BTYPE_REVOKE,
+            Self::RevokeCollection { .. } => BTYPE_REVOKE_COLLECTION,
+            Self::TransferFrom { .. } => BTYPE_TRANSFER_FROM,
+        }
+    }
+
+    /// Returns the token this operation applies to, or `None` for collection-level operations.
+    pub fn token_id(&self) -> Option<TokenId> {
+        match self {
+            Self::Mint { token_id, .. }
+            | Self::Burn { token_id, .. }
+            | Self::Transfer { token_id, .. }
+            | Self::Approve { token_id, .. }
+            | Self::Revoke { token_id, .. }
+            | Self::TransferFrom { token_id, .. } => Some(*token_id),
+            Self::ApproveCollection { .. } | Self::RevokeCollection { .. } => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transaction {
+    pub operation: Operation,
+    pub memo: Option<Memo>,
+    pub created_at_time: Option<u64>,
+}
+
+impl Transaction {
+    /// Returns the hash the ledger uses to deduplicate this transaction.
+    pub fn hash(&self) -> HashOf<Transaction> {
+        let value = Value::map([
+            ("btype", Value::text(self.operation.btype())),
+            ("tx", self.to_value()),
+        ]);
+        HashOf::new(value.hash())
+    }
+
+    /// Returns the `tx` field of the ICRC-3 block recording this transaction.
+    fn to_value(&self) -> Value {
+        let mut tx = Map::new();
+        let mut insert = |key: &str, value: Value| {
+            tx.insert(key.to_string(), value);
+        };
+        match &self.operation {
+            Operation::Mint {
+                token_id,
+                to,
+                metadata,
+            } => {
+                insert("tid", Value::Nat64(*token_id));
+                insert("to", Value::from(*to));
+                insert("meta", Value::Map(metadata.clone()));
+            }
+            Operation::Burn { token_id, from } => {
+                insert("tid", Value::Nat64(*token_id));
+                insert("from", Value::from(*from));
+            }
+            Operation::Transfer { token_id, from, to } => {
+                insert("tid", Value::Nat64(*token_id));
+                insert("from", Value::from(*from));
+                insert("to", Value::from(*to));
+            }
+            Operation::Approve {
+                token_id,
+                from,
+                spender,
+                expires_at,
+            } => {
+                insert("tid", Value::Nat64(*token_id));
+                insert("from", Value::from(*from));
+                insert("spender", Value::from(*spender));
+                if let Some(expires_at) = expires_at {
+                    insert("exp", Value::Nat64(*expires_at));
+                }
+            }
+            Operation::ApproveCollection {
+                from,
+                spender,
+                expires_at,
+            } => {
+                insert("from", Value::from(*from));
+                insert("spender", Value::from(*spender));
+                if let Some(expires_at) = expires_at {
+                    insert("exp", Value::Nat64(*expires_at));
+                }
+            }
+            Operation::Revoke {
+                token_id,
+                from,
+                spender,
+            } => {
+                insert("tid", Value::Nat64(*token_id));
+                insert("from", Value::from(*from));
+                if let Some(spender) = spender {
+                    insert("spender", Value::from(*spender));
+                }
+            }
+            Operation::RevokeCollection { from, spender } => {
+                insert("from", Value::from(*from));
+                if let Some(spender) = spender {
+                    insert("spender", Value::from(*spender));
+                }
+            }
+            Operation::TransferFrom {
+                token_id,
+                spender,
+                from,
+                to,
+            } => {
+                insert("tid", Value::Nat64(*token_id));
+                insert("spender", Value::from(*spender));
+                insert("from", Value::from(*from));
+                insert("to", Value::from(*to));
+            }
+        }
+        if let Some(memo) = &self.memo {
+            insert("memo", Value::Blob(memo.0.clone()));
+        }
+        if let Some(created_at_time) = self.created_at_time {
+            insert("ts", Value::Nat64(created_at_time));
+        }
+        Value::Map(tx)
+    }
+
+    fn from_value(btype: &str, tx: Value) -> Result<Self, String> {
+        let mut tx = tx
+            .as_map()
+            .map_err(|variant| format!("expected the tx field to be a Map, found {variant}"))?;
+
+        let memo = remove_opt(&mut tx, "memo", |value| value.as_blob())?.map(Memo);
+        let created_at_time = remove_opt(&mut tx, "ts", as_u64)?;
+        let operation = match btype {
+            BTYPE_MINT => Operation::Mint {
+                token_id: remove(&mut tx, "tid", as_u64)?,
+                to: remove(&mut tx, "to", Account::try_from)?,
+                metadata: remove(&mut tx, "meta", |value| value.as_map())?,
+            },
+            BTYPE_BURN => Operation::Burn {
+                token_id: remove(&mut tx, "tid", as_u64)?,
+                from: remove(&mut tx, "from", Account::try_from)?,
+            },
+            BTYPE_TRANSFER => Operation::Transfer {
+                token_id: remove(&mut tx, "tid", as_u64)?,
+                from: remove(&mut tx, "from", Account::try_from)?,
+                to: remove(&mut tx, "to", Account::try_from)?,
+            },
+            BTYPE_APPROVE => Operation::Approve {
+                token_id: remove(&mut tx, "tid", as_u64)?,
+                from: remove(&mut tx, "from", Account::try_from)?,
+                spender: remove(&mut tx, "spender", Account::try_from)?,
+                expires_at: remove_opt(&mut tx, "exp", as_u64)?,
+            },
+            BTYPE_APPROVE_COLLECTION => Operation::ApproveCollection {
+                from: remove(&mut tx, "from", Account::try_from)?,
+                spender: remove(&mut tx, "spender", Account::try_from)?,
+                expires_at: remove_opt(&mut tx, "exp", as_u64)?,
+            },
+            BTYPE_REVOKE => Operation::Revoke {
+                token_id: remove(&mut tx, "tid", as_u64)?,
+                from: remove(&mut tx, "from", Account::try_from)?,
+                spender: remove_opt(&mut tx, "spender", Account::try_from)?,
+            },
+            BTYPE_REVOKE_COLLECTION => Operation::RevokeCollection {
+                from: remove(&mut tx, "from", Account::try_from)?,
+                spender: remove_opt(&mut tx, "spender", Account::try_from)?,
+            },
+            BTYPE_TRANSFER_FROM => Operation::TransferFrom {
+                token_id: remove(&mut tx, "tid", as_u64)?,
+                spender: remove(&mut tx, "spender", Account::try_from)?,
+                from: remove(&mut tx, "from", Account::try_from)?,
+                to: remove(&mut tx, "to", Account::try_from)?,
+            },
+            unknown => return Err(format!("unknown block type {unknown}")),
+        };
+        Ok(Self {
+            operation,
+            memo,
+            created_at_time,
+        })
+    }
+}
+
+fn as_u64(value: Value) -> Result<u64, String> {
+    let nat = value.as_nat()?;
+    nat.0
+        .to_u64()
+        .ok_or_else(|| format!("{nat} does not fit into 64 bits"))
+}
+
+fn remove<T>(
+    map: &mut Map,
+    key: &str,
+    f: impl FnOnce(Value) -> Result<T, String>,
+) -> Result<T, String> {
+    remove_opt(map, key, f)?.ok_or_else(|| format!("missing field {key}"))
+}
+
+fn remove_opt<T>(
+    map: &mut Map,
+    key: &str,
+    f: impl FnOnce(Value) -> Result<T, String>,
+) -> Result<Option<T>, String> {
+    map.remove(key)
+        .map(f)
+        .transpose()
+        .map_err(|err| format!("invalid field {key}: {err}"))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    pub parent_hash: Option<HashOf<EncodedBlock>>,
+    pub transaction: Transaction,
+    pub timestamp: u64,
+}
+
+impl From<Block> for Value {
+    fn from(block: Block) -> Self {
+        let mut map = Map::new();
+        map.insert(
+            "btype".to_string(),
+            Value::text(block.transaction.operation.btype()),
+        );
+        map.insert("ts".to_string(), Value::Nat64(block.timestamp));
+        if let Some(parent_hash) = block.parent_hash {
+            map.insert(
+                "phash".to_string(),
+                Value::Blob(ByteBuf::from(parent_hash.as_slice().to_vec())),
+            );
+        }
+        map.insert("tx".to_string(), block.transaction.to_value());
+        Value::Map(map)
+    }
+}
+
+impl TryFrom<Value> for Block {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let mut map = value
+            .as_map()
+            .map_err(|variant| format!("expected a block to be a Map, found {variant}"))?;
+        let btype = remove(&mut map, "btype", |value| value.as_text())?;
+        let timestamp = remove(&mut map, "ts", as_u64)?;
+        let parent_hash = remove_opt(&mut map, "phash", |value| {
+            let bytes: [u8; 32] = value
+                .as_blob()?
+                .as_slice()
+                .try_into()
+                .map_err(|_| "expected a 32-byte hash".to_string())?;
+            Ok(HashOf::new(bytes))
+        })?;
+        let transaction = remove(&mut map, "tx", |tx| Transaction::from_value(&btype, tx))?;
+        Ok(Self {
+            parent_hash,
+            transaction,
+            timestamp,
+        })
+    }
+}
+
+impl BlockType for Block {
+    type Transaction = Transaction;
+    type AccountId = Account;
+    // Non-fungible token transactions carry no fees.
+    type Tokens = ();
+
+    fn from_transaction(
+        parent_hash: Option<HashOf<EncodedBlock>>,
+        transaction: Self::Transaction,
+        block_timestamp: TimeStamp,
+        _effective_fee: Self::Tokens,
+        _fee_collector: Option<FeeCollector<Self::AccountId>>,
+    ) -> Self {
+        Self {
+            parent_hash,
+            transaction,
+            timestamp: block_timestamp.as_nanos_since_unix_epoch(),
+        }
+    }
+
+    fn encode(self) -> EncodedBlock {
+        generic_block_to_encoded_block(Value::from(self)).expect("bug: failed to encode a block")
+    }
+
+    fn decode(encoded: EncodedBlock) -> Result<Self, String> {
+        Self::try_from(try_encoded_block_to_generic_block(&encoded)?)
+    }
+
+    fn block_hash(encoded: &EncodedBlock) -> HashOf<EncodedBlock> {
+        let value = try_encoded_block_to_generic_block(encoded)
+            .unwrap_or_else(|err| panic!("bug: failed to decode an encoded block: {err}"));
+        HashOf::new(value.hash())
+    }
+
+    fn parent_hash(&self) -> Option<HashOf<EncodedBlock>> {
+        self.parent_hash
+    }
+
+    fn timestamp(&self) -> TimeStamp {
+        TimeStamp::from_nanos_since_unix_epoch(self.timestamp)
+    }
+}
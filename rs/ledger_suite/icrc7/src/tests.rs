@@ -0,0 +1,150 @@
+use super::*;
+use ic_icrc1_test_utils::account_strategy;
+use proptest::prelude::*;
+
+fn memo_strategy() -> impl Strategy<Value = Option<Memo>> {
+    prop::option::of(prop::collection::vec(any::<u8>(), 0..32).prop_map(Memo::from))
+}
+
+fn metadata_strategy() -> impl Strategy<Value = Map> {
+    prop::collection::btree_map(
+        "[a-z:_]{1,16}",
+        prop_oneof![
+            any::<u64>().prop_map(Value::Nat64),
+            "[ -~]{0,32}".prop_map(Value::Text),
+            prop::collection::vec(any::<u8>(), 0..32).prop_map(Value::blob),
+        ],
+        0..4,
+    )
+}
+
+fn operation_strategy() -> impl Strategy<Value = Operation> {
+    let arb_token_id = any::<TokenId>;
+    let arb_expires_at = || prop::option::of(any::<u64>());
+    prop_oneof![
+        (arb_token_id(), account_strategy(), metadata_strategy()).prop_map(
+            |(token_id, to, metadata)| {
+                Operation::Mint {
+                    token_id,
+                    to,
+                    metadata,
+                }
+            }
+        ),
+        (arb_token_id(), account_strategy())
+            .prop_map(|(token_id, from)| Operation::Burn { token_id, from }),
+        (arb_token_id(), account_strategy(), account_strategy())
+            .prop_map(|(token_id, from, to)| Operation::Transfer { token_id, from, to }),
+        (
+            arb_token_id(),
+            account_strategy(),
+            account_strategy(),
+            arb_expires_at()
+        )
+            .prop_map(|(token_id, from, spender, expires_at)| Operation::Approve {
+                token_id,
+                from,
+                spender,
+                expires_at,
+            }),
+        (account_strategy(), account_strategy(), arb_expires_at()).prop_map(
+            |(from, spender, expires_at)| Operation::ApproveCollection {
+                from,
+                spender,
+                expires_at,
+            }
+        ),
+        (
+            arb_token_id(),
+            account_strategy(),
+            prop::option::of(account_strategy())
+        )
+            .prop_map(|(token_id, from, spender)| Operation::Revoke {
+                token_id,
+                from,
+                spender,
+            }),
+        (account_strategy(), prop::option::of(account_strategy()))
+            .prop_map(|(from, spender)| Operation::RevokeCollection { from, spender }),
+        (
+            arb_token_id(),
+            account_strategy(),
+            account_strategy(),
+            account_strategy()
+        )
+            .prop_map(|(token_id, spender, from, to)| Operation::TransferFrom {
+                token_id,
+                spender,
+                from,
+                to,
+            }),
+    ]
+}
+
+fn block_strategy() -> impl Strategy<Value = Block> {
+    (
+        operation_strategy(),
+        memo_strategy(),
+        prop::option::of(any::<u64>()),
+        prop::option::of(any::<[u8; 32]>()),
+        any::<u64>(),
+    )
+        .prop_map(
+            |(operation, memo, created_at_time, parent_hash, timestamp)| Block {
+                parent_hash: parent_hash.map(HashOf::new),
+                transaction: Transaction {
+                    operation,
+                    memo,
+                    created_at_time,
+                },
+                timestamp,
+            },
+        )
+}
+
+proptest! {
+    #[test]
+    fn block_encoding_round_trip(block in block_strategy()) {
+        let encoded = block.clone().encode();
+        prop_assert_eq!(Block::decode(encoded).unwrap(), block);
+    }
+
+    #[test]
+    fn block_hash_matches_icrc3_value_hash(block in block_strategy()) {
+        let value = Value::from(block.clone());
+        let encoded = block.encode();
+        prop_assert_eq!(Block::block_hash(&encoded).into_bytes(), value.hash());
+    }
+}
+
+#[test]
+fn should_distinguish_transactions_with_the_same_fields() {
+    let from = Account::from(candid::Principal::management_canister());
+    let burn = Transaction {
+        operation: Operation::Burn { token_id: 1, from },
+        memo: None,
+        created_at_time: Some(1),
+    };
+    let revoke = Transaction {
+        operation: Operation::Revoke {
+            token_id: 1,
+            from,
+            spender: None,
+        },
+        ..burn.clone()
+    };
+    assert_ne!(burn.hash(), revoke.hash());
+}
+
+#[test]
+fn should_reject_unknown_block_types() {
+    let block = Value::map([
+        ("btype", Value::text("7unknown")),
+        ("ts", Value::Nat64(0)),
+        ("tx", Value::map(Vec::<(String, Value)>::new())),
+    ]);
+    assert_eq!(
+        Block::try_from(block),
+        Err("invalid field tx: unknown block type 7unknown".to_string())
+    );
+}
@@ -49,6 +49,9 @@ use icrc_ledger_types::icrc3;
 use icrc_ledger_types::icrc3::archive::ArchiveInfo;
 use icrc_ledger_types::icrc3::blocks::{GenericBlock as IcrcBlock, GetBlocksResult};
 use icrc_ledger_types::icrc3::transactions::Transfer;
+use icrc_ledger_types::icrc4::transfer_batch::{
+    BalanceQueryResult, TransferBatchError, TransferBatchResults,
+};
 use icrc_ledger_types::icrc21::errors::ErrorInfo;
 use icrc_ledger_types::icrc21::errors::Icrc21Error;
 use icrc_ledger_types::icrc21::requests::ConsentMessageMetadata;
@@ -574,7 +577,7 @@ where
     assert_eq!(
         standards,
         vec![
-            "ICRC-1", "ICRC-10", "ICRC-103", "ICRC-106", "ICRC-2", "ICRC-21", "ICRC-3"
+            "ICRC-1", "ICRC-10", "ICRC-103", "ICRC-106", "ICRC-2", "ICRC-21", "ICRC-3", "ICRC-4"
         ]
     );
 }
@@ -924,6 +927,149 @@ where
     send_transfer_from(&env, canister_id, p2.0, &transfer_from_args).expect("transfer_from failed");
 }
 
+pub fn test_icrc4_transfer_batch<T>(ledger_wasm: Vec<u8>, encode_init_args: fn(InitArgs) -> T)
+where
+    T: CandidType,
+{
+    fn transfer_batch(
+        env: &StateMachine,
+        canister_id: CanisterId,
+        from: Principal,
+        args: &[TransferArg],
+    ) -> TransferBatchResults {
+        Decode!(
+            &env.execute_ingress_as(
+                PrincipalId(from),
+                canister_id,
+                "icrc4_transfer_batch",
+                Encode!(&args).unwrap()
+            )
+            .expect("failed to execute icrc4_transfer_batch")
+            .bytes(),
+            TransferBatchResults
+        )
+        .expect("failed to decode icrc4_transfer_batch response")
+    }
+
+    fn balance_of_batch(
+        env: &StateMachine,
+        canister_id: CanisterId,
+        accounts: &[Account],
+    ) -> Vec<u64> {
+        Decode!(
+            &env.query(
+                canister_id,
+                "icrc4_balance_of_batch",
+                Encode!(&accounts).unwrap()
+            )
+            .expect("failed to query icrc4_balance_of_batch")
+            .bytes(),
+            BalanceQueryResult
+        )
+        .expect("failed to decode icrc4_balance_of_batch response")
+        .into_iter()
+        .map(|balance| balance.0.to_u64().unwrap())
+        .collect()
+    }
+
+    fn maximum_update_batch_size(env: &StateMachine, canister_id: CanisterId) -> Option<Nat> {
+        Decode!(
+            &env.query(
+                canister_id,
+                "icrc4_maximum_update_batch_size",
+                Encode!().unwrap()
+            )
+            .expect("failed to query icrc4_maximum_update_batch_size")
+            .bytes(),
+            Option<Nat>
+        )
+        .expect("failed to decode icrc4_maximum_update_batch_size response")
+    }
+
+    let p1 = PrincipalId::new_user_test_id(1);
+    let p2 = PrincipalId::new_user_test_id(2);
+    let p3 = PrincipalId::new_user_test_id(3);
+    let (env, canister_id) = setup(
+        ledger_wasm,
+        encode_init_args,
+        vec![(Account::from(p1.0), 10_000_000)],
+    );
+
+    let to_p = |p: PrincipalId, amount: u64| TransferArg {
+        from_subaccount: None,
+        to: p.0.into(),
+        fee: None,
+        amount: Nat::from(amount),
+        created_at_time: None,
+        memo: None,
+    };
+
+    // Each transfer of the batch results in its own block.
+    let results = transfer_batch(
+        &env,
+        canister_id,
+        p1.0,
+        &[to_p(p2, 1_000_000), to_p(p3, 2_000_000)],
+    );
+    assert_eq!(
+        results,
+        vec![Some(Ok(Nat::from(1u64))), Some(Ok(Nat::from(2u64)))]
+    );
+    assert_eq!(
+        balance_of_batch(&env, canister_id, &[p1.0.into(), p2.0.into(), p3.0.into()]),
+        vec![10_000_000 - 3_000_000 - 2 * FEE, 1_000_000, 2_000_000]
+    );
+    assert_eq!(total_supply(&env, canister_id), 10_000_000 - 2 * FEE);
+
+    // Transfers fail independently of each other, including duplicates
+    // within the same batch.
+    let now = system_time_to_nanos(env.time());
+    let dedup_arg = TransferArg {
+        created_at_time: Some(now),
+        ..to_p(p3, 10_000)
+    };
+    let results = transfer_batch(
+        &env,
+        canister_id,
+        p2.0,
+        &[
+            dedup_arg.clone(),
+            to_p(p3, 100_000_000),
+            dedup_arg,
+            to_p(p1, 20_000),
+        ],
+    );
+    let p2_balance = 1_000_000 - 10_000 - FEE;
+    assert_eq!(
+        results,
+        vec![
+            Some(Ok(Nat::from(3u64))),
+            Some(Err(TransferBatchError::InsufficientFunds {
+                balance: Nat::from(p2_balance)
+            })),
+            Some(Err(TransferBatchError::Duplicate {
+                duplicate_of: Nat::from(3u64)
+            })),
+            Some(Ok(Nat::from(4u64))),
+        ]
+    );
+    assert_eq!(
+        balance_of(&env, canister_id, p2.0),
+        p2_balance - 20_000 - FEE
+    );
+
+    // Batches exceeding the advertised limit are rejected as a whole.
+    let limit = maximum_update_batch_size(&env, canister_id).expect("no update batch limit");
+    let batch_size = limit.0.to_usize().unwrap() + 1;
+    let p1_balance = balance_of(&env, canister_id, p1.0);
+    let results = transfer_batch(&env, canister_id, p1.0, &vec![to_p(p2, 1); batch_size]);
+    assert_eq!(
+        results,
+        vec![Some(Err(TransferBatchError::TooManyRequests { limit })); batch_size]
+    );
+    assert_eq!(balance_of(&env, canister_id, p1.0), p1_balance);
+}
+
 pub fn test_mint_burn<T>(ledger_wasm: Vec<u8>, encode_init_args: fn(InitArgs) -> T)
 where
     T: CandidType,
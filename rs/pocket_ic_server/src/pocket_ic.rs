@@ -27,7 +27,10 @@ use hyper::header::{CONTENT_TYPE, HeaderValue};
 use hyper::{Method, StatusCode};
 use ic_boundary::{Health, RootKey, status};
 use ic_btc_adapter::config::{Config as BitcoinAdapterConfig, IncomingSource as BtcIncomingSource};
-use ic_btc_adapter::{AdapterNetwork, start_server as start_btc_server};
+use ic_btc_adapter::{
+    AdapterNetwork, LocalChainConfig as BtcLocalChainConfig, LocalChainHandle,
+    start_local_chain_server, start_server as start_btc_server,
+};
 use ic_btc_interface::{
     Fees as BitcoinFees, InitConfig as BitcoinInitConfig, Network as BitcoinNetwork,
 };
@@ -98,15 +101,18 @@ use ic_registry_transport::{
     GetChunk, dechunkify_delta, deserialize_atomic_mutate_response,
     deserialize_get_changes_since_response, serialize_get_changes_since_request,
 };
+use ic_replicated_state::ReplicatedState;
+use ic_replicated_state::canister_state::system_state::CyclesUseCase;
 use ic_sns_wasm::init::SnsWasmCanisterInitPayloadBuilder;
 use ic_sns_wasm::pb::v1::add_wasm_response::Result as AddWasmResult;
 use ic_sns_wasm::pb::v1::{AddWasmRequest, AddWasmResponse, SnsCanisterType, SnsWasm};
 use ic_state_machine_tests::{
-    FakeVerifier, StateMachine, StateMachineBuilder, StateMachineConfig, StateMachineStateDir,
-    SubmitIngressError, Subnets, WasmResult, add_global_registry_records,
-    add_initial_registry_records,
+    FakeVerifier, FaultInjection, FaultInjectionRule, StateMachine, StateMachineBuilder,
+    StateMachineConfig, StateMachineStateDir, SubmitIngressError, Subnets, WasmResult,
+    add_global_registry_records, add_initial_registry_records,
 };
 use ic_state_manager::StateManagerImpl;
+use ic_state_tool::commands::cdiff::{FieldChange, StateDiff, diff_states};
 use ic_types::batch::BlockmakerMetrics;
 use ic_types::ingress::{IngressState, IngressStatus};
 use ic_types::messages::{CertificateDelegationFormat, CertificateDelegationMetadata};
@@ -134,10 +140,12 @@ use icrc_ledger_types::icrc1::account::Account;
 use itertools::Itertools;
 use pocket_ic::common::rest::{
     self, BinaryBlob, BlobCompression, CanisterHttpHeader, CanisterHttpMethod, CanisterHttpRequest,
-    CanisterHttpResponse, ExtendedSubnetConfigSet, IcpConfig, IcpConfigFlag, IcpFeatures,
-    IcpFeaturesConfig, IncompleteStateFlag, MockCanisterHttpResponse, RawAddCycles,
-    RawCanisterCall, RawCanisterId, RawEffectivePrincipal, RawMessageId, RawSetStableMemory,
-    SubnetInstructionConfig, SubnetKind, TickConfigs, Topology,
+    CanisterHttpResponse, CanisterStateChange, CanisterStateDiffKind, ExtendedSubnetConfigSet,
+    IcpConfig, IcpConfigFlag, IcpFeatures, IcpFeaturesConfig, IncompleteStateFlag, LocalChain,
+    LocalChainConfig, MockCanisterHttpResponse, RawAddCycles, RawCallProfile, RawCanisterCall,
+    RawCanisterCyclesProfile, RawCanisterId, RawCanisterStateDiff, RawEffectivePrincipal,
+    RawExecutionProfile, RawMessageId, RawSetStableMemory, SubnetInstructionConfig, SubnetKind,
+    TickConfigs, Topology,
 };
 use pocket_ic::{ErrorCode, RejectCode, RejectResponse, copy_dir};
 use registry_canister::init::RegistryCanisterInitPayloadBuilder;
@@ -168,6 +176,9 @@ use tower::{service_fn, util::ServiceExt};
 // See build.rs
 include!(concat!(env!("OUT_DIR"), "/dashboard.rs"));
 
+/// The file (in the state directory) persisting the fault injection rules and held messages.
+const FAULT_INJECTION_FILE: &str = "fault_injection.cbor";
+
 const MAINNET_NNS_SUBNET_ID: &str =
     "tdb26-jop6k-aogll-7ltgs-eruif-6kk7m-qpktf-gdiqx-mxtrf-vb5e6-eqe";
 const MAINNET_II_SUBNET_ID: &str =
@@ -212,6 +223,10 @@ const MIGRATION_CANISTER_WASM: &[u8] = include_bytes!(env!("MIGRATION_CANISTER_W
 
 const DEFAULT_SUBACCOUNT: Subaccount = Subaccount([0; 32]);
 
+// The ID of the Dogecoin testnet canister `hd7hi-kqaaa-aaaan-aaaea-cai`
+// which the ckDOGE minter also uses on the regtest network.
+const DOGECOIN_TESTNET_CANISTER_ID: CanisterId = CanisterId::from_u64(0x1a00008);
+
 // Initial amount of cycles when bootstrapping system canisters so that
 // - the canister has enough cycles to never run out of cycles;
 // - it is still possible to top up the canister with further cycles without overflowing 128-bit range.
@@ -290,6 +305,34 @@ fn compute_subnet_seed(
     hasher.finish()
 }
 
+/// Applies `f` to the latest state stored in the given subnet state directory.
+fn with_latest_state<T>(
+    subnet_state_dir: &Path,
+    subnet_type: SubnetType,
+    f: impl FnOnce(&ReplicatedState) -> T,
+) -> std::io::Result<T> {
+    // We create a temporary state manager used to read the given state.
+    // We first copy the subnet state directory into a temporary directory
+    // so that the temporary state manager has a private copy
+    // of the subnet state directory (otherwise, it might crash).
+    let temp_state_dir = TempDir::new()?;
+    copy_dir(subnet_state_dir, temp_state_dir.path())?;
+    let state_manager = StateManagerImpl::new(
+        Arc::new(FakeVerifier),
+        SubnetId::new(PrincipalId::default()),
+        subnet_type,
+        no_op_logger(),
+        &MetricsRegistry::new(),
+        &ic_config::state_manager::Config::new(temp_state_dir.path().to_path_buf()),
+        None,
+        MaliciousFlags::default(),
+    );
+    let result = f(state_manager.get_latest_state().get_ref());
+    // Shut down the temporary state manager to avoid race conditions.
+    state_manager.flush_tip_channel();
+    Ok(result)
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 struct RawTopologyInternal {
     pub subnet_configs: Vec<SubnetConfigInternal>,
@@ -334,19 +377,23 @@ fn logger_config_from_level(log_level: Option<Level>) -> LoggerConfig {
 }
 
 struct BitcoinAdapterParts {
-    adapter: JoinHandle<()>,
+    adapter: Vec<JoinHandle<()>>,
     uds_path: PathBuf,
+    // only set if the adapter produces a local chain instead of connecting to nodes
+    local_chain: Option<LocalChainHandle>,
 }
 
 impl BitcoinAdapterParts {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         bitcoind_addr: Vec<SocketAddr>,
+        local_chain_config: Option<LocalChainConfig>,
         uds_path: PathBuf,
         network: AdapterNetwork,
         log_level: Option<Level>,
         replica_logger: ReplicaLogger,
         metrics_registry: MetricsRegistry,
-    ) -> Self {
+    ) -> Result<Self, String> {
         let bitcoin_adapter_config = BitcoinAdapterConfig {
             nodes: bitcoind_addr,
             socks_proxy: None,
@@ -354,11 +401,25 @@ impl BitcoinAdapterParts {
             logger: logger_config_from_level(log_level),
             incoming_source: BtcIncomingSource::Path(uds_path.clone()),
             address_limits: (1, 1),
+            local_chain: local_chain_config.map(|config| BtcLocalChainConfig {
+                // PocketIC only produces blocks on request.
+                block_interval: None,
+                initial_blocks: config.initial_blocks,
+                coinbase_address: config.coinbase_address,
+            }),
             ..BitcoinAdapterConfig::default_with(network)
         };
-        let adapter = tokio::spawn(async move {
-            start_btc_server(replica_logger, metrics_registry, bitcoin_adapter_config).await
-        });
+        let (adapter, local_chain) = if bitcoin_adapter_config.local_chain.is_some() {
+            let (local_chain, adapter) =
+                start_local_chain_server(replica_logger, metrics_registry, bitcoin_adapter_config)
+                    .map_err(|err| format!("Failed to start the local chain: {err}"))?;
+            (adapter.into(), Some(local_chain))
+        } else {
+            let adapter = tokio::spawn(async move {
+                start_btc_server(replica_logger, metrics_registry, bitcoin_adapter_config).await
+            });
+            (vec![adapter], None)
+        };
         let start = std::time::Instant::now();
         loop {
             if let Ok(true) = std::fs::exists(uds_path.clone()) {
@@ -371,13 +432,19 @@ impl BitcoinAdapterParts {
             }
             std::thread::sleep(std::time::Duration::from_millis(10));
         }
-        BitcoinAdapterParts { adapter, uds_path }
+        Ok(BitcoinAdapterParts {
+            adapter,
+            uds_path,
+            local_chain,
+        })
     }
 }
 
 impl Drop for BitcoinAdapterParts {
     fn drop(&mut self) {
-        self.adapter.abort();
+        for adapter in &self.adapter {
+            adapter.abort();
+        }
         remove_file(self.uds_path.clone()).unwrap();
     }
 }
@@ -545,16 +612,21 @@ struct PocketIcSubnets {
     routing_table: RoutingTable,
     chain_keys: BTreeMap<MasterPublicKeyId, Vec<SubnetId>>,
     icp_config: IcpConfig,
+    fault_injection: Option<Arc<FaultInjection>>,
+    // cycles consumed by every canister (by use case) when profiling was started
+    profiling_cycles: Option<BTreeMap<CanisterId, BTreeMap<CyclesUseCase, u128>>>,
     log_level: Option<Level>,
     bitcoind_addr: Option<Vec<SocketAddr>>,
     dogecoind_addr: Option<Vec<SocketAddr>>,
+    bitcoin_local_chain: Option<LocalChainConfig>,
+    dogecoin_local_chain: Option<LocalChainConfig>,
     icp_features: Option<IcpFeatures>,
     initial_time: SystemTime,
     auto_progress_enabled: bool,
     gateway_port: Option<u16>,
     synced_registry_version: RegistryVersion,
-    _bitcoin_adapter_parts: Option<BitcoinAdapterParts>,
-    _dogecoin_adapter_parts: Option<BitcoinAdapterParts>,
+    bitcoin_adapter_parts: Option<BitcoinAdapterParts>,
+    dogecoin_adapter_parts: Option<BitcoinAdapterParts>,
 }
 
 impl PocketIcSubnets {
@@ -567,6 +639,7 @@ impl PocketIcSubnets {
         registry_data_provider: Arc<ProtoRegistryDataProvider>,
         create_at_registry_version: Option<RegistryVersion>,
         icp_config: &IcpConfig,
+        fault_injection: Option<Arc<FaultInjection>>,
         log_level: Option<Level>,
         bitcoin_adapter_uds_path: Option<PathBuf>,
         dogecoin_adapter_uds_path: Option<PathBuf>,
@@ -581,6 +654,9 @@ impl PocketIcSubnets {
             canister_backtrace,
             function_name_length_limits,
             canister_execution_rate_limiting,
+            // handled in `PocketIcSubnets::new`
+            fault_injection: _,
+            execution_profiling,
         } = icp_config;
         let mut hypervisor_config = match beta_features.clone().unwrap_or(IcpConfigFlag::Disabled) {
             IcpConfigFlag::Disabled => execution_environment::Config::default(),
@@ -625,6 +701,12 @@ impl PocketIcSubnets {
                 hypervisor_config.rate_limiting_of_instructions = FlagStatus::Disabled;
             }
         };
+        match execution_profiling {
+            None | Some(IcpConfigFlag::Disabled) => (),
+            Some(IcpConfigFlag::Enabled) => {
+                hypervisor_config.execution_profiling = FlagStatus::Enabled;
+            }
+        };
         if let SubnetInstructionConfig::Benchmarking = instruction_config {
             let instruction_limit = NumInstructions::new(99_999_999_999_999);
             if instruction_limit > subnet_config.scheduler_config.max_instructions_per_round {
@@ -637,6 +719,14 @@ impl PocketIcSubnets {
                 .max_instructions_per_message_without_dts = instruction_limit;
             hypervisor_config.max_query_call_graph_instructions = instruction_limit;
         }
+        // the Dogecoin canister for the regtest network can also be deployed
+        // under the Dogecoin testnet canister ID (as expected by the ckDOGE minter)
+        if dogecoin_adapter_uds_path.is_some() {
+            hypervisor_config
+                .bitcoin
+                .privileged_access
+                .push(DOGECOIN_TESTNET_CANISTER_ID);
+        }
         // bound PocketIc resource consumption
         hypervisor_config.embedders_config.max_sandbox_count = 64;
         hypervisor_config.embedders_config.max_sandbox_idle_time = Duration::from_secs(30);
@@ -661,6 +751,7 @@ impl PocketIcSubnets {
             .with_log_level(log_level)
             .with_bitcoin_testnet_uds_path(bitcoin_adapter_uds_path)
             .with_dogecoin_testnet_uds_path(dogecoin_adapter_uds_path)
+            .with_fault_injection(fault_injection)
             .create_at_registry_version(create_at_registry_version)
     }
 
@@ -671,6 +762,8 @@ impl PocketIcSubnets {
         log_level: Option<Level>,
         bitcoind_addr: Option<Vec<SocketAddr>>,
         dogecoind_addr: Option<Vec<SocketAddr>>,
+        bitcoin_local_chain: Option<LocalChainConfig>,
+        dogecoin_local_chain: Option<LocalChainConfig>,
         icp_features: Option<IcpFeatures>,
         initial_time: SystemTime,
         auto_progress_enabled: bool,
@@ -684,6 +777,18 @@ impl PocketIcSubnets {
         let synced_registry_version = synced_registry_version
             .map(RegistryVersion::new)
             .unwrap_or(ZERO_REGISTRY_VERSION);
+        let fault_injection = match icp_config.fault_injection {
+            Some(IcpConfigFlag::Enabled) => {
+                // Resume with the rules and held messages of a persisted instance (if any).
+                let fault_injection = state_dir
+                    .as_ref()
+                    .and_then(|state_dir| std::fs::read(state_dir.join(FAULT_INJECTION_FILE)).ok())
+                    .map(|bytes| FaultInjection::deserialize(&bytes).unwrap())
+                    .unwrap_or_default();
+                Some(Arc::new(fault_injection))
+            }
+            None | Some(IcpConfigFlag::Disabled) => None,
+        };
         Self {
             subnet_configs: vec![],
             subnets: Arc::new(SubnetsImpl::new()),
@@ -697,34 +802,53 @@ impl PocketIcSubnets {
             routing_table,
             chain_keys,
             icp_config,
+            fault_injection,
+            profiling_cycles: None,
             log_level,
             bitcoind_addr,
             dogecoind_addr,
+            bitcoin_local_chain,
+            dogecoin_local_chain,
             icp_features,
             initial_time,
             auto_progress_enabled,
             gateway_port,
             synced_registry_version,
-            _bitcoin_adapter_parts: None,
-            _dogecoin_adapter_parts: None,
+            bitcoin_adapter_parts: None,
+            dogecoin_adapter_parts: None,
         }
     }
 
     fn persist_topology(&self, default_effective_canister_id: Principal) {
         if let Some(ref state_dir) = self.state_dir {
-            let raw_topology: RawTopologyInternal = RawTopologyInternal {
-                subnet_configs: self.subnet_configs.clone(),
-                default_effective_canister_id: default_effective_canister_id.into(),
-                icp_features: self.icp_features.clone(),
-                synced_registry_version: Some(self.synced_registry_version.get()),
-                time: self.time(),
-            };
-            let topology_json = serde_json::to_string(&raw_topology).unwrap();
-            let mut topology_file = File::create(state_dir.join("topology.json")).unwrap();
-            topology_file.write_all(topology_json.as_bytes()).unwrap();
+            self.write_topology(state_dir, default_effective_canister_id);
+            self.write_fault_injection(state_dir);
+        }
+    }
+
+    fn write_fault_injection(&self, state_dir: &Path) {
+        if let Some(ref fault_injection) = self.fault_injection {
+            std::fs::write(
+                state_dir.join(FAULT_INJECTION_FILE),
+                fault_injection.serialize(),
+            )
+            .unwrap();
         }
     }
 
+    fn write_topology(&self, state_dir: &Path, default_effective_canister_id: Principal) {
+        let raw_topology: RawTopologyInternal = RawTopologyInternal {
+            subnet_configs: self.subnet_configs.clone(),
+            default_effective_canister_id: default_effective_canister_id.into(),
+            icp_features: self.icp_features.clone(),
+            synced_registry_version: Some(self.synced_registry_version.get()),
+            time: self.time(),
+        };
+        let topology_json = serde_json::to_string(&raw_topology).unwrap();
+        let mut topology_file = File::create(state_dir.join("topology.json")).unwrap();
+        topology_file.write_all(topology_json.as_bytes()).unwrap();
+    }
+
     fn get_all(&self) -> Vec<Arc<Subnet>> {
         self.subnets.get_all()
     }
@@ -786,18 +910,20 @@ impl PocketIcSubnets {
                 .expect("Failed to copy state directory");
         }
 
-        let bitcoin_adapter_uds_path =
-            if matches!(subnet_kind, SubnetKind::Bitcoin) && self.bitcoind_addr.is_some() {
-                Some(NamedTempFile::new().unwrap().into_temp_path().to_path_buf())
-            } else {
-                None
-            };
-        let dogecoin_adapter_uds_path =
-            if matches!(subnet_kind, SubnetKind::Bitcoin) && self.dogecoind_addr.is_some() {
-                Some(NamedTempFile::new().unwrap().into_temp_path().to_path_buf())
-            } else {
-                None
-            };
+        let bitcoin_adapter_uds_path = if matches!(subnet_kind, SubnetKind::Bitcoin)
+            && (self.bitcoind_addr.is_some() || self.bitcoin_local_chain.is_some())
+        {
+            Some(NamedTempFile::new().unwrap().into_temp_path().to_path_buf())
+        } else {
+            None
+        };
+        let dogecoin_adapter_uds_path = if matches!(subnet_kind, SubnetKind::Bitcoin)
+            && (self.dogecoind_addr.is_some() || self.dogecoin_local_chain.is_some())
+        {
+            Some(NamedTempFile::new().unwrap().into_temp_path().to_path_buf())
+        } else {
+            None
+        };
 
         let latest_registry_version = self.registry_data_provider.latest_version().get();
         let create_at_registry_version = if update_registry_and_system_canisters {
@@ -818,6 +944,7 @@ impl PocketIcSubnets {
             self.registry_data_provider.clone(),
             create_at_registry_version.map(RegistryVersion::new),
             &self.icp_config,
+            self.fault_injection.clone(),
             self.log_level,
             bitcoin_adapter_uds_path.clone(),
             dogecoin_adapter_uds_path.clone(),
@@ -839,12 +966,14 @@ impl PocketIcSubnets {
                 }
             }
 
-            for name in ["key_1", "test_key_1", "dfx_test_key"] {
-                let key_id = EcdsaKeyId {
-                    curve: EcdsaCurve::Secp256k1,
-                    name: name.to_string(),
-                };
-                subnet_chain_keys.push(MasterPublicKeyId::Ecdsa(key_id));
+            for curve in [EcdsaCurve::Secp256k1, EcdsaCurve::Secp256r1] {
+                for name in ["key_1", "test_key_1", "dfx_test_key"] {
+                    let key_id = EcdsaKeyId {
+                        curve,
+                        name: name.to_string(),
+                    };
+                    subnet_chain_keys.push(MasterPublicKeyId::Ecdsa(key_id));
+                }
             }
 
             for name in ["key_1", "test_key_1", "dfx_test_key"] {
@@ -901,24 +1030,26 @@ impl PocketIcSubnets {
         // We need `StateMachine` components (metrics/logger)
         // to create a bitcoin/dogecoin adapter (if applicable).
         if let Some(bitcoin_adapter_uds_path) = bitcoin_adapter_uds_path {
-            self._bitcoin_adapter_parts = Some(BitcoinAdapterParts::new(
-                self.bitcoind_addr.clone().unwrap(),
+            self.bitcoin_adapter_parts = Some(BitcoinAdapterParts::new(
+                self.bitcoind_addr.clone().unwrap_or_default(),
+                self.bitcoin_local_chain.clone(),
                 bitcoin_adapter_uds_path,
                 AdapterNetwork::Bitcoin(BitcoinAdapterNetwork::Regtest),
                 self.log_level,
                 sm.replica_logger.clone(),
                 sm.metrics_registry.clone(),
-            ));
+            )?);
         }
         if let Some(dogecoin_adapter_uds_path) = dogecoin_adapter_uds_path {
-            self._dogecoin_adapter_parts = Some(BitcoinAdapterParts::new(
-                self.dogecoind_addr.clone().unwrap(),
+            self.dogecoin_adapter_parts = Some(BitcoinAdapterParts::new(
+                self.dogecoind_addr.clone().unwrap_or_default(),
+                self.dogecoin_local_chain.clone(),
                 dogecoin_adapter_uds_path,
                 AdapterNetwork::Dogecoin(DogecoinAdapterNetwork::Regtest),
                 self.log_level,
                 sm.replica_logger.clone(),
                 sm.metrics_registry.clone(),
-            ));
+            )?);
         }
 
         // Update the routing table.
@@ -1079,6 +1210,7 @@ impl PocketIcSubnets {
                 wasm_memory_limit: Some(3_221_225_472_u64.into()),
                 wasm_memory_threshold: Some(0_u64.into()),
                 environment_variables: None,
+                controller_roles: None,
             };
             let canister_id = nns_subnet.state_machine.create_canister_with_cycles(
                 Some(REGISTRY_CANISTER_ID.get()),
@@ -1162,6 +1294,7 @@ impl PocketIcSubnets {
                 wasm_memory_limit: Some(3_221_225_472_u64.into()),
                 wasm_memory_threshold: Some(0_u64.into()),
                 environment_variables: None,
+                controller_roles: None,
             };
             let canister_id = nns_subnet.state_machine.create_canister_with_cycles(
                 Some(CYCLES_MINTING_CANISTER_ID.get()),
@@ -1332,6 +1465,7 @@ impl PocketIcSubnets {
                 wasm_memory_limit: Some(3_221_225_472_u64.into()),
                 wasm_memory_threshold: Some(0_u64.into()),
                 environment_variables: None,
+                controller_roles: None,
             };
             let canister_id = nns_subnet.state_machine.create_canister_with_cycles(
                 Some(LEDGER_CANISTER_ID.get()),
@@ -1413,6 +1547,7 @@ impl PocketIcSubnets {
                 wasm_memory_limit: Some(3_221_225_472_u64.into()),
                 wasm_memory_threshold: Some(0_u64.into()),
                 environment_variables: None,
+                controller_roles: None,
             };
             let canister_id = nns_subnet.state_machine.create_canister_with_cycles(
                 Some(LEDGER_INDEX_CANISTER_ID.get()),
@@ -1478,6 +1613,7 @@ impl PocketIcSubnets {
                 wasm_memory_limit: Some(3_221_225_472_u64.into()),
                 wasm_memory_threshold: Some(0_u64.into()),
                 environment_variables: None,
+                controller_roles: None,
             };
             let canister_id = ii_subnet.state_machine.create_canister_with_cycles(
                 Some(CYCLES_LEDGER_CANISTER_ID.get()),
@@ -1542,6 +1678,7 @@ impl PocketIcSubnets {
                 wasm_memory_limit: Some(3_221_225_472_u64.into()),
                 wasm_memory_threshold: Some(0_u64.into()),
                 environment_variables: None,
+                controller_roles: None,
             };
             let canister_id = ii_subnet.state_machine.create_canister_with_cycles(
                 Some(CYCLES_LEDGER_INDEX_CANISTER_ID.get()),
@@ -1609,6 +1746,7 @@ impl PocketIcSubnets {
                 wasm_memory_limit: Some(4_294_967_296_u64.into()),
                 wasm_memory_threshold: Some(0_u64.into()),
                 environment_variables: None,
+                controller_roles: None,
             };
             let canister_id = nns_subnet.state_machine.create_canister_with_cycles(
                 Some(GOVERNANCE_CANISTER_ID.get()),
@@ -1686,6 +1824,7 @@ impl PocketIcSubnets {
                 wasm_memory_limit: Some(3_221_225_472_u64.into()),
                 wasm_memory_threshold: Some(0_u64.into()),
                 environment_variables: None,
+                controller_roles: None,
             };
             let canister_id = nns_subnet.state_machine.create_canister_with_cycles(
                 Some(ROOT_CANISTER_ID.get()),
@@ -1753,6 +1892,7 @@ impl PocketIcSubnets {
                 wasm_memory_limit: Some(3_221_225_472_u64.into()),
                 wasm_memory_threshold: Some(0_u64.into()),
                 environment_variables: None,
+                controller_roles: None,
             };
             let canister_id = nns_subnet.state_machine.create_canister_with_cycles(
                 Some(SNS_WASM_CANISTER_ID.get()),
@@ -1851,6 +1991,7 @@ impl PocketIcSubnets {
                 wasm_memory_limit: Some(3_221_225_472_u64.into()),
                 wasm_memory_threshold: Some(0_u64.into()),
                 environment_variables: None,
+                controller_roles: None,
             };
             let canister_id = sns_subnet.state_machine.create_canister_with_cycles(
                 Some(SNS_AGGREGATOR_CANISTER_ID.get()),
@@ -1924,6 +2065,7 @@ impl PocketIcSubnets {
                 wasm_memory_limit: Some(3_221_225_472_u64.into()),
                 wasm_memory_threshold: Some(0_u64.into()),
                 environment_variables: None,
+                controller_roles: None,
             };
             let canister_id = ii_subnet.state_machine.create_canister_with_cycles(
                 Some(IDENTITY_CANISTER_ID.get()),
@@ -2101,6 +2243,7 @@ impl PocketIcSubnets {
                 wasm_memory_limit: Some(3_221_225_472_u64.into()),
                 wasm_memory_threshold: Some(0_u64.into()),
                 environment_variables: None,
+                controller_roles: None,
             };
             let canister_id = nns_subnet.state_machine.create_canister_with_cycles(
                 Some(NNS_UI_CANISTER_ID.get()),
@@ -2196,6 +2339,7 @@ impl PocketIcSubnets {
                 wasm_memory_limit: Some(2_000_000_000_u64.into()),
                 wasm_memory_threshold: Some(0_u64.into()),
                 environment_variables: None,
+                controller_roles: None,
             };
             let canister_id = btc_subnet.state_machine.create_canister_with_cycles(
                 Some(BITCOIN_TESTNET_CANISTER_ID.get()),
@@ -2269,6 +2413,7 @@ impl PocketIcSubnets {
                 wasm_memory_limit: Some(3_221_225_472_u64.into()),
                 wasm_memory_threshold: Some(0_u64.into()),
                 environment_variables: None,
+                controller_roles: None,
             };
             let canister_id = btc_subnet.state_machine.create_canister_with_cycles(
                 Some(DOGECOIN_CANISTER_ID.get()),
@@ -2334,6 +2479,7 @@ impl PocketIcSubnets {
                 wasm_memory_limit: Some(3_221_225_472_u64.into()),
                 wasm_memory_threshold: Some(0_u64.into()),
                 environment_variables: None,
+                controller_roles: None,
             };
             let canister_id = nns_subnet.state_machine.create_canister_with_cycles(
                 Some(MIGRATION_CANISTER_ID.get()),
@@ -2552,6 +2698,35 @@ impl Drop for PocketIc {
 }
 
 impl PocketIc {
+    /// Writes the current state of this instance into the given (empty) directory
+    /// so that new instances can be created from that directory later.
+    /// The state of every subnet is checkpointed by executing a round.
+    pub(crate) fn save_state(&self, state_dir: &Path) -> Result<(), String> {
+        let subnets = self.subnets.get_all();
+        for subnet in &subnets {
+            subnet.state_machine.checkpointed_tick();
+        }
+        for subnet in &subnets {
+            subnet.state_machine.await_state_hash();
+        }
+        for config in &self.subnets.subnet_configs {
+            let state_machine = self.subnets.get(config.subnet_id).unwrap();
+            let subnet_seed = compute_subnet_seed(config.ranges.clone(), config.alloc_range);
+            copy_dir(
+                state_machine.state_manager.state_layout().raw_path(),
+                state_dir.join(hex::encode(subnet_seed)),
+            )
+            .map_err(|e| format!("Could not copy subnet state: {}", e))?;
+        }
+        self.subnets
+            .registry_data_provider
+            .write_to_file(state_dir.join("registry.proto"));
+        self.subnets
+            .write_topology(state_dir, self.default_effective_canister_id);
+        self.subnets.write_fault_injection(state_dir);
+        Ok(())
+    }
+
     pub(crate) fn topology(&self) -> Topology {
         let mut subnet_configs = BTreeMap::new();
         for config in self.subnets.subnet_configs.iter() {
@@ -2587,6 +2762,8 @@ impl PocketIc {
         log_level: Option<Level>,
         bitcoind_addr: Option<Vec<SocketAddr>>,
         dogecoind_addr: Option<Vec<SocketAddr>>,
+        bitcoin_local_chain: Option<LocalChainConfig>,
+        dogecoin_local_chain: Option<LocalChainConfig>,
         icp_features: Option<IcpFeatures>,
         incomplete_state: Option<IncompleteStateFlag>,
         initial_time: Option<Time>,
@@ -2735,31 +2912,11 @@ impl PocketIc {
                         }
                     };
 
-                    let metadata = {
-                        // We create a temporary state manager used to read the given state metadata.
-                        // We first copy the subnet state directory into a temporary directory
-                        // so that the temporary state manager has a private copy
-                        // of the subnet state directory (otherwise, it might crash).
-                        let temp_state_dir = TempDir::new().unwrap();
-                        copy_dir(subnet_state_dir, temp_state_dir.path())
-                            .expect("Failed to copy state directory");
-                        let state_manager = StateManagerImpl::new(
-                            Arc::new(FakeVerifier),
-                            SubnetId::new(PrincipalId::default()),
-                            conv_type(subnet_kind),
-                            no_op_logger(),
-                            &MetricsRegistry::new(),
-                            &ic_config::state_manager::Config::new(
-                                temp_state_dir.path().to_path_buf(),
-                            ),
-                            None,
-                            MaliciousFlags::default(),
-                        );
-                        let metadata = state_manager.get_latest_state().take().metadata.clone();
-                        // Shut down the temporary state manager to avoid race conditions.
-                        state_manager.flush_tip_channel();
-                        metadata
-                    };
+                    let metadata =
+                        with_latest_state(subnet_state_dir, conv_type(subnet_kind), |state| {
+                            state.metadata.clone()
+                        })
+                        .expect("Failed to copy state directory");
 
                     let subnet_id = metadata.own_subnet_id;
                     let ranges: Vec<_> = metadata
@@ -2849,6 +3006,8 @@ impl PocketIc {
             log_level,
             bitcoind_addr,
             dogecoind_addr,
+            bitcoin_local_chain,
+            dogecoin_local_chain,
             icp_features,
             initial_time,
             auto_progress_enabled,
@@ -3644,24 +3803,27 @@ pub struct CanisterSnapshotDownload {
     pub snapshot_dir: PathBuf,
 }
 
-fn ensure_empty_dir(path: &Path) -> Result<(), String> {
+fn ensure_empty_dir(path: &Path, name: &str) -> Result<(), String> {
     // Create the directory if needed (including parents).
     // Succeeds silently if the directory already exists.
     std::fs::create_dir_all(path)
-        .map_err(|e| format!("Could not create snapshot directory: {}", e))?;
+        .map_err(|e| format!("Could not create {} directory: {}", name.to_lowercase(), e))?;
 
     // Now ensure it's actually a directory.
     if !path.is_dir() {
-        return Err("Snapshot directory path exists but is not a directory".to_string());
+        return Err(format!(
+            "{} directory path exists but is not a directory",
+            name
+        ));
     }
 
     // Check if it is empty.
     if std::fs::read_dir(path)
-        .map_err(|e| format!("Could not read snapshot directory: {}", e))?
+        .map_err(|e| format!("Could not read {} directory: {}", name.to_lowercase(), e))?
         .next()
         .is_some()
     {
-        return Err("Snapshot directory is not empty".to_string());
+        return Err(format!("{} directory is not empty", name));
     }
 
     Ok(())
@@ -3735,7 +3897,7 @@ impl Operation for CanisterSnapshotDownload {
         let subnet = route(pic, effective_principal, false);
         match subnet {
             Ok(subnet) => {
-                if let Err(e) = ensure_empty_dir(&self.snapshot_dir) {
+                if let Err(e) = ensure_empty_dir(&self.snapshot_dir, "Snapshot") {
                     return OpOut::Error(PocketIcError::InvalidCanisterSnapshotDirectory(e));
                 }
 
@@ -4025,6 +4187,456 @@ impl Operation for CanisterSnapshotUpload {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct SaveState {
+    pub state_dir: PathBuf,
+}
+
+impl Operation for SaveState {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        if let Err(e) = ensure_empty_dir(&self.state_dir, "State") {
+            return OpOut::Error(PocketIcError::InvalidStateDirectory(e));
+        }
+        match pic.save_state(&self.state_dir) {
+            Ok(()) => OpOut::NoOutput,
+            Err(e) => OpOut::Error(PocketIcError::InvalidStateDirectory(e)),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "save_state(state_dir='{}')",
+            self.state_dir.display()
+        ))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DiffStates {
+    pub from_state_dir: PathBuf,
+    pub to_state_dir: PathBuf,
+}
+
+/// Returns the directory and type of every subnet in the state saved in the given directory,
+/// keyed by the (hex-encoded) subnet seed.
+fn saved_subnets(state_dir: &Path) -> Result<BTreeMap<String, (PathBuf, SubnetType)>, String> {
+    let topology_file = File::open(state_dir.join("topology.json"))
+        .map_err(|e| format!("Could not open topology file: {}", e))?;
+    let topology: RawTopologyInternal = serde_json::from_reader(BufReader::new(topology_file))
+        .map_err(|e| format!("Could not parse topology file: {}", e))?;
+    Ok(topology
+        .subnet_configs
+        .into_iter()
+        .map(|config| {
+            let subnet_seed = hex::encode(compute_subnet_seed(config.ranges, config.alloc_range));
+            let subnet_state_dir = state_dir.join(&subnet_seed);
+            (
+                subnet_seed,
+                (subnet_state_dir, conv_type(config.subnet_kind)),
+            )
+        })
+        .collect())
+}
+
+/// Applies `f` to the latest state of the given saved subnet,
+/// or to an empty state if the subnet does not exist in the saved state.
+fn with_saved_subnet_state<T>(
+    subnet: Option<&(PathBuf, SubnetType)>,
+    f: impl FnOnce(&ReplicatedState) -> T,
+) -> Result<T, String> {
+    match subnet {
+        Some((subnet_state_dir, subnet_type)) => {
+            with_latest_state(subnet_state_dir, *subnet_type, f).map_err(|e| {
+                format!(
+                    "Could not read subnet state at {}: {}",
+                    subnet_state_dir.display(),
+                    e
+                )
+            })
+        }
+        None => Ok(f(&ReplicatedState::new(
+            SubnetId::new(PrincipalId::default()),
+            SubnetType::Application,
+        ))),
+    }
+}
+
+/// Converts the semantic diff of a subnet state into the diffs of the individual canisters.
+fn canister_state_diffs(state_diff: StateDiff) -> Vec<RawCanisterStateDiff> {
+    let raw_canister_id = |canister_id: &str| -> RawCanisterId {
+        Principal::from_text(canister_id)
+            .expect("State diff contains an invalid canister ID")
+            .into()
+    };
+    let value_change = |change: FieldChange| CanisterStateChange {
+        field: change.field,
+        before: Some(change.before),
+        after: Some(change.after),
+    };
+    let described_change = |field: &str, description: String| CanisterStateChange {
+        field: field.to_string(),
+        before: None,
+        after: Some(description),
+    };
+
+    let mut diff = vec![];
+    for canister_id in state_diff.added_canisters {
+        diff.push(RawCanisterStateDiff {
+            canister_id: raw_canister_id(&canister_id),
+            kind: CanisterStateDiffKind::Created,
+            changes: vec![],
+        });
+    }
+    for canister_id in state_diff.removed_canisters {
+        diff.push(RawCanisterStateDiff {
+            canister_id: raw_canister_id(&canister_id),
+            kind: CanisterStateDiffKind::Deleted,
+            changes: vec![],
+        });
+    }
+    for canister in state_diff.canisters {
+        let mut changes: Vec<_> = canister.settings.into_iter().map(value_change).collect();
+        if canister.cycles_balance_delta != 0 {
+            changes.push(described_change(
+                "cycles_balance",
+                format!("{:+}", canister.cycles_balance_delta),
+            ));
+        }
+        changes.extend(canister.module_hash.map(value_change));
+        if let Some(memory_diff) = canister.wasm_memory {
+            changes.push(described_change("wasm_memory", memory_diff.to_string()));
+        }
+        if let Some(memory_diff) = canister.stable_memory {
+            changes.push(described_change("stable_memory", memory_diff.to_string()));
+        }
+        changes.extend(canister.queues.into_iter().map(value_change));
+        for snapshot_id in canister.added_snapshots {
+            changes.push(described_change("snapshots", format!("+{}", snapshot_id)));
+        }
+        for snapshot_id in canister.removed_snapshots {
+            changes.push(described_change("snapshots", format!("-{}", snapshot_id)));
+        }
+        diff.push(RawCanisterStateDiff {
+            canister_id: raw_canister_id(&canister.canister_id),
+            kind: CanisterStateDiffKind::Modified,
+            changes,
+        });
+    }
+    diff
+}
+
+/// Computes the differences between the canisters in the two saved states
+/// by diffing the states of every subnet.
+fn diff_saved_states(
+    from_state_dir: &Path,
+    to_state_dir: &Path,
+) -> Result<Vec<RawCanisterStateDiff>, String> {
+    let from_subnets = saved_subnets(from_state_dir)?;
+    let to_subnets = saved_subnets(to_state_dir)?;
+    let subnet_seeds: BTreeSet<_> = from_subnets.keys().chain(to_subnets.keys()).collect();
+    let mut diff = vec![];
+    for subnet_seed in subnet_seeds {
+        let state_diff = with_saved_subnet_state(from_subnets.get(subnet_seed), |before| {
+            with_saved_subnet_state(to_subnets.get(subnet_seed), |after| {
+                diff_states(before, after)
+            })
+        })??;
+        diff.extend(canister_state_diffs(state_diff));
+    }
+    Ok(diff)
+}
+
+impl Operation for DiffStates {
+    fn compute(&self, _pic: &mut PocketIc) -> OpOut {
+        match diff_saved_states(&self.from_state_dir, &self.to_state_dir) {
+            Ok(diff) => OpOut::StateDiff(diff),
+            Err(e) => OpOut::Error(PocketIcError::InvalidStateDirectory(e)),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "diff_states(from_state_dir='{}',to_state_dir='{}')",
+            self.from_state_dir.display(),
+            self.to_state_dir.display()
+        ))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AddFaultInjectionRule {
+    pub rule: FaultInjectionRule,
+}
+
+const FAULT_INJECTION_DISABLED: &str =
+    "Fault injection must be enabled in the ICP config of the PocketIC instance.";
+
+impl Operation for AddFaultInjectionRule {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        match pic.subnets.fault_injection {
+            Some(ref fault_injection) => {
+                fault_injection.add_rule(self.rule.clone());
+                OpOut::NoOutput
+            }
+            None => OpOut::Error(PocketIcError::Forbidden(
+                FAULT_INJECTION_DISABLED.to_string(),
+            )),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("add_fault_injection_rule({:?})", self.rule))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ClearFaultInjectionRules;
+
+impl Operation for ClearFaultInjectionRules {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        match pic.subnets.fault_injection {
+            Some(ref fault_injection) => {
+                fault_injection.clear_rules();
+                OpOut::NoOutput
+            }
+            None => OpOut::Error(PocketIcError::Forbidden(
+                FAULT_INJECTION_DISABLED.to_string(),
+            )),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId("clear_fault_injection_rules".to_string())
+    }
+}
+
+const EXECUTION_PROFILING_DISABLED: &str =
+    "Execution profiling must be enabled in the ICP config of the PocketIC instance.";
+
+/// Returns the cycles consumed by every canister (on all subnets) by use case.
+fn consumed_cycles_by_use_case(
+    pic: &PocketIc,
+) -> BTreeMap<CanisterId, BTreeMap<CyclesUseCase, u128>> {
+    let mut consumed_cycles = BTreeMap::new();
+    for subnet in pic.subnets.get_all() {
+        let state = subnet.state_machine.get_latest_state();
+        for canister in state.canisters_iter() {
+            let by_use_case = canister
+                .system_state
+                .canister_metrics
+                .get_consumed_cycles_by_use_cases()
+                .iter()
+                .map(|(use_case, cycles)| (*use_case, cycles.get()))
+                .collect();
+            consumed_cycles.insert(canister.canister_id(), by_use_case);
+        }
+    }
+    consumed_cycles
+}
+
+#[derive(Clone, Debug)]
+pub struct StartProfiling;
+
+impl Operation for StartProfiling {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let mut profilers = vec![];
+        for subnet in pic.subnets.get_all() {
+            match subnet.state_machine.execution_profiler() {
+                Some(profiler) => profilers.push(profiler),
+                None => {
+                    return OpOut::Error(PocketIcError::Forbidden(
+                        EXECUTION_PROFILING_DISABLED.to_string(),
+                    ));
+                }
+            }
+        }
+        let consumed_cycles = consumed_cycles_by_use_case(pic);
+        pic.subnets.profiling_cycles = Some(consumed_cycles);
+        for profiler in profilers {
+            profiler.start();
+        }
+        OpOut::NoOutput
+    }
+
+    fn id(&self) -> OpId {
+        OpId("start_profiling".to_string())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StopProfiling;
+
+impl Operation for StopProfiling {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let Some(cycles_before) = pic.subnets.profiling_cycles.take() else {
+            return OpOut::Error(PocketIcError::Forbidden(
+                "Profiling has not been started.".to_string(),
+            ));
+        };
+        let mut executions = vec![];
+        for subnet in pic.subnets.get_all() {
+            if let Some(profiler) = subnet.state_machine.execution_profiler() {
+                executions.extend(profiler.stop().into_iter().map(|profile| {
+                    RawExecutionProfile {
+                        canister_id: profile.canister_id.get().0.into(),
+                        api_type: profile.api_type.to_string(),
+                        function: profile.function,
+                        instructions: profile.instructions.get(),
+                        system_api_call_counts: profile
+                            .system_api_call_counters
+                            .calls_by_function
+                            .into_iter()
+                            .map(|(function, count)| (function, count as u64))
+                            .collect(),
+                        dirty_heap_bytes: profile.dirty_heap_bytes.get(),
+                        dirty_stable_memory_bytes: profile.dirty_stable_memory_bytes.get(),
+                    }
+                }));
+            }
+        }
+        let mut cycles = vec![];
+        for (canister_id, cycles_after) in consumed_cycles_by_use_case(pic) {
+            let before = cycles_before.get(&canister_id);
+            let cycles_by_use_case: BTreeMap<String, u128> = cycles_after
+                .into_iter()
+                .filter_map(|(use_case, after)| {
+                    let before = before
+                        .and_then(|before| before.get(&use_case))
+                        .copied()
+                        .unwrap_or_default();
+                    let consumed = after.saturating_sub(before);
+                    (consumed > 0).then(|| (format!("{use_case:?}"), consumed))
+                })
+                .collect();
+            if !cycles_by_use_case.is_empty() {
+                cycles.push(RawCanisterCyclesProfile {
+                    canister_id: canister_id.get().0.into(),
+                    cycles_by_use_case,
+                });
+            }
+        }
+        OpOut::CallProfile(RawCallProfile { executions, cycles })
+    }
+
+    fn id(&self) -> OpId {
+        OpId("stop_profiling".to_string())
+    }
+}
+
+fn local_chain(pic: &PocketIc, chain: LocalChain) -> Result<LocalChainHandle, OpOut> {
+    let adapter_parts = match chain {
+        LocalChain::Bitcoin => &pic.subnets.bitcoin_adapter_parts,
+        LocalChain::Dogecoin => &pic.subnets.dogecoin_adapter_parts,
+    };
+    adapter_parts
+        .as_ref()
+        .and_then(|adapter_parts| adapter_parts.local_chain.clone())
+        .ok_or_else(|| {
+            OpOut::Error(PocketIcError::Forbidden(format!(
+                "The {chain:?} local chain is not configured for this instance."
+            )))
+        })
+}
+
+fn local_chain_height(count: u64) -> Result<u32, OpOut> {
+    u32::try_from(count).map_err(|_| {
+        OpOut::Error(PocketIcError::LocalChainError(format!(
+            "The number of blocks {count} exceeds the maximum block height."
+        )))
+    })
+}
+
+fn local_chain_ids<T: ToString, E: std::fmt::Display>(result: Result<Vec<T>, E>) -> OpOut {
+    match result {
+        Ok(ids) => OpOut::LocalChainIds(ids.iter().map(|id| id.to_string()).collect()),
+        Err(err) => OpOut::Error(PocketIcError::LocalChainError(err.to_string())),
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MineBlocks {
+    pub chain: LocalChain,
+    pub count: u64,
+    pub coinbase_address: Option<String>,
+}
+
+impl Operation for MineBlocks {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let local_chain = match local_chain(pic, self.chain) {
+            Ok(local_chain) => local_chain,
+            Err(err) => return err,
+        };
+        let count = match local_chain_height(self.count) {
+            Ok(count) => count,
+            Err(err) => return err,
+        };
+        let result = match &self.coinbase_address {
+            Some(address) => pic
+                .runtime
+                .block_on(local_chain.mine_blocks_to_address(count, address.clone())),
+            None => pic.runtime.block_on(local_chain.mine_blocks(count)),
+        };
+        local_chain_ids(result)
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "mine_blocks({:?},{},{:?})",
+            self.chain, self.count, self.coinbase_address
+        ))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ReorgBlocks {
+    pub chain: LocalChain,
+    pub depth: u64,
+    pub count: u64,
+}
+
+impl Operation for ReorgBlocks {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let local_chain = match local_chain(pic, self.chain) {
+            Ok(local_chain) => local_chain,
+            Err(err) => return err,
+        };
+        let (depth, count) = match (
+            local_chain_height(self.depth),
+            local_chain_height(self.count),
+        ) {
+            (Ok(depth), Ok(count)) => (depth, count),
+            (Err(err), _) | (_, Err(err)) => return err,
+        };
+        local_chain_ids(pic.runtime.block_on(local_chain.reorg(depth, count)))
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "reorg_blocks({:?},{},{})",
+            self.chain, self.depth, self.count
+        ))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GetMempool {
+    pub chain: LocalChain,
+}
+
+impl Operation for GetMempool {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let local_chain = match local_chain(pic, self.chain) {
+            Ok(local_chain) => local_chain,
+            Err(err) => return err,
+        };
+        local_chain_ids(pic.runtime.block_on(local_chain.mempool()))
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("get_mempool({:?})", self.chain))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SubmitIngressMessage(pub CanisterCall);
 
@@ -5270,6 +5882,8 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
                 false,
                 None,
             )
@@ -5290,6 +5904,8 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
                 false,
                 None,
             )
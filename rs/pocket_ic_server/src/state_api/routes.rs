@@ -9,11 +9,13 @@ use super::state::{
     ApiState, DEFAULT_SYNC_WAIT_DURATION, OpOut, PocketIcError, StateLabel, UpdateReply,
 };
 use crate::pocket_ic::{
-    AddCycles, AwaitIngressMessage, CallRequest, CallRequestVersion, CanisterReadStateRequest,
-    CanisterSnapshotDownload, CanisterSnapshotUpload, DashboardRequest, GetCanisterHttp,
-    GetControllers, GetCyclesBalance, GetStableMemory, GetSubnet, GetTime, GetTopology,
-    IngressMessageStatus, MockCanisterHttp, PubKey, Query, QueryRequest, SetCertifiedTime,
-    SetStableMemory, SetTime, StatusRequest, SubmitIngressMessage, SubnetReadStateRequest, Tick,
+    AddCycles, AddFaultInjectionRule, AwaitIngressMessage, CallRequest, CallRequestVersion,
+    CanisterReadStateRequest, CanisterSnapshotDownload, CanisterSnapshotUpload,
+    ClearFaultInjectionRules, DashboardRequest, DiffStates, GetCanisterHttp, GetControllers,
+    GetCyclesBalance, GetMempool, GetStableMemory, GetSubnet, GetTime, GetTopology,
+    IngressMessageStatus, MineBlocks, MockCanisterHttp, PubKey, Query, QueryRequest, ReorgBlocks,
+    SaveState, SetCertifiedTime, SetStableMemory, SetTime, StartProfiling, StatusRequest,
+    StopProfiling, SubmitIngressMessage, SubnetReadStateRequest, Tick,
 };
 use crate::{BlobStore, InstanceId, OpId, Operation, async_trait, pocket_ic::PocketIc};
 use aide::{
@@ -37,16 +39,18 @@ use backoff::{ExponentialBackoff, ExponentialBackoffBuilder};
 use ic_boundary::{ErrorClientFacing, MAX_REQUEST_BODY_SIZE};
 use ic_http_endpoints_public::{cors_layer, make_plaintext_response, query, read_state};
 use ic_registry_routing_table::RoutingTable;
+use ic_state_machine_tests::{FaultInjectionAction, FaultInjectionRule};
 use ic_types::{CanisterId, SnapshotId, SubnetId};
 use pocket_ic::RejectResponse;
 use pocket_ic::common::rest::{
     self, ApiResponse, AutoProgressConfig, ExtendedSubnetConfigSet, HttpGatewayConfig,
     HttpGatewayDetails, IcpConfig, IcpFeatures, InitialTime, InstanceConfig,
-    MockCanisterHttpResponse, RawAddCycles, RawCanisterCall, RawCanisterHttpRequest, RawCanisterId,
-    RawCanisterResult, RawCanisterSnapshotDownload, RawCanisterSnapshotId,
-    RawCanisterSnapshotUpload, RawCycles, RawIngressStatusArgs, RawMessageId,
-    RawMockCanisterHttpResponse, RawPrincipalId, RawSetStableMemory, RawStableMemory, RawSubnetId,
-    RawTime, TickConfigs, Topology,
+    MockCanisterHttpResponse, RawAddCycles, RawCallProfile, RawCanisterCall,
+    RawCanisterHttpRequest, RawCanisterId, RawCanisterResult, RawCanisterSnapshotDownload,
+    RawCanisterSnapshotId, RawCanisterSnapshotUpload, RawCanisterStateDiff, RawCycles,
+    RawDiffStates, RawFaultInjectionRule, RawIngressStatusArgs, RawLocalChain, RawLocalChainIds,
+    RawMessageId, RawMineBlocks, RawMockCanisterHttpResponse, RawPrincipalId, RawReorgBlocks,
+    RawSaveState, RawSetStableMemory, RawStableMemory, RawSubnetId, RawTime, TickConfigs, Topology,
 };
 use serde::Serialize;
 use slog::Level;
@@ -113,6 +117,8 @@ where
         .directory_route("/get_subnet", post(handler_get_subnet))
         .directory_route("/pub_key", post(handler_pub_key))
         .directory_route("/ingress_status", post(handler_ingress_status))
+        .directory_route("/diff_states", post(handler_diff_states))
+        .directory_route("/get_mempool", post(handler_get_mempool))
 }
 
 pub fn instance_update_routes<S>() -> ApiRouter<S>
@@ -143,6 +149,19 @@ where
             "/canister_snapshot_upload",
             post(handler_canister_snapshot_upload),
         )
+        .directory_route("/save_state", post(handler_save_state))
+        .directory_route(
+            "/add_fault_injection_rule",
+            post(handler_add_fault_injection_rule),
+        )
+        .directory_route(
+            "/clear_fault_injection_rules",
+            post(handler_clear_fault_injection_rules),
+        )
+        .directory_route("/start_profiling", post(handler_start_profiling))
+        .directory_route("/stop_profiling", post(handler_stop_profiling))
+        .directory_route("/mine_blocks", post(handler_mine_blocks))
+        .directory_route("/reorg_blocks", post(handler_reorg_blocks))
 }
 
 async fn handle_limit_error(req: Request, next: Next) -> Response {
@@ -582,6 +601,36 @@ impl TryFrom<OpOut> for RawCanisterSnapshotId {
     }
 }
 
+impl TryFrom<OpOut> for Vec<RawCanisterStateDiff> {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::StateDiff(diff) => Ok(diff),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
+impl TryFrom<OpOut> for RawCallProfile {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::CallProfile(profile) => Ok(profile),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
+impl TryFrom<OpOut> for RawLocalChainIds {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::LocalChainIds(ids) => Ok(RawLocalChainIds { ids }),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
 #[async_trait]
 impl FromOpOut for PocketHttpResponse {
     async fn from(value: OpOut) -> (StatusCode, ApiResponse<PocketHttpResponse>) {
@@ -886,6 +935,170 @@ pub async fn handler_canister_snapshot_upload(
     (code, Json(response))
 }
 
+pub async fn handler_save_state(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+    axum::extract::Json(RawSaveState { state_dir }): axum::extract::Json<RawSaveState>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    let timeout = timeout_or_default(headers);
+    let op = SaveState { state_dir };
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
+pub async fn handler_diff_states(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+    axum::extract::Json(RawDiffStates {
+        from_state_dir,
+        to_state_dir,
+    }): axum::extract::Json<RawDiffStates>,
+) -> (StatusCode, Json<ApiResponse<Vec<RawCanisterStateDiff>>>) {
+    let timeout = timeout_or_default(headers);
+    let op = DiffStates {
+        from_state_dir,
+        to_state_dir,
+    };
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
+fn try_from_raw_fault_injection_rule(
+    raw_rule: RawFaultInjectionRule,
+) -> Result<FaultInjectionRule, String> {
+    let to_canister_id = |raw_canister_id: Option<RawCanisterId>| {
+        raw_canister_id
+            .map(|raw_canister_id| CanisterId::try_from(raw_canister_id.canister_id))
+            .transpose()
+            .map_err(|e| format!("{e:?}"))
+    };
+    let action = match raw_rule.action {
+        rest::FaultInjectionAction::Reject {
+            reject_code,
+            message,
+        } => FaultInjectionAction::Reject {
+            code: ic_error_types::RejectCode::try_from(reject_code as u64)
+                .map_err(|e| format!("{e:?}"))?,
+            message,
+        },
+        rest::FaultInjectionAction::Timeout => FaultInjectionAction::Timeout,
+        rest::FaultInjectionAction::Delay { rounds } => FaultInjectionAction::Delay { rounds },
+    };
+    Ok(FaultInjectionRule {
+        caller: to_canister_id(raw_rule.caller)?,
+        callee: to_canister_id(raw_rule.callee)?,
+        method_name: raw_rule.method,
+        action,
+    })
+}
+
+pub async fn handler_add_fault_injection_rule(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+    axum::extract::Json(raw_rule): axum::extract::Json<RawFaultInjectionRule>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    let timeout = timeout_or_default(headers);
+    match try_from_raw_fault_injection_rule(raw_rule) {
+        Ok(rule) => {
+            let op = AddFaultInjectionRule { rule };
+            let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+            (code, Json(response))
+        }
+        Err(message) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::Error { message }),
+        ),
+    }
+}
+
+pub async fn handler_clear_fault_injection_rules(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    let timeout = timeout_or_default(headers);
+    let op = ClearFaultInjectionRules;
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
+pub async fn handler_start_profiling(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    let timeout = timeout_or_default(headers);
+    let op = StartProfiling;
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
+pub async fn handler_stop_profiling(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+) -> (StatusCode, Json<ApiResponse<RawCallProfile>>) {
+    let timeout = timeout_or_default(headers);
+    let op = StopProfiling;
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
+pub async fn handler_mine_blocks(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+    extract::Json(RawMineBlocks {
+        chain,
+        count,
+        coinbase_address,
+    }): extract::Json<RawMineBlocks>,
+) -> (StatusCode, Json<ApiResponse<RawLocalChainIds>>) {
+    let timeout = timeout_or_default(headers);
+    let op = MineBlocks {
+        chain,
+        count,
+        coinbase_address,
+    };
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
+pub async fn handler_reorg_blocks(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+    extract::Json(RawReorgBlocks {
+        chain,
+        depth,
+        count,
+    }): extract::Json<RawReorgBlocks>,
+) -> (StatusCode, Json<ApiResponse<RawLocalChainIds>>) {
+    let timeout = timeout_or_default(headers);
+    let op = ReorgBlocks {
+        chain,
+        depth,
+        count,
+    };
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
+pub async fn handler_get_mempool(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+    extract::Json(RawLocalChain { chain }): extract::Json<RawLocalChain>,
+) -> (StatusCode, Json<ApiResponse<RawLocalChainIds>>) {
+    let timeout = timeout_or_default(headers);
+    let op = GetMempool { chain };
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
 pub async fn handler_dashboard(
     State(AppState { api_state, .. }): State<AppState>,
     NoApi(Path(instance_id)): NoApi<Path<InstanceId>>,
@@ -1507,6 +1720,30 @@ pub async fn create_instance(
     };
     let auto_progress_enabled = auto_progress.is_some();
 
+    for (addr, local_chain, chain) in [
+        (
+            &instance_config.bitcoind_addr,
+            &instance_config.bitcoin_local_chain,
+            "bitcoin",
+        ),
+        (
+            &instance_config.dogecoind_addr,
+            &instance_config.dogecoin_local_chain,
+            "dogecoin",
+        ),
+    ] {
+        if addr.is_some() && local_chain.is_some() {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(rest::CreateInstanceResponse::Error {
+                    message: format!(
+                        "The fields `{chain}d_addr` and `{chain}_local_chain` cannot be specified at the same time."
+                    ),
+                }),
+            );
+        }
+    }
+
     if let Some(ref icp_features) = instance_config.icp_features {
         // using `let IcpFeatures { }` with explicit field names
         // to force an update after adding a new field to `IcpFeatures`
@@ -1571,6 +1808,8 @@ pub async fn create_instance(
                     log_level,
                     instance_config.bitcoind_addr,
                     instance_config.dogecoind_addr,
+                    instance_config.bitcoin_local_chain,
+                    instance_config.dogecoin_local_chain,
                     instance_config.icp_features,
                     instance_config.incomplete_state,
                     initial_time,
@@ -49,7 +49,8 @@ use itertools::Itertools;
 use pocket_ic::RejectResponse;
 use pocket_ic::common::rest::{
     AutoProgressConfig, CanisterHttpRequest, HttpGatewayBackend, HttpGatewayConfig,
-    HttpGatewayDetails, HttpGatewayInfo, InstanceHttpGatewayConfig, Topology,
+    HttpGatewayDetails, HttpGatewayInfo, InstanceHttpGatewayConfig, RawCallProfile,
+    RawCanisterStateDiff, Topology,
 };
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
@@ -261,6 +262,9 @@ pub enum OpOut {
     Topology(Topology),
     CanisterHttp(Vec<CanisterHttpRequest>),
     CanisterSnapshotId(Vec<u8>),
+    StateDiff(Vec<RawCanisterStateDiff>),
+    CallProfile(RawCallProfile),
+    LocalChainIds(Vec<String>),
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Deserialize, Serialize)]
@@ -280,6 +284,8 @@ pub enum PocketIcError {
     BlockmakerContainedInFailed(NodeId),
     InvalidCanisterSnapshotDirectory(String),
     CanisterSnapshotError(String),
+    InvalidStateDirectory(String),
+    LocalChainError(String),
 }
 
 impl std::fmt::Debug for OpOut {
@@ -351,6 +357,12 @@ impl std::fmt::Debug for OpOut {
             OpOut::Error(PocketIcError::CanisterSnapshotError(msg)) => {
                 write!(f, "CanisterSnapshotError({msg})")
             }
+            OpOut::Error(PocketIcError::InvalidStateDirectory(msg)) => {
+                write!(f, "InvalidStateDirectory({msg})")
+            }
+            OpOut::Error(PocketIcError::LocalChainError(msg)) => {
+                write!(f, "LocalChainError({msg})")
+            }
             OpOut::Bytes(bytes) => write!(f, "Bytes({})", base64::encode(bytes)),
             OpOut::StableMemBytes(bytes) => write!(f, "StableMemory({})", base64::encode(bytes)),
             OpOut::MaybeSubnetId(Some(subnet_id)) => write!(f, "SubnetId({subnet_id})"),
@@ -381,6 +393,9 @@ impl std::fmt::Debug for OpOut {
             OpOut::CanisterSnapshotId(snapshot_id) => {
                 write!(f, "CanisterSnapshotId({})", hex::encode(snapshot_id))
             }
+            OpOut::StateDiff(diff) => write!(f, "StateDiff({diff:?})"),
+            OpOut::CallProfile(profile) => write!(f, "CallProfile({profile:?})"),
+            OpOut::LocalChainIds(ids) => write!(f, "LocalChainIds({})", ids.join(",")),
         }
     }
 }
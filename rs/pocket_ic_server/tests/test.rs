@@ -132,6 +132,8 @@ fn test_creation_of_instance_extended() {
         log_level: None,
         bitcoind_addr: None,
         dogecoind_addr: None,
+        bitcoin_local_chain: None,
+        dogecoin_local_chain: None,
         icp_features: None,
         incomplete_state: None,
         initial_time: None,
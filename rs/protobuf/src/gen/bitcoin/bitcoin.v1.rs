@@ -69,7 +69,7 @@ pub struct SendTransactionResponse {}
 /// Wraps the different types of requests to the Bitcoin Adapter.
 #[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, ::prost::Message)]
 pub struct BitcoinAdapterRequestWrapper {
-    #[prost(oneof = "bitcoin_adapter_request_wrapper::R", tags = "3, 4")]
+    #[prost(oneof = "bitcoin_adapter_request_wrapper::R", tags = "3, 4, 5")]
     pub r: ::core::option::Option<bitcoin_adapter_request_wrapper::R>,
 }
 /// Nested message and enum types in `BitcoinAdapterRequestWrapper`.
@@ -80,12 +80,14 @@ pub mod bitcoin_adapter_request_wrapper {
         GetSuccessorsRequest(super::GetSuccessorsRequestInitial),
         #[prost(message, tag = "4")]
         SendTransactionRequest(super::SendTransactionRequest),
+        #[prost(message, tag = "5")]
+        GetBlockFiltersRequest(super::GetBlockFiltersRequest),
     }
 }
 /// Wraps the different types of responses from the Bitcoin Adapter.
 #[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, ::prost::Message)]
 pub struct BitcoinAdapterResponseWrapper {
-    #[prost(oneof = "bitcoin_adapter_response_wrapper::R", tags = "3, 4, 5, 6")]
+    #[prost(oneof = "bitcoin_adapter_response_wrapper::R", tags = "3, 4, 5, 6, 7, 8")]
     pub r: ::core::option::Option<bitcoin_adapter_response_wrapper::R>,
 }
 /// Nested message and enum types in `BitcoinAdapterResponseWrapper`.
@@ -100,6 +102,10 @@ pub mod bitcoin_adapter_response_wrapper {
         GetSuccessorsReject(super::GetSuccessorsReject),
         #[prost(message, tag = "6")]
         SendTransactionReject(super::SendTransactionReject),
+        #[prost(message, tag = "7")]
+        GetBlockFiltersResponse(super::GetBlockFiltersResponse),
+        #[prost(message, tag = "8")]
+        GetBlockFiltersReject(super::GetBlockFiltersReject),
     }
 }
 /// A Bitcoin Adapter request, used to store the requests in the
@@ -160,6 +166,39 @@ pub struct SendTransactionReject {
     #[prost(string, tag = "2")]
     pub message: ::prost::alloc::string::String,
 }
+/// A request to retrieve the BIP-158 basic filters of the given blocks.
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, ::prost::Message)]
+pub struct GetBlockFiltersRequest {
+    #[prost(enumeration = "Network", tag = "1")]
+    pub network: i32,
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub block_hashes: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "3")]
+    pub scripts: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, ::prost::Message)]
+pub struct BlockFilter {
+    #[prost(bytes = "vec", tag = "1")]
+    pub block_hash: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub filter: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bool, tag = "3")]
+    pub matches: bool,
+}
+/// A response containing the filters the adapter has verified.
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, ::prost::Message)]
+pub struct GetBlockFiltersResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub filters: ::prost::alloc::vec::Vec<BlockFilter>,
+}
+/// A `GetBlockFilters` reject response containing additional information about the rejection.
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, ::prost::Message)]
+pub struct GetBlockFiltersReject {
+    #[prost(enumeration = "super::super::types::v1::RejectCode", tag = "1")]
+    pub reject_code: i32,
+    #[prost(string, tag = "2")]
+    pub message: ::prost::alloc::string::String,
+}
 #[derive(
     serde::Serialize,
     serde::Deserialize,
@@ -249,6 +249,8 @@ pub struct ExecutionStateBits {
     pub next_scheduled_method: ::core::option::Option<i32>,
     #[prost(bool, tag = "8")]
     pub is_wasm64: bool,
+    #[prost(uint64, tag = "9")]
+    pub executed_instructions: u64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StopCanisterContext {
@@ -435,10 +437,23 @@ pub struct CanisterCodeDeployment {
     #[prost(bytes = "vec", tag = "2")]
     pub module_hash: ::prost::alloc::vec::Vec<u8>,
 }
+/// Principals holding a controller role on a canister.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ControllerRoles {
+    #[prost(message, repeated, tag = "1")]
+    pub upgraders: ::prost::alloc::vec::Vec<super::super::super::types::v1::PrincipalId>,
+    #[prost(message, repeated, tag = "2")]
+    pub operators: ::prost::alloc::vec::Vec<super::super::super::types::v1::PrincipalId>,
+    #[prost(message, repeated, tag = "3")]
+    pub observers: ::prost::alloc::vec::Vec<super::super::super::types::v1::PrincipalId>,
+}
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterControllersChange {
     #[prost(message, repeated, tag = "1")]
     pub controllers: ::prost::alloc::vec::Vec<super::super::super::types::v1::PrincipalId>,
+    /// Only present if the controller roles have been changed.
+    #[prost(message, optional, tag = "2")]
+    pub controller_roles: ::core::option::Option<ControllerRoles>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterLoadSnapshot {
@@ -550,6 +565,23 @@ pub struct TotalQueryStats {
     pub egress_payload_size: ::core::option::Option<Unsigned128>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryCacheTagInvalidation {
+    #[prost(bytes = "vec", tag = "1")]
+    pub tag: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint64, tag = "2")]
+    pub invalidation: u64,
+}
+/// Invalidations of the query cache tags of a canister.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryCacheTags {
+    #[prost(message, repeated, tag = "1")]
+    pub invalidations: ::prost::alloc::vec::Vec<QueryCacheTagInvalidation>,
+    #[prost(uint64, tag = "2")]
+    pub counter: u64,
+    #[prost(uint64, tag = "3")]
+    pub evicted_invalidation: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WasmChunkData {
     #[prost(bytes = "vec", tag = "1")]
     pub hash: ::prost::alloc::vec::Vec<u8>,
@@ -588,6 +620,13 @@ pub mod log_visibility_v2 {
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CanisterLogField {
+    #[prost(string, tag = "1")]
+    pub key: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub value: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterLogRecord {
     #[prost(uint64, tag = "1")]
     pub idx: u64,
@@ -595,6 +634,11 @@ pub struct CanisterLogRecord {
     pub timestamp_nanos: u64,
     #[prost(bytes = "vec", tag = "3")]
     pub content: ::prost::alloc::vec::Vec<u8>,
+    /// Unspecified for unstructured records (written by `ic0.debug_print` or traps).
+    #[prost(enumeration = "CanisterLogSeverity", tag = "4")]
+    pub severity: i32,
+    #[prost(message, repeated, tag = "5")]
+    pub fields: ::prost::alloc::vec::Vec<CanisterLogField>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SnapshotId {
@@ -725,6 +769,12 @@ pub struct CanisterStateBits {
         ::prost::alloc::string::String,
         ::prost::alloc::string::String,
     >,
+    /// Invalidations of the canister's query cache tags.
+    #[prost(message, optional, tag = "57")]
+    pub query_cache_tags: ::core::option::Option<QueryCacheTags>,
+    /// Principals holding a controller role on the canister.
+    #[prost(message, optional, tag = "58")]
+    pub controller_roles: ::core::option::Option<ControllerRoles>,
     #[prost(oneof = "canister_state_bits::CanisterStatus", tags = "11, 12, 13")]
     pub canister_status: ::core::option::Option<canister_state_bits::CanisterStatus>,
 }
@@ -904,6 +954,41 @@ impl CyclesUseCase {
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
+pub enum CanisterLogSeverity {
+    Unspecified = 0,
+    Debug = 1,
+    Info = 2,
+    Warning = 3,
+    Error = 4,
+}
+impl CanisterLogSeverity {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "CANISTER_LOG_SEVERITY_UNSPECIFIED",
+            Self::Debug => "CANISTER_LOG_SEVERITY_DEBUG",
+            Self::Info => "CANISTER_LOG_SEVERITY_INFO",
+            Self::Warning => "CANISTER_LOG_SEVERITY_WARNING",
+            Self::Error => "CANISTER_LOG_SEVERITY_ERROR",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "CANISTER_LOG_SEVERITY_UNSPECIFIED" => Some(Self::Unspecified),
+            "CANISTER_LOG_SEVERITY_DEBUG" => Some(Self::Debug),
+            "CANISTER_LOG_SEVERITY_INFO" => Some(Self::Info),
+            "CANISTER_LOG_SEVERITY_WARNING" => Some(Self::Warning),
+            "CANISTER_LOG_SEVERITY_ERROR" => Some(Self::Error),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
 pub enum LongExecutionMode {
     Unspecified = 0,
     Opportunistic = 1,
@@ -234,7 +234,7 @@ pub mod pricing_version {
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Replication {
-    #[prost(oneof = "replication::ReplicationType", tags = "1, 2")]
+    #[prost(oneof = "replication::ReplicationType", tags = "1, 2, 3")]
     pub replication_type: ::core::option::Option<replication::ReplicationType>,
 }
 /// Nested message and enum types in `Replication`.
@@ -245,6 +245,8 @@ pub mod replication {
         FullyReplicated(()),
         #[prost(message, tag = "2")]
         NonReplicated(super::super::super::super::types::v1::NodeId),
+        #[prost(message, tag = "3")]
+        AggregatedByCanister(()),
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -309,6 +311,22 @@ pub struct BitcoinSendTransactionInternalContextTree {
     #[prost(message, optional, tag = "2")]
     pub context: ::core::option::Option<BitcoinSendTransactionInternalContext>,
 }
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BitcoinGetBlockFiltersContext {
+    #[prost(message, optional, tag = "1")]
+    pub request: ::core::option::Option<super::super::queues::v1::Request>,
+    #[prost(message, optional, tag = "2")]
+    pub payload: ::core::option::Option<super::super::super::bitcoin::v1::GetBlockFiltersRequest>,
+    #[prost(message, optional, tag = "3")]
+    pub time: ::core::option::Option<Time>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BitcoinGetBlockFiltersContextTree {
+    #[prost(uint64, tag = "1")]
+    pub callback_id: u64,
+    #[prost(message, optional, tag = "2")]
+    pub context: ::core::option::Option<BitcoinGetBlockFiltersContext>,
+}
 /// TODO(EXC-1454): Deprecated.
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct InstallCodeRequest {
@@ -420,6 +438,9 @@ pub struct SubnetCallContextManager {
     pub sign_with_threshold_contexts: ::prost::alloc::vec::Vec<SignWithThresholdContextTree>,
     #[prost(message, repeated, tag = "19")]
     pub pre_signature_stashes: ::prost::alloc::vec::Vec<PreSignatureStashTree>,
+    #[prost(message, repeated, tag = "20")]
+    pub bitcoin_get_block_filters_contexts:
+        ::prost::alloc::vec::Vec<BitcoinGetBlockFiltersContextTree>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SubnetMetrics {
@@ -17,8 +17,8 @@ use ic_registry_client_helpers::subnet::SubnetRegistry;
 use ic_types::{
     Height, RegistryVersion, SubnetId,
     consensus::{
-        BlockProposal, CatchUpPackage, ConsensusMessage, ConsensusMessageHashable, Finalization,
-        HasHeight, Notarization, RandomBeacon, RandomTape,
+        Block, BlockProposal, CatchUpPackage, ConsensusMessage, ConsensusMessageHashable,
+        Finalization, HasHeight, Notarization, RandomBeacon, RandomTape,
     },
     time::UNIX_EPOCH,
 };
@@ -226,6 +226,34 @@ where
     }
 }
 
+/// Reads the block finalized at the given height, i.e., the block proposal
+/// referenced by the finalization in the backup. Returns `None` if the height
+/// has no finalization or no block proposal matching it.
+pub(crate) fn read_finalized_block(
+    height_artifacts: &HeightArtifacts,
+) -> Result<Option<Block>, String> {
+    let Some(file_name) = height_artifacts.finalizations.first() else {
+        return Ok(None);
+    };
+    let file = height_artifacts.path.join(file_name);
+    let finalization = pb::Finalization::decode(read_file(&file).as_slice())
+        .map_err(ProxyDecodeError::DecodeError)
+        .and_then(Finalization::try_from)
+        .map_err(|err| deserialization_error(&file, err))?;
+
+    for file_name in &height_artifacts.proposals {
+        let file = height_artifacts.path.join(file_name);
+        let proposal = pb::BlockProposal::decode(read_file(&file).as_slice())
+            .map_err(ProxyDecodeError::DecodeError)
+            .and_then(BlockProposal::try_from)
+            .map_err(|err| deserialization_error(&file, err))?;
+        if *proposal.content.get_hash() == finalization.content.block {
+            return Ok(Some(proposal.content.into_inner()));
+        }
+    }
+    Ok(None)
+}
+
 /// Deserializes consensus artifacts, reading them from the backup spool height
 /// by height and inserting them into the consensus pool. It stops at certain
 /// points which require the execution state to catch up.
@@ -68,6 +68,12 @@ pub enum SubCommand {
     /// Restore from the backup.
     RestoreFromBackup(RestoreFromBackupCmd),
 
+    /// Export the ingress messages, HTTP outcall responses and threshold
+    /// signatures of all finalized blocks in the backup, as well as the call
+    /// contexts of the latest checkpoint, as newline-delimited JSON. Does not
+    /// require a replica config.
+    ExportFromBackup(ExportFromBackupCmd),
+
     /// The replay will add a test Neuron to the Governance canister
     /// and the corresponding account in the ledger.
     WithNeuronForTests(WithNeuronCmd),
@@ -117,6 +123,54 @@ pub struct RestoreFromBackupCmd {
     pub start_height: u64,
 }
 
+/// A Candid interface file of a canister, given as `<canister_id>=<path>`.
+#[derive(Clone)]
+pub struct CandidInterfaceArg {
+    pub canister_id: CanisterId,
+    pub did_file: PathBuf,
+}
+
+impl std::str::FromStr for CandidInterfaceArg {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (canister_id, did_file) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Expected <canister_id>=<path>, got {s}"))?;
+        Ok(CandidInterfaceArg {
+            canister_id: canister_id
+                .parse::<CanisterId>()
+                .map_err(|e| format!("Unable to parse canister_id {e:?}"))?,
+            did_file: PathBuf::from(did_file),
+        })
+    }
+}
+
+#[derive(Clone, Parser)]
+pub struct ExportFromBackupCmd {
+    /// Backup spool path
+    pub backup_spool_path: PathBuf,
+    /// The replica version of the backup to be exported
+    pub replica_version: String,
+    /// Height from which the export should start
+    #[clap(long, default_value_t = 0)]
+    pub start_height: u64,
+    /// Last height to be exported; all heights are exported if not specified
+    #[clap(long)]
+    pub end_height: Option<u64>,
+    /// Output file; the records are written to stdout if not specified
+    #[clap(long)]
+    pub output: Option<PathBuf>,
+    /// Candid interface used to decode the arguments of ingress messages to a
+    /// canister, given as `<canister_id>=<path to .did file>`. Can be repeated.
+    #[clap(long = "candid")]
+    pub candid_interfaces: Vec<CandidInterfaceArg>,
+    /// State directory whose latest checkpoint at or below the end height the
+    /// call contexts are exported from; defaults to the state the backup tool
+    /// keeps next to the spool, i.e. `<spool>/../data/<subnet_id>/ic_state`
+    #[clap(long)]
+    pub state_dir: Option<PathBuf>,
+}
+
 #[derive(Clone, Parser)]
 pub struct RestoreFromBackup2Cmd {
     /// Registry local store path
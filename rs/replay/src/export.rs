@@ -0,0 +1,742 @@
+//! Export of the payloads of finalized blocks from a backup spool.
+//!
+//! The exporter walks the backup artifacts height by height and writes one
+//! JSON object per line for every ingress message, canister HTTP outcall
+//! response and completed threshold signature contained in a finalized block.
+//! The artifacts are only decoded, i.e., neither signatures nor payloads are
+//! validated.
+//!
+//! Canister call contexts are part of the replicated state and not of the
+//! blocks. They are exported from the latest verified checkpoint at or below
+//! the end height in the state directory of the subnet, which by default is
+//! the one the backup tool keeps next to the spool.
+//!
+//! If a Candid interface is supplied for a canister, the arguments of ingress
+//! messages to that canister are additionally decoded into their textual
+//! Candid representation.
+
+use crate::{backup, cmd::ExportFromBackupCmd};
+use candid::{IDLArgs, TypeEnv, types::Type};
+use candid_parser::utils::CandidSource;
+use ic_logger::replica_logger::no_op_logger;
+use ic_metrics::MetricsRegistry;
+use ic_protobuf::types::v1::{
+    CanisterHttpResponseMessage, canister_http_response_message::MessageType,
+};
+use ic_registry_subnet_type::SubnetType;
+use ic_replicated_state::{
+    CallOrigin, ReplicatedState, page_map::TestPageAllocatorFileDescriptorImpl,
+};
+use ic_state_layout::StateLayout;
+use ic_state_manager::{CheckpointMetrics, checkpoint::load_checkpoint};
+use ic_types::{
+    CanisterId, Height, SubnetId,
+    batch::{CanisterHttpPayload, slice_to_messages},
+    canister_http::CanisterHttpResponseContent,
+    consensus::{Block, idkg::CompletedSignature},
+    messages::{CallbackId, Payload},
+};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// A single line of the export.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ExportRecord {
+    Ingress {
+        height: u64,
+        block_time_nanos: u64,
+        message_id: String,
+        sender: String,
+        canister_id: String,
+        method_name: String,
+        ingress_expiry_nanos: u64,
+        arg_hex: String,
+        /// The textual Candid representation of the argument, if a Candid
+        /// interface was supplied for the canister and the decoding succeeded.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        arg_candid: Option<String>,
+    },
+    CanisterHttpResponse {
+        height: u64,
+        block_time_nanos: u64,
+        callback_id: u64,
+        /// One of `success`, `reject`, `timeout` or `divergence`.
+        status: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        canister_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body_hex: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reject_code: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reject_message: Option<String>,
+    },
+    ThresholdSignature {
+        height: u64,
+        block_time_nanos: u64,
+        pseudo_random_id: String,
+        callback_id: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reply_hex: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reject_message: Option<String>,
+    },
+    CallContext {
+        checkpoint_height: u64,
+        canister_id: String,
+        call_context_id: u64,
+        /// One of `ingress`, `canister_update`, `query`, `canister_query` or
+        /// `system_task`.
+        origin: &'static str,
+        /// The user or canister that made the call, if any.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        originator: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        originator_callback_id: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        method_name: Option<String>,
+        time_nanos: u64,
+        available_cycles: u128,
+        responded: bool,
+        deleted: bool,
+        outstanding_calls: usize,
+    },
+}
+
+/// The Candid interfaces used to decode ingress message arguments.
+struct CandidInterfaces(BTreeMap<CanisterId, (TypeEnv, Type)>);
+
+impl CandidInterfaces {
+    fn load(cmd: &ExportFromBackupCmd) -> Result<Self, String> {
+        let mut interfaces = BTreeMap::new();
+        for interface in &cmd.candid_interfaces {
+            let (env, actor) = CandidSource::File(&interface.did_file)
+                .load()
+                .map_err(|err| format!("Couldn't load {:?}: {err}", interface.did_file))?;
+            let actor =
+                actor.ok_or_else(|| format!("{:?} defines no service", interface.did_file))?;
+            interfaces.insert(interface.canister_id, (env, actor));
+        }
+        Ok(Self(interfaces))
+    }
+
+    /// Decodes the argument of a call to the given method, returning `None`
+    /// if no interface is known for the canister or the decoding fails.
+    fn decode_arg(
+        &self,
+        canister_id: &CanisterId,
+        method_name: &str,
+        arg: &[u8],
+    ) -> Option<String> {
+        let (env, actor) = self.0.get(canister_id)?;
+        let method = env.get_method(actor, method_name).ok()?;
+        IDLArgs::from_bytes_with_types(arg, env, &method.args)
+            .ok()
+            .map(|args| args.to_string())
+    }
+}
+
+/// Exports the payloads of all finalized blocks in the backup of the given
+/// subnet and the call contexts of its latest checkpoint as newline-delimited
+/// JSON.
+pub(crate) fn export_from_backup(
+    cmd: &ExportFromBackupCmd,
+    subnet_id: SubnetId,
+) -> Result<(), String> {
+    let backup_dir = cmd
+        .backup_spool_path
+        .join(subnet_id.to_string())
+        .join(&cmd.replica_version);
+    let candid_interfaces = CandidInterfaces::load(cmd)?;
+    let height_artifacts =
+        backup::heights_to_artifacts_metadata(&backup_dir, Height::from(cmd.start_height))
+            .map_err(|err| format!("Couldn't read the backup at {backup_dir:?}: {err}"))?;
+
+    let mut writer: Box<dyn Write> = match &cmd.output {
+        Some(path) => Box::new(BufWriter::new(
+            File::create(path).map_err(|err| format!("Couldn't create {path:?}: {err}"))?,
+        )),
+        None => Box::new(BufWriter::new(std::io::stdout())),
+    };
+
+    for (height, artifacts) in height_artifacts {
+        if cmd
+            .end_height
+            .is_some_and(|end_height| height.get() > end_height)
+        {
+            break;
+        }
+        let Some(block) = backup::read_finalized_block(&artifacts)? else {
+            eprintln!("No finalized block found at height {height}, skipping it");
+            continue;
+        };
+        for record in block_records(&block, &candid_interfaces)? {
+            write_record(&mut writer, &record)?;
+        }
+    }
+
+    let state_dir = match &cmd.state_dir {
+        Some(state_dir) => state_dir.clone(),
+        None => default_state_dir(&cmd.backup_spool_path, subnet_id),
+    };
+    match load_latest_checkpoint(&state_dir, cmd.end_height)? {
+        Some((height, state)) => {
+            for record in call_context_records(&state, height) {
+                write_record(&mut writer, &record)?;
+            }
+        }
+        None => eprintln!("No verified checkpoint found in {state_dir:?}, skipping call contexts"),
+    }
+    writer
+        .flush()
+        .map_err(|err| format!("Couldn't write the export: {err}"))
+}
+
+/// The state directory the backup tool keeps for the subnet next to the spool,
+/// i.e. `<backup root>/data/<subnet_id>/ic_state`.
+fn default_state_dir(backup_spool_path: &Path, subnet_id: SubnetId) -> PathBuf {
+    backup_spool_path
+        .parent()
+        .unwrap_or(backup_spool_path)
+        .join("data")
+        .join(subnet_id.to_string())
+        .join("ic_state")
+}
+
+/// Loads the latest verified checkpoint in the given state directory whose
+/// height is at most `end_height`, if any.
+fn load_latest_checkpoint(
+    state_dir: &Path,
+    end_height: Option<u64>,
+) -> Result<Option<(Height, ReplicatedState)>, String> {
+    if !state_dir.exists() {
+        return Ok(None);
+    }
+    let metrics_registry = MetricsRegistry::new();
+    let state_layout =
+        StateLayout::new_no_init(no_op_logger(), state_dir.to_path_buf(), &metrics_registry);
+    let heights = state_layout
+        .checkpoint_heights()
+        .map_err(|err| format!("Couldn't list the checkpoints in {state_dir:?}: {err}"))?;
+    let Some(height) = heights
+        .into_iter()
+        .rev()
+        .find(|height| end_height.is_none_or(|end_height| height.get() <= end_height))
+    else {
+        return Ok(None);
+    };
+    let checkpoint_layout = state_layout
+        .checkpoint_verified(height)
+        .map_err(|err| format!("Couldn't open the checkpoint at height {height}: {err}"))?;
+    let state = load_checkpoint(
+        &checkpoint_layout,
+        SubnetType::Application,
+        &CheckpointMetrics::new(&metrics_registry, no_op_logger()),
+        None,
+        Arc::new(TestPageAllocatorFileDescriptorImpl::new()),
+    )
+    .map_err(|err| format!("Couldn't load the checkpoint at height {height}: {err}"))?;
+    Ok(Some((height, state)))
+}
+
+/// Returns the export records for the call contexts of all canisters in the
+/// given state.
+fn call_context_records(state: &ReplicatedState, checkpoint_height: Height) -> Vec<ExportRecord> {
+    let mut records = vec![];
+    for canister in state.canisters_iter() {
+        let Some(call_context_manager) = canister.system_state.call_context_manager() else {
+            continue;
+        };
+        for (call_context_id, call_context) in call_context_manager.call_contexts().iter() {
+            let (origin, originator, originator_callback_id, message_id, method_name) =
+                match call_context.call_origin() {
+                    CallOrigin::Ingress(user_id, message_id, method_name) => (
+                        "ingress",
+                        Some(user_id.to_string()),
+                        None,
+                        Some(message_id.to_string()),
+                        Some(method_name.clone()),
+                    ),
+                    CallOrigin::CanisterUpdate(canister_id, callback_id, _, method_name) => (
+                        "canister_update",
+                        Some(canister_id.to_string()),
+                        Some(callback_id.get()),
+                        None,
+                        Some(method_name.clone()),
+                    ),
+                    CallOrigin::Query(user_id, method_name) => (
+                        "query",
+                        Some(user_id.to_string()),
+                        None,
+                        None,
+                        Some(method_name.clone()),
+                    ),
+                    CallOrigin::CanisterQuery(canister_id, callback_id, method_name) => (
+                        "canister_query",
+                        Some(canister_id.to_string()),
+                        Some(callback_id.get()),
+                        None,
+                        Some(method_name.clone()),
+                    ),
+                    CallOrigin::SystemTask => ("system_task", None, None, None, None),
+                };
+            records.push(ExportRecord::CallContext {
+                checkpoint_height: checkpoint_height.get(),
+                canister_id: canister.canister_id().to_string(),
+                call_context_id: call_context_id.get(),
+                origin,
+                originator,
+                originator_callback_id,
+                message_id,
+                method_name,
+                time_nanos: call_context.time().as_nanos_since_unix_epoch(),
+                available_cycles: call_context.available_cycles().get(),
+                responded: call_context.has_responded(),
+                deleted: call_context.is_deleted(),
+                outstanding_calls: call_context_manager.outstanding_calls(*call_context_id),
+            });
+        }
+    }
+    records
+}
+
+fn write_record(writer: &mut dyn Write, record: &ExportRecord) -> Result<(), String> {
+    serde_json::to_writer(&mut *writer, record)
+        .map_err(|err| format!("Couldn't serialize record: {err}"))?;
+    writeln!(writer).map_err(|err| format!("Couldn't write the export: {err}"))
+}
+
+/// Returns the export records for all payloads of the given block.
+fn block_records(
+    block: &Block,
+    candid_interfaces: &CandidInterfaces,
+) -> Result<Vec<ExportRecord>, String> {
+    let height = block.height.get();
+    let block_time_nanos = block.context.time.as_nanos_since_unix_epoch();
+    let payload = block.payload.as_ref();
+    let mut records = vec![];
+
+    if !payload.is_summary() {
+        let batch = &payload.as_data().batch;
+        for (message_id, ingress) in batch.ingress.iter() {
+            let ingress = ingress.map_err(|err| {
+                format!("Couldn't decode ingress message {message_id} at height {height}: {err}")
+            })?;
+            let canister_id = ingress.canister_id();
+            let method_name = ingress.method_name();
+            records.push(ExportRecord::Ingress {
+                height,
+                block_time_nanos,
+                message_id: ingress.id().to_string(),
+                sender: ingress.sender().to_string(),
+                canister_id: canister_id.to_string(),
+                arg_candid: candid_interfaces.decode_arg(
+                    &canister_id,
+                    &method_name,
+                    ingress.method_arg(),
+                ),
+                method_name,
+                ingress_expiry_nanos: ingress.expiry_time().as_nanos_since_unix_epoch(),
+                arg_hex: hex::encode(ingress.method_arg()),
+            });
+        }
+
+        let canister_http = canister_http_payload(&batch.canister_http)
+            .map_err(|err| format!("Couldn't decode HTTP outcalls at height {height}: {err}"))?;
+        // Responses aggregated by the canister are exported individually, one per node.
+        let responses = canister_http.responses.into_iter().chain(
+            canister_http
+                .aggregated_responses
+                .into_iter()
+                .flat_map(|aggregated| aggregated.responses),
+        );
+        for response in responses {
+            let content = response.content;
+            let (status, body_hex, reject_code, reject_message) = match content.content {
+                CanisterHttpResponseContent::Success(body) => {
+                    ("success", Some(hex::encode(body)), None, None)
+                }
+                CanisterHttpResponseContent::Reject(reject) => (
+                    "reject",
+                    None,
+                    Some(format!("{:?}", reject.reject_code)),
+                    Some(reject.message),
+                ),
+            };
+            records.push(ExportRecord::CanisterHttpResponse {
+                height,
+                block_time_nanos,
+                callback_id: content.id.get(),
+                status,
+                canister_id: Some(content.canister_id.to_string()),
+                body_hex,
+                reject_code,
+                reject_message,
+            });
+        }
+        let unanswered = canister_http
+            .timeouts
+            .into_iter()
+            .map(|callback_id| ("timeout", callback_id))
+            .chain(
+                canister_http
+                    .divergence_responses
+                    .iter()
+                    .filter_map(|divergence| divergence.shares.first())
+                    .map(|share| ("divergence", share.content.id)),
+            );
+        for (status, callback_id) in unanswered {
+            records.push(ExportRecord::CanisterHttpResponse {
+                height,
+                block_time_nanos,
+                callback_id: callback_id.get(),
+                status,
+                canister_id: None,
+                body_hex: None,
+                reject_code: None,
+                reject_message: None,
+            });
+        }
+    }
+
+    if let Some(idkg) = payload.as_idkg() {
+        for (pseudo_random_id, signature) in &idkg.signature_agreements {
+            let CompletedSignature::Unreported(response) = signature else {
+                continue;
+            };
+            let (reply_hex, reject_message) = match &response.payload {
+                Payload::Data(data) => (Some(hex::encode(data)), None),
+                Payload::Reject(reject) => (None, Some(reject.message().clone())),
+            };
+            records.push(ExportRecord::ThresholdSignature {
+                height,
+                block_time_nanos,
+                pseudo_random_id: hex::encode(pseudo_random_id),
+                callback_id: response.callback.get(),
+                reply_hex,
+                reject_message,
+            });
+        }
+    }
+
+    Ok(records)
+}
+
+/// Decodes the canister HTTP payload of a block (a sequence of length
+/// delimited protobuf messages).
+fn canister_http_payload(data: &[u8]) -> Result<CanisterHttpPayload, String> {
+    let messages: Vec<CanisterHttpResponseMessage> =
+        slice_to_messages(data).map_err(|err| err.to_string())?;
+    let mut payload = CanisterHttpPayload::default();
+    for message in messages {
+        match message.message_type {
+            Some(MessageType::Timeout(timeout)) => payload.timeouts.push(CallbackId::new(timeout)),
+            Some(MessageType::Response(response)) => payload
+                .responses
+                .push(response.try_into().map_err(|err| format!("{err}"))?),
+            Some(MessageType::DivergenceResponse(response)) => payload
+                .divergence_responses
+                .push(response.try_into().map_err(|err| format!("{err}"))?),
+            Some(MessageType::AggregatedResponses(response)) => payload
+                .aggregated_responses
+                .push(response.try_into().map_err(|err| format!("{err}"))?),
+            None => return Err("Missing message type".to_string()),
+        }
+    }
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use ic_protobuf::types::v1 as pb;
+    use ic_replicated_state::CallContext;
+    use ic_test_utilities_consensus::idkg::empty_idkg_payload;
+    use ic_test_utilities_state::{
+        CallContextBuilder, CanisterStateBuilder, ReplicatedStateBuilder,
+    };
+    use ic_test_utilities_types::{
+        ids::{canister_test_id, message_test_id, subnet_test_id, user_test_id},
+        messages::SignedIngressBuilder,
+    };
+    use ic_types::{
+        Cycles, NumBytes, RegistryVersion, ReplicaVersion,
+        batch::{
+            BatchPayload, ConsensusResponse, IngressPayload, ValidationContext, iterator_to_bytes,
+        },
+        canister_http::{
+            CanisterHttpResponse, CanisterHttpResponseMetadata, CanisterHttpResponseWithConsensus,
+        },
+        consensus::{
+            BlockPayload, DataPayload, Payload as BlockPayloadThunk, Rank, dkg::DkgDataPayload,
+            idkg::IDkgPayload,
+        },
+        crypto::{CryptoHash, CryptoHashOf, Signed, crypto_hash},
+        messages::RequestMetadata,
+        signature::BasicSignatureBatch,
+        time::{CoarseTime, UNIX_EPOCH},
+    };
+
+    /// Returns a data block at height 5 with the given batch and IDKG payload.
+    fn data_block(batch: BatchPayload, idkg: Option<IDkgPayload>) -> Block {
+        Block::new(
+            CryptoHashOf::from(CryptoHash(vec![])),
+            BlockPayloadThunk::new(
+                crypto_hash,
+                BlockPayload::Data(DataPayload {
+                    batch,
+                    dkg: DkgDataPayload::new_empty(Height::from(0)),
+                    idkg,
+                }),
+            ),
+            Height::from(5),
+            Rank(0),
+            ValidationContext {
+                registry_version: RegistryVersion::from(1),
+                certified_height: Height::from(4),
+                time: UNIX_EPOCH,
+            },
+        )
+    }
+
+    fn http_response_with_consensus(callback_id: u64) -> CanisterHttpResponseWithConsensus {
+        let response = CanisterHttpResponse {
+            id: CallbackId::new(callback_id),
+            timeout: UNIX_EPOCH,
+            canister_id: canister_test_id(3),
+            content: CanisterHttpResponseContent::Success(b"abc".to_vec()),
+        };
+        CanisterHttpResponseWithConsensus {
+            proof: Signed {
+                content: CanisterHttpResponseMetadata {
+                    id: response.id,
+                    timeout: response.timeout,
+                    content_hash: crypto_hash(&response),
+                    registry_version: RegistryVersion::from(1),
+                    replica_version: ReplicaVersion::default(),
+                },
+                signature: BasicSignatureBatch {
+                    signatures_map: BTreeMap::new(),
+                },
+            },
+            content: response,
+        }
+    }
+
+    /// Encodes a timeout for callback 2 and a successful response for
+    /// callback 1 the way the HTTP outcalls payload builder does.
+    fn canister_http_bytes(response: &CanisterHttpResponseWithConsensus) -> Vec<u8> {
+        let messages = vec![
+            CanisterHttpResponseMessage {
+                message_type: Some(MessageType::Timeout(2)),
+            },
+            CanisterHttpResponseMessage {
+                message_type: Some(MessageType::Response(
+                    pb::CanisterHttpResponseWithConsensus::from(response),
+                )),
+            },
+        ];
+        iterator_to_bytes(messages.into_iter(), NumBytes::new(1024 * 1024))
+    }
+
+    #[test]
+    fn test_block_records_contain_ingress_messages() {
+        let ingress = SignedIngressBuilder::new()
+            .canister_id(CanisterId::from_u64(7))
+            .method_name("greet")
+            .method_payload(vec![1, 2, 3])
+            .build();
+        let block = data_block(
+            BatchPayload {
+                ingress: IngressPayload::from(vec![ingress.clone()]),
+                ..BatchPayload::default()
+            },
+            None,
+        );
+
+        let records = block_records(&block, &CandidInterfaces(BTreeMap::new())).unwrap();
+
+        assert_eq!(records.len(), 1);
+        let json = serde_json::to_value(&records[0]).unwrap();
+        assert_eq!(json["type"], "ingress");
+        assert_eq!(json["height"], 5);
+        assert_eq!(json["message_id"], ingress.id().to_string());
+        assert_eq!(json["canister_id"], CanisterId::from_u64(7).to_string());
+        assert_eq!(json["method_name"], "greet");
+        assert_eq!(json["arg_hex"], "010203");
+        assert!(json.get("arg_candid").is_none());
+    }
+
+    #[test]
+    fn test_canister_http_payload_is_decoded() {
+        let response = http_response_with_consensus(1);
+
+        let payload = canister_http_payload(&canister_http_bytes(&response)).unwrap();
+
+        assert_eq!(payload.timeouts, vec![CallbackId::new(2)]);
+        assert_eq!(payload.responses, vec![response]);
+        assert!(payload.divergence_responses.is_empty());
+        assert!(payload.aggregated_responses.is_empty());
+        assert!(canister_http_payload(&[0xff, 0xff]).is_err());
+    }
+
+    #[test]
+    fn test_block_records_contain_canister_http_responses() {
+        let block = data_block(
+            BatchPayload {
+                canister_http: canister_http_bytes(&http_response_with_consensus(1)),
+                ..BatchPayload::default()
+            },
+            None,
+        );
+
+        let records = block_records(&block, &CandidInterfaces(BTreeMap::new())).unwrap();
+
+        let json: Vec<_> = records
+            .iter()
+            .map(|record| serde_json::to_value(record).unwrap())
+            .collect();
+        assert_eq!(json.len(), 2);
+        assert_eq!(json[0]["type"], "canister_http_response");
+        assert_eq!(json[0]["callback_id"], 1);
+        assert_eq!(json[0]["status"], "success");
+        assert_eq!(json[0]["canister_id"], canister_test_id(3).to_string());
+        assert_eq!(json[0]["body_hex"], hex::encode(b"abc"));
+        assert_eq!(json[1]["callback_id"], 2);
+        assert_eq!(json[1]["status"], "timeout");
+        assert!(json[1].get("canister_id").is_none());
+    }
+
+    #[test]
+    fn test_block_records_contain_unreported_threshold_signatures() {
+        let mut idkg = empty_idkg_payload(subnet_test_id(1));
+        idkg.signature_agreements.insert(
+            [1; 32],
+            CompletedSignature::Unreported(ConsensusResponse::new(
+                CallbackId::new(9),
+                Payload::Data(vec![4, 5]),
+            )),
+        );
+        idkg.signature_agreements
+            .insert([2; 32], CompletedSignature::ReportedToExecution);
+        let block = data_block(BatchPayload::default(), Some(idkg));
+
+        let records = block_records(&block, &CandidInterfaces(BTreeMap::new())).unwrap();
+
+        assert_eq!(records.len(), 1);
+        let json = serde_json::to_value(&records[0]).unwrap();
+        assert_eq!(json["type"], "threshold_signature");
+        assert_eq!(json["height"], 5);
+        assert_eq!(json["pseudo_random_id"], hex::encode([1; 32]));
+        assert_eq!(json["callback_id"], 9);
+        assert_eq!(json["reply_hex"], "0405");
+        assert!(json.get("reject_message").is_none());
+    }
+
+    #[test]
+    fn test_candid_arguments_are_decoded() {
+        let dir = tempfile::tempdir().unwrap();
+        let did_file = dir.path().join("greet.did");
+        std::fs::write(&did_file, "service : { greet : (text) -> (text) }").unwrap();
+        let canister_id = canister_test_id(7);
+        let cmd = ExportFromBackupCmd::parse_from([
+            "export-from-backup".to_string(),
+            "spool".to_string(),
+            "version".to_string(),
+            "--candid".to_string(),
+            format!("{canister_id}={}", did_file.display()),
+        ]);
+
+        let interfaces = CandidInterfaces::load(&cmd).unwrap();
+
+        let arg = candid::Encode!(&"world").unwrap();
+        assert_eq!(
+            interfaces.decode_arg(&canister_id, "greet", &arg),
+            Some("(\"world\")".to_string())
+        );
+        assert_eq!(interfaces.decode_arg(&canister_id, "unknown", &arg), None);
+        assert_eq!(
+            interfaces.decode_arg(&canister_id, "greet", &[1, 2, 3]),
+            None
+        );
+        assert_eq!(
+            interfaces.decode_arg(&canister_test_id(8), "greet", &arg),
+            None
+        );
+    }
+
+    #[test]
+    fn test_call_context_records() {
+        let canister_id = canister_test_id(7);
+        let state = ReplicatedStateBuilder::new()
+            .with_canister(
+                CanisterStateBuilder::new()
+                    .with_canister_id(canister_id)
+                    .with_call_context(
+                        CallContextBuilder::new()
+                            .with_call_origin(CallOrigin::Ingress(
+                                user_test_id(1),
+                                message_test_id(2),
+                                "greet".to_string(),
+                            ))
+                            .build(),
+                    )
+                    .with_call_context(CallContext::new(
+                        CallOrigin::CanisterUpdate(
+                            canister_test_id(8),
+                            CallbackId::new(3),
+                            CoarseTime::from_secs_since_unix_epoch(0),
+                            "update".to_string(),
+                        ),
+                        true,
+                        false,
+                        Cycles::new(100),
+                        UNIX_EPOCH,
+                        RequestMetadata::new(0, UNIX_EPOCH),
+                    ))
+                    .build(),
+            )
+            .build();
+
+        let records = call_context_records(&state, Height::from(100));
+
+        let json: Vec<_> = records
+            .iter()
+            .map(|record| serde_json::to_value(record).unwrap())
+            .collect();
+        assert_eq!(json.len(), 2);
+        assert_eq!(json[0]["type"], "call_context");
+        assert_eq!(json[0]["checkpoint_height"], 100);
+        assert_eq!(json[0]["canister_id"], canister_id.to_string());
+        assert_eq!(json[0]["origin"], "ingress");
+        assert_eq!(json[0]["originator"], user_test_id(1).to_string());
+        assert_eq!(json[0]["method_name"], "greet");
+        assert_eq!(json[0]["responded"], false);
+        assert!(json[0].get("originator_callback_id").is_none());
+        assert_eq!(json[1]["origin"], "canister_update");
+        assert_eq!(json[1]["originator"], canister_test_id(8).to_string());
+        assert_eq!(json[1]["originator_callback_id"], 3);
+        assert_eq!(json[1]["method_name"], "update");
+        assert_eq!(json[1]["available_cycles"], 100);
+        assert_eq!(json[1]["responded"], true);
+        assert_eq!(json[1]["outstanding_calls"], 0);
+    }
+
+    #[test]
+    fn test_default_state_dir_is_next_to_the_spool() {
+        assert_eq!(
+            default_state_dir(Path::new("/var/backup/spool"), subnet_test_id(1)),
+            PathBuf::from(format!("/var/backup/data/{}/ic_state", subnet_test_id(1)))
+        );
+    }
+}
@@ -28,6 +28,7 @@ use std::{cell::RefCell, convert::TryFrom, rc::Rc};
 
 mod backup;
 pub mod cmd;
+mod export;
 pub mod ingress;
 mod mocks;
 pub mod player;
@@ -67,6 +68,23 @@ mod validator;
 /// // replay(args);
 /// ```
 pub fn replay(args: ReplayToolArgs) -> ReplayResult {
+    // Exporting from a backup only reads the backup spool, so it doesn't need
+    // a replica config.
+    if let Some(SubCommand::ExportFromBackup(cmd)) = &args.subcmd {
+        let subnet_id = args
+            .subnet_id
+            .unwrap_or_else(|| {
+                println!("Subnet is required!");
+                std::process::exit(1);
+            })
+            .0;
+        if let Err(err) = export::export_from_backup(cmd, subnet_id) {
+            eprintln!("Export failed: {err}");
+            std::process::exit(1);
+        }
+        return Ok(Default::default());
+    }
+
     let rt = tokio::runtime::Runtime::new().expect("Could not create tokio runtime.");
     let result: Rc<RefCell<ReplayResult>> = Rc::new(RefCell::new(Ok(Default::default())));
     let res_clone = Rc::clone(&result);
@@ -2,6 +2,7 @@ use ic_base_types::CanisterId;
 use ic_btc_interface::NetworkInRequest as BitcoinNetwork;
 use ic_btc_replica_types::{GetSuccessorsResponseComplete, GetSuccessorsResponsePartial};
 use ic_btc_service::{
+    BtcServiceGetBlockFiltersRequest, BtcServiceGetBlockFiltersResponse,
     BtcServiceGetSuccessorsRequest, BtcServiceGetSuccessorsResponse,
     BtcServiceSendTransactionRequest, BtcServiceSendTransactionResponse,
     btc_service_server::{BtcService, BtcServiceServer},
@@ -108,6 +109,13 @@ impl BtcService for MockBitcoinAdapter {
             .clone()
             .map(tonic::Response::new)
     }
+
+    async fn get_block_filters(
+        &self,
+        _request: tonic::Request<BtcServiceGetBlockFiltersRequest>,
+    ) -> Result<tonic::Response<BtcServiceGetBlockFiltersResponse>, tonic::Status> {
+        Err(tonic::Status::unimplemented("block filters are not mocked"))
+    }
 }
 
 fn spawn_mock_bitcoin_adapter(
@@ -87,7 +87,20 @@ pub fn push_response(
 
             Ok(())
         }
-        BitcoinAdapterResponseWrapper::SendTransactionReject(reject) => {
+        BitcoinAdapterResponseWrapper::GetBlockFiltersResponse(r) => {
+            // Retrieve the associated request from the call context manager.
+            let callback_id = CallbackId::from(response.callback_id);
+            let payload = Payload::Data(r.encode());
+
+            // Add response to the consensus queue.
+            state
+                .consensus_queue
+                .push(ConsensusResponse::new(callback_id, payload));
+
+            Ok(())
+        }
+        BitcoinAdapterResponseWrapper::SendTransactionReject(reject)
+        | BitcoinAdapterResponseWrapper::GetBlockFiltersReject(reject) => {
             // Retrieve the associated request from the call context manager.
             let callback_id = CallbackId::from(response.callback_id);
             let reject_payload =
@@ -5,12 +5,12 @@ use crate::{NumWasmPages, PageMap, canister_state::WASM_PAGE_SIZE_IN_BYTES, num_
 use ic_management_canister_types_private::Global;
 use ic_sys::PAGE_SIZE;
 use ic_types::{
-    CountBytes, ExecutionRound, NumBytes,
+    CountBytes, ExecutionRound, NumBytes, NumInstructions,
     methods::{SystemMethod, WasmMethod},
 };
 use ic_validate_eq::ValidateEq;
 use ic_validate_eq_derive::ValidateEq;
-use ic_wasm_types::CanisterModule;
+use ic_wasm_types::{CanisterModule, CompilationTier};
 use maplit::btreemap;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -134,6 +134,12 @@ pub struct WasmBinary {
     /// ensure that this happens only once.
     #[validate_eq(Ignore)]
     pub embedder_cache: Arc<std::sync::Mutex<Option<EmbedderCache>>>,
+
+    /// Same as `embedder_cache`, but for the binary compiled with
+    /// `CompilationTier::Optimized`. Kept separately so that executions on
+    /// older states, which may still use the baseline tier, don't evict it.
+    #[validate_eq(Ignore)]
+    pub optimized_embedder_cache: Arc<std::sync::Mutex<Option<EmbedderCache>>>,
 }
 
 impl WasmBinary {
@@ -141,11 +147,25 @@ impl WasmBinary {
         Arc::new(WasmBinary {
             binary,
             embedder_cache: Arc::new(std::sync::Mutex::new(None)),
+            optimized_embedder_cache: Arc::new(std::sync::Mutex::new(None)),
         })
     }
 
+    /// Returns the cached compiled representation of the binary for the
+    /// given compilation tier.
+    pub fn embedder_cache(
+        &self,
+        tier: CompilationTier,
+    ) -> &std::sync::Mutex<Option<EmbedderCache>> {
+        match tier {
+            CompilationTier::Baseline => &self.embedder_cache,
+            CompilationTier::Optimized => &self.optimized_embedder_cache,
+        }
+    }
+
     pub fn clear_compilation_cache(&self) {
         *self.embedder_cache.lock().unwrap() = None;
+        *self.optimized_embedder_cache.lock().unwrap() = None;
     }
 }
 
@@ -350,6 +370,12 @@ pub struct ExecutionState {
 
     /// Checks if execution is in Wasm64 mode.
     pub wasm_execution_mode: WasmExecutionMode,
+
+    /// Number of instructions the current Wasm module has executed in
+    /// execution rounds. Once it exceeds the tiered compilation threshold,
+    /// the module is executed with `CompilationTier::Optimized`. Being part of
+    /// the replicated state, all replicas switch tiers at the same execution.
+    pub executed_instructions: NumInstructions,
 }
 
 // We have to implement it by hand as embedder_cache can not be compared for
@@ -370,6 +396,7 @@ impl PartialEq for ExecutionState {
             last_executed_round,
             next_scheduled_method,
             wasm_execution_mode,
+            executed_instructions,
         } = rhs;
 
         (
@@ -382,6 +409,7 @@ impl PartialEq for ExecutionState {
             &self.last_executed_round,
             &self.next_scheduled_method,
             &self.wasm_execution_mode,
+            &self.executed_instructions,
         ) == (
             &wasm_binary.binary,
             wasm_memory,
@@ -392,6 +420,7 @@ impl PartialEq for ExecutionState {
             last_executed_round,
             next_scheduled_method,
             wasm_execution_mode,
+            executed_instructions,
         )
     }
 }
@@ -401,7 +430,8 @@ impl ExecutionState {
     /// The state will be created with empty stable memory, but may have wasm
     /// memory from data sections in the wasm module.
     /// The state will be created with last_executed_round = 0, a
-    /// default next_scheduled_method, and wasm_execution_mode = WasmExecutionMode::Wasm32.
+    /// default next_scheduled_method, wasm_execution_mode = WasmExecutionMode::Wasm32,
+    /// and executed_instructions = 0.
     /// Be sure to change these if needed.
     pub fn new(
         canister_root: PathBuf,
@@ -423,6 +453,7 @@ impl ExecutionState {
             last_executed_round: ExecutionRound::from(0),
             next_scheduled_method: NextScheduledMethod::default(),
             wasm_execution_mode: WasmExecutionMode::Wasm32,
+            executed_instructions: NumInstructions::new(0),
         }
     }
 
@@ -1,8 +1,10 @@
 mod call_context_manager;
 pub mod proto;
+mod query_cache_tags;
 mod task_queue;
 pub mod wasm_chunk_store;
 
+pub use self::query_cache_tags::{MAX_QUERY_CACHE_TAG_SIZE, MAX_QUERY_CACHE_TAGS, QueryCacheTags};
 pub use self::task_queue::{TaskQueue, is_low_wasm_memory_hook_condition_satisfied};
 
 use self::wasm_chunk_store::{WasmChunkStore, WasmChunkStoreMetadata};
@@ -23,7 +25,7 @@ use ic_interfaces::execution_environment::HypervisorError;
 use ic_logger::{ReplicaLogger, error};
 use ic_management_canister_types_private::{
     CanisterChange, CanisterChangeDetails, CanisterChangeOrigin, CanisterStatusType,
-    LogVisibilityV2,
+    ControllerRoles, LogVisibilityV2,
 };
 use ic_registry_subnet_type::SubnetType;
 use ic_types::ingress::WasmResult;
@@ -356,6 +358,12 @@ pub struct SystemState {
 
     /// Environment variables.
     pub environment_variables: EnvironmentVariables,
+
+    /// Invalidations of the query cache tags of the canister.
+    pub query_cache_tags: QueryCacheTags,
+
+    /// Principals that are granted a subset of the controller permissions.
+    pub controller_roles: ControllerRoles,
 }
 
 /// A wrapper around the different canister statuses.
@@ -505,6 +513,8 @@ impl SystemState {
             reserved_balance_limit: None,
             memory_allocation: MemoryAllocation::default(),
             environment_variables: Default::default(),
+            query_cache_tags: Default::default(),
+            controller_roles: Default::default(),
             wasm_memory_threshold: NumBytes::new(0),
             freeze_threshold,
             status,
@@ -555,6 +565,8 @@ impl SystemState {
         next_snapshot_id: u64,
         snapshots_memory_usage: NumBytes,
         environment_variables: BTreeMap<String, String>,
+        query_cache_tags: QueryCacheTags,
+        controller_roles: ControllerRoles,
         metrics: &dyn CheckpointLoadingMetrics,
     ) -> Self {
         let system_state = Self {
@@ -586,6 +598,8 @@ impl SystemState {
             next_snapshot_id,
             snapshots_memory_usage,
             environment_variables: EnvironmentVariables::new(environment_variables),
+            query_cache_tags,
+            controller_roles,
         };
         system_state.check_invariants().unwrap_or_else(|msg| {
             metrics.observe_broken_soft_invariant(msg);
@@ -2226,6 +2240,8 @@ pub mod testing {
             next_snapshot_id: Default::default(),
             snapshots_memory_usage: Default::default(),
             environment_variables: Default::default(),
+            query_cache_tags: Default::default(),
+            controller_roles: Default::default(),
         };
     }
 }
@@ -0,0 +1,137 @@
+use ic_protobuf::state::canister_state_bits::v1 as pb;
+use std::collections::BTreeMap;
+
+/// The maximum number of query cache tags tracked per canister.
+pub const MAX_QUERY_CACHE_TAGS: usize = 100;
+
+/// The maximum size of a query cache tag in bytes.
+pub const MAX_QUERY_CACHE_TAG_SIZE: usize = 64;
+
+/// Tracks the invalidations of the query cache tags of a canister.
+///
+/// Every invalidation increments a per-canister counter and records the new
+/// counter value for the invalidated tag. A query reply that was cached with a
+/// tag remains valid as long as `last_invalidation(tag)` does not exceed the
+/// value of the counter at the time the reply was cached.
+///
+/// To bound the size of the state, at most `MAX_QUERY_CACHE_TAGS` tags are
+/// tracked. When a tag is evicted, its invalidation is (conservatively)
+/// attributed to all tags that are not tracked.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct QueryCacheTags {
+    /// The value of `counter` at the last invalidation of each tracked tag.
+    invalidations: BTreeMap<Vec<u8>, u64>,
+    /// The total number of invalidations.
+    counter: u64,
+    /// The last invalidation of any tag that is no longer tracked.
+    evicted_invalidation: u64,
+}
+
+impl QueryCacheTags {
+    /// Returns the total number of invalidations so far.
+    pub fn counter(&self) -> u64 {
+        self.counter
+    }
+
+    /// Returns the value of the counter at the last invalidation of the tag.
+    pub fn last_invalidation(&self, tag: &[u8]) -> u64 {
+        self.invalidations
+            .get(tag)
+            .copied()
+            .unwrap_or(self.evicted_invalidation)
+    }
+
+    /// Invalidates all cached query replies with the given tag, evicting the
+    /// least recently invalidated tag if too many tags are tracked.
+    pub fn invalidate(&mut self, tag: &[u8]) {
+        self.counter += 1;
+        self.invalidations.insert(tag.to_vec(), self.counter);
+        if self.invalidations.len() > MAX_QUERY_CACHE_TAGS
+            && let Some((evicted_tag, invalidation)) = self
+                .invalidations
+                .iter()
+                .min_by_key(|(_, invalidation)| **invalidation)
+                .map(|(tag, invalidation)| (tag.clone(), *invalidation))
+        {
+            self.invalidations.remove(&evicted_tag);
+            self.evicted_invalidation = self.evicted_invalidation.max(invalidation);
+        }
+    }
+}
+
+impl From<&QueryCacheTags> for pb::QueryCacheTags {
+    fn from(item: &QueryCacheTags) -> Self {
+        Self {
+            invalidations: item
+                .invalidations
+                .iter()
+                .map(|(tag, invalidation)| pb::QueryCacheTagInvalidation {
+                    tag: tag.clone(),
+                    invalidation: *invalidation,
+                })
+                .collect(),
+            counter: item.counter,
+            evicted_invalidation: item.evicted_invalidation,
+        }
+    }
+}
+
+impl From<pb::QueryCacheTags> for QueryCacheTags {
+    fn from(item: pb::QueryCacheTags) -> Self {
+        Self {
+            invalidations: item
+                .invalidations
+                .into_iter()
+                .map(|invalidation| (invalidation.tag, invalidation.invalidation))
+                .collect(),
+            counter: item.counter,
+            evicted_invalidation: item.evicted_invalidation,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalidate_only_affects_the_given_tag() {
+        let mut tags = QueryCacheTags::default();
+        let cached_at = tags.counter();
+
+        tags.invalidate(b"a");
+
+        assert!(tags.last_invalidation(b"a") > cached_at);
+        assert!(tags.last_invalidation(b"b") <= cached_at);
+    }
+
+    #[test]
+    fn evicted_tags_are_conservatively_invalidated() {
+        let mut tags = QueryCacheTags::default();
+        for i in 0..MAX_QUERY_CACHE_TAGS as u64 {
+            tags.invalidate(&i.to_le_bytes());
+        }
+        let cached_at = tags.counter();
+        assert!(tags.last_invalidation(b"untracked") <= cached_at);
+
+        // Tracking one more tag evicts the least recently invalidated one.
+        tags.invalidate(b"new");
+
+        assert_eq!(tags.invalidations.len(), MAX_QUERY_CACHE_TAGS);
+        assert_eq!(tags.last_invalidation(&0_u64.to_le_bytes()), 1);
+        assert_eq!(tags.last_invalidation(b"untracked"), 1);
+        assert!(tags.last_invalidation(&1_u64.to_le_bytes()) <= cached_at);
+    }
+
+    #[test]
+    fn proto_round_trip() {
+        let mut tags = QueryCacheTags::default();
+        tags.invalidate(b"a");
+        tags.invalidate(b"b");
+        tags.invalidate(b"a");
+
+        let proto = pb::QueryCacheTags::from(&tags);
+
+        assert_eq!(QueryCacheTags::from(proto), tags);
+    }
+}
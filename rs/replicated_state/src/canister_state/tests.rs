@@ -20,7 +20,7 @@ use ic_logger::replica_logger::no_op_logger;
 use ic_management_canister_types_private::Global;
 use ic_management_canister_types_private::{
     BoundedAllowedViewers, CanisterChange, CanisterChangeDetails, CanisterChangeOrigin,
-    CanisterLogRecord, LogVisibilityV2,
+    CanisterLogField, CanisterLogRecord, CanisterLogSeverity, LogVisibilityV2,
 };
 use ic_metrics::MetricsRegistry;
 use ic_test_utilities_types::ids::{canister_test_id, message_test_id, user_test_id};
@@ -32,7 +32,7 @@ use ic_types::messages::{
 use ic_types::methods::{Callback, WasmClosure};
 use ic_types::nominal_cycles::NominalCycles;
 use ic_types::time::CoarseTime;
-use ic_types::{CountBytes, Cycles, Time};
+use ic_types::{CountBytes, Cycles, NumInstructions, Time};
 use ic_wasm_types::CanisterModule;
 use prometheus::IntCounter;
 use strum::IntoEnumIterator;
@@ -1030,6 +1030,8 @@ fn canister_state_canister_log_record_round_trip() {
         idx: 42,
         timestamp_nanos: 27,
         content: vec![1, 2, 3],
+        severity: None,
+        fields: None,
     };
     let encoded = pb::CanisterLogRecord::from(&initial);
     let round_trip = CanisterLogRecord::from(encoded);
@@ -1037,6 +1039,25 @@ fn canister_state_canister_log_record_round_trip() {
     assert_eq!(initial, round_trip);
 }
 
+#[test]
+fn canister_state_structured_canister_log_record_round_trip() {
+    use ic_protobuf::state::canister_state_bits::v1 as pb;
+
+    for fields in [vec![], vec![CanisterLogField::new("key", "value")]] {
+        let initial = CanisterLogRecord {
+            idx: 42,
+            timestamp_nanos: 27,
+            content: vec![1, 2, 3],
+            severity: Some(CanisterLogSeverity::Warning),
+            fields: Some(fields),
+        };
+        let encoded = pb::CanisterLogRecord::from(&initial);
+        let round_trip = CanisterLogRecord::from(encoded);
+
+        assert_eq!(initial, round_trip);
+    }
+}
+
 #[test]
 fn execution_state_test_partial_eq() {
     let state_1 = ExecutionState::new(
@@ -1130,6 +1151,14 @@ fn execution_state_test_partial_eq() {
         },
         state_1
     );
+
+    assert_ne!(
+        ExecutionState {
+            executed_instructions: NumInstructions::new(12345),
+            ..state_1.clone()
+        },
+        state_1
+    );
 }
 
 /// Performs operations with canister history and thus exercises
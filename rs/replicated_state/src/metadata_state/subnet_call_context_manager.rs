@@ -1,6 +1,8 @@
 pub mod proto;
 
-use ic_btc_replica_types::{GetSuccessorsRequestInitial, SendTransactionRequest};
+use ic_btc_replica_types::{
+    GetBlockFiltersRequest, GetSuccessorsRequestInitial, SendTransactionRequest,
+};
 use ic_logger::{ReplicaLogger, info};
 use ic_management_canister_types_private::{
     EcdsaKeyId, MasterPublicKeyId, SchnorrKeyId, VetKdKeyId,
@@ -40,6 +42,7 @@ pub enum SubnetCallContext {
     ReshareChainKey(ReshareChainKeyContext),
     BitcoinGetSuccessors(BitcoinGetSuccessorsContext),
     BitcoinSendTransactionInternal(BitcoinSendTransactionInternalContext),
+    BitcoinGetBlockFilters(BitcoinGetBlockFiltersContext),
     SignWithThreshold(SignWithThresholdContext),
 }
 
@@ -51,6 +54,7 @@ impl SubnetCallContext {
             SubnetCallContext::ReshareChainKey(context) => &context.request,
             SubnetCallContext::BitcoinGetSuccessors(context) => &context.request,
             SubnetCallContext::BitcoinSendTransactionInternal(context) => &context.request,
+            SubnetCallContext::BitcoinGetBlockFilters(context) => &context.request,
             SubnetCallContext::SignWithThreshold(context) => &context.request,
         }
     }
@@ -62,6 +66,7 @@ impl SubnetCallContext {
             SubnetCallContext::ReshareChainKey(context) => context.time,
             SubnetCallContext::BitcoinGetSuccessors(context) => context.time,
             SubnetCallContext::BitcoinSendTransactionInternal(context) => context.time,
+            SubnetCallContext::BitcoinGetBlockFilters(context) => context.time,
             SubnetCallContext::SignWithThreshold(context) => context.batch_time,
         }
     }
@@ -224,6 +229,7 @@ pub struct SubnetCallContextManager {
     pub bitcoin_get_successors_contexts: BTreeMap<CallbackId, BitcoinGetSuccessorsContext>,
     pub bitcoin_send_transaction_internal_contexts:
         BTreeMap<CallbackId, BitcoinSendTransactionInternalContext>,
+    pub bitcoin_get_block_filters_contexts: BTreeMap<CallbackId, BitcoinGetBlockFiltersContext>,
     canister_management_calls: CanisterManagementCalls,
     pub raw_rand_contexts: VecDeque<RawRandContext>,
     pub pre_signature_stashes: BTreeMap<IDkgMasterPublicKeyId, PreSignatureStash>,
@@ -261,6 +267,10 @@ impl SubnetCallContextManager {
                 self.bitcoin_send_transaction_internal_contexts
                     .insert(callback_id, context);
             }
+            SubnetCallContext::BitcoinGetBlockFilters(context) => {
+                self.bitcoin_get_block_filters_contexts
+                    .insert(callback_id, context);
+            }
         };
 
         callback_id
@@ -347,6 +357,19 @@ impl SubnetCallContextManager {
                         SubnetCallContext::BitcoinSendTransactionInternal(context)
                     })
             })
+            .or_else(|| {
+                self.bitcoin_get_block_filters_contexts
+                    .remove(&callback_id)
+                    .map(|context| {
+                        info!(
+                            logger,
+                            "Received the response for BitcoinGetBlockFilters with callback id {:?} from {:?}",
+                            context.request.sender_reply_callback,
+                            context.request.sender
+                        );
+                        SubnetCallContext::BitcoinGetBlockFilters(context)
+                    })
+            })
     }
 
     pub fn push_install_code_call(&mut self, call: InstallCodeCall) -> InstallCodeCallId {
@@ -727,6 +750,13 @@ pub struct BitcoinSendTransactionInternalContext {
     pub time: Time,
 }
 
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct BitcoinGetBlockFiltersContext {
+    pub request: Request,
+    pub payload: GetBlockFiltersRequest,
+    pub time: Time,
+}
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct InstallCodeCall {
     pub call: CanisterCall,
@@ -784,6 +814,7 @@ mod testing {
             reshare_chain_key_contexts: Default::default(),
             bitcoin_get_successors_contexts: Default::default(),
             bitcoin_send_transaction_internal_contexts: Default::default(),
+            bitcoin_get_block_filters_contexts: Default::default(),
             canister_management_calls,
             raw_rand_contexts: Default::default(),
             pre_signature_stashes: Default::default(),
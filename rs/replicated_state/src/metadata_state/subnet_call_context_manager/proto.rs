@@ -79,6 +79,16 @@ impl From<&SubnetCallContextManager> for pb_metadata::SubnetCallContextManager {
                     }
                 })
                 .collect(),
+            bitcoin_get_block_filters_contexts: item
+                .bitcoin_get_block_filters_contexts
+                .iter()
+                .map(
+                    |(callback_id, context)| pb_metadata::BitcoinGetBlockFiltersContextTree {
+                        callback_id: callback_id.get(),
+                        context: Some(context.into()),
+                    },
+                )
+                .collect(),
             install_code_calls: item
                 .canister_management_calls
                 .install_code_call_manager
@@ -219,6 +229,17 @@ impl TryFrom<(Time, pb_metadata::SubnetCallContextManager)> for SubnetCallContex
                 .insert(CallbackId::new(entry.callback_id), context);
         }
 
+        let mut bitcoin_get_block_filters_contexts =
+            BTreeMap::<CallbackId, BitcoinGetBlockFiltersContext>::new();
+        for entry in item.bitcoin_get_block_filters_contexts {
+            let pb_context = try_from_option_field(
+                entry.context,
+                "SystemMetadata::BitcoinGetBlockFiltersContext",
+            )?;
+            let context = BitcoinGetBlockFiltersContext::try_from((time, pb_context))?;
+            bitcoin_get_block_filters_contexts.insert(CallbackId::new(entry.callback_id), context);
+        }
+
         let mut install_code_calls = BTreeMap::<InstallCodeCallId, InstallCodeCall>::new();
         // TODO(EXC-1454): Remove when `install_code_requests` field is not needed.
         for entry in item.install_code_requests {
@@ -263,6 +284,7 @@ impl TryFrom<(Time, pb_metadata::SubnetCallContextManager)> for SubnetCallContex
             canister_http_request_contexts,
             bitcoin_get_successors_contexts,
             bitcoin_send_transaction_internal_contexts,
+            bitcoin_get_block_filters_contexts,
             canister_management_calls: CanisterManagementCalls {
                 install_code_call_manager,
                 stop_canister_call_manager,
@@ -684,6 +706,37 @@ impl TryFrom<(Time, pb_metadata::BitcoinSendTransactionInternalContext)>
     }
 }
 
+impl From<&BitcoinGetBlockFiltersContext> for pb_metadata::BitcoinGetBlockFiltersContext {
+    fn from(context: &BitcoinGetBlockFiltersContext) -> Self {
+        Self {
+            request: Some((&context.request).into()),
+            payload: Some((&context.payload).into()),
+            time: Some(pb_metadata::Time {
+                time_nanos: context.time.as_nanos_since_unix_epoch(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<(Time, pb_metadata::BitcoinGetBlockFiltersContext)> for BitcoinGetBlockFiltersContext {
+    type Error = ProxyDecodeError;
+    fn try_from(
+        (time, context): (Time, pb_metadata::BitcoinGetBlockFiltersContext),
+    ) -> Result<Self, Self::Error> {
+        let request: Request =
+            try_from_option_field(context.request, "BitcoinGetBlockFiltersContext::request")?;
+        let payload: GetBlockFiltersRequest =
+            try_from_option_field(context.payload, "BitcoinGetBlockFiltersContext::payload")?;
+        Ok(BitcoinGetBlockFiltersContext {
+            request,
+            payload,
+            time: context
+                .time
+                .map_or(time, |t| Time::from_nanos_since_unix_epoch(t.time_nanos)),
+        })
+    }
+}
+
 impl From<&InstallCodeCall> for pb_metadata::InstallCodeCall {
     fn from(install_code_call: &InstallCodeCall) -> Self {
         use pb_metadata::install_code_call::CanisterCall as PbCanisterCall;
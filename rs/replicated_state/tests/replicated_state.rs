@@ -1,8 +1,9 @@
 use assert_matches::assert_matches;
 use ic_base_types::{CanisterId, NumBytes, NumSeconds, PrincipalId, SubnetId};
 use ic_btc_replica_types::{
-    BitcoinAdapterResponse, BitcoinAdapterResponseWrapper, BitcoinReject,
-    GetSuccessorsRequestInitial, GetSuccessorsResponseComplete, Network, SendTransactionRequest,
+    BitcoinAdapterResponse, BitcoinAdapterResponseWrapper, BitcoinReject, BlockFilter,
+    GetBlockFiltersRequest, GetBlockFiltersResponse, GetSuccessorsRequestInitial,
+    GetSuccessorsResponseComplete, Network, SendTransactionRequest,
 };
 use ic_error_types::RejectCode;
 use ic_management_canister_types_private::{
@@ -15,7 +16,8 @@ use ic_replicated_state::canister_state::execution_state::{
     CustomSection, CustomSectionType, WasmMetadata,
 };
 use ic_replicated_state::metadata_state::subnet_call_context_manager::{
-    BitcoinGetSuccessorsContext, BitcoinSendTransactionInternalContext, SubnetCallContext,
+    BitcoinGetBlockFiltersContext, BitcoinGetSuccessorsContext,
+    BitcoinSendTransactionInternalContext, SubnetCallContext,
 };
 use ic_replicated_state::replicated_state::testing::ReplicatedStateTesting;
 use ic_replicated_state::replicated_state::{
@@ -883,6 +885,42 @@ fn insert_bitcoin_send_transaction_reject_response() {
     );
 }
 
+#[test]
+fn insert_bitcoin_get_block_filters_response() {
+    let mut state = ReplicatedState::new(SUBNET_ID, SubnetType::Application);
+
+    state.metadata.subnet_call_context_manager.push_context(
+        SubnetCallContext::BitcoinGetBlockFilters(BitcoinGetBlockFiltersContext {
+            request: RequestBuilder::default().build(),
+            payload: GetBlockFiltersRequest {
+                network: Network::BitcoinRegtest,
+                block_hashes: vec![vec![1; 32]],
+                scripts: vec![],
+            },
+            time: UNIX_EPOCH,
+        }),
+    );
+
+    let response = GetBlockFiltersResponse {
+        filters: vec![BlockFilter {
+            block_hash: vec![1; 32],
+            filter: vec![2; 8],
+            matches: false,
+        }],
+    };
+
+    state
+        .push_response_bitcoin(BitcoinAdapterResponse {
+            response: BitcoinAdapterResponseWrapper::GetBlockFiltersResponse(response.clone()),
+            callback_id: 0,
+        })
+        .unwrap();
+    assert_eq!(
+        state.consensus_queue[0].payload,
+        Payload::Data(response.encode())
+    );
+}
+
 #[test]
 fn time_out_messages_updates_subnet_input_schedules_correctly() {
     let mut fixture = ReplicatedStateFixture::with_canisters(&[CANISTER_ID, OTHER_CANISTER_ID]);
@@ -723,3 +723,41 @@ impl ::std::fmt::Display for Operator {
         }
     }
 }
+
+/// BlockEvent represents the addition or removal of a BlockIdentifier from
+/// storage. Streaming BlockEvents allows lightweight clients to update their
+/// own state without needing to implement their own syncing logic.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct BlockEvent {
+    /// sequence is the unique identifier of a BlockEvent within the context of a NetworkIdentifier.
+    pub sequence: i64,
+
+    /// The block_identifier uniquely identifies a block in a particular network.
+    pub block_identifier: BlockIdentifier,
+
+    /// BlockEventType determines if a BlockEvent represents the addition or removal of a block.
+    #[serde(rename = "type")]
+    pub type_: BlockEventType,
+}
+
+/// BlockEventType determines if a BlockEvent represents the addition or
+/// removal of a block.
+#[allow(non_camel_case_types)]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Deserialize, Serialize)]
+pub enum BlockEventType {
+    /// A block was added to the canonical chain.
+    #[serde(rename = "block_added")]
+    BlockAdded,
+    /// A block was removed from the canonical chain in a reorg.
+    #[serde(rename = "block_removed")]
+    BlockRemoved,
+}
+
+impl ::std::fmt::Display for BlockEventType {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        match *self {
+            BlockEventType::BlockAdded => write!(f, "block_added"),
+            BlockEventType::BlockRemoved => write!(f, "block_removed"),
+        }
+    }
+}
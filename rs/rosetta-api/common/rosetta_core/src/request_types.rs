@@ -550,3 +550,34 @@ impl CallRequest {
         }
     }
 }
+
+/// EventsBlocksRequest is utilized to fetch a sequence of BlockEvents
+/// indicating which blocks were added and removed from storage to reach the
+/// current state.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct EventsBlocksRequest {
+    /// The network_identifier specifies which network a particular object is associated with.
+    pub network_identifier: NetworkIdentifier,
+
+    /// offset is the offset into the event stream to sync events from. If this field is not populated, we return the limit events backwards from tip. If this is set to 0, we start from the beginning.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+
+    /// limit is the maximum number of events to fetch in one call. The implementation may return less than limit events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+}
+
+impl EventsBlocksRequest {
+    pub fn new(
+        network_identifier: NetworkIdentifier,
+        offset: Option<i64>,
+        limit: Option<i64>,
+    ) -> EventsBlocksRequest {
+        EventsBlocksRequest {
+            network_identifier,
+            offset,
+            limit,
+        }
+    }
+}
@@ -534,6 +534,19 @@ impl RosettaClient {
         self.call_endpoint("/search/transactions", request).await
     }
 
+    pub async fn events_blocks(
+        &self,
+        network_identifier: NetworkIdentifier,
+        offset: Option<i64>,
+        limit: Option<i64>,
+    ) -> Result<EventsBlocksResponse, Error> {
+        self.call_endpoint(
+            "/events/blocks",
+            &EventsBlocksRequest::new(network_identifier, offset, limit),
+        )
+        .await
+    }
+
     pub async fn mempool(
         &self,
         network_identifier: NetworkIdentifier,
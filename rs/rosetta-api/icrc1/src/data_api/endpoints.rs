@@ -137,6 +137,15 @@ pub async fn search_transactions(
     )?))
 }
 
+pub async fn events_blocks(
+    State(state): State<Arc<MultiTokenAppState>>,
+    Json(request): Json<EventsBlocksRequest>,
+) -> Result<Json<EventsBlocksResponse>> {
+    let state = get_state_from_network_id(&request.network_identifier, &state)
+        .map_err(|err| Error::invalid_network_id(&format!("{err:?}")))?;
+    Ok(Json(services::events_blocks(&state.storage, request)?))
+}
+
 pub async fn call(
     State(state): State<Arc<MultiTokenAppState>>,
     Json(request): Json<CallRequest>,
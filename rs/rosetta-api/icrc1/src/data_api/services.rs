@@ -3,6 +3,7 @@ use std::sync::Mutex;
 
 use crate::common::constants::DEFAULT_BLOCKCHAIN;
 use crate::common::constants::MAX_BLOCKS_PER_QUERY_BLOCK_RANGE_REQUEST;
+use crate::common::constants::MAX_EVENTS_PER_EVENTS_BLOCKS_REQUEST;
 use crate::common::constants::MAX_TRANSACTIONS_PER_SEARCH_TRANSACTIONS_REQUEST;
 use crate::common::constants::STATUS_COMPLETED;
 use crate::common::types::OperationType;
@@ -24,6 +25,7 @@ use ic_ledger_core::tokens::Zero;
 use icrc_ledger_types::icrc1::account::Account;
 use num_bigint::{BigInt, BigUint};
 use rosetta_core::miscellaneous::OperationStatus;
+use rosetta_core::request_types::EventsBlocksRequest;
 use rosetta_core::request_types::SearchTransactionsRequest;
 use rosetta_core::response_types::SearchTransactionsResponse;
 use rosetta_core::{identifiers::*, miscellaneous::Version, objects::*, response_types::*};
@@ -529,6 +531,72 @@ pub fn search_transactions(
     })
 }
 
+/// Returns the stream of block events of the synced blockchain.
+/// The ICRC-1 ledger has no forks, so every event is a `block_added` event and its sequence
+/// number is the index of the added block. This keeps sequence numbers stable across re-syncs
+/// of the local storage. Only blocks that have been fully processed and that are not preceded by
+/// a gap are reported.
+pub fn events_blocks(
+    storage_client: &StorageClient,
+    request: EventsBlocksRequest,
+) -> Result<EventsBlocksResponse, Error> {
+    let max_sequence = storage_client
+        .get_highest_block_idx_in_account_balance_table()
+        .map_err(|e| Error::unable_to_find_block(&e))?
+        .ok_or_else(|| {
+            Error::unable_to_find_block(&"Highest processed block not found".to_owned())
+        })?;
+
+    let limit: u64 = request
+        .limit
+        .unwrap_or(MAX_EVENTS_PER_EVENTS_BLOCKS_REQUEST as i64)
+        .try_into()
+        .map_err(|err| {
+            Error::request_processing_error(&format!("Limit has to be a valid u64: {err}"))
+        })?;
+    let limit = limit.min(MAX_EVENTS_PER_EVENTS_BLOCKS_REQUEST);
+
+    let offset: Option<u64> = request
+        .offset
+        .map(|offset| {
+            offset.try_into().map_err(|err| {
+                Error::request_processing_error(&format!("Offset has to be a valid u64: {err}"))
+            })
+        })
+        .transpose()?;
+
+    if limit == 0 {
+        return Ok(EventsBlocksResponse::new(max_sequence as i64, vec![]));
+    }
+
+    // Without an offset the last `limit` events up to the tip are returned.
+    let start_sequence =
+        offset.unwrap_or_else(|| max_sequence.saturating_sub(limit.saturating_sub(1)));
+    if start_sequence > max_sequence {
+        return Ok(EventsBlocksResponse::new(max_sequence as i64, vec![]));
+    }
+    let end_sequence = max_sequence.min(start_sequence.saturating_add(limit - 1));
+
+    let mut rosetta_blocks = storage_client
+        .get_blocks_by_index_range(start_sequence, end_sequence)
+        .map_err(|e| Error::unable_to_find_block(&e))?;
+    rosetta_blocks.sort_by_key(|block| block.index);
+
+    let events = rosetta_blocks
+        .into_iter()
+        .zip(start_sequence..)
+        // Stop at the first gap so that no sequence number is ever skipped.
+        .take_while(|(block, sequence)| block.index == *sequence)
+        .map(|(block, sequence)| BlockEvent {
+            sequence: sequence as i64,
+            block_identifier: BlockIdentifier::from(block),
+            type_: BlockEventType::BlockAdded,
+        })
+        .collect();
+
+    Ok(EventsBlocksResponse::new(max_sequence as i64, events))
+}
+
 pub fn initial_sync_is_completed(
     storage_client: &StorageClient,
     sync_state: Arc<Mutex<Option<bool>>>,
@@ -1334,6 +1402,129 @@ mod test {
         assert!(block_res.is_err());
     }
 
+    #[test]
+    fn test_events_blocks() {
+        let mut runner = TestRunner::new(TestRunnerConfig {
+            max_shrink_iters: 0,
+            cases: 1,
+            ..Default::default()
+        });
+
+        runner
+            .run(
+                &(valid_blockchain_strategy::<U256>(BLOCKCHAIN_LENGTH).no_shrink()),
+                |blockchain| {
+                    let network_identifier = NetworkIdentifier::new(
+                        DEFAULT_BLOCKCHAIN.to_owned(),
+                        Principal::anonymous().to_string(),
+                    );
+                    let request = |offset: Option<i64>, limit: Option<i64>| {
+                        EventsBlocksRequest::new(network_identifier.clone(), offset, limit)
+                    };
+                    let mut rosetta_blocks = vec![];
+                    for block in blockchain.into_iter() {
+                        // We only push Mint blocks since `update_account_balances` will
+                        // complain if we e.g., transfer from an account with no balance.
+                        if let ic_icrc1::Operation::Mint { .. } = block.transaction.operation {
+                            let mut block_no_fc = block;
+                            block_no_fc.fee_collector_block_index = None;
+                            rosetta_blocks.push(
+                                RosettaBlock::from_generic_block(
+                                    encoded_block_to_generic_block(&block_no_fc.encode()),
+                                    rosetta_blocks.len() as u64,
+                                )
+                                .unwrap(),
+                            );
+                        }
+                    }
+                    if rosetta_blocks.is_empty() {
+                        return Ok(());
+                    }
+                    let max_sequence = rosetta_blocks.len() as i64 - 1;
+
+                    let sync_storage = || {
+                        let storage_client_memory = StorageClient::new_in_memory().unwrap();
+                        storage_client_memory
+                            .store_blocks(rosetta_blocks.clone())
+                            .unwrap();
+                        storage_client_memory.update_account_balances().unwrap();
+                        storage_client_memory
+                    };
+
+                    let empty_storage = StorageClient::new_in_memory().unwrap();
+                    assert!(
+                        events_blocks(&empty_storage, request(Some(0), None))
+                            .unwrap_err()
+                            .0
+                            .message
+                            .contains("Unable to find block")
+                    );
+
+                    let storage_client_memory = sync_storage();
+                    let all_events =
+                        events_blocks(&storage_client_memory, request(Some(0), None)).unwrap();
+                    assert_eq!(all_events.max_sequence, max_sequence);
+                    assert_eq!(
+                        all_events.events,
+                        rosetta_blocks
+                            .iter()
+                            .map(|block| BlockEvent {
+                                sequence: block.index as i64,
+                                block_identifier: BlockIdentifier::from(block.clone()),
+                                type_: BlockEventType::BlockAdded,
+                            })
+                            .collect::<Vec<_>>()
+                    );
+
+                    // Without an offset, the last events up to the tip are returned.
+                    let tip_events =
+                        events_blocks(&storage_client_memory, request(None, Some(2))).unwrap();
+                    assert_eq!(
+                        tip_events.events,
+                        all_events.events[all_events.events.len().saturating_sub(2)..].to_vec()
+                    );
+
+                    let page =
+                        events_blocks(&storage_client_memory, request(Some(1), Some(3))).unwrap();
+                    assert_eq!(
+                        page.events,
+                        all_events
+                            .events
+                            .iter()
+                            .skip(1)
+                            .take(3)
+                            .cloned()
+                            .collect::<Vec<_>>()
+                    );
+
+                    let past_tip = events_blocks(
+                        &storage_client_memory,
+                        request(Some(max_sequence + 1), None),
+                    )
+                    .unwrap();
+                    assert_eq!(past_tip.max_sequence, max_sequence);
+                    assert!(past_tip.events.is_empty());
+
+                    assert!(
+                        events_blocks(&storage_client_memory, request(Some(-1), None)).is_err()
+                    );
+                    assert!(
+                        events_blocks(&storage_client_memory, request(None, Some(-1))).is_err()
+                    );
+
+                    // Sequence numbers are the same after syncing the blockchain from scratch.
+                    let resynced_storage = sync_storage();
+                    assert_eq!(
+                        events_blocks(&resynced_storage, request(Some(0), None)).unwrap(),
+                        all_events
+                    );
+
+                    Ok(())
+                },
+            )
+            .unwrap()
+    }
+
     #[test]
     fn test_call_query_blocks() {
         let mut runner = TestRunner::new(TestRunnerConfig {
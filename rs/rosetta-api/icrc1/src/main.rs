@@ -368,6 +368,7 @@ async fn main() -> Result<()> {
         .route("/account/balance", post(account_balance))
         .route("/block/transaction", post(block_transaction))
         .route("/search/transactions", post(search_transactions))
+        .route("/events/blocks", post(events_blocks))
         .route("/mempool", post(mempool))
         .route("/mempool/transaction", post(mempool_transaction))
         .route("/construction/derive", post(construction_derive))
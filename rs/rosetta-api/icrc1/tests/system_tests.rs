@@ -1817,6 +1817,75 @@ fn test_search_transactions() {
         .unwrap()
 }
 
+#[test]
+fn test_events_blocks() {
+    let mut runner = TestRunner::new(TestRunnerConfig {
+        max_shrink_iters: 0,
+        cases: *NUM_TEST_CASES,
+        ..Default::default()
+    });
+
+    runner
+        .run(
+            &(valid_transactions_strategy(
+                (*MINTING_IDENTITY).clone(),
+                DEFAULT_TRANSFER_FEE,
+                50,
+                SystemTime::now(),
+            )
+            .no_shrink()),
+            |args_with_caller| {
+                let rt = Runtime::new().unwrap();
+                let setup = Setup::builder().build();
+
+                rt.block_on(async {
+                    let env = RosettaTestingEnvironmentBuilder::new(&setup)
+                        .with_args_with_caller(args_with_caller.clone())
+                        .build()
+                        .await;
+
+                    if !args_with_caller.is_empty() {
+                        let rosetta_blocks = get_rosetta_blocks_from_icrc1_ledger(
+                            env.icrc1_agent,
+                            0,
+                            *MAX_BLOCKS_PER_REQUEST,
+                        )
+                        .await;
+                        let last_block_index = rosetta_blocks.len() as u64 - 1;
+                        wait_for_rosetta_block(
+                            &env.rosetta_client,
+                            env.network_identifier.clone(),
+                            last_block_index,
+                        )
+                        .await;
+
+                        let events_blocks_response = env
+                            .rosetta_client
+                            .events_blocks(env.network_identifier.clone(), Some(0), None)
+                            .await
+                            .expect("Unable to call events_blocks");
+
+                        assert_eq!(events_blocks_response.max_sequence, last_block_index as i64);
+                        assert_eq!(
+                            events_blocks_response.events,
+                            rosetta_blocks
+                                .into_iter()
+                                .map(|block| BlockEvent {
+                                    sequence: block.index as i64,
+                                    block_identifier: block.get_block_identifier(),
+                                    type_: BlockEventType::BlockAdded,
+                                })
+                                .collect::<Vec<_>>()
+                        );
+                    }
+
+                    Ok(())
+                })
+            },
+        )
+        .unwrap()
+}
+
 #[cfg(not(target_os = "macos"))]
 #[test]
 fn test_cli_data() {
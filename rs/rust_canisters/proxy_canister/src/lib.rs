@@ -56,6 +56,7 @@ impl From<UnvalidatedCanisterHttpRequestArgs>
             transform: args.transform,
             is_replicated: args.is_replicated,
             pricing_version: args.pricing_version,
+            aggregate_by_canister: None,
         }
     }
 }
@@ -1,7 +1,7 @@
 use ic_base_types::{NumBytes, NumSeconds};
 use ic_logger::{ReplicaLogger, error, info, warn};
 use ic_management_canister_types_private::{
-    Global, LogVisibilityV2, OnLowWasmMemoryHookStatus, SnapshotSource,
+    ControllerRoles, Global, LogVisibilityV2, OnLowWasmMemoryHookStatus, SnapshotSource,
 };
 use ic_metrics::{MetricsRegistry, buckets::decimal_buckets};
 use ic_protobuf::state::{
@@ -14,7 +14,8 @@ use ic_replicated_state::{
     canister_state::{
         execution_state::{NextScheduledMethod, WasmMetadata},
         system_state::{
-            CanisterHistory, CyclesUseCase, TaskQueue, wasm_chunk_store::WasmChunkStoreMetadata,
+            CanisterHistory, CyclesUseCase, QueryCacheTags, TaskQueue,
+            wasm_chunk_store::WasmChunkStoreMetadata,
         },
     },
     page_map::{Shard, StorageLayout, StorageResult},
@@ -150,6 +151,7 @@ pub struct ExecutionStateBits {
     pub binary_hash: WasmHash,
     pub next_scheduled_method: NextScheduledMethod,
     pub is_wasm64: bool,
+    pub executed_instructions: NumInstructions,
 }
 
 /// This struct contains bits of the `CanisterState` that are not already
@@ -195,6 +197,8 @@ pub struct CanisterStateBits {
     pub snapshots_memory_usage: NumBytes,
     pub task_queue: TaskQueue,
     pub environment_variables: BTreeMap<String, String>,
+    pub query_cache_tags: QueryCacheTags,
+    pub controller_roles: ControllerRoles,
 }
 
 /// This struct contains bits of the `CanisterSnapshot` that are not already
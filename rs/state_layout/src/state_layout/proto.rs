@@ -73,6 +73,8 @@ impl From<CanisterStateBits> for pb_canister_state_bits::CanisterStateBits {
             snapshots_memory_usage: item.snapshots_memory_usage.get(),
             tasks: Some((&item.task_queue).into()),
             environment_variables: item.environment_variables.into_iter().collect(),
+            query_cache_tags: Some((&item.query_cache_tags).into()),
+            controller_roles: Some((&item.controller_roles).into()),
         }
     }
 }
@@ -209,6 +211,14 @@ impl TryFrom<pb_canister_state_bits::CanisterStateBits> for CanisterStateBits {
             snapshots_memory_usage: NumBytes::from(value.snapshots_memory_usage),
             task_queue,
             environment_variables: value.environment_variables.into_iter().collect(),
+            query_cache_tags: value
+                .query_cache_tags
+                .map(QueryCacheTags::from)
+                .unwrap_or_default(),
+            controller_roles: match value.controller_roles {
+                Some(controller_roles) => controller_roles.try_into()?,
+                None => ControllerRoles::default(),
+            },
         })
     }
 }
@@ -235,6 +245,7 @@ impl From<&ExecutionStateBits> for pb_canister_state_bits::ExecutionStateBits {
                     .into(),
             ),
             is_wasm64: item.is_wasm64,
+            executed_instructions: item.executed_instructions.get(),
         }
     }
 }
@@ -271,6 +282,7 @@ impl TryFrom<pb_canister_state_bits::ExecutionStateBits> for ExecutionStateBits
                 None => NextScheduledMethod::default(),
             },
             is_wasm64: value.is_wasm64,
+            executed_instructions: value.executed_instructions.into(),
         })
     }
 }
@@ -62,6 +62,8 @@ fn default_canister_state_bits() -> CanisterStateBits {
         next_snapshot_id: 0,
         snapshots_memory_usage: NumBytes::from(0),
         environment_variables: BTreeMap::new(),
+        query_cache_tags: QueryCacheTags::default(),
+        controller_roles: ControllerRoles::default(),
     }
 }
 
@@ -191,6 +193,19 @@ fn test_encode_decode_non_empty_history() {
         CanisterChangeOrigin::from_canister(canister_test_id(123).get(), None),
         CanisterChangeDetails::controllers_change(vec![]),
     ));
+    canister_history.add_canister_change(CanisterChange::new(
+        444,
+        6,
+        CanisterChangeOrigin::from_canister(canister_test_id(123).get(), None),
+        CanisterChangeDetails::controllers_and_roles_change(
+            vec![canister_test_id(123).into()],
+            ControllerRoles::new(
+                vec![user_test_id(1).get()],
+                vec![],
+                vec![user_test_id(2).get()],
+            ),
+        ),
+    ));
     canister_history.add_canister_change(CanisterChange::new(
         555,
         7,
@@ -293,6 +308,44 @@ fn test_encode_decode_non_empty_environment_variables() {
     );
 }
 
+#[test]
+fn test_encode_decode_query_cache_tags() {
+    let mut query_cache_tags = QueryCacheTags::default();
+    query_cache_tags.invalidate(b"tag1");
+    query_cache_tags.invalidate(b"tag2");
+
+    let canister_state_bits = CanisterStateBits {
+        query_cache_tags: query_cache_tags.clone(),
+        ..default_canister_state_bits()
+    };
+    let pb_bits = pb_canister_state_bits::CanisterStateBits::from(canister_state_bits);
+    let decoded_canister_state_bits = CanisterStateBits::try_from(pb_bits).unwrap();
+    assert_eq!(
+        decoded_canister_state_bits.query_cache_tags,
+        query_cache_tags
+    );
+}
+
+#[test]
+fn test_encode_decode_controller_roles() {
+    let controller_roles = ControllerRoles::new(
+        vec![user_test_id(1).get()],
+        vec![user_test_id(2).get(), canister_test_id(3).get()],
+        vec![user_test_id(4).get()],
+    );
+
+    let canister_state_bits = CanisterStateBits {
+        controller_roles: controller_roles.clone(),
+        ..default_canister_state_bits()
+    };
+    let pb_bits = pb_canister_state_bits::CanisterStateBits::from(canister_state_bits);
+    let decoded_canister_state_bits = CanisterStateBits::try_from(pb_bits).unwrap();
+    assert_eq!(
+        decoded_canister_state_bits.controller_roles,
+        controller_roles
+    );
+}
+
 #[test]
 fn test_encode_decode_task_queue() {
     let ingress = Arc::new(IngressBuilder::new().method_name("test_ingress").build());
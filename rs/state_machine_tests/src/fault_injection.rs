@@ -0,0 +1,399 @@
+//! Deterministic fault injection for inter-canister calls.
+//!
+//! A [`FaultInjection`] holds a set of [`FaultInjectionRule`]s that are applied
+//! to the canister messages produced in every round of the `StateMachine`s it
+//! is installed in. Messages affected by a rule are taken out of the canister
+//! output queues before message routing builds the streams and are delivered
+//! directly into the input queue of their receiver once they are due.
+//!
+//! `StateMachine`s with fault injection do not induct messages between
+//! canisters on the same subnet within a round, so that fault injection also
+//! applies to such calls.
+//!
+//! The rules and held messages can be serialized (see
+//! [`FaultInjection::serialize`]) so that they survive a checkpoint and
+//! restore of the test environment.
+
+use ic_error_types::RejectCode;
+use ic_interfaces::execution_environment::{
+    ExecutionRoundSummary, ExecutionRoundType, RegistryExecutionSettings, Scheduler,
+};
+use ic_logger::{ReplicaLogger, warn};
+use ic_replicated_state::ReplicatedState;
+use ic_types::{
+    CanisterId, ExecutionRound, Randomness, ReplicaVersion, SubnetId,
+    batch::ChainKeyData,
+    messages::{CallbackId, Payload, RejectContext, Request, RequestOrResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// The fault to inject into a matching inter-canister call.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub enum FaultInjectionAction {
+    /// The request is dropped and the caller receives a reject response with
+    /// the given code and message instead.
+    Reject { code: RejectCode, message: String },
+    /// The response to the (best-effort) call is dropped so that the call
+    /// times out once its deadline expires. Guaranteed response calls are not
+    /// affected.
+    Timeout,
+    /// The request is delivered to the callee the given number of rounds
+    /// later than it would have been delivered otherwise.
+    Delay { rounds: u64 },
+}
+
+/// A rule matching inter-canister calls by caller, callee and method name
+/// (`None` matches any value).
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct FaultInjectionRule {
+    pub caller: Option<CanisterId>,
+    pub callee: Option<CanisterId>,
+    pub method_name: Option<String>,
+    pub action: FaultInjectionAction,
+}
+
+impl FaultInjectionRule {
+    fn matches_queue(&self, sender: &CanisterId, receiver: &CanisterId) -> bool {
+        self.caller.is_none_or(|caller| caller == *sender)
+            && self.callee.is_none_or(|callee| callee == *receiver)
+    }
+
+    fn matches_request(&self, request: &Request) -> bool {
+        self.matches_queue(&request.sender, &request.receiver)
+            && self
+                .method_name
+                .as_ref()
+                .is_none_or(|method_name| *method_name == request.method_name)
+    }
+}
+
+/// A message taken out of an output queue that is waiting to be delivered.
+#[derive(Deserialize, Serialize)]
+struct HeldMessage {
+    destination: SubnetId,
+    rounds_left: u64,
+    #[serde(with = "request_or_response")]
+    msg: RequestOrResponse,
+}
+
+/// (De)serializes a `RequestOrResponse` via its (serializable) contents.
+mod request_or_response {
+    use ic_types::messages::{Request, RequestOrResponse, Response};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::sync::Arc;
+
+    #[derive(Deserialize, Serialize)]
+    enum Message {
+        Request(Request),
+        Response(Response),
+    }
+
+    pub(super) fn serialize<S: Serializer>(
+        msg: &RequestOrResponse,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match msg {
+            RequestOrResponse::Request(request) => Message::Request(request.as_ref().clone()),
+            RequestOrResponse::Response(response) => Message::Response(response.as_ref().clone()),
+        }
+        .serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<RequestOrResponse, D::Error> {
+        Ok(match Message::deserialize(deserializer)? {
+            Message::Request(request) => RequestOrResponse::Request(Arc::new(request)),
+            Message::Response(response) => RequestOrResponse::Response(Arc::new(response)),
+        })
+    }
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct HeldMessages {
+    /// Messages in the order in which they are to be delivered.
+    messages: Vec<HeldMessage>,
+    /// Callbacks (identified by the originator and the callback ID) whose
+    /// responses are to be dropped.
+    dropped_responses: BTreeSet<(CanisterId, CallbackId)>,
+}
+
+/// Fault injection rules shared by all `StateMachine`s of a (multi-subnet)
+/// test environment, together with the messages held back by these rules.
+#[derive(Default)]
+pub struct FaultInjection {
+    rules: RwLock<Vec<FaultInjectionRule>>,
+    held: Mutex<HeldMessages>,
+}
+
+impl FaultInjection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule. If multiple rules match a call, the rule added first applies.
+    pub fn add_rule(&self, rule: FaultInjectionRule) {
+        self.rules.write().unwrap().push(rule);
+    }
+
+    /// Removes all rules. Messages that are already held back are still
+    /// delivered once they are due.
+    pub fn clear_rules(&self) {
+        self.rules.write().unwrap().clear();
+    }
+
+    pub fn rules(&self) -> Vec<FaultInjectionRule> {
+        self.rules.read().unwrap().clone()
+    }
+
+    /// Serializes the rules together with the messages currently held back.
+    pub fn serialize(&self) -> Vec<u8> {
+        let rules = self.rules.read().unwrap();
+        let held = self.held.lock().unwrap();
+        serde_cbor::to_vec(&SerializedFaultInjection {
+            rules: &rules,
+            held: &held,
+        })
+        .expect("Failed to serialize fault injection state")
+    }
+
+    /// Restores a `FaultInjection` from the output of [`Self::serialize`].
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, String> {
+        let DeserializedFaultInjection { rules, held } = serde_cbor::from_slice(bytes)
+            .map_err(|err| format!("Failed to deserialize fault injection state: {err}"))?;
+        Ok(Self {
+            rules: RwLock::new(rules),
+            held: Mutex::new(held),
+        })
+    }
+
+    /// Delivers all held messages destined for the subnet of `state` that are
+    /// due in this round.
+    fn deliver_held_messages(&self, state: &mut ReplicatedState, log: &ReplicaLogger) {
+        let own_subnet_id = state.metadata.own_subnet_id;
+        // Fault injection is a testing facility: the subnet message memory
+        // limits are not enforced for held messages.
+        let mut subnet_available_guaranteed_response_memory = i64::MAX;
+
+        let mut held = self.held.lock().unwrap();
+        let mut messages = Vec::with_capacity(held.messages.len());
+        for mut held_msg in std::mem::take(&mut held.messages) {
+            if held_msg.destination != own_subnet_id {
+                messages.push(held_msg);
+                continue;
+            }
+            if held_msg.rounds_left > 0 {
+                held_msg.rounds_left -= 1;
+                messages.push(held_msg);
+                continue;
+            }
+            match state.push_input(
+                held_msg.msg,
+                &mut subnet_available_guaranteed_response_memory,
+            ) {
+                Ok(_) => {}
+                Err((err, RequestOrResponse::Request(request))) => {
+                    let response = reject_response(
+                        &request,
+                        RejectCode::SysTransient,
+                        format!("Failed to deliver request: {err}"),
+                    );
+                    messages.push(HeldMessage {
+                        destination: destination(state, &request.sender),
+                        rounds_left: 0,
+                        msg: RequestOrResponse::Response(Arc::new(response)),
+                    });
+                }
+                Err((err, RequestOrResponse::Response(response))) => {
+                    warn!(log, "Dropping held response {:?}: {}", response, err);
+                }
+            }
+        }
+        held.messages = messages;
+    }
+
+    /// Takes all messages out of the output queues that are affected by a rule
+    /// (or that follow such a message in the same output queue) and applies
+    /// the matching rules to them.
+    fn intercept_output_messages(&self, state: &mut ReplicatedState) {
+        let rules = self.rules.read().unwrap();
+        let mut held = self.held.lock().unwrap();
+        if rules.is_empty() && held.dropped_responses.is_empty() {
+            return;
+        }
+
+        // Take out all messages from the affected output queues, preserving
+        // the order of messages within each queue.
+        let mut intercepted = vec![];
+        for canister in state.canisters_iter_mut() {
+            let sender = canister.canister_id();
+            let mut iter = canister.output_into_iter();
+            while let Some(msg) = iter.peek() {
+                let receiver = msg.receiver();
+                let affected = rules
+                    .iter()
+                    .any(|rule| rule.matches_queue(&sender, &receiver))
+                    || held
+                        .dropped_responses
+                        .iter()
+                        .any(|(originator, _)| *originator == receiver);
+                if affected {
+                    intercepted.push(iter.pop().unwrap());
+                } else {
+                    iter.exclude_queue();
+                }
+            }
+        }
+
+        for msg in intercepted {
+            // Messages must not overtake held messages between the same sender
+            // and receiver.
+            let min_rounds = held
+                .messages
+                .iter()
+                .filter(|held_msg| {
+                    held_msg.msg.sender() == msg.sender()
+                        && held_msg.msg.receiver() == msg.receiver()
+                })
+                .map(|held_msg| held_msg.rounds_left)
+                .max()
+                .unwrap_or_default();
+            let (msg, rounds) = match msg {
+                RequestOrResponse::Request(request) => {
+                    match rules.iter().find(|rule| rule.matches_request(&request)) {
+                        Some(FaultInjectionRule {
+                            action: FaultInjectionAction::Reject { code, message },
+                            ..
+                        }) => {
+                            let response = reject_response(&request, *code, message.clone());
+                            held.messages.push(HeldMessage {
+                                destination: destination(state, &request.sender),
+                                rounds_left: 0,
+                                msg: RequestOrResponse::Response(Arc::new(response)),
+                            });
+                            continue;
+                        }
+                        Some(FaultInjectionRule {
+                            action: FaultInjectionAction::Timeout,
+                            ..
+                        }) if request.is_best_effort() => {
+                            held.dropped_responses
+                                .insert((request.sender, request.sender_reply_callback));
+                            (RequestOrResponse::Request(request), min_rounds)
+                        }
+                        Some(FaultInjectionRule {
+                            action: FaultInjectionAction::Delay { rounds },
+                            ..
+                        }) => (RequestOrResponse::Request(request), min_rounds.max(*rounds)),
+                        _ => (RequestOrResponse::Request(request), min_rounds),
+                    }
+                }
+                RequestOrResponse::Response(response) => {
+                    if held
+                        .dropped_responses
+                        .remove(&(response.originator, response.originator_reply_callback))
+                    {
+                        continue;
+                    }
+                    (RequestOrResponse::Response(response), min_rounds)
+                }
+            };
+            held.messages.push(HeldMessage {
+                destination: destination(state, &msg.receiver()),
+                rounds_left: rounds,
+                msg,
+            });
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SerializedFaultInjection<'a> {
+    rules: &'a Vec<FaultInjectionRule>,
+    held: &'a HeldMessages,
+}
+
+#[derive(Deserialize)]
+struct DeserializedFaultInjection {
+    rules: Vec<FaultInjectionRule>,
+    held: HeldMessages,
+}
+
+/// Returns the subnet hosting the given canister (defaulting to the own subnet
+/// so that delivering to a non-existent canister fails there).
+fn destination(state: &ReplicatedState, canister_id: &CanisterId) -> SubnetId {
+    if state.canister_state(canister_id).is_some() {
+        return state.metadata.own_subnet_id;
+    }
+    state
+        .metadata
+        .network_topology
+        .route(canister_id.get())
+        .unwrap_or(state.metadata.own_subnet_id)
+}
+
+fn reject_response(request: &Request, code: RejectCode, message: String) -> Response {
+    Response {
+        originator: request.sender,
+        respondent: request.receiver,
+        originator_reply_callback: request.sender_reply_callback,
+        refund: request.payment,
+        response_payload: Payload::Reject(RejectContext::new(code, message)),
+        deadline: request.deadline,
+    }
+}
+
+/// A `Scheduler` applying the rules of a [`FaultInjection`] around every
+/// execution round of the wrapped scheduler.
+pub(crate) struct FaultInjectingScheduler {
+    inner: Box<dyn Scheduler<State = ReplicatedState>>,
+    fault_injection: Arc<FaultInjection>,
+    log: ReplicaLogger,
+}
+
+impl FaultInjectingScheduler {
+    pub(crate) fn new(
+        inner: Box<dyn Scheduler<State = ReplicatedState>>,
+        fault_injection: Arc<FaultInjection>,
+        log: ReplicaLogger,
+    ) -> Self {
+        Self {
+            inner,
+            fault_injection,
+            log,
+        }
+    }
+}
+
+impl Scheduler for FaultInjectingScheduler {
+    type State = ReplicatedState;
+
+    fn execute_round(
+        &self,
+        mut state: ReplicatedState,
+        randomness: Randomness,
+        chain_key_data: ChainKeyData,
+        replica_version: &ReplicaVersion,
+        current_round: ExecutionRound,
+        round_summary: Option<ExecutionRoundSummary>,
+        current_round_type: ExecutionRoundType,
+        registry_settings: &RegistryExecutionSettings,
+    ) -> ReplicatedState {
+        self.fault_injection
+            .deliver_held_messages(&mut state, &self.log);
+        let mut state = self.inner.execute_round(
+            state,
+            randomness,
+            chain_key_data,
+            replica_version,
+            current_round,
+            round_summary,
+            current_round_type,
+            registry_settings,
+        );
+        self.fault_injection.intercept_output_messages(&mut state);
+        state
+    }
+}
@@ -25,7 +25,7 @@ use ic_crypto_utils_threshold_sig_der::threshold_sig_public_key_to_der;
 use ic_cycles_account_manager::{CyclesAccountManager, IngressInductionCost};
 use ic_error_types::RejectCode;
 pub use ic_error_types::{ErrorCode, UserError};
-use ic_execution_environment::{ExecutionServices, IngressHistoryReaderImpl};
+use ic_execution_environment::{ExecutionProfiler, ExecutionServices, IngressHistoryReaderImpl};
 use ic_http_endpoints_public::{IngressWatcher, IngressWatcherHandle, metrics::HttpHandlerMetrics};
 use ic_https_outcalls_consensus::payload_builder::CanisterHttpPayloadBuilderImpl;
 use ic_ingress_manager::{IngressManager, RandomStateKind};
@@ -37,7 +37,7 @@ use ic_interfaces::{
     consensus_pool::ConsensusTime,
     execution_environment::{
         IngressFilterService, IngressHistoryReader, QueryExecutionInput, QueryExecutionService,
-        TransformExecutionService,
+        Scheduler, TransformExecutionService,
     },
     ingress_pool::{
         IngressPool, IngressPoolObject, PoolSection, UnvalidatedIngressArtifact,
@@ -204,9 +204,13 @@ const COMPLETED_EXECUTION_MESSAGES_BUFFER_SIZE: usize = 10_000;
 
 const SNAPSHOT_DATA_CHUNK_SIZE: u64 = 2_000_000;
 
+mod fault_injection;
 #[cfg(test)]
 mod tests;
 
+use fault_injection::FaultInjectingScheduler;
+pub use fault_injection::{FaultInjection, FaultInjectionAction, FaultInjectionRule};
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Deserialize, Serialize)]
 pub enum SubmitIngressError {
     HttpError(String),
@@ -891,6 +895,7 @@ impl StateMachineNode {
 #[allow(clippy::large_enum_variant)]
 enum SignatureSecretKey {
     EcdsaSecp256k1(ic_secp256k1::PrivateKey),
+    EcdsaSecp256r1(ic_secp256r1::PrivateKey),
     SchnorrBip340(ic_secp256k1::PrivateKey),
     Ed25519(ic_ed25519::DerivedPrivateKey),
     VetKD(ic_crypto_test_utils_vetkd::PrivateKey),
@@ -951,6 +956,7 @@ pub struct StateMachine {
     vetkd_payload_builder: Arc<dyn BatchPayloadBuilder>,
     remove_old_states: bool,
     cycles_account_manager: Arc<CyclesAccountManager>,
+    execution_profiler: Option<Arc<ExecutionProfiler>>,
     cost_schedule: CanisterCyclesCostSchedule,
     // This field must be the last one so that the temporary directory is deleted at the very end.
     state_dir: Box<dyn StateMachineStateDir>,
@@ -1036,6 +1042,7 @@ pub struct StateMachineBuilder {
     /// Otherwise, no new registry records are created.
     create_at_registry_version: Option<RegistryVersion>,
     cost_schedule: CanisterCyclesCostSchedule,
+    fault_injection: Option<Arc<FaultInjection>>,
 }
 
 impl StateMachineBuilder {
@@ -1077,6 +1084,7 @@ impl StateMachineBuilder {
             remove_old_states: true,
             create_at_registry_version: Some(INITIAL_REGISTRY_VERSION),
             cost_schedule: CanisterCyclesCostSchedule::Normal,
+            fault_injection: None,
         }
     }
 
@@ -1326,6 +1334,17 @@ impl StateMachineBuilder {
         }
     }
 
+    /// Applies the rules of the given `FaultInjection` to the inter-canister
+    /// calls of the `StateMachine`. This disables same-subnet message induction
+    /// so that calls between canisters on the same subnet take at least one
+    /// round to be delivered.
+    pub fn with_fault_injection(self, fault_injection: Option<Arc<FaultInjection>>) -> Self {
+        Self {
+            fault_injection,
+            ..self
+        }
+    }
+
     pub fn build_internal(self) -> StateMachine {
         StateMachine::setup_from_dir(
             self.state_dir,
@@ -1361,6 +1380,7 @@ impl StateMachineBuilder {
             self.remove_old_states,
             self.create_at_registry_version,
             self.cost_schedule,
+            self.fault_injection,
         )
     }
 
@@ -1700,6 +1720,7 @@ impl StateMachine {
         remove_old_states: bool,
         create_at_registry_version: Option<RegistryVersion>,
         cost_schedule: CanisterCyclesCostSchedule,
+        fault_injection: Option<Arc<FaultInjection>>,
     ) -> Self {
         let checkpoint_interval_length = checkpoint_interval_length.unwrap_or(match subnet_type {
             SubnetType::Application | SubnetType::VerifiedApplication => 499,
@@ -1829,8 +1850,16 @@ impl StateMachine {
         // experience.
         //
         // The API state machine provides is blocking anyway.
+        //
+        // With fault injection, calls between canisters on this subnet must be
+        // routed via the loopback stream so that they can be intercepted.
+        let setup_execution = if fault_injection.is_some() {
+            ExecutionServices::setup_execution_without_same_subnet_message_induction
+        } else {
+            ExecutionServices::setup_execution
+        };
         let execution_services = runtime.block_on(async {
-            ExecutionServices::setup_execution(
+            setup_execution(
                 replica_logger.clone(),
                 &metrics_registry,
                 subnet_id,
@@ -1844,11 +1873,19 @@ impl StateMachine {
             )
         });
 
+        let scheduler: Box<dyn Scheduler<State = ReplicatedState>> = match fault_injection {
+            Some(fault_injection) => Box::new(FaultInjectingScheduler::new(
+                execution_services.scheduler,
+                fault_injection,
+                replica_logger.clone(),
+            )),
+            None => execution_services.scheduler,
+        };
         let message_routing = SyncMessageRouting::new(
             Arc::clone(&state_manager) as _,
             Arc::clone(&state_manager) as _,
             Arc::clone(&execution_services.ingress_history_writer) as _,
-            execution_services.scheduler,
+            scheduler,
             hypervisor_config,
             Arc::clone(&execution_services.cycles_account_manager),
             subnet_id,
@@ -1899,26 +1936,48 @@ impl StateMachine {
 
                     (public_key, private_key)
                 }
-                MasterPublicKeyId::Ecdsa(id) => {
-                    use ic_secp256k1::{DerivationIndex, DerivationPath, PrivateKey};
+                MasterPublicKeyId::Ecdsa(id) => match id.curve {
+                    EcdsaCurve::Secp256k1 => {
+                        use ic_secp256k1::{DerivationIndex, DerivationPath, PrivateKey};
 
-                    let path =
-                        DerivationPath::new(vec![DerivationIndex(id.name.as_bytes().to_vec())]);
+                        let path =
+                            DerivationPath::new(vec![DerivationIndex(id.name.as_bytes().to_vec())]);
 
-                    // We use a fixed seed here so that all subnets in PocketIC share the same keys.
-                    let private_key = PrivateKey::generate_from_seed(&[42; 32])
-                        .derive_subkey(&path)
-                        .0;
+                        // We use a fixed seed here so that all subnets in PocketIC share the same keys.
+                        let private_key = PrivateKey::generate_from_seed(&[42; 32])
+                            .derive_subkey(&path)
+                            .0;
 
-                    let public_key = MasterPublicKey {
-                        algorithm_id: AlgorithmId::ThresholdEcdsaSecp256k1,
-                        public_key: private_key.public_key().serialize_sec1(true),
-                    };
+                        let public_key = MasterPublicKey {
+                            algorithm_id: AlgorithmId::ThresholdEcdsaSecp256k1,
+                            public_key: private_key.public_key().serialize_sec1(true),
+                        };
 
-                    let private_key = SignatureSecretKey::EcdsaSecp256k1(private_key);
+                        let private_key = SignatureSecretKey::EcdsaSecp256k1(private_key);
 
-                    (public_key, private_key)
-                }
+                        (public_key, private_key)
+                    }
+                    EcdsaCurve::Secp256r1 => {
+                        use ic_secp256r1::{DerivationIndex, DerivationPath, PrivateKey};
+
+                        let path =
+                            DerivationPath::new(vec![DerivationIndex(id.name.as_bytes().to_vec())]);
+
+                        // We use a fixed seed here so that all subnets in PocketIC share the same keys.
+                        let private_key = PrivateKey::generate_insecure_key_for_testing(42)
+                            .derive_subkey(&path)
+                            .0;
+
+                        let public_key = MasterPublicKey {
+                            algorithm_id: AlgorithmId::ThresholdEcdsaSecp256r1,
+                            public_key: private_key.public_key().serialize_sec1(true),
+                        };
+
+                        let private_key = SignatureSecretKey::EcdsaSecp256r1(private_key);
+
+                        (public_key, private_key)
+                    }
+                },
                 MasterPublicKeyId::Schnorr(id) => match id.algorithm {
                     SchnorrAlgorithm::Bip340Secp256k1 => {
                         use ic_secp256k1::{DerivationIndex, DerivationPath, PrivateKey};
@@ -2076,6 +2135,7 @@ impl StateMachine {
             vetkd_payload_builder,
             remove_old_states,
             cycles_account_manager: execution_services.cycles_account_manager,
+            execution_profiler: execution_services.execution_profiler,
             cost_schedule,
         }
     }
@@ -2421,28 +2481,39 @@ impl StateMachine {
     ) -> Result<SignWithECDSAReply, UserError> {
         assert!(context.is_ecdsa());
 
-        if let Some(SignatureSecretKey::EcdsaSecp256k1(k)) =
-            self.chain_key_subnet_secret_keys.get(&context.key_id())
-        {
-            let path = ic_secp256k1::DerivationPath::from_canister_id_and_path(
-                context.request.sender.get().as_slice(),
-                &context.derivation_path,
-            );
-            let dk = k.derive_subkey(&path).0;
-            let signature = dk
-                .sign_digest_with_ecdsa(&context.ecdsa_args().message_hash)
-                .to_vec();
-            Ok(SignWithECDSAReply { signature })
-        } else {
-            Err(UserError::new(
-                ErrorCode::CanisterRejectedMessage,
-                format!(
-                    "Subnet {} does not hold threshold key {}.",
-                    self.subnet_id,
-                    context.key_id()
-                ),
-            ))
-        }
+        let signature = match self.chain_key_subnet_secret_keys.get(&context.key_id()) {
+            Some(SignatureSecretKey::EcdsaSecp256k1(k)) => {
+                let path = ic_secp256k1::DerivationPath::from_canister_id_and_path(
+                    context.request.sender.get().as_slice(),
+                    &context.derivation_path,
+                );
+                let dk = k.derive_subkey(&path).0;
+                dk.sign_digest_with_ecdsa(&context.ecdsa_args().message_hash)
+                    .to_vec()
+            }
+            Some(SignatureSecretKey::EcdsaSecp256r1(k)) => {
+                let path = ic_secp256r1::DerivationPath::from_canister_id_and_path(
+                    context.request.sender.get().as_slice(),
+                    &context.derivation_path,
+                );
+                let dk = k.derive_subkey(&path).0;
+                dk.sign_digest(&context.ecdsa_args().message_hash)
+                    .expect("message hash is 32 bytes long")
+                    .to_vec()
+            }
+            _ => {
+                return Err(UserError::new(
+                    ErrorCode::CanisterRejectedMessage,
+                    format!(
+                        "Subnet {} does not hold threshold key {}.",
+                        self.subnet_id,
+                        context.key_id()
+                    ),
+                ));
+            }
+        };
+
+        Ok(SignWithECDSAReply { signature })
     }
 
     fn build_sign_with_schnorr_reply(
@@ -2769,6 +2840,12 @@ impl StateMachine {
         &self.metrics_registry
     }
 
+    /// Returns the execution profiler if execution profiling is enabled
+    /// in the hypervisor config of this state machine.
+    pub fn execution_profiler(&self) -> Option<Arc<ExecutionProfiler>> {
+        self.execution_profiler.clone()
+    }
+
     /// Returns the total number of Wasm instructions this state machine consumed in replicated
     /// message execution (ingress messages, inter-canister messages, and heartbeats).
     pub fn instructions_consumed(&self) -> f64 {
@@ -845,6 +845,7 @@ pub fn load_canister_state(
                 wasm_execution_mode: WasmExecutionMode::from_is_wasm64(
                     execution_state_bits.is_wasm64,
                 ),
+                executed_instructions: execution_state_bits.executed_instructions,
             })
         }
         None => None,
@@ -908,6 +909,8 @@ pub fn load_canister_state(
         canister_state_bits.next_snapshot_id,
         canister_state_bits.snapshots_memory_usage,
         canister_state_bits.environment_variables,
+        canister_state_bits.query_cache_tags,
+        canister_state_bits.controller_roles,
         metrics,
     );
 
@@ -658,6 +658,8 @@ fn switch_to_checkpoint(
             // We can reuse the cache because the Wasm binary has the same
             // contents, only the storage of that binary changed.
             let embedder_cache = Arc::clone(&tip_state.wasm_binary.embedder_cache);
+            let optimized_embedder_cache =
+                Arc::clone(&tip_state.wasm_binary.optimized_embedder_cache);
             let tip_state_wasm_binary = &tip_state.wasm_binary.binary;
             let wasm_binary = canister_layout
                 .wasm()
@@ -686,6 +688,7 @@ fn switch_to_checkpoint(
                 ic_replicated_state::canister_state::execution_state::WasmBinary {
                     binary: wasm_binary,
                     embedder_cache,
+                    optimized_embedder_cache,
                 },
             );
 
@@ -1270,6 +1273,7 @@ fn serialize_canister_protos_to_checkpoint_readwrite(
             binary_hash: execution_state.wasm_binary.binary.module_hash().into(),
             next_scheduled_method: execution_state.next_scheduled_method,
             is_wasm64: execution_state.wasm_execution_mode.is_wasm64(),
+            executed_instructions: execution_state.executed_instructions,
         });
 
     canister_layout.canister().serialize(
@@ -1344,6 +1348,8 @@ fn serialize_canister_protos_to_checkpoint_readwrite(
                 .environment_variables
                 .clone()
                 .into(),
+            query_cache_tags: canister_state.system_state.query_cache_tags.clone(),
+            controller_roles: canister_state.system_state.controller_roles.clone(),
         }
         .into(),
     )?;
@@ -1,7 +1,11 @@
-//! Computes diff of canonical trees between checkpoints.
+//! Computes diff of canonical trees between checkpoints, or a semantic diff of
+//! the decoded replicated states.
 
 use ic_registry_subnet_type::SubnetType;
-use ic_replicated_state::page_map::TestPageAllocatorFileDescriptorImpl;
+use ic_replicated_state::{
+    CanisterState, Memory, PageIndex, ReplicatedState,
+    page_map::TestPageAllocatorFileDescriptorImpl,
+};
 use ic_state_layout::CompleteCheckpointLayout;
 use ic_state_manager::{
     CheckpointError, CheckpointMetrics,
@@ -9,12 +13,19 @@ use ic_state_manager::{
     tree_diff::{Changes, PrettyPrintedChanges, diff},
     tree_hash::hash_state,
 };
+use ic_sys::{PAGE_SIZE, PageBytes};
 use ic_types::Height;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fmt;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-/// Loads the checkpoints at `path_a` and `path_b` and diffs them.
-fn diff_checkpoints(path_a: PathBuf, path_b: PathBuf) -> Result<Changes, CheckpointError> {
+/// Loads the checkpoints at `path_a` and `path_b`.
+fn load_checkpoints(
+    path_a: PathBuf,
+    path_b: PathBuf,
+) -> Result<(ReplicatedState, ReplicatedState), CheckpointError> {
     let unused_height = Height::from(0);
     let own_subnet_type = SubnetType::Application;
     let dummy_metrics_registry = ic_metrics::MetricsRegistry::new();
@@ -33,12 +44,495 @@ fn diff_checkpoints(path_a: PathBuf, path_b: PathBuf) -> Result<Changes, Checkpo
         None,
         Arc::new(TestPageAllocatorFileDescriptorImpl::new()),
     )?;
+    Ok((state_a, state_b))
+}
 
+/// Loads the checkpoints at `path_a` and `path_b` and diffs them.
+fn diff_checkpoints(path_a: PathBuf, path_b: PathBuf) -> Result<Changes, CheckpointError> {
+    let (state_a, state_b) = load_checkpoints(path_a, path_b)?;
     let tree_a = hash_state(&state_a);
     let tree_b = hash_state(&state_b);
     Ok(diff(&tree_a, &tree_b))
 }
 
+/// A field whose value differs between the two states.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+impl FieldChange {
+    fn new(field: &str, before: impl fmt::Debug, after: impl fmt::Debug) -> Self {
+        Self {
+            field: field.to_string(),
+            before: format!("{before:?}"),
+            after: format!("{after:?}"),
+        }
+    }
+}
+
+/// Pushes a `FieldChange` onto `changes` if `before` and `after` differ.
+fn compare<T: PartialEq + fmt::Debug>(
+    changes: &mut Vec<FieldChange>,
+    field: &str,
+    before: T,
+    after: T,
+) {
+    if before != after {
+        changes.push(FieldChange::new(field, before, after));
+    }
+}
+
+/// Differences between the two versions of a Wasm or stable memory.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MemoryDiff {
+    /// Memory size in Wasm pages before and after.
+    pub size_before: u64,
+    pub size_after: u64,
+    /// Inclusive ranges of (OS) pages whose contents differ, in ascending order.
+    pub changed_page_ranges: Vec<(u64, u64)>,
+}
+
+impl fmt::Display for MemoryDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranges: Vec<_> = self
+            .changed_page_ranges
+            .iter()
+            .map(|(start, end)| {
+                if start == end {
+                    start.to_string()
+                } else {
+                    format!("{start}-{end}")
+                }
+            })
+            .collect();
+        write!(
+            f,
+            "size {} -> {} Wasm pages, changed pages [{}]",
+            self.size_before,
+            self.size_after,
+            ranges.join(", ")
+        )
+    }
+}
+
+/// Differences between the two versions of a canister present in both states.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CanisterDiff {
+    pub canister_id: String,
+    /// Changed settings and status.
+    pub settings: Vec<FieldChange>,
+    /// Difference between the new and the old cycles balance.
+    pub cycles_balance_delta: i128,
+    /// Change of the installed module hash, if any.
+    pub module_hash: Option<FieldChange>,
+    pub wasm_memory: Option<MemoryDiff>,
+    pub stable_memory: Option<MemoryDiff>,
+    /// Changed ingress, input and output queue sizes.
+    pub queues: Vec<FieldChange>,
+    pub added_snapshots: Vec<String>,
+    pub removed_snapshots: Vec<String>,
+}
+
+impl CanisterDiff {
+    fn is_empty(&self) -> bool {
+        self.settings.is_empty()
+            && self.cycles_balance_delta == 0
+            && self.module_hash.is_none()
+            && self.wasm_memory.is_none()
+            && self.stable_memory.is_none()
+            && self.queues.is_empty()
+            && self.added_snapshots.is_empty()
+            && self.removed_snapshots.is_empty()
+    }
+}
+
+/// Semantic differences between two replicated states.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct StateDiff {
+    /// Changed subnet-level `SystemMetadata` fields.
+    pub metadata: Vec<FieldChange>,
+    pub added_canisters: Vec<String>,
+    pub removed_canisters: Vec<String>,
+    /// Canisters present in both states that differ, in canister ID order.
+    pub canisters: Vec<CanisterDiff>,
+}
+
+impl StateDiff {
+    pub fn is_empty(&self) -> bool {
+        self.metadata.is_empty()
+            && self.added_canisters.is_empty()
+            && self.removed_canisters.is_empty()
+            && self.canisters.is_empty()
+    }
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn fmt_changes(f: &mut fmt::Formatter<'_>, changes: &[FieldChange]) -> fmt::Result {
+            for change in changes {
+                writeln!(
+                    f,
+                    "  {}: {} -> {}",
+                    change.field, change.before, change.after
+                )?;
+            }
+            Ok(())
+        }
+        if !self.metadata.is_empty() {
+            writeln!(f, "SystemMetadata:")?;
+            fmt_changes(f, &self.metadata)?;
+        }
+        for canister_id in &self.added_canisters {
+            writeln!(f, "+ canister {canister_id}")?;
+        }
+        for canister_id in &self.removed_canisters {
+            writeln!(f, "- canister {canister_id}")?;
+        }
+        for canister in &self.canisters {
+            writeln!(f, "~ canister {}", canister.canister_id)?;
+            fmt_changes(f, &canister.settings)?;
+            if canister.cycles_balance_delta != 0 {
+                writeln!(f, "  cycles balance: {:+}", canister.cycles_balance_delta)?;
+            }
+            if let Some(change) = &canister.module_hash {
+                fmt_changes(f, std::slice::from_ref(change))?;
+            }
+            if let Some(diff) = &canister.wasm_memory {
+                writeln!(f, "  wasm memory: {diff}")?;
+            }
+            if let Some(diff) = &canister.stable_memory {
+                writeln!(f, "  stable memory: {diff}")?;
+            }
+            fmt_changes(f, &canister.queues)?;
+            for snapshot_id in &canister.added_snapshots {
+                writeln!(f, "  + snapshot {snapshot_id}")?;
+            }
+            for snapshot_id in &canister.removed_snapshots {
+                writeln!(f, "  - snapshot {snapshot_id}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Computes the semantic differences between `state_a` and `state_b`.
+pub fn diff_states(state_a: &ReplicatedState, state_b: &ReplicatedState) -> StateDiff {
+    let meta_a = &state_a.metadata;
+    let meta_b = &state_b.metadata;
+    let mut metadata = vec![];
+    compare(
+        &mut metadata,
+        "own_subnet_id",
+        meta_a.own_subnet_id,
+        meta_b.own_subnet_id,
+    );
+    compare(
+        &mut metadata,
+        "own_subnet_type",
+        meta_a.own_subnet_type,
+        meta_b.own_subnet_type,
+    );
+    compare(
+        &mut metadata,
+        "own_subnet_features",
+        meta_a.own_subnet_features,
+        meta_b.own_subnet_features,
+    );
+    compare(
+        &mut metadata,
+        "cost_schedule",
+        meta_a.cost_schedule,
+        meta_b.cost_schedule,
+    );
+    compare(
+        &mut metadata,
+        "batch_time",
+        meta_a.batch_time,
+        meta_b.batch_time,
+    );
+    compare(
+        &mut metadata,
+        "prev_state_hash",
+        &meta_a.prev_state_hash,
+        &meta_b.prev_state_hash,
+    );
+    compare(
+        &mut metadata,
+        "split_from",
+        meta_a.split_from,
+        meta_b.split_from,
+    );
+    compare(
+        &mut metadata,
+        "state_sync_version",
+        meta_a.state_sync_version,
+        meta_b.state_sync_version,
+    );
+    compare(
+        &mut metadata,
+        "certification_version",
+        meta_a.certification_version,
+        meta_b.certification_version,
+    );
+    compare(
+        &mut metadata,
+        "heap_delta_estimate",
+        meta_a.heap_delta_estimate,
+        meta_b.heap_delta_estimate,
+    );
+    compare(
+        &mut metadata,
+        "ingress_history_size",
+        meta_a.ingress_history.len(),
+        meta_b.ingress_history.len(),
+    );
+    compare(
+        &mut metadata,
+        "expected_compiled_wasms",
+        meta_a.expected_compiled_wasms.len(),
+        meta_b.expected_compiled_wasms.len(),
+    );
+    if meta_a.network_topology != meta_b.network_topology {
+        metadata.push(FieldChange {
+            field: "network_topology".to_string(),
+            before: "<changed>".to_string(),
+            after: "<changed>".to_string(),
+        });
+    }
+
+    let mut diff = StateDiff {
+        metadata,
+        ..Default::default()
+    };
+    for (canister_id, canister_a) in &state_a.canister_states {
+        match state_b.canister_states.get(canister_id) {
+            Some(canister_b) => {
+                let canister_diff = diff_canisters(state_a, canister_a, state_b, canister_b);
+                if !canister_diff.is_empty() {
+                    diff.canisters.push(canister_diff);
+                }
+            }
+            None => diff.removed_canisters.push(canister_id.to_string()),
+        }
+    }
+    diff.added_canisters = state_b
+        .canister_states
+        .keys()
+        .filter(|canister_id| !state_a.canister_states.contains_key(canister_id))
+        .map(|canister_id| canister_id.to_string())
+        .collect();
+    diff
+}
+
+fn diff_canisters(
+    state_a: &ReplicatedState,
+    canister_a: &CanisterState,
+    state_b: &ReplicatedState,
+    canister_b: &CanisterState,
+) -> CanisterDiff {
+    let canister_id = canister_a.canister_id();
+    let (sys_a, sys_b) = (&canister_a.system_state, &canister_b.system_state);
+
+    let mut settings = vec![];
+    compare(
+        &mut settings,
+        "status",
+        sys_a.status_string(),
+        sys_b.status_string(),
+    );
+    compare(
+        &mut settings,
+        "controllers",
+        &sys_a.controllers,
+        &sys_b.controllers,
+    );
+    compare(
+        &mut settings,
+        "compute_allocation",
+        canister_a.compute_allocation(),
+        canister_b.compute_allocation(),
+    );
+    compare(
+        &mut settings,
+        "memory_allocation",
+        sys_a.memory_allocation,
+        sys_b.memory_allocation,
+    );
+    compare(
+        &mut settings,
+        "freeze_threshold",
+        sys_a.freeze_threshold,
+        sys_b.freeze_threshold,
+    );
+    compare(
+        &mut settings,
+        "reserved_cycles_limit",
+        sys_a.reserved_balance_limit(),
+        sys_b.reserved_balance_limit(),
+    );
+    compare(
+        &mut settings,
+        "wasm_memory_limit",
+        sys_a.wasm_memory_limit,
+        sys_b.wasm_memory_limit,
+    );
+    compare(
+        &mut settings,
+        "wasm_memory_threshold",
+        sys_a.wasm_memory_threshold,
+        sys_b.wasm_memory_threshold,
+    );
+    compare(
+        &mut settings,
+        "log_visibility",
+        &sys_a.log_visibility,
+        &sys_b.log_visibility,
+    );
+    compare(
+        &mut settings,
+        "log_memory_limit",
+        sys_a.log_memory_limit,
+        sys_b.log_memory_limit,
+    );
+    compare(
+        &mut settings,
+        "environment_variables",
+        &sys_a.environment_variables,
+        &sys_b.environment_variables,
+    );
+    compare(
+        &mut settings,
+        "controller_roles",
+        &sys_a.controller_roles,
+        &sys_b.controller_roles,
+    );
+    compare(
+        &mut settings,
+        "canister_version",
+        sys_a.canister_version,
+        sys_b.canister_version,
+    );
+    compare(
+        &mut settings,
+        "reserved_balance",
+        sys_a.reserved_balance(),
+        sys_b.reserved_balance(),
+    );
+    compare(
+        &mut settings,
+        "certified_data",
+        hex::encode(&sys_a.certified_data),
+        hex::encode(&sys_b.certified_data),
+    );
+
+    let cycles_balance_delta =
+        (sys_b.balance().get() as i128).saturating_sub(sys_a.balance().get() as i128);
+
+    let exec_a = canister_a.execution_state.as_ref();
+    let exec_b = canister_b.execution_state.as_ref();
+    let module_hash_a = exec_a.map(|e| hex::encode(e.wasm_binary.binary.module_hash()));
+    let module_hash_b = exec_b.map(|e| hex::encode(e.wasm_binary.binary.module_hash()));
+    let module_hash = (module_hash_a != module_hash_b)
+        .then(|| FieldChange::new("module_hash", module_hash_a, module_hash_b));
+    let wasm_memory = diff_memories(
+        exec_a.map(|e| &e.wasm_memory),
+        exec_b.map(|e| &e.wasm_memory),
+    );
+    let stable_memory = diff_memories(
+        exec_a.map(|e| &e.stable_memory),
+        exec_b.map(|e| &e.stable_memory),
+    );
+
+    let (queues_a, queues_b) = (sys_a.queues(), sys_b.queues());
+    let mut queues = vec![];
+    compare(
+        &mut queues,
+        "ingress_queue_size",
+        queues_a.ingress_queue_message_count(),
+        queues_b.ingress_queue_message_count(),
+    );
+    compare(
+        &mut queues,
+        "input_queues_size",
+        queues_a.input_queues_message_count(),
+        queues_b.input_queues_message_count(),
+    );
+    compare(
+        &mut queues,
+        "output_queues_size",
+        queues_a.output_queues_message_count(),
+        queues_b.output_queues_message_count(),
+    );
+
+    let snapshot_ids = |state: &ReplicatedState| -> BTreeSet<String> {
+        state
+            .canister_snapshots
+            .list_snapshots(canister_id)
+            .into_iter()
+            .map(|(snapshot_id, _)| snapshot_id.to_string())
+            .collect()
+    };
+    let (snapshots_a, snapshots_b) = (snapshot_ids(state_a), snapshot_ids(state_b));
+
+    CanisterDiff {
+        canister_id: canister_id.to_string(),
+        settings,
+        cycles_balance_delta,
+        module_hash,
+        wasm_memory,
+        stable_memory,
+        queues,
+        added_snapshots: snapshots_b.difference(&snapshots_a).cloned().collect(),
+        removed_snapshots: snapshots_a.difference(&snapshots_b).cloned().collect(),
+    }
+}
+
+/// Compares two versions of a memory page by page. A missing memory is
+/// treated as an empty one.
+fn diff_memories(memory_a: Option<&Memory>, memory_b: Option<&Memory>) -> Option<MemoryDiff> {
+    let size = |memory: Option<&Memory>| memory.map_or(0, |m| m.size.get() as u64);
+    let (size_before, size_after) = (size(memory_a), size(memory_b));
+
+    const ZERO_PAGE: PageBytes = [0; PAGE_SIZE];
+    let num_host_pages =
+        |memory: Option<&Memory>| memory.map_or(0, |m| m.page_map.num_host_pages());
+    let get_page = |memory: Option<&Memory>, page: PageIndex| {
+        memory.map_or(&ZERO_PAGE, |m| m.page_map.get_page(page))
+    };
+    let num_pages = num_host_pages(memory_a).max(num_host_pages(memory_b)) as u64;
+
+    let mut changed_page_ranges: Vec<(u64, u64)> = vec![];
+    for index in 0..num_pages {
+        let page = PageIndex::new(index);
+        if get_page(memory_a, page) == get_page(memory_b, page) {
+            continue;
+        }
+        match changed_page_ranges.last_mut() {
+            Some((_, end)) if *end + 1 == index => *end = index,
+            _ => changed_page_ranges.push((index, index)),
+        }
+    }
+
+    if size_before == size_after && changed_page_ranges.is_empty() {
+        return None;
+    }
+    Some(MemoryDiff {
+        size_before,
+        size_after,
+        changed_page_ranges,
+    })
+}
+
+/// Loads the checkpoints at `path_a` and `path_b` and computes their semantic
+/// differences.
+pub fn semantic_diff_checkpoints(path_a: PathBuf, path_b: PathBuf) -> Result<StateDiff, String> {
+    let (state_a, state_b) = load_checkpoints(path_a, path_b).map_err(|err| err.to_string())?;
+    Ok(diff_states(&state_a, &state_b))
+}
+
 /// `cdiff` command entry point.
 pub fn do_diff(path_a: PathBuf, path_b: PathBuf) -> Result<(), String> {
     let d = diff_checkpoints(path_a, path_b).map_err(|err| format!("✗ Diff FAILED:\n\t{err}"))?;
@@ -50,3 +544,91 @@ pub fn do_diff(path_a: PathBuf, path_b: PathBuf) -> Result<(), String> {
 
     Ok(())
 }
+
+/// `cdiff --semantic` command entry point. Prints the semantic differences as
+/// human-readable text, or as JSON if `json` is set.
+pub fn do_semantic_diff(path_a: PathBuf, path_b: PathBuf, json: bool) -> Result<(), String> {
+    let d = semantic_diff_checkpoints(path_a, path_b)
+        .map_err(|err| format!("✗ Diff FAILED:\n\t{err}"))?;
+    if json {
+        let json = serde_json::to_string_pretty(&d)
+            .map_err(|err| format!("✗ Failed to serialize diff:\n\t{err}"))?;
+        println!("{json}");
+    } else if d.is_empty() {
+        println!("✓ States are identical");
+    } else {
+        print!("{d}");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_state_machine_tests::StateMachineBuilder;
+    use ic_types::PrincipalId;
+
+    const TEST_CANISTER: &str = r#"
+        (module
+            (import "ic0" "msg_reply" (func $msg_reply))
+            (func $write
+                (i32.store (i32.const 70000) (i32.const 42))
+                (call $msg_reply))
+            (memory 2)
+            (export "canister_update write" (func $write)))"#;
+
+    #[test]
+    fn semantic_diff_test() {
+        let env = StateMachineBuilder::new().build();
+        let canister_id = env.install_canister_wat(TEST_CANISTER, vec![], None);
+        env.checkpointed_tick();
+        env.state_manager.flush_tip_channel();
+        let state_layout = env.state_manager.state_layout();
+        let last_checkpoint = || {
+            let height = *state_layout.checkpoint_heights().unwrap().last().unwrap();
+            state_layout
+                .checkpoint_verified(height)
+                .unwrap()
+                .raw_path()
+                .to_path_buf()
+        };
+        let path_a = last_checkpoint();
+
+        let diff = semantic_diff_checkpoints(path_a.clone(), path_a.clone()).unwrap();
+        assert!(diff.is_empty(), "{diff}");
+
+        env.execute_ingress_as(PrincipalId::new_anonymous(), canister_id, "write", vec![])
+            .unwrap();
+        let new_canister_id = env.create_canister(None);
+        env.checkpointed_tick();
+        env.state_manager.flush_tip_channel();
+        let path_b = last_checkpoint();
+
+        let diff = semantic_diff_checkpoints(path_a, path_b).unwrap();
+        assert!(
+            diff.metadata
+                .iter()
+                .any(|change| change.field == "batch_time"),
+            "{diff}"
+        );
+        assert_eq!(diff.added_canisters, vec![new_canister_id.to_string()]);
+        assert!(diff.removed_canisters.is_empty());
+        assert_eq!(diff.canisters.len(), 1, "{diff}");
+        let canister = &diff.canisters[0];
+        assert_eq!(canister.canister_id, canister_id.to_string());
+        let page = (70000 / PAGE_SIZE) as u64;
+        assert_eq!(
+            canister.wasm_memory.as_ref().unwrap().changed_page_ranges,
+            vec![(page, page)]
+        );
+        assert_eq!(canister.stable_memory, None);
+        assert_eq!(canister.module_hash, None);
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(
+            json["canisters"][0]["wasm_memory"]["changed_page_ranges"],
+            serde_json::json!([[page, page]])
+        );
+    }
+}
@@ -0,0 +1,478 @@
+//! Re-executes a single message against a canister of a checkpoint, without
+//! persisting any of the resulting changes.
+
+use ic_config::{
+    execution_environment::Config as ExecutionConfig, flag_status::FlagStatus,
+    subnet_config::SubnetConfig,
+};
+use ic_embedders::wasmtime_embedder::system_api::InstructionLimits;
+use ic_execution_environment::{
+    ExecutionServicesForTesting, RoundInstructions, RoundLimits, execute_canister,
+};
+use ic_interfaces_state_manager::{Labeled, StateReader};
+use ic_limits::{MAX_INGRESS_TTL, SMALL_APP_SUBNET_MAX_SIZE};
+use ic_management_canister_types_private::CanisterLogRecord;
+use ic_registry_subnet_type::SubnetType;
+use ic_replicated_state::{
+    PageIndex, ReplicatedState, canister_state::NextExecution,
+    page_map::TestPageAllocatorFileDescriptorImpl,
+};
+use ic_state_layout::CompleteCheckpointLayout;
+use ic_state_manager::{CheckpointMetrics, checkpoint::load_checkpoint};
+use ic_types::{
+    CanisterId, Height, NumInstructions, PrincipalId, Time, UserId,
+    ingress::{IngressState, IngressStatus, WasmResult},
+    messages::{Ingress, MessageId},
+    state_manager::{StateManagerError, StateManagerResult},
+};
+use std::{path::PathBuf, sync::Arc};
+
+/// The message to execute.
+pub struct Message {
+    pub canister_id: CanisterId,
+    pub method_name: String,
+    pub method_payload: Vec<u8>,
+    pub sender: PrincipalId,
+}
+
+/// Serves the loaded checkpoint as the only (and latest) state.
+struct CheckpointStateReader(Labeled<Arc<ReplicatedState>>);
+
+impl StateReader for CheckpointStateReader {
+    type State = ReplicatedState;
+
+    fn get_state_at(&self, height: Height) -> StateManagerResult<Labeled<Arc<ReplicatedState>>> {
+        if height == self.0.height() {
+            Ok(self.0.clone())
+        } else {
+            Err(StateManagerError::StateRemoved(height))
+        }
+    }
+
+    fn get_latest_state(&self) -> Labeled<Arc<ReplicatedState>> {
+        self.0.clone()
+    }
+
+    fn get_latest_certified_state(&self) -> Option<Labeled<Arc<ReplicatedState>>> {
+        Some(self.0.clone())
+    }
+
+    fn latest_state_height(&self) -> Height {
+        self.0.height()
+    }
+
+    fn latest_certified_height(&self) -> Height {
+        self.0.height()
+    }
+}
+
+/// Outcome of executing a message against a checkpoint.
+pub struct ExecutionOutcome {
+    /// The state of the message after execution, if it completed.
+    pub ingress_state: Option<IngressState>,
+    /// Number of instructions executed by the message.
+    pub instructions_used: NumInstructions,
+    /// Wasm memory pages modified by the message, in ascending order.
+    pub wasm_dirty_pages: Vec<PageIndex>,
+    /// Stable memory pages modified by the message, in ascending order.
+    pub stable_memory_dirty_pages: Vec<PageIndex>,
+    /// Canister log records produced by the message.
+    pub logs: Vec<CanisterLogRecord>,
+    /// Number of messages that were queued in the checkpoint and were dropped
+    /// so that only the given message is executed.
+    pub discarded_inputs: usize,
+}
+
+/// Loads the checkpoint at `path` and executes `message` as an ingress message
+/// sent to the canister (query methods are executed in replicated mode).
+///
+/// The execution runs in-process without sandboxing and with deterministic
+/// time slicing disabled, so that the full message executes in a single slice.
+/// Messages already queued for the canister in the checkpoint are dropped, so
+/// that only the given message runs. Canisters with an aborted long execution
+/// are rejected, as that execution would have to complete first. The
+/// checkpoint itself is never modified.
+pub fn execute_message(
+    path: PathBuf,
+    message: Message,
+    subnet_type: SubnetType,
+    time: Option<Time>,
+) -> Result<ExecutionOutcome, String> {
+    let height = Height::new(0);
+    let cp_layout = CompleteCheckpointLayout::new_untracked(path.clone(), height)
+        .map_err(|e| format!("failed to create checkpoint layout: {e}"))?;
+
+    let metrics_registry = ic_metrics::MetricsRegistry::new();
+    let checkpoint_metrics = CheckpointMetrics::new(&metrics_registry, crate::commands::logger());
+    let fd_factory = Arc::new(TestPageAllocatorFileDescriptorImpl::new());
+
+    let state = load_checkpoint(
+        &cp_layout,
+        subnet_type,
+        &checkpoint_metrics,
+        None,
+        Arc::clone(&fd_factory) as Arc<_>,
+    )
+    .map_err(|e| format!("failed to load checkpoint at {}: {}", path.display(), e))?;
+
+    let own_subnet_id = state.metadata.own_subnet_id;
+    let time = time.unwrap_or_else(|| state.time());
+    let mut canister = state
+        .canister_state(&message.canister_id)
+        .cloned()
+        .ok_or_else(|| format!("canister {} not found in checkpoint", message.canister_id))?;
+
+    let subnet_config = SubnetConfig::new(subnet_type);
+    let scheduler_config = subnet_config.scheduler_config.clone();
+    let config = ExecutionConfig {
+        canister_sandboxing_flag: FlagStatus::Disabled,
+        ..ExecutionConfig::default()
+    };
+    let state_reader = Arc::new(CheckpointStateReader(Labeled::new(
+        height,
+        Arc::new(state.clone()),
+    )));
+    let (completed_execution_messages_tx, _completed_execution_messages_rx) =
+        tokio::sync::mpsc::channel(1);
+    let execution_services = ExecutionServicesForTesting::setup_execution(
+        crate::commands::logger(),
+        &metrics_registry,
+        own_subnet_id,
+        subnet_type,
+        config,
+        subnet_config,
+        state_reader,
+        fd_factory,
+        completed_execution_messages_tx,
+        &std::env::temp_dir(),
+        None,
+    );
+    let exec_env = execution_services.execution_environment;
+
+    match canister.next_execution() {
+        NextExecution::None | NextExecution::StartNew => {}
+        NextExecution::ContinueLong | NextExecution::ContinueInstallCode => {
+            return Err(format!(
+                "canister {} has an aborted execution that must complete before any other \
+                message can run",
+                message.canister_id
+            ));
+        }
+    }
+    // Only heartbeat, global timer and low Wasm memory tasks can be left after
+    // the check above. Drop them together with the queued messages.
+    while canister.system_state.task_queue.pop_front().is_some() {}
+    let mut discarded_inputs = 0;
+    while canister.pop_input().is_some() {
+        discarded_inputs += 1;
+    }
+
+    let message_id = MessageId::from([0; 32]);
+    let log_start_idx = canister.system_state.canister_log.next_idx();
+    canister.push_ingress(Ingress {
+        source: UserId::from(message.sender),
+        receiver: message.canister_id,
+        effective_canister_id: None,
+        method_name: message.method_name,
+        method_payload: message.method_payload,
+        message_id: message_id.clone(),
+        expiry_time: time + MAX_INGRESS_TTL,
+    });
+
+    let network_topology = Arc::new(state.metadata.network_topology.clone());
+    let subnet_size = network_topology
+        .get_subnet_size(&own_subnet_id)
+        .unwrap_or(SMALL_APP_SUBNET_MAX_SIZE);
+    let mut round_limits = RoundLimits {
+        instructions: RoundInstructions::from(i64::MAX),
+        subnet_available_memory: exec_env.scaled_subnet_available_memory(&state),
+        subnet_available_callbacks: exec_env.subnet_available_callbacks(&state),
+        compute_allocation_used: state.total_compute_allocation(),
+        subnet_memory_reservation: exec_env.scaled_subnet_memory_reservation(),
+    };
+    let max_instructions_per_message = scheduler_config.max_instructions_per_message;
+    let result = execute_canister(
+        &exec_env,
+        canister,
+        InstructionLimits::new(max_instructions_per_message, max_instructions_per_message),
+        scheduler_config.max_instructions_per_message_without_dts,
+        network_topology,
+        time,
+        &mut round_limits,
+        subnet_size,
+        state.get_own_cost_schedule(),
+    );
+
+    let ingress_state = match result.ingress_status {
+        Some((id, IngressStatus::Known { state, .. })) if id == message_id => Some(state),
+        _ => None,
+    };
+    let canister = result.canister;
+    let (mut wasm_dirty_pages, mut stable_memory_dirty_pages) = match &canister.execution_state {
+        Some(execution_state) => (
+            execution_state
+                .wasm_memory
+                .page_map
+                .get_page_delta_indices(),
+            execution_state
+                .stable_memory
+                .page_map
+                .get_page_delta_indices(),
+        ),
+        None => (vec![], vec![]),
+    };
+    wasm_dirty_pages.sort();
+    stable_memory_dirty_pages.sort();
+    let logs = canister
+        .system_state
+        .canister_log
+        .records()
+        .iter()
+        .filter(|record| record.idx >= log_start_idx)
+        .cloned()
+        .collect();
+
+    Ok(ExecutionOutcome {
+        ingress_state,
+        instructions_used: result
+            .instructions_used
+            .unwrap_or_else(|| NumInstructions::from(0)),
+        wasm_dirty_pages,
+        stable_memory_dirty_pages,
+        logs,
+        discarded_inputs,
+    })
+}
+
+/// `execute` command entry point.
+///
+/// Prints the reply, the number of executed instructions, the dirty Wasm and
+/// stable memory pages, and the canister log records produced by the message.
+pub fn do_execute(
+    path: PathBuf,
+    message: Message,
+    subnet_type: SubnetType,
+    time: Option<Time>,
+) -> Result<(), String> {
+    let outcome = execute_message(path, message, subnet_type, time)
+        .map_err(|err| format!("✗ Execution FAILED:\n\t{err}"))?;
+
+    if outcome.discarded_inputs > 0 {
+        println!(
+            "NOTE: dropped {} message(s) queued for the canister in the checkpoint",
+            outcome.discarded_inputs
+        );
+    }
+
+    match outcome.ingress_state {
+        Some(IngressState::Completed(WasmResult::Reply(reply))) => {
+            println!("REPLY: 0x{}", hex::encode(reply))
+        }
+        Some(IngressState::Completed(WasmResult::Reject(reject))) => {
+            println!("REJECT: {reject}")
+        }
+        Some(IngressState::Failed(err)) => println!("ERROR: {err}"),
+        Some(other) => println!("STATUS: {other:?}"),
+        None => println!("STATUS: the message did not complete"),
+    }
+    println!("INSTRUCTIONS: {}", outcome.instructions_used);
+    print_dirty_pages("WASM MEMORY", &outcome.wasm_dirty_pages);
+    print_dirty_pages("STABLE MEMORY", &outcome.stable_memory_dirty_pages);
+    println!("LOGS ({}):", outcome.logs.len());
+    for record in outcome.logs {
+        println!(
+            "  [{}] {}: {}",
+            record.idx,
+            Time::from_nanos_since_unix_epoch(record.timestamp_nanos),
+            String::from_utf8_lossy(&record.content)
+        );
+    }
+
+    Ok(())
+}
+
+fn print_dirty_pages(memory: &str, pages: &[PageIndex]) {
+    println!("DIRTY {memory} PAGES ({}):", pages.len());
+    if !pages.is_empty() {
+        let pages: Vec<_> = pages.iter().map(|page| page.get().to_string()).collect();
+        println!("  {}", pages.join(", "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_interfaces_state_manager::{CertificationScope, StateManager};
+    use ic_replicated_state::canister_state::system_state::ExecutionTask;
+    use ic_state_machine_tests::{StateMachine, StateMachineBuilder};
+    use ic_types::{
+        CanisterTimer, Cycles,
+        messages::{CanisterMessageOrTask, CanisterTask},
+    };
+
+    const TEST_CANISTER: &str = r#"
+        (module
+            (import "ic0" "debug_print" (func $debug_print (param i32 i32)))
+            (import "ic0" "msg_reply_data_append"
+                (func $msg_reply_data_append (param i32 i32)))
+            (import "ic0" "msg_reply" (func $msg_reply))
+            (func $write
+                (i32.store (i32.const 0) (i32.const 42))
+                (call $debug_print (i32.const 100) (i32.const 5))
+                (call $msg_reply_data_append (i32.const 100) (i32.const 5))
+                (call $msg_reply))
+            (func $other
+                (call $msg_reply_data_append (i32.const 200) (i32.const 5))
+                (call $msg_reply))
+            (func $timer
+                (call $debug_print (i32.const 200) (i32.const 5)))
+            (memory 1)
+            (data (i32.const 100) "hello")
+            (data (i32.const 200) "other")
+            (export "canister_update write" (func $write))
+            (export "canister_update other" (func $other))
+            (export "canister_global_timer" (func $timer)))"#;
+
+    fn write_message(canister_id: CanisterId) -> Message {
+        Message {
+            canister_id,
+            method_name: "write".to_string(),
+            method_payload: vec![],
+            sender: PrincipalId::new_anonymous(),
+        }
+    }
+
+    fn latest_checkpoint_path(env: &StateMachine) -> PathBuf {
+        env.state_manager.flush_tip_channel();
+        let state_layout = env.state_manager.state_layout();
+        let height = *state_layout.checkpoint_heights().unwrap().last().unwrap();
+        state_layout
+            .checkpoint_verified(height)
+            .unwrap()
+            .raw_path()
+            .to_path_buf()
+    }
+
+    /// Applies `f` to the canister and writes a checkpoint of the result.
+    fn checkpoint_with(
+        env: &StateMachine,
+        canister_id: CanisterId,
+        f: impl FnOnce(&mut ReplicatedState),
+    ) {
+        env.checkpointed_tick();
+        let (height, mut state) = env.state_manager.take_tip();
+        f(&mut state);
+        assert!(state.canister_state(&canister_id).is_some());
+        env.state_manager.commit_and_certify(
+            state,
+            height.increment(),
+            CertificationScope::Full,
+            None,
+        );
+    }
+
+    #[test]
+    fn execute_test() {
+        let env = StateMachineBuilder::new().build();
+        let canister_id = env.install_canister_wat(TEST_CANISTER, vec![], None);
+        env.checkpointed_tick();
+        let path = latest_checkpoint_path(&env);
+
+        let outcome = execute_message(
+            path.clone(),
+            write_message(canister_id),
+            SubnetType::Application,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            outcome.ingress_state,
+            Some(IngressState::Completed(WasmResult::Reply(
+                b"hello".to_vec()
+            )))
+        );
+        assert!(outcome.instructions_used > NumInstructions::from(0));
+        assert_eq!(outcome.wasm_dirty_pages, vec![PageIndex::new(0)]);
+        assert!(outcome.stable_memory_dirty_pages.is_empty());
+        assert_eq!(outcome.logs.len(), 1);
+        assert_eq!(outcome.logs[0].content, b"hello".to_vec());
+        assert_eq!(outcome.discarded_inputs, 0);
+
+        // The checkpoint is not modified, so executing again yields the same outcome.
+        let again = execute_message(
+            path,
+            write_message(canister_id),
+            SubnetType::Application,
+            None,
+        )
+        .unwrap();
+        assert_eq!(again.ingress_state, outcome.ingress_state);
+        assert_eq!(again.instructions_used, outcome.instructions_used);
+        assert_eq!(again.logs[0].idx, outcome.logs[0].idx);
+    }
+
+    #[test]
+    fn execute_ignores_queued_messages_and_timers() {
+        let env = StateMachineBuilder::new().build();
+        let canister_id = env.install_canister_wat(TEST_CANISTER, vec![], None);
+        checkpoint_with(&env, canister_id, |state| {
+            let expiry_time = state.time() + MAX_INGRESS_TTL;
+            let canister = state.canister_state_mut(&canister_id).unwrap();
+            canister.push_ingress(Ingress {
+                source: UserId::from(PrincipalId::new_anonymous()),
+                receiver: canister_id,
+                effective_canister_id: None,
+                method_name: "other".to_string(),
+                method_payload: vec![],
+                message_id: MessageId::from([1; 32]),
+                expiry_time,
+            });
+            canister.system_state.global_timer =
+                CanisterTimer::Active(Time::from_nanos_since_unix_epoch(0));
+        });
+
+        let outcome = execute_message(
+            latest_checkpoint_path(&env),
+            write_message(canister_id),
+            SubnetType::Application,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            outcome.ingress_state,
+            Some(IngressState::Completed(WasmResult::Reply(
+                b"hello".to_vec()
+            )))
+        );
+        assert_eq!(outcome.discarded_inputs, 1);
+        // Only the requested message ran, not the queued one or the timer.
+        assert_eq!(outcome.logs.len(), 1);
+        assert_eq!(outcome.logs[0].content, b"hello".to_vec());
+    }
+
+    #[test]
+    fn execute_rejects_canister_with_aborted_execution() {
+        let env = StateMachineBuilder::new().build();
+        let canister_id = env.install_canister_wat(TEST_CANISTER, vec![], None);
+        checkpoint_with(&env, canister_id, |state| {
+            let canister = state.canister_state_mut(&canister_id).unwrap();
+            canister
+                .system_state
+                .task_queue
+                .enqueue(ExecutionTask::AbortedExecution {
+                    input: CanisterMessageOrTask::Task(CanisterTask::GlobalTimer),
+                    prepaid_execution_cycles: Cycles::zero(),
+                });
+        });
+
+        let err = execute_message(
+            latest_checkpoint_path(&env),
+            write_message(canister_id),
+            SubnetType::Application,
+            None,
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("aborted execution"), "{err}");
+    }
+}
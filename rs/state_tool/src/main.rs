@@ -2,13 +2,13 @@
 //!
 //! A command-line tool to manage Internet Computer replicated states (decode
 //! persisted state files, diff checkpoints, compute partial state hashes and
-//! checkpoint manifests, import state trees).
+//! checkpoint manifests, import state trees, re-execute messages offline).
 
 use clap::Parser;
 use ic_registry_routing_table::CanisterIdRange;
 use ic_registry_subnet_type::SubnetType;
 use ic_state_tool::commands;
-use ic_types::{Height, PrincipalId, Time};
+use ic_types::{CanisterId, Height, PrincipalId, Time};
 use std::{error::Error, path::PathBuf};
 
 /// Supported `state_tool` commands and their arguments.
@@ -17,7 +17,17 @@ use std::{error::Error, path::PathBuf};
 enum Opt {
     /// Computes diff of canonical trees between checkpoints.
     #[clap(name = "cdiff")]
-    CDiff { path_a: PathBuf, path_b: PathBuf },
+    CDiff {
+        path_a: PathBuf,
+        path_b: PathBuf,
+        /// Decode both states and report per-canister and subnet metadata
+        /// changes instead of canonical tree differences.
+        #[clap(long = "semantic")]
+        semantic: bool,
+        /// Print the semantic diff as JSON.
+        #[clap(long = "json", requires = "semantic")]
+        json: bool,
+    },
 
     /// Computes partial state hash that is used for certification.
     #[clap(name = "chash")]
@@ -89,6 +99,33 @@ enum Opt {
         file: PathBuf,
     },
 
+    /// Executes a single message against a canister of a checkpoint and prints
+    /// the outcome. Nothing is written back to the checkpoint.
+    #[clap(name = "execute")]
+    Execute {
+        /// Path to a checkpoint.
+        #[clap(long = "state")]
+        path: PathBuf,
+        /// The canister to execute the message on.
+        #[clap(long = "canister_id")]
+        canister_id: PrincipalId,
+        /// The update or query method to call.
+        #[clap(long = "method")]
+        method: String,
+        /// Hex-encoded argument of the call.
+        #[clap(long = "payload", default_value = "")]
+        payload: String,
+        /// The caller of the method (defaults to the anonymous principal).
+        #[clap(long = "sender", default_value_t = PrincipalId::new_anonymous())]
+        sender: PrincipalId,
+        /// Type of the subnet the checkpoint belongs to.
+        #[clap(long = "subnet_type", default_value = "application")]
+        subnet_type: SubnetType,
+        /// Time of the execution. Defaults to the batch time of the checkpoint.
+        #[clap(long)]
+        time_nanos: Option<u64>,
+    },
+
     /// Converts textual principal representation to hex.
     #[clap(name = "canister_id_to_hex")]
     CanisterIdToHex {
@@ -226,7 +263,18 @@ fn main() {
 pub(crate) fn main_inner(args: Vec<String>) {
     let opt = Parser::parse_from(args);
     let result = match opt {
-        Opt::CDiff { path_a, path_b } => commands::cdiff::do_diff(path_a, path_b),
+        Opt::CDiff {
+            path_a,
+            path_b,
+            semantic,
+            json,
+        } => {
+            if semantic {
+                commands::cdiff::do_semantic_diff(path_a, path_b, json)
+            } else {
+                commands::cdiff::do_diff(path_a, path_b)
+            }
+        }
         Opt::CHash { path } => commands::chash::do_hash(path),
         Opt::ImportState {
             state,
@@ -242,6 +290,29 @@ pub(crate) fn main_inner(args: Vec<String>) {
         Opt::VerifyManifest { file } => commands::verify_manifest::do_verify_manifest(&file),
         Opt::ListStates { config } => commands::list::do_list(config),
         Opt::Decode { file } => commands::decode::do_decode(file),
+        Opt::Execute {
+            path,
+            canister_id,
+            method,
+            payload,
+            sender,
+            subnet_type,
+            time_nanos,
+        } => hex::decode(payload)
+            .map_err(|e| format!("invalid payload: {e}"))
+            .and_then(|method_payload| {
+                commands::execute::do_execute(
+                    path,
+                    commands::execute::Message {
+                        canister_id: CanisterId::unchecked_from_principal(canister_id),
+                        method_name: method,
+                        method_payload,
+                        sender,
+                    },
+                    subnet_type,
+                    time_nanos.map(Time::from_nanos_since_unix_epoch),
+                )
+            }),
         Opt::CanisterIdToHex { canister_id } => {
             commands::convert_ids::do_canister_id_to_hex(canister_id)
         }
@@ -505,6 +505,16 @@ pub fn fake_ecdsa_idkg_master_public_key_id() -> IDkgMasterPublicKeyId {
         .unwrap()
 }
 
+pub fn fake_ecdsa_secp256r1_key_id() -> EcdsaKeyId {
+    EcdsaKeyId::from_str("Secp256r1:some_key").unwrap()
+}
+
+pub fn fake_ecdsa_secp256r1_idkg_master_public_key_id() -> IDkgMasterPublicKeyId {
+    MasterPublicKeyId::Ecdsa(fake_ecdsa_secp256r1_key_id())
+        .try_into()
+        .unwrap()
+}
+
 pub fn fake_schnorr_key_id(algorithm: SchnorrAlgorithm) -> SchnorrKeyId {
     SchnorrKeyId {
         algorithm,
@@ -549,6 +559,9 @@ pub fn fake_master_public_key_ids_for_all_idkg_algorithms() -> Vec<IDkgMasterPub
     AlgorithmId::iter()
         .flat_map(|alg| match alg {
             AlgorithmId::ThresholdEcdsaSecp256k1 => Some(fake_ecdsa_idkg_master_public_key_id()),
+            AlgorithmId::ThresholdEcdsaSecp256r1 => {
+                Some(fake_ecdsa_secp256r1_idkg_master_public_key_id())
+            }
             AlgorithmId::ThresholdSchnorrBip340 => Some(fake_schnorr_idkg_master_public_key_id(
                 SchnorrAlgorithm::Bip340Secp256k1,
             )),
@@ -6,8 +6,8 @@ use ic_config::execution_environment::Config as HypervisorConfig;
 use ic_config::subnet_config::SchedulerConfig;
 use ic_cycles_account_manager::ResourceSaturation;
 use ic_embedders::{
-    WasmtimeEmbedder,
-    wasm_utils::compile,
+    CompilationTier, WasmtimeEmbedder,
+    wasm_utils::compile_with_tier,
     wasmtime_embedder::{
         WasmtimeInstance,
         system_api::{
@@ -43,6 +43,7 @@ pub struct WasmtimeInstanceBuilder {
     config: ic_config::embedders::Config,
     memory_usage: NumBytes,
     environment_variables: BTreeMap<String, String>,
+    compilation_tier: CompilationTier,
 }
 
 impl Default for WasmtimeInstanceBuilder {
@@ -58,6 +59,7 @@ impl Default for WasmtimeInstanceBuilder {
             config: ic_config::embedders::Config::default(),
             memory_usage: NumBytes::from(0),
             environment_variables: BTreeMap::new(),
+            compilation_tier: CompilationTier::Baseline,
         }
     }
 }
@@ -124,6 +126,13 @@ impl WasmtimeInstanceBuilder {
         }
     }
 
+    pub fn with_compilation_tier(self, compilation_tier: CompilationTier) -> Self {
+        Self {
+            compilation_tier,
+            ..self
+        }
+    }
+
     #[allow(clippy::result_large_err)]
     pub fn try_build(self) -> Result<WasmtimeInstance, (HypervisorError, SystemApiImpl)> {
         let log = no_op_logger();
@@ -135,7 +144,11 @@ impl WasmtimeInstanceBuilder {
         };
 
         let embedder = WasmtimeEmbedder::new(self.config, log.clone());
-        let (compiled, _result) = compile(&embedder, &BinaryEncodedWasm::new(wasm));
+        let (compiled, _result) = compile_with_tier(
+            &embedder,
+            &BinaryEncodedWasm::new(wasm),
+            self.compilation_tier,
+        );
 
         let cycles_account_manager = CyclesAccountManagerBuilder::new().build();
         let system_state = SystemStateBuilder::default()
@@ -63,7 +63,7 @@ use ic_types::crypto::threshold_sig::ni_dkg::{
 };
 use ic_types::messages::CertificateDelegationMetadata;
 use ic_types::{
-    CanisterId, Cycles, Height, NumInstructions, QueryStatsEpoch, Time, UserId,
+    CanisterId, Cycles, Height, NodeId, NumInstructions, QueryStatsEpoch, Time, UserId,
     batch::QueryStats,
     crypto::{AlgorithmId, canister_threshold_sig::MasterPublicKey},
     ingress::{IngressState, IngressStatus, WasmResult},
@@ -84,6 +84,7 @@ use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     convert::TryFrom,
     os::unix::prelude::FileExt,
+    path::PathBuf,
     str::FromStr,
     sync::Arc,
     time::Duration,
@@ -2215,6 +2216,11 @@ impl ExecutionTestBuilder {
         self
     }
 
+    pub fn with_persistent_compilation_cache_dir(mut self, dir: PathBuf) -> Self {
+        self.execution_config.persistent_compilation_cache_dir = Some(dir);
+        self
+    }
+
     pub fn with_query_cache_capacity(mut self, capacity_bytes: u64) -> Self {
         self.execution_config.query_cache_capacity = capacity_bytes.into();
         self
@@ -2405,6 +2411,48 @@ impl ExecutionTestBuilder {
         self
     }
 
+    pub fn with_canister_http_aggregate_by_canister(mut self) -> Self {
+        self.execution_config.canister_http_aggregate_by_canister = FlagStatus::Enabled;
+        self
+    }
+
+    pub fn with_node_ids(self, node_ids: BTreeSet<NodeId>) -> Self {
+        Self {
+            registry_settings: RegistryExecutionSettings {
+                node_ids,
+                ..self.registry_settings
+            },
+            ..self
+        }
+    }
+
+    pub fn with_query_cache_hints(mut self) -> Self {
+        self.execution_config
+            .embedders_config
+            .feature_flags
+            .query_cache_hints = FlagStatus::Enabled;
+        self
+    }
+
+    pub fn with_wasm_gc_and_exceptions(mut self) -> Self {
+        self.execution_config
+            .embedders_config
+            .feature_flags
+            .wasm_gc_and_exceptions = FlagStatus::Enabled;
+        self
+    }
+
+    pub fn with_tiered_compilation(mut self, threshold: NumInstructions) -> Self {
+        self.execution_config
+            .embedders_config
+            .feature_flags
+            .tiered_compilation = FlagStatus::Enabled;
+        self.execution_config
+            .embedders_config
+            .tiered_compilation_threshold = threshold;
+        self
+    }
+
     pub fn with_environment_variables_flag(
         mut self,
         environment_variables_flag: FlagStatus,
@@ -18,7 +18,8 @@ use ic_replicated_state::{
     metadata_state::{
         Stream, SubnetMetrics,
         subnet_call_context_manager::{
-            BitcoinGetSuccessorsContext, BitcoinSendTransactionInternalContext, SubnetCallContext,
+            BitcoinGetBlockFiltersContext, BitcoinGetSuccessorsContext,
+            BitcoinSendTransactionInternalContext, SubnetCallContext,
         },
     },
     page_map::PageMap,
@@ -186,6 +187,15 @@ impl ReplicatedStateBuilder {
                         ),
                     );
                 }
+                BitcoinAdapterRequestWrapper::GetBlockFiltersRequest(payload) => {
+                    state.metadata.subnet_call_context_manager.push_context(
+                        SubnetCallContext::BitcoinGetBlockFilters(BitcoinGetBlockFiltersContext {
+                            request: RequestBuilder::default().build(),
+                            payload,
+                            time: UNIX_EPOCH,
+                        }),
+                    );
+                }
             }
         }
 
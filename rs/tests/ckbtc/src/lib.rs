@@ -413,6 +413,7 @@ pub async fn install_minter(
         kyt_principal: None,
         kyt_fee: None,
         get_utxos_cache_expiration_seconds: None,
+        utxo_consolidation_max_fee_per_vbyte: None,
     };
 
     let minter_arg = MinterArg::Init(args);
@@ -76,6 +76,13 @@ pub fn make_ecdsa_key_id() -> MasterPublicKeyId {
     })
 }
 
+pub fn make_ecdsa_secp256r1_key_id() -> MasterPublicKeyId {
+    MasterPublicKeyId::Ecdsa(EcdsaKeyId {
+        curve: EcdsaCurve::Secp256r1,
+        name: "some_ecdsa_secp256r1_key".to_string(),
+    })
+}
+
 pub fn make_eddsa_key_id() -> MasterPublicKeyId {
     MasterPublicKeyId::Schnorr(SchnorrKeyId {
         algorithm: SchnorrAlgorithm::Ed25519,
@@ -100,6 +107,7 @@ pub fn make_vetkd_key_id() -> MasterPublicKeyId {
 pub fn make_key_ids_for_all_schemes() -> Vec<MasterPublicKeyId> {
     vec![
         make_ecdsa_key_id(),
+        make_ecdsa_secp256r1_key_id(),
         make_bip340_key_id(),
         make_eddsa_key_id(),
         make_vetkd_key_id(),
@@ -109,6 +117,7 @@ pub fn make_key_ids_for_all_schemes() -> Vec<MasterPublicKeyId> {
 pub fn make_key_ids_for_all_idkg_schemes() -> Vec<MasterPublicKeyId> {
     vec![
         make_ecdsa_key_id(),
+        make_ecdsa_secp256r1_key_id(),
         make_bip340_key_id(),
         make_eddsa_key_id(),
     ]
@@ -1147,6 +1156,12 @@ pub fn verify_ecdsa_signature(pk: &[u8], sig: &[u8], msg: &[u8]) -> bool {
     pk.verify_prehash(msg, &signature).is_ok()
 }
 
+pub fn verify_ecdsa_secp256r1_signature(pk: &[u8], sig: &[u8], msg: &[u8]) -> bool {
+    let pk =
+        ic_secp256r1::PublicKey::deserialize_sec1(pk).expect("Bytes are not a valid public key");
+    pk.verify_signature_prehashed(msg, sig)
+}
+
 pub fn verify_vetkey(public_key: &[u8], encrypted_key: &[u8], input: &[u8]) -> bool {
     let dpk = DerivedPublicKey::deserialize(public_key).expect("Failed to deserialize public key");
 
@@ -1165,6 +1180,7 @@ pub fn verify_signature(key_id: &MasterPublicKeyId, msg: &[u8], pk: &[u8], sig:
     let res = match key_id {
         MasterPublicKeyId::Ecdsa(key_id) => match key_id.curve {
             EcdsaCurve::Secp256k1 => verify_ecdsa_signature(pk, sig, msg),
+            EcdsaCurve::Secp256r1 => verify_ecdsa_secp256r1_signature(pk, sig, msg),
         },
         MasterPublicKeyId::Schnorr(key_id) => match key_id.algorithm {
             SchnorrAlgorithm::Bip340Secp256k1 => verify_bip340_signature(pk, sig, msg),
@@ -1184,10 +1200,11 @@ pub enum SignWithChainKeyReply {
     VetKd(VetKdDeriveKeyResult),
 }
 
-fn cast_ecdsa_key_id(key_id: EcdsaKeyId) -> ic_cdk::management_canister::EcdsaKeyId {
-    ic_cdk::management_canister::EcdsaKeyId {
+fn cast_ecdsa_key_id(key_id: EcdsaKeyId) -> ic_signer::EcdsaKeyId {
+    ic_signer::EcdsaKeyId {
         curve: match key_id.curve {
-            EcdsaCurve::Secp256k1 => ic_cdk::management_canister::EcdsaCurve::Secp256k1,
+            EcdsaCurve::Secp256k1 => ic_signer::EcdsaCurve::Secp256k1,
+            EcdsaCurve::Secp256r1 => ic_signer::EcdsaCurve::Secp256r1,
         },
         name: key_id.name,
     }
@@ -343,6 +343,8 @@ fn minter_init_args(ecdsa_key_id: &EcdsaKeyId, cketh_ledger: Principal) -> Minte
         next_transaction_nonce: Nat::from(0_u8),
         last_scraped_block_number: Nat::from(0_u8),
         evm_rpc_id: None,
+        evm_rpc_custom_provider_urls: None,
+        custom_chain_is_op_stack: None,
     }
 }
 
@@ -355,6 +357,7 @@ async fn install_ledger_suite_orchestrator(
         more_controller_ids: vec![ROOT_CANISTER_ID.get().0],
         minter_id: Some(minter),
         cycles_management: None,
+        additional_minters: None,
     });
     let lso_wasm = Wasm::from_file(
         env::var("LEDGER_SUITE_ORCHESTRATOR_WASM_PATH")
@@ -83,6 +83,7 @@ fn ic_xc_ledger_suite_orchestrator_test(env: TestEnv) {
             more_controller_ids: vec![ROOT_CANISTER_ID.get().0],
             minter_id: Some(Principal::from_str("sv3dd-oaaaa-aaaar-qacoa-cai").unwrap()),
             cycles_management: None,
+            additional_minters: None,
         });
         let canister = install_nns_controlled_canister(
             &logger,
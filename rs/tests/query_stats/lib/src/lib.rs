@@ -127,6 +127,7 @@ pub(crate) async fn single_https_outcall(canister: &Principal, agents: &[Agent])
         }),
         is_replicated: None,
         pricing_version: None,
+        aggregate_by_canister: None,
     };
 
     agents
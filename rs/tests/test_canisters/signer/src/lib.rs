@@ -1,7 +1,34 @@
 use candid::CandidType;
-use ic_cdk::management_canister::{EcdsaKeyId, SchnorrAux, SchnorrKeyId, VetKDKeyId};
+use ic_cdk::management_canister::{SchnorrAux, SchnorrKeyId, VetKDKeyId};
 use serde::Deserialize;
 
+/// The curve of a threshold ECDSA key.
+///
+/// Unlike [`ic_cdk::management_canister::EcdsaCurve`], this includes `secp256r1`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, CandidType, Deserialize)]
+pub enum EcdsaCurve {
+    #[serde(rename = "secp256k1")]
+    Secp256k1,
+    #[serde(rename = "secp256r1")]
+    Secp256r1,
+}
+
+impl From<EcdsaCurve> for u32 {
+    /// The curve identifier used by the `ic0.cost_sign_with_ecdsa` system API.
+    fn from(curve: EcdsaCurve) -> Self {
+        match curve {
+            EcdsaCurve::Secp256k1 => 0,
+            EcdsaCurve::Secp256r1 => 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, CandidType, Deserialize)]
+pub struct EcdsaKeyId {
+    pub curve: EcdsaCurve,
+    pub name: String,
+}
+
 #[derive(Clone, Debug, CandidType, Deserialize)]
 pub struct GenEcdsaParams {
     pub derivation_path_length: usize,
@@ -1,16 +1,25 @@
 use std::marker::PhantomData;
 
-use candid::Encode;
+use candid::{CandidType, Encode, Principal};
 use ic_cdk::{
-    api::{msg_reject, msg_reply},
+    api::{cost_sign_with_ecdsa, msg_reject, msg_reply},
+    call::Call,
     management_canister::{
-        SignWithEcdsaArgs, SignWithEcdsaResult, SignWithSchnorrArgs, SignWithSchnorrResult,
-        VetKDDeriveKeyArgs, VetKDDeriveKeyResult, sign_with_ecdsa, sign_with_schnorr,
-        vetkd_derive_key,
+        SignWithEcdsaResult, SignWithSchnorrArgs, SignWithSchnorrResult, VetKDDeriveKeyArgs,
+        VetKDDeriveKeyResult, sign_with_schnorr, vetkd_derive_key,
     },
     update,
 };
-use ic_signer::{GenEcdsaParams, GenSchnorrParams, GenVetkdParams};
+use ic_signer::{EcdsaKeyId, GenEcdsaParams, GenSchnorrParams, GenVetkdParams};
+
+/// The arguments of `sign_with_ecdsa`. Unlike [`ic_cdk::management_canister::SignWithEcdsaArgs`],
+/// the key can be over any curve supported by the IC, including `secp256r1`.
+#[derive(CandidType)]
+struct SignWithEcdsaArgs {
+    message_hash: Vec<u8>,
+    derivation_path: Vec<Vec<u8>>,
+    key_id: EcdsaKeyId,
+}
 
 /// Generates a dummy ECDSA signature of given size parameters.
 /// The call does not verify the signature, it only generates it.
@@ -30,11 +39,23 @@ pub async fn gen_ecdsa_sig(
 
     match sign_with_ecdsa(&signature_request).await {
         Ok(sig) => msg_reply(Encode!(&sig).unwrap()),
-        Err(err) => msg_reject(err.to_string()),
+        Err(err) => msg_reject(err),
     }
     PhantomData
 }
 
+async fn sign_with_ecdsa(args: &SignWithEcdsaArgs) -> Result<SignWithEcdsaResult, String> {
+    let cycles = cost_sign_with_ecdsa(&args.key_id.name, args.key_id.curve.into())
+        .map_err(|err| format!("{err:?}"))?;
+    Call::unbounded_wait(Principal::management_canister(), "sign_with_ecdsa")
+        .with_arg(args)
+        .with_cycles(cycles)
+        .await
+        .map_err(|err| err.to_string())?
+        .candid()
+        .map_err(|err| err.to_string())
+}
+
 /// Generates a dummy Schnorr signature of given size parameters.
 /// The call does not verify the signature, it only generates it.
 #[update(manual_reply = true)]
@@ -93,6 +93,7 @@ pub type BoundedHttpHeaders = BoundedVec<
 ///   };
 ///   is_replicated : opt bool;
 ///   pricing_version : opt nat32;
+///   aggregate_by_canister : opt bool;
 /// }
 /// ```
 #[derive(Clone, PartialEq, Debug, CandidType, Deserialize)]
@@ -106,6 +107,10 @@ pub struct CanisterHttpRequestArgs {
     pub transform: Option<TransformContext>,
     pub is_replicated: Option<bool>,
     pub pricing_version: Option<u32>,
+    /// If set to `true`, the responses received by the individual nodes are
+    /// delivered to the canister, which aggregates them itself, instead of
+    /// requiring the nodes to agree on a single response.
+    pub aggregate_by_canister: Option<bool>,
 }
 
 impl Payload<'_> for CanisterHttpRequestArgs {}
@@ -118,6 +123,11 @@ impl CanisterHttpRequestArgs {
             .as_ref()
             .map(|transform_context| PrincipalId::from(transform_context.function.0.principal))
     }
+
+    /// Returns true if the canister requested to aggregate the responses itself.
+    pub fn is_aggregated_by_canister(&self) -> bool {
+        self.aggregate_by_canister.unwrap_or(false)
+    }
 }
 
 #[test]
@@ -142,6 +152,7 @@ fn test_http_headers_max_number() {
             transform: None,
             is_replicated: None,
             pricing_version: None,
+            aggregate_by_canister: None,
         };
 
         // Act.
@@ -196,6 +207,7 @@ fn test_http_headers_max_total_size() {
             transform: None,
             is_replicated: None,
             pricing_version: None,
+            aggregate_by_canister: None,
         };
 
         // Act.
@@ -244,6 +256,7 @@ fn test_http_headers_max_element_size() {
             transform: None,
             is_replicated: None,
             pricing_version: None,
+            aggregate_by_canister: None,
         };
 
         // Act.
@@ -341,3 +354,39 @@ pub struct CanisterHttpResponsePayload {
 }
 
 impl Payload<'_> for CanisterHttpResponsePayload {}
+
+/// The response received by a single node for a canister http request whose
+/// responses are aggregated by the canister.
+/// Struct used for encoding/decoding
+/// ```text
+/// record {
+///   node_id : principal;
+///   result : variant { Ok : http_request_result; Err : text };
+/// }
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, CandidType, Deserialize, Serialize)]
+pub struct CanisterHttpNodeResponse {
+    pub node_id: PrincipalId,
+    pub result: Result<CanisterHttpResponsePayload, String>,
+}
+
+/// The reply to a canister http request whose responses are aggregated by the
+/// canister. Each entry is the response received by a different node.
+///
+/// Aggregation by the canister is behind a feature flag, so this reply is not
+/// part of the management canister interface yet.
+/// Struct used for encoding/decoding
+/// ```text
+/// record {
+///   responses : vec record {
+///     node_id : principal;
+///     result : variant { Ok : http_request_result; Err : text };
+///   };
+/// }
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default, CandidType, Deserialize, Serialize)]
+pub struct CanisterHttpAggregatedResponsePayload {
+    pub responses: Vec<CanisterHttpNodeResponse>,
+}
+
+impl Payload<'_> for CanisterHttpAggregatedResponsePayload {}
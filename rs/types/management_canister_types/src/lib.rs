@@ -10,7 +10,8 @@ pub use bounded_vec::*;
 use candid::{CandidType, Decode, DecoderConfig, Deserialize, Encode, Reserved};
 pub use data_size::*;
 pub use http::{
-    ALLOWED_HTTP_OUTCALLS_PRICING_VERSIONS, BoundedHttpHeaders, CanisterHttpRequestArgs,
+    ALLOWED_HTTP_OUTCALLS_PRICING_VERSIONS, BoundedHttpHeaders,
+    CanisterHttpAggregatedResponsePayload, CanisterHttpNodeResponse, CanisterHttpRequestArgs,
     CanisterHttpResponsePayload, DEFAULT_HTTP_OUTCALLS_PRICING_VERSION, HttpHeader, HttpMethod,
     PRICING_VERSION_LEGACY, PRICING_VERSION_PAY_AS_YOU_GO, TransformArgs, TransformContext,
     TransformFunc,
@@ -109,6 +110,7 @@ pub enum Method {
     // Private APIs used exclusively by the bitcoin canisters.
     BitcoinSendTransactionInternal, // API for sending transactions to the network.
     BitcoinGetSuccessors,           // API for fetching blocks from the network.
+    BitcoinGetBlockFilters,         // API for fetching BIP-158 block filters from the network.
 
     // Subnet information
     NodeMetricsHistory,
@@ -312,17 +314,24 @@ impl CanisterCodeDeploymentRecord {
 /// ```text
 /// record {
 ///   controllers : vec principal;
+///   controller_roles : opt controller_roles;
 /// }
 /// ```
 #[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
 pub struct CanisterControllersChangeRecord {
     controllers: Vec<PrincipalId>,
+    /// Only present if the controller roles have been changed.
+    controller_roles: Option<ControllerRoles>,
 }
 
 impl CanisterControllersChangeRecord {
     pub fn controllers(&self) -> &[PrincipalId] {
         &self.controllers
     }
+
+    pub fn controller_roles(&self) -> Option<&ControllerRoles> {
+        self.controller_roles.as_ref()
+    }
 }
 
 /// `CandidType` for `CanisterLoadSnapshotRecord`
@@ -482,6 +491,11 @@ impl RenameToRecord {
 ///   };
 ///   controllers_change : record {
 ///     controllers : vec principal;
+///     controller_roles : opt record {
+///       upgraders : vec principal;
+///       operators : vec principal;
+///       observers : vec principal;
+///     };
 ///   };
 ///   load_snapshot : record {
 ///     canister_version : nat64;
@@ -551,6 +565,17 @@ impl CanisterChangeDetails {
     pub fn controllers_change(controllers: Vec<PrincipalId>) -> CanisterChangeDetails {
         CanisterChangeDetails::CanisterControllersChange(CanisterControllersChangeRecord {
             controllers,
+            controller_roles: None,
+        })
+    }
+
+    pub fn controllers_and_roles_change(
+        controllers: Vec<PrincipalId>,
+        controller_roles: ControllerRoles,
+    ) -> CanisterChangeDetails {
+        CanisterChangeDetails::CanisterControllersChange(CanisterControllersChangeRecord {
+            controllers,
+            controller_roles: Some(controller_roles),
         })
     }
 
@@ -653,7 +678,8 @@ impl CanisterChange {
 
     /// Returns the number of bytes to represent a canister change in memory.
     /// The vector of controllers in `CanisterCreation`, `CanisterControllersChange`
-    /// and `CanisterSettingsChange` is counted separately because
+    /// and `CanisterSettingsChange` (and the controller roles in
+    /// `CanisterControllersChange`) is counted separately because
     /// the controllers are stored on heap and thus not accounted
     /// for in `size_of::<CanisterChange>()`.
     pub fn count_bytes(&self) -> NumBytes {
@@ -663,6 +689,10 @@ impl CanisterChange {
             }
             CanisterChangeDetails::CanisterControllersChange(canister_controllers_change) => {
                 canister_controllers_change.controllers().len()
+                    + canister_controllers_change
+                        .controller_roles()
+                        .map(ControllerRoles::num_principals)
+                        .unwrap_or_default()
             }
             CanisterChangeDetails::CanisterSettingsChange(canister_settings_change) => {
                 canister_settings_change
@@ -911,6 +941,10 @@ impl From<&CanisterChangeDetails> for pb_canister_state_bits::canister_change::C
                             .iter()
                             .map(|c| (*c).into())
                             .collect::<Vec<ic_protobuf::types::v1::PrincipalId>>(),
+                        controller_roles: canister_controllers_change
+                            .controller_roles
+                            .as_ref()
+                            .map(|roles| roles.into()),
                     },
                 )
             }
@@ -1019,13 +1053,22 @@ impl TryFrom<pb_canister_state_bits::canister_change::ChangeDetails> for Caniste
             }
             pb_canister_state_bits::canister_change::ChangeDetails::CanisterControllersChange(
                 canister_controllers_change,
-            ) => Ok(CanisterChangeDetails::controllers_change(
-                canister_controllers_change
+            ) => {
+                let controllers = canister_controllers_change
                     .controllers
                     .into_iter()
                     .map(TryInto::try_into)
-                    .collect::<Result<Vec<PrincipalId>, _>>()?,
-            )),
+                    .collect::<Result<Vec<PrincipalId>, _>>()?;
+                match canister_controllers_change.controller_roles {
+                    Some(controller_roles) => {
+                        Ok(CanisterChangeDetails::controllers_and_roles_change(
+                            controllers,
+                            controller_roles.try_into()?,
+                        ))
+                    }
+                    None => Ok(CanisterChangeDetails::controllers_change(controllers)),
+                }
+            }
             pb_canister_state_bits::canister_change::ChangeDetails::CanisterLoadSnapshot(
                 canister_load_snapshot,
             ) => {
@@ -1276,6 +1319,132 @@ impl TryFrom<pb_canister_state_bits::LogVisibilityV2> for LogVisibilityV2 {
     }
 }
 
+/// A subset of the controller permissions that can be granted to a principal
+/// without making it a controller of the canister.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, EnumIter)]
+pub enum ControllerRole {
+    /// May upgrade the canister code, but not install or reinstall it.
+    Upgrader,
+    /// May start and stop the canister and take, list, read and delete its
+    /// snapshots, but not upload or load them.
+    Operator,
+    /// May read the status and the logs of the canister.
+    Observer,
+}
+
+impl fmt::Display for ControllerRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ControllerRole::Upgrader => write!(f, "upgrader"),
+            ControllerRole::Operator => write!(f, "operator"),
+            ControllerRole::Observer => write!(f, "observer"),
+        }
+    }
+}
+
+/// Principals holding a [`ControllerRole`] on a canister. Each role can be
+/// held by at most as many principals as there can be controllers.
+/// ```text
+/// record {
+///   upgraders : vec principal;
+///   operators : vec principal;
+///   observers : vec principal;
+/// }
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize)]
+pub struct ControllerRoles {
+    upgraders: BoundedControllers,
+    operators: BoundedControllers,
+    observers: BoundedControllers,
+}
+
+impl Payload<'_> for ControllerRoles {}
+
+impl ControllerRoles {
+    /// Creates the roles from the given principals, dropping duplicates.
+    pub fn new(
+        upgraders: Vec<PrincipalId>,
+        operators: Vec<PrincipalId>,
+        observers: Vec<PrincipalId>,
+    ) -> Self {
+        fn normalize(principals: Vec<PrincipalId>) -> BoundedControllers {
+            let principals: BTreeSet<PrincipalId> = principals.into_iter().collect();
+            BoundedControllers::new(principals.into_iter().collect())
+        }
+        Self {
+            upgraders: normalize(upgraders),
+            operators: normalize(operators),
+            observers: normalize(observers),
+        }
+    }
+
+    /// Returns the principals holding the given role.
+    pub fn principals(&self, role: ControllerRole) -> &[PrincipalId] {
+        match role {
+            ControllerRole::Upgrader => self.upgraders.get(),
+            ControllerRole::Operator => self.operators.get(),
+            ControllerRole::Observer => self.observers.get(),
+        }
+    }
+
+    pub fn has_role(&self, principal: &PrincipalId, role: ControllerRole) -> bool {
+        self.principals(role).contains(principal)
+    }
+
+    /// Returns the total number of principals over all roles.
+    pub fn num_principals(&self) -> usize {
+        self.upgraders.get().len() + self.operators.get().len() + self.observers.get().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.upgraders.get().is_empty()
+            && self.operators.get().is_empty()
+            && self.observers.get().is_empty()
+    }
+
+    /// Returns a copy of the roles with duplicate principals removed.
+    pub fn normalized(&self) -> Self {
+        Self::new(
+            self.upgraders.get().clone(),
+            self.operators.get().clone(),
+            self.observers.get().clone(),
+        )
+    }
+}
+
+impl From<&ControllerRoles> for pb_canister_state_bits::ControllerRoles {
+    fn from(item: &ControllerRoles) -> Self {
+        fn to_proto(principals: &[PrincipalId]) -> Vec<ic_protobuf::types::v1::PrincipalId> {
+            principals.iter().map(|p| (*p).into()).collect()
+        }
+        Self {
+            upgraders: to_proto(item.upgraders.get()),
+            operators: to_proto(item.operators.get()),
+            observers: to_proto(item.observers.get()),
+        }
+    }
+}
+
+impl TryFrom<pb_canister_state_bits::ControllerRoles> for ControllerRoles {
+    type Error = ProxyDecodeError;
+
+    fn try_from(item: pb_canister_state_bits::ControllerRoles) -> Result<Self, Self::Error> {
+        fn from_proto(
+            principals: Vec<ic_protobuf::types::v1::PrincipalId>,
+        ) -> Result<Vec<PrincipalId>, ProxyDecodeError> {
+            principals
+                .into_iter()
+                .map(|p| Ok(PrincipalId::try_from(p)?))
+                .collect()
+        }
+        Ok(Self::new(
+            from_proto(item.upgraders)?,
+            from_proto(item.operators)?,
+            from_proto(item.observers)?,
+        ))
+    }
+}
+
 /// Struct used for encoding/decoding
 /// ```text
 /// record {
@@ -1290,6 +1459,7 @@ impl TryFrom<pb_canister_state_bits::LogVisibilityV2> for LogVisibilityV2 {
 ///   wasm_memory_limit : nat;
 ///   wasm_memory_threshold : nat;
 ///   environment_variables : vec environment_variable;
+///   controller_roles : controller_roles;
 /// }
 /// ```
 #[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
@@ -1305,6 +1475,7 @@ pub struct DefiniteCanisterSettingsArgs {
     wasm_memory_limit: candid::Nat,
     wasm_memory_threshold: candid::Nat,
     environment_variables: Vec<EnvironmentVariable>,
+    controller_roles: ControllerRoles,
 }
 
 impl DefiniteCanisterSettingsArgs {
@@ -1320,6 +1491,7 @@ impl DefiniteCanisterSettingsArgs {
         wasm_memory_limit: Option<u64>,
         wasm_memory_threshold: u64,
         environment_variables: EnvironmentVariables,
+        controller_roles: ControllerRoles,
     ) -> Self {
         let memory_allocation = candid::Nat::from(memory_allocation.unwrap_or(0));
         let reserved_cycles_limit = candid::Nat::from(reserved_cycles_limit.unwrap_or(0));
@@ -1343,6 +1515,7 @@ impl DefiniteCanisterSettingsArgs {
             wasm_memory_limit,
             wasm_memory_threshold: candid::Nat::from(wasm_memory_threshold),
             environment_variables,
+            controller_roles,
         }
     }
 
@@ -1385,6 +1558,10 @@ impl DefiniteCanisterSettingsArgs {
     pub fn environment_variables(&self) -> &[EnvironmentVariable] {
         &self.environment_variables
     }
+
+    pub fn controller_roles(&self) -> &ControllerRoles {
+        &self.controller_roles
+    }
 }
 
 impl Payload<'_> for DefiniteCanisterSettingsArgs {}
@@ -1495,6 +1672,7 @@ impl CanisterStatusResultV2 {
         wasm_memory_limit: Option<u64>,
         wasm_memory_threshold: u64,
         environment_variables: EnvironmentVariables,
+        controller_roles: ControllerRoles,
     ) -> Self {
         Self {
             status,
@@ -1529,6 +1707,7 @@ impl CanisterStatusResultV2 {
                 wasm_memory_limit,
                 wasm_memory_threshold,
                 environment_variables,
+                controller_roles,
             ),
             freezing_threshold: candid::Nat::from(freezing_threshold),
             idle_cycles_burned_per_day: candid::Nat::from(idle_cycles_burned_per_day),
@@ -2247,6 +2426,7 @@ pub struct EnvironmentVariable {
 ///   wasm_memory_limit : opt nat;
 ///   wasm_memory_threshold : opt nat;
 ///   environment_variables : opt vec environment_variable;
+///   controller_roles : opt controller_roles;
 /// }
 /// ```
 #[derive(Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize)]
@@ -2261,6 +2441,7 @@ pub struct CanisterSettingsArgs {
     pub wasm_memory_limit: Option<candid::Nat>,
     pub wasm_memory_threshold: Option<candid::Nat>,
     pub environment_variables: Option<Vec<EnvironmentVariable>>,
+    pub controller_roles: Option<ControllerRoles>,
 }
 
 impl Payload<'_> for CanisterSettingsArgs {}
@@ -2280,6 +2461,7 @@ impl CanisterSettingsArgs {
             wasm_memory_limit: None,
             wasm_memory_threshold: None,
             environment_variables: None,
+            controller_roles: None,
         }
     }
 }
@@ -2296,6 +2478,7 @@ pub struct CanisterSettingsArgsBuilder {
     wasm_memory_limit: Option<candid::Nat>,
     wasm_memory_threshold: Option<candid::Nat>,
     environment_variables: Option<Vec<EnvironmentVariable>>,
+    controller_roles: Option<ControllerRoles>,
 }
 
 #[allow(dead_code)]
@@ -2316,6 +2499,7 @@ impl CanisterSettingsArgsBuilder {
             wasm_memory_limit: self.wasm_memory_limit,
             wasm_memory_threshold: self.wasm_memory_threshold,
             environment_variables: self.environment_variables,
+            controller_roles: self.controller_roles,
         }
     }
 
@@ -2430,6 +2614,14 @@ impl CanisterSettingsArgsBuilder {
             ..self
         }
     }
+
+    /// Sets the principals holding controller roles.
+    pub fn with_controller_roles(self, controller_roles: ControllerRoles) -> Self {
+        Self {
+            controller_roles: Some(controller_roles),
+            ..self
+        }
+    }
 }
 
 /// Struct used for encoding/decoding
@@ -2575,7 +2767,7 @@ impl SetupInitialDKGResponse {
 
 /// Types of curves that can be used for ECDSA signing.
 /// ```text
-/// variant { secp256k1; }
+/// variant { secp256k1; secp256r1; }
 /// ```
 #[derive(
     Copy,
@@ -2594,6 +2786,8 @@ impl SetupInitialDKGResponse {
 pub enum EcdsaCurve {
     #[serde(rename = "secp256k1")]
     Secp256k1,
+    #[serde(rename = "secp256r1")]
+    Secp256r1,
 }
 
 impl TryFrom<u32> for EcdsaCurve {
@@ -2602,6 +2796,7 @@ impl TryFrom<u32> for EcdsaCurve {
     fn try_from(value: u32) -> Result<Self, Self::Error> {
         match value {
             0 => Ok(EcdsaCurve::Secp256k1),
+            1 => Ok(EcdsaCurve::Secp256r1),
             _ => Err(format!(
                 "{value} is not a recognized EcdsaCurve variant identifier."
             )),
@@ -2613,6 +2808,7 @@ impl From<&EcdsaCurve> for pb_types::EcdsaCurve {
     fn from(item: &EcdsaCurve) -> Self {
         match item {
             EcdsaCurve::Secp256k1 => pb_types::EcdsaCurve::Secp256k1,
+            EcdsaCurve::Secp256r1 => pb_types::EcdsaCurve::Secp256r1,
         }
     }
 }
@@ -2623,6 +2819,7 @@ impl TryFrom<pb_types::EcdsaCurve> for EcdsaCurve {
     fn try_from(item: pb_types::EcdsaCurve) -> Result<Self, Self::Error> {
         match item {
             pb_types::EcdsaCurve::Secp256k1 => Ok(EcdsaCurve::Secp256k1),
+            pb_types::EcdsaCurve::Secp256r1 => Ok(EcdsaCurve::Secp256r1),
             pb_types::EcdsaCurve::Unspecified => Err(ProxyDecodeError::ValueOutOfRange {
                 typ: "EcdsaCurve",
                 err: format!("Unable to convert {item:?} to an EcdsaCurve"),
@@ -2643,6 +2840,7 @@ impl FromStr for EcdsaCurve {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "secp256k1" => Ok(Self::Secp256k1),
+            "secp256r1" => Ok(Self::Secp256r1),
             _ => Err(format!("{s} is not a recognized ECDSA curve")),
         }
     }
@@ -3419,6 +3617,8 @@ pub use ic_btc_interface::{
     GetUtxosRequest as BitcoinGetUtxosArgs, SendTransactionRequest as BitcoinSendTransactionArgs,
 };
 pub use ic_btc_replica_types::{
+    BlockFilter as BitcoinBlockFilter, GetBlockFiltersRequest as BitcoinGetBlockFiltersArgs,
+    GetBlockFiltersResponse as BitcoinGetBlockFiltersResponse,
     GetSuccessorsRequest as BitcoinGetSuccessorsArgs,
     GetSuccessorsRequestInitial as BitcoinGetSuccessorsRequestInitial,
     GetSuccessorsResponse as BitcoinGetSuccessorsResponse,
@@ -3435,6 +3635,8 @@ impl Payload<'_> for BitcoinGetCurrentFeePercentilesArgs {}
 impl Payload<'_> for BitcoinGetSuccessorsArgs {}
 impl Payload<'_> for BitcoinGetSuccessorsResponse {}
 impl Payload<'_> for BitcoinSendTransactionInternalArgs {}
+impl Payload<'_> for BitcoinGetBlockFiltersArgs {}
+impl Payload<'_> for BitcoinGetBlockFiltersResponse {}
 
 /// Query methods exported by the management canister.
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Display, EnumIter, EnumString)]
@@ -3536,6 +3738,11 @@ impl FetchCanisterLogsRange {
         Self { start, end }
     }
 
+    /// Returns the start of the range (inclusive).
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
     /// Returns the length of the range.
     /// If user provides an `end` value below `start`, the length is 0.
     fn len(&self) -> u64 {
@@ -3569,6 +3776,149 @@ pub enum FetchCanisterLogsFilter {
 
 impl Payload<'_> for FetchCanisterLogsFilter {}
 
+/// The severity of a structured canister log record, ordered from the least
+/// to the most severe.
+///
+/// `ic0.debug_print_structured` takes the severity as the number of the
+/// corresponding variant of the protobuf enum, i.e., `debug` is 1 and `error`
+/// is 4.
+///
+/// `CandidType` for `CanisterLogSeverity`
+/// ```text
+/// variant {
+///     debug;
+///     info;
+///     warning;
+///     error;
+/// }
+/// ```
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    CandidType,
+    Deserialize,
+    EnumIter,
+    Serialize,
+)]
+pub enum CanisterLogSeverity {
+    #[serde(rename = "debug")]
+    Debug,
+    #[serde(rename = "info")]
+    Info,
+    #[serde(rename = "warning")]
+    Warning,
+    #[serde(rename = "error")]
+    Error,
+}
+
+impl TryFrom<u32> for CanisterLogSeverity {
+    type Error = String;
+
+    /// Converts the severity passed to `ic0.debug_print_structured`.
+    fn try_from(value: u32) -> Result<Self, String> {
+        i32::try_from(value)
+            .ok()
+            .and_then(|value| pb_canister_state_bits::CanisterLogSeverity::try_from(value).ok())
+            .and_then(|severity| CanisterLogSeverity::try_from(severity).ok())
+            .ok_or_else(|| format!("Invalid canister log severity: {value}"))
+    }
+}
+
+impl From<CanisterLogSeverity> for u32 {
+    /// Returns the severity as passed to `ic0.debug_print_structured`.
+    fn from(value: CanisterLogSeverity) -> Self {
+        pb_canister_state_bits::CanisterLogSeverity::from(value) as u32
+    }
+}
+
+impl From<CanisterLogSeverity> for pb_canister_state_bits::CanisterLogSeverity {
+    fn from(value: CanisterLogSeverity) -> Self {
+        match value {
+            CanisterLogSeverity::Debug => pb_canister_state_bits::CanisterLogSeverity::Debug,
+            CanisterLogSeverity::Info => pb_canister_state_bits::CanisterLogSeverity::Info,
+            CanisterLogSeverity::Warning => pb_canister_state_bits::CanisterLogSeverity::Warning,
+            CanisterLogSeverity::Error => pb_canister_state_bits::CanisterLogSeverity::Error,
+        }
+    }
+}
+
+impl TryFrom<pb_canister_state_bits::CanisterLogSeverity> for CanisterLogSeverity {
+    type Error = ProxyDecodeError;
+
+    fn try_from(
+        value: pb_canister_state_bits::CanisterLogSeverity,
+    ) -> Result<Self, ProxyDecodeError> {
+        match value {
+            pb_canister_state_bits::CanisterLogSeverity::Unspecified => {
+                Err(ProxyDecodeError::ValueOutOfRange {
+                    typ: "CanisterLogSeverity",
+                    err: format!("Unexpected value of CanisterLogSeverity: {value:?}"),
+                })
+            }
+            pb_canister_state_bits::CanisterLogSeverity::Debug => Ok(CanisterLogSeverity::Debug),
+            pb_canister_state_bits::CanisterLogSeverity::Info => Ok(CanisterLogSeverity::Info),
+            pb_canister_state_bits::CanisterLogSeverity::Warning => {
+                Ok(CanisterLogSeverity::Warning)
+            }
+            pb_canister_state_bits::CanisterLogSeverity::Error => Ok(CanisterLogSeverity::Error),
+        }
+    }
+}
+
+/// A key/value field of a structured canister log record.
+///
+/// `CandidType` for `CanisterLogField`
+/// ```text
+/// record {
+///     key : text;
+///     value : text;
+/// }
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, CandidType, Deserialize, Serialize)]
+pub struct CanisterLogField {
+    pub key: String,
+    pub value: String,
+}
+
+impl CanisterLogField {
+    pub fn new(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+}
+
+impl DataSize for CanisterLogField {
+    fn data_size(&self) -> usize {
+        self.key.data_size() + self.value.data_size()
+    }
+}
+
+impl From<&CanisterLogField> for pb_canister_state_bits::CanisterLogField {
+    fn from(item: &CanisterLogField) -> Self {
+        Self {
+            key: item.key.clone(),
+            value: item.value.clone(),
+        }
+    }
+}
+
+impl From<pb_canister_state_bits::CanisterLogField> for CanisterLogField {
+    fn from(item: pb_canister_state_bits::CanisterLogField) -> Self {
+        Self {
+            key: item.key,
+            value: item.value,
+        }
+    }
+}
+
 /// `CandidType` for `FetchCanisterLogsRequest`
 /// ```text
 /// record {
@@ -3576,13 +3926,19 @@ impl Payload<'_> for FetchCanisterLogsFilter {}
 ///     filter : opt variant {
 ///       by_idx : record { start : nat64; end : nat64 };
 ///       by_timestamp_nanos : record { start : nat64; end : nat64 };
-///     }
+///     };
+///     min_severity : opt canister_log_severity;
+///     fields : opt vec record { key : text; value : text };
 /// }
 /// ```
 #[derive(Clone, Debug, Default, CandidType, Deserialize)]
 pub struct FetchCanisterLogsRequest {
     pub canister_id: PrincipalId,
     pub filter: Option<FetchCanisterLogsFilter>,
+    /// Only return structured records with at least the given severity.
+    pub min_severity: Option<CanisterLogSeverity>,
+    /// Only return structured records that contain all of the given fields.
+    pub fields: Option<Vec<CanisterLogField>>,
 }
 
 impl Payload<'_> for FetchCanisterLogsRequest {}
@@ -3592,6 +3948,8 @@ impl FetchCanisterLogsRequest {
         Self {
             canister_id: canister_id.into(),
             filter: None,
+            min_severity: None,
+            fields: None,
         }
     }
 
@@ -3599,6 +3957,8 @@ impl FetchCanisterLogsRequest {
         Self {
             canister_id: canister_id.into(),
             filter: Some(filter),
+            min_severity: None,
+            fields: None,
         }
     }
 
@@ -3613,21 +3973,53 @@ impl FetchCanisterLogsRequest {
 ///     idx : nat64;
 ///     timestamp_nanos : nat64;
 ///     content : blob;
+///     severity : opt canister_log_severity;
+///     fields : opt vec record { key : text; value : text };
 /// }
 /// ```
+///
+/// The `severity` and `fields` are only set for structured records (written
+/// by `ic0.debug_print_structured`).
 #[derive(Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize, Serialize)]
 pub struct CanisterLogRecord {
     pub idx: u64,
     pub timestamp_nanos: u64,
     #[serde(with = "serde_bytes")]
     pub content: Vec<u8>,
+    pub severity: Option<CanisterLogSeverity>,
+    pub fields: Option<Vec<CanisterLogField>>,
 }
 
 impl Payload<'_> for CanisterLogRecord {}
 
+impl CanisterLogRecord {
+    /// The size accounted for every record in addition to its content: the
+    /// index, the timestamp and the content buffer. The severity and fields of
+    /// structured records are accounted for separately, so that the size of
+    /// unstructured records does not depend on them.
+    pub const BASE_SIZE: usize = 2 * size_of::<u64>() + size_of::<Vec<u8>>();
+
+    /// The size accounted for the severity and the field buffer of a structured
+    /// record in addition to the `BASE_SIZE` and the size of its fields.
+    pub const STRUCTURED_BASE_SIZE: usize =
+        size_of::<CanisterLogSeverity>() + size_of::<Vec<CanisterLogField>>();
+
+    /// Returns the fields of the record (empty for unstructured records).
+    pub fn fields(&self) -> &[CanisterLogField] {
+        self.fields.as_deref().unwrap_or_default()
+    }
+}
+
 impl DataSize for CanisterLogRecord {
     fn data_size(&self) -> usize {
-        std::mem::size_of::<Self>() + self.content.as_slice().data_size()
+        let structured_size = match self.severity {
+            Some(_) => {
+                Self::STRUCTURED_BASE_SIZE
+                    + self.fields().iter().map(|f| f.data_size()).sum::<usize>()
+            }
+            None => 0,
+        };
+        Self::BASE_SIZE + self.content.as_slice().data_size() + structured_size
     }
 }
 
@@ -3637,8 +4029,19 @@ fn test_canister_log_record_data_size() {
         idx: 100,
         timestamp_nanos: 200,
         content: vec![1, 2, 3],
+        severity: None,
+        fields: None,
     };
     assert_eq!(record.data_size(), 8 + 8 + 24 + 3);
+
+    let record = CanisterLogRecord {
+        idx: 100,
+        timestamp_nanos: 200,
+        content: vec![1, 2, 3],
+        severity: Some(CanisterLogSeverity::Info),
+        fields: Some(vec![CanisterLogField::new("key", "value")]),
+    };
+    assert_eq!(record.data_size(), 8 + 8 + 24 + 3 + 1 + 24 + 3 + 5);
 }
 
 impl From<&CanisterLogRecord> for pb_canister_state_bits::CanisterLogRecord {
@@ -3647,16 +4050,30 @@ impl From<&CanisterLogRecord> for pb_canister_state_bits::CanisterLogRecord {
             idx: item.idx,
             timestamp_nanos: item.timestamp_nanos,
             content: item.content.clone(),
+            severity: item
+                .severity
+                .map(pb_canister_state_bits::CanisterLogSeverity::from)
+                .unwrap_or(pb_canister_state_bits::CanisterLogSeverity::Unspecified)
+                as i32,
+            fields: item.fields().iter().map(|f| f.into()).collect(),
         }
     }
 }
 
 impl From<pb_canister_state_bits::CanisterLogRecord> for CanisterLogRecord {
     fn from(item: pb_canister_state_bits::CanisterLogRecord) -> Self {
+        // Records without a (known) severity are unstructured.
+        let severity = pb_canister_state_bits::CanisterLogSeverity::try_from(item.severity)
+            .ok()
+            .and_then(|severity| CanisterLogSeverity::try_from(severity).ok());
+        // Fields are only kept for structured records.
+        let fields = severity.map(|_| item.fields.into_iter().map(|f| f.into()).collect());
         Self {
             idx: item.idx,
             timestamp_nanos: item.timestamp_nanos,
             content: item.content,
+            severity,
+            fields,
         }
     }
 }
@@ -4782,6 +5199,7 @@ mod tests {
         for curve in EcdsaCurve::iter() {
             match curve {
                 EcdsaCurve::Secp256k1 => assert_eq!(EcdsaCurve::try_from(0).unwrap(), curve),
+                EcdsaCurve::Secp256r1 => assert_eq!(EcdsaCurve::try_from(1).unwrap(), curve),
             }
         }
     }
@@ -4813,6 +5231,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn canister_log_severity_from_u32_exhaustive() {
+        // If this test fails, make sure this trait impl covers all variants:
+        // `impl TryFrom<u32> for CanisterLogSeverity`
+        for severity in CanisterLogSeverity::iter() {
+            let value = match severity {
+                CanisterLogSeverity::Debug => 1,
+                CanisterLogSeverity::Info => 2,
+                CanisterLogSeverity::Warning => 3,
+                CanisterLogSeverity::Error => 4,
+            };
+            assert_eq!(CanisterLogSeverity::try_from(value).unwrap(), severity);
+            assert_eq!(u32::from(severity), value);
+            assert_eq!(
+                pb_canister_state_bits::CanisterLogSeverity::from(severity) as u32,
+                value
+            );
+        }
+        for value in [0, 5, u32::MAX] {
+            assert!(CanisterLogSeverity::try_from(value).is_err());
+        }
+    }
+
+    #[test]
+    fn canister_log_severity_round_trip() {
+        for initial in CanisterLogSeverity::iter() {
+            let encoded = pb_canister_state_bits::CanisterLogSeverity::from(initial);
+            let round_trip = CanisterLogSeverity::try_from(encoded).unwrap();
+            assert_eq!(initial, round_trip);
+        }
+    }
+
     #[test]
     fn canister_install_mode_round_trip() {
         fn canister_install_mode_round_trip_aux(mode: CanisterInstallMode) {
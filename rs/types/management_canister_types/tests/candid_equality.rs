@@ -34,6 +34,7 @@ type ListCanisterSnapshotsArgs = CanisterIdRecord;
 type ListCanisterSnapshotsResult = Vec<CanisterSnapshotResponse>;
 type FetchCanisterLogsArgs = FetchCanisterLogsRequest;
 type FetchCanisterLogsResult = FetchCanisterLogsResponse;
+type BitcoinGetBlockFiltersResult = BitcoinGetBlockFiltersResponse;
 
 #[candid_method(update)]
 fn create_canister(_: CreateCanisterArgs) -> CreateCanisterResult {
@@ -150,6 +151,11 @@ fn node_metrics_history(_: NodeMetricsHistoryArgs) -> NodeMetricsHistoryResult {
     unreachable!()
 }
 
+#[candid_method(update)]
+fn bitcoin_get_block_filters(_: BitcoinGetBlockFiltersArgs) -> BitcoinGetBlockFiltersResult {
+    unreachable!()
+}
+
 #[candid_method(update)]
 fn provisional_create_canister_with_cycles(
     _: ProvisionalCreateCanisterWithCyclesArgs,
@@ -1,9 +1,10 @@
 use crate::{
     ReplicaVersion, Time,
     canister_http::{
-        CanisterHttpReject, CanisterHttpRequestId, CanisterHttpResponse,
-        CanisterHttpResponseArtifact, CanisterHttpResponseContent, CanisterHttpResponseDivergence,
-        CanisterHttpResponseMetadata, CanisterHttpResponseShare, CanisterHttpResponseWithConsensus,
+        CanisterHttpAggregatedResponses, CanisterHttpReject, CanisterHttpRequestId,
+        CanisterHttpResponse, CanisterHttpResponseArtifact, CanisterHttpResponseContent,
+        CanisterHttpResponseDivergence, CanisterHttpResponseMetadata, CanisterHttpResponseShare,
+        CanisterHttpResponseWithConsensus,
     },
     crypto::{BasicSig, BasicSigOf, CryptoHash, CryptoHashOf, Signed},
     messages::CallbackId,
@@ -29,17 +30,22 @@ pub struct CanisterHttpPayload {
     pub responses: Vec<CanisterHttpResponseWithConsensus>,
     pub timeouts: Vec<CallbackId>,
     pub divergence_responses: Vec<CanisterHttpResponseDivergence>,
+    /// Responses to requests whose individual node responses are aggregated by the canister.
+    pub aggregated_responses: Vec<CanisterHttpAggregatedResponses>,
 }
 
 impl CanisterHttpPayload {
     /// Returns the number of responses that this payload contains
     pub fn num_responses(&self) -> usize {
-        self.responses.len() + self.timeouts.len() + self.divergence_responses.len()
+        self.responses.len()
+            + self.timeouts.len()
+            + self.divergence_responses.len()
+            + self.aggregated_responses.len()
     }
 
     /// Returns the number of non_timeout responses
     pub fn num_non_timeout_responses(&self) -> usize {
-        self.responses.len()
+        self.responses.len() + self.aggregated_responses.len()
     }
 
     /// Returns true, if this is an empty payload
@@ -84,6 +90,15 @@ impl From<&CanisterHttpResponseDivergence> for pb::CanisterHttpResponseDivergenc
     }
 }
 
+impl From<&CanisterHttpAggregatedResponses> for pb::CanisterHttpAggregatedResponses {
+    fn from(payload: &CanisterHttpAggregatedResponses) -> Self {
+        pb::CanisterHttpAggregatedResponses {
+            id: payload.id.get(),
+            responses: payload.responses.iter().map(Into::into).collect(),
+        }
+    }
+}
+
 impl TryFrom<pb::CanisterHttpResponseWithConsensus> for CanisterHttpResponseWithConsensus {
     type Error = ProxyDecodeError;
 
@@ -151,6 +166,22 @@ impl TryFrom<pb::CanisterHttpResponseDivergence> for CanisterHttpResponseDiverge
     }
 }
 
+impl TryFrom<pb::CanisterHttpAggregatedResponses> for CanisterHttpAggregatedResponses {
+    type Error = ProxyDecodeError;
+
+    fn try_from(payload: pb::CanisterHttpAggregatedResponses) -> Result<Self, Self::Error> {
+        let responses = payload
+            .responses
+            .into_iter()
+            .map(TryFrom::try_from)
+            .collect::<Result<Vec<CanisterHttpResponseWithConsensus>, ProxyDecodeError>>()?;
+        Ok(CanisterHttpAggregatedResponses {
+            id: CanisterHttpRequestId::new(payload.id),
+            responses,
+        })
+    }
+}
+
 impl From<&CanisterHttpResponseContent> for pb::CanisterHttpResponseContent {
     fn from(content: &CanisterHttpResponseContent) -> Self {
         let inner = match content {
@@ -385,6 +416,57 @@ mod tests {
         assert_eq!(payload, new_payload);
     }
 
+    /// Tests, whether a roundtrip of protobuf conversions generates the same
+    /// `CanisterHttpAggregatedResponses`
+    #[test]
+    fn canister_http_aggregated_responses_conversion() {
+        let response =
+            |node: u64, content: CanisterHttpResponseContent| CanisterHttpResponseWithConsensus {
+                content: CanisterHttpResponse {
+                    id: CanisterHttpRequestId::new(1),
+                    timeout: Time::from_nanos_since_unix_epoch(1234),
+                    canister_id: crate::CanisterId::from(1),
+                    content,
+                },
+                proof: Signed {
+                    content: CanisterHttpResponseMetadata {
+                        id: CanisterHttpRequestId::new(1),
+                        timeout: Time::from_nanos_since_unix_epoch(1234),
+                        content_hash: CryptoHashOf::<CanisterHttpResponse>::new(CryptoHash(vec![
+                            node as u8;
+                            4
+                        ])),
+                        registry_version: RegistryVersion::new(1),
+                        replica_version: ReplicaVersion::default(),
+                    },
+                    signature: BasicSignatureBatch {
+                        signatures_map: vec![(
+                            NodeId::from(PrincipalId::new_node_test_id(node)),
+                            BasicSigOf::new(BasicSig(vec![0, 1, 2, 3])),
+                        )]
+                        .into_iter()
+                        .collect(),
+                    },
+                },
+            };
+        let payload = CanisterHttpAggregatedResponses {
+            id: CanisterHttpRequestId::new(1),
+            responses: vec![
+                response(1, CanisterHttpResponseContent::Success(vec![1, 2, 3])),
+                response(
+                    2,
+                    CanisterHttpResponseContent::Reject(CanisterHttpReject {
+                        reject_code: RejectCode::SysTransient,
+                        message: "test reject".to_string(),
+                    }),
+                ),
+            ],
+        };
+        let pb_payload = pb::CanisterHttpAggregatedResponses::from(&payload);
+        let new_payload = CanisterHttpAggregatedResponses::try_from(pb_payload).unwrap();
+        assert_eq!(payload, new_payload);
+    }
+
     /// Tests that a roundtrip of protobuf conversions for `CanisterHttpResponse`
     /// works correctly.
     #[test]
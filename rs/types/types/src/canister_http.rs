@@ -100,6 +100,13 @@ pub const MAX_CANISTER_HTTP_HEADER_NAME_VALUE_LENGTH: usize = 8 * 1024;
 /// Maximum total number of bytes to represent all HTTP header names and values.
 pub const MAX_CANISTER_HTTP_HEADER_TOTAL_SIZE: usize = 48 * 1024;
 
+/// Returns the maximum number of response bytes each of the `num_nodes` nodes may
+/// receive for a request whose responses are aggregated by the canister, such that
+/// all responses together do not exceed [`MAX_CANISTER_HTTP_RESPONSE_BYTES`].
+pub fn max_aggregated_response_bytes_per_node(num_nodes: usize) -> u64 {
+    MAX_CANISTER_HTTP_RESPONSE_BYTES / (num_nodes.max(1) as u64)
+}
+
 /// In the context of canister http, the [`CallbackId`] of the request
 /// is used to uniquely identify the request and it's associated artifacts.
 pub type CanisterHttpRequestId = CallbackId;
@@ -142,6 +149,10 @@ pub enum Replication {
     FullyReplicated,
     /// The request is not replicated, i.e. only the node with the given `NodeId` will attempt the http request.
     NonReplicated(NodeId),
+    /// All nodes attempt the http request, but instead of reaching consensus on a single response,
+    /// the individually signed responses of the nodes are delivered to the canister, which
+    /// aggregates them itself.
+    AggregatedByCanister,
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize, FromRepr)]
@@ -162,6 +173,9 @@ impl From<&CanisterHttpRequestContext> for pb_metadata::CanisterHttpRequestConte
                     node_id,
                 ))
             }
+            Replication::AggregatedByCanister => {
+                pb_metadata::replication::ReplicationType::AggregatedByCanister(())
+            }
         };
 
         let replication_message = pb_metadata::Replication {
@@ -229,6 +243,9 @@ impl TryFrom<pb_metadata::CanisterHttpRequestContext> for CanisterHttpRequestCon
                 Some(pb_metadata::replication::ReplicationType::NonReplicated(node_id)) => {
                     Replication::NonReplicated(node_id_try_from_protobuf(node_id)?)
                 }
+                Some(pb_metadata::replication::ReplicationType::AggregatedByCanister(_)) => {
+                    Replication::AggregatedByCanister
+                }
                 None => Replication::FullyReplicated,
             },
             None => Replication::FullyReplicated,
@@ -384,13 +401,29 @@ impl CanisterHttpRequestContext {
             ));
         };
 
+        let aggregated_by_canister = args.is_aggregated_by_canister();
+        if aggregated_by_canister && args.is_replicated == Some(false) {
+            return Err(CanisterHttpRequestContextError::ConflictingReplication);
+        }
+
+        // If the responses of all nodes are delivered to the canister, they have to fit
+        // into a single response together.
+        let max_allowed_response_bytes = if aggregated_by_canister {
+            if node_ids.is_empty() {
+                return Err(CanisterHttpRequestContextError::NoNodesAvailableForAggregation);
+            }
+            max_aggregated_response_bytes_per_node(node_ids.len())
+        } else {
+            MAX_CANISTER_HTTP_RESPONSE_BYTES
+        };
+
         let max_response_bytes = match args.max_response_bytes {
             Some(max_response_bytes) => {
-                if max_response_bytes > MAX_CANISTER_HTTP_RESPONSE_BYTES {
+                if max_response_bytes > max_allowed_response_bytes {
                     Err(CanisterHttpRequestContextError::MaxResponseBytes(
                         InvalidMaxResponseBytes {
                             min: 0,
-                            max: MAX_CANISTER_HTTP_RESPONSE_BYTES,
+                            max: max_allowed_response_bytes,
                             given: max_response_bytes,
                         },
                     ))
@@ -398,6 +431,7 @@ impl CanisterHttpRequestContext {
                     Ok(Some(NumBytes::from(max_response_bytes)))
                 }
             }
+            None if aggregated_by_canister => Ok(Some(NumBytes::from(max_allowed_response_bytes))),
             None => Ok(None),
         }?;
 
@@ -413,6 +447,7 @@ impl CanisterHttpRequestContext {
         )?;
 
         let replication = match args.is_replicated {
+            _ if aggregated_by_canister => Replication::AggregatedByCanister,
             Some(false) => {
                 if node_ids.is_empty() {
                     return Err(CanisterHttpRequestContextError::NoNodesAvailableForDelegation);
@@ -494,6 +529,8 @@ pub enum CanisterHttpRequestContextError {
     TooLargeHeaders(usize),
     TooLargeRequest(usize),
     NoNodesAvailableForDelegation,
+    NoNodesAvailableForAggregation,
+    ConflictingReplication,
 }
 
 impl From<CanisterHttpRequestContextError> for UserError {
@@ -552,6 +589,16 @@ impl From<CanisterHttpRequestContextError> for UserError {
                 "No nodes available for delegation for non-replicated canister HTTP request."
                     .to_string(),
             ),
+            CanisterHttpRequestContextError::NoNodesAvailableForAggregation => UserError::new(
+                ErrorCode::CanisterRejectedMessage,
+                "No nodes available for canister HTTP request aggregated by the canister."
+                    .to_string(),
+            ),
+            CanisterHttpRequestContextError::ConflictingReplication => UserError::new(
+                ErrorCode::CanisterRejectedMessage,
+                "aggregate_by_canister can not be combined with a non-replicated canister HTTP request."
+                    .to_string(),
+            ),
         }
     }
 }
@@ -710,6 +757,28 @@ impl CountBytes for CanisterHttpResponseWithConsensus {
     }
 }
 
+/// The individually signed responses of sufficiently many nodes for a request whose
+/// responses are aggregated by the canister.
+///
+/// Each proof is signed by exactly one node, and no two responses are signed by the same node.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
+#[cfg_attr(test, derive(ExhaustiveSet))]
+pub struct CanisterHttpAggregatedResponses {
+    pub id: CallbackId,
+    pub responses: Vec<CanisterHttpResponseWithConsensus>,
+}
+
+impl CountBytes for CanisterHttpAggregatedResponses {
+    fn count_bytes(&self) -> usize {
+        let CanisterHttpAggregatedResponses { id, responses } = &self;
+        size_of_val(id)
+            + responses
+                .iter()
+                .map(|response| response.count_bytes())
+                .sum::<usize>()
+    }
+}
+
 /// A collection of signature shares supporting the same [`CallbackId`] with different hashes.
 ///
 /// This can be used as a proof that consensus can not be reached for this call
@@ -795,6 +864,8 @@ mod tests {
 
     use super::*;
 
+    use assert_matches::assert_matches;
+    use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
     use strum::IntoEnumIterator;
 
     #[test]
@@ -881,6 +952,139 @@ mod tests {
         );
     }
 
+    fn aggregated_request_args(
+        max_response_bytes: Option<u64>,
+        is_replicated: Option<bool>,
+    ) -> CanisterHttpRequestArgs {
+        CanisterHttpRequestArgs {
+            url: "https://example.com".to_string(),
+            max_response_bytes,
+            headers: Default::default(),
+            body: None,
+            method: HttpMethod::GET,
+            transform: None,
+            is_replicated,
+            pricing_version: None,
+            aggregate_by_canister: Some(true),
+        }
+    }
+
+    fn test_request() -> Request {
+        Request {
+            receiver: CanisterId::ic_00(),
+            sender: CanisterId::ic_00(),
+            sender_reply_callback: CallbackId::from(3),
+            payment: Cycles::new(10),
+            method_name: "http_request".to_string(),
+            method_payload: Vec::new(),
+            metadata: Default::default(),
+            deadline: NO_DEADLINE,
+        }
+    }
+
+    fn test_node_ids(num_nodes: u64) -> BTreeSet<NodeId> {
+        (0..num_nodes)
+            .map(|i| NodeId::from(PrincipalId::new_node_test_id(i)))
+            .collect()
+    }
+
+    #[test]
+    fn aggregated_request_limits_response_bytes_per_node() {
+        let rng = &mut reproducible_rng();
+        let node_ids = test_node_ids(4);
+
+        let context = CanisterHttpRequestContext::generate_from_args(
+            UNIX_EPOCH,
+            &test_request(),
+            aggregated_request_args(None, None),
+            &node_ids,
+            rng,
+        )
+        .unwrap();
+        assert_eq!(context.replication, Replication::AggregatedByCanister);
+        assert_eq!(
+            context.max_response_bytes,
+            Some(NumBytes::from(MAX_CANISTER_HTTP_RESPONSE_BYTES / 4))
+        );
+
+        let context = CanisterHttpRequestContext::generate_from_args(
+            UNIX_EPOCH,
+            &test_request(),
+            aggregated_request_args(Some(1_000), Some(true)),
+            &node_ids,
+            rng,
+        )
+        .unwrap();
+        assert_eq!(context.replication, Replication::AggregatedByCanister);
+        assert_eq!(context.max_response_bytes, Some(NumBytes::from(1_000)));
+
+        assert_matches!(
+            CanisterHttpRequestContext::generate_from_args(
+                UNIX_EPOCH,
+                &test_request(),
+                aggregated_request_args(Some(MAX_CANISTER_HTTP_RESPONSE_BYTES / 4 + 1), None),
+                &node_ids,
+                rng,
+            ),
+            Err(CanisterHttpRequestContextError::MaxResponseBytes(err))
+                if err.max == MAX_CANISTER_HTTP_RESPONSE_BYTES / 4
+        );
+    }
+
+    #[test]
+    fn aggregated_request_cannot_be_non_replicated() {
+        assert_matches!(
+            CanisterHttpRequestContext::generate_from_args(
+                UNIX_EPOCH,
+                &test_request(),
+                aggregated_request_args(None, Some(false)),
+                &test_node_ids(4),
+                &mut reproducible_rng(),
+            ),
+            Err(CanisterHttpRequestContextError::ConflictingReplication)
+        );
+    }
+
+    #[test]
+    fn aggregated_request_requires_nodes() {
+        assert_matches!(
+            CanisterHttpRequestContext::generate_from_args(
+                UNIX_EPOCH,
+                &test_request(),
+                aggregated_request_args(None, None),
+                &BTreeSet::new(),
+                &mut reproducible_rng(),
+            ),
+            Err(CanisterHttpRequestContextError::NoNodesAvailableForAggregation)
+        );
+    }
+
+    #[test]
+    fn replication_proto_round_trip() {
+        for replication in [
+            Replication::FullyReplicated,
+            Replication::NonReplicated(NodeId::from(PrincipalId::new_node_test_id(1))),
+            Replication::AggregatedByCanister,
+        ] {
+            let context = CanisterHttpRequestContext {
+                url: "https://example.com".to_string(),
+                headers: vec![],
+                body: None,
+                max_response_bytes: None,
+                http_method: CanisterHttpMethod::GET,
+                transform: None,
+                request: test_request(),
+                time: UNIX_EPOCH,
+                replication,
+                pricing_version: PricingVersion::Legacy,
+            };
+            let encoded = pb_metadata::CanisterHttpRequestContext::from(&context);
+            let round_trip = CanisterHttpRequestContext::try_from(encoded).unwrap();
+
+            assert_eq!(context, round_trip);
+        }
+    }
+
     #[test]
     fn canister_http_method_proto_round_trip() {
         for initial in CanisterHttpMethod::iter() {
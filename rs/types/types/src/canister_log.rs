@@ -1,5 +1,7 @@
 use candid::Deserialize;
-use ic_management_canister_types_private::{CanisterLogRecord, DataSize};
+use ic_management_canister_types_private::{
+    CanisterLogField, CanisterLogRecord, CanisterLogSeverity, DataSize,
+};
 use ic_validate_eq::ValidateEq;
 use ic_validate_eq_derive::ValidateEq;
 use serde::Serialize;
@@ -33,11 +35,29 @@ const _: () = assert!(DEFAULT_AGGREGATE_LOG_MEMORY_LIMIT <= MAX_AGGREGATE_LOG_ME
 const _: () = assert!(MIN_AGGREGATE_LOG_MEMORY_LIMIT <= MAX_DELTA_LOG_MEMORY_LIMIT);
 const _: () = assert!(MAX_DELTA_LOG_MEMORY_LIMIT <= MAX_AGGREGATE_LOG_MEMORY_LIMIT);
 
-const _: () = assert!(std::mem::size_of::<CanisterLogRecord>() <= MIN_AGGREGATE_LOG_MEMORY_LIMIT);
+const _: () = assert!(
+    CanisterLogRecord::BASE_SIZE + CanisterLogRecord::STRUCTURED_BASE_SIZE
+        <= MIN_AGGREGATE_LOG_MEMORY_LIMIT
+);
 
 /// Truncates the content of a log record so that the record fits within the allowed size.
+/// The fields of a structured record are dropped (starting from the last one) if even
+/// the fields alone do not fit.
 fn truncate_content(byte_capacity: usize, mut record: CanisterLogRecord) -> CanisterLogRecord {
-    let max_content_size = byte_capacity.saturating_sub(std::mem::size_of::<CanisterLogRecord>());
+    let mut max_content_size = byte_capacity.saturating_sub(CanisterLogRecord::BASE_SIZE);
+    if record.severity.is_some() {
+        max_content_size = max_content_size.saturating_sub(CanisterLogRecord::STRUCTURED_BASE_SIZE);
+    }
+    if let Some(fields) = record.fields.as_mut() {
+        let mut fields_size: usize = fields.iter().map(|f| f.data_size()).sum();
+        while fields_size > max_content_size {
+            let field = fields
+                .pop()
+                .expect("Fields must not be empty if their size is positive");
+            fields_size -= field.data_size();
+        }
+        max_content_size -= fields_size;
+    }
     record.content.truncate(max_content_size);
     record
 }
@@ -225,6 +245,29 @@ impl CanisterLog {
                 idx: self.next_idx,
                 timestamp_nanos,
                 content,
+                severity: None,
+                fields: None,
+            },
+        ));
+        self.next_idx += 1;
+    }
+
+    /// Adds a new structured log record with the given severity and fields.
+    pub fn add_structured_record(
+        &mut self,
+        timestamp_nanos: u64,
+        severity: CanisterLogSeverity,
+        content: Vec<u8>,
+        fields: Vec<CanisterLogField>,
+    ) {
+        self.records.push_back(truncate_content(
+            self.byte_capacity(),
+            CanisterLogRecord {
+                idx: self.next_idx,
+                timestamp_nanos,
+                content,
+                severity: Some(severity),
+                fields: Some(fields),
             },
         ));
         self.next_idx += 1;
@@ -271,6 +314,8 @@ mod tests {
                 idx,
                 timestamp_nanos,
                 content: content.to_vec(),
+                severity: None,
+                fields: None,
             })
             .collect()
     }
@@ -315,6 +360,27 @@ mod tests {
         assert_eq!(log.byte_capacity(), TEST_MAX_ALLOWED_SIZE);
     }
 
+    #[test]
+    fn test_canister_log_add_structured_record_applies_memory_limit() {
+        let mut log = CanisterLog::default_aggregate();
+        let big_value = String::from_utf8(BIGGER_THAN_LIMIT_MESSAGE.to_vec()).unwrap();
+        log.add_structured_record(
+            100,
+            CanisterLogSeverity::Error,
+            b"message".to_vec(),
+            vec![
+                CanisterLogField::new("key", "value"),
+                CanisterLogField::new("big", big_value),
+            ],
+        );
+        // The field that does not fit is dropped, the rest of the record is kept.
+        let record = log.records().back().unwrap();
+        assert_eq!(record.severity, Some(CanisterLogSeverity::Error));
+        assert_eq!(record.fields(), &[CanisterLogField::new("key", "value")]);
+        assert_eq!(record.content, b"message".to_vec());
+        assert!(log.bytes_used() <= TEST_MAX_ALLOWED_SIZE);
+    }
+
     #[test]
     fn test_canister_log_clear() {
         // Arrange.
@@ -39,8 +39,8 @@ use crate::xnet::CertifiedStreamSlice;
 use crate::{CryptoHashOfState, ReplicaVersion};
 use ic_base_types::{CanisterId, NodeId, PrincipalId, RegistryVersion, SubnetId};
 use ic_btc_replica_types::{
-    BitcoinAdapterResponse, BitcoinAdapterResponseWrapper, BitcoinReject,
-    GetSuccessorsResponseComplete, SendTransactionResponse,
+    BitcoinAdapterResponse, BitcoinAdapterResponseWrapper, BitcoinReject, BlockFilter,
+    GetBlockFiltersResponse, GetSuccessorsResponseComplete, SendTransactionResponse,
 };
 use ic_crypto_internal_types::NodeIndex;
 use ic_error_types::RejectCode;
@@ -526,22 +526,35 @@ impl ExhaustiveSet for BitcoinAdapterResponseWrapper {
         let transactions = std::iter::once(BitcoinAdapterResponseWrapper::SendTransactionResponse(
             SendTransactionResponse {},
         ));
+        let filters = std::iter::once(BitcoinAdapterResponseWrapper::GetBlockFiltersResponse(
+            GetBlockFiltersResponse {
+                filters: vec![BlockFilter {
+                    block_hash: vec![1; 32],
+                    filter: vec![2; 8],
+                    matches: true,
+                }],
+            },
+        ));
         let rejects = BitcoinReject::exhaustive_set(rng)
             .into_iter()
             .enumerate()
-            .map(|(i, reject)| {
-                if i % 2 == 0 {
-                    BitcoinAdapterResponseWrapper::GetSuccessorsReject(reject)
-                } else {
-                    BitcoinAdapterResponseWrapper::SendTransactionReject(reject)
-                }
+            .map(|(i, reject)| match i % 3 {
+                0 => BitcoinAdapterResponseWrapper::GetSuccessorsReject(reject),
+                1 => BitcoinAdapterResponseWrapper::SendTransactionReject(reject),
+                _ => BitcoinAdapterResponseWrapper::GetBlockFiltersReject(reject),
             });
-        let result: Vec<_> = successors.chain(transactions).chain(rejects).collect();
+        let result: Vec<_> = successors
+            .chain(transactions)
+            .chain(filters)
+            .chain(rejects)
+            .collect();
         match &result[0] {
             BitcoinAdapterResponseWrapper::GetSuccessorsResponse(_) => (),
             BitcoinAdapterResponseWrapper::SendTransactionResponse(_) => (),
             BitcoinAdapterResponseWrapper::GetSuccessorsReject(_) => (),
             BitcoinAdapterResponseWrapper::SendTransactionReject(_) => (),
+            BitcoinAdapterResponseWrapper::GetBlockFiltersResponse(_) => (),
+            BitcoinAdapterResponseWrapper::GetBlockFiltersReject(_) => (),
             // Any new variants should be inserted to `result` above!
         }
         result
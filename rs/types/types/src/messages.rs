@@ -11,7 +11,7 @@ mod webauthn;
 pub use self::http::{
     Authentication, Certificate, CertificateDelegation, CertificateDelegationFormat,
     CertificateDelegationMetadata, Delegation, HasCanisterId, HttpCallContent, HttpCanisterUpdate,
-    HttpQueryContent, HttpQueryResponse, HttpQueryResponseReply, HttpReadState,
+    HttpLogStreamItem, HttpQueryContent, HttpQueryResponse, HttpQueryResponseReply, HttpReadState,
     HttpReadStateContent, HttpReadStateResponse, HttpReply, HttpRequest, HttpRequestContent,
     HttpRequestEnvelope, HttpRequestError, HttpSignedQueryResponse, HttpStatusResponse,
     HttpUserQuery, NodeSignature, QueryResponseHash, RawHttpRequestVal, ReplicaHealthStatus,
@@ -691,6 +691,28 @@ pub struct NodeSignature {
     pub identity: NodeId,
 }
 
+/// An item of the response to `/api/v3/canister/_/log_stream`,
+/// which is a CBOR sequence of such items.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpLogStreamItem {
+    /// The signed response to the `fetch_canister_logs` query with the given
+    /// argument (fetching the log records starting at the next index to stream).
+    /// The query response hash is computed over the query of the request
+    /// with its argument replaced by `arg`. The response may also contain
+    /// records with an index below the start of the index range in `arg`
+    /// (which have already been streamed), so clients must skip them.
+    Records {
+        arg: Blob,
+        response: HttpSignedQueryResponse,
+    },
+    /// The log records with index in `[from_idx, to_idx)` were dropped from
+    /// the canister log buffer before they could be streamed. This item is not
+    /// signed: it is always followed by a `Records` item whose `arg` starts at
+    /// `from_idx` and whose first new record has index `to_idx`.
+    Gap { from_idx: u64, to_idx: u64 },
+}
+
 /// The body of the `QueryResponse`
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
 pub struct HttpQueryResponseReply {
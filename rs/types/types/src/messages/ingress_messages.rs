@@ -597,6 +597,7 @@ pub fn extract_effective_canister_id(
         | Ok(Method::BitcoinSendTransaction)
         | Ok(Method::BitcoinSendTransactionInternal)
         | Ok(Method::BitcoinGetSuccessors)
+        | Ok(Method::BitcoinGetBlockFilters)
         | Ok(Method::BitcoinGetCurrentFeePercentiles)
         | Ok(Method::NodeMetricsHistory)
         | Ok(Method::SubnetInfo)
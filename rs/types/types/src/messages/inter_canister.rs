@@ -272,6 +272,7 @@ impl Request {
             | Ok(Method::BitcoinSendTransaction)
             | Ok(Method::BitcoinSendTransactionInternal)
             | Ok(Method::BitcoinGetSuccessors)
+            | Ok(Method::BitcoinGetBlockFilters)
             | Ok(Method::BitcoinGetCurrentFeePercentiles)
             | Ok(Method::NodeMetricsHistory)
             | Ok(Method::SubnetInfo) => {
@@ -45,6 +45,32 @@ impl BinaryEncodedWasm {
     }
 }
 
+/// The Cranelift optimization level of a compiled module.
+///
+/// Both tiers compile the same instrumented Wasm binary, so they execute the
+/// same number of instructions and differ only in the speed of the generated
+/// machine code.
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Debug,
+    Default,
+    serde::Deserialize,
+    DeterministicHeapBytes,
+    serde::Serialize,
+)]
+pub enum CompilationTier {
+    /// Fast compilation without optimizations, used for all new modules.
+    #[default]
+    Baseline,
+    /// Optimized compilation, used for modules that execute many instructions.
+    Optimized,
+}
+
 /// Represents the current loading state of the canister module storage.
 #[derive(Debug, PartialEq, Eq)]
 pub enum ModuleLoadingStatus {